@@ -0,0 +1,105 @@
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Time source for session timestamps (turns, lifecycle records).
+///
+/// Timestamps are rendered as seconds since the Unix epoch with millisecond
+/// precision, e.g. `1700000000.250`. Deadlines are measured with a monotonic
+/// `Instant` instead, since wall-clock time can jump.
+pub trait Clock: Send + Sync {
+    /// Time elapsed since the Unix epoch.
+    fn now(&self) -> Duration;
+
+    fn timestamp(&self) -> String {
+        format_timestamp(self.now())
+    }
+}
+
+/// Wall-clock time; the session default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+    }
+}
+
+/// Always reports the same instant.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedClock {
+    now: Duration,
+}
+
+impl FixedClock {
+    pub fn new(now: Duration) -> Self {
+        Self { now }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> Duration {
+        self.now
+    }
+}
+
+/// Starts at a fixed instant and advances by `step` on every read, so
+/// consecutive timestamps are reproducible and strictly increasing.
+#[derive(Debug)]
+pub struct MonotonicTestClock {
+    next: Mutex<Duration>,
+    step: Duration,
+}
+
+impl MonotonicTestClock {
+    pub fn new(start: Duration, step: Duration) -> Self {
+        Self {
+            next: Mutex::new(start),
+            step,
+        }
+    }
+}
+
+impl Clock for MonotonicTestClock {
+    fn now(&self) -> Duration {
+        let mut next = self.next.lock().expect("test clock mutex");
+        let now = *next;
+        *next += self.step;
+        now
+    }
+}
+
+pub fn format_timestamp(since_epoch: Duration) -> String {
+    format!(
+        "{}.{:03}",
+        since_epoch.as_secs(),
+        since_epoch.subsec_millis()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_timestamp_uses_millisecond_precision() {
+        assert_eq!(
+            format_timestamp(Duration::from_millis(1_700_000_000_005)),
+            "1700000000.005"
+        );
+        assert_eq!(format_timestamp(Duration::ZERO), "0.000");
+    }
+
+    #[test]
+    fn monotonic_test_clock_advances_by_step() {
+        let clock = MonotonicTestClock::new(Duration::from_secs(10), Duration::from_millis(1));
+        assert_eq!(clock.timestamp(), "10.000");
+        assert_eq!(clock.timestamp(), "10.001");
+        assert_eq!(
+            FixedClock::new(Duration::from_secs(5)).timestamp(),
+            FixedClock::new(Duration::from_secs(5)).timestamp()
+        );
+    }
+}
@@ -1,52 +1,260 @@
 use forge_cxdb_runtime::CxdbFsSnapshotPolicy;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum CxdbPersistenceMode {
     Off,
     Required,
+    /// Like `Required`, but appends are written by a background task so the
+    /// submit loop does not wait on CXDB. Failures surface from
+    /// `Session::flush_pending_persistence`, e.g. after `close`, and later
+    /// appends are rejected until `Session::reset_buffered_persistence`.
+    Buffered,
+}
+
+/// Line-ending handling for the file-editing tools (`write_file`, `edit_file`,
+/// `apply_patch`). Matching always runs on LF-normalized content unless the
+/// mode is `Preserve`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineEndingMode {
+    /// Write back using the convention detected in the existing file.
+    Auto,
+    Lf,
+    Crlf,
+    /// Leave content untouched.
+    #[default]
+    Preserve,
+}
+
+/// When the session snapshots its working directory, given a
+/// `fs_snapshot_policy`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FsSnapshotCadence {
+    Off,
+    /// Capture with every persisted record, attached atomically on append.
+    #[default]
+    EveryRecord,
+    /// Capture once at close and attach it to the final head turn.
+    OnSessionEnd,
+    /// Capture after every N completed tool rounds and attach it to the
+    /// head turn. `0` never captures.
+    EveryNRounds(usize),
+}
+
+/// What `submit` does when the model returns neither text nor tool calls.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmptyResponsePolicy {
+    /// Record the empty assistant turn and finish the submit.
+    #[default]
+    TreatAsComplete,
+    /// Re-issue the request up to N times, then fail like `Error`.
+    Retry(usize),
+    /// Fail the submit with `SessionError::EmptyResponse`.
+    Error,
+}
+
+/// What `build_request` does when the provider profile cannot take native
+/// tool definitions (`ProviderCapabilities::supports_tools` is false).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolsUnsupportedPolicy {
+    /// Omit `tools`/`tool_choice` and list the tools in the system prompt.
+    #[default]
+    DescribeInPrompt,
+    /// Fail the request with `SessionError::ToolsUnsupported`.
+    Error,
+}
+
+/// What the `<environment>` section of the system prompt includes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnvironmentContextFields {
+    /// Repository root, git-repository flag, branch, status summary and
+    /// recent commits. When off, git is not queried at all.
+    pub git_details: bool,
+    pub os_version: bool,
+    /// Extra `key: value` lines appended to the section, in key order.
+    pub custom: BTreeMap<String, String>,
+}
+
+impl Default for EnvironmentContextFields {
+    fn default() -> Self {
+        Self {
+            git_details: true,
+            os_version: true,
+            custom: BTreeMap::new(),
+        }
+    }
+}
+
+/// How subagent submits are scheduled relative to the parent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubagentExecution {
+    /// Submits run as background tasks; `wait` joins them.
+    #[default]
+    Concurrent,
+    /// `spawn_agent` and `send_input` run the submit to completion before
+    /// returning, so subagents finish in spawn order and `wait` returns
+    /// immediately. Meant for reproducible tests.
+    Sequential,
 }
 
 /// Runtime configuration for a coding-agent session.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SessionConfig {
     pub max_turns: usize,
+    /// Tool rounds allowed per submit; `0` falls back to the provider
+    /// profile's `suggested_max_tool_rounds_per_input()`.
     pub max_tool_rounds_per_input: usize,
+    /// Wall-clock budget for one submit, checked before each model call; `0`
+    /// means no deadline.
+    pub submit_deadline_ms: u64,
+    /// Cap on `total_tokens` summed over every model call in the session;
+    /// `0` means no cap. Checked before each call against the usage so far
+    /// plus a projection of that call (the previous call's size), so a call
+    /// that grows past its projection can still end slightly over the cap.
+    pub max_session_tokens: u64,
+    /// Tokens kept free for the response when a request is checked against
+    /// the profile's context window before sending. Larger requests fail with
+    /// `ContextWindowExceeded`; `None` skips the check.
+    pub context_output_reserve_tokens: Option<usize>,
     pub default_command_timeout_ms: u64,
     pub max_command_timeout_ms: u64,
+    /// Combined stdout/stderr bytes a command may produce before it is
+    /// terminated and its result marked `output_truncated`; `0` means no cap.
+    /// The dispatcher passes it with every `shell` call.
+    pub max_command_output_bytes: usize,
     pub reasoning_effort: Option<String>,
+    /// When false, only the most recent assistant turn replays its reasoning.
+    pub include_historical_reasoning: bool,
     pub system_prompt_override: Option<String>,
     pub tool_output_limits: HashMap<String, usize>,
     pub tool_line_limits: HashMap<String, usize>,
+    /// Upper bound on the backoff before retrying a rate-limited tool call.
+    pub tool_rate_limit_max_backoff_ms: u64,
+    pub normalize_line_endings: LineEndingMode,
+    /// Stash prior file content before file-mutating tools run and expose the
+    /// `undo_edit` tool.
+    pub keep_edit_backups: bool,
+    pub edit_backup_max_entries: usize,
+    pub edit_backup_max_bytes: usize,
+    /// Append a unified diff of the change to `write_file`/`edit_file` results.
+    pub include_edit_diffs: bool,
+    /// Minimum share (0.0–1.0) of tokens that must match exactly when
+    /// `edit_file` or `apply_patch` falls back to whitespace-tolerant matching;
+    /// weaker matches are refused. `0.0` accepts every fuzzy match.
+    pub fuzzy_match_min_confidence: f64,
     pub enable_loop_detection: bool,
     pub loop_detection_window: usize,
     pub max_subagent_depth: usize,
+    pub enable_subagents: bool,
+    /// Wall-clock limit for each subagent submit unless `spawn_agent` passes
+    /// `max_runtime_ms`; `0` means no limit.
+    pub subagent_max_runtime_ms: u64,
+    /// Whether subagent submits run in the background (default) or to
+    /// completion inside `spawn_agent`/`send_input`.
+    pub subagent_execution: SubagentExecution,
+    /// Byte cap on a subagent's `output` as `wait` returns it to the parent;
+    /// `0` (the default) disables the cap. Persistence keeps the full text.
+    pub subagent_output_max_bytes: usize,
+    /// Ask the model to condense oversize subagent output instead of
+    /// truncating it. The call goes through the request interceptor and counts
+    /// toward `max_session_tokens`; it is skipped when its estimated size
+    /// would cross the budget and falls back to truncation if it fails.
+    pub summarize_subagent_output: bool,
+    /// When a submit ends in `AwaitingInput` and a follow-up is queued, treat
+    /// the follow-up as the answer and keep going instead of returning.
+    pub followups_answer_questions: bool,
     pub tool_hook_strict: bool,
     pub thread_key: Option<String>,
+    /// Fixed session id; takes precedence over `id_seed`.
+    pub session_id: Option<String>,
+    /// Seed for a deterministic (v5) session id when `session_id` is unset.
+    pub id_seed: Option<String>,
     pub cxdb_persistence: CxdbPersistenceMode,
+    /// Persist turns using `CompactTurn` instead of the verbose derive form.
+    pub compact_persistence: bool,
     pub fs_snapshot_policy: Option<CxdbFsSnapshotPolicy>,
+    pub fs_snapshot_cadence: FsSnapshotCadence,
+    /// Extra instruction file names (e.g. `.cursorrules`) discovered after the
+    /// provider profile's own list; duplicates are ignored.
+    pub extra_instruction_files: Vec<String>,
+    /// Which lines the default `<environment>` section of the system prompt
+    /// includes; profiles that override `render_environment_context` may
+    /// ignore it.
+    pub environment_context_fields: EnvironmentContextFields,
+    /// Retries after a transient CXDB `create_context`/`append_turn` failure
+    /// (transport error or 5xx-class response) before it is reported; `0`
+    /// disables retrying. Other errors are reported at once.
+    pub persistence_retry_attempts: u32,
+    /// Delay before the first persistence retry; doubles on each further one.
+    pub persistence_retry_backoff_ms: u64,
+    /// Retries after a retryable model-call error (rate limit, server error)
+    /// before the submit fails; `0` disables retrying.
+    pub llm_retry_attempts: u32,
+    /// Delay before the first model-call retry; doubles on each further one.
+    /// A provider `retry_after` hint takes precedence.
+    pub llm_retry_backoff_ms: u64,
+    /// What a submit does when the model returns neither text nor tool
+    /// calls; defaults to finishing the submit as if the model were done.
+    pub on_empty_response: EmptyResponsePolicy,
+    pub on_tools_unsupported: ToolsUnsupportedPolicy,
 }
 
 impl Default for SessionConfig {
     fn default() -> Self {
         Self {
             max_turns: 0,
-            max_tool_rounds_per_input: 200,
+            max_tool_rounds_per_input: crate::DEFAULT_MAX_TOOL_ROUNDS_PER_INPUT,
+            submit_deadline_ms: 0,
+            max_session_tokens: 0,
+            context_output_reserve_tokens: None,
             default_command_timeout_ms: 10_000,
             max_command_timeout_ms: 600_000,
+            max_command_output_bytes: crate::DEFAULT_MAX_COMMAND_OUTPUT_BYTES,
             reasoning_effort: None,
+            include_historical_reasoning: true,
             system_prompt_override: None,
             tool_output_limits: default_tool_output_limits(),
             tool_line_limits: default_tool_line_limits(),
+            tool_rate_limit_max_backoff_ms: 5_000,
+            normalize_line_endings: LineEndingMode::Preserve,
+            keep_edit_backups: false,
+            edit_backup_max_entries: 20,
+            edit_backup_max_bytes: 4 * 1024 * 1024,
+            include_edit_diffs: false,
+            fuzzy_match_min_confidence: 0.0,
             enable_loop_detection: true,
             loop_detection_window: 10,
             max_subagent_depth: 1,
+            enable_subagents: true,
+            subagent_max_runtime_ms: 0,
+            subagent_execution: SubagentExecution::Concurrent,
+            subagent_output_max_bytes: 0,
+            summarize_subagent_output: false,
+            followups_answer_questions: false,
             tool_hook_strict: false,
             thread_key: None,
+            session_id: None,
+            id_seed: None,
             cxdb_persistence: CxdbPersistenceMode::Off,
+            compact_persistence: false,
             fs_snapshot_policy: None,
+            fs_snapshot_cadence: FsSnapshotCadence::EveryRecord,
+            extra_instruction_files: Vec::new(),
+            environment_context_fields: EnvironmentContextFields::default(),
+            persistence_retry_attempts: 2,
+            persistence_retry_backoff_ms: 100,
+            llm_retry_attempts: 0,
+            llm_retry_backoff_ms: 1_000,
+            on_empty_response: EmptyResponsePolicy::TreatAsComplete,
+            on_tools_unsupported: ToolsUnsupportedPolicy::DescribeInPrompt,
         }
     }
 }
@@ -84,14 +292,45 @@ mod tests {
         let config = SessionConfig::default();
         assert_eq!(config.max_turns, 0);
         assert_eq!(config.max_tool_rounds_per_input, 200);
+        assert_eq!(config.submit_deadline_ms, 0);
+        assert_eq!(config.max_session_tokens, 0);
         assert_eq!(config.default_command_timeout_ms, 10_000);
         assert_eq!(config.max_command_timeout_ms, 600_000);
+        assert_eq!(config.max_command_output_bytes, 16 * 1024 * 1024);
+        assert_eq!(config.fuzzy_match_min_confidence, 0.0);
+        assert!(config.include_historical_reasoning);
         assert_eq!(config.system_prompt_override, None);
+        assert_eq!(config.tool_rate_limit_max_backoff_ms, 5_000);
+        assert_eq!(config.llm_retry_attempts, 0);
+        assert_eq!(config.llm_retry_backoff_ms, 1_000);
         assert_eq!(config.loop_detection_window, 10);
         assert_eq!(config.max_subagent_depth, 1);
+        assert!(config.enable_subagents);
+        assert_eq!(config.subagent_max_runtime_ms, 0);
+        assert_eq!(config.subagent_execution, SubagentExecution::Concurrent);
+        assert_eq!(
+            config.on_empty_response,
+            EmptyResponsePolicy::TreatAsComplete
+        );
+        assert_eq!(
+            config.on_tools_unsupported,
+            ToolsUnsupportedPolicy::DescribeInPrompt
+        );
+        assert_eq!(config.subagent_output_max_bytes, 0);
+        assert!(!config.summarize_subagent_output);
+        assert!(!config.followups_answer_questions);
         assert!(!config.tool_hook_strict);
         assert_eq!(config.thread_key, None);
+        assert_eq!(config.session_id, None);
+        assert_eq!(config.id_seed, None);
         assert_eq!(config.cxdb_persistence, CxdbPersistenceMode::Off);
+        assert!(!config.compact_persistence);
         assert_eq!(config.fs_snapshot_policy, None);
+        assert_eq!(config.fs_snapshot_cadence, FsSnapshotCadence::EveryRecord);
+        assert_eq!(
+            config.environment_context_fields,
+            EnvironmentContextFields::default()
+        );
+        assert!(config.environment_context_fields.git_details);
     }
 }
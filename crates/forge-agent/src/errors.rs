@@ -1,3 +1,6 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
 use thiserror::Error;
 
 /// Session-level failures in orchestration and lifecycle management.
@@ -7,6 +10,8 @@ pub enum SessionError {
     InvalidConfiguration(String),
     #[error("session is closed")]
     Closed,
+    #[error("session is paused")]
+    Paused,
     #[error("invalid session state transition: {from} -> {to}")]
     InvalidStateTransition { from: String, to: String },
     #[error("event payload serialization failed: {0}")]
@@ -15,6 +20,22 @@ pub enum SessionError {
     CheckpointUnsupported(String),
     #[error("cxdb persistence failed: {0}")]
     Persistence(String),
+    #[error("invalid seeded history: {0}")]
+    InvalidHistory(String),
+    /// The model returned neither text nor tool calls on every attempt.
+    #[error("model returned an empty response after {attempts} attempt(s)")]
+    EmptyResponse { attempts: usize },
+    /// The provider rejected the request as too large for its context window.
+    /// A `limit` of `0` means the window size is unknown.
+    #[error("context window exceeded: {tokens} tokens > {limit} limit")]
+    ContextWindowExceeded { tokens: usize, limit: usize },
+    /// Attachments were submitted to a profile without `supports_vision`.
+    #[error("provider profile '{0}' does not accept image attachments")]
+    AttachmentsUnsupported(String),
+    /// The profile cannot take tool definitions and
+    /// `ToolsUnsupportedPolicy::Error` is configured.
+    #[error("provider profile '{0}' does not support tool calls")]
+    ToolsUnsupported(String),
 }
 
 /// Tool-level failures in lookup, validation, and execution.
@@ -26,6 +47,10 @@ pub enum ToolError {
     Validation(String),
     #[error("tool execution failed: {0}")]
     Execution(String),
+    /// The tool hit an external rate limit; dispatch retries once after
+    /// `retry_after_ms`, capped by `SessionConfig::tool_rate_limit_max_backoff_ms`.
+    #[error("tool rate limited; retry after {retry_after_ms}ms")]
+    RateLimited { retry_after_ms: u64 },
 }
 
 /// Top-level error type for the forge-agent crate.
@@ -37,6 +62,8 @@ pub enum AgentError {
     Tool(#[from] ToolError),
     #[error("execution environment error: {0}")]
     ExecutionEnvironment(String),
+    #[error("execution environment initialization failed: {0}")]
+    Execution(#[source] Box<AgentError>),
     #[error("not implemented yet: {0}")]
     NotImplemented(String),
     #[error(transparent)]
@@ -47,4 +74,139 @@ impl AgentError {
     pub fn session_closed() -> Self {
         Self::Session(SessionError::Closed)
     }
+
+    pub fn session_paused() -> Self {
+        Self::Session(SessionError::Paused)
+    }
+}
+
+/// Category of a failure the run recovered from instead of aborting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveredErrorKind {
+    LlmRetry,
+    Persistence,
+    Tool,
+}
+
+/// One recovered failure, in the order it was recorded.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveredError {
+    pub kind: RecoveredErrorKind,
+    /// What failed: a tool name, a persistence operation, a provider, ...
+    pub source: String,
+    pub message: String,
+    pub timestamp: String,
+}
+
+/// Counts and time span of everything recorded in a [`RunDiagnostics`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunDiagnosticsSummary {
+    pub total: usize,
+    pub counts: BTreeMap<RecoveredErrorKind, usize>,
+    pub first_timestamp: Option<String>,
+    pub last_timestamp: Option<String>,
+}
+
+/// Collects recoverable failures across a run so they can be inspected
+/// afterwards without scraping events. Shared via `Arc`; recording only needs
+/// `&self`.
+#[derive(Debug, Default)]
+pub struct RunDiagnostics {
+    entries: Mutex<Vec<RecoveredError>>,
+}
+
+impl RunDiagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(
+        &self,
+        kind: RecoveredErrorKind,
+        source: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.record_at(kind, source, message, crate::events::current_timestamp());
+    }
+
+    /// Like [`record`](Self::record), stamped with `timestamp` instead of the
+    /// system clock; sessions pass their own clock's timestamp.
+    pub fn record_at(
+        &self,
+        kind: RecoveredErrorKind,
+        source: impl Into<String>,
+        message: impl Into<String>,
+        timestamp: impl Into<String>,
+    ) {
+        self.entries
+            .lock()
+            .expect("run diagnostics mutex")
+            .push(RecoveredError {
+                kind,
+                source: source.into(),
+                message: message.into(),
+                timestamp: timestamp.into(),
+            });
+    }
+
+    pub fn entries(&self) -> Vec<RecoveredError> {
+        self.entries.lock().expect("run diagnostics mutex").clone()
+    }
+
+    pub fn summary(&self) -> RunDiagnosticsSummary {
+        let entries = self.entries.lock().expect("run diagnostics mutex");
+        let mut counts = BTreeMap::new();
+        for entry in entries.iter() {
+            *counts.entry(entry.kind).or_insert(0) += 1;
+        }
+        RunDiagnosticsSummary {
+            total: entries.len(),
+            counts,
+            first_timestamp: entries.first().map(|entry| entry.timestamp.clone()),
+            last_timestamp: entries.last().map(|entry| entry.timestamp.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_diagnostics_summary_counts_and_orders_recovered_errors() {
+        let diagnostics = RunDiagnostics::new();
+        diagnostics.record(RecoveredErrorKind::LlmRetry, "openai", "rate limited");
+        diagnostics.record(RecoveredErrorKind::Tool, "shell", "exit code 1");
+        diagnostics.record(RecoveredErrorKind::Persistence, "append_turn", "timeout");
+        diagnostics.record(RecoveredErrorKind::Tool, "read_file", "missing file");
+
+        let sources: Vec<String> = diagnostics
+            .entries()
+            .into_iter()
+            .map(|entry| entry.source)
+            .collect();
+        assert_eq!(sources, vec!["openai", "shell", "append_turn", "read_file"]);
+
+        let summary = diagnostics.summary();
+        assert_eq!(summary.total, 4);
+        assert_eq!(
+            summary.counts,
+            BTreeMap::from([
+                (RecoveredErrorKind::LlmRetry, 1),
+                (RecoveredErrorKind::Persistence, 1),
+                (RecoveredErrorKind::Tool, 2),
+            ])
+        );
+        assert!(summary.first_timestamp.is_some());
+        assert!(summary.first_timestamp <= summary.last_timestamp);
+    }
+
+    #[test]
+    fn run_diagnostics_summary_is_empty_without_records() {
+        assert_eq!(
+            RunDiagnostics::new().summary(),
+            RunDiagnosticsSummary::default()
+        );
+    }
 }
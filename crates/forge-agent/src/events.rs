@@ -1,8 +1,9 @@
-use crate::{AgentError, SessionError};
+use crate::{AgentError, Clock, SessionError, SystemClock};
 use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 
 pub type EventStream = UnboundedReceiver<SessionEvent>;
@@ -83,6 +84,8 @@ pub enum EventKind {
     ToolCallEnd,
     SteeringInjected,
     TurnLimit,
+    DeadlineExceeded,
+    TokenBudgetExceeded,
     LoopDetection,
     Warning,
     Error,
@@ -94,6 +97,10 @@ pub struct SessionEvent {
     pub timestamp: String,
     pub session_id: String,
     pub data: EventData,
+    /// Per-session emission order starting at 1; `0` until the session's
+    /// emitter stamps it.
+    #[serde(default)]
+    pub sequence_no: u64,
 }
 
 impl SessionEvent {
@@ -103,6 +110,7 @@ impl SessionEvent {
             timestamp: current_timestamp(),
             session_id: session_id.into(),
             data,
+            sequence_no: 0,
         }
     }
 
@@ -117,6 +125,7 @@ impl SessionEvent {
             timestamp: timestamp.into(),
             session_id: session_id.into(),
             data,
+            sequence_no: 0,
         }
     }
 
@@ -244,6 +253,24 @@ impl SessionEvent {
         Self::new(EventKind::TurnLimit, session_id, data)
     }
 
+    pub fn deadline_exceeded(
+        session_id: impl Into<String>,
+        elapsed_ms: u64,
+        deadline_ms: u64,
+    ) -> Self {
+        let mut data = EventData::new();
+        data.insert_u64("elapsed_ms", elapsed_ms);
+        data.insert_u64("deadline_ms", deadline_ms);
+        Self::new(EventKind::DeadlineExceeded, session_id, data)
+    }
+
+    pub fn token_budget_exceeded(session_id: impl Into<String>, used: u64, budget: u64) -> Self {
+        let mut data = EventData::new();
+        data.insert_u64("used_tokens", used);
+        data.insert_u64("max_session_tokens", budget);
+        Self::new(EventKind::TokenBudgetExceeded, session_id, data)
+    }
+
     pub fn loop_detection(session_id: impl Into<String>, message: impl Into<String>) -> Self {
         let mut data = EventData::new();
         data.insert_string("message", message);
@@ -349,13 +376,46 @@ impl EventEmitter for BufferedEventEmitter {
     }
 }
 
-fn current_timestamp() -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
+/// Stamps unstamped events with a monotonic `sequence_no` and a timestamp
+/// from the session clock before forwarding them. Each session wraps its
+/// emitter in one of these; events a child session already stamped pass
+/// through unchanged.
+pub(crate) struct SequencedEventEmitter {
+    inner: Arc<dyn EventEmitter>,
+    last_sequence_no: AtomicU64,
+    clock: Mutex<Arc<dyn Clock>>,
+}
+
+impl SequencedEventEmitter {
+    pub(crate) fn new(inner: Arc<dyn EventEmitter>, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            inner,
+            last_sequence_no: AtomicU64::new(0),
+            clock: Mutex::new(clock),
+        }
+    }
+
+    pub(crate) fn set_clock(&self, clock: Arc<dyn Clock>) {
+        *self.clock.lock().expect("event clock mutex") = clock;
+    }
+}
+
+impl EventEmitter for SequencedEventEmitter {
+    fn emit(&self, mut event: SessionEvent) -> Result<(), AgentError> {
+        if event.sequence_no == 0 {
+            event.sequence_no = self.last_sequence_no.fetch_add(1, Ordering::SeqCst) + 1;
+            event.timestamp = self.clock.lock().expect("event clock mutex").timestamp();
+        }
+        self.inner.emit(event)
+    }
+
+    fn subscribe(&self) -> EventStream {
+        self.inner.subscribe()
+    }
+}
 
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default();
-    now.as_secs().to_string()
+pub(crate) fn current_timestamp() -> String {
+    SystemClock.timestamp()
 }
 
 #[cfg(test)]
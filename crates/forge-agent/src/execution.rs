@@ -1,4 +1,4 @@
-use crate::AgentError;
+use crate::{AgentError, SessionConfig};
 use async_trait::async_trait;
 use regex::RegexBuilder;
 use serde::{Deserialize, Serialize};
@@ -6,17 +6,34 @@ use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Instant;
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt};
 use tokio::process::{Child, Command};
+use tokio::sync::Notify;
 use tokio::time::{Duration, sleep};
 
+pub const DEFAULT_MAX_COMMAND_OUTPUT_BYTES: usize = 16 * 1024 * 1024;
+
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GrepOptions {
     pub glob_filter: Option<String>,
     pub case_insensitive: bool,
+    /// Only match the pattern at word boundaries.
+    #[serde(default)]
+    pub whole_word: bool,
+    /// Treat the pattern as a literal string rather than a regex.
+    #[serde(default)]
+    pub fixed_string: bool,
     pub max_results: Option<usize>,
+    /// Stop scanning once this many matches are collected and note the
+    /// truncation in the output.
+    #[serde(default)]
+    pub max_matches: Option<usize>,
+    /// Like `max_matches`, counting files with at least one match.
+    #[serde(default)]
+    pub max_files: Option<usize>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -26,6 +43,14 @@ pub struct ExecResult {
     pub exit_code: i32,
     pub timed_out: bool,
     pub duration_ms: u128,
+    /// Set when stdout and stderr together exceeded the environment's output
+    /// cap and the command was terminated.
+    #[serde(default)]
+    pub output_truncated: bool,
+    /// Timeout the command ran under after the environment applied its
+    /// default and cap; `0` when the environment does not report it.
+    #[serde(default)]
+    pub timeout_ms: u64,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -35,6 +60,37 @@ pub struct DirEntry {
     pub size: Option<u64>,
 }
 
+/// Kind of filesystem entry, as reported without following symlinks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryType {
+    File,
+    Dir,
+    Symlink,
+}
+
+impl EntryType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntryType::File => "file",
+            EntryType::Dir => "dir",
+            EntryType::Symlink => "symlink",
+        }
+    }
+
+    fn of(file_type: std::fs::FileType) -> Option<Self> {
+        if file_type.is_symlink() {
+            Some(EntryType::Symlink)
+        } else if file_type.is_dir() {
+            Some(EntryType::Dir)
+        } else if file_type.is_file() {
+            Some(EntryType::File)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum EnvVarPolicy {
@@ -53,10 +109,75 @@ pub trait ExecutionEnvironment: Send + Sync {
         limit: Option<usize>,
     ) -> Result<String, AgentError>;
 
+    /// Reads a text file decoded from `encoding` (a WHATWG label such as
+    /// `utf-16le` or `latin1`). Environments without raw byte access only
+    /// support UTF-8.
+    async fn read_file_with_encoding(
+        &self,
+        path: &str,
+        offset: Option<usize>,
+        limit: Option<usize>,
+        encoding: &str,
+    ) -> Result<String, AgentError> {
+        if is_utf8_label(encoding) {
+            return self.read_file(path, offset, limit).await;
+        }
+        Err(AgentError::NotImplemented(format!(
+            "read_file encoding '{}'",
+            encoding
+        )))
+    }
+
+    /// Reads a file's raw bytes. Environments without raw byte access only
+    /// support UTF-8 files.
+    async fn read_file_bytes(&self, path: &str) -> Result<Vec<u8>, AgentError> {
+        self.read_file(path, None, None)
+            .await
+            .map(String::into_bytes)
+    }
+
     async fn write_file(&self, path: &str, content: &str) -> Result<(), AgentError>;
+
+    /// Writes raw bytes. Environments without raw byte access only support
+    /// UTF-8 content.
+    async fn write_file_bytes(&self, path: &str, content: &[u8]) -> Result<(), AgentError> {
+        match std::str::from_utf8(content) {
+            Ok(text) => self.write_file(path, text).await,
+            Err(_) => Err(AgentError::NotImplemented(format!(
+                "write_file_bytes '{}' with non-UTF-8 content",
+                path
+            ))),
+        }
+    }
+
     async fn delete_file(&self, path: &str) -> Result<(), AgentError>;
     async fn move_file(&self, from: &str, to: &str) -> Result<(), AgentError>;
     async fn file_exists(&self, path: &str) -> Result<bool, AgentError>;
+
+    /// Environments that cannot tell report `false`, so callers treat the
+    /// path as a file.
+    async fn is_directory(&self, _path: &str) -> Result<bool, AgentError> {
+        Ok(false)
+    }
+
+    /// Removes a directory. Without `recursive` a non-empty directory is an
+    /// error.
+    async fn delete_directory(&self, path: &str, _recursive: bool) -> Result<(), AgentError> {
+        Err(AgentError::NotImplemented(format!(
+            "delete_directory '{}'",
+            path
+        )))
+    }
+
+    /// Creates a directory; `recursive` also creates missing parents.
+    /// Succeeds if the directory already exists and fails if a file is there.
+    async fn create_directory(&self, path: &str, _recursive: bool) -> Result<(), AgentError> {
+        Err(AgentError::NotImplemented(format!(
+            "create_directory '{}'",
+            path
+        )))
+    }
+
     async fn list_directory(&self, path: &str, depth: usize) -> Result<Vec<DirEntry>, AgentError>;
 
     async fn exec_command(
@@ -67,6 +188,22 @@ pub trait ExecutionEnvironment: Send + Sync {
         env_vars: Option<HashMap<String, String>>,
     ) -> Result<ExecResult, AgentError>;
 
+    /// Like `exec_command`, terminating the command once its combined output
+    /// passes `max_output_bytes` (`0` means no cap). Environments without an
+    /// output cap ignore the limit.
+    async fn exec_command_with_output_limit(
+        &self,
+        command: &str,
+        timeout_ms: u64,
+        working_dir: Option<&str>,
+        env_vars: Option<HashMap<String, String>>,
+        max_output_bytes: usize,
+    ) -> Result<ExecResult, AgentError> {
+        let _ = max_output_bytes;
+        self.exec_command(command, timeout_ms, working_dir, env_vars)
+            .await
+    }
+
     async fn grep(
         &self,
         pattern: &str,
@@ -76,6 +213,29 @@ pub trait ExecutionEnvironment: Send + Sync {
 
     async fn glob(&self, pattern: &str, path: &str) -> Result<Vec<String>, AgentError>;
 
+    /// Like `glob`, keeping only entries of `entry_type`. Environments without
+    /// type information only support `None`.
+    async fn glob_with_type(
+        &self,
+        pattern: &str,
+        path: &str,
+        entry_type: Option<EntryType>,
+    ) -> Result<Vec<String>, AgentError> {
+        match entry_type {
+            None => self.glob(pattern, path).await,
+            Some(entry_type) => Err(AgentError::NotImplemented(format!(
+                "glob type '{}'",
+                entry_type.as_str()
+            ))),
+        }
+    }
+
+    /// Scratch directory for this session, created on first use and removed
+    /// by `cleanup`.
+    async fn temp_dir(&self) -> Result<PathBuf, AgentError> {
+        Err(AgentError::NotImplemented("temp_dir".to_string()))
+    }
+
     async fn initialize(&self) -> Result<(), AgentError> {
         Ok(())
     }
@@ -101,7 +261,9 @@ pub struct LocalExecutionEnvironment {
     env_policy: EnvVarPolicy,
     default_command_timeout_ms: u64,
     max_command_timeout_ms: u64,
+    max_command_output_bytes: usize,
     running_processes: Arc<Mutex<HashSet<u32>>>,
+    temp_dir: Arc<Mutex<Option<PathBuf>>>,
 }
 
 impl LocalExecutionEnvironment {
@@ -113,7 +275,9 @@ impl LocalExecutionEnvironment {
             env_policy: env_policy_from_env().unwrap_or_default(),
             default_command_timeout_ms: 10_000,
             max_command_timeout_ms: 600_000,
+            max_command_output_bytes: DEFAULT_MAX_COMMAND_OUTPUT_BYTES,
             running_processes: Arc::new(Mutex::new(HashSet::new())),
+            temp_dir: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -132,6 +296,22 @@ impl LocalExecutionEnvironment {
         self
     }
 
+    /// Caps the combined stdout and stderr bytes buffered per command; `0`
+    /// means no cap.
+    pub fn with_max_command_output_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_command_output_bytes = max_bytes;
+        self
+    }
+
+    /// Applies the command timeout and output limits from `config`.
+    pub fn with_session_limits(self, config: &SessionConfig) -> Self {
+        self.with_command_timeout_limits(
+            config.default_command_timeout_ms,
+            config.max_command_timeout_ms,
+        )
+        .with_max_command_output_bytes(config.max_command_output_bytes)
+    }
+
     fn resolve_path(&self, path: &str) -> PathBuf {
         let path = Path::new(path);
         if path.is_absolute() {
@@ -243,22 +423,61 @@ impl ExecutionEnvironment for LocalExecutionEnvironment {
             ))
         })?;
 
-        if offset.is_none() && limit.is_none() {
-            return Ok(content);
-        }
+        Ok(select_line_window(content, offset, limit))
+    }
 
-        let start = offset.unwrap_or(1).saturating_sub(1);
-        let max_lines = limit.unwrap_or(usize::MAX);
-        let lines: Vec<&str> = content.lines().collect();
-        if start >= lines.len() {
-            return Ok(String::new());
+    async fn read_file_with_encoding(
+        &self,
+        path: &str,
+        offset: Option<usize>,
+        limit: Option<usize>,
+        encoding: &str,
+    ) -> Result<String, AgentError> {
+        if is_utf8_label(encoding) {
+            return self.read_file(path, offset, limit).await;
+        }
+        let Some(decoder) = encoding_rs::Encoding::for_label(encoding.trim().as_bytes()) else {
+            return Err(AgentError::ExecutionEnvironment(format!(
+                "unknown encoding '{}'",
+                encoding
+            )));
+        };
+        let path = self.resolve_path(path);
+        let raw = tokio::fs::read(&path).await.map_err(|error| {
+            AgentError::ExecutionEnvironment(format!(
+                "failed to read '{}': {}",
+                path.display(),
+                error
+            ))
+        })?;
+        let (content, had_errors) = decoder.decode_with_bom_removal(&raw);
+        if had_errors {
+            return Err(AgentError::ExecutionEnvironment(format!(
+                "'{}' is not valid {} text",
+                path.display(),
+                decoder.name()
+            )));
         }
 
-        let end = start.saturating_add(max_lines).min(lines.len());
-        Ok(lines[start..end].join("\n"))
+        Ok(select_line_window(content.into_owned(), offset, limit))
+    }
+
+    async fn read_file_bytes(&self, path: &str) -> Result<Vec<u8>, AgentError> {
+        let path = self.resolve_path(path);
+        tokio::fs::read(&path).await.map_err(|error| {
+            AgentError::ExecutionEnvironment(format!(
+                "failed to read '{}': {}",
+                path.display(),
+                error
+            ))
+        })
     }
 
     async fn write_file(&self, path: &str, content: &str) -> Result<(), AgentError> {
+        self.write_file_bytes(path, content.as_bytes()).await
+    }
+
+    async fn write_file_bytes(&self, path: &str, content: &[u8]) -> Result<(), AgentError> {
         let path = self.resolve_path(path);
         if let Some(parent) = path.parent() {
             tokio::fs::create_dir_all(parent).await.map_err(|error| {
@@ -319,6 +538,54 @@ impl ExecutionEnvironment for LocalExecutionEnvironment {
         Ok(tokio::fs::metadata(path).await.is_ok())
     }
 
+    async fn is_directory(&self, path: &str) -> Result<bool, AgentError> {
+        let path = self.resolve_path(path);
+        Ok(tokio::fs::metadata(path)
+            .await
+            .is_ok_and(|metadata| metadata.is_dir()))
+    }
+
+    async fn delete_directory(&self, path: &str, recursive: bool) -> Result<(), AgentError> {
+        let path = self.resolve_path(path);
+        let result = if recursive {
+            tokio::fs::remove_dir_all(&path).await
+        } else {
+            tokio::fs::remove_dir(&path).await
+        };
+        result.map_err(|error| {
+            AgentError::ExecutionEnvironment(format!(
+                "failed to delete directory '{}': {}",
+                path.display(),
+                error
+            ))
+        })
+    }
+
+    async fn create_directory(&self, path: &str, recursive: bool) -> Result<(), AgentError> {
+        let path = self.resolve_path(path);
+        if let Ok(metadata) = tokio::fs::metadata(&path).await {
+            if metadata.is_dir() {
+                return Ok(());
+            }
+            return Err(AgentError::ExecutionEnvironment(format!(
+                "cannot create directory '{}': a file already exists at that path",
+                path.display()
+            )));
+        }
+        let result = if recursive {
+            tokio::fs::create_dir_all(&path).await
+        } else {
+            tokio::fs::create_dir(&path).await
+        };
+        result.map_err(|error| {
+            AgentError::ExecutionEnvironment(format!(
+                "failed to create directory '{}': {}",
+                path.display(),
+                error
+            ))
+        })
+    }
+
     async fn list_directory(&self, path: &str, depth: usize) -> Result<Vec<DirEntry>, AgentError> {
         let root = self.resolve_path(path);
         let max_depth = depth.saturating_add(1);
@@ -366,6 +633,24 @@ impl ExecutionEnvironment for LocalExecutionEnvironment {
         timeout_ms: u64,
         working_dir: Option<&str>,
         env_vars: Option<HashMap<String, String>>,
+    ) -> Result<ExecResult, AgentError> {
+        self.exec_command_with_output_limit(
+            command,
+            timeout_ms,
+            working_dir,
+            env_vars,
+            self.max_command_output_bytes,
+        )
+        .await
+    }
+
+    async fn exec_command_with_output_limit(
+        &self,
+        command: &str,
+        timeout_ms: u64,
+        working_dir: Option<&str>,
+        env_vars: Option<HashMap<String, String>>,
+        max_output_bytes: usize,
     ) -> Result<ExecResult, AgentError> {
         let started = Instant::now();
         let timeout_ms = self.effective_timeout_ms(timeout_ms);
@@ -403,29 +688,38 @@ impl ExecutionEnvironment for LocalExecutionEnvironment {
             pid: child_pid,
         };
 
-        let stdout_task = tokio::spawn(read_pipe(child.stdout.take()));
-        let stderr_task = tokio::spawn(read_pipe(child.stderr.take()));
+        let output_cap = (max_output_bytes > 0).then(|| OutputCap::new(max_output_bytes));
+        let stdout_task = tokio::spawn(read_pipe(child.stdout.take(), output_cap.clone()));
+        let stderr_task = tokio::spawn(read_pipe(child.stderr.take(), output_cap.clone()));
 
         let mut timed_out = false;
-        let status =
-            match tokio::time::timeout(Duration::from_millis(timeout_ms), child.wait()).await {
-                Ok(wait_result) => wait_result.map_err(|error| {
+        let status = tokio::select! {
+            wait_result = child.wait() => wait_result.map_err(|error| {
+                AgentError::ExecutionEnvironment(format!(
+                    "failed to wait for command '{}': {}",
+                    command, error
+                ))
+            })?,
+            _ = sleep(Duration::from_millis(timeout_ms)) => {
+                timed_out = true;
+                terminate_command(&mut child).await?;
+                child.wait().await.map_err(|error| {
                     AgentError::ExecutionEnvironment(format!(
-                        "failed to wait for command '{}': {}",
+                        "failed to collect timed-out command '{}': {}",
                         command, error
                     ))
-                })?,
-                Err(_) => {
-                    timed_out = true;
-                    terminate_command(&mut child).await?;
-                    child.wait().await.map_err(|error| {
-                        AgentError::ExecutionEnvironment(format!(
-                            "failed to collect timed-out command '{}': {}",
-                            command, error
-                        ))
-                    })?
-                }
-            };
+                })?
+            }
+            _ = wait_for_output_cap(output_cap.as_deref()) => {
+                terminate_command(&mut child).await?;
+                child.wait().await.map_err(|error| {
+                    AgentError::ExecutionEnvironment(format!(
+                        "failed to collect over-limit command '{}': {}",
+                        command, error
+                    ))
+                })?
+            }
+        };
 
         let mut stdout = String::from_utf8_lossy(&stdout_task.await.map_err(|error| {
             AgentError::ExecutionEnvironment(format!(
@@ -442,17 +736,25 @@ impl ExecutionEnvironment for LocalExecutionEnvironment {
         })?)
         .to_string();
 
-        if timed_out {
+        let output_truncated = output_cap.as_ref().is_some_and(|cap| cap.exceeded());
+        if timed_out || output_truncated {
             if !stdout.is_empty() && !stdout.ends_with('\n') {
                 stdout.push('\n');
             }
             if !stderr.is_empty() && !stderr.ends_with('\n') {
                 stderr.push('\n');
             }
+        }
+        if timed_out {
             stderr.push_str(&format!(
                 "[ERROR: Command timed out after {}ms. Partial output is shown above.\nYou can retry with a longer timeout by setting the timeout_ms parameter.]",
                 timeout_ms
             ));
+        } else if output_truncated {
+            stderr.push_str(&format!(
+                "[ERROR: Command output exceeded {} bytes and the command was terminated. Partial output is shown above.\nRedirect large output to a file and inspect it with read_file or grep instead.]",
+                max_output_bytes
+            ));
         }
 
         let result = ExecResult {
@@ -461,6 +763,8 @@ impl ExecutionEnvironment for LocalExecutionEnvironment {
             exit_code: status.code().unwrap_or(if timed_out { 124 } else { -1 }),
             timed_out,
             duration_ms: started.elapsed().as_millis(),
+            output_truncated,
+            timeout_ms,
         };
 
         Ok(result)
@@ -486,6 +790,15 @@ impl ExecutionEnvironment for LocalExecutionEnvironment {
     }
 
     async fn glob(&self, pattern: &str, path: &str) -> Result<Vec<String>, AgentError> {
+        self.glob_with_type(pattern, path, None).await
+    }
+
+    async fn glob_with_type(
+        &self,
+        pattern: &str,
+        path: &str,
+        entry_type: Option<EntryType>,
+    ) -> Result<Vec<String>, AgentError> {
         let root = self.resolve_path(path);
         let pattern_path = if Path::new(pattern).is_absolute() {
             PathBuf::from(pattern)
@@ -507,7 +820,13 @@ impl ExecutionEnvironment for LocalExecutionEnvironment {
                     pattern_string, error
                 ))
             })?;
-            matches.push(entry);
+            let keep = entry_type.is_none_or(|wanted| {
+                std::fs::symlink_metadata(&entry)
+                    .is_ok_and(|meta| EntryType::of(meta.file_type()) == Some(wanted))
+            });
+            if keep {
+                matches.push(entry);
+            }
         }
 
         let mut by_mtime: Vec<(PathBuf, std::time::SystemTime)> = matches
@@ -539,6 +858,55 @@ impl ExecutionEnvironment for LocalExecutionEnvironment {
         &self.os_version
     }
 
+    async fn temp_dir(&self) -> Result<PathBuf, AgentError> {
+        let mut temp_dir = match self.temp_dir.lock() {
+            Ok(temp_dir) => temp_dir,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if let Some(path) = temp_dir.as_ref() {
+            return Ok(path.clone());
+        }
+        // A fresh, randomly named directory with owner-only permissions, so
+        // nothing pre-existing in a shared temp root can be adopted.
+        let mut builder = tempfile::Builder::new();
+        builder.prefix("forge-agent-");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            builder.permissions(std::fs::Permissions::from_mode(0o700));
+        }
+        let path = builder
+            .tempdir()
+            .map_err(|error| {
+                AgentError::ExecutionEnvironment(format!(
+                    "failed to create temp directory: {}",
+                    error
+                ))
+            })?
+            .keep();
+        *temp_dir = Some(path.clone());
+        Ok(path)
+    }
+
+    async fn cleanup(&self) -> Result<(), AgentError> {
+        let temp_dir = match self.temp_dir.lock() {
+            Ok(mut temp_dir) => temp_dir.take(),
+            Err(poisoned) => poisoned.into_inner().take(),
+        };
+        let Some(path) = temp_dir else {
+            return Ok(());
+        };
+        match tokio::fs::remove_dir_all(&path).await {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(AgentError::ExecutionEnvironment(format!(
+                "failed to remove temp directory '{}': {}",
+                path.display(),
+                error
+            ))),
+        }
+    }
+
     async fn terminate_all_commands(&self) -> Result<(), AgentError> {
         let pids = self.running_process_ids();
         if pids.is_empty() {
@@ -576,6 +944,29 @@ impl ExecutionEnvironment for LocalExecutionEnvironment {
     }
 }
 
+pub(crate) fn is_utf8_label(encoding: &str) -> bool {
+    matches!(
+        encoding.trim().to_ascii_lowercase().as_str(),
+        "utf-8" | "utf8"
+    )
+}
+
+fn select_line_window(content: String, offset: Option<usize>, limit: Option<usize>) -> String {
+    if offset.is_none() && limit.is_none() {
+        return content;
+    }
+
+    let start = offset.unwrap_or(1).saturating_sub(1);
+    let max_lines = limit.unwrap_or(usize::MAX);
+    let lines: Vec<&str> = content.lines().collect();
+    if start >= lines.len() {
+        return String::new();
+    }
+
+    let end = start.saturating_add(max_lines).min(lines.len());
+    lines[start..end].join("\n")
+}
+
 fn detect_binary_mime_type(path: &Path, bytes: &[u8]) -> &'static str {
     if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A])
         || path.extension() == Some(OsStr::new("png"))
@@ -634,18 +1025,77 @@ fn build_shell_command(command: &str) -> Command {
     }
 }
 
-async fn read_pipe<R>(pipe: Option<R>) -> Vec<u8>
+/// Byte budget shared by a command's stdout and stderr readers.
+struct OutputCap {
+    remaining: Mutex<usize>,
+    exceeded: AtomicBool,
+    notify: Notify,
+}
+
+impl OutputCap {
+    fn new(max_bytes: usize) -> Arc<Self> {
+        Arc::new(Self {
+            remaining: Mutex::new(max_bytes),
+            exceeded: AtomicBool::new(false),
+            notify: Notify::new(),
+        })
+    }
+
+    /// Reserves up to `len` bytes and returns how many fit. Running out marks
+    /// the cap exceeded and wakes the waiter.
+    fn take(&self, len: usize) -> usize {
+        let granted = match self.remaining.lock() {
+            Ok(mut remaining) => {
+                let granted = len.min(*remaining);
+                *remaining -= granted;
+                granted
+            }
+            Err(_) => 0,
+        };
+        if granted < len {
+            self.exceeded.store(true, Ordering::SeqCst);
+            self.notify.notify_one();
+        }
+        granted
+    }
+
+    fn exceeded(&self) -> bool {
+        self.exceeded.load(Ordering::SeqCst)
+    }
+}
+
+async fn wait_for_output_cap(cap: Option<&OutputCap>) {
+    match cap {
+        Some(cap) => cap.notify.notified().await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn read_pipe<R>(pipe: Option<R>, cap: Option<Arc<OutputCap>>) -> Vec<u8>
 where
     R: tokio::io::AsyncRead + Unpin + Send + 'static,
 {
-    match pipe {
-        Some(mut reader) => {
-            let mut bytes = Vec::new();
-            let _ = reader.read_to_end(&mut bytes).await;
-            bytes
+    let Some(mut reader) = pipe else {
+        return Vec::new();
+    };
+    let mut bytes = Vec::new();
+    let Some(cap) = cap else {
+        let _ = reader.read_to_end(&mut bytes).await;
+        return bytes;
+    };
+    let mut chunk = [0_u8; 8192];
+    loop {
+        let read = match reader.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        let granted = cap.take(read);
+        bytes.extend_from_slice(&chunk[..granted]);
+        if granted < read {
+            break;
         }
-        None => Vec::new(),
     }
+    bytes
 }
 
 #[cfg(unix)]
@@ -687,44 +1137,154 @@ async fn run_ripgrep(
     let mut cmd = Command::new("rg");
     cmd.arg("--line-number")
         .arg("--no-heading")
+        .arg("--null")
         .arg("--color")
         .arg("never");
     if options.case_insensitive {
         cmd.arg("--ignore-case");
     }
+    if options.whole_word {
+        cmd.arg("--word-regexp");
+    }
+    if options.fixed_string {
+        cmd.arg("--fixed-strings");
+    }
     if let Some(glob_filter) = &options.glob_filter {
         cmd.arg("--glob").arg(glob_filter);
     }
     if let Some(max) = options.max_results {
         cmd.arg("--max-count").arg(max.to_string());
     }
-    cmd.arg(pattern).arg(path);
+    cmd.arg("--regexp").arg(pattern).arg(path);
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
 
-    let output = cmd.output().await.map_err(|error| {
+    let mut child = cmd.spawn().map_err(|error| {
         AgentError::ExecutionEnvironment(format!("failed to execute ripgrep: {}", error))
     })?;
+    let stderr_task = tokio::spawn(read_pipe(child.stderr.take(), None));
+    let mut results = GrepResults::new(options);
+    if let Some(stdout) = child.stdout.take() {
+        let mut reader = tokio::io::BufReader::new(stdout);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line).await.map_err(|error| {
+                AgentError::ExecutionEnvironment(format!(
+                    "failed to read ripgrep output: {}",
+                    error
+                ))
+            })?;
+            if read == 0 {
+                break;
+            }
+            // `--null` ends the path with NUL; single-file searches omit it.
+            let text = String::from_utf8_lossy(&line);
+            let text = text.trim_end_matches(['\n', '\r']);
+            let accepted = match text.split_once('\0') {
+                Some((file, rest)) => results.push(file, format!("{}:{}", file, rest)),
+                None => results.push("", text.to_string()),
+            };
+            if !accepted {
+                let _ = child.start_kill();
+                break;
+            }
+        }
+    }
 
-    let exit = output.status.code().unwrap_or(-1);
-    if output.status.success() {
-        return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+    let status = child.wait().await.map_err(|error| {
+        AgentError::ExecutionEnvironment(format!("failed to wait for ripgrep: {}", error))
+    })?;
+    if results.truncated() || status.success() {
+        return Ok(results.finish());
     }
+    let exit = status.code().unwrap_or(-1);
     if exit == 1 {
         return Ok(String::new());
     }
 
+    let stderr = stderr_task.await.unwrap_or_default();
     Err(AgentError::ExecutionEnvironment(format!(
         "ripgrep failed with exit code {}: {}",
         exit,
-        String::from_utf8_lossy(&output.stderr)
+        String::from_utf8_lossy(&stderr)
     )))
 }
 
+/// Grep result lines, capped by `GrepOptions::max_matches` and `max_files`.
+struct GrepResults {
+    lines: Vec<String>,
+    files: HashSet<String>,
+    max_matches: Option<usize>,
+    max_files: Option<usize>,
+    truncation: Option<String>,
+}
+
+impl GrepResults {
+    fn new(options: &GrepOptions) -> Self {
+        Self {
+            lines: Vec::new(),
+            files: HashSet::new(),
+            max_matches: options.max_matches,
+            max_files: options.max_files,
+            truncation: None,
+        }
+    }
+
+    /// Adds a match from `file`. Returns `false`, dropping the match, when it
+    /// would exceed a cap; the caller should stop scanning.
+    fn push(&mut self, file: &str, line: String) -> bool {
+        if let Some(max) = self.max_matches.filter(|max| self.lines.len() >= *max) {
+            self.truncation = Some(format!("[results truncated at {} matches]", max));
+            return false;
+        }
+        let new_file = !self.files.contains(file);
+        if let Some(max) = self
+            .max_files
+            .filter(|max| new_file && self.files.len() >= *max)
+        {
+            self.truncation = Some(format!("[results truncated at {} files]", max));
+            return false;
+        }
+        if new_file {
+            self.files.insert(file.to_string());
+        }
+        self.lines.push(line);
+        true
+    }
+
+    fn truncated(&self) -> bool {
+        self.truncation.is_some()
+    }
+
+    fn finish(self) -> String {
+        let mut output = self.lines.join("\n");
+        if let Some(note) = self.truncation {
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            output.push_str(&note);
+        }
+        output
+    }
+}
+
 async fn grep_fallback(
     pattern: &str,
     path: &Path,
     options: &GrepOptions,
 ) -> Result<String, AgentError> {
-    let regex = RegexBuilder::new(pattern)
+    let mut expression = if options.fixed_string {
+        regex::escape(pattern)
+    } else {
+        pattern.to_string()
+    };
+    if options.whole_word {
+        expression = format!(r"\b(?:{})\b", expression);
+    }
+    let regex = RegexBuilder::new(&expression)
         .case_insensitive(options.case_insensitive)
         .build()
         .map_err(|error| {
@@ -743,7 +1303,7 @@ async fn grep_fallback(
             ))
         })?;
 
-    let mut matches = Vec::new();
+    let mut results = GrepResults::new(options);
     let max_results = options.max_results.unwrap_or(100);
     let files = enumerate_files(path)?;
 
@@ -759,17 +1319,18 @@ async fn grep_fallback(
             Err(_) => continue,
         };
 
+        let file_name = file.display().to_string();
         for (idx, line) in content.lines().enumerate() {
             if regex.is_match(line) {
-                matches.push(format!("{}:{}:{}", file.display(), idx + 1, line));
-                if matches.len() >= max_results {
-                    return Ok(matches.join("\n"));
+                let entry = format!("{}:{}:{}", file_name, idx + 1, line);
+                if !results.push(&file_name, entry) || results.lines.len() >= max_results {
+                    return Ok(results.finish());
                 }
             }
         }
     }
 
-    Ok(matches.join("\n"))
+    Ok(results.finish())
 }
 
 fn enumerate_files(path: &Path) -> Result<Vec<PathBuf>, AgentError> {
@@ -937,6 +1498,7 @@ mod tests {
                     glob_filter: Some("*.rs".to_string()),
                     case_insensitive: false,
                     max_results: Some(10),
+                    ..GrepOptions::default()
                 },
             )
             .await
@@ -948,6 +1510,109 @@ mod tests {
         assert!(globbed.iter().any(|path| path.ends_with("src/main.rs")));
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn grep_fallback_honors_case_word_and_literal_options() {
+        let dir = tempdir().expect("temp dir should be created");
+        std::fs::write(
+            dir.path().join("notes.txt"),
+            "Error: disk full\nterrorism report\ncost is $1.50 (approx)\ncost is $1x50\n",
+        )
+        .expect("seed file");
+        let grep = |pattern: &'static str, options: GrepOptions| {
+            let path = dir.path().to_path_buf();
+            async move {
+                grep_fallback(pattern, &path, &options)
+                    .await
+                    .expect("grep should succeed")
+            }
+        };
+
+        assert_eq!(
+            grep("error", GrepOptions::default()).await.lines().count(),
+            1
+        );
+        let output = grep(
+            "error",
+            GrepOptions {
+                case_insensitive: true,
+                ..GrepOptions::default()
+            },
+        )
+        .await;
+        assert_eq!(output.lines().count(), 2);
+
+        let output = grep(
+            "error",
+            GrepOptions {
+                case_insensitive: true,
+                whole_word: true,
+                ..GrepOptions::default()
+            },
+        )
+        .await;
+        assert!(output.contains("Error: disk full"));
+        assert!(!output.contains("terrorism"));
+
+        let output = grep(
+            "$1.50 (approx)",
+            GrepOptions {
+                fixed_string: true,
+                ..GrepOptions::default()
+            },
+        )
+        .await;
+        assert_eq!(output.lines().count(), 1);
+        assert!(output.contains("cost is $1.50 (approx)"));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn grep_stops_at_match_and_file_caps_and_notes_truncation() {
+        let dir = tempdir().expect("temp dir should be created");
+        for index in 0..50 {
+            std::fs::write(
+                dir.path().join(format!("f{:02}.txt", index)),
+                "needle one\nneedle two\nneedle three\n",
+            )
+            .expect("seed file");
+        }
+        let env = LocalExecutionEnvironment::new(dir.path());
+
+        let by_matches = GrepOptions {
+            max_matches: Some(10),
+            ..GrepOptions::default()
+        };
+        let output = env
+            .grep("needle", ".", by_matches.clone())
+            .await
+            .expect("grep should succeed");
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 11);
+        assert!(lines[..10].iter().all(|line| line.contains("needle")));
+        assert_eq!(lines[10], "[results truncated at 10 matches]");
+
+        let fallback = grep_fallback("needle", dir.path(), &by_matches)
+            .await
+            .expect("fallback grep should succeed");
+        assert_eq!(fallback.lines().count(), 11);
+        assert!(fallback.ends_with("[results truncated at 10 matches]"));
+
+        let by_files = GrepOptions {
+            max_files: Some(2),
+            ..GrepOptions::default()
+        };
+        let output = grep_fallback("needle", dir.path(), &by_files)
+            .await
+            .expect("fallback grep should succeed");
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 7);
+        let files: HashSet<&str> = lines[..6]
+            .iter()
+            .map(|line| line.split(':').next().expect("path prefix"))
+            .collect();
+        assert_eq!(files.len(), 2);
+        assert_eq!(lines[6], "[results truncated at 2 files]");
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn exec_command_timeout_returns_partial_output_and_error_message() {
         let dir = tempdir().expect("temp dir should be created");
@@ -964,6 +1629,32 @@ mod tests {
         assert!(result.stderr.contains("Command timed out after 150ms"));
     }
 
+    #[cfg(unix)]
+    #[tokio::test(flavor = "current_thread")]
+    async fn exec_command_over_output_cap_kills_process_and_marks_truncated() {
+        let dir = tempdir().expect("temp dir should be created");
+        let env = LocalExecutionEnvironment::new(dir.path())
+            .with_command_timeout_limits(30_000, 30_000)
+            .with_max_command_output_bytes(64 * 1024);
+
+        let result = env
+            .exec_command("yes", 0, None, None)
+            .await
+            .expect("command should return a truncated result");
+
+        assert!(result.output_truncated);
+        assert!(!result.timed_out);
+        assert!(result.duration_ms < 30_000);
+        assert!(result.stdout.len() <= 64 * 1024 + 1);
+        assert!(result.stdout.starts_with("y\ny\n"));
+        assert!(
+            result
+                .stderr
+                .contains("Command output exceeded 65536 bytes and the command was terminated")
+        );
+        assert!(env.running_process_ids().is_empty());
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn read_file_returns_structured_error_for_binary_content() {
         let dir = tempdir().expect("temp dir should be created");
@@ -982,6 +1673,62 @@ mod tests {
         assert!(message.contains("application/octet-stream"));
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn read_file_with_encoding_decodes_utf16le_and_latin1() {
+        let dir = tempdir().expect("temp dir should be created");
+        let env = LocalExecutionEnvironment::new(dir.path());
+        let utf16: Vec<u8> = [0xFEFF_u16]
+            .into_iter()
+            .chain("héllo\nwörld".encode_utf16())
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        tokio::fs::write(dir.path().join("utf16.txt"), utf16)
+            .await
+            .expect("utf-16 file should be written");
+        tokio::fs::write(dir.path().join("latin1.txt"), [b'c', b'a', b'f', 0xE9])
+            .await
+            .expect("latin-1 file should be written");
+
+        let utf16 = env
+            .read_file_with_encoding("utf16.txt", None, None, "UTF-16LE")
+            .await
+            .expect("utf-16 read should succeed");
+        assert_eq!(utf16, "héllo\nwörld");
+        let second_line = env
+            .read_file_with_encoding("utf16.txt", Some(2), Some(1), "utf-16le")
+            .await
+            .expect("windowed utf-16 read should succeed");
+        assert_eq!(second_line, "wörld");
+
+        let latin1 = env
+            .read_file_with_encoding("latin1.txt", None, None, "latin1")
+            .await
+            .expect("latin-1 read should succeed");
+        assert_eq!(latin1, "café");
+        assert!(env.read_file("latin1.txt", None, None).await.is_err());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn read_file_with_encoding_rejects_unknown_labels() {
+        let dir = tempdir().expect("temp dir should be created");
+        let env = LocalExecutionEnvironment::new(dir.path());
+        env.write_file("a.txt", "a")
+            .await
+            .expect("write should succeed");
+
+        let err = env
+            .read_file_with_encoding("a.txt", None, None, "klingon")
+            .await
+            .expect_err("unknown encoding should fail");
+        assert!(err.to_string().contains("unknown encoding 'klingon'"));
+        assert_eq!(
+            env.read_file_with_encoding("a.txt", None, None, "utf-8")
+                .await
+                .expect("utf-8 read should succeed"),
+            "a"
+        );
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn read_file_returns_image_mime_for_png_binary_content() {
         let dir = tempdir().expect("temp dir should be created");
@@ -1041,4 +1788,30 @@ mod tests {
         assert_eq!(env.effective_timeout_ms(0), 10_000);
         assert_eq!(env.effective_timeout_ms(700_000), 600_000);
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn temp_dir_is_created_on_first_use_and_removed_on_cleanup() {
+        let dir = tempdir().expect("temp dir should be created");
+        let env = LocalExecutionEnvironment::new(dir.path());
+
+        let temp_dir = env.temp_dir().await.expect("temp dir should be created");
+        assert!(temp_dir.is_dir());
+        assert_eq!(env.temp_dir().await.expect("temp dir reused"), temp_dir);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&temp_dir)
+                .expect("temp dir metadata")
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+        std::fs::write(temp_dir.join("scratch.txt"), "x").expect("scratch file written");
+
+        env.cleanup().await.expect("cleanup should succeed");
+        assert!(!temp_dir.exists());
+        env.cleanup()
+            .await
+            .expect("repeated cleanup should succeed");
+    }
 }
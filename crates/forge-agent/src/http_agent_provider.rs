@@ -6,14 +6,15 @@
 //! backends (OpenAI, Anthropic, etc.) where forge manages the tool loop.
 
 use std::sync::Arc;
-use std::time::Instant;
 
 use async_trait::async_trait;
 use forge_llm::agent_provider::{
-    AgentLoopEvent, AgentProvider, AgentRunOptions, AgentRunResult, ToolActivityRecord,
+    AgentLoopEvent, AgentProvider, AgentRunOptions, AgentRunResult, AgentRunStopReason,
+    ToolActivityRecord,
 };
-use forge_llm::{Client, Message, Request, SDKError, ToolChoice, Usage};
+use forge_llm::{Client, Message, Request, SDKError, ToolChoice, Usage, retry_async};
 
+use crate::clock::{Clock, SystemClock};
 use crate::config::SessionConfig;
 use crate::errors::AgentError;
 use crate::events::{EventEmitter, NoopEventEmitter};
@@ -21,9 +22,10 @@ use crate::execution::ExecutionEnvironment;
 use crate::profiles::ProviderProfile;
 use crate::session::utils::{
     approximate_context_tokens, build_environment_context_snapshot, convert_history_to_messages,
-    current_timestamp, detect_loop, discover_project_documents, validate_reasoning_effort,
+    detect_loop, discover_project_documents, gate_native_tools,
+    is_subagent_tool, llm_retry_policy, validate_reasoning_effort,
 };
-use crate::tools::ToolDispatchOptions;
+use crate::tools::{AGENT_STATUS_TOOL, EditBackups, ToolDispatchOptions, UNDO_EDIT_TOOL};
 use crate::turn::{
     AssistantTurn, SteeringTurn, ToolResultTurn, ToolResultsTurn, Turn, UserTurn,
 };
@@ -39,6 +41,7 @@ pub struct HttpApiAgentProvider {
     provider_profile: Arc<dyn ProviderProfile>,
     execution_env: Arc<dyn ExecutionEnvironment>,
     event_emitter: Arc<dyn EventEmitter>,
+    clock: Arc<dyn Clock>,
     config: SessionConfig,
 }
 
@@ -54,6 +57,7 @@ impl HttpApiAgentProvider {
             provider_profile,
             execution_env,
             event_emitter: Arc::new(NoopEventEmitter),
+            clock: Arc::new(SystemClock),
             config,
         }
     }
@@ -62,6 +66,12 @@ impl HttpApiAgentProvider {
         self.event_emitter = emitter;
         self
     }
+
+    /// Replaces the time source for turn timestamps and `duration_ms`.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
 }
 
 #[async_trait]
@@ -75,27 +85,32 @@ impl AgentProvider for HttpApiAgentProvider {
         prompt: &str,
         options: &AgentRunOptions,
     ) -> Result<AgentRunResult, SDKError> {
-        let start = Instant::now();
+        let start = self.clock.now();
 
         // Internal history for this run.
         let mut history: Vec<Turn> = Vec::new();
         let mut tool_activity: Vec<ToolActivityRecord> = Vec::new();
+        let edit_backups = EditBackups::for_config(&self.config);
         let mut total_usage = Usage::default();
         let mut final_text = String::new();
         let mut response_id = String::new();
         let mut call_counter = 0u64;
 
         // Push initial user turn.
-        let user_turn = Turn::User(UserTurn::new(prompt.to_string(), current_timestamp()));
+        let user_turn = Turn::User(UserTurn::new(prompt.to_string(), self.clock.timestamp()));
         history.push(user_turn);
 
-        let max_tool_rounds = options
-            .max_tool_rounds
-            .unwrap_or(self.config.max_tool_rounds_per_input);
+        let max_tool_rounds = options.max_tool_rounds.unwrap_or(
+            match self.config.max_tool_rounds_per_input {
+                0 => self.provider_profile.suggested_max_tool_rounds_per_input(),
+                configured => configured,
+            },
+        );
         let max_turns = options.max_turns.unwrap_or(self.config.max_turns);
 
         let mut round_count = 0usize;
         let mut context_warning_emitted = false;
+        let mut stop_reason = AgentRunStopReason::Completed;
 
         loop {
             // Check tool round limit.
@@ -108,6 +123,7 @@ impl AgentProvider for HttpApiAgentProvider {
                         ),
                     });
                 }
+                stop_reason = AgentRunStopReason::RoundLimit;
                 break;
             }
 
@@ -118,6 +134,7 @@ impl AgentProvider for HttpApiAgentProvider {
                         message: format!("Turn limit reached ({} turns)", max_turns),
                     });
                 }
+                stop_reason = AgentRunStopReason::TurnLimit;
                 break;
             }
 
@@ -152,7 +169,14 @@ impl AgentProvider for HttpApiAgentProvider {
                 .map_err(|e| sdk_error_from_agent_error(e))?;
 
             // Call LLM.
-            let response = self.llm_client.complete(request).await?;
+            let retry_policy = llm_retry_policy(
+                &self.config,
+                self.provider_profile.id(),
+                None,
+                self.clock.clone(),
+            );
+            let response =
+                retry_async(&retry_policy, || self.llm_client.complete(request.clone())).await?;
 
             let text = response.text();
             let tool_calls = response.tool_calls();
@@ -179,7 +203,7 @@ impl AgentProvider for HttpApiAgentProvider {
                 reasoning,
                 response.usage.clone(),
                 Some(response.id),
-                current_timestamp(),
+                self.clock.timestamp(),
             ));
             history.push(assistant_turn);
 
@@ -204,10 +228,12 @@ impl AgentProvider for HttpApiAgentProvider {
                     &self.config,
                     self.event_emitter.clone(),
                     ToolDispatchOptions {
-                        session_id: format!("http-agent-{}", start.elapsed().as_nanos()),
+                        session_id: format!("http-agent-{}", self.clock.now().as_nanos()),
                         supports_parallel_tool_calls: supports_parallel,
+                        force_sequential: false,
                         hook: None,
                         hook_strict: false,
+                        edit_backups: edit_backups.clone(),
                     },
                 )
                 .await
@@ -266,7 +292,7 @@ impl AgentProvider for HttpApiAgentProvider {
                 })
                 .collect();
             let tool_results_turn =
-                Turn::ToolResults(ToolResultsTurn::new(result_turns, current_timestamp()));
+                Turn::ToolResults(ToolResultsTurn::new(result_turns, self.clock.timestamp()));
             history.push(tool_results_turn);
 
             // Loop detection.
@@ -285,7 +311,7 @@ impl AgentProvider for HttpApiAgentProvider {
                 if !already_warned {
                     history.push(Turn::Steering(SteeringTurn::new(
                         warning.clone(),
-                        current_timestamp(),
+                        self.clock.timestamp(),
                     )));
                     if let Some(ref on_event) = options.on_event {
                         on_event(AgentLoopEvent::Warning { message: warning });
@@ -294,7 +320,7 @@ impl AgentProvider for HttpApiAgentProvider {
             }
         }
 
-        let elapsed = start.elapsed();
+        let elapsed = self.clock.now().saturating_sub(start);
         let model = self.provider_profile.model().to_string();
 
         Ok(AgentRunResult {
@@ -306,6 +332,7 @@ impl AgentProvider for HttpApiAgentProvider {
             provider: self.provider_profile.id().to_string(),
             cost_usd: None,
             duration_ms: Some(elapsed.as_millis() as u64),
+            stop_reason,
         })
     }
 }
@@ -318,27 +345,50 @@ impl HttpApiAgentProvider {
         history: &[Turn],
         options: &AgentRunOptions,
     ) -> Result<Request, AgentError> {
-        let tools = self.provider_profile.tools();
+        let mut tools = self.provider_profile.tools();
+        if !self.config.enable_subagents {
+            tools.retain(|tool| !is_subagent_tool(&tool.name));
+        }
+        if !self.config.keep_edit_backups {
+            tools.retain(|tool| tool.name != UNDO_EDIT_TOOL);
+        }
+        // Budget reporting needs Session state this provider does not keep.
+        tools.retain(|tool| tool.name != AGENT_STATUS_TOOL);
         let environment_context = build_environment_context_snapshot(
             self.provider_profile.as_ref(),
             self.execution_env.as_ref(),
+            &self.config.environment_context_fields,
         );
         let project_docs = discover_project_documents(
             self.execution_env.working_directory(),
             self.provider_profile.as_ref(),
+            &self.config.extra_instruction_files,
         );
-        let system_prompt = self.provider_profile.build_system_prompt(
+        let (tools, text_only_tools_block) = gate_native_tools(
+            self.provider_profile.as_ref(),
+            self.config.on_tools_unsupported,
+            tools,
+        )?;
+        let mut system_prompt = self.provider_profile.build_system_prompt(
             &environment_context,
-            &tools,
+            tools.as_deref().unwrap_or_default(),
             &project_docs,
             options.system_prompt_override.as_deref()
                 .or(self.config.system_prompt_override.as_deref()),
         );
 
+        if let Some(block) = text_only_tools_block {
+            system_prompt.push_str("\n\n");
+            system_prompt.push_str(&block);
+        }
+
         let mut messages = vec![Message::system(system_prompt)];
-        messages.extend(convert_history_to_messages(history));
+        messages.extend(convert_history_to_messages(
+            history,
+            self.provider_profile.group_tool_results(),
+            self.config.include_historical_reasoning,
+        ));
 
-        let tools = if tools.is_empty() { None } else { Some(tools) };
         let tool_choice = tools.as_ref().map(|_| ToolChoice {
             mode: "auto".to_string(),
             tool_name: None,
@@ -451,8 +501,11 @@ mod tests {
             ProviderCapabilities {
                 supports_reasoning: false,
                 supports_streaming: false,
+                supports_tools: true,
                 supports_parallel_tool_calls: false,
                 context_window_size: 128_000,
+                supports_prompt_caching: false,
+                supports_vision: false,
             }
         }
         fn knowledge_cutoff(&self) -> Option<&str> {
@@ -475,6 +528,66 @@ mod tests {
         assert!(result.ends_with("..."));
     }
 
+    struct TextAdapter;
+
+    #[async_trait]
+    impl forge_llm::ProviderAdapter for TextAdapter {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        async fn complete(&self, _request: Request) -> Result<forge_llm::Response, SDKError> {
+            Ok(forge_llm::Response {
+                id: "resp-1".to_string(),
+                model: "test-model".to_string(),
+                provider: "test".to_string(),
+                message: Message::assistant("done"),
+                finish_reason: forge_llm::FinishReason {
+                    reason: "stop".to_string(),
+                    raw: None,
+                },
+                usage: Usage::default(),
+                raw: None,
+                warnings: Vec::new(),
+                rate_limit: None,
+            })
+        }
+
+        async fn stream(
+            &self,
+            _request: Request,
+        ) -> Result<forge_llm::StreamEventStream, SDKError> {
+            Ok(Box::pin(futures::stream::empty()))
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_to_completion_with_clock_expected_duration_from_injected_clock() {
+        let mut client = Client::default();
+        client
+            .register_provider(Arc::new(TextAdapter))
+            .expect("provider should register");
+        let provider = HttpApiAgentProvider::new(
+            Arc::new(client),
+            Arc::new(TestProviderProfile::new()),
+            Arc::new(crate::LocalExecutionEnvironment::new(std::env::temp_dir())),
+            SessionConfig::default(),
+        )
+        .with_clock(Arc::new(crate::MonotonicTestClock::new(
+            std::time::Duration::from_secs(100),
+            std::time::Duration::from_secs(1),
+        )));
+
+        let result = provider
+            .run_to_completion("hi", &AgentRunOptions::default())
+            .await
+            .expect("run should succeed");
+
+        assert_eq!(result.text, "done");
+        // Reads: start, user turn, assistant turn, end.
+        assert_eq!(result.duration_ms, Some(3_000));
+    }
+
     #[test]
     fn sdk_error_from_agent_error_converts() {
         let err = AgentError::session_closed();
@@ -5,6 +5,7 @@
 //! session orchestration, provider profiles, tools, execution environments,
 //! event delivery, and output truncation.
 
+pub mod clock;
 pub mod config;
 pub mod errors;
 pub mod events;
@@ -17,6 +18,7 @@ pub mod tools;
 pub mod truncation;
 pub mod turn;
 
+pub use clock::*;
 pub use config::*;
 pub use errors::*;
 pub use events::*;
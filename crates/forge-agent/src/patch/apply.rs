@@ -2,117 +2,266 @@ use std::sync::Arc;
 
 use crate::{AgentError, ExecutionEnvironment, ToolError};
 
-use super::matching::{find_subsequence, find_subsequence_fuzzy_unique};
+use super::matching::{find_subsequence, find_subsequence_fuzzy_unique, token_match_confidence};
 use super::types::{PatchHunk, PatchHunkLine, PatchOperation};
 
+/// Applies parsed patch operations. By default the first failure aborts the
+/// patch. With `partial`, operations and hunks that fail are skipped and
+/// listed in the returned report, so only the failures need to be redone.
+/// Fuzzy hunk matches below `min_confidence` fail like unmatched hunks.
 pub(crate) async fn apply_patch_operations(
     operations: &[PatchOperation],
     env: Arc<dyn ExecutionEnvironment>,
+    partial: bool,
+    min_confidence: f64,
 ) -> Result<String, AgentError> {
     let mut summaries = Vec::new();
+    let mut skipped = Vec::new();
     for operation in operations {
-        match operation {
-            PatchOperation::AddFile { path, lines } => {
-                if env.file_exists(path).await? {
-                    return Err(
-                        ToolError::Execution(format!("file already exists: '{}'", path)).into(),
-                    );
-                }
-                env.write_file(path, &lines.join("\n")).await?;
-                summaries.push(format!("A {}", path));
+        match apply_operation(operation, env.as_ref(), partial, min_confidence).await {
+            Ok((summary, skipped_hunks)) => {
+                summaries.push(summary);
+                skipped.extend(
+                    skipped_hunks
+                        .into_iter()
+                        .map(|reason| format!("{}: {}", operation.path(), reason)),
+                );
             }
-            PatchOperation::DeleteFile { path } => {
-                if !env.file_exists(path).await? {
-                    return Err(ToolError::Execution(format!("file not found: '{}'", path)).into());
-                }
-                env.delete_file(path).await?;
-                summaries.push(format!("D {}", path));
+            Err(error) if partial => skipped.push(format!("{}: {}", operation.path(), error)),
+            Err(error) => return Err(error),
+        }
+    }
+
+    if skipped.is_empty() {
+        return Ok(format!("Applied patch:\n{}", summaries.join("\n")));
+    }
+    let applied = if summaries.is_empty() {
+        "(none)".to_string()
+    } else {
+        summaries.join("\n")
+    };
+    let report = format!(
+        "Partially applied patch:\nApplied:\n{}\nSkipped:\n{}",
+        applied,
+        skipped.join("\n")
+    );
+    if summaries.is_empty() {
+        return Err(ToolError::Execution(report).into());
+    }
+    Ok(report)
+}
+
+async fn apply_operation(
+    operation: &PatchOperation,
+    env: &dyn ExecutionEnvironment,
+    partial: bool,
+    min_confidence: f64,
+) -> Result<(String, Vec<String>), AgentError> {
+    match operation {
+        PatchOperation::AddFile { path, lines } => {
+            if env.file_exists(path).await? {
+                return Err(
+                    ToolError::Execution(format!("file already exists: '{}'", path)).into(),
+                );
+            }
+            env.write_file(path, &lines.join("\n")).await?;
+            Ok((format!("A {} (+{})", path, lines.len()), Vec::new()))
+        }
+        PatchOperation::DeleteFile { path } => {
+            if !env.file_exists(path).await? {
+                return Err(ToolError::Execution(format!("file not found: '{}'", path)).into());
             }
-            PatchOperation::UpdateFile {
-                path,
-                move_to,
-                hunks,
-            } => {
-                if !env.file_exists(path).await? {
+            // Binary files cannot be read as text; they are listed without a count.
+            let removed = env
+                .read_file(path, None, None)
+                .await
+                .ok()
+                .map(|content| split_content_lines(&content).len());
+            env.delete_file(path).await?;
+            let summary = match removed {
+                Some(removed) => format!("D {} (-{})", path, removed),
+                None => format!("D {}", path),
+            };
+            Ok((summary, Vec::new()))
+        }
+        PatchOperation::UpdateFile {
+            path,
+            move_to,
+            hunks,
+        } => {
+            if !env.file_exists(path).await? {
+                return Err(
+                    ToolError::Execution(format!("cannot update missing file '{}'", path)).into(),
+                );
+            }
+
+            let original = env.read_file(path, None, None).await?;
+            let (updated, counts, skipped_hunks) = if partial {
+                apply_matching_hunks_to_content(&original, hunks, min_confidence)?
+            } else {
+                let (updated, counts) = apply_hunks_to_content(&original, hunks, min_confidence)
+                    .map_err(AgentError::from)?;
+                (updated, counts, Vec::new())
+            };
+
+            let move_target = move_to.as_deref().filter(|target| *target != path.as_str());
+            let summary = if let Some(target_path) = move_target {
+                if env.file_exists(target_path).await? {
                     return Err(ToolError::Execution(format!(
-                        "cannot update missing file '{}'",
-                        path
+                        "move target already exists: '{}'",
+                        target_path
                     ))
                     .into());
                 }
+                env.write_file(path, &updated).await?;
+                env.move_file(path, target_path).await?;
+                format!("R {} -> {} {}", path, target_path, counts)
+            } else {
+                env.write_file(path, &updated).await?;
+                format!("M {} {}", path, counts)
+            };
+            Ok((summary, skipped_hunks))
+        }
+    }
+}
 
-                let original = env.read_file(path, None, None).await?;
-                let updated = apply_hunks_to_content(&original, hunks).map_err(AgentError::from)?;
-
-                let move_target = move_to.as_deref().filter(|target| *target != path.as_str());
-                if let Some(target_path) = move_target {
-                    if env.file_exists(target_path).await? {
-                        return Err(ToolError::Execution(format!(
-                            "move target already exists: '{}'",
-                            target_path
-                        ))
-                        .into());
-                    }
-                    env.write_file(path, &updated).await?;
-                    env.move_file(path, target_path).await?;
-                    summaries.push(format!("R {} -> {}", path, target_path));
-                } else {
-                    env.write_file(path, &updated).await?;
-                    summaries.push(format!("M {}", path));
-                }
+/// Lines added and removed by the hunks that were applied to one file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct LineCounts {
+    added: usize,
+    removed: usize,
+}
+
+impl LineCounts {
+    fn of_hunk(hunk: &PatchHunk) -> Self {
+        hunk.lines.iter().fold(Self::default(), |mut counts, line| {
+            match line {
+                PatchHunkLine::Add(_) => counts.added += 1,
+                PatchHunkLine::Delete(_) => counts.removed += 1,
+                PatchHunkLine::Context(_) | PatchHunkLine::EndOfFile => {}
             }
-        }
+            counts
+        })
     }
 
-    Ok(format!("Applied patch:\n{}", summaries.join("\n")))
+    fn add(&mut self, other: Self) {
+        self.added += other.added;
+        self.removed += other.removed;
+    }
 }
 
-fn apply_hunks_to_content(content: &str, hunks: &[PatchHunk]) -> Result<String, ToolError> {
+impl std::fmt::Display for LineCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "(+{} -{})", self.added, self.removed)
+    }
+}
+
+fn apply_hunks_to_content(
+    content: &str,
+    hunks: &[PatchHunk],
+    min_confidence: f64,
+) -> Result<(String, LineCounts), ToolError> {
     let mut lines = split_content_lines(content);
-    let had_trailing_newline = content.ends_with('\n');
     let mut search_from = 0usize;
+    let mut counts = LineCounts::default();
+    for hunk in hunks {
+        counts.add(apply_hunk(
+            &mut lines,
+            hunk,
+            &mut search_from,
+            min_confidence,
+        )?);
+    }
+    Ok((join_content_lines(lines, content.ends_with('\n')), counts))
+}
 
+/// Applies every hunk that matches and returns the failure reasons of the rest.
+/// Fails only when no hunk applies.
+fn apply_matching_hunks_to_content(
+    content: &str,
+    hunks: &[PatchHunk],
+    min_confidence: f64,
+) -> Result<(String, LineCounts, Vec<String>), ToolError> {
+    let mut lines = split_content_lines(content);
+    let mut search_from = 0usize;
+    let mut counts = LineCounts::default();
+    let mut skipped = Vec::new();
     for hunk in hunks {
-        let (old_lines, new_lines) = hunk_old_new_lines(hunk);
-        if old_lines.is_empty() {
-            let insert_at = search_from.min(lines.len());
-            lines.splice(insert_at..insert_at, new_lines.clone());
-            search_from = insert_at + new_lines.len();
-            continue;
+        match apply_hunk(&mut lines, hunk, &mut search_from, min_confidence) {
+            Ok(hunk_counts) => counts.add(hunk_counts),
+            Err(error) => skipped.push(error.to_string()),
         }
+    }
+    if !hunks.is_empty() && skipped.len() == hunks.len() {
+        return Err(ToolError::Execution(skipped.join("; ")));
+    }
+    Ok((
+        join_content_lines(lines, content.ends_with('\n')),
+        counts,
+        skipped,
+    ))
+}
 
-        let position = if let Some(index) = find_subsequence(&lines, &old_lines, search_from)
-            .or_else(|| find_subsequence(&lines, &old_lines, 0))
-        {
-            index
-        } else {
-            match find_subsequence_fuzzy_unique(&lines, &old_lines, search_from) {
-                Ok(Some(index)) => index,
-                Ok(None) => {
-                    return Err(ToolError::Execution(format!(
-                        "failed to match hunk '{}' (exact and fuzzy matching failed)",
-                        hunk.header
-                    )));
-                }
-                Err(matches) => {
+fn apply_hunk(
+    lines: &mut Vec<String>,
+    hunk: &PatchHunk,
+    search_from: &mut usize,
+    min_confidence: f64,
+) -> Result<LineCounts, ToolError> {
+    let (old_lines, new_lines) = hunk_old_new_lines(hunk);
+    if old_lines.is_empty() {
+        let insert_at = (*search_from).min(lines.len());
+        lines.splice(insert_at..insert_at, new_lines.clone());
+        *search_from = insert_at + new_lines.len();
+        return Ok(LineCounts::of_hunk(hunk));
+    }
+
+    let position = if let Some(index) = find_subsequence(lines, &old_lines, *search_from)
+        .or_else(|| find_subsequence(lines, &old_lines, 0))
+    {
+        index
+    } else {
+        match find_subsequence_fuzzy_unique(lines, &old_lines, *search_from) {
+            Ok(Some(index)) => {
+                let confidence = token_match_confidence(
+                    &old_lines.join("\n"),
+                    &lines[index..index + old_lines.len()].join("\n"),
+                );
+                if confidence < min_confidence {
                     return Err(ToolError::Execution(format!(
-                        "failed to match hunk '{}': fuzzy match is ambiguous ({} candidates)",
-                        hunk.header, matches
+                        "low-confidence match for hunk '{}': {:.2} of tokens match exactly, below the {:.2} threshold",
+                        hunk.header, confidence, min_confidence
                     )));
                 }
+                index
+            }
+            Ok(None) => {
+                return Err(ToolError::Execution(format!(
+                    "failed to match hunk '{}' (exact and fuzzy matching failed)",
+                    hunk.header
+                )));
             }
-        };
+            Err(matches) => {
+                return Err(ToolError::Execution(format!(
+                    "failed to match hunk '{}': fuzzy match is ambiguous ({} candidates)",
+                    hunk.header, matches
+                )));
+            }
+        }
+    };
 
-        let end = position + old_lines.len();
-        lines.splice(position..end, new_lines.clone());
-        search_from = position + new_lines.len();
-    }
+    let end = position + old_lines.len();
+    lines.splice(position..end, new_lines.clone());
+    *search_from = position + new_lines.len();
+    Ok(LineCounts::of_hunk(hunk))
+}
 
+fn join_content_lines(lines: Vec<String>, trailing_newline: bool) -> String {
     let mut updated = lines.join("\n");
-    if had_trailing_newline {
+    if trailing_newline {
         updated.push('\n');
     }
-    Ok(updated)
+    updated
 }
 
 fn split_content_lines(content: &str) -> Vec<String> {
@@ -146,7 +295,7 @@ fn hunk_old_new_lines(hunk: &PatchHunk) -> (Vec<String>, Vec<String>) {
 
 #[cfg(test)]
 mod tests {
-    use super::apply_hunks_to_content;
+    use super::{apply_hunks_to_content, apply_matching_hunks_to_content};
     use crate::patch::types::{PatchHunk, PatchHunkLine};
 
     #[test]
@@ -158,8 +307,10 @@ mod tests {
                 PatchHunkLine::Add("line-two".to_string()),
             ],
         }];
-        let updated = apply_hunks_to_content("line1\nline2\n", &hunks).expect("should apply");
+        let (updated, counts) =
+            apply_hunks_to_content("line1\nline2\n", &hunks, 0.0).expect("should apply");
         assert_eq!(updated, "line1\nline-two\n");
+        assert_eq!(counts.to_string(), "(+1 -1)");
     }
 
     #[test]
@@ -173,8 +324,64 @@ mod tests {
                 PatchHunkLine::Add("println!(\"hello\");".to_string()),
             ],
         }];
-        let updated = apply_hunks_to_content("fn  greet() {\nprintln!(\"hi\");\n}\n", &hunks)
-            .expect("fuzzy hunk should apply");
+        let (updated, _) =
+            apply_hunks_to_content("fn  greet() {\nprintln!(\"hi\");\n}\n", &hunks, 0.0)
+                .expect("fuzzy hunk should apply");
         assert!(updated.contains("println!(\"hello\")"));
     }
+
+    #[test]
+    fn apply_hunks_to_content_min_confidence_gates_fuzzy_matches() {
+        let hunks = vec![PatchHunk {
+            header: "@@ update".to_string(),
+            lines: vec![
+                PatchHunkLine::Delete("let label = \"draft\"; // keep this".to_string()),
+                PatchHunkLine::Add("let label = \"final\";".to_string()),
+            ],
+        }];
+        let near_miss = "let  label = \u{201C}draft\u{201D}; // keep this\n";
+        let (updated, _) =
+            apply_hunks_to_content(near_miss, &hunks, 0.8).expect("near-miss should apply");
+        assert_eq!(updated, "let label = \"final\";\n");
+
+        let hunks = vec![PatchHunk {
+            header: "@@ quotes".to_string(),
+            lines: vec![
+                PatchHunkLine::Delete("\"a\" \"b\" \"c\" d".to_string()),
+                PatchHunkLine::Add("x".to_string()),
+            ],
+        }];
+        let poor = "\u{201C}a\u{201D} \u{201C}b\u{201D} \u{201C}c\u{201D} d\n";
+        let err = apply_hunks_to_content(poor, &hunks, 0.8).expect_err("poor match is refused");
+        assert!(
+            err.to_string()
+                .contains("low-confidence match for hunk '@@ quotes'")
+        );
+    }
+
+    #[test]
+    fn apply_matching_hunks_to_content_skips_unmatched_hunks() {
+        let hunks = vec![
+            PatchHunk {
+                header: "@@ first".to_string(),
+                lines: vec![
+                    PatchHunkLine::Delete("line1".to_string()),
+                    PatchHunkLine::Add("line-one".to_string()),
+                ],
+            },
+            PatchHunk {
+                header: "@@ missing".to_string(),
+                lines: vec![
+                    PatchHunkLine::Delete("absent".to_string()),
+                    PatchHunkLine::Add("present".to_string()),
+                ],
+            },
+        ];
+        let (updated, counts, skipped) =
+            apply_matching_hunks_to_content("line1\nline2\n", &hunks, 0.0).expect("should apply");
+        assert_eq!(updated, "line-one\nline2\n");
+        assert_eq!(counts.to_string(), "(+1 -1)");
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].contains("'@@ missing'"));
+    }
 }
@@ -2,13 +2,33 @@ use regex::{Regex, RegexBuilder};
 
 use crate::ToolError;
 
+use super::matching::token_match_confidence;
+
 pub(crate) fn apply_edit(
     content: &str,
     file_path: &str,
     old_string: &str,
     new_string: &str,
     replace_all: bool,
+    occurrence: Option<usize>,
+    min_confidence: f64,
 ) -> Result<(String, usize), ToolError> {
+    if let Some(occurrence) = occurrence {
+        if replace_all {
+            return Err(ToolError::Execution(
+                "occurrence cannot be combined with replace_all=true".to_string(),
+            ));
+        }
+        return replace_occurrence(
+            content,
+            file_path,
+            old_string,
+            new_string,
+            occurrence,
+            min_confidence,
+        );
+    }
+
     let replacement_count = content.match_indices(old_string).count();
     if replacement_count > 0 {
         if replacement_count > 1 && !replace_all {
@@ -26,19 +46,7 @@ pub(crate) fn apply_edit(
         return Ok((next_content, replacement_count));
     }
 
-    let fuzzy_regex = build_fuzzy_regex(old_string)?;
-    let matches: Vec<(usize, usize)> = fuzzy_regex
-        .find_iter(content)
-        .take(128)
-        .map(|m| (m.start(), m.end()))
-        .collect();
-
-    if matches.is_empty() {
-        return Err(ToolError::Execution(format!(
-            "old_string not found in '{}' (exact and fuzzy matching failed)",
-            file_path
-        )));
-    }
+    let matches = find_fuzzy_matches(content, file_path, old_string, min_confidence)?;
     if matches.len() > 1 && !replace_all {
         return Err(ToolError::Execution(format!(
             "old_string is not unique in '{}': fuzzy match found {} locations; provide more context or set replace_all=true",
@@ -61,6 +69,81 @@ pub(crate) fn apply_edit(
     Ok((updated, replaced))
 }
 
+/// Replaces only the `occurrence`-th (1-based) match, so intentionally repeated
+/// snippets can be targeted without the uniqueness check. Exact matches take
+/// precedence over fuzzy ones, as in the unique case.
+fn replace_occurrence(
+    content: &str,
+    file_path: &str,
+    old_string: &str,
+    new_string: &str,
+    occurrence: usize,
+    min_confidence: f64,
+) -> Result<(String, usize), ToolError> {
+    if occurrence == 0 {
+        return Err(ToolError::Execution(
+            "occurrence is 1-based and must be at least 1".to_string(),
+        ));
+    }
+
+    let exact: Vec<(usize, usize)> = content
+        .match_indices(old_string)
+        .map(|(start, matched)| (start, start + matched.len()))
+        .collect();
+    let matches = if exact.is_empty() {
+        find_fuzzy_matches(content, file_path, old_string, min_confidence)?
+    } else {
+        exact
+    };
+    let Some(&(start, end)) = matches.get(occurrence - 1) else {
+        return Err(ToolError::Execution(format!(
+            "occurrence {} is out of range in '{}': found {} match{}",
+            occurrence,
+            file_path,
+            matches.len(),
+            if matches.len() == 1 { "" } else { "es" }
+        )));
+    };
+
+    let mut updated = content.to_string();
+    updated.replace_range(start..end, new_string);
+    Ok((updated, 1))
+}
+
+/// Fails when any match has fewer than `min_confidence` of its tokens
+/// matching `old_string` exactly.
+fn find_fuzzy_matches(
+    content: &str,
+    file_path: &str,
+    old_string: &str,
+    min_confidence: f64,
+) -> Result<Vec<(usize, usize)>, ToolError> {
+    let fuzzy_regex = build_fuzzy_regex(old_string)?;
+    let matches: Vec<(usize, usize)> = fuzzy_regex
+        .find_iter(content)
+        .take(128)
+        .map(|m| (m.start(), m.end()))
+        .collect();
+
+    if matches.is_empty() {
+        return Err(ToolError::Execution(format!(
+            "old_string not found in '{}' (exact and fuzzy matching failed)",
+            file_path
+        )));
+    }
+    let confidence = matches
+        .iter()
+        .map(|&(start, end)| token_match_confidence(old_string, &content[start..end]))
+        .fold(1.0, f64::min);
+    if confidence < min_confidence {
+        return Err(ToolError::Execution(format!(
+            "low-confidence match for old_string in '{}': {:.2} of tokens match exactly, below the {:.2} threshold",
+            file_path, confidence, min_confidence
+        )));
+    }
+    Ok(matches)
+}
+
 fn build_fuzzy_regex(old_string: &str) -> Result<Regex, ToolError> {
     if old_string.chars().count() > 20_000 {
         return Err(ToolError::Execution(
@@ -117,8 +200,8 @@ mod tests {
 
     #[test]
     fn apply_edit_exact_match_replaces_once() {
-        let (updated, replaced) =
-            apply_edit("a b", "f.txt", "a b", "x", false).expect("exact match should succeed");
+        let (updated, replaced) = apply_edit("a b", "f.txt", "a b", "x", false, None, 0.0)
+            .expect("exact match should succeed");
         assert_eq!(updated, "x");
         assert_eq!(replaced, 1);
     }
@@ -131,18 +214,82 @@ mod tests {
             "fn main() {\n}",
             "fn run() {\n}",
             false,
+            None,
+            0.0,
         )
         .expect("fuzzy match should succeed");
         assert!(updated.contains("fn run() {"));
         assert_eq!(replaced, 1);
     }
 
+    #[test]
+    fn apply_edit_fuzzy_match_applies_near_miss_above_min_confidence() {
+        let (updated, replaced) = apply_edit(
+            "let  label = \u{2018}draft\u{2019}; // keep this\n",
+            "f.txt",
+            "let label = 'draft'; // keep this",
+            "let label = 'final';",
+            false,
+            None,
+            0.8,
+        )
+        .expect("near-miss should clear the threshold");
+        assert_eq!(updated, "let label = 'final';\n");
+        assert_eq!(replaced, 1);
+    }
+
+    #[test]
+    fn apply_edit_fuzzy_match_rejects_match_below_min_confidence() {
+        let err = apply_edit(
+            "\u{2018}a\u{2019} \u{2018}b\u{2019} \u{2018}c\u{2019} d\n",
+            "f.txt",
+            "'a' 'b' 'c' d",
+            "x",
+            false,
+            None,
+            0.8,
+        )
+        .expect_err("poor match should be refused");
+        let message = err.to_string();
+        assert!(message.contains("low-confidence match"));
+        assert!(message.contains("0.25"));
+    }
+
     #[test]
     fn apply_edit_fuzzy_match_reports_ambiguity_without_replace_all() {
-        let err = apply_edit("a  b\nx\na b\n", "f.txt", "a   b", "z", false)
+        let err = apply_edit("a  b\nx\na b\n", "f.txt", "a   b", "z", false, None, 0.0)
             .expect_err("expected ambiguity");
         let message = err.to_string();
         assert!(message.contains("not unique"));
         assert!(message.contains("fuzzy match found"));
     }
+
+    #[test]
+    fn apply_edit_occurrence_targets_nth_match() {
+        let content = "item\nitem\nitem\n";
+        let (first, replaced) =
+            apply_edit(content, "f.txt", "item", "first", false, Some(1), 0.0).expect("first");
+        assert_eq!(first, "first\nitem\nitem\n");
+        assert_eq!(replaced, 1);
+
+        let (second, _) =
+            apply_edit(content, "f.txt", "item", "second", false, Some(2), 0.0).expect("second");
+        assert_eq!(second, "item\nsecond\nitem\n");
+    }
+
+    #[test]
+    fn apply_edit_occurrence_out_of_range_errors() {
+        let err = apply_edit(
+            "item\nitem\nitem\n",
+            "f.txt",
+            "item",
+            "x",
+            false,
+            Some(4),
+            0.0,
+        )
+        .expect_err("expected out of range");
+        assert!(err.to_string().contains("occurrence 4 is out of range"));
+        assert!(err.to_string().contains("found 3 matches"));
+    }
 }
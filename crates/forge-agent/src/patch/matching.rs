@@ -67,6 +67,25 @@ pub(crate) fn find_subsequence_fuzzy_unique(
     }
 }
 
+/// Share of whitespace-separated tokens that are identical at the same
+/// position in `expected` and `actual`; `1.0` when both are empty. Fuzzy
+/// matches differ only in whitespace and punctuation variants, so this is
+/// the fraction of the match that needed no leniency.
+pub(crate) fn token_match_confidence(expected: &str, actual: &str) -> f64 {
+    let expected: Vec<&str> = expected.split_whitespace().collect();
+    let actual: Vec<&str> = actual.split_whitespace().collect();
+    let total = expected.len().max(actual.len());
+    if total == 0 {
+        return 1.0;
+    }
+    let exact = expected
+        .iter()
+        .zip(&actual)
+        .filter(|(expected, actual)| expected == actual)
+        .count();
+    exact as f64 / total as f64
+}
+
 pub(crate) fn normalize_line(input: &str) -> String {
     let mut result = String::with_capacity(input.len());
     let mut saw_whitespace = false;
@@ -98,7 +117,9 @@ fn canonical_char(ch: char) -> char {
 
 #[cfg(test)]
 mod tests {
-    use super::{find_subsequence, find_subsequence_fuzzy_unique, normalize_line};
+    use super::{
+        find_subsequence, find_subsequence_fuzzy_unique, normalize_line, token_match_confidence,
+    };
 
     #[test]
     fn normalize_line_collapses_whitespace_and_unicode_punctuation() {
@@ -106,6 +127,16 @@ mod tests {
         assert_eq!(normalized, "a 'b' - c");
     }
 
+    #[test]
+    fn token_match_confidence_counts_verbatim_tokens() {
+        assert_eq!(token_match_confidence("a  b\tc", "a b c"), 1.0);
+        assert_eq!(
+            token_match_confidence("say 'hi' now", "say \u{2018}hi\u{2019} now"),
+            2.0 / 3.0
+        );
+        assert_eq!(token_match_confidence("", ""), 1.0);
+    }
+
     #[test]
     fn find_subsequence_returns_exact_index() {
         let haystack = vec![
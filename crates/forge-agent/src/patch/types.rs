@@ -14,6 +14,16 @@ pub(crate) enum PatchOperation {
     },
 }
 
+impl PatchOperation {
+    pub(crate) fn path(&self) -> &str {
+        match self {
+            PatchOperation::AddFile { path, .. }
+            | PatchOperation::DeleteFile { path }
+            | PatchOperation::UpdateFile { path, .. } => path,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct PatchHunk {
     pub(crate) header: String,
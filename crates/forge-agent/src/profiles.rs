@@ -1,8 +1,8 @@
 use crate::{
-    ToolRegistry, build_anthropic_tool_registry, build_gemini_tool_registry,
-    build_openai_tool_registry,
+    EnvironmentContextFields, SessionError, ToolRegistry, build_anthropic_tool_registry,
+    build_gemini_tool_registry, build_openai_tool_registry,
 };
-use forge_llm::ToolDefinition;
+use forge_llm::{Request, ToolDefinition};
 use serde_json::Value;
 use std::sync::Arc;
 
@@ -10,6 +10,8 @@ pub const OPENAI_PROFILE_ID: &str = "openai";
 pub const ANTHROPIC_PROFILE_ID: &str = "anthropic";
 pub const GEMINI_PROFILE_ID: &str = "gemini";
 
+pub const DEFAULT_MAX_TOOL_ROUNDS_PER_INPUT: usize = 200;
+
 pub const PROJECT_DOC_TRUNCATION_MARKER: &str = "[Project instructions truncated at 32KB]";
 
 const DEFAULT_OPENAI_INSTRUCTIONS: &str = "\
@@ -37,8 +39,15 @@ If blocked, state the blocker clearly and propose the next concrete action.";
 pub struct ProviderCapabilities {
     pub supports_reasoning: bool,
     pub supports_streaming: bool,
+    /// Accepts native tool definitions on `Request.tools`.
+    pub supports_tools: bool,
     pub supports_parallel_tool_calls: bool,
     pub context_window_size: usize,
+    /// The provider honours cache-control markers; the profile requests them
+    /// for the stable system prompt and tool definitions.
+    pub supports_prompt_caching: bool,
+    /// Accepts image parts in user messages.
+    pub supports_vision: bool,
 }
 
 impl Default for ProviderCapabilities {
@@ -46,12 +55,103 @@ impl Default for ProviderCapabilities {
         Self {
             supports_reasoning: true,
             supports_streaming: true,
+            supports_tools: true,
             supports_parallel_tool_calls: false,
             context_window_size: 128_000,
+            supports_prompt_caching: false,
+            supports_vision: false,
         }
     }
 }
 
+/// Built-in capability facts for a model a default profile knows about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KnownModelSpec {
+    pub profile_id: &'static str,
+    pub model: &'static str,
+    pub context_window_size: usize,
+    pub supports_parallel_tool_calls: bool,
+    pub knowledge_cutoff: &'static str,
+}
+
+const KNOWN_MODELS: &[KnownModelSpec] = &[
+    KnownModelSpec {
+        profile_id: OPENAI_PROFILE_ID,
+        model: "gpt-5.2",
+        context_window_size: 1_047_576,
+        supports_parallel_tool_calls: true,
+        knowledge_cutoff: "2025-08",
+    },
+    KnownModelSpec {
+        profile_id: OPENAI_PROFILE_ID,
+        model: "gpt-5.2-codex",
+        context_window_size: 1_047_576,
+        supports_parallel_tool_calls: true,
+        knowledge_cutoff: "2025-08",
+    },
+    KnownModelSpec {
+        profile_id: OPENAI_PROFILE_ID,
+        model: "gpt-5-mini",
+        context_window_size: 1_047_576,
+        supports_parallel_tool_calls: true,
+        knowledge_cutoff: "2024-05",
+    },
+    KnownModelSpec {
+        profile_id: ANTHROPIC_PROFILE_ID,
+        model: "claude-opus-4-6",
+        context_window_size: 200_000,
+        supports_parallel_tool_calls: true,
+        knowledge_cutoff: "2025-05",
+    },
+    KnownModelSpec {
+        profile_id: ANTHROPIC_PROFILE_ID,
+        model: "claude-sonnet-4-5",
+        context_window_size: 200_000,
+        supports_parallel_tool_calls: true,
+        knowledge_cutoff: "2025-01",
+    },
+    KnownModelSpec {
+        profile_id: GEMINI_PROFILE_ID,
+        model: "gemini-3-pro-preview",
+        context_window_size: 1_048_576,
+        supports_parallel_tool_calls: true,
+        knowledge_cutoff: "2025-01",
+    },
+    KnownModelSpec {
+        profile_id: GEMINI_PROFILE_ID,
+        model: "gemini-3-flash-preview",
+        context_window_size: 1_048_576,
+        supports_parallel_tool_calls: true,
+        knowledge_cutoff: "2025-01",
+    },
+];
+
+/// Looks up the built-in capability facts for a model under a profile id.
+pub fn known_model_spec(profile_id: &str, model: &str) -> Option<&'static KnownModelSpec> {
+    KNOWN_MODELS
+        .iter()
+        .find(|spec| spec.profile_id == profile_id && spec.model == model)
+}
+
+fn require_known_model(
+    profile_id: &str,
+    model: &str,
+) -> Result<&'static KnownModelSpec, SessionError> {
+    known_model_spec(profile_id, model).ok_or_else(|| {
+        SessionError::InvalidConfiguration(format!(
+            "unknown model '{}' for {} profile; use with_default_tools to accept default capabilities",
+            model, profile_id
+        ))
+    })
+}
+
+impl KnownModelSpec {
+    fn apply_to(&self, capabilities: &mut ProviderCapabilities) {
+        capabilities.context_window_size = self.context_window_size;
+        capabilities.supports_parallel_tool_calls = self.supports_parallel_tool_calls;
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct EnvironmentContext {
     pub working_directory: String,
@@ -65,6 +165,7 @@ pub struct EnvironmentContext {
     pub date_yyyy_mm_dd: String,
     pub model: String,
     pub knowledge_cutoff: Option<String>,
+    pub fields: EnvironmentContextFields,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -88,8 +189,23 @@ pub trait ProviderProfile: Send + Sync {
         project_docs: &[ProjectDocument],
         user_override: Option<&str>,
     ) -> String;
+    /// Renders the `<environment>` section of the system prompt. Override to
+    /// reshape or extend it; `environment.fields` holds the session's toggles.
+    fn render_environment_context(&self, environment: &EnvironmentContext) -> String {
+        format_environment_context_block(environment)
+    }
     fn tools(&self) -> Vec<ToolDefinition> {
-        self.tool_registry().definitions()
+        self.tool_registry()
+            .definitions()
+            .into_iter()
+            .map(|definition| self.adapt_tool_schema(definition))
+            .collect()
+    }
+    /// Rewrites a tool definition into the shape the provider expects before it
+    /// is sent on `Request.tools`. Dispatch validation keeps using the registry
+    /// definition.
+    fn adapt_tool_schema(&self, definition: ToolDefinition) -> ToolDefinition {
+        definition
     }
     fn provider_options(&self) -> Option<Value> {
         None
@@ -98,6 +214,27 @@ pub trait ProviderProfile: Send + Sync {
     fn knowledge_cutoff(&self) -> Option<&str> {
         None
     }
+    /// Tool round cap used when `SessionConfig::max_tool_rounds_per_input` is `0`.
+    fn suggested_max_tool_rounds_per_input(&self) -> usize {
+        DEFAULT_MAX_TOOL_ROUNDS_PER_INPUT
+    }
+    /// Whether a round's tool results should be sent as one grouped tool
+    /// message instead of one message per result.
+    fn group_tool_results(&self) -> bool {
+        false
+    }
+    /// Rough token count of `request`, used to reject oversized requests
+    /// before they are sent. Defaults to four characters per token over the
+    /// serialized messages and tool definitions.
+    fn estimate_request_tokens(&self, request: &Request) -> usize {
+        let message_chars = serde_json::to_string(&request.messages).map_or(0, |json| json.len());
+        let tool_chars = request
+            .tools
+            .as_ref()
+            .and_then(|tools| serde_json::to_string(tools).ok())
+            .map_or(0, |json| json.len());
+        (message_chars + tool_chars) / 4
+    }
 }
 
 #[derive(Clone)]
@@ -110,6 +247,41 @@ pub struct StaticProviderProfile {
     pub capabilities: ProviderCapabilities,
 }
 
+impl StaticProviderProfile {
+    /// Builds a profile with an empty base prompt, no tools, no provider
+    /// options, and default capabilities.
+    pub fn minimal(id: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            model: model.into(),
+            base_system_prompt: String::new(),
+            tool_registry: Arc::new(ToolRegistry::default()),
+            provider_options: None,
+            capabilities: ProviderCapabilities::default(),
+        }
+    }
+
+    pub fn with_base_system_prompt(mut self, base_system_prompt: impl Into<String>) -> Self {
+        self.base_system_prompt = base_system_prompt.into();
+        self
+    }
+
+    pub fn with_tool_registry(mut self, tool_registry: Arc<ToolRegistry>) -> Self {
+        self.tool_registry = tool_registry;
+        self
+    }
+
+    pub fn with_provider_options(mut self, provider_options: Value) -> Self {
+        self.provider_options = Some(provider_options);
+        self
+    }
+
+    pub fn with_capabilities(mut self, capabilities: ProviderCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+}
+
 impl ProviderProfile for StaticProviderProfile {
     fn id(&self) -> &str {
         &self.id
@@ -136,7 +308,7 @@ impl ProviderProfile for StaticProviderProfile {
     ) -> String {
         build_layered_system_prompt(
             self.base_instructions(),
-            environment,
+            &self.render_environment_context(environment),
             tools,
             project_docs,
             user_override,
@@ -171,6 +343,17 @@ impl OpenAiProviderProfile {
         Self::new(model, Arc::new(build_openai_tool_registry()))
     }
 
+    /// Builds a profile with default tools and capabilities taken from the
+    /// built-in model table. Unknown models are rejected.
+    pub fn for_model(model: impl Into<String>) -> Result<Self, SessionError> {
+        let model = model.into();
+        let spec = require_known_model(OPENAI_PROFILE_ID, &model)?;
+        let mut profile = Self::with_default_tools(model);
+        spec.apply_to(&mut profile.capabilities);
+        profile.knowledge_cutoff = Some(spec.knowledge_cutoff.to_string());
+        Ok(profile)
+    }
+
     pub fn new(model: impl Into<String>, tool_registry: Arc<ToolRegistry>) -> Self {
         Self {
             model: model.into(),
@@ -179,6 +362,7 @@ impl OpenAiProviderProfile {
             capabilities: ProviderCapabilities {
                 supports_parallel_tool_calls: true,
                 context_window_size: 200_000,
+                supports_vision: true,
                 ..ProviderCapabilities::default()
             },
             base_instructions: DEFAULT_OPENAI_INSTRUCTIONS.to_string(),
@@ -233,7 +417,7 @@ impl ProviderProfile for OpenAiProviderProfile {
     ) -> String {
         build_layered_system_prompt(
             self.base_instructions(),
-            environment,
+            &self.render_environment_context(environment),
             tools,
             project_docs,
             user_override,
@@ -252,6 +436,13 @@ impl ProviderProfile for OpenAiProviderProfile {
         self.knowledge_cutoff.as_deref()
     }
 
+    fn adapt_tool_schema(&self, definition: ToolDefinition) -> ToolDefinition {
+        ToolDefinition {
+            parameters: strict_tool_schema(definition.parameters),
+            ..definition
+        }
+    }
+
     fn project_instruction_files(&self) -> Vec<String> {
         vec![
             "AGENTS.md".to_string(),
@@ -275,6 +466,17 @@ impl AnthropicProviderProfile {
         Self::new(model, Arc::new(build_anthropic_tool_registry()))
     }
 
+    /// Builds a profile with default tools and capabilities taken from the
+    /// built-in model table. Unknown models are rejected.
+    pub fn for_model(model: impl Into<String>) -> Result<Self, SessionError> {
+        let model = model.into();
+        let spec = require_known_model(ANTHROPIC_PROFILE_ID, &model)?;
+        let mut profile = Self::with_default_tools(model);
+        spec.apply_to(&mut profile.capabilities);
+        profile.knowledge_cutoff = Some(spec.knowledge_cutoff.to_string());
+        Ok(profile)
+    }
+
     pub fn new(model: impl Into<String>, tool_registry: Arc<ToolRegistry>) -> Self {
         Self {
             model: model.into(),
@@ -283,6 +485,8 @@ impl AnthropicProviderProfile {
             capabilities: ProviderCapabilities {
                 supports_parallel_tool_calls: true,
                 context_window_size: 200_000,
+                supports_prompt_caching: true,
+                supports_vision: true,
                 ..ProviderCapabilities::default()
             },
             base_instructions: DEFAULT_ANTHROPIC_INSTRUCTIONS.to_string(),
@@ -337,7 +541,7 @@ impl ProviderProfile for AnthropicProviderProfile {
     ) -> String {
         build_layered_system_prompt(
             self.base_instructions(),
-            environment,
+            &self.render_environment_context(environment),
             tools,
             project_docs,
             user_override,
@@ -345,7 +549,10 @@ impl ProviderProfile for AnthropicProviderProfile {
     }
 
     fn provider_options(&self) -> Option<Value> {
-        self.provider_options.clone()
+        Some(with_anthropic_auto_cache(
+            self.provider_options.clone(),
+            self.capabilities.supports_prompt_caching,
+        ))
     }
 
     fn capabilities(&self) -> ProviderCapabilities {
@@ -359,6 +566,10 @@ impl ProviderProfile for AnthropicProviderProfile {
     fn project_instruction_files(&self) -> Vec<String> {
         vec!["AGENTS.md".to_string(), "CLAUDE.md".to_string()]
     }
+
+    fn group_tool_results(&self) -> bool {
+        true
+    }
 }
 
 #[derive(Clone)]
@@ -376,6 +587,17 @@ impl GeminiProviderProfile {
         Self::new(model, Arc::new(build_gemini_tool_registry()))
     }
 
+    /// Builds a profile with default tools and capabilities taken from the
+    /// built-in model table. Unknown models are rejected.
+    pub fn for_model(model: impl Into<String>) -> Result<Self, SessionError> {
+        let model = model.into();
+        let spec = require_known_model(GEMINI_PROFILE_ID, &model)?;
+        let mut profile = Self::with_default_tools(model);
+        spec.apply_to(&mut profile.capabilities);
+        profile.knowledge_cutoff = Some(spec.knowledge_cutoff.to_string());
+        Ok(profile)
+    }
+
     pub fn new(model: impl Into<String>, tool_registry: Arc<ToolRegistry>) -> Self {
         Self {
             model: model.into(),
@@ -384,6 +606,7 @@ impl GeminiProviderProfile {
             capabilities: ProviderCapabilities {
                 supports_parallel_tool_calls: true,
                 context_window_size: 1_000_000,
+                supports_vision: true,
                 ..ProviderCapabilities::default()
             },
             base_instructions: DEFAULT_GEMINI_INSTRUCTIONS.to_string(),
@@ -438,7 +661,7 @@ impl ProviderProfile for GeminiProviderProfile {
     ) -> String {
         build_layered_system_prompt(
             self.base_instructions(),
-            environment,
+            &self.render_environment_context(environment),
             tools,
             project_docs,
             user_override,
@@ -462,6 +685,78 @@ impl ProviderProfile for GeminiProviderProfile {
     }
 }
 
+/// Converts a JSON schema into OpenAI strict function-calling form: every
+/// object closes `additionalProperties`, lists all properties as required, and
+/// previously optional properties accept `null` instead.
+pub fn strict_tool_schema(schema: Value) -> Value {
+    let Value::Object(mut object) = schema else {
+        return schema;
+    };
+
+    if let Some(items) = object.remove("items") {
+        object.insert("items".to_string(), strict_tool_schema(items));
+    }
+
+    let Some(Value::Object(properties)) = object.remove("properties") else {
+        return Value::Object(object);
+    };
+    let previously_required: Vec<String> = object
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| {
+            required
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut strict_properties = serde_json::Map::new();
+    for (name, property) in properties {
+        let mut property = strict_tool_schema(property);
+        if !previously_required.contains(&name) {
+            property = nullable_schema(property);
+        }
+        strict_properties.insert(name, property);
+    }
+
+    let required = strict_properties
+        .keys()
+        .cloned()
+        .map(Value::String)
+        .collect::<Vec<_>>();
+    object.insert("properties".to_string(), Value::Object(strict_properties));
+    object.insert("required".to_string(), Value::Array(required));
+    object.insert("additionalProperties".to_string(), Value::Bool(false));
+    Value::Object(object)
+}
+
+fn nullable_schema(schema: Value) -> Value {
+    let Value::Object(mut object) = schema else {
+        return schema;
+    };
+    match object.remove("type") {
+        Some(Value::String(type_name)) if type_name != "null" => {
+            object.insert(
+                "type".to_string(),
+                Value::Array(vec![Value::String(type_name), Value::from("null")]),
+            );
+        }
+        Some(Value::Array(mut types)) => {
+            if !types.iter().any(|entry| entry == "null") {
+                types.push(Value::from("null"));
+            }
+            object.insert("type".to_string(), Value::Array(types));
+        }
+        Some(other) => {
+            object.insert("type".to_string(), other);
+        }
+        None => {}
+    }
+    Value::Object(object)
+}
+
 pub fn default_project_instruction_files_for_profile(profile_id: &str) -> Vec<String> {
     let mut files = vec!["AGENTS.md".to_string()];
     match profile_id {
@@ -473,9 +768,12 @@ pub fn default_project_instruction_files_for_profile(profile_id: &str) -> Vec<St
     files
 }
 
+/// Joins the system prompt layers. `environment_block` is the already
+/// rendered `<environment>` section, normally
+/// [`ProviderProfile::render_environment_context`], so profiles can reshape it.
 pub fn build_layered_system_prompt(
     base_instructions: &str,
-    environment: &EnvironmentContext,
+    environment_block: &str,
     tools: &[ToolDefinition],
     project_docs: &[ProjectDocument],
     user_override: Option<&str>,
@@ -485,7 +783,7 @@ pub fn build_layered_system_prompt(
             "## Provider Base Instructions\n{}",
             base_instructions.trim()
         ),
-        format_environment_context_block(environment),
+        environment_block.to_string(),
         format_tool_descriptions_block(tools),
         format_project_docs_block(project_docs),
     ];
@@ -503,47 +801,71 @@ pub fn build_layered_system_prompt(
     layers.join("\n\n")
 }
 
-fn format_environment_context_block(environment: &EnvironmentContext) -> String {
-    let repository_root = environment
-        .repository_root
-        .as_deref()
-        .unwrap_or("n/a")
-        .to_string();
-    let git_branch = environment
-        .git_branch
-        .as_deref()
-        .unwrap_or("n/a")
-        .to_string();
-    let git_status_summary = environment
-        .git_status_summary
-        .as_deref()
-        .unwrap_or("n/a")
-        .to_string();
-    let knowledge_cutoff = environment
-        .knowledge_cutoff
-        .as_deref()
-        .unwrap_or("unknown")
-        .to_string();
-    let commits = if environment.git_recent_commits.is_empty() {
-        "none".to_string()
-    } else {
-        environment.git_recent_commits.join(" | ")
-    };
+/// Default `<environment>` section, honoring `environment.fields`.
+pub fn format_environment_context_block(environment: &EnvironmentContext) -> String {
+    let fields = &environment.fields;
+    let mut lines = vec![
+        "<environment>".to_string(),
+        format!("Working directory: {}", environment.working_directory),
+    ];
+    if fields.git_details {
+        lines.push(format!(
+            "Repository root: {}",
+            environment.repository_root.as_deref().unwrap_or("n/a")
+        ));
+        lines.push(format!(
+            "Is git repository: {}",
+            environment.is_git_repository
+        ));
+        let commits = if environment.git_recent_commits.is_empty() {
+            "none".to_string()
+        } else {
+            environment.git_recent_commits.join(" | ")
+        };
+        lines.push(format!(
+            "Git branch: {}",
+            environment.git_branch.as_deref().unwrap_or("n/a")
+        ));
+        lines.push(format!(
+            "Git status summary: {}",
+            environment.git_status_summary.as_deref().unwrap_or("n/a")
+        ));
+        lines.push(format!("Recent commits: {}", commits));
+    }
+    lines.push(format!("Platform: {}", environment.platform));
+    if fields.os_version {
+        lines.push(format!("OS version: {}", environment.os_version));
+    }
+    lines.push(format!("Today's date: {}", environment.date_yyyy_mm_dd));
+    lines.push(format!("Model: {}", environment.model));
+    lines.push(format!(
+        "Knowledge cutoff: {}",
+        environment.knowledge_cutoff.as_deref().unwrap_or("unknown")
+    ));
+    for (key, value) in &fields.custom {
+        lines.push(format!("{}: {}", key, value));
+    }
+    lines.push("</environment>".to_string());
+    lines.join("\n")
+}
 
-    format!(
-        "<environment>\nWorking directory: {}\nRepository root: {}\nIs git repository: {}\nGit branch: {}\nGit status summary: {}\nRecent commits: {}\nPlatform: {}\nOS version: {}\nToday's date: {}\nModel: {}\nKnowledge cutoff: {}\n</environment>",
-        environment.working_directory,
-        repository_root,
-        environment.is_git_repository,
-        git_branch,
-        git_status_summary,
-        commits,
-        environment.platform,
-        environment.os_version,
-        environment.date_yyyy_mm_dd,
-        environment.model,
-        knowledge_cutoff
-    )
+// The Anthropic adapter places cache-control markers on the system prompt,
+// last tool definition and first message unless `auto_cache` is false, so the
+// capability is always written out rather than left to the adapter default.
+// An explicit caller setting wins.
+fn with_anthropic_auto_cache(provider_options: Option<Value>, enabled: bool) -> Value {
+    let mut options = provider_options.unwrap_or_else(|| Value::Object(Default::default()));
+    if let Some(root) = options.as_object_mut() {
+        let anthropic = root
+            .entry(ANTHROPIC_PROFILE_ID)
+            .or_insert_with(|| Value::Object(Default::default()));
+        if let Some(anthropic) = anthropic.as_object_mut() {
+            anthropic
+                .entry("auto_cache")
+                .or_insert(Value::Bool(enabled));
+        }
+    }
+    options
 }
 
 fn format_tool_descriptions_block(tools: &[ToolDefinition]) -> String {
@@ -559,6 +881,22 @@ fn format_tool_descriptions_block(tools: &[ToolDefinition]) -> String {
     lines.join("\n")
 }
 
+/// Prompt layer standing in for `Request.tools` when the model cannot take
+/// native tool definitions.
+pub(crate) fn format_text_only_tools_block(tools: &[ToolDefinition]) -> String {
+    let mut lines = vec![
+        "## Tools Unavailable for Direct Calls".to_string(),
+        "This model cannot issue tool calls. The tools below exist in this environment; describe the call you would make in plain text instead.".to_string(),
+    ];
+    for tool in tools {
+        lines.push(format!(
+            "- {}: {}\n  parameters: {}",
+            tool.name, tool.description, tool.parameters
+        ));
+    }
+    lines.join("\n")
+}
+
 fn format_project_docs_block(project_docs: &[ProjectDocument]) -> String {
     if project_docs.is_empty() {
         return "<project_instructions>\n(none)\n</project_instructions>".to_string();
@@ -576,7 +914,7 @@ fn format_project_docs_block(project_docs: &[ProjectDocument]) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{RegisteredTool, ToolExecutor};
+    use crate::{RegisteredTool, ToolExecutor, ToolMetadata};
     use serde_json::json;
 
     fn dummy_environment() -> EnvironmentContext {
@@ -595,6 +933,7 @@ mod tests {
             date_yyyy_mm_dd: "2026-02-09".to_string(),
             model: "gpt-5.2-codex".to_string(),
             knowledge_cutoff: Some("2024-10".to_string()),
+            fields: EnvironmentContextFields::default(),
         }
     }
 
@@ -645,6 +984,155 @@ mod tests {
         assert!(!gemini_tools.contains(&"apply_patch".to_string()));
     }
 
+    #[test]
+    fn static_profile_minimal_matches_long_form_literal() {
+        let literal = StaticProviderProfile {
+            id: OPENAI_PROFILE_ID.to_string(),
+            model: "gpt-5.2-codex".to_string(),
+            base_system_prompt: "base".to_string(),
+            tool_registry: Arc::new(build_openai_tool_registry()),
+            provider_options: None,
+            capabilities: ProviderCapabilities::default(),
+        };
+        let built = StaticProviderProfile::minimal(OPENAI_PROFILE_ID, "gpt-5.2-codex")
+            .with_base_system_prompt("base")
+            .with_tool_registry(Arc::new(build_openai_tool_registry()));
+
+        assert_eq!(built.id(), literal.id());
+        assert_eq!(built.model(), literal.model());
+        assert_eq!(built.tools(), literal.tools());
+        assert_eq!(built.provider_options(), literal.provider_options());
+        assert_eq!(built.capabilities(), literal.capabilities());
+        assert_eq!(
+            built.build_system_prompt(&dummy_environment(), &built.tools(), &[], None),
+            literal.build_system_prompt(&dummy_environment(), &literal.tools(), &[], None)
+        );
+    }
+
+    #[test]
+    fn static_profile_minimal_defaults_to_empty_registry() {
+        let profile = StaticProviderProfile::minimal("test", "model");
+        assert!(profile.tools().is_empty());
+        assert!(profile.base_instructions().is_empty());
+        assert_eq!(profile.provider_options(), None);
+        assert_eq!(profile.capabilities(), ProviderCapabilities::default());
+    }
+
+    #[test]
+    fn for_model_known_models_populate_capabilities() {
+        let openai = OpenAiProviderProfile::for_model("gpt-5.2-codex").expect("known model");
+        assert_eq!(openai.capabilities().context_window_size, 1_047_576);
+        assert!(openai.capabilities().supports_parallel_tool_calls);
+        assert_eq!(openai.knowledge_cutoff(), Some("2025-08"));
+
+        let anthropic =
+            AnthropicProviderProfile::for_model("claude-sonnet-4-5").expect("known model");
+        assert_eq!(anthropic.capabilities().context_window_size, 200_000);
+        assert_eq!(anthropic.knowledge_cutoff(), Some("2025-01"));
+
+        let gemini = GeminiProviderProfile::for_model("gemini-3-pro-preview").expect("known model");
+        assert_eq!(gemini.capabilities().context_window_size, 1_048_576);
+        assert!(gemini.knowledge_cutoff().is_some());
+    }
+
+    #[test]
+    fn for_model_unknown_model_returns_invalid_configuration() {
+        let error = OpenAiProviderProfile::for_model("gpt-unknown")
+            .err()
+            .expect("unknown model should fail");
+        assert!(matches!(error, SessionError::InvalidConfiguration(_)));
+        assert!(AnthropicProviderProfile::for_model("gpt-5.2").is_err());
+    }
+
+    #[test]
+    fn known_model_table_matches_llm_catalog_context_windows() {
+        for spec in KNOWN_MODELS {
+            assert!(
+                spec.context_window_size > 0,
+                "{} has zero window",
+                spec.model
+            );
+            let info = forge_llm::get_model_info(spec.model).expect("model in llm catalog");
+            assert_eq!(info.context_window as usize, spec.context_window_size);
+            assert_eq!(info.provider, spec.profile_id);
+        }
+    }
+
+    #[test]
+    fn openai_tools_use_strict_schemas() {
+        let profile = OpenAiProviderProfile::with_default_tools("gpt-5.2-codex");
+        let tools = profile.tools();
+        assert!(!tools.is_empty());
+        for tool in &tools {
+            assert_eq!(
+                tool.parameters.get("additionalProperties"),
+                Some(&json!(false)),
+                "{} should close additionalProperties",
+                tool.name
+            );
+            let properties = tool.parameters["properties"]
+                .as_object()
+                .expect("object schema");
+            let required = tool.parameters["required"]
+                .as_array()
+                .expect("required array");
+            assert_eq!(required.len(), properties.len(), "{}", tool.name);
+        }
+
+        let read_file = tools
+            .iter()
+            .find(|tool| tool.name == "read_file")
+            .expect("read_file tool");
+        assert_eq!(
+            read_file.parameters["properties"]["offset"]["type"],
+            json!(["integer", "null"])
+        );
+        assert_eq!(
+            read_file.parameters["properties"]["file_path"]["type"],
+            json!("string")
+        );
+    }
+
+    #[test]
+    fn anthropic_tools_keep_registry_schemas() {
+        let profile = AnthropicProviderProfile::with_default_tools("claude-sonnet-4-5");
+        assert_eq!(profile.tools(), profile.tool_registry().definitions());
+
+        let read_file = profile
+            .tools()
+            .into_iter()
+            .find(|tool| tool.name == "read_file")
+            .expect("read_file tool");
+        assert_eq!(read_file.parameters["required"], json!(["file_path"]));
+        assert_eq!(
+            read_file.parameters["properties"]["offset"]["type"],
+            json!("integer")
+        );
+    }
+
+    #[test]
+    fn strict_tool_schema_recurses_into_nested_objects() {
+        let schema = strict_tool_schema(json!({
+            "type": "object",
+            "properties": {
+                "items": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": { "name": { "type": "string" } }
+                    }
+                }
+            }
+        }));
+        let nested = &schema["properties"]["items"]["items"];
+        assert_eq!(nested["additionalProperties"], json!(false));
+        assert_eq!(nested["required"], json!(["name"]));
+        assert_eq!(
+            schema["properties"]["items"]["type"],
+            json!(["array", "null"])
+        );
+    }
+
     #[test]
     fn build_layered_system_prompt_orders_layers_deterministically() {
         let mut registry = ToolRegistry::default();
@@ -656,6 +1144,9 @@ mod tests {
                 parameters: json!({"type":"object"}),
             },
             executor: no_op.clone(),
+            structured_output: false,
+            output_envelope: false,
+            metadata: ToolMetadata::default(),
         });
         registry.register(RegisteredTool {
             definition: ToolDefinition {
@@ -664,6 +1155,9 @@ mod tests {
                 parameters: json!({"type":"object"}),
             },
             executor: no_op,
+            structured_output: false,
+            output_envelope: false,
+            metadata: ToolMetadata::default(),
         });
         let profile = StaticProviderProfile {
             id: OPENAI_PROFILE_ID.to_string(),
@@ -709,4 +1203,87 @@ mod tests {
         let zeta_idx = prompt.find("- zeta: last tool").expect("zeta tool listed");
         assert!(alpha_idx < zeta_idx);
     }
+
+    #[test]
+    fn environment_context_block_omits_git_details_when_disabled() {
+        let mut environment = dummy_environment();
+        let default_block = format_environment_context_block(&environment);
+        assert!(default_block.contains("Git branch: main"));
+        assert!(default_block.contains("Recent commits: abc123 add tests"));
+
+        environment.fields.git_details = false;
+        environment.fields.os_version = false;
+        environment
+            .fields
+            .custom
+            .insert("Team".to_string(), "platform".to_string());
+        let block = format_environment_context_block(&environment);
+
+        assert!(!block.contains("Git branch"));
+        assert!(!block.contains("Git status summary"));
+        assert!(!block.contains("Recent commits"));
+        assert!(!block.contains("OS version"));
+        assert!(!block.contains("Repository root"));
+        assert!(!block.contains("Is git repository"));
+        assert!(block.contains("Working directory: /repo/work"));
+        assert!(block.contains("Team: platform\n</environment>"));
+    }
+
+    #[test]
+    fn render_environment_context_override_replaces_environment_layer() {
+        struct TerseProfile(StaticProviderProfile);
+
+        impl ProviderProfile for TerseProfile {
+            fn id(&self) -> &str {
+                self.0.id()
+            }
+            fn model(&self) -> &str {
+                self.0.model()
+            }
+            fn tool_registry(&self) -> Arc<ToolRegistry> {
+                self.0.tool_registry()
+            }
+            fn base_instructions(&self) -> &str {
+                self.0.base_instructions()
+            }
+            fn build_system_prompt(
+                &self,
+                environment: &EnvironmentContext,
+                tools: &[ToolDefinition],
+                project_docs: &[ProjectDocument],
+                user_override: Option<&str>,
+            ) -> String {
+                build_layered_system_prompt(
+                    self.base_instructions(),
+                    &self.render_environment_context(environment),
+                    tools,
+                    project_docs,
+                    user_override,
+                )
+            }
+            fn render_environment_context(&self, environment: &EnvironmentContext) -> String {
+                format!(
+                    "<environment>\nPlatform: {}\n</environment>",
+                    environment.platform
+                )
+            }
+            fn capabilities(&self) -> ProviderCapabilities {
+                self.0.capabilities()
+            }
+        }
+
+        let profile = TerseProfile(StaticProviderProfile {
+            id: OPENAI_PROFILE_ID.to_string(),
+            model: "gpt-5.2-codex".to_string(),
+            base_system_prompt: "Base prompt".to_string(),
+            tool_registry: Arc::new(ToolRegistry::default()),
+            provider_options: None,
+            capabilities: ProviderCapabilities::default(),
+        });
+
+        let prompt = profile.build_system_prompt(&dummy_environment(), &[], &[], None);
+
+        assert!(prompt.contains("<environment>\nPlatform: linux\n</environment>"));
+        assert!(!prompt.contains("Working directory"));
+    }
 }
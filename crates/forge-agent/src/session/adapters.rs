@@ -51,10 +51,21 @@ impl ProviderProfile for ModelOverrideProviderProfile {
             .build_system_prompt(environment, tools, project_docs, user_override)
     }
 
+    fn render_environment_context(&self, environment: &EnvironmentContext) -> String {
+        self.inner.render_environment_context(environment)
+    }
+
     fn tools(&self) -> Vec<forge_llm::ToolDefinition> {
         self.inner.tools()
     }
 
+    fn adapt_tool_schema(
+        &self,
+        definition: forge_llm::ToolDefinition,
+    ) -> forge_llm::ToolDefinition {
+        self.inner.adapt_tool_schema(definition)
+    }
+
     fn provider_options(&self) -> Option<Value> {
         self.inner.provider_options()
     }
@@ -66,6 +77,18 @@ impl ProviderProfile for ModelOverrideProviderProfile {
     fn knowledge_cutoff(&self) -> Option<&str> {
         self.inner.knowledge_cutoff()
     }
+
+    fn suggested_max_tool_rounds_per_input(&self) -> usize {
+        self.inner.suggested_max_tool_rounds_per_input()
+    }
+
+    fn group_tool_results(&self) -> bool {
+        self.inner.group_tool_results()
+    }
+
+    fn estimate_request_tokens(&self, request: &forge_llm::Request) -> usize {
+        self.inner.estimate_request_tokens(request)
+    }
 }
 
 #[derive(Clone)]
@@ -115,6 +138,18 @@ impl crate::ExecutionEnvironment for ScopedExecutionEnvironment {
             .await
     }
 
+    async fn read_file_with_encoding(
+        &self,
+        path: &str,
+        offset: Option<usize>,
+        limit: Option<usize>,
+        encoding: &str,
+    ) -> Result<String, AgentError> {
+        self.inner
+            .read_file_with_encoding(&self.resolve_path(path), offset, limit, encoding)
+            .await
+    }
+
     async fn write_file(&self, path: &str, content: &str) -> Result<(), AgentError> {
         self.inner
             .write_file(&self.resolve_path(path), content)
@@ -135,6 +170,22 @@ impl crate::ExecutionEnvironment for ScopedExecutionEnvironment {
         self.inner.file_exists(&self.resolve_path(path)).await
     }
 
+    async fn is_directory(&self, path: &str) -> Result<bool, AgentError> {
+        self.inner.is_directory(&self.resolve_path(path)).await
+    }
+
+    async fn delete_directory(&self, path: &str, recursive: bool) -> Result<(), AgentError> {
+        self.inner
+            .delete_directory(&self.resolve_path(path), recursive)
+            .await
+    }
+
+    async fn create_directory(&self, path: &str, recursive: bool) -> Result<(), AgentError> {
+        self.inner
+            .create_directory(&self.resolve_path(path), recursive)
+            .await
+    }
+
     async fn list_directory(
         &self,
         path: &str,
@@ -160,6 +211,28 @@ impl crate::ExecutionEnvironment for ScopedExecutionEnvironment {
             .await
     }
 
+    async fn exec_command_with_output_limit(
+        &self,
+        command: &str,
+        timeout_ms: u64,
+        working_dir: Option<&str>,
+        env_vars: Option<HashMap<String, String>>,
+        max_output_bytes: usize,
+    ) -> Result<crate::ExecResult, AgentError> {
+        let effective_working_dir = working_dir
+            .map(|path| self.resolve_path(path))
+            .unwrap_or_else(|| self.scoped_working_directory.to_string_lossy().to_string());
+        self.inner
+            .exec_command_with_output_limit(
+                command,
+                timeout_ms,
+                Some(&effective_working_dir),
+                env_vars,
+                max_output_bytes,
+            )
+            .await
+    }
+
     async fn grep(
         &self,
         pattern: &str,
@@ -175,6 +248,21 @@ impl crate::ExecutionEnvironment for ScopedExecutionEnvironment {
         self.inner.glob(pattern, &self.resolve_path(path)).await
     }
 
+    async fn glob_with_type(
+        &self,
+        pattern: &str,
+        path: &str,
+        entry_type: Option<crate::EntryType>,
+    ) -> Result<Vec<String>, AgentError> {
+        self.inner
+            .glob_with_type(pattern, &self.resolve_path(path), entry_type)
+            .await
+    }
+
+    async fn temp_dir(&self) -> Result<PathBuf, AgentError> {
+        self.inner.temp_dir().await
+    }
+
     async fn initialize(&self) -> Result<(), AgentError> {
         self.inner.initialize().await
     }
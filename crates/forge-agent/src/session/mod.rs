@@ -1,16 +1,18 @@
 use crate::{
-    AgentError, AssistantTurn, CxdbPersistenceMode, EnvironmentContext, EventData, EventEmitter,
-    EventKind, EventStream, ExecutionEnvironment, NoopEventEmitter, ProjectDocument,
-    ProviderProfile, SessionConfig, SessionError, SessionEvent, SteeringTurn, ToolCallHook,
-    ToolDispatchOptions, ToolError, ToolResultTurn, ToolResultsTurn, Turn, UserTurn,
-    truncate_tool_output,
+    AGENT_STATUS_TOOL, AgentError, AssistantTurn, Attachment, Clock, CxdbPersistenceMode,
+    EditBackups, EmptyResponsePolicy, EnvironmentContext, EventData, EventEmitter, EventKind,
+    EventStream, ExecutionEnvironment, FsSnapshotCadence, NoopEventEmitter, ProjectDocument,
+    ProviderProfile, RecoveredErrorKind, RunDiagnostics, SequencedEventEmitter, SessionConfig,
+    SessionError, SessionEvent, SteeringTurn, SubagentExecution, SystemClock, ToolCallHook,
+    ToolDispatchOptions, ToolError, ToolResultTurn, ToolResultsTurn, Turn, UNDO_EDIT_TOOL,
+    UserTurn, truncate_tool_output,
 };
 use forge_cxdb_runtime::{
-    CxdbAppendTurnRequest, CxdbBinaryClient, CxdbClientError, CxdbFsSnapshotCapture,
-    CxdbFsSnapshotPolicy, CxdbHttpClient, CxdbRuntimeStore, CxdbStoreContext, CxdbStoredTurn,
-    CxdbStoredTurnRef, CxdbTurnId,
+    CxdbAppendTurnRequest, CxdbAttachReconcileReport, CxdbBinaryClient, CxdbClientError,
+    CxdbFsSnapshotCapture, CxdbFsSnapshotPolicy, CxdbHttpClient, CxdbRuntimeStore,
+    CxdbStoreContext, CxdbStoredTurn, CxdbStoredTurnRef, CxdbTurnId,
 };
-use forge_llm::{Client, Message, Request, ToolCall, ToolChoice, ToolResult, Usage};
+use forge_llm::{Client, Message, Request, ToolCall, ToolChoice, ToolResult, Usage, retry_async};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use serde_json::Value;
@@ -32,8 +34,8 @@ mod runner;
 mod subagents;
 mod types;
 pub use types::{
-    SessionCheckpoint, SessionPersistenceSnapshot, SessionState, SubAgentHandle, SubAgentResult,
-    SubAgentStatus, SubmitOptions, SubmitResult,
+    SessionCheckpoint, SessionPersistenceSnapshot, SessionState, StopReason, SubAgentHandle,
+    SubAgentResult, SubAgentStatus, SubmitOptions, SubmitResult,
 };
 use types::{SubAgentRecord, SubAgentTaskOutput};
 
@@ -72,6 +74,29 @@ pub trait SessionPersistenceWriter: Send + Sync {
             "attach_fs is not supported by this persistence writer".to_string(),
         ))
     }
+
+    /// Captures the workspace and attaches it to `turn_id`. Journaling
+    /// writers record the attach before uploading anything.
+    async fn capture_upload_attach(
+        &self,
+        turn_id: &CxdbTurnId,
+        workspace_root: &Path,
+        policy: &CxdbFsSnapshotPolicy,
+    ) -> Result<CxdbFsSnapshotCapture, CxdbClientError> {
+        let capture = self
+            .capture_upload_workspace(workspace_root, policy)
+            .await?;
+        self.attach_fs(turn_id, &capture.fs_root_hash).await?;
+        Ok(capture)
+    }
+
+    /// Finishes or drops attaches a previous process journaled but never
+    /// confirmed. Writers without a journal have nothing to reconcile.
+    async fn reconcile_pending_attaches(
+        &self,
+    ) -> Result<CxdbAttachReconcileReport, CxdbClientError> {
+        Ok(CxdbAttachReconcileReport::default())
+    }
 }
 
 #[async_trait::async_trait]
@@ -113,15 +138,50 @@ where
     ) -> Result<(), CxdbClientError> {
         CxdbRuntimeStore::attach_fs(self, turn_id, fs_root_hash).await
     }
+
+    async fn capture_upload_attach(
+        &self,
+        turn_id: &CxdbTurnId,
+        workspace_root: &Path,
+        policy: &CxdbFsSnapshotPolicy,
+    ) -> Result<CxdbFsSnapshotCapture, CxdbClientError> {
+        CxdbRuntimeStore::capture_upload_attach(self, turn_id, workspace_root, policy).await
+    }
+
+    async fn reconcile_pending_attaches(
+        &self,
+    ) -> Result<CxdbAttachReconcileReport, CxdbClientError> {
+        CxdbRuntimeStore::reconcile_pending_attaches(self).await
+    }
+}
+
+/// Last-chance hook over the outgoing LLM `Request`.
+///
+/// Runs after `build_request` has resolved every precedence rule (submit
+/// options, session config, provider profile), so whatever the interceptor
+/// writes is exactly what the provider receives.
+pub trait RequestInterceptor: Send + Sync {
+    fn intercept(&self, request: &mut Request);
+}
+
+impl<F> RequestInterceptor for F
+where
+    F: Fn(&mut Request) + Send + Sync,
+{
+    fn intercept(&self, request: &mut Request) {
+        self(request)
+    }
 }
 
 pub struct Session {
     id: String,
     provider_profile: Arc<dyn ProviderProfile>,
     provider_profiles: HashMap<String, Arc<dyn ProviderProfile>>,
+    subagent_profiles: HashMap<String, Arc<dyn ProviderProfile>>,
     execution_env: Arc<dyn ExecutionEnvironment>,
+    execution_env_initialized: bool,
     history: Vec<Turn>,
-    event_emitter: Arc<dyn EventEmitter>,
+    event_emitter: Arc<SequencedEventEmitter>,
     config: SessionConfig,
     state: SessionState,
     llm_client: Arc<Client>,
@@ -133,12 +193,27 @@ pub struct Session {
     abort_requested: Arc<AtomicBool>,
     abort_notify: Arc<Notify>,
     tool_call_hook: Option<Arc<dyn ToolCallHook>>,
+    clock: Arc<dyn Clock>,
+    request_interceptor: Option<Arc<dyn RequestInterceptor>>,
+    run_diagnostics: Option<Arc<RunDiagnostics>>,
+    edit_backups: Option<Arc<EditBackups>>,
     thread_key: Option<String>,
     persistence_writer: Option<Arc<dyn SessionPersistenceWriter>>,
     persistence_context_id: Option<String>,
     persistence_parent_turn_id: Option<String>,
     persistence_sequence_no: u64,
     persistence_mode: CxdbPersistenceMode,
+    buffered_appender: Option<BufferedAppender>,
+    /// First buffered append failure; later appends are rejected until
+    /// `reset_buffered_persistence`.
+    buffered_append_error: Option<String>,
+    pending_session_events: VecDeque<PendingSessionEvent>,
+    fs_snapshot_rounds: usize,
+    session_tokens_used: u64,
+    /// `total_tokens` of the latest agent-loop model call; projects the next.
+    last_call_tokens: u64,
+    tool_rounds_used: usize,
+    paused_from: Option<SessionState>,
 }
 
 #[derive(Clone)]
@@ -195,8 +270,26 @@ impl Session {
         binary_client: Arc<dyn CxdbBinaryClient>,
         http_client: Arc<dyn CxdbHttpClient>,
     ) -> Result<Self, AgentError> {
-        let runtime_store = Arc::new(CxdbRuntimeStore::new(binary_client, http_client));
-        if config.cxdb_persistence == CxdbPersistenceMode::Required {
+        Self::new_with_cxdb_store(
+            provider_profile,
+            execution_env,
+            llm_client,
+            config,
+            Arc::new(CxdbRuntimeStore::new(binary_client, http_client)),
+        )
+    }
+
+    /// Like [`new_with_cxdb_persistence`](Self::new_with_cxdb_persistence),
+    /// for callers that configure the store themselves (e.g. with an attach
+    /// journal).
+    pub fn new_with_cxdb_store(
+        provider_profile: Arc<dyn ProviderProfile>,
+        execution_env: Arc<dyn ExecutionEnvironment>,
+        llm_client: Arc<Client>,
+        config: SessionConfig,
+        runtime_store: Arc<CxdbRuntimeStore<Arc<dyn CxdbBinaryClient>, Arc<dyn CxdbHttpClient>>>,
+    ) -> Result<Self, AgentError> {
+        if config.cxdb_persistence != CxdbPersistenceMode::Off {
             publish_agent_registry_bundle_blocking(runtime_store.clone())?;
         }
         let store: Arc<dyn SessionPersistenceWriter> = runtime_store;
@@ -242,6 +335,7 @@ impl Session {
             event_emitter,
             persistence_writer,
             0,
+            Arc::new(SystemClock),
         )
     }
 
@@ -253,25 +347,29 @@ impl Session {
         event_emitter: Arc<dyn EventEmitter>,
         persistence_writer: Option<Arc<dyn SessionPersistenceWriter>>,
         subagent_depth: usize,
+        clock: Arc<dyn Clock>,
     ) -> Result<Self, AgentError> {
         let persistence_mode = config.cxdb_persistence;
-        if persistence_mode == CxdbPersistenceMode::Required && persistence_writer.is_none() {
+        if persistence_mode != CxdbPersistenceMode::Off && persistence_writer.is_none() {
             return Err(SessionError::InvalidConfiguration(
-                "cxdb_persistence=required requires a configured CXDB writer".to_string(),
+                "cxdb_persistence=required/buffered requires a configured CXDB writer".to_string(),
             )
             .into());
         }
         let thread_key = config.thread_key.clone();
+        let edit_backups = EditBackups::for_config(&config);
         let mut session = Self {
-            id: Uuid::new_v4().to_string(),
+            id: resolve_session_id(&config),
             provider_profiles: HashMap::from([(
                 provider_profile.id().to_string(),
                 provider_profile.clone(),
             )]),
+            subagent_profiles: HashMap::new(),
             provider_profile,
             execution_env,
+            execution_env_initialized: false,
             history: Vec::new(),
-            event_emitter,
+            event_emitter: Arc::new(SequencedEventEmitter::new(event_emitter, clock.clone())),
             config,
             state: SessionState::Idle,
             llm_client,
@@ -283,15 +381,29 @@ impl Session {
             abort_requested: Arc::new(AtomicBool::new(false)),
             abort_notify: Arc::new(Notify::new()),
             tool_call_hook: None,
+            clock,
+            request_interceptor: None,
+            run_diagnostics: None,
+            edit_backups,
             thread_key,
             persistence_writer,
             persistence_context_id: None,
             persistence_parent_turn_id: None,
             persistence_sequence_no: 0,
             persistence_mode,
+            buffered_appender: None,
+            buffered_append_error: None,
+            pending_session_events: VecDeque::new(),
+            fs_snapshot_rounds: 0,
+            session_tokens_used: 0,
+            last_call_tokens: 0,
+            tool_rounds_used: 0,
+            paused_from: None,
         };
         session.emit(EventKind::SessionStart, EventData::new())?;
-        session.persist_session_event_blocking("session_start", serde_json::json!({}))?;
+        // Constructors are synchronous; the start record is written at the
+        // first async persistence point instead of blocking here.
+        session.queue_session_event("session_start", None);
         Ok(session)
     }
 
@@ -322,12 +434,34 @@ impl Session {
 
         self.state = next_state;
         if self.state == SessionState::Closed {
+            self.spawn_execution_env_cleanup();
             self.close_all_subagents()?;
             self.emit_session_end()?;
         }
         Ok(())
     }
 
+    /// Best-effort cleanup for synchronous closes; async shutdown paths await
+    /// `cleanup()` themselves before transitioning.
+    fn spawn_execution_env_cleanup(&mut self) {
+        if !std::mem::take(&mut self.execution_env_initialized) || !self.owns_execution_env() {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let execution_env = self.execution_env.clone();
+        runtime.spawn(async move {
+            let _ = execution_env.cleanup().await;
+        });
+    }
+
+    /// Subagents share their parent's environment (directly or through a
+    /// scoped wrapper), so only the root session cleans it up.
+    fn owns_execution_env(&self) -> bool {
+        self.subagent_depth == 0
+    }
+
     pub fn config(&self) -> &SessionConfig {
         &self.config
     }
@@ -341,10 +475,49 @@ impl Session {
             .insert(profile.id().to_string(), profile);
     }
 
+    /// Profile a subagent spawned with `role` runs under instead of the
+    /// parent's. A `model` argument still overrides the profile's model.
+    pub fn register_subagent_profile(
+        &mut self,
+        role: impl Into<String>,
+        profile: Arc<dyn ProviderProfile>,
+    ) {
+        self.subagent_profiles.insert(role.into(), profile);
+    }
+
     pub fn set_tool_call_hook(&mut self, hook: Option<Arc<dyn ToolCallHook>>) {
         self.tool_call_hook = hook;
     }
 
+    /// Replaces the time source for turn, event, diagnostic and persistence
+    /// timestamps. Queued lifecycle records that have not been written yet
+    /// are re-stamped; events already emitted, such as `SessionStart`, keep
+    /// their timestamps.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        for event in &mut self.pending_session_events {
+            event.timestamp = clock.timestamp();
+        }
+        self.event_emitter.set_clock(clock.clone());
+        self.clock = clock;
+    }
+
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    pub fn set_request_interceptor(&mut self, interceptor: Option<Arc<dyn RequestInterceptor>>) {
+        self.request_interceptor = interceptor;
+    }
+
+    /// Attaches a collector for recovered tool and persistence failures.
+    pub fn set_run_diagnostics(&mut self, diagnostics: Option<Arc<RunDiagnostics>>) {
+        self.run_diagnostics = diagnostics;
+    }
+
+    pub fn run_diagnostics(&self) -> Option<Arc<RunDiagnostics>> {
+        self.run_diagnostics.clone()
+    }
+
     pub fn thread_key(&self) -> Option<&str> {
         self.thread_key.as_deref()
     }
@@ -362,14 +535,79 @@ impl Session {
         self.llm_client.clone()
     }
 
+    /// `total_tokens` reported by every model call this session has made.
+    pub fn session_tokens_used(&self) -> u64 {
+        self.session_tokens_used
+    }
+
+    /// Whether a model call of `projected_tokens` would cross
+    /// `max_session_tokens`. Projections are estimates, so a call that grows
+    /// past its projection can still overshoot the budget.
+    fn token_budget_exhausted(&self, projected_tokens: u64) -> bool {
+        let budget = self.config.max_session_tokens;
+        budget > 0
+            && (self.session_tokens_used >= budget
+                || self.session_tokens_used.saturating_add(projected_tokens) > budget)
+    }
+
     pub fn history(&self) -> &[Turn] {
         &self.history
     }
 
+    pub fn assistant_turns(&self) -> impl Iterator<Item = &AssistantTurn> {
+        crate::turn::assistant_turns(&self.history)
+    }
+
+    pub fn tool_results(&self) -> impl Iterator<Item = &ToolResultTurn> {
+        crate::turn::tool_results(&self.history)
+    }
+
+    pub fn find_tool_result(&self, call_id: &str) -> Option<&ToolResultTurn> {
+        crate::turn::find_tool_result(&self.history, call_id)
+    }
+
+    pub fn last_assistant_text(&self) -> Option<&str> {
+        crate::turn::last_assistant_text(&self.history)
+    }
+
     pub fn push_turn(&mut self, turn: Turn) {
         self.history.push(turn);
     }
 
+    /// Appends an existing conversation without calling the provider, e.g. when
+    /// migrating a transcript into a new session. Only an idle session that
+    /// has not been submitted to can be seeded, and the turns must form a
+    /// coherent transcript.
+    pub fn seed_history(&mut self, turns: Vec<Turn>) -> Result<(), SessionError> {
+        if self.state != SessionState::Idle {
+            return Err(SessionError::InvalidHistory(format!(
+                "cannot seed a session in state {}",
+                self.state.as_str()
+            )));
+        }
+        if !self.history.is_empty() {
+            return Err(SessionError::InvalidHistory(
+                "cannot seed a session that already has history".to_string(),
+            ));
+        }
+        crate::turn::validate_turn_sequence(&turns).map_err(SessionError::InvalidHistory)?;
+        self.history = turns;
+        Ok(())
+    }
+
+    /// [`seed_history`](Self::seed_history), then persists the seeded turns as
+    /// a single backfill record rather than as new turns. If the backfill
+    /// cannot be persisted the seeded turns are rolled back.
+    pub async fn seed_history_with_backfill(&mut self, turns: Vec<Turn>) -> Result<(), AgentError> {
+        self.seed_history(turns)?;
+        let seeded = self.history.clone();
+        if let Err(error) = self.persist_history_backfill(&seeded).await {
+            self.history.clear();
+            return Err(error);
+        }
+        Ok(())
+    }
+
     pub fn steer(&mut self, message: impl Into<String>) -> Result<(), AgentError> {
         if self.state == SessionState::Closed {
             return Err(AgentError::session_closed());
@@ -424,6 +662,23 @@ impl Session {
         self.submit(user_input).await
     }
 
+    /// Writes queued session lifecycle records and initializes the execution
+    /// environment once. Submissions call this lazily; environment failures
+    /// surface as `AgentError::Execution`.
+    pub async fn initialize(&mut self) -> Result<(), AgentError> {
+        self.write_pending_session_events().await?;
+        if self.execution_env_initialized {
+            return Ok(());
+        }
+        self.reconcile_pending_attaches().await?;
+        self.execution_env
+            .initialize()
+            .await
+            .map_err(|err| AgentError::Execution(Box::new(err)))?;
+        self.execution_env_initialized = true;
+        Ok(())
+    }
+
     pub async fn submit(&mut self, user_input: impl Into<String>) -> Result<(), AgentError> {
         self.submit_with_options(user_input, SubmitOptions::default())
             .await
@@ -434,18 +689,72 @@ impl Session {
         user_input: impl Into<String>,
         options: SubmitOptions,
     ) -> Result<(), AgentError> {
-        let mut pending_inputs = VecDeque::from([user_input.into()]);
+        self.submit_until_stopped(user_input.into(), &options)
+            .await
+            .map(|_| ())
+    }
+
+    async fn submit_until_stopped(
+        &mut self,
+        user_input: String,
+        options: &SubmitOptions,
+    ) -> Result<StopReason, AgentError> {
+        if !options.attachments.is_empty() {
+            let provider_profile = self.resolve_provider_profile(options.provider.as_deref())?;
+            if !provider_profile.capabilities().supports_vision {
+                return Err(SessionError::AttachmentsUnsupported(
+                    provider_profile.id().to_string(),
+                )
+                .into());
+            }
+        }
+
+        let mut pending_inputs = VecDeque::from([user_input]);
+        let mut attachments = options.attachments.clone();
+        let mut stopped_reason = StopReason::NaturalCompletion;
 
         while let Some(next_input) = pending_inputs.pop_front() {
-            let completed_naturally = self.submit_single(next_input, &options).await?;
-            if completed_naturally {
+            stopped_reason = self
+                .submit_single(next_input, std::mem::take(&mut attachments), options)
+                .await?;
+            if stopped_reason == StopReason::NaturalCompletion {
                 while let Some(follow_up) = self.pop_followup_message() {
                     pending_inputs.push_back(follow_up);
                 }
+            } else if stopped_reason == StopReason::AwaitingInput
+                && self.config.followups_answer_questions
+                && pending_inputs.is_empty()
+                && let Some(answer) = self.pop_followup_message()
+            {
+                pending_inputs.push_back(answer);
             }
         }
 
-        Ok(())
+        Ok(stopped_reason)
+    }
+
+    /// Effective per-input tool round cap. A configured value of `0` defers to
+    /// the provider profile's suggested default.
+    pub fn max_tool_rounds_per_input(&self) -> usize {
+        if self.config.max_tool_rounds_per_input == 0 {
+            self.provider_profile.suggested_max_tool_rounds_per_input()
+        } else {
+            self.config.max_tool_rounds_per_input
+        }
+    }
+
+    /// Output of the read-only `agent_status` tool. Limits of `0` mean
+    /// unlimited, matching `SessionConfig`.
+    fn agent_status_report(&self) -> String {
+        serde_json::json!({
+            "tool_rounds_used": self.tool_rounds_used,
+            "max_tool_rounds_per_input": self.max_tool_rounds_per_input(),
+            "turns_used": self.history.len(),
+            "max_turns": self.config.max_turns,
+            "tokens_used": self.session_tokens_used,
+            "max_session_tokens": self.config.max_session_tokens,
+        })
+        .to_string()
     }
 
     pub async fn submit_with_result(
@@ -454,7 +763,9 @@ impl Session {
         options: SubmitOptions,
     ) -> Result<SubmitResult, AgentError> {
         let baseline_turns = self.history.len();
-        self.submit_with_options(user_input, options).await?;
+        let stopped_reason = self
+            .submit_until_stopped(user_input.into(), &options)
+            .await?;
         let mut assistant_text = String::new();
         let mut tool_call_count = 0usize;
         let mut tool_call_ids = Vec::new();
@@ -492,22 +803,41 @@ impl Session {
             tool_error_count,
             usage,
             thread_key: self.thread_key.clone(),
+            stopped_reason,
         })
     }
 
+    /// Submits `user_input` and returns the turns appended to history while it
+    /// ran, including steering and loop-warning turns injected along the way.
+    pub async fn submit_and_collect(
+        &mut self,
+        user_input: impl Into<String>,
+        options: SubmitOptions,
+    ) -> Result<Vec<Turn>, AgentError> {
+        let baseline_turns = self.history.len();
+        self.submit_until_stopped(user_input.into(), &options)
+            .await?;
+        Ok(self.history.iter().skip(baseline_turns).cloned().collect())
+    }
+
     async fn submit_single(
         &mut self,
         user_input: String,
+        attachments: Vec<Attachment>,
         options: &SubmitOptions,
-    ) -> Result<bool, AgentError> {
+    ) -> Result<StopReason, AgentError> {
         if self.state == SessionState::Closed {
             return Err(AgentError::session_closed());
         }
+        if self.state == SessionState::Paused {
+            return Err(AgentError::session_paused());
+        }
 
         if self.is_abort_requested() {
             self.shutdown_to_closed().await?;
-            return Ok(false);
+            return Ok(StopReason::Aborted);
         }
+        self.initialize().await?;
 
         let abort_notify = self.abort_notify.clone();
         let abort_requested = self.abort_requested.clone();
@@ -520,7 +850,9 @@ impl Session {
         });
 
         self.transition_to(SessionState::Processing)?;
-        let user_turn = Turn::User(UserTurn::new(user_input.clone(), current_timestamp()));
+        let user_turn = Turn::User(
+            UserTurn::new(user_input.clone(), self.clock.timestamp()).with_attachments(attachments),
+        );
         self.push_turn(user_turn.clone());
         self.persist_turn_if_enabled(&user_turn).await?;
         self.emit(
@@ -529,19 +861,25 @@ impl Session {
         )?;
         self.drain_steering_queue().await?;
 
+        let max_tool_rounds = self.max_tool_rounds_per_input();
+        // Monotonic, unlike `Clock::now`, so wall-clock steps cannot skew it.
+        let submit_started = tokio::time::Instant::now();
         let mut round_count = 0usize;
-        let mut completed_naturally = false;
+        self.tool_rounds_used = 0;
+        let mut empty_response_retries = 0usize;
+        let stopped_reason;
         let mut context_warning_emitted = false;
         loop {
             if self.is_abort_requested() {
                 abort_kill_watchdog.abort();
                 self.shutdown_to_closed().await?;
-                return Ok(false);
+                return Ok(StopReason::Aborted);
             }
 
-            if round_count >= self.config.max_tool_rounds_per_input {
+            if round_count >= max_tool_rounds {
                 self.event_emitter
                     .emit(SessionEvent::turn_limit_round(self.id.clone(), round_count))?;
+                stopped_reason = StopReason::RoundLimit;
                 break;
             }
 
@@ -552,6 +890,30 @@ impl Session {
                         "total_turns": self.history.len()
                     }))?,
                 )?;
+                stopped_reason = StopReason::TurnLimit;
+                break;
+            }
+
+            let deadline_ms = self.config.submit_deadline_ms;
+            let elapsed_ms = submit_started.elapsed().as_millis() as u64;
+            if deadline_ms > 0 && elapsed_ms >= deadline_ms {
+                self.event_emitter.emit(SessionEvent::deadline_exceeded(
+                    self.id.clone(),
+                    elapsed_ms,
+                    deadline_ms,
+                ))?;
+                stopped_reason = StopReason::DeadlineExceeded;
+                break;
+            }
+
+            if self.token_budget_exhausted(self.last_call_tokens) {
+                self.event_emitter
+                    .emit(SessionEvent::token_budget_exceeded(
+                        self.id.clone(),
+                        self.session_tokens_used,
+                        self.config.max_session_tokens,
+                    ))?;
+                stopped_reason = StopReason::TokenBudgetExceeded;
                 break;
             }
 
@@ -559,11 +921,28 @@ impl Session {
                 context_warning_emitted = self.emit_context_usage_warning_if_needed()?;
             }
 
-            let request = self.build_request(options)?;
+            let mut request = self.build_request(options)?;
+            if let Some(interceptor) = &self.request_interceptor {
+                interceptor.intercept(&mut request);
+            }
+            let provider_profile = self.resolve_provider_profile(options.provider.as_deref())?;
+            if let Some((tokens, limit)) =
+                self.preflight_context_overflow(provider_profile.as_ref(), &request)
+            {
+                abort_kill_watchdog.abort();
+                self.transition_to(SessionState::Idle)?;
+                return Err(SessionError::ContextWindowExceeded { tokens, limit }.into());
+            }
             self.emit(EventKind::AssistantTextStart, EventData::new())?;
             let response = {
                 let llm_client = self.llm_client.clone();
-                let llm_call = llm_client.complete(request);
+                let retry_policy = llm_retry_policy(
+                    &self.config,
+                    provider_profile.id(),
+                    self.run_diagnostics.clone(),
+                    self.clock.clone(),
+                );
+                let llm_call = retry_async(&retry_policy, || llm_client.complete(request.clone()));
                 tokio::pin!(llm_call);
                 tokio::select! {
                     result = &mut llm_call => {
@@ -573,6 +952,19 @@ impl Session {
                                 self.event_emitter
                                     .emit(SessionEvent::error(self.id.clone(), error.to_string()))?;
                                 abort_kill_watchdog.abort();
+                                // Context overflows leave the session usable so callers
+                                // can compact history and resubmit.
+                                if let Some((tokens, limit)) = classify_context_overflow(&error) {
+                                    self.transition_to(SessionState::Idle)?;
+                                    return Err(SessionError::ContextWindowExceeded {
+                                        tokens: tokens
+                                            .unwrap_or_else(|| approximate_context_tokens(&self.history)),
+                                        limit: limit.unwrap_or_else(|| {
+                                            provider_profile.capabilities().context_window_size
+                                        }),
+                                    }
+                                    .into());
+                                }
                                 self.shutdown_to_closed().await?;
                                 return Err(error.into());
                             }
@@ -581,14 +973,37 @@ impl Session {
                     _ = self.abort_notify.notified() => {
                         abort_kill_watchdog.abort();
                         self.shutdown_to_closed().await?;
-                        return Ok(false);
+                        return Ok(StopReason::Aborted);
                     }
                 }
             };
 
+            self.last_call_tokens = response.usage.total_tokens;
+            self.session_tokens_used = self
+                .session_tokens_used
+                .saturating_add(response.usage.total_tokens);
             let text = response.text();
             let tool_calls = response.tool_calls();
             let reasoning = response.reasoning();
+            if text.is_empty() && tool_calls.is_empty() {
+                match self.config.on_empty_response {
+                    EmptyResponsePolicy::TreatAsComplete => {}
+                    EmptyResponsePolicy::Retry(max_retries)
+                        if empty_response_retries < max_retries =>
+                    {
+                        empty_response_retries += 1;
+                        continue;
+                    }
+                    EmptyResponsePolicy::Retry(_) | EmptyResponsePolicy::Error => {
+                        abort_kill_watchdog.abort();
+                        self.transition_to(SessionState::Idle)?;
+                        return Err(SessionError::EmptyResponse {
+                            attempts: empty_response_retries + 1,
+                        }
+                        .into());
+                    }
+                }
+            }
             if !text.is_empty() {
                 self.event_emitter.emit(SessionEvent::assistant_text_delta(
                     self.id.clone(),
@@ -601,7 +1016,7 @@ impl Session {
                 reasoning.clone(),
                 response.usage.clone(),
                 Some(response.id),
-                current_timestamp(),
+                self.clock.timestamp(),
             ));
             self.push_turn(assistant_turn.clone());
             self.persist_turn_if_enabled(&assistant_turn).await?;
@@ -614,14 +1029,37 @@ impl Session {
             if tool_calls.is_empty() {
                 if should_transition_to_awaiting_input(&text) {
                     self.transition_to(SessionState::AwaitingInput)?;
+                    stopped_reason = StopReason::AwaitingInput;
                 } else {
-                    completed_naturally = true;
+                    stopped_reason = StopReason::NaturalCompletion;
                 }
                 break;
             }
 
             round_count += 1;
+            self.tool_rounds_used = round_count;
+            let tool_names: HashMap<String, String> = tool_calls
+                .iter()
+                .map(|call| (call.id.clone(), call.name.clone()))
+                .collect();
             let results = self.execute_tool_calls(tool_calls, options).await?;
+            if let Some(diagnostics) = &self.run_diagnostics {
+                for result in results.iter().filter(|result| result.is_error) {
+                    diagnostics.record_at(
+                        RecoveredErrorKind::Tool,
+                        tool_names
+                            .get(&result.tool_call_id)
+                            .cloned()
+                            .unwrap_or_else(|| result.tool_call_id.clone()),
+                        result
+                            .content
+                            .as_str()
+                            .map(str::to_string)
+                            .unwrap_or_else(|| result.content.to_string()),
+                        self.clock.timestamp(),
+                    );
+                }
+            }
             let result_turns = results
                 .into_iter()
                 .map(|result| ToolResultTurn {
@@ -631,9 +1069,10 @@ impl Session {
                 })
                 .collect();
             let tool_results_turn =
-                Turn::ToolResults(ToolResultsTurn::new(result_turns, current_timestamp()));
+                Turn::ToolResults(ToolResultsTurn::new(result_turns, self.clock.timestamp()));
             self.push_turn(tool_results_turn.clone());
             self.persist_turn_if_enabled(&tool_results_turn).await?;
+            self.snapshot_workspace_after_round().await?;
             self.drain_steering_queue().await?;
             self.inject_loop_detection_warning_if_needed().await?;
         }
@@ -642,7 +1081,7 @@ impl Session {
         if self.state == SessionState::Processing {
             self.transition_to(SessionState::Idle)?;
         }
-        Ok(completed_naturally)
+        Ok(stopped_reason)
     }
 
     async fn execute_tool_calls(
@@ -669,7 +1108,7 @@ impl Session {
             .supports_parallel_tool_calls;
         if tool_calls
             .iter()
-            .all(|tool_call| !is_subagent_tool(&tool_call.name))
+            .all(|tool_call| !is_session_tool(&tool_call.name))
         {
             let results = self
                 .provider_profile
@@ -682,8 +1121,10 @@ impl Session {
                     ToolDispatchOptions {
                         session_id: self.id.clone(),
                         supports_parallel_tool_calls: supports_parallel,
+                        force_sequential: false,
                         hook: self.tool_call_hook.clone(),
                         hook_strict: self.config.tool_hook_strict,
+                        edit_backups: self.edit_backups.clone(),
                     },
                 )
                 .await?;
@@ -703,7 +1144,7 @@ impl Session {
 
         let mut results = Vec::with_capacity(tool_calls.len());
         for tool_call in tool_calls {
-            if is_subagent_tool(&tool_call.name) {
+            if is_session_tool(&tool_call.name) {
                 let result = self.execute_subagent_tool_call(tool_call).await?;
                 self.persist_event_turn(
                     "tool_call_end",
@@ -729,8 +1170,10 @@ impl Session {
                     ToolDispatchOptions {
                         session_id: self.id.clone(),
                         supports_parallel_tool_calls: false,
+                        force_sequential: false,
                         hook: self.tool_call_hook.clone(),
                         hook_strict: self.config.tool_hook_strict,
+                        edit_backups: self.edit_backups.clone(),
                     },
                 )
                 .await?;
@@ -751,10 +1194,45 @@ impl Session {
         Ok(results)
     }
 
+    /// Suspends an idle or awaiting-input session. `submit` fails until
+    /// [`resume`](Self::resume); `steer` and `follow_up` keep queueing.
+    pub fn pause(&mut self) -> Result<(), AgentError> {
+        if self.state == SessionState::Paused {
+            return Ok(());
+        }
+        let previous = self.state.clone();
+        self.transition_to(SessionState::Paused)?;
+        self.paused_from = Some(previous);
+        self.queue_session_event("session_paused", None);
+        Ok(())
+    }
+
+    /// Returns a paused session to the state it was paused from.
+    pub fn resume(&mut self) -> Result<(), AgentError> {
+        if self.state != SessionState::Paused {
+            return Ok(());
+        }
+        let next = self.paused_from.take().unwrap_or(SessionState::Idle);
+        self.transition_to(next)?;
+        self.queue_session_event("session_resumed", None);
+        Ok(())
+    }
+
+    /// Closes the session synchronously. The session-end record is queued,
+    /// and so are those of any idle subagents; prefer `shutdown`, or await
+    /// `flush_pending_persistence`, to write them.
     pub fn close(&mut self) -> Result<(), AgentError> {
         self.transition_to(SessionState::Closed)
     }
 
+    /// Closes the session and its subagents and writes everything still
+    /// queued or buffered.
+    pub async fn shutdown(&mut self) -> Result<(), AgentError> {
+        self.shutdown_subagents().await?;
+        self.close()?;
+        self.flush_pending_persistence().await
+    }
+
     pub fn checkpoint(&self) -> Result<SessionCheckpoint, AgentError> {
         if self
             .subagent_records
@@ -775,6 +1253,8 @@ impl Session {
             followup_queue: self.followup_queue.iter().cloned().collect(),
             config: self.config.clone(),
             thread_key: self.thread_key.clone(),
+            session_tokens_used: self.session_tokens_used,
+            last_call_tokens: self.last_call_tokens,
         })
     }
 
@@ -793,6 +1273,7 @@ impl Session {
             event_emitter,
             None,
             0,
+            Arc::new(SystemClock),
         )?;
         session.id = checkpoint.session_id;
         session.state = checkpoint.state;
@@ -802,6 +1283,8 @@ impl Session {
         session.config = checkpoint.config;
         session.thread_key = checkpoint.thread_key;
         session.config.thread_key = session.thread_key.clone();
+        session.session_tokens_used = checkpoint.session_tokens_used;
+        session.last_call_tokens = checkpoint.last_call_tokens;
         session.provider_profiles =
             HashMap::from([(provider_profile.id().to_string(), provider_profile)]);
         Ok(session)
@@ -825,13 +1308,32 @@ impl Session {
             self.id.clone(),
             self.state.to_string(),
         ))?;
-        self.persist_session_event_blocking(
-            "session_end",
-            serde_json::json!({ "final_state": self.state.to_string() }),
-        )?;
+        self.queue_session_event("session_end", Some(self.state.to_string()));
         Ok(())
     }
 }
 
+impl Drop for Session {
+    fn drop(&mut self) {
+        let queued = self.pending_session_events.len();
+        if queued == 0 && self.buffered_appender.is_none() {
+            return;
+        }
+        let _ = self.event_emitter.emit(SessionEvent::warning(
+            self.id.clone(),
+            format!(
+                "session dropped with {} queued lifecycle record(s) unwritten{}; \
+                 call shutdown or flush_pending_persistence before dropping",
+                queued,
+                if self.buffered_appender.is_some() {
+                    " and buffered appends unconfirmed"
+                } else {
+                    ""
+                }
+            ),
+        ));
+    }
+}
+
 #[cfg(test)]
 mod tests;
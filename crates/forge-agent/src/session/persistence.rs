@@ -1,13 +1,15 @@
 use super::{AgentError, SessionError, SessionPersistenceWriter};
 use forge_cxdb_runtime::{
-    CxdbBinaryClient, CxdbClientError, CxdbFsSnapshotCapture, CxdbFsSnapshotPolicy, CxdbHttpClient,
-    CxdbRuntimeStore,
+    CxdbAppendTurnRequest, CxdbBinaryClient, CxdbClientError, CxdbFsSnapshotCapture,
+    CxdbHttpClient, CxdbRuntimeStore,
 };
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use serde_json::Value;
-use std::path::Path;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::{Notify, mpsc};
+use tokio::task::JoinHandle;
 
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub(super) struct FsSnapshotStatsRecord {
@@ -31,6 +33,15 @@ pub(super) struct SessionLifecycleRecord {
     pub(super) snapshot_stats: Option<FsSnapshotStatsRecord>,
 }
 
+/// Session lifecycle event raised on a synchronous path (construction or
+/// `close`) and written at the next async persistence point.
+#[derive(Clone, Debug)]
+pub(super) struct PendingSessionEvent {
+    pub(super) event_kind: &'static str,
+    pub(super) timestamp: String,
+    pub(super) final_state: Option<String>,
+}
+
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub(super) struct AgentTurnRecord {
     pub(super) session_id: String,
@@ -60,8 +71,123 @@ pub(super) struct ToolCallLifecycleRecord {
     pub(super) snapshot_stats: Option<FsSnapshotStatsRecord>,
 }
 
+/// Bounded exponential-backoff retry for CXDB writes that stops waiting as
+/// soon as the session is aborted. Only transient errors (transport failures
+/// and 5xx-class responses) are retried.
+#[derive(Clone)]
+pub(super) struct PersistenceRetry {
+    pub(super) attempts: u32,
+    pub(super) backoff_ms: u64,
+    pub(super) abort_requested: Arc<AtomicBool>,
+    pub(super) abort_notify: Arc<Notify>,
+}
+
+impl PersistenceRetry {
+    pub(super) async fn run<T, F, Fut>(&self, mut operation: F) -> Result<T, CxdbClientError>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, CxdbClientError>>,
+    {
+        let mut delay_ms = self.backoff_ms;
+        let mut retries = 0;
+        loop {
+            let error = match operation().await {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+            if !error.is_transient()
+                || retries >= self.attempts
+                || self.abort_requested.load(Ordering::SeqCst)
+            {
+                return Err(error);
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(std::time::Duration::from_millis(delay_ms)) => {}
+                _ = self.abort_notify.notified() => return Err(error),
+            }
+            retries += 1;
+            delay_ms = delay_ms.saturating_mul(2);
+        }
+    }
+}
+
+/// Head turn and first failure reported by a drained [`BufferedAppender`].
+pub(super) struct BufferedAppendOutcome {
+    pub(super) head_turn_id: Option<String>,
+    pub(super) error: Option<CxdbClientError>,
+}
+
+/// Background writer for `CxdbPersistenceMode::Buffered`. Appends requests in
+/// queue order, chaining each onto the previous turn, and drops everything
+/// after the first failure so the persisted history has no gaps.
+pub(super) struct BufferedAppender {
+    sender: mpsc::UnboundedSender<CxdbAppendTurnRequest>,
+    worker: JoinHandle<BufferedAppendOutcome>,
+    failed: Arc<AtomicBool>,
+}
+
+impl BufferedAppender {
+    pub(super) fn spawn(
+        store: Arc<dyn SessionPersistenceWriter>,
+        head_turn_id: Option<String>,
+        retry: PersistenceRetry,
+    ) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<CxdbAppendTurnRequest>();
+        let failed = Arc::new(AtomicBool::new(false));
+        let worker_failed = failed.clone();
+        let worker = tokio::spawn(async move {
+            let mut outcome = BufferedAppendOutcome {
+                head_turn_id,
+                error: None,
+            };
+            while let Some(mut request) = receiver.recv().await {
+                if outcome.error.is_some() {
+                    continue;
+                }
+                request.parent_turn_id = outcome.head_turn_id.clone();
+                match retry.run(|| store.append_turn(request.clone())).await {
+                    Ok(turn) => outcome.head_turn_id = Some(turn.turn_id),
+                    Err(error) => {
+                        worker_failed.store(true, Ordering::SeqCst);
+                        outcome.error = Some(error);
+                    }
+                }
+            }
+            outcome
+        });
+        Self {
+            sender,
+            worker,
+            failed,
+        }
+    }
+
+    /// Queues `request`; returns false if an append has failed or the worker
+    /// is gone, so the caller drains and surfaces the error.
+    pub(super) fn enqueue(&self, request: CxdbAppendTurnRequest) -> bool {
+        !self.failed.load(Ordering::SeqCst) && self.sender.send(request).is_ok()
+    }
+
+    /// Closes the queue and waits until every queued append has been handled.
+    pub(super) async fn finish(self) -> BufferedAppendOutcome {
+        drop(self.sender);
+        self.worker
+            .await
+            .unwrap_or_else(|error| BufferedAppendOutcome {
+                head_turn_id: None,
+                error: Some(CxdbClientError::Backend(format!(
+                    "buffered persistence writer failed: {error}"
+                ))),
+            })
+    }
+}
+
 pub(super) const AGENT_REGISTRY_BUNDLE_ID: &str = "forge.agent.runtime.v2";
 const AGENT_TRANSCRIPT_TYPE_VERSION: u32 = 2;
+/// Transcript turns whose `turn` field holds a `CompactTurn`.
+const AGENT_COMPACT_TRANSCRIPT_TYPE_VERSION: u32 = 3;
+/// History backfills whose `turn` field holds `CompactTurn`s.
+const AGENT_COMPACT_BACKFILL_TYPE_VERSION: u32 = 2;
 
 fn type_field_tags(type_id: &str) -> &'static [(&'static str, &'static str)] {
     const TURN_FIELDS: [(&str, &str); 8] = [
@@ -106,7 +232,10 @@ fn type_field_tags(type_id: &str) -> &'static [(&'static str, &'static str)] {
         | "forge.agent.tool_results_turn"
         | "forge.agent.system_turn"
         | "forge.agent.steering_turn"
-        | "forge.link.subagent_spawn" => &TURN_FIELDS,
+        | "forge.agent.history_backfill"
+        | "forge.link.subagent_spawn"
+        | "forge.link.subagent_output"
+        | "forge.link.subagent_complete" => &TURN_FIELDS,
         "forge.agent.session_lifecycle" => &SESSION_LIFECYCLE_FIELDS,
         "forge.agent.tool_call_lifecycle" => &TOOL_CALL_LIFECYCLE_FIELDS,
         _ => &[],
@@ -145,23 +274,6 @@ pub(super) fn decode_typed_record<T: DeserializeOwned>(payload: &[u8]) -> Result
         .map_err(|err| SessionError::Persistence(format!("msgpack decode failed: {err}")))
 }
 
-pub(super) fn capture_fs_snapshot_blocking(
-    store: Arc<dyn SessionPersistenceWriter>,
-    policy: Option<&CxdbFsSnapshotPolicy>,
-    workspace_root: &Path,
-) -> Result<Option<CxdbFsSnapshotCapture>, CxdbClientError> {
-    let Some(policy) = policy.cloned() else {
-        return Ok(None);
-    };
-    let workspace_root = workspace_root.to_path_buf();
-    run_cxdb_future_blocking("capture_upload_workspace", async move {
-        store
-            .capture_upload_workspace(&workspace_root, &policy)
-            .await
-    })
-    .map(Some)
-}
-
 pub(super) fn snapshot_capture_fields(
     capture: Option<&CxdbFsSnapshotCapture>,
 ) -> (
@@ -259,6 +371,20 @@ pub(super) fn agent_type_version(type_id: &str) -> u32 {
     }
 }
 
+/// Type version for records written with `compact_persistence`; only the
+/// families that embed turns change shape.
+pub(super) fn agent_compact_type_version(type_id: &str) -> u32 {
+    match type_id {
+        "forge.agent.user_turn"
+        | "forge.agent.assistant_turn"
+        | "forge.agent.tool_results_turn"
+        | "forge.agent.system_turn"
+        | "forge.agent.steering_turn" => AGENT_COMPACT_TRANSCRIPT_TYPE_VERSION,
+        "forge.agent.history_backfill" => AGENT_COMPACT_BACKFILL_TYPE_VERSION,
+        other => agent_type_version(other),
+    }
+}
+
 pub(super) fn run_cxdb_future_blocking<F, T>(
     operation: &str,
     future: F,
@@ -312,11 +438,12 @@ fn agent_registry_bundle_json() -> Result<Vec<u8>, AgentError> {
         "registry_version": 1,
         "bundle_id": AGENT_REGISTRY_BUNDLE_ID,
         "types": {
-            "forge.agent.user_turn": { "versions": { "2": { "fields": turn_fields_descriptor() } } },
-            "forge.agent.assistant_turn": { "versions": { "2": { "fields": turn_fields_descriptor() } } },
-            "forge.agent.tool_results_turn": { "versions": { "2": { "fields": turn_fields_descriptor() } } },
-            "forge.agent.system_turn": { "versions": { "2": { "fields": turn_fields_descriptor() } } },
-            "forge.agent.steering_turn": { "versions": { "2": { "fields": turn_fields_descriptor() } } },
+            "forge.agent.user_turn": { "versions": { "2": { "fields": turn_fields_descriptor() }, "3": { "fields": turn_fields_descriptor() } } },
+            "forge.agent.assistant_turn": { "versions": { "2": { "fields": turn_fields_descriptor() }, "3": { "fields": turn_fields_descriptor() } } },
+            "forge.agent.tool_results_turn": { "versions": { "2": { "fields": turn_fields_descriptor() }, "3": { "fields": turn_fields_descriptor() } } },
+            "forge.agent.system_turn": { "versions": { "2": { "fields": turn_fields_descriptor() }, "3": { "fields": turn_fields_descriptor() } } },
+            "forge.agent.steering_turn": { "versions": { "2": { "fields": turn_fields_descriptor() }, "3": { "fields": turn_fields_descriptor() } } },
+            "forge.agent.history_backfill": { "versions": { "1": { "fields": turn_fields_descriptor() }, "2": { "fields": turn_fields_descriptor() } } },
             "forge.agent.session_lifecycle": { "versions": { "1": { "fields": session_lifecycle_fields_descriptor() } } },
             "forge.agent.tool_call_lifecycle": { "versions": { "1": { "fields": tool_call_lifecycle_fields_descriptor() } } }
         }
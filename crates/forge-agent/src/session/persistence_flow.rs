@@ -22,6 +22,7 @@ impl Session {
             return Ok(snapshot);
         }
 
+        self.flush_pending_persistence().await?;
         self.ensure_persistence_context().await?;
         snapshot.context_id = self.persistence_context_id.clone();
 
@@ -38,67 +39,106 @@ impl Session {
         Ok(snapshot)
     }
 
-    pub(super) fn persist_session_event_blocking(
+    pub(super) fn queue_session_event(
         &mut self,
-        event_kind: &str,
-        payload: Value,
-    ) -> Result<(), AgentError> {
+        event_kind: &'static str,
+        final_state: Option<String>,
+    ) {
+        if !self.persistence_enabled() {
+            return;
+        }
+        self.pending_session_events.push_back(PendingSessionEvent {
+            event_kind,
+            timestamp: self.clock.timestamp(),
+            final_state,
+        });
+    }
+
+    /// Writes session lifecycle events queued by synchronous paths
+    /// (construction and `close`) and waits for buffered appends to land.
+    /// Async persistence paths write queued events first, so they keep their
+    /// place ahead of later turns.
+    pub async fn flush_pending_persistence(&mut self) -> Result<(), AgentError> {
+        self.write_pending_session_events().await?;
+        self.drain_buffered_appends().await
+    }
+
+    /// Makes every write issued so far durable, e.g. before `checkpoint()` or
+    /// a hand-off: writes queued lifecycle records, drains buffered appends
+    /// and confirms the store's head is the last turn this session appended.
+    /// No-op when persistence is off.
+    pub async fn flush_persistence(&mut self) -> Result<(), AgentError> {
         if !self.persistence_enabled() {
             return Ok(());
         }
-        let Some(store) = self.persistence_writer.clone() else {
+        self.flush_pending_persistence().await?;
+        let (Some(store), Some(context_id), Some(expected_head)) = (
+            self.persistence_writer.clone(),
+            self.persistence_context_id.clone(),
+            self.persistence_parent_turn_id.clone(),
+        ) else {
             return Ok(());
         };
+        match store.get_head(&context_id).await {
+            Ok(head) if head.turn_id == expected_head => Ok(()),
+            Ok(head) => Err(SessionError::Persistence(format!(
+                "flush confirmed head {} but the session last appended {}",
+                head.turn_id, expected_head
+            ))
+            .into()),
+            Err(error) => self.handle_persistence_error(error, "get_head"),
+        }
+    }
 
-        if self.persistence_context_id.is_none() {
-            let created = run_cxdb_future_blocking("create_context", {
-                let store = store.clone();
-                async move { store.create_context(None).await }
-            });
-            match created {
-                Ok(context) => {
-                    let head_turn_id = if context.head_turn_id == "0" {
-                        None
-                    } else {
-                        Some(context.head_turn_id)
-                    };
-                    self.persistence_context_id = Some(context.context_id);
-                    self.persistence_parent_turn_id = head_turn_id;
-                }
-                Err(error) => return self.handle_persistence_error(error, "create_context"),
-            }
+    pub(super) async fn write_pending_session_events(&mut self) -> Result<(), AgentError> {
+        while let Some(event) = self.pending_session_events.pop_front() {
+            self.persist_session_event(event).await?;
         }
+        Ok(())
+    }
 
+    async fn persist_session_event(
+        &mut self,
+        event: PendingSessionEvent,
+    ) -> Result<(), AgentError> {
+        if !self.persistence_enabled() {
+            return Ok(());
+        }
+        self.ensure_persistence_context().await?;
+        let Some(store) = self.persistence_writer.clone() else {
+            return Ok(());
+        };
         let Some(context_id) = self.persistence_context_id.clone() else {
             return Ok(());
         };
 
-        let snapshot_capture = match capture_fs_snapshot_blocking(
-            store.clone(),
-            self.config.fs_snapshot_policy.as_ref(),
-            self.execution_env.working_directory(),
-        ) {
-            Ok(value) => value,
-            Err(error) => return self.handle_persistence_error(error, "capture_upload_workspace"),
+        let snapshot_capture = if let Some(policy) = self.per_record_snapshot_policy() {
+            let workspace_root = self.execution_env.working_directory();
+            match store.capture_upload_workspace(workspace_root, policy).await {
+                Ok(capture) => Some(capture),
+                Err(error) => {
+                    return self.handle_persistence_error(error, "capture_upload_workspace");
+                }
+            }
+        } else {
+            None
         };
 
         let sequence_no = self.next_persistence_sequence();
-        let kind = match event_kind {
+        let kind = match event.event_kind {
             "session_start" => "started",
             "session_end" => "ended",
+            "session_paused" => "paused",
+            "session_resumed" => "resumed",
             other => other,
         };
-        let final_state = payload
-            .get("final_state")
-            .and_then(Value::as_str)
-            .map(ToOwned::to_owned);
         let (fs_root_hash, snapshot_policy_id, snapshot_stats) =
             snapshot_capture_fields(snapshot_capture.as_ref());
         let record = SessionLifecycleRecord {
             session_id: self.id.clone(),
             kind: kind.to_string(),
-            timestamp: current_timestamp(),
-            final_state,
+            timestamp: event.timestamp,
+            final_state: event.final_state,
             sequence_no,
             thread_key: self.thread_key.clone(),
             fs_root_hash,
@@ -106,7 +146,7 @@ impl Session {
             snapshot_stats,
         };
         let payload_bytes = encode_typed_record("forge.agent.session_lifecycle", &record)?;
-        let idempotency_key = agent_idempotency_key(&self.id, sequence_no, event_kind);
+        let idempotency_key = agent_idempotency_key(&self.id, sequence_no, event.event_kind);
         let request = CxdbAppendTurnRequest {
             context_id,
             parent_turn_id: self.persistence_parent_turn_id.clone(),
@@ -119,15 +159,85 @@ impl Session {
                 .map(|capture| capture.fs_root_hash.clone()),
         };
 
-        match run_cxdb_future_blocking("append_turn", {
-            let store = store.clone();
-            async move { store.append_turn(request).await }
-        }) {
-            Ok(turn) => {
-                self.persistence_parent_turn_id = Some(turn.turn_id);
-                Ok(())
+        self.append_persistence_request(store, request).await?;
+        if event.event_kind == "session_end"
+            && self.config.fs_snapshot_cadence == FsSnapshotCadence::OnSessionEnd
+        {
+            return self.snapshot_workspace_to_head().await;
+        }
+        Ok(())
+    }
+
+    /// Completes or drops filesystem attaches an earlier process left half
+    /// done. Runs once from [`initialize`](Self::initialize).
+    ///
+    /// Leftovers are another run's unfinished work, so a failure here is
+    /// reported as a warning and never blocks this session from starting.
+    pub async fn reconcile_pending_attaches(&mut self) -> Result<(), AgentError> {
+        if !self.persistence_enabled() {
+            return Ok(());
+        }
+        let Some(store) = self.persistence_writer.clone() else {
+            return Ok(());
+        };
+        if let Err(error) = store.reconcile_pending_attaches().await {
+            if let Some(diagnostics) = &self.run_diagnostics {
+                diagnostics.record_at(
+                    RecoveredErrorKind::Persistence,
+                    "reconcile_pending_attaches",
+                    error.to_string(),
+                    self.clock.timestamp(),
+                );
             }
-            Err(error) => self.handle_persistence_error(error, "append_turn"),
+            self.event_emitter.emit(SessionEvent::warning(
+                self.id.clone(),
+                format!("reconcile_pending_attaches failed: {error}"),
+            ))?;
+        }
+        Ok(())
+    }
+
+    fn per_record_snapshot_policy(&self) -> Option<&CxdbFsSnapshotPolicy> {
+        self.config
+            .fs_snapshot_policy
+            .as_ref()
+            .filter(|_| self.config.fs_snapshot_cadence == FsSnapshotCadence::EveryRecord)
+    }
+
+    pub(super) async fn snapshot_workspace_after_round(&mut self) -> Result<(), AgentError> {
+        self.fs_snapshot_rounds += 1;
+        let FsSnapshotCadence::EveryNRounds(every) = self.config.fs_snapshot_cadence else {
+            return Ok(());
+        };
+        if every == 0 || !self.fs_snapshot_rounds.is_multiple_of(every) {
+            return Ok(());
+        }
+        self.snapshot_workspace_to_head().await
+    }
+
+    /// Captures the working directory and attaches it to the current head
+    /// turn, for cadences that snapshot out of band with appends.
+    async fn snapshot_workspace_to_head(&mut self) -> Result<(), AgentError> {
+        if !self.persistence_enabled() {
+            return Ok(());
+        }
+        let Some(policy) = self.config.fs_snapshot_policy.clone() else {
+            return Ok(());
+        };
+        let Some(store) = self.persistence_writer.clone() else {
+            return Ok(());
+        };
+        self.drain_buffered_appends().await?;
+        let Some(turn_id) = self.persistence_parent_turn_id.clone() else {
+            return Ok(());
+        };
+        let execution_env = self.execution_env.clone();
+        match store
+            .capture_upload_attach(&turn_id, execution_env.working_directory(), &policy)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(error) => self.handle_persistence_error(error, "capture_upload_attach"),
         }
     }
 
@@ -137,13 +247,106 @@ impl Session {
         operation: &str,
     ) -> Result<(), AgentError> {
         match self.persistence_mode {
-            CxdbPersistenceMode::Off => Ok(()),
-            CxdbPersistenceMode::Required => {
+            CxdbPersistenceMode::Off => {
+                if let Some(diagnostics) = &self.run_diagnostics {
+                    diagnostics.record_at(
+                        RecoveredErrorKind::Persistence,
+                        operation,
+                        error.to_string(),
+                        self.clock.timestamp(),
+                    );
+                }
+                Ok(())
+            }
+            CxdbPersistenceMode::Required | CxdbPersistenceMode::Buffered => {
                 Err(SessionError::Persistence(format!("{} failed: {}", operation, error)).into())
             }
         }
     }
 
+    fn persistence_retry(&self) -> PersistenceRetry {
+        PersistenceRetry {
+            attempts: self.config.persistence_retry_attempts,
+            backoff_ms: self.config.persistence_retry_backoff_ms,
+            abort_requested: self.abort_requested.clone(),
+            abort_notify: self.abort_notify.clone(),
+        }
+    }
+
+    /// Appends `request` onto the current head: inline (with retries), or
+    /// through the background appender in `Buffered` mode.
+    async fn append_persistence_request(
+        &mut self,
+        store: Arc<dyn SessionPersistenceWriter>,
+        request: CxdbAppendTurnRequest,
+    ) -> Result<(), AgentError> {
+        if self.persistence_mode == CxdbPersistenceMode::Buffered {
+            if let Some(error) = self.buffered_append_error.as_ref() {
+                return Err(SessionError::Persistence(format!(
+                    "append_turn rejected after an earlier buffered append failed ({error}); \
+                     call reset_buffered_persistence to resume"
+                ))
+                .into());
+            }
+            let retry = self.persistence_retry();
+            let head_turn_id = self.persistence_parent_turn_id.clone();
+            let appender = self
+                .buffered_appender
+                .get_or_insert_with(|| BufferedAppender::spawn(store, head_turn_id, retry));
+            if !appender.enqueue(request) {
+                return self.drain_buffered_appends().await;
+            }
+            return Ok(());
+        }
+        let retry = self.persistence_retry();
+        match retry.run(|| store.append_turn(request.clone())).await {
+            Ok(turn) => {
+                self.persistence_parent_turn_id = Some(turn.turn_id);
+                Ok(())
+            }
+            Err(error) => self.handle_persistence_error(error, "append_turn"),
+        }
+    }
+
+    /// Waits for buffered appends to land and adopts the resulting head.
+    pub(super) async fn drain_buffered_appends(&mut self) -> Result<(), AgentError> {
+        let Some(appender) = self.buffered_appender.take() else {
+            return Ok(());
+        };
+        let outcome = appender.finish().await;
+        if outcome.head_turn_id.is_some() {
+            self.persistence_parent_turn_id = outcome.head_turn_id;
+        }
+        match outcome.error {
+            Some(error) => {
+                self.buffered_append_error = Some(error.to_string());
+                self.handle_persistence_error(error, "append_turn")
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Clears a buffered append failure and re-reads the context head from
+    /// the store, so the next append chains onto what actually landed.
+    /// Writes dropped after the failure are not replayed.
+    pub async fn reset_buffered_persistence(&mut self) -> Result<(), AgentError> {
+        self.drain_buffered_appends().await.ok();
+        self.buffered_append_error = None;
+        let (Some(store), Some(context_id)) = (
+            self.persistence_writer.clone(),
+            self.persistence_context_id.clone(),
+        ) else {
+            return Ok(());
+        };
+        match store.get_head(&context_id).await {
+            Ok(head) => {
+                self.persistence_parent_turn_id = (head.turn_id != "0").then_some(head.turn_id);
+                Ok(())
+            }
+            Err(error) => self.handle_persistence_error(error, "get_head"),
+        }
+    }
+
     pub(super) async fn ensure_persistence_context(&mut self) -> Result<(), AgentError> {
         if !self.persistence_enabled() || self.persistence_context_id.is_some() {
             return Ok(());
@@ -151,7 +354,11 @@ impl Session {
         let Some(store) = self.persistence_writer.clone() else {
             return Ok(());
         };
-        match store.create_context(None).await {
+        match self
+            .persistence_retry()
+            .run(|| store.create_context(None))
+            .await
+        {
             Ok(context) => {
                 self.persistence_parent_turn_id = if context.head_turn_id == "0" {
                     None
@@ -170,38 +377,39 @@ impl Session {
             return Ok(());
         }
 
-        let (type_id, timestamp, turn_payload) = match turn {
+        let (type_id, timestamp, verbose_payload) = match turn {
             Turn::User(turn) => (
                 "forge.agent.user_turn",
                 turn.timestamp.clone(),
-                serde_json::to_value(turn)
-                    .map_err(|err| SessionError::Persistence(err.to_string()))?,
+                serde_json::to_value(turn),
             ),
             Turn::Assistant(turn) => (
                 "forge.agent.assistant_turn",
                 turn.timestamp.clone(),
-                serde_json::to_value(turn)
-                    .map_err(|err| SessionError::Persistence(err.to_string()))?,
+                serde_json::to_value(turn),
             ),
             Turn::ToolResults(turn) => (
                 "forge.agent.tool_results_turn",
                 turn.timestamp.clone(),
-                serde_json::to_value(turn)
-                    .map_err(|err| SessionError::Persistence(err.to_string()))?,
+                serde_json::to_value(turn),
             ),
             Turn::System(turn) => (
                 "forge.agent.system_turn",
                 turn.timestamp.clone(),
-                serde_json::to_value(turn)
-                    .map_err(|err| SessionError::Persistence(err.to_string()))?,
+                serde_json::to_value(turn),
             ),
             Turn::Steering(turn) => (
                 "forge.agent.steering_turn",
                 turn.timestamp.clone(),
-                serde_json::to_value(turn)
-                    .map_err(|err| SessionError::Persistence(err.to_string()))?,
+                serde_json::to_value(turn),
             ),
         };
+        let turn_payload = if self.config.compact_persistence {
+            serde_json::to_value(turn.to_compact())
+        } else {
+            verbose_payload
+        }
+        .map_err(|err| SessionError::Persistence(err.to_string()))?;
 
         self.persist_typed_payload(
             type_id,
@@ -220,6 +428,39 @@ impl Session {
         .await
     }
 
+    /// Writes seeded turns as one `forge.agent.history_backfill` record whose
+    /// `turn` field holds the whole batch.
+    pub(super) async fn persist_history_backfill(
+        &mut self,
+        turns: &[Turn],
+    ) -> Result<(), AgentError> {
+        if !self.persistence_enabled() {
+            return Ok(());
+        }
+        let batch = if self.config.compact_persistence {
+            serde_json::to_value(turns.iter().map(Turn::to_compact).collect::<Vec<_>>())
+        } else {
+            serde_json::to_value(turns)
+        }
+        .map_err(|err| SessionError::Persistence(err.to_string()))?;
+
+        self.persist_typed_payload(
+            "forge.agent.history_backfill",
+            "history_backfill",
+            AgentTurnRecord {
+                session_id: self.id.clone(),
+                timestamp: self.clock.timestamp(),
+                turn: batch,
+                sequence_no: 0,
+                thread_key: self.thread_key.clone(),
+                fs_root_hash: None,
+                snapshot_policy_id: None,
+                snapshot_stats: None,
+            },
+        )
+        .await
+    }
+
     pub(super) async fn persist_event_turn(
         &mut self,
         event_kind: &str,
@@ -266,7 +507,7 @@ impl Session {
             ToolCallLifecycleRecord {
                 session_id: self.id.clone(),
                 kind,
-                timestamp: current_timestamp(),
+                timestamp: self.clock.timestamp(),
                 call_id,
                 tool_name,
                 arguments,
@@ -291,6 +532,7 @@ impl Session {
         if !self.persistence_enabled() {
             return Ok(());
         }
+        self.write_pending_session_events().await?;
         self.ensure_persistence_context().await?;
         let Some(store) = self.persistence_writer.clone() else {
             return Ok(());
@@ -299,7 +541,7 @@ impl Session {
             return Ok(());
         };
 
-        let snapshot_capture = if let Some(policy) = self.config.fs_snapshot_policy.as_ref() {
+        let snapshot_capture = if let Some(policy) = self.per_record_snapshot_policy() {
             let workspace_root = self.execution_env.working_directory();
             match store.capture_upload_workspace(workspace_root, policy).await {
                 Ok(capture) => Some(capture),
@@ -324,19 +566,17 @@ impl Session {
             context_id,
             parent_turn_id: self.persistence_parent_turn_id.clone(),
             type_id: type_id.to_string(),
-            type_version: agent_type_version(type_id),
+            type_version: if self.config.compact_persistence {
+                agent_compact_type_version(type_id)
+            } else {
+                agent_type_version(type_id)
+            },
             payload: payload_bytes,
             idempotency_key,
             fs_root_hash: snapshot_capture
                 .as_ref()
                 .map(|capture| capture.fs_root_hash.clone()),
         };
-        match store.append_turn(request).await {
-            Ok(turn) => {
-                self.persistence_parent_turn_id = Some(turn.turn_id);
-                Ok(())
-            }
-            Err(error) => self.handle_persistence_error(error, "append_turn"),
-        }
+        self.append_persistence_request(store, request).await
     }
 }
@@ -3,7 +3,7 @@ use super::*;
 impl Session {
     pub(super) async fn drain_steering_queue(&mut self) -> Result<(), AgentError> {
         while let Some(content) = self.pop_steering_message() {
-            let turn = Turn::Steering(SteeringTurn::new(content.clone(), current_timestamp()));
+            let turn = Turn::Steering(SteeringTurn::new(content.clone(), self.clock.timestamp()));
             self.push_turn(turn.clone());
             self.persist_turn_if_enabled(&turn).await?;
             self.event_emitter
@@ -34,7 +34,7 @@ impl Session {
             return Ok(());
         }
 
-        let turn = Turn::Steering(SteeringTurn::new(warning.clone(), current_timestamp()));
+        let turn = Turn::Steering(SteeringTurn::new(warning.clone(), self.clock.timestamp()));
         self.push_turn(turn.clone());
         self.persist_turn_if_enabled(&turn).await?;
         self.event_emitter
@@ -65,6 +65,24 @@ impl Session {
         Ok(true)
     }
 
+    /// Estimated tokens and context window when `request` cannot fit the
+    /// window with `context_output_reserve_tokens` left for the response.
+    /// Always `None` when the reserve is unset.
+    pub(super) fn preflight_context_overflow(
+        &self,
+        provider_profile: &dyn ProviderProfile,
+        request: &Request,
+    ) -> Option<(usize, usize)> {
+        let reserve = self.config.context_output_reserve_tokens?;
+        let context_window_size = provider_profile.capabilities().context_window_size;
+        if context_window_size == 0 {
+            return None;
+        }
+        let estimated = provider_profile.estimate_request_tokens(request);
+        (estimated > context_window_size.saturating_sub(reserve))
+            .then_some((estimated, context_window_size))
+    }
+
     pub(super) fn build_request(&self, options: &SubmitOptions) -> Result<Request, AgentError> {
         let mut provider_profile = self.resolve_provider_profile(options.provider.as_deref())?;
         if let Some(model_override) = options
@@ -79,18 +97,31 @@ impl Session {
             ));
         }
 
-        let tools = provider_profile.tools();
+        let mut tools = provider_profile.tools();
+        if !self.config.enable_subagents {
+            tools.retain(|tool| !is_subagent_tool(&tool.name));
+        }
+        if !self.config.keep_edit_backups {
+            tools.retain(|tool| tool.name != UNDO_EDIT_TOOL);
+        }
         let environment_context = build_environment_context_snapshot(
             provider_profile.as_ref(),
             self.execution_env.as_ref(),
+            &self.config.environment_context_fields,
         );
         let project_docs = discover_project_documents(
             self.execution_env.working_directory(),
             provider_profile.as_ref(),
+            &self.config.extra_instruction_files,
         );
-        let system_prompt = provider_profile.build_system_prompt(
+        let (tools, text_only_tools_block) = gate_native_tools(
+            provider_profile.as_ref(),
+            self.config.on_tools_unsupported,
+            tools,
+        )?;
+        let mut system_prompt = provider_profile.build_system_prompt(
             &environment_context,
-            &tools,
+            tools.as_deref().unwrap_or_default(),
             &project_docs,
             options
                 .system_prompt_override
@@ -98,10 +129,18 @@ impl Session {
                 .or(self.config.system_prompt_override.as_deref()),
         );
 
+        if let Some(block) = text_only_tools_block {
+            system_prompt.push_str("\n\n");
+            system_prompt.push_str(&block);
+        }
+
         let mut messages = vec![Message::system(system_prompt)];
-        messages.extend(convert_history_to_messages(&self.history));
+        messages.extend(convert_history_to_messages(
+            &self.history,
+            provider_profile.group_tool_results(),
+            self.config.include_historical_reasoning,
+        ));
 
-        let tools = if tools.is_empty() { None } else { Some(tools) };
         let tool_choice = tools.as_ref().map(|_| ToolChoice {
             mode: "auto".to_string(),
             tool_name: None,
@@ -170,6 +209,15 @@ impl Session {
         }
 
         let _ = self.execution_env.terminate_all_commands().await;
-        self.transition_to(SessionState::Closed)
+        if std::mem::take(&mut self.execution_env_initialized) && self.owns_execution_env() {
+            let _ = self.execution_env.cleanup().await;
+        }
+        self.transition_to(SessionState::Closed)?;
+        for record in self.subagent_records.values_mut() {
+            if let Some(session) = record.session.as_mut() {
+                let _ = session.flush_pending_persistence().await;
+            }
+        }
+        self.flush_pending_persistence().await
     }
 }
@@ -1,4 +1,5 @@
 use super::*;
+use futures::future::BoxFuture;
 
 impl Session {
     pub(super) async fn execute_subagent_tool_call(
@@ -104,6 +105,10 @@ impl Session {
         }
 
         let output = match tool_call.name.as_str() {
+            AGENT_STATUS_TOOL => Ok(self.agent_status_report()),
+            _ if !self.config.enable_subagents => {
+                Err(ToolError::UnknownTool(tool_call.name.clone()).into())
+            }
             "spawn_agent" => self.handle_spawn_agent(arguments).await,
             "send_input" => self.handle_send_input(arguments).await,
             "wait" => self.handle_wait(arguments).await,
@@ -230,7 +235,13 @@ impl Session {
         let task = required_string_argument(&arguments, "task")?;
         let working_dir = optional_string_argument(&arguments, "working_dir")?;
         let model_override = optional_string_argument(&arguments, "model")?;
+        let role = optional_string_argument(&arguments, "role")?;
         let requested_max_turns = optional_usize_argument(&arguments, "max_turns")?;
+        let max_runtime_ms = optional_usize_argument(&arguments, "max_runtime_ms")?
+            .map(|value| value as u64)
+            .unwrap_or(self.config.subagent_max_runtime_ms);
+        let max_runtime =
+            (max_runtime_ms > 0).then(|| std::time::Duration::from_millis(max_runtime_ms));
         let mut child_config = self.config.clone();
         child_config.max_turns = requested_max_turns.unwrap_or(50);
         child_config.max_subagent_depth = self.config.max_subagent_depth;
@@ -249,17 +260,31 @@ impl Session {
                 self.execution_env.clone()
             };
 
+        let base_provider_profile = role
+            .and_then(|role| self.subagent_profiles.get(role.trim()).cloned())
+            .unwrap_or_else(|| self.provider_profile.clone());
         let child_provider_profile: Arc<dyn ProviderProfile> =
             if let Some(model) = model_override.filter(|value| !value.trim().is_empty()) {
                 Arc::new(ModelOverrideProviderProfile::new(
-                    self.provider_profile.clone(),
+                    base_provider_profile,
                     model,
                 ))
             } else {
-                self.provider_profile.clone()
+                base_provider_profile
             };
 
-        let child_id = Uuid::new_v4().to_string();
+        // With a configured parent id, derive child ids from the parent id and
+        // spawn index so reruns reproduce them without colliding.
+        let child_index = self.subagents.len();
+        let deterministic_ids = self.config.session_id.is_some() || self.config.id_seed.is_some();
+        let child_id = if deterministic_ids {
+            deterministic_uuid(&format!("{}:subagent:{}", self.id, child_index))
+        } else {
+            Uuid::new_v4().to_string()
+        };
+        child_config.session_id = None;
+        child_config.id_seed =
+            deterministic_ids.then(|| format!("{}:subagent:{}:session", self.id, child_index));
         self.subagents.insert(
             child_id.clone(),
             SubAgentHandle {
@@ -276,11 +301,14 @@ impl Session {
             self.event_emitter.clone(),
             self.persistence_writer.clone(),
             self.subagent_depth + 1,
+            self.clock.clone(),
         )?;
+        child_session.subagent_profiles = self.subagent_profiles.clone();
 
         let mut parent_turn_id: Option<String> = None;
         if self.persistence_enabled() {
             self.ensure_persistence_context().await?;
+            self.drain_buffered_appends().await?;
             if let (Some(store), Some(context_id)) = (
                 self.persistence_writer.clone(),
                 self.persistence_context_id.clone(),
@@ -324,7 +352,7 @@ impl Session {
             "subagent_spawn",
             AgentTurnRecord {
                 session_id: self.id.clone(),
-                timestamp: current_timestamp(),
+                timestamp: self.clock.timestamp(),
                 turn: serde_json::json!({
                     "session_id": session_id,
                     "parent_turn": parent_turn_id,
@@ -342,20 +370,31 @@ impl Session {
         )
         .await?;
 
-        let active_task = Some(spawn_subagent_submit_task(Box::new(child_session), task));
-        self.subagent_records.insert(
-            child_id.clone(),
-            SubAgentRecord {
-                session: None,
-                active_task,
-                result: None,
-            },
-        );
-        tokio::task::yield_now().await;
+        let active_task = Some(spawn_subagent_submit_task(
+            Box::new(child_session),
+            task,
+            max_runtime,
+        ));
+        let mut record = SubAgentRecord {
+            session: None,
+            active_task,
+            result: None,
+            max_runtime,
+            pending_inputs: VecDeque::new(),
+            undelivered_results: Vec::new(),
+            full_output_persisted: false,
+            complete_persisted: false,
+        };
+        let status = self.settle_if_sequential(&child_id, &mut record).await;
+        self.subagent_records.insert(child_id.clone(), record);
+        let status = status?;
+        if status == SubAgentStatus::Running {
+            tokio::task::yield_now().await;
+        }
 
         Ok(serde_json::json!({
             "agent_id": child_id,
-            "status": subagent_status_label(&SubAgentStatus::Running),
+            "status": subagent_status_label(&status),
         })
         .to_string())
     }
@@ -366,13 +405,35 @@ impl Session {
     ) -> Result<String, AgentError> {
         let agent_id = required_string_argument(&arguments, "agent_id")?;
         let message = required_string_argument(&arguments, "message")?;
+        let queue = match arguments.get("queue") {
+            None => false,
+            Some(value) => value.as_bool().ok_or_else(|| {
+                ToolError::Validation("argument 'queue' must be a boolean".to_string())
+            })?,
+        };
         let mut record = self
             .subagent_records
             .remove(&agent_id)
             .ok_or_else(|| ToolError::Execution(format!("subagent '{}' not found", agent_id)))?;
-        self.reconcile_subagent_record(&agent_id, &mut record, false)
-            .await?;
+        if let Err(error) = self
+            .reconcile_subagent_record(&agent_id, &mut record, false)
+            .await
+        {
+            self.subagent_records.insert(agent_id.clone(), record);
+            return Err(error);
+        }
 
+        if record.active_task.is_some() && queue {
+            record.pending_inputs.push_back(message);
+            let queued = record.pending_inputs.len();
+            self.subagent_records.insert(agent_id.clone(), record);
+            return Ok(serde_json::json!({
+                "agent_id": agent_id,
+                "status": subagent_status_label(&SubAgentStatus::Running),
+                "queued": queued,
+            })
+            .to_string());
+        }
         if record.active_task.is_some() {
             self.subagent_records.insert(agent_id.clone(), record);
             return Err(ToolError::Execution(format!(
@@ -391,25 +452,54 @@ impl Session {
             .into());
         };
 
-        record.active_task = Some(spawn_subagent_submit_task(session, message));
+        record.active_task = Some(spawn_subagent_submit_task(
+            session,
+            message,
+            record.max_runtime,
+        ));
         self.set_subagent_status(&agent_id, SubAgentStatus::Running);
+        let status = self.settle_if_sequential(&agent_id, &mut record).await;
         self.subagent_records.insert(agent_id.clone(), record);
+        let status = status?;
 
         Ok(serde_json::json!({
             "agent_id": agent_id,
-            "status": subagent_status_label(&SubAgentStatus::Running),
+            "status": subagent_status_label(&status),
         })
         .to_string())
     }
 
+    /// In `Sequential` mode, runs the just-started submit to completion and
+    /// returns the settled status; otherwise reports `Running`.
+    async fn settle_if_sequential(
+        &mut self,
+        agent_id: &str,
+        record: &mut SubAgentRecord,
+    ) -> Result<SubAgentStatus, AgentError> {
+        if self.config.subagent_execution != SubagentExecution::Sequential {
+            return Ok(SubAgentStatus::Running);
+        }
+        self.reconcile_subagent_record(agent_id, record, true)
+            .await?;
+        Ok(self
+            .subagents
+            .get(agent_id)
+            .map_or(SubAgentStatus::Failed, |handle| handle.status.clone()))
+    }
+
     pub(super) async fn handle_wait(&mut self, arguments: Value) -> Result<String, AgentError> {
         let agent_id = required_string_argument(&arguments, "agent_id")?;
         let mut record = self
             .subagent_records
             .remove(&agent_id)
             .ok_or_else(|| ToolError::Execution(format!("subagent '{}' not found", agent_id)))?;
-        self.reconcile_subagent_record(&agent_id, &mut record, true)
-            .await?;
+        if let Err(error) = self
+            .reconcile_subagent_record(&agent_id, &mut record, true)
+            .await
+        {
+            self.subagent_records.insert(agent_id.clone(), record);
+            return Err(error);
+        }
 
         let result = record.result.clone().unwrap_or(SubAgentResult {
             output: String::new(),
@@ -423,16 +513,148 @@ impl Session {
                 .map(|session| session.history().len())
                 .unwrap_or_default(),
         });
+        let output = self
+            .parent_visible_subagent_output(&agent_id, &mut record, &result)
+            .await;
+        let earlier_results: Vec<Value> = std::mem::take(&mut record.undelivered_results)
+            .into_iter()
+            .map(|earlier| {
+                let max_bytes = self.config.subagent_output_max_bytes;
+                let output = if max_bytes == 0 {
+                    earlier.output
+                } else {
+                    cap_output_bytes(&earlier.output, max_bytes)
+                };
+                serde_json::json!({
+                    "output": output,
+                    "success": earlier.success,
+                    "turns_used": earlier.turns_used,
+                })
+            })
+            .collect();
         self.subagent_records.insert(agent_id.clone(), record);
+        let output = output?;
 
-        Ok(serde_json::json!({
+        let mut payload = serde_json::json!({
             "agent_id": agent_id,
             "status": subagent_status_label(self.subagents.get(&agent_id).map(|h| &h.status).unwrap_or(&SubAgentStatus::Failed)),
-            "output": result.output,
+            "output": output,
             "success": result.success,
             "turns_used": result.turns_used
-        })
-        .to_string())
+        });
+        // Submits that ran before queued inputs, oldest first.
+        if !earlier_results.is_empty() {
+            payload["earlier_results"] = Value::Array(earlier_results);
+        }
+        Ok(payload.to_string())
+    }
+
+    /// Applies `subagent_output_max_bytes` to a finished subagent's output,
+    /// persisting the full text first so nothing is lost to the cap.
+    async fn parent_visible_subagent_output(
+        &mut self,
+        agent_id: &str,
+        record: &mut SubAgentRecord,
+        result: &SubAgentResult,
+    ) -> Result<String, AgentError> {
+        let max_bytes = self.config.subagent_output_max_bytes;
+        if max_bytes == 0 || result.output.len() <= max_bytes {
+            return Ok(result.output.clone());
+        }
+
+        if !record.full_output_persisted {
+            self.persist_typed_payload(
+                "forge.link.subagent_output",
+                "subagent_output",
+                AgentTurnRecord {
+                    session_id: self.id.clone(),
+                    timestamp: self.clock.timestamp(),
+                    turn: serde_json::json!({
+                        "subagent_id": agent_id,
+                        "output": result.output,
+                        "success": result.success,
+                        "turns_used": result.turns_used,
+                    }),
+                    sequence_no: 0,
+                    thread_key: self.thread_key.clone(),
+                    fs_root_hash: None,
+                    snapshot_policy_id: None,
+                    snapshot_stats: None,
+                },
+            )
+            .await?;
+            record.full_output_persisted = true;
+        }
+
+        if self.config.summarize_subagent_output
+            && let Some(summary) = self.summarize_subagent_output(&result.output).await?
+        {
+            return Ok(cap_output_bytes(&summary, max_bytes));
+        }
+        Ok(cap_output_bytes(&result.output, max_bytes))
+    }
+
+    async fn summarize_subagent_output(
+        &mut self,
+        output: &str,
+    ) -> Result<Option<String>, AgentError> {
+        // At ~4 bytes per token, keep the report within half the window.
+        let input_max_bytes = self
+            .provider_profile
+            .capabilities()
+            .context_window_size
+            .saturating_mul(2);
+        let mut request = Request {
+            model: self.provider_profile.model().to_string(),
+            messages: vec![
+                Message::system(SUBAGENT_SUMMARY_PROMPT),
+                Message::user(cap_output_bytes(output, input_max_bytes)),
+            ],
+            provider: Some(self.provider_profile.id().to_string()),
+            tools: None,
+            tool_choice: None,
+            response_format: None,
+            temperature: None,
+            top_p: None,
+            max_tokens: None,
+            stop_sequences: None,
+            reasoning_effort: None,
+            metadata: None,
+            provider_options: self.provider_profile.provider_options(),
+        };
+        if let Some(interceptor) = &self.request_interceptor {
+            interceptor.intercept(&mut request);
+        }
+        let projected_tokens = self.provider_profile.estimate_request_tokens(&request) as u64;
+        if self.token_budget_exhausted(projected_tokens) {
+            self.event_emitter.emit(SessionEvent::warning(
+                self.id.clone(),
+                "subagent output summarization skipped: session token budget exhausted; truncating",
+            ))?;
+            return Ok(None);
+        }
+        let retry_policy = llm_retry_policy(
+            &self.config,
+            self.provider_profile.id(),
+            self.run_diagnostics.clone(),
+            self.clock.clone(),
+        );
+        let llm_client = self.llm_client.clone();
+        match retry_async(&retry_policy, || llm_client.complete(request.clone())).await {
+            Ok(response) => {
+                self.session_tokens_used = self
+                    .session_tokens_used
+                    .saturating_add(response.usage.total_tokens);
+                Ok(Some(response.text()).filter(|text| !text.trim().is_empty()))
+            }
+            Err(error) => {
+                self.event_emitter.emit(SessionEvent::warning(
+                    self.id.clone(),
+                    format!("subagent output summarization failed: {error}; truncating"),
+                ))?;
+                Ok(None)
+            }
+        }
     }
 
     pub(super) async fn handle_close_agent(
@@ -447,9 +669,10 @@ impl Session {
         if let Some(task) = record.active_task.take() {
             task.abort();
         }
+        record.pending_inputs.clear();
         if let Some(session) = record.session.as_mut() {
             session.request_abort();
-            let _ = session.close();
+            let _ = session.shutdown().await;
         }
         self.set_subagent_status(&agent_id, SubAgentStatus::Failed);
         self.subagent_records.insert(agent_id.clone(), record);
@@ -467,37 +690,119 @@ impl Session {
         record: &mut SubAgentRecord,
         wait_for_completion: bool,
     ) -> Result<(), AgentError> {
-        let Some(task) = record.active_task.take() else {
-            return Ok(());
-        };
+        loop {
+            if let Some(task) = record.active_task.take() {
+                if !wait_for_completion && !task.is_finished() {
+                    record.active_task = Some(task);
+                    self.set_subagent_status(agent_id, SubAgentStatus::Running);
+                    return Ok(());
+                }
 
-        if !wait_for_completion && !task.is_finished() {
-            record.active_task = Some(task);
-            self.set_subagent_status(agent_id, SubAgentStatus::Running);
-            return Ok(());
-        }
+                match task.await {
+                    Ok(output) => {
+                        let status = if output.result.success {
+                            SubAgentStatus::Completed
+                        } else {
+                            SubAgentStatus::Failed
+                        };
+                        record.session = Some(output.session);
+                        record.result = Some(output.result);
+                        record.full_output_persisted = false;
+                        self.set_subagent_status(agent_id, status);
+                    }
+                    Err(error) => {
+                        record.result = Some(SubAgentResult {
+                            output: format!("subagent task join failed: {}", error),
+                            success: false,
+                            turns_used: 0,
+                        });
+                        self.set_subagent_status(agent_id, SubAgentStatus::Failed);
+                    }
+                }
+                record.complete_persisted = false;
+            }
+            // Nothing new finished, unless an earlier completion write failed
+            // and is retried here before any queued input moves on.
+            if record.result.is_none() || record.complete_persisted {
+                return Ok(());
+            }
+            self.persist_subagent_complete(agent_id, record).await?;
 
-        match task.await {
-            Ok(output) => {
-                let status = if output.result.success {
-                    SubAgentStatus::Completed
-                } else {
-                    SubAgentStatus::Failed
-                };
-                record.session = Some(output.session);
-                record.result = Some(output.result);
-                self.set_subagent_status(agent_id, status);
+            if record.pending_inputs.is_empty() {
+                return Ok(());
             }
-            Err(error) => {
-                record.result = Some(SubAgentResult {
-                    output: format!("subagent task join failed: {}", error),
-                    success: false,
-                    turns_used: 0,
-                });
-                self.set_subagent_status(agent_id, SubAgentStatus::Failed);
+            // Queued inputs only follow a successful submit on an open session;
+            // a failed or closed child drops the rest of the queue.
+            let healthy = record.result.as_ref().is_some_and(|result| result.success)
+                && record
+                    .session
+                    .as_ref()
+                    .is_some_and(|session| session.state() != &SessionState::Closed);
+            if !healthy {
+                let dropped = std::mem::take(&mut record.pending_inputs).len();
+                self.event_emitter.emit(SessionEvent::warning(
+                    self.id.clone(),
+                    format!(
+                        "subagent '{}' did not complete; dropped {} queued input(s)",
+                        agent_id, dropped
+                    ),
+                ))?;
+                return Ok(());
             }
+            let (Some(session), Some(message)) =
+                (record.session.take(), record.pending_inputs.pop_front())
+            else {
+                return Ok(());
+            };
+            record.undelivered_results.extend(record.result.take());
+            record.active_task = Some(spawn_subagent_submit_task(
+                session,
+                message,
+                record.max_runtime,
+            ));
+            self.set_subagent_status(agent_id, SubAgentStatus::Running);
         }
+    }
 
+    /// Writes the completion counterpart of `forge.link.subagent_spawn`, so a
+    /// replay can tell how each subagent submit ended without its transcript.
+    async fn persist_subagent_complete(
+        &mut self,
+        agent_id: &str,
+        record: &mut SubAgentRecord,
+    ) -> Result<(), AgentError> {
+        let Some(result) = record.result.as_ref() else {
+            return Ok(());
+        };
+        let status = self
+            .subagents
+            .get(agent_id)
+            .map_or(SubAgentStatus::Failed, |handle| handle.status.clone());
+        self.persist_typed_payload(
+            "forge.link.subagent_complete",
+            "subagent_complete",
+            AgentTurnRecord {
+                session_id: self.id.clone(),
+                timestamp: self.clock.timestamp(),
+                turn: serde_json::json!({
+                    "session_id": self.id,
+                    "subagent_id": agent_id,
+                    "child_session_id": record.session.as_ref().map(|session| session.id.clone()),
+                    "status": subagent_status_label(&status),
+                    "success": result.success,
+                    "turns_used": result.turns_used,
+                    "output_hash": blake3::hash(result.output.as_bytes()).to_hex().to_string(),
+                    "output_bytes": result.output.len(),
+                }),
+                sequence_no: 0,
+                thread_key: self.thread_key.clone(),
+                fs_root_hash: None,
+                snapshot_policy_id: None,
+                snapshot_stats: None,
+            },
+        )
+        .await?;
+        record.complete_persisted = true;
         Ok(())
     }
 
@@ -506,6 +811,47 @@ impl Session {
             handle.status = status;
         }
     }
+    /// Async counterpart of `close_all_subagents`: stops running children
+    /// and shuts down idle ones so their queued records are written. Boxed
+    /// because it recurses through nested subagents.
+    pub(super) fn shutdown_subagents(&mut self) -> BoxFuture<'_, Result<(), AgentError>> {
+        Box::pin(async move {
+            let agent_ids: Vec<String> = self.subagent_records.keys().cloned().collect();
+            for agent_id in agent_ids {
+                let Some(mut record) = self.subagent_records.remove(&agent_id) else {
+                    continue;
+                };
+                let mut failures = Vec::new();
+                if record.result.is_some()
+                    && !record.complete_persisted
+                    && let Err(error) = self.persist_subagent_complete(&agent_id, &mut record).await
+                {
+                    failures.push(format!(
+                        "subagent '{}' completion record not written: {}",
+                        agent_id, error
+                    ));
+                }
+                if let Some(task) = record.active_task.take() {
+                    task.abort();
+                }
+                if let Some(session) = record.session.as_mut()
+                    && let Err(error) = session.shutdown().await
+                {
+                    failures.push(format!(
+                        "subagent '{}' shutdown failed: {}",
+                        agent_id, error
+                    ));
+                }
+                self.subagent_records.insert(agent_id, record);
+                for failure in failures {
+                    self.event_emitter
+                        .emit(SessionEvent::warning(self.id.clone(), failure))?;
+                }
+            }
+            Ok(())
+        })
+    }
+
     pub(super) fn close_all_subagents(&mut self) -> Result<(), AgentError> {
         let agent_ids: Vec<String> = self.subagent_records.keys().cloned().collect();
         for agent_id in agent_ids {
@@ -1,9 +1,10 @@
 
 use super::*;
 use crate::{
-    BufferedEventEmitter, LocalExecutionEnvironment, PROJECT_DOC_TRUNCATION_MARKER,
-    ProviderCapabilities, RegisteredTool, StaticProviderProfile, ToolCallHook, ToolExecutor,
-    ToolPreHookOutcome, ToolRegistry, build_openai_tool_registry,
+    BufferedEventEmitter, LocalExecutionEnvironment, MonotonicTestClock,
+    PROJECT_DOC_TRUNCATION_MARKER, ProviderCapabilities, RegisteredTool, StaticProviderProfile,
+    ToolCallHook, ToolExecutor, ToolMetadata, ToolPreHookOutcome, ToolRegistry,
+    build_openai_tool_registry,
 };
 use async_trait::async_trait;
 use forge_llm::{
@@ -21,7 +22,7 @@ use tempfile::tempdir;
 
 #[derive(Clone)]
 struct SequenceAdapter {
-    responses: Arc<Mutex<VecDeque<Response>>>,
+    responses: Arc<Mutex<VecDeque<Result<Response, SDKError>>>>,
     requests: Arc<Mutex<Vec<Request>>>,
     delay_ms: u64,
 }
@@ -37,11 +38,13 @@ impl ProviderAdapter for SequenceAdapter {
             tokio::time::sleep(std::time::Duration::from_millis(self.delay_ms)).await;
         }
         self.requests.lock().expect("requests mutex").push(request);
-        self.responses
-            .lock()
-            .expect("responses mutex")
-            .pop_front()
-            .ok_or_else(|| SDKError::Configuration(ConfigurationError::new("no response queued")))
+        let next = self.responses.lock().expect("responses mutex").pop_front();
+        match next {
+            Some(result) => result,
+            None => Err(SDKError::Configuration(ConfigurationError::new(
+                "no response queued",
+            ))),
+        }
     }
 
     async fn stream(&self, _request: Request) -> Result<StreamEventStream, SDKError> {
@@ -62,8 +65,14 @@ struct RecordingPersistence {
     next_turn_id: Mutex<u64>,
     append_requests: Mutex<Vec<CxdbAppendTurnRequest>>,
     snapshot_calls: Mutex<usize>,
+    attach_calls: Mutex<Vec<(CxdbTurnId, String)>>,
     fail_create: bool,
     fail_append: bool,
+    transient_append_failures: Mutex<usize>,
+    append_attempts: Mutex<usize>,
+    /// Fails every append of this type while set.
+    fail_append_of_type: Mutex<Option<String>>,
+    head_turn_id: Mutex<Option<String>>,
 }
 
 impl RecordingPersistence {
@@ -73,8 +82,13 @@ impl RecordingPersistence {
             next_turn_id: Mutex::new(1),
             append_requests: Mutex::new(Vec::new()),
             snapshot_calls: Mutex::new(0),
+            attach_calls: Mutex::new(Vec::new()),
             fail_create,
             fail_append,
+            transient_append_failures: Mutex::new(0),
+            append_attempts: Mutex::new(0),
+            fail_append_of_type: Mutex::new(None),
+            head_turn_id: Mutex::new(None),
         }
     }
 
@@ -142,11 +156,36 @@ impl SessionPersistenceWriter for RecordingPersistence {
         &self,
         request: CxdbAppendTurnRequest,
     ) -> Result<CxdbStoredTurn, CxdbClientError> {
+        *self.append_attempts.lock().expect("append attempts mutex") += 1;
         if self.fail_append {
             return Err(CxdbClientError::Backend(
                 "forced append failure".to_string(),
             ));
         }
+        {
+            let mut transient = self
+                .transient_append_failures
+                .lock()
+                .expect("transient failures mutex");
+            if *transient > 0 {
+                *transient -= 1;
+                return Err(CxdbClientError::Unavailable(
+                    "transient append failure".to_string(),
+                ));
+            }
+        }
+        if self
+            .fail_append_of_type
+            .lock()
+            .expect("fail type mutex")
+            .as_deref()
+            == Some(request.type_id.as_str())
+        {
+            return Err(CxdbClientError::Backend(format!(
+                "forced {} append failure",
+                request.type_id
+            )));
+        }
         self.append_requests
             .lock()
             .expect("append requests mutex")
@@ -154,6 +193,7 @@ impl SessionPersistenceWriter for RecordingPersistence {
         let mut next = self.next_turn_id.lock().expect("next turn mutex");
         let turn_id = next.to_string();
         *next += 1;
+        *self.head_turn_id.lock().expect("head turn mutex") = Some(turn_id.clone());
         Ok(CxdbStoredTurn {
             context_id: request.context_id,
             turn_id,
@@ -170,7 +210,12 @@ impl SessionPersistenceWriter for RecordingPersistence {
     async fn get_head(&self, context_id: &String) -> Result<CxdbStoredTurnRef, CxdbClientError> {
         Ok(CxdbStoredTurnRef {
             context_id: context_id.clone(),
-            turn_id: "0".to_string(),
+            turn_id: self
+                .head_turn_id
+                .lock()
+                .expect("head turn mutex")
+                .clone()
+                .unwrap_or_else(|| "0".to_string()),
             depth: 0,
         })
     }
@@ -195,6 +240,18 @@ impl SessionPersistenceWriter for RecordingPersistence {
             },
         })
     }
+
+    async fn attach_fs(
+        &self,
+        turn_id: &CxdbTurnId,
+        fs_root_hash: &String,
+    ) -> Result<(), CxdbClientError> {
+        self.attach_calls
+            .lock()
+            .expect("attach calls mutex")
+            .push((turn_id.clone(), fs_root_hash.clone()));
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -232,6 +289,99 @@ impl ToolCallHook for RecordingHook {
     }
 }
 
+#[derive(Default)]
+struct LifecycleEnv {
+    fail_initialize: bool,
+    initialize_calls: Arc<Mutex<usize>>,
+    cleanup_calls: Arc<Mutex<usize>>,
+}
+
+#[async_trait]
+impl ExecutionEnvironment for LifecycleEnv {
+    async fn read_file(
+        &self,
+        _path: &str,
+        _offset: Option<usize>,
+        _limit: Option<usize>,
+    ) -> Result<String, AgentError> {
+        Err(AgentError::NotImplemented("read_file".to_string()))
+    }
+
+    async fn write_file(&self, _path: &str, _content: &str) -> Result<(), AgentError> {
+        Err(AgentError::NotImplemented("write_file".to_string()))
+    }
+
+    async fn delete_file(&self, _path: &str) -> Result<(), AgentError> {
+        Err(AgentError::NotImplemented("delete_file".to_string()))
+    }
+
+    async fn move_file(&self, _from: &str, _to: &str) -> Result<(), AgentError> {
+        Err(AgentError::NotImplemented("move_file".to_string()))
+    }
+
+    async fn file_exists(&self, _path: &str) -> Result<bool, AgentError> {
+        Err(AgentError::NotImplemented("file_exists".to_string()))
+    }
+
+    async fn list_directory(
+        &self,
+        _path: &str,
+        _depth: usize,
+    ) -> Result<Vec<crate::DirEntry>, AgentError> {
+        Err(AgentError::NotImplemented("list_directory".to_string()))
+    }
+
+    async fn exec_command(
+        &self,
+        _command: &str,
+        _timeout_ms: u64,
+        _working_dir: Option<&str>,
+        _env_vars: Option<HashMap<String, String>>,
+    ) -> Result<crate::ExecResult, AgentError> {
+        Err(AgentError::NotImplemented("exec_command".to_string()))
+    }
+
+    async fn grep(
+        &self,
+        _pattern: &str,
+        _path: &str,
+        _options: crate::GrepOptions,
+    ) -> Result<String, AgentError> {
+        Err(AgentError::NotImplemented("grep".to_string()))
+    }
+
+    async fn glob(&self, _pattern: &str, _path: &str) -> Result<Vec<String>, AgentError> {
+        Err(AgentError::NotImplemented("glob".to_string()))
+    }
+
+    async fn initialize(&self) -> Result<(), AgentError> {
+        *self.initialize_calls.lock().expect("initialize mutex") += 1;
+        if self.fail_initialize {
+            return Err(AgentError::ExecutionEnvironment(
+                "container failed to start".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn cleanup(&self) -> Result<(), AgentError> {
+        *self.cleanup_calls.lock().expect("cleanup mutex") += 1;
+        Ok(())
+    }
+
+    fn working_directory(&self) -> &Path {
+        Path::new(".")
+    }
+
+    fn platform(&self) -> &str {
+        "linux"
+    }
+
+    fn os_version(&self) -> &str {
+        "test"
+    }
+}
+
 fn test_usage() -> Usage {
     Usage {
         input_tokens: 1,
@@ -295,9 +445,16 @@ fn build_test_client(responses: Vec<Response>) -> (Arc<Client>, Arc<Mutex<Vec<Re
 fn build_test_client_with_delay(
     responses: Vec<Response>,
     delay_ms: u64,
+) -> (Arc<Client>, Arc<Mutex<Vec<Request>>>) {
+    build_test_client_with_results(responses.into_iter().map(Ok).collect(), delay_ms)
+}
+
+fn build_test_client_with_results(
+    results: Vec<Result<Response, SDKError>>,
+    delay_ms: u64,
 ) -> (Arc<Client>, Arc<Mutex<Vec<Request>>>) {
     let adapter = Arc::new(SequenceAdapter {
-        responses: Arc::new(Mutex::new(VecDeque::from(responses))),
+        responses: Arc::new(Mutex::new(VecDeque::from(results))),
         requests: Arc::new(Mutex::new(Vec::new())),
         delay_ms,
     });
@@ -340,6 +497,9 @@ fn tool_registry_with_named_echoes(names: &[&str]) -> Arc<ToolRegistry> {
                 }),
             },
             executor,
+            structured_output: false,
+            output_envelope: false,
+            metadata: ToolMetadata::default(),
         });
     }
     Arc::new(tool_registry)
@@ -355,14 +515,9 @@ fn write_test_file(path: &Path, content: &str) {
 #[test]
 fn session_new_emits_session_start() {
     let emitter = Arc::new(BufferedEventEmitter::default());
-    let profile = Arc::new(StaticProviderProfile {
-        id: "openai".to_string(),
-        model: "gpt-5.2-codex".to_string(),
-        base_system_prompt: "base".to_string(),
-        tool_registry: Arc::new(ToolRegistry::default()),
-        provider_options: None,
-        capabilities: ProviderCapabilities::default(),
-    });
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("openai", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
     let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
     let client = Arc::new(Client::default());
     let session = Session::new_with_emitter(
@@ -380,39 +535,241 @@ fn session_new_emits_session_start() {
     assert_eq!(events[0].kind, EventKind::SessionStart);
 }
 
-#[test]
-fn session_new_with_required_cxdb_failure_returns_error() {
-    let profile = Arc::new(StaticProviderProfile {
-        id: "openai".to_string(),
-        model: "gpt-5.2-codex".to_string(),
-        base_system_prompt: "base".to_string(),
-        tool_registry: Arc::new(ToolRegistry::default()),
-        provider_options: None,
-        capabilities: ProviderCapabilities::default(),
-    });
+#[tokio::test(flavor = "current_thread")]
+async fn session_initialize_with_required_cxdb_failure_returns_error() {
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("openai", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
     let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
     let client = Arc::new(Client::default());
     let mut config = SessionConfig::default();
     config.cxdb_persistence = CxdbPersistenceMode::Required;
     let store = Arc::new(RecordingPersistence::with_failures(true, false));
 
-    let error = Session::new_with_persistence(profile, env, client, config, Some(store))
-        .err()
-        .expect("required cxdb create failure should fail constructor");
+    let mut session = Session::new_with_persistence(profile, env, client, config, Some(store))
+        .expect("constructor should defer persistence");
+    let error = session
+        .initialize()
+        .await
+        .expect_err("required cxdb create failure should fail initialize");
     assert!(error.to_string().contains("cxdb persistence failed"));
 }
 
-#[test]
-fn session_new_with_off_cxdb_failure_succeeds() {
+/// Writer whose leftover-attach reconcile always fails, like an unreadable
+/// journal file.
+struct FailingReconcilePersistence {
+    inner: RecordingPersistence,
+}
+
+#[async_trait]
+impl SessionPersistenceWriter for FailingReconcilePersistence {
+    async fn create_context(
+        &self,
+        base_turn_id: Option<CxdbTurnId>,
+    ) -> Result<CxdbStoreContext, CxdbClientError> {
+        self.inner.create_context(base_turn_id).await
+    }
+
+    async fn append_turn(
+        &self,
+        request: CxdbAppendTurnRequest,
+    ) -> Result<CxdbStoredTurn, CxdbClientError> {
+        self.inner.append_turn(request).await
+    }
+
+    async fn get_head(&self, context_id: &String) -> Result<CxdbStoredTurnRef, CxdbClientError> {
+        self.inner.get_head(context_id).await
+    }
+
+    async fn reconcile_pending_attaches(
+        &self,
+    ) -> Result<CxdbAttachReconcileReport, CxdbClientError> {
+        Err(CxdbClientError::Backend(
+            "failed to lock attach journal: permission denied".to_string(),
+        ))
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn session_initialize_with_failing_attach_reconcile_expected_warning_not_error() {
+    let emitter = Arc::new(BufferedEventEmitter::default());
+    let mut session = Session::new_with_emitter_and_persistence(
+        Arc::new(StaticProviderProfile::minimal("openai", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        Arc::new(Client::default()),
+        SessionConfig {
+            cxdb_persistence: CxdbPersistenceMode::Required,
+            ..SessionConfig::default()
+        },
+        emitter.clone(),
+        Some(Arc::new(FailingReconcilePersistence {
+            inner: RecordingPersistence::default(),
+        })),
+    )
+    .expect("session should initialize");
+
+    session
+        .initialize()
+        .await
+        .expect("reconcile failure should not fail initialize");
+
+    let warning = emitter
+        .snapshot()
+        .into_iter()
+        .find(|event| event.kind == EventKind::Warning)
+        .expect("reconcile failure should warn");
+    assert!(
+        warning
+            .data
+            .get_str("message")
+            .is_some_and(|message| message.contains("reconcile_pending_attaches failed"))
+    );
+}
+
+/// Writer that completes every call on the runtime it was created on, like a
+/// client bound to its reactor. Blocking that runtime's thread deadlocks it.
+struct RuntimeBoundPersistence {
+    handle: tokio::runtime::Handle,
+    inner: Arc<RecordingPersistence>,
+}
+
+#[async_trait]
+impl SessionPersistenceWriter for RuntimeBoundPersistence {
+    async fn create_context(
+        &self,
+        base_turn_id: Option<CxdbTurnId>,
+    ) -> Result<CxdbStoreContext, CxdbClientError> {
+        let inner = self.inner.clone();
+        self.handle
+            .spawn(async move { inner.create_context(base_turn_id).await })
+            .await
+            .expect("create task should complete")
+    }
+
+    async fn append_turn(
+        &self,
+        request: CxdbAppendTurnRequest,
+    ) -> Result<CxdbStoredTurn, CxdbClientError> {
+        let inner = self.inner.clone();
+        self.handle
+            .spawn(async move { inner.append_turn(request).await })
+            .await
+            .expect("append task should complete")
+    }
+
+    async fn get_head(&self, context_id: &String) -> Result<CxdbStoredTurnRef, CxdbClientError> {
+        self.inner.get_head(context_id).await
+    }
+
+    async fn capture_upload_workspace(
+        &self,
+        workspace_root: &Path,
+        policy: &CxdbFsSnapshotPolicy,
+    ) -> Result<CxdbFsSnapshotCapture, CxdbClientError> {
+        self.inner
+            .capture_upload_workspace(workspace_root, policy)
+            .await
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn session_start_persists_on_initialize_without_blocking_runtime() {
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("openai", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let client = Arc::new(Client::default());
+    let mut config = SessionConfig::default();
+    config.cxdb_persistence = CxdbPersistenceMode::Required;
+    let inner = Arc::new(RecordingPersistence::default());
+    let store = Arc::new(RuntimeBoundPersistence {
+        handle: tokio::runtime::Handle::current(),
+        inner: inner.clone(),
+    });
+
+    let mut session = Session::new_with_persistence(profile, env, client, config, Some(store))
+        .expect("session should initialize");
+    assert!(inner.appended().is_empty());
+
+    tokio::time::timeout(std::time::Duration::from_secs(5), session.initialize())
+        .await
+        .expect("initialize should not deadlock")
+        .expect("initialize should succeed");
+    let appended = inner.appended();
+    assert_eq!(appended.len(), 1);
+    let record: SessionLifecycleRecord =
+        decode_typed_record(&appended[0].payload).expect("lifecycle record should decode");
+    assert_eq!(record.kind, "started");
+
+    session.close().expect("close should succeed");
+    session
+        .flush_pending_persistence()
+        .await
+        .expect("flush should succeed");
+    let kinds: Vec<String> = inner
+        .appended()
+        .iter()
+        .filter_map(|request| {
+            decode_typed_record::<SessionLifecycleRecord>(&request.payload)
+                .ok()
+                .map(|record| record.kind)
+        })
+        .collect();
+    assert_eq!(kinds, vec!["started".to_string(), "ended".to_string()]);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn monotonic_test_clock_gives_rapid_turns_increasing_timestamps() {
     let profile = Arc::new(StaticProviderProfile {
-        id: "openai".to_string(),
+        id: "test".to_string(),
         model: "gpt-5.2-codex".to_string(),
         base_system_prompt: "base".to_string(),
-        tool_registry: Arc::new(ToolRegistry::default()),
+        tool_registry: tool_registry_with_echo(),
         provider_options: None,
         capabilities: ProviderCapabilities::default(),
     });
     let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let (client, _) = build_test_client(vec![
+        tool_call_response(
+            "resp-1",
+            "call-1",
+            "echo_tool",
+            serde_json::json!({"value":"hello"}),
+        ),
+        text_response("resp-2", "done"),
+    ]);
+    let mut session = Session::new(profile, env, client, SessionConfig::default())
+        .expect("session should initialize");
+    session.set_clock(Arc::new(MonotonicTestClock::new(
+        std::time::Duration::from_secs(1_700_000_000),
+        std::time::Duration::from_millis(1),
+    )));
+
+    session.submit("hi").await.expect("submit should succeed");
+
+    let timestamps: Vec<f64> = session
+        .history()
+        .iter()
+        .map(|turn| match turn {
+            Turn::User(turn) => turn.timestamp.clone(),
+            Turn::Assistant(turn) => turn.timestamp.clone(),
+            Turn::ToolResults(turn) => turn.timestamp.clone(),
+            Turn::System(turn) => turn.timestamp.clone(),
+            Turn::Steering(turn) => turn.timestamp.clone(),
+        })
+        .map(|timestamp| timestamp.parse().expect("timestamp should be numeric"))
+        .collect();
+    assert!(timestamps.len() >= 4);
+    assert_eq!(timestamps[0], 1_700_000_000.0);
+    assert!(timestamps.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn session_new_with_off_cxdb_failure_succeeds() {
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("openai", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
     let client = Arc::new(Client::default());
     let config = SessionConfig::default();
     let store = Arc::new(RecordingPersistence::with_failures(true, false));
@@ -454,6 +811,10 @@ async fn submit_with_cxdb_persistence_persists_turns_and_tool_events() {
         .await
         .expect("submit should succeed with cxdb persistence");
     session.close().expect("close should succeed");
+    session
+        .flush_pending_persistence()
+        .await
+        .expect("flush should succeed");
 
     let appended = store.appended();
     assert!(!appended.is_empty());
@@ -493,84 +854,932 @@ async fn submit_with_cxdb_persistence_persists_turns_and_tool_events() {
 }
 
 #[tokio::test(flavor = "current_thread")]
-async fn submit_with_fs_snapshot_policy_adds_fs_lineage_to_persisted_payloads() {
-    let profile = Arc::new(StaticProviderProfile {
-        id: "test".to_string(),
-        model: "gpt-5.2-codex".to_string(),
-        base_system_prompt: "base".to_string(),
-        tool_registry: Arc::new(ToolRegistry::default()),
-        provider_options: None,
-        capabilities: ProviderCapabilities::default(),
-    });
-    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
-    let (client, _) = build_test_client(vec![text_response("resp-1", "done")]);
-    let mut config = SessionConfig::default();
-    config.cxdb_persistence = CxdbPersistenceMode::Required;
-    config.fs_snapshot_policy = Some(CxdbFsSnapshotPolicy::default());
-    let store = Arc::new(RecordingPersistence::default());
-    let mut session =
-        Session::new_with_persistence(profile, env, client, config, Some(store.clone()))
-            .expect("session should initialize");
+async fn persistence_transient_append_failure_expected_retried_and_written() {
+    let build = |retries: u32, store: Arc<RecordingPersistence>| {
+        let (client, _) = build_test_client(vec![text_response("resp-1", "done")]);
+        Session::new_with_persistence(
+            Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+            Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+            client,
+            SessionConfig {
+                cxdb_persistence: CxdbPersistenceMode::Required,
+                persistence_retry_attempts: retries,
+                persistence_retry_backoff_ms: 1,
+                ..SessionConfig::default()
+            },
+            Some(store),
+        )
+        .expect("session should initialize")
+    };
 
+    let store = Arc::new(RecordingPersistence::default());
+    let mut session = build(2, store.clone());
     session
-        .submit("hi")
+        .flush_pending_persistence()
         .await
-        .expect("submit should succeed with fs snapshot policy");
-
-    let appended = store.appended();
-    assert!(!appended.is_empty());
+        .expect("session start should persist");
+    *store.transient_append_failures.lock().expect("mutex") = 1;
+    session.submit("hi").await.expect("submit should succeed");
     assert!(
-        appended
+        store
+            .appended()
             .iter()
-            .all(|request| request.fs_root_hash.is_some())
+            .any(|request| request.type_id == "forge.agent.user_turn")
     );
 
-    let first = &appended[0];
-    if first.type_id == "forge.agent.session_lifecycle" {
-        let record: SessionLifecycleRecord =
-            decode_typed_record(&first.payload).expect("first payload should decode");
-        assert!(record.fs_root_hash.is_some());
-        assert!(record.snapshot_policy_id.is_some());
-        assert!(record.snapshot_stats.is_some());
-    } else {
-        let record: AgentTurnRecord =
-            decode_typed_record(&first.payload).expect("first payload should decode");
-        assert!(record.fs_root_hash.is_some());
-        assert!(record.snapshot_policy_id.is_some());
-        assert!(record.snapshot_stats.is_some());
-    }
+    let store = Arc::new(RecordingPersistence::default());
+    *store.transient_append_failures.lock().expect("mutex") = 1;
+    let mut session = build(0, store.clone());
+    session
+        .submit("hi")
+        .await
+        .expect_err("without retries the failed append should surface");
 }
 
-#[test]
-fn session_rejects_steer_when_closed() {
-    let profile = Arc::new(StaticProviderProfile {
-        id: "openai".to_string(),
-        model: "gpt-5.2-codex".to_string(),
-        base_system_prompt: "base".to_string(),
-        tool_registry: Arc::new(ToolRegistry::default()),
-        provider_options: None,
-        capabilities: ProviderCapabilities::default(),
-    });
-    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
-    let client = Arc::new(Client::default());
-    let mut session =
-        Session::new(profile, env, client, SessionConfig::default()).expect("new session");
-    session.close().expect("close should succeed");
-
-    let err = session.steer("halt").expect_err("steer should fail");
-    assert!(matches!(err, AgentError::Session(SessionError::Closed)));
+#[tokio::test(flavor = "current_thread")]
+async fn persistence_permanent_append_failure_expected_not_retried() {
+    let (client, _) = build_test_client(vec![]);
+    let store = Arc::new(RecordingPersistence::with_failures(false, true));
+    let mut session = Session::new_with_persistence(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig {
+            cxdb_persistence: CxdbPersistenceMode::Required,
+            persistence_retry_attempts: 2,
+            persistence_retry_backoff_ms: 1,
+            ..SessionConfig::default()
+        },
+        Some(store.clone()),
+    )
+    .expect("session should initialize");
+
+    session
+        .flush_pending_persistence()
+        .await
+        .expect_err("the failed append should surface");
+    assert_eq!(*store.append_attempts.lock().expect("mutex"), 1);
 }
 
-#[test]
-fn session_state_enforces_spec_transitions() {
+fn buffered_persistence_session(store: Arc<RecordingPersistence>, responses: usize) -> Session {
+    let (client, _) = build_test_client(
+        (0..responses)
+            .map(|index| text_response(&format!("resp-{index}"), "done"))
+            .collect(),
+    );
+    Session::new_with_persistence(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig {
+            cxdb_persistence: CxdbPersistenceMode::Buffered,
+            persistence_retry_attempts: 0,
+            ..SessionConfig::default()
+        },
+        Some(store),
+    )
+    .expect("session should initialize")
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn buffered_persistence_close_expected_all_writes_landed_in_order() {
+    let store = Arc::new(RecordingPersistence::with_failures(false, false));
+    let mut session = buffered_persistence_session(store.clone(), 2);
+
+    session
+        .submit("first")
+        .await
+        .expect("submit should succeed");
+    session
+        .submit("second")
+        .await
+        .expect("submit should succeed");
+    session.close().expect("close should succeed");
+    session
+        .flush_pending_persistence()
+        .await
+        .expect("buffered writes should flush");
+
+    let appended = store.appended();
+    let type_ids: Vec<&str> = appended
+        .iter()
+        .map(|request| request.type_id.as_str())
+        .collect();
+    assert_eq!(
+        type_ids,
+        vec![
+            "forge.agent.session_lifecycle",
+            "forge.agent.user_turn",
+            "forge.agent.assistant_turn",
+            "forge.agent.user_turn",
+            "forge.agent.assistant_turn",
+            "forge.agent.session_lifecycle",
+        ]
+    );
+    for (index, request) in appended.iter().enumerate() {
+        let expected_parent = (index > 0).then(|| index.to_string());
+        assert_eq!(request.parent_turn_id, expected_parent);
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn buffered_persistence_append_failure_expected_error_at_flush() {
+    let store = Arc::new(RecordingPersistence::with_failures(false, true));
+    let mut session = buffered_persistence_session(store, 1);
+
+    session
+        .submit("hi")
+        .await
+        .expect("buffered submit should not wait on failed writes");
+    session.close().expect("close should succeed");
+    let error = session
+        .flush_pending_persistence()
+        .await
+        .expect_err("flush should surface the failed append");
+    assert!(matches!(
+        error,
+        AgentError::Session(SessionError::Persistence(_))
+    ));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn buffered_persistence_after_failure_expected_rejected_until_reset() {
+    let store = Arc::new(RecordingPersistence::with_failures(false, false));
+    let mut session = buffered_persistence_session(store.clone(), 2);
+    session
+        .flush_pending_persistence()
+        .await
+        .expect("session start should persist");
+
+    *store.transient_append_failures.lock().expect("mutex") = 1;
+    session
+        .submit("first")
+        .await
+        .expect("buffered submit should not wait on failed writes");
+    session
+        .flush_pending_persistence()
+        .await
+        .expect_err("flush should surface the failed append");
+    session
+        .submit("second")
+        .await
+        .expect_err("appends stay rejected until reset");
+
+    session
+        .reset_buffered_persistence()
+        .await
+        .expect("reset should refetch the head");
+    session
+        .shutdown()
+        .await
+        .expect("shutdown should flush the session end");
+
+    let appended = store.appended();
+    let type_ids: Vec<&str> = appended
+        .iter()
+        .map(|request| request.type_id.as_str())
+        .collect();
+    assert_eq!(
+        type_ids,
+        vec![
+            "forge.agent.session_lifecycle",
+            "forge.agent.session_lifecycle",
+        ]
+    );
+    assert_eq!(appended[1].parent_turn_id.as_deref(), Some("1"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn flush_persistence_buffered_expected_store_has_all_writes() {
+    let store = Arc::new(RecordingPersistence::with_failures(false, false));
+    let mut session = buffered_persistence_session(store.clone(), 1);
+
+    session.submit("hi").await.expect("submit should succeed");
+    session
+        .flush_persistence()
+        .await
+        .expect("flush should drain buffered writes");
+
+    let type_ids: Vec<String> = store
+        .appended()
+        .into_iter()
+        .map(|request| request.type_id)
+        .collect();
+    assert_eq!(
+        type_ids,
+        vec![
+            "forge.agent.session_lifecycle",
+            "forge.agent.user_turn",
+            "forge.agent.assistant_turn",
+        ]
+    );
+    let snapshot = session
+        .persistence_snapshot()
+        .await
+        .expect("snapshot should succeed");
+    assert_eq!(snapshot.head_turn_id.as_deref(), Some("3"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn flush_persistence_off_expected_noop() {
+    let (client, _) = build_test_client(vec![text_response("resp-1", "done")]);
+    let store = Arc::new(RecordingPersistence::default());
+    let mut session = Session::new_with_persistence(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig::default(),
+        Some(store.clone()),
+    )
+    .expect("session should initialize");
+
+    session.submit("hi").await.expect("submit should succeed");
+    session
+        .flush_persistence()
+        .await
+        .expect("flush should be a no-op");
+    assert!(store.appended().is_empty());
+}
+
+fn empty_then_text_session(
+    on_empty_response: EmptyResponsePolicy,
+) -> (Session, Arc<Mutex<Vec<Request>>>) {
+    let (client, requests) = build_test_client(vec![
+        text_response("resp-empty", ""),
+        text_response("resp-text", "done"),
+    ]);
+    let session = Session::new(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig {
+            on_empty_response,
+            ..SessionConfig::default()
+        },
+    )
+    .expect("session should initialize");
+    (session, requests)
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn empty_response_treat_as_complete_expected_empty_turn_and_idle() {
+    let (mut session, requests) = empty_then_text_session(EmptyResponsePolicy::TreatAsComplete);
+
+    session.submit("hi").await.expect("submit should succeed");
+
+    assert_eq!(requests.lock().expect("requests mutex").len(), 1);
+    assert_eq!(session.last_assistant_text(), Some(""));
+    assert_eq!(session.state(), &SessionState::Idle);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn empty_response_retry_expected_reissued_until_text() {
+    let (mut session, requests) = empty_then_text_session(EmptyResponsePolicy::Retry(2));
+
+    session.submit("hi").await.expect("submit should succeed");
+
+    assert_eq!(requests.lock().expect("requests mutex").len(), 2);
+    assert_eq!(session.assistant_turns().count(), 1);
+    assert_eq!(session.last_assistant_text(), Some("done"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn empty_response_error_expected_typed_error_and_idle() {
+    let (mut session, requests) = empty_then_text_session(EmptyResponsePolicy::Error);
+
+    let error = session
+        .submit("hi")
+        .await
+        .expect_err("empty response should fail the submit");
+
+    assert!(matches!(
+        error,
+        AgentError::Session(SessionError::EmptyResponse { attempts: 1 })
+    ));
+    assert_eq!(requests.lock().expect("requests mutex").len(), 1);
+    assert_eq!(session.assistant_turns().count(), 0);
+    assert_eq!(session.state(), &SessionState::Idle);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn agent_status_tool_expected_report_session_budgets_mid_session() {
+    let (client, requests) = build_test_client(vec![
+        tool_call_response(
+            "resp-1",
+            "call-1",
+            "echo_tool",
+            serde_json::json!({ "value": "x" }),
+        ),
+        tool_call_response(
+            "resp-2",
+            "call-2",
+            crate::AGENT_STATUS_TOOL,
+            serde_json::json!({}),
+        ),
+        text_response("resp-3", "done"),
+    ]);
     let profile = Arc::new(StaticProviderProfile {
-        id: "openai".to_string(),
+        id: "test".to_string(),
         model: "gpt-5.2-codex".to_string(),
-        base_system_prompt: "base".to_string(),
-        tool_registry: Arc::new(ToolRegistry::default()),
+        base_system_prompt: "system".to_string(),
+        tool_registry: tool_registry_with_echo(),
         provider_options: None,
         capabilities: ProviderCapabilities::default(),
     });
+    let mut session = Session::new(
+        profile,
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig {
+            max_tool_rounds_per_input: 10,
+            max_turns: 50,
+            max_session_tokens: 100,
+            ..SessionConfig::default()
+        },
+    )
+    .expect("new session");
+
+    session
+        .submit("check budgets")
+        .await
+        .expect("submit should succeed");
+
+    let status = session
+        .history()
+        .iter()
+        .find_map(|turn| match turn {
+            Turn::ToolResults(turn) => turn
+                .results
+                .iter()
+                .find(|result| result.tool_call_id == "call-2"),
+            _ => None,
+        })
+        .expect("agent_status result");
+    assert!(!status.is_error);
+    let report: serde_json::Value =
+        serde_json::from_str(status.content.as_str().expect("string output")).expect("status json");
+    // Dispatched in round 2: user, assistant, results and assistant turns
+    // recorded, two 2-token responses received.
+    assert_eq!(
+        report,
+        serde_json::json!({
+            "tool_rounds_used": 2,
+            "max_tool_rounds_per_input": 10,
+            "turns_used": 4,
+            "max_turns": 50,
+            "tokens_used": 4,
+            "max_session_tokens": 100,
+        })
+    );
+    assert_eq!(requests.lock().expect("requests mutex").len(), 3);
+    assert_eq!(session.session_tokens_used(), 6);
+    assert_eq!(session.history().len(), 6);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn max_session_tokens_expected_halt_before_call_over_budget() {
+    // Every test response reports 2 total tokens.
+    let (client, requests) = build_test_client(
+        (1..=4)
+            .map(|index| {
+                tool_call_response(
+                    &format!("resp-{index}"),
+                    &format!("call-{index}"),
+                    "echo_tool",
+                    serde_json::json!({ "value": "x" }),
+                )
+            })
+            .chain([text_response("resp-5", "should_not_be_called")])
+            .collect(),
+    );
+    let profile = Arc::new(StaticProviderProfile {
+        id: "test".to_string(),
+        model: "gpt-5.2-codex".to_string(),
+        base_system_prompt: "system".to_string(),
+        tool_registry: tool_registry_with_echo(),
+        provider_options: None,
+        capabilities: ProviderCapabilities::default(),
+    });
+    let emitter = Arc::new(BufferedEventEmitter::default());
+    let mut session = Session::new_with_emitter(
+        profile,
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig {
+            max_session_tokens: 5,
+            ..SessionConfig::default()
+        },
+        emitter.clone(),
+    )
+    .expect("new session");
+
+    let result = session
+        .submit_with_result("run tools", SubmitOptions::default())
+        .await
+        .expect("submit should succeed");
+
+    // Two 2-token calls; a third projected at 2 more would cross 5.
+    assert_eq!(result.stopped_reason, StopReason::TokenBudgetExceeded);
+    assert_eq!(session.state(), &SessionState::Idle);
+    assert_eq!(requests.lock().expect("requests mutex").len(), 2);
+    assert_eq!(session.session_tokens_used(), 4);
+    assert!(
+        emitter
+            .snapshot()
+            .iter()
+            .any(|event| event.kind == EventKind::TokenBudgetExceeded)
+    );
+
+    let result = session
+        .submit_with_result("keep going", SubmitOptions::default())
+        .await
+        .expect("submit should succeed");
+    assert_eq!(result.stopped_reason, StopReason::TokenBudgetExceeded);
+    assert_eq!(requests.lock().expect("requests mutex").len(), 2);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn max_session_tokens_checkpoint_resume_expected_usage_kept() {
+    // Every test response reports 2 total tokens.
+    let (client, requests) = build_test_client(vec![
+        text_response("resp-1", "first"),
+        text_response("resp-2", "second"),
+        text_response("resp-3", "should_not_be_called"),
+    ]);
+    let profile = Arc::new(StaticProviderProfile::minimal("test", "test-model"));
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let emitter = Arc::new(BufferedEventEmitter::default());
+    let mut session = Session::new_with_emitter(
+        profile.clone(),
+        env.clone(),
+        client.clone(),
+        SessionConfig {
+            max_session_tokens: 5,
+            ..SessionConfig::default()
+        },
+        emitter.clone(),
+    )
+    .expect("new session");
+    session.submit("first input").await.expect("first submit");
+
+    let checkpoint = session.checkpoint().expect("checkpoint should succeed");
+    assert_eq!(checkpoint.session_tokens_used, 2);
+    let mut restored = Session::from_checkpoint(checkpoint, profile, env, client, emitter)
+        .expect("restore should succeed");
+    assert_eq!(restored.session_tokens_used(), 2);
+
+    restored
+        .submit("second input")
+        .await
+        .expect("second submit");
+    let result = restored
+        .submit_with_result("third input", SubmitOptions::default())
+        .await
+        .expect("third submit should return");
+    assert_eq!(result.stopped_reason, StopReason::TokenBudgetExceeded);
+    assert_eq!(restored.session_tokens_used(), 4);
+    assert_eq!(requests.lock().expect("requests mutex").len(), 2);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn compact_persistence_writes_compact_turn_payloads() {
+    let (client, _) = build_test_client(vec![text_response("resp-1", "done")]);
+    let store = Arc::new(RecordingPersistence::default());
+    let mut session = Session::new_with_persistence(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig {
+            cxdb_persistence: CxdbPersistenceMode::Required,
+            compact_persistence: true,
+            ..SessionConfig::default()
+        },
+        Some(store.clone()),
+    )
+    .expect("session should initialize");
+
+    session.submit("hi").await.expect("submit should succeed");
+
+    let persisted: Vec<Turn> = store
+        .appended()
+        .iter()
+        .filter(|request| request.type_id.ends_with("_turn"))
+        .map(|request| {
+            assert_eq!(request.type_version, 3);
+            let record = decode_typed_record::<AgentTurnRecord>(&request.payload)
+                .expect("turn record should decode");
+            assert!(record.turn.get("k").is_some());
+            let compact: crate::CompactTurn =
+                serde_json::from_value(record.turn).expect("compact turn should decode");
+            Turn::from_compact(compact)
+        })
+        .collect();
+    assert_eq!(persisted, session.history().to_vec());
+}
+
+fn seeded_conversation() -> Vec<Turn> {
+    vec![
+        Turn::User(UserTurn::new(
+            "list files",
+            "2026-01-01T00:00:00.000Z".to_string(),
+        )),
+        Turn::Assistant(AssistantTurn::new(
+            "",
+            vec![build_tool_call(
+                "call-1",
+                "echo_tool",
+                serde_json::json!({"value": "a.txt"}),
+            )],
+            None,
+            Usage::default(),
+            None,
+            "2026-01-01T00:00:00.000Z".to_string(),
+        )),
+        Turn::ToolResults(ToolResultsTurn::new(
+            vec![ToolResultTurn {
+                tool_call_id: "call-1".to_string(),
+                content: Value::String("a.txt".to_string()),
+                is_error: false,
+            }],
+            "2026-01-01T00:00:00.000Z".to_string(),
+        )),
+        Turn::Assistant(AssistantTurn::new(
+            "There is one file: a.txt",
+            vec![],
+            None,
+            Usage::default(),
+            None,
+            "2026-01-01T00:00:00.000Z".to_string(),
+        )),
+    ]
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn seed_history_then_submit_expected_continuation_sees_seeded_turns() {
+    let (client, requests) = build_test_client(vec![text_response("resp-1", "done")]);
+    let mut session = Session::new(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig::default(),
+    )
+    .expect("session should initialize");
+
+    session
+        .seed_history(seeded_conversation())
+        .expect("coherent history should seed");
+    assert!(requests.lock().expect("requests mutex").is_empty());
+
+    session
+        .submit("what was in it?")
+        .await
+        .expect("submit should succeed");
+
+    assert_eq!(session.history()[..4], seeded_conversation()[..4]);
+    let requests = requests.lock().expect("requests mutex");
+    assert_eq!(requests.len(), 1);
+    let texts: Vec<String> = requests[0]
+        .messages
+        .iter()
+        .map(|message| message.text())
+        .collect();
+    assert!(texts.iter().any(|text| text == "list files"));
+    assert!(texts.iter().any(|text| text == "There is one file: a.txt"));
+    assert_eq!(texts.last().map(String::as_str), Some("what was in it?"));
+
+    let error = session
+        .seed_history(seeded_conversation())
+        .expect_err("submitted session should reject seeding");
+    assert!(matches!(error, SessionError::InvalidHistory(_)));
+}
+
+#[test]
+fn seed_history_incoherent_tool_results_expected_rejected() {
+    let (client, _) = build_test_client(vec![]);
+    let mut session = Session::new(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig::default(),
+    )
+    .expect("session should initialize");
+
+    let mut dangling = seeded_conversation();
+    dangling.truncate(2);
+    let mut orphaned = seeded_conversation();
+    orphaned.remove(1);
+
+    for turns in [dangling, orphaned] {
+        let error = session
+            .seed_history(turns)
+            .expect_err("incoherent history should be rejected");
+        assert!(matches!(error, SessionError::InvalidHistory(_)));
+    }
+    assert!(session.history().is_empty());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn seed_history_with_backfill_expected_single_backfill_record() {
+    let (client, _) = build_test_client(vec![]);
+    let store = Arc::new(RecordingPersistence::default());
+    let mut session = Session::new_with_persistence(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig {
+            cxdb_persistence: CxdbPersistenceMode::Required,
+            ..SessionConfig::default()
+        },
+        Some(store.clone()),
+    )
+    .expect("session should initialize");
+
+    session
+        .seed_history_with_backfill(seeded_conversation())
+        .await
+        .expect("backfill should succeed");
+
+    let appended = store.appended();
+    assert!(
+        appended
+            .iter()
+            .all(|request| !request.type_id.ends_with("_turn"))
+    );
+    let backfills: Vec<_> = appended
+        .iter()
+        .filter(|request| request.type_id == "forge.agent.history_backfill")
+        .collect();
+    assert_eq!(backfills.len(), 1);
+    let record = decode_typed_record::<AgentTurnRecord>(&backfills[0].payload)
+        .expect("backfill record should decode");
+    let turns: Vec<Turn> =
+        serde_json::from_value(record.turn).expect("backfill batch should decode");
+    assert_eq!(turns, seeded_conversation());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn seed_history_with_backfill_failing_store_expected_history_rolled_back() {
+    let (client, _) = build_test_client(vec![]);
+    let store = Arc::new(RecordingPersistence::default());
+    *store.fail_append_of_type.lock().expect("mutex") =
+        Some("forge.agent.history_backfill".to_string());
+    let mut session = Session::new_with_persistence(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig {
+            cxdb_persistence: CxdbPersistenceMode::Required,
+            ..SessionConfig::default()
+        },
+        Some(store.clone()),
+    )
+    .expect("session should initialize");
+
+    session
+        .seed_history_with_backfill(seeded_conversation())
+        .await
+        .expect_err("backfill should fail");
+
+    assert!(session.history().is_empty());
+    assert!(
+        store
+            .appended()
+            .iter()
+            .all(|request| request.type_id != "forge.agent.history_backfill")
+    );
+
+    *store.fail_append_of_type.lock().expect("mutex") = None;
+    session
+        .seed_history_with_backfill(seeded_conversation())
+        .await
+        .expect("seeding should be retryable after a failed backfill");
+    assert_eq!(session.history(), seeded_conversation().as_slice());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn configured_session_id_is_shared_and_used_by_persistence() {
+    let config = SessionConfig {
+        session_id: Some("golden-session".to_string()),
+        cxdb_persistence: CxdbPersistenceMode::Required,
+        ..SessionConfig::default()
+    };
+    let build = |store: Arc<RecordingPersistence>| {
+        let (client, _) = build_test_client(vec![text_response("resp-1", "done")]);
+        Session::new_with_persistence(
+            Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+            Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+            client,
+            config.clone(),
+            Some(store),
+        )
+        .expect("session should initialize")
+    };
+    let store = Arc::new(RecordingPersistence::default());
+    let mut first = build(store.clone());
+    let second = build(Arc::new(RecordingPersistence::default()));
+    assert_eq!(first.id(), "golden-session");
+    assert_eq!(first.id(), second.id());
+
+    first.submit("hi").await.expect("submit should succeed");
+
+    let appended = store.appended();
+    assert!(!appended.is_empty());
+    for request in appended
+        .iter()
+        .filter(|request| request.type_id == "forge.agent.session_lifecycle")
+    {
+        let record = decode_typed_record::<SessionLifecycleRecord>(&request.payload)
+            .expect("lifecycle record should decode");
+        assert_eq!(record.session_id, "golden-session");
+    }
+    assert!(
+        appended
+            .iter()
+            .all(|request| request.idempotency_key.contains("golden-session"))
+    );
+}
+
+#[test]
+fn id_seed_derives_stable_distinct_session_ids() {
+    let build = |seed: &str| {
+        let (client, _) = build_test_client(vec![]);
+        Session::new(
+            Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+            Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+            client,
+            SessionConfig {
+                id_seed: Some(seed.to_string()),
+                ..SessionConfig::default()
+            },
+        )
+        .expect("new session")
+    };
+
+    assert_eq!(build("run-a").id(), build("run-a").id());
+    assert_ne!(build("run-a").id(), build("run-b").id());
+    assert!(Uuid::parse_str(build("run-a").id()).is_ok());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn subagent_ids_stay_unique_with_fixed_parent_id() {
+    let (client, _) = build_test_client(vec![
+        text_response("child-1", "one"),
+        text_response("child-2", "two"),
+    ]);
+    let config = SessionConfig {
+        session_id: Some("fixed-parent".to_string()),
+        ..SessionConfig::default()
+    };
+    let mut session = Session::new(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        config,
+    )
+    .expect("new session");
+
+    for call_id in ["call-1", "call-2"] {
+        let spawn = session
+            .execute_subagent_tool_call(build_tool_call(
+                call_id,
+                "spawn_agent",
+                serde_json::json!({ "task": "child task" }),
+            ))
+            .await
+            .expect("spawn should execute");
+        assert!(!spawn.is_error);
+    }
+
+    let ids: Vec<&String> = session.subagents().keys().collect();
+    assert_eq!(ids.len(), 2);
+    assert_ne!(ids[0], ids[1]);
+    assert!(ids.iter().all(|id| id.as_str() != "fixed-parent"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn submit_with_fs_snapshot_policy_adds_fs_lineage_to_persisted_payloads() {
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("test", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let (client, _) = build_test_client(vec![text_response("resp-1", "done")]);
+    let mut config = SessionConfig::default();
+    config.cxdb_persistence = CxdbPersistenceMode::Required;
+    config.fs_snapshot_policy = Some(CxdbFsSnapshotPolicy::default());
+    let store = Arc::new(RecordingPersistence::default());
+    let mut session =
+        Session::new_with_persistence(profile, env, client, config, Some(store.clone()))
+            .expect("session should initialize");
+
+    session
+        .submit("hi")
+        .await
+        .expect("submit should succeed with fs snapshot policy");
+
+    let appended = store.appended();
+    assert!(!appended.is_empty());
+    assert!(
+        appended
+            .iter()
+            .all(|request| request.fs_root_hash.is_some())
+    );
+
+    let first = &appended[0];
+    if first.type_id == "forge.agent.session_lifecycle" {
+        let record: SessionLifecycleRecord =
+            decode_typed_record(&first.payload).expect("first payload should decode");
+        assert!(record.fs_root_hash.is_some());
+        assert!(record.snapshot_policy_id.is_some());
+        assert!(record.snapshot_stats.is_some());
+    } else {
+        let record: AgentTurnRecord =
+            decode_typed_record(&first.payload).expect("first payload should decode");
+        assert!(record.fs_root_hash.is_some());
+        assert!(record.snapshot_policy_id.is_some());
+        assert!(record.snapshot_stats.is_some());
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn on_session_end_snapshot_cadence_captures_and_attaches_once_at_close() {
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("test", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let (client, _) = build_test_client(vec![text_response("resp-1", "done")]);
+    let config = SessionConfig {
+        cxdb_persistence: CxdbPersistenceMode::Required,
+        fs_snapshot_policy: Some(CxdbFsSnapshotPolicy::default()),
+        fs_snapshot_cadence: FsSnapshotCadence::OnSessionEnd,
+        ..SessionConfig::default()
+    };
+    let store = Arc::new(RecordingPersistence::default());
+    let mut session =
+        Session::new_with_persistence(profile, env, client, config, Some(store.clone()))
+            .expect("session should initialize");
+
+    session.submit("hi").await.expect("submit should succeed");
+    assert_eq!(
+        *store.snapshot_calls.lock().expect("snapshot calls mutex"),
+        0
+    );
+
+    session.close().expect("close should succeed");
+    session
+        .flush_pending_persistence()
+        .await
+        .expect("flush should persist session end");
+    session
+        .flush_pending_persistence()
+        .await
+        .expect("second flush should be a no-op");
+
+    assert_eq!(
+        *store.snapshot_calls.lock().expect("snapshot calls mutex"),
+        1
+    );
+    let attaches = store
+        .attach_calls
+        .lock()
+        .expect("attach calls mutex")
+        .clone();
+    let appended = store.appended();
+    assert_eq!(attaches.len(), 1);
+    assert_eq!(attaches[0].1, "a".repeat(64));
+    // The snapshot lands on the session_end turn, the last one appended;
+    // the recording writer numbers turns from 0.
+    assert_eq!(
+        appended.last().map(|request| request.type_id.as_str()),
+        Some("forge.agent.session_lifecycle")
+    );
+    assert_eq!(attaches[0].0, (appended.len() - 1).to_string());
+    assert!(
+        appended
+            .iter()
+            .all(|request| request.fs_root_hash.is_none())
+    );
+}
+
+#[test]
+fn session_rejects_steer_when_closed() {
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("openai", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let client = Arc::new(Client::default());
+    let mut session =
+        Session::new(profile, env, client, SessionConfig::default()).expect("new session");
+    session.close().expect("close should succeed");
+
+    let err = session.steer("halt").expect_err("steer should fail");
+    assert!(matches!(err, AgentError::Session(SessionError::Closed)));
+}
+
+#[test]
+fn session_state_enforces_spec_transitions() {
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("openai", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
     let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
     let client = Arc::new(Client::default());
     let mut session =
@@ -601,14 +1810,9 @@ fn session_state_enforces_spec_transitions() {
 #[test]
 fn closing_session_emits_session_end_once_with_final_state() {
     let emitter = Arc::new(BufferedEventEmitter::default());
-    let profile = Arc::new(StaticProviderProfile {
-        id: "openai".to_string(),
-        model: "gpt-5.2-codex".to_string(),
-        base_system_prompt: "base".to_string(),
-        tool_registry: Arc::new(ToolRegistry::default()),
-        provider_options: None,
-        capabilities: ProviderCapabilities::default(),
-    });
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("openai", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
     let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
     let client = Arc::new(Client::default());
     let mut session = Session::new_with_emitter(
@@ -633,14 +1837,9 @@ fn closing_session_emits_session_end_once_with_final_state() {
 #[test]
 fn session_exposes_async_event_subscription() {
     let emitter = Arc::new(BufferedEventEmitter::default());
-    let profile = Arc::new(StaticProviderProfile {
-        id: "openai".to_string(),
-        model: "gpt-5.2-codex".to_string(),
-        base_system_prompt: "base".to_string(),
-        tool_registry: Arc::new(ToolRegistry::default()),
-        provider_options: None,
-        capabilities: ProviderCapabilities::default(),
-    });
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("openai", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
     let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
     let client = Arc::new(Client::default());
     let session =
@@ -721,6 +1920,52 @@ async fn submit_transitions_to_awaiting_input_for_question_then_back_to_idle_on_
     assert_eq!(requests.lock().expect("requests mutex").len(), 2);
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn emitted_events_carry_gapless_increasing_sequence_numbers() {
+    let (client, _) = build_test_client(vec![
+        tool_call_response(
+            "resp-1",
+            "call-1",
+            "echo_tool",
+            serde_json::json!({ "value": "first" }),
+        ),
+        text_response("resp-2", "done"),
+    ]);
+    let profile = Arc::new(StaticProviderProfile {
+        id: "test".to_string(),
+        model: "gpt-5.2-codex".to_string(),
+        base_system_prompt: "system".to_string(),
+        tool_registry: tool_registry_with_echo(),
+        provider_options: None,
+        capabilities: ProviderCapabilities::default(),
+    });
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let emitter = Arc::new(BufferedEventEmitter::default());
+    let mut session = Session::new_with_emitter(
+        profile,
+        env,
+        client,
+        SessionConfig::default(),
+        emitter.clone(),
+    )
+    .expect("new session");
+    let mut stream = session.subscribe_events();
+
+    session
+        .submit("run tool")
+        .await
+        .expect("submit should succeed");
+    session.close().expect("close should succeed");
+
+    let mut sequence_numbers = Vec::new();
+    while let Ok(Some(event)) = stream.try_next() {
+        sequence_numbers.push(event.sequence_no);
+    }
+    assert!(sequence_numbers.len() > 5);
+    let expected: Vec<u64> = (1..=sequence_numbers.len() as u64).collect();
+    assert_eq!(sequence_numbers, expected);
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn submit_enforces_per_input_round_limit_and_emits_turn_limit_event() {
     let (client, requests) = build_test_client(vec![
@@ -764,6 +2009,105 @@ async fn submit_enforces_per_input_round_limit_and_emits_turn_limit_event() {
     assert!(matches!(session.history()[2], Turn::ToolResults(_)));
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn submit_with_result_reports_round_limit_stop_reason() {
+    let (client, _) = build_test_client(vec![
+        tool_call_response(
+            "resp-1",
+            "call-1",
+            "echo_tool",
+            serde_json::json!({ "value": "first" }),
+        ),
+        text_response("resp-2", "should_not_be_called"),
+    ]);
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("test", "gpt-5.2-codex")
+            .with_tool_registry(tool_registry_with_echo()),
+    );
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let config = SessionConfig {
+        max_tool_rounds_per_input: 1,
+        ..SessionConfig::default()
+    };
+    let mut session = Session::new(profile, env, client, config).expect("new session");
+
+    let result = session
+        .submit_with_result("run tool", SubmitOptions::default())
+        .await
+        .expect("submit should succeed");
+
+    assert_eq!(result.stopped_reason, StopReason::RoundLimit);
+    assert_eq!(result.final_state, SessionState::Idle);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn submit_with_result_reports_natural_completion_and_awaiting_input() {
+    let (client, _) = build_test_client(vec![
+        text_response("resp-1", "Which file should I edit next?"),
+        text_response("resp-2", "Done."),
+    ]);
+    let profile = Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex"));
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let mut session =
+        Session::new(profile, env, client, SessionConfig::default()).expect("new session");
+
+    let first = session
+        .submit_with_result("start", SubmitOptions::default())
+        .await
+        .expect("first submit should succeed");
+    assert_eq!(first.stopped_reason, StopReason::AwaitingInput);
+
+    let second = session
+        .submit_with_result("Edit src/main.rs", SubmitOptions::default())
+        .await
+        .expect("second submit should succeed");
+    assert_eq!(second.stopped_reason, StopReason::NaturalCompletion);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn queued_followup_answers_awaiting_input_when_enabled() {
+    let (client, requests) = build_test_client(vec![
+        text_response("resp-1", "Which file should I edit next?"),
+        text_response("resp-2", "Done."),
+    ]);
+    let profile = Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex"));
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let config = SessionConfig {
+        followups_answer_questions: true,
+        ..SessionConfig::default()
+    };
+    let mut session = Session::new(profile, env, client, config).expect("new session");
+    session
+        .follow_up("Edit src/main.rs")
+        .expect("follow-up should queue");
+
+    let result = session
+        .submit_with_result("start", SubmitOptions::default())
+        .await
+        .expect("submit should succeed");
+
+    assert_eq!(result.stopped_reason, StopReason::NaturalCompletion);
+    assert_eq!(session.pop_followup_message(), None);
+    assert_eq!(requests.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn max_tool_rounds_per_input_zero_defers_to_profile_suggestion() {
+    let (client, _) = build_test_client(vec![]);
+    let profile = Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex"));
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let config = SessionConfig {
+        max_tool_rounds_per_input: 0,
+        ..SessionConfig::default()
+    };
+    let session = Session::new(profile, env, client, config).expect("new session");
+
+    assert_eq!(
+        session.max_tool_rounds_per_input(),
+        crate::DEFAULT_MAX_TOOL_ROUNDS_PER_INPUT
+    );
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn submit_multiple_times_keeps_history_consistent() {
     let (client, requests) = build_test_client(vec![
@@ -828,6 +2172,119 @@ async fn steering_messages_are_injected_into_history_and_next_request() {
     );
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn submit_and_collect_returns_only_turns_appended_by_that_submit() {
+    let (client, _) = build_test_client(vec![
+        text_response("resp-1", "first"),
+        text_response("resp-2", "second"),
+    ]);
+    let profile = Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex"));
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let mut session =
+        Session::new(profile, env, client, SessionConfig::default()).expect("new session");
+    session
+        .submit("first input")
+        .await
+        .expect("first submit should succeed");
+    let baseline = session.history().len();
+    session
+        .steer("Use concise output")
+        .expect("steer should queue");
+
+    let collected = session
+        .submit_and_collect("second input", SubmitOptions::default())
+        .await
+        .expect("second submit should succeed");
+
+    assert_eq!(collected.as_slice(), &session.history()[baseline..]);
+    assert!(matches!(&collected[0], Turn::User(turn) if turn.content == "second input"));
+    assert!(
+        collected
+            .iter()
+            .any(|turn| matches!(turn, Turn::Steering(_)))
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn paused_session_rejects_submit_until_resumed() {
+    let (client, requests) = build_test_client(vec![text_response("resp-1", "done")]);
+    let profile = Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex"));
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let mut session =
+        Session::new(profile, env, client, SessionConfig::default()).expect("new session");
+
+    session.pause().expect("pause should succeed");
+    assert_eq!(session.state(), &SessionState::Paused);
+    let error = session
+        .submit("hello")
+        .await
+        .expect_err("submit should fail while paused");
+    assert!(matches!(error, AgentError::Session(SessionError::Paused)));
+    assert!(requests.lock().expect("requests mutex").is_empty());
+
+    session.resume().expect("resume should succeed");
+    assert_eq!(session.state(), &SessionState::Idle);
+    session
+        .submit("hello")
+        .await
+        .expect("submit should succeed after resume");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn steering_queued_while_paused_survives_resume_and_lifecycle_persists() {
+    let (client, requests) = build_test_client(vec![text_response("resp-1", "done")]);
+    let config = SessionConfig {
+        cxdb_persistence: CxdbPersistenceMode::Required,
+        ..SessionConfig::default()
+    };
+    let store = Arc::new(RecordingPersistence::default());
+    let mut session = Session::new_with_persistence(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        config,
+        Some(store.clone()),
+    )
+    .expect("session should initialize");
+
+    session.pause().expect("pause should succeed");
+    session
+        .steer("Use concise output")
+        .expect("steer should queue while paused");
+    session
+        .follow_up("next input")
+        .expect("follow-up should queue while paused");
+    session.resume().expect("resume should succeed");
+    assert_eq!(
+        session.pop_followup_message().as_deref(),
+        Some("next input")
+    );
+
+    session
+        .submit("hello")
+        .await
+        .expect("submit should succeed");
+
+    assert!(matches!(session.history()[1], Turn::Steering(_)));
+    let requests = requests.lock().expect("requests mutex");
+    assert!(
+        requests[0]
+            .messages
+            .iter()
+            .any(|message| message.role == Role::User && message.text() == "Use concise output")
+    );
+    let kinds: Vec<String> = store
+        .appended()
+        .iter()
+        .filter_map(|request| {
+            decode_typed_record::<SessionLifecycleRecord>(&request.payload)
+                .ok()
+                .map(|record| record.kind)
+        })
+        .collect();
+    assert_eq!(kinds, vec!["started", "paused", "resumed"]);
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn follow_up_queue_triggers_new_processing_cycle_after_completion() {
     let (client, requests) = build_test_client(vec![
@@ -1010,36 +2467,291 @@ async fn submit_emits_context_usage_warning_event_when_history_exceeds_threshold
 }
 
 #[tokio::test(flavor = "current_thread")]
-async fn submit_does_not_emit_context_usage_warning_when_usage_is_below_threshold() {
-    let (client, _requests) = build_test_client(vec![text_response("resp-1", "done")]);
-    let emitter = Arc::new(BufferedEventEmitter::default());
+async fn submit_does_not_emit_context_usage_warning_when_usage_is_below_threshold() {
+    let (client, _requests) = build_test_client(vec![text_response("resp-1", "done")]);
+    let emitter = Arc::new(BufferedEventEmitter::default());
+    let profile = Arc::new(StaticProviderProfile {
+        id: "test".to_string(),
+        model: "gpt-5.2-codex".to_string(),
+        base_system_prompt: "system".to_string(),
+        tool_registry: Arc::new(ToolRegistry::default()),
+        provider_options: None,
+        capabilities: ProviderCapabilities {
+            context_window_size: 8_000,
+            ..ProviderCapabilities::default()
+        },
+    });
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let mut session = Session::new_with_emitter(
+        profile,
+        env,
+        client,
+        SessionConfig::default(),
+        emitter.clone(),
+    )
+    .expect("new session");
+
+    session.submit("hi").await.expect("submit should succeed");
+
+    let events = emitter.snapshot();
+    assert!(!events.iter().any(|event| {
+        event.kind == EventKind::Warning && event.data.get_str("category") == Some("context_usage")
+    }));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn submit_classifies_provider_context_overflow_and_stays_usable() {
+    let overflow = SDKError::Provider(forge_llm::ProviderError::new(
+        "test",
+        forge_llm::ProviderErrorKind::InvalidRequest,
+        "prompt is too long: 210000 tokens > 200000 maximum",
+    ));
+    let (client, _) =
+        build_test_client_with_results(vec![Err(overflow), Ok(text_response("resp-1", "done"))], 0);
+    let mut session = Session::new(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig::default(),
+    )
+    .expect("new session");
+
+    let error = session
+        .submit("hi")
+        .await
+        .expect_err("overflow should surface");
+
+    assert!(matches!(
+        error,
+        AgentError::Session(SessionError::ContextWindowExceeded {
+            tokens: 210_000,
+            limit: 200_000,
+        })
+    ));
+    assert_eq!(session.state(), &SessionState::Idle);
+    session
+        .submit("retry")
+        .await
+        .expect("session should accept a retry after overflow");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn submit_context_overflow_without_counts_falls_back_to_profile_window() {
+    let overflow = SDKError::Provider(forge_llm::ProviderError::new(
+        "test",
+        forge_llm::ProviderErrorKind::ContextLength,
+        "request too large",
+    ));
+    let (client, _) = build_test_client_with_results(vec![Err(overflow)], 0);
+    let profile = StaticProviderProfile::minimal("test", "gpt-5.2-codex").with_capabilities(
+        ProviderCapabilities {
+            context_window_size: 8_000,
+            ..ProviderCapabilities::default()
+        },
+    );
+    let mut session = Session::new(
+        Arc::new(profile),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig::default(),
+    )
+    .expect("new session");
+
+    let error = session
+        .submit("hi")
+        .await
+        .expect_err("overflow should surface");
+
+    assert!(matches!(
+        error,
+        AgentError::Session(SessionError::ContextWindowExceeded {
+            tokens: _,
+            limit: 8_000,
+        })
+    ));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn submit_context_overflow_with_provider_override_falls_back_to_override_window() {
+    let overflow = SDKError::Provider(forge_llm::ProviderError::new(
+        "test",
+        forge_llm::ProviderErrorKind::ContextLength,
+        "request too large",
+    ));
+    let (client, _) = build_test_client_with_results(vec![Err(overflow)], 0);
+    let base_profile = StaticProviderProfile::minimal("base", "base-model").with_capabilities(
+        ProviderCapabilities {
+            context_window_size: 8_000,
+            ..ProviderCapabilities::default()
+        },
+    );
+    let alt_profile = StaticProviderProfile::minimal("test", "gpt-5.2-codex").with_capabilities(
+        ProviderCapabilities {
+            context_window_size: 2_000,
+            ..ProviderCapabilities::default()
+        },
+    );
+    let mut session = Session::new(
+        Arc::new(base_profile),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig::default(),
+    )
+    .expect("new session");
+    session.register_provider_profile(Arc::new(alt_profile));
+
+    let error = session
+        .submit_with_options(
+            "hi",
+            SubmitOptions {
+                provider: Some("test".to_string()),
+                ..SubmitOptions::default()
+            },
+        )
+        .await
+        .expect_err("overflow should surface");
+
+    assert!(matches!(
+        error,
+        AgentError::Session(SessionError::ContextWindowExceeded {
+            tokens: _,
+            limit: 2_000,
+        })
+    ));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn submit_preflight_over_context_window_expected_error_without_llm_call() {
+    let (client, requests) = build_test_client(vec![text_response("resp-1", "done")]);
+    let profile = StaticProviderProfile::minimal("test", "gpt-5.2-codex").with_capabilities(
+        ProviderCapabilities {
+            context_window_size: 1_000,
+            ..ProviderCapabilities::default()
+        },
+    );
+    let mut session = Session::new(
+        Arc::new(profile),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig {
+            context_output_reserve_tokens: Some(100),
+            ..SessionConfig::default()
+        },
+    )
+    .expect("new session");
+
+    let error = session
+        .submit("x".repeat(8_000))
+        .await
+        .expect_err("oversized request should be rejected before sending");
+
+    assert!(matches!(
+        error,
+        AgentError::Session(SessionError::ContextWindowExceeded {
+            tokens: 2_000..,
+            limit: 1_000,
+        })
+    ));
+    assert!(requests.lock().expect("requests mutex").is_empty());
+    assert_eq!(session.state(), &SessionState::Idle);
+}
+
+#[test]
+fn classify_context_overflow_matches_known_provider_signatures() {
+    let other = |message: &str| classify_context_overflow(&SDKError::Other(message.to_string()));
+
+    assert_eq!(
+        other(
+            "This model's maximum context length is 128000 tokens. However, your messages resulted in 130412 tokens."
+        ),
+        Some((Some(130_412), Some(128_000)))
+    );
+    assert_eq!(
+        other(
+            "The input token count (1048577) exceeds the maximum number of tokens allowed (1048576)."
+        ),
+        Some((Some(1_048_577), Some(1_048_576)))
+    );
+    assert_eq!(
+        other(
+            "This model's maximum context length is 128000 tokens. However, you requested 130412 tokens (129000 in the messages, 1412 in the completion)."
+        ),
+        Some((Some(130_412), Some(128_000)))
+    );
+    assert_eq!(
+        other("prompt is too long: 215000 tokens > 200000 maximum"),
+        Some((Some(215_000), Some(200_000)))
+    );
+    assert_eq!(
+        other("input length and `max_tokens` exceed context limit: 198000 + 4096 > 200000"),
+        Some((None, None))
+    );
+    assert_eq!(other("context_length_exceeded"), Some((None, None)));
+    assert_eq!(other("rate limit exceeded"), None);
+    assert_eq!(other("the context window of this deployment is 8000"), None);
+    assert_eq!(
+        classify_context_overflow(&SDKError::Configuration(ConfigurationError::new(
+            "context length"
+        ))),
+        None
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn submit_deadline_stops_loop_after_in_flight_round_finishes() {
+    let (client, requests) = build_test_client_with_delay(
+        vec![
+            tool_call_response(
+                "resp-1",
+                "call-1",
+                "echo_tool",
+                serde_json::json!({ "value": "first" }),
+            ),
+            tool_call_response(
+                "resp-2",
+                "call-2",
+                "echo_tool",
+                serde_json::json!({ "value": "second" }),
+            ),
+            text_response("resp-3", "should_not_be_called"),
+        ],
+        50,
+    );
     let profile = Arc::new(StaticProviderProfile {
         id: "test".to_string(),
         model: "gpt-5.2-codex".to_string(),
         base_system_prompt: "system".to_string(),
-        tool_registry: Arc::new(ToolRegistry::default()),
+        tool_registry: tool_registry_with_echo(),
         provider_options: None,
-        capabilities: ProviderCapabilities {
-            context_window_size: 8_000,
-            ..ProviderCapabilities::default()
-        },
+        capabilities: ProviderCapabilities::default(),
     });
     let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
-    let mut session = Session::new_with_emitter(
-        profile,
-        env,
-        client,
-        SessionConfig::default(),
-        emitter.clone(),
-    )
-    .expect("new session");
+    let emitter = Arc::new(BufferedEventEmitter::default());
+    let config = SessionConfig {
+        submit_deadline_ms: 30,
+        ..SessionConfig::default()
+    };
+    let mut session = Session::new_with_emitter(profile, env, client, config, emitter.clone())
+        .expect("new session");
 
-    session.submit("hi").await.expect("submit should succeed");
+    let result = session
+        .submit_with_result("run tool", SubmitOptions::default())
+        .await
+        .expect("submit should succeed");
 
-    let events = emitter.snapshot();
-    assert!(!events.iter().any(|event| {
-        event.kind == EventKind::Warning && event.data.get_str("category") == Some("context_usage")
-    }));
+    assert_eq!(result.stopped_reason, StopReason::DeadlineExceeded);
+    assert_eq!(session.state(), &SessionState::Idle);
+    assert_eq!(requests.lock().expect("requests mutex").len(), 1);
+    assert!(matches!(
+        session.history().last(),
+        Some(Turn::ToolResults(_))
+    ));
+    assert!(
+        emitter
+            .snapshot()
+            .iter()
+            .any(|event| event.kind == EventKind::DeadlineExceeded)
+    );
 }
 
 #[tokio::test(flavor = "current_thread")]
@@ -1090,6 +2802,62 @@ async fn abort_handle_cancels_inflight_llm_call_and_closes_session() {
     );
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn submit_surfaces_execution_environment_initialize_failure() {
+    let env = LifecycleEnv {
+        fail_initialize: true,
+        ..LifecycleEnv::default()
+    };
+    let (client, requests) = build_test_client(vec![text_response("resp-1", "done")]);
+    let mut session = Session::new(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(env),
+        client,
+        SessionConfig::default(),
+    )
+    .expect("new session");
+
+    let error = session
+        .submit("hi")
+        .await
+        .expect_err("initialize failure should surface");
+
+    assert!(matches!(
+        &error,
+        AgentError::Execution(source)
+            if matches!(source.as_ref(), AgentError::ExecutionEnvironment(message) if message == "container failed to start")
+    ));
+    assert!(requests.lock().expect("requests mutex").is_empty());
+    assert!(session.history().is_empty());
+    assert_eq!(session.state(), &SessionState::Idle);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn execution_environment_initializes_once_and_cleans_up_on_close() {
+    let env = LifecycleEnv::default();
+    let initialize_calls = env.initialize_calls.clone();
+    let cleanup_calls = env.cleanup_calls.clone();
+    let (client, _) = build_test_client(vec![
+        text_response("resp-1", "one"),
+        text_response("resp-2", "two"),
+    ]);
+    let mut session = Session::new(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(env),
+        client,
+        SessionConfig::default(),
+    )
+    .expect("new session");
+
+    session.submit("first").await.expect("first submit");
+    session.submit("second").await.expect("second submit");
+    assert_eq!(*initialize_calls.lock().expect("initialize mutex"), 1);
+
+    session.close().expect("close should succeed");
+    tokio::task::yield_now().await;
+    assert_eq!(*cleanup_calls.lock().expect("cleanup mutex"), 1);
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn abort_handle_terminates_running_shell_command() {
     #[cfg(windows)]
@@ -1147,16 +2915,10 @@ fn discover_project_documents_respects_provider_filter_and_precedence() {
     write_test_file(&root.join("apps/CLAUDE.md"), "apps claude");
     write_test_file(&root.join("apps/service/AGENTS.md"), "service agents");
 
-    let profile = StaticProviderProfile {
-        id: "anthropic".to_string(),
-        model: "claude".to_string(),
-        base_system_prompt: "base".to_string(),
-        tool_registry: Arc::new(ToolRegistry::default()),
-        provider_options: None,
-        capabilities: ProviderCapabilities::default(),
-    };
+    let profile =
+        StaticProviderProfile::minimal("anthropic", "claude").with_base_system_prompt("base");
 
-    let docs = discover_project_documents(&nested, &profile);
+    let docs = discover_project_documents(&nested, &profile, &[]);
     let paths: Vec<String> = docs.iter().map(|doc| doc.path.clone()).collect();
     assert_eq!(
         paths,
@@ -1179,22 +2941,513 @@ fn discover_project_documents_truncates_to_32kb_with_marker() {
     fs::create_dir_all(&nested).expect("nested dir should be created");
     fs::create_dir_all(root.join(".git")).expect(".git marker dir should be created");
 
-    let oversized = "A".repeat(40 * 1024);
-    write_test_file(&root.join("AGENTS.md"), &oversized);
+    let oversized = "A".repeat(40 * 1024);
+    write_test_file(&root.join("AGENTS.md"), &oversized);
+
+    let profile =
+        StaticProviderProfile::minimal("openai", "gpt-5.2-codex").with_base_system_prompt("base");
+
+    let docs = discover_project_documents(&nested, &profile, &[]);
+    assert_eq!(docs.len(), 1);
+    assert!(docs[0].content.contains(PROJECT_DOC_TRUNCATION_MARKER));
+    assert!(docs[0].content.len() <= (32 * 1024) + PROJECT_DOC_TRUNCATION_MARKER.len() + 1);
+}
+
+#[test]
+fn discover_project_documents_extra_instruction_files_expected_discovered_after_profile_files() {
+    let tmp = tempdir().expect("temp dir should be created");
+    let root = tmp.path();
+    fs::create_dir_all(root.join(".git")).expect(".git marker dir should be created");
+
+    write_test_file(&root.join("AGENTS.md"), "root agents");
+    write_test_file(&root.join(".cursorrules"), "cursor rules");
+
+    let profile =
+        StaticProviderProfile::minimal("openai", "gpt-5.2-codex").with_base_system_prompt("base");
+    let extras = vec![
+        ".cursorrules".to_string(),
+        "AGENTS.md".to_string(),
+        ".cursorrules".to_string(),
+    ];
+
+    let docs = discover_project_documents(root, &profile, &extras);
+    let paths: Vec<&str> = docs.iter().map(|doc| doc.path.as_str()).collect();
+    assert_eq!(paths, vec!["AGENTS.md", ".cursorrules"]);
+    assert_eq!(docs[1].content, "cursor rules");
+}
+
+fn include_test_root() -> (tempfile::TempDir, PathBuf) {
+    let tmp = tempdir().expect("temp dir should be created");
+    let root = tmp.path().to_path_buf();
+    fs::create_dir_all(root.join(".git")).expect(".git marker dir should be created");
+    (tmp, root)
+}
+
+#[test]
+fn discover_project_documents_expands_includes_relative_to_doc() {
+    let (_tmp, root) = include_test_root();
+    write_test_file(
+        &root.join("AGENTS.md"),
+        "top\n@include docs/shared.md\ninline: {{include:docs/short.md}}!\n",
+    );
+    write_test_file(&root.join("docs/shared.md"), "shared\n@include nested.md\n");
+    write_test_file(&root.join("docs/nested.md"), "nested guidance\n");
+    write_test_file(&root.join("docs/short.md"), "short");
+    let profile = StaticProviderProfile::minimal("openai", "gpt-5.2-codex");
+
+    let docs = discover_project_documents(&root, &profile, &[]);
+
+    assert_eq!(docs.len(), 1);
+    assert_eq!(
+        docs[0].content,
+        "top\nshared\nnested guidance\ninline: short!\n"
+    );
+}
+
+#[test]
+fn discover_project_documents_missing_include_leaves_warning_marker() {
+    let (_tmp, root) = include_test_root();
+    write_test_file(
+        &root.join("AGENTS.md"),
+        "before\n@include missing.md\nafter\n",
+    );
+    let profile = StaticProviderProfile::minimal("openai", "gpt-5.2-codex");
+
+    let docs = discover_project_documents(&root, &profile, &[]);
+
+    assert_eq!(
+        docs[0].content,
+        "before\n<!-- include 'missing.md' skipped: file not found -->\nafter\n"
+    );
+}
+
+#[test]
+fn discover_project_documents_include_cycle_stops_with_marker() {
+    let (_tmp, root) = include_test_root();
+    write_test_file(&root.join("AGENTS.md"), "root\n@include a.md\n");
+    write_test_file(&root.join("a.md"), "a\n@include b.md\n");
+    write_test_file(&root.join("b.md"), "b\n@include a.md\n");
+    let profile = StaticProviderProfile::minimal("openai", "gpt-5.2-codex");
+
+    let docs = discover_project_documents(&root, &profile, &[]);
+
+    assert_eq!(
+        docs[0].content,
+        "root\na\nb\n<!-- include 'a.md' skipped: include cycle -->\n"
+    );
+}
+
+#[test]
+fn discover_project_documents_included_bytes_count_against_budget() {
+    let (_tmp, root) = include_test_root();
+    write_test_file(&root.join("AGENTS.md"), "@include big.md\n");
+    write_test_file(&root.join("big.md"), &"A".repeat(40 * 1024));
+    let profile = StaticProviderProfile::minimal("openai", "gpt-5.2-codex");
+
+    let docs = discover_project_documents(&root, &profile, &[]);
+
+    assert!(docs[0].content.contains(PROJECT_DOC_TRUNCATION_MARKER));
+    assert!(docs[0].content.len() <= (32 * 1024) + PROJECT_DOC_TRUNCATION_MARKER.len() + 1);
+}
+
+#[test]
+fn expand_project_doc_includes_fan_out_expected_stops_once_budget_exhausted() {
+    let (_tmp, root) = include_test_root();
+    write_test_file(&root.join("a.md"), &"@include big.md\n".repeat(4));
+    write_test_file(&root.join("b.md"), &"@include big.md\n".repeat(4));
+    write_test_file(&root.join("big.md"), &"B".repeat(1024));
+    let doc_path = root.join("AGENTS.md");
+    write_test_file(&doc_path, "@include a.md\n@include b.md\n");
+    let root = canonicalize_or_fallback(&root);
+    let doc_path = canonicalize_or_fallback(&doc_path);
+
+    let expanded = expand_project_doc_includes(
+        "@include a.md\n@include b.md\n",
+        &doc_path,
+        &root,
+        &mut vec![doc_path.clone()],
+        2048,
+    );
+
+    assert_eq!(expanded.matches(&"B".repeat(1024)).count(), 2);
+    assert!(expanded.contains("<!-- include 'b.md' skipped: project doc budget exhausted -->"));
+}
+
+#[test]
+fn convert_history_groups_tool_results_only_for_grouping_profiles() {
+    let history = vec![Turn::ToolResults(ToolResultsTurn::new(
+        vec![
+            ToolResultTurn {
+                tool_call_id: "call-1".to_string(),
+                content: Value::String("one".to_string()),
+                is_error: false,
+            },
+            ToolResultTurn {
+                tool_call_id: "call-2".to_string(),
+                content: Value::String("two".to_string()),
+                is_error: true,
+            },
+        ],
+        current_timestamp(),
+    ))];
+    assert!(!StaticProviderProfile::minimal("test", "gpt-5.2-codex").group_tool_results());
+    assert!(
+        crate::AnthropicProviderProfile::with_default_tools("claude-sonnet-4-5")
+            .group_tool_results()
+    );
+
+    let per_result = convert_history_to_messages(&history, false, true);
+    assert_eq!(
+        per_result,
+        vec![
+            Message::tool_result("call-1", "one", false),
+            Message::tool_result("call-2", "two", true),
+        ]
+    );
+
+    let grouped = convert_history_to_messages(&history, true, true);
+    assert_eq!(grouped.len(), 1);
+    assert_eq!(grouped[0].role, Role::Tool);
+    assert_eq!(grouped[0].tool_call_id, None);
+    let grouped_parts: Vec<ContentPart> = per_result
+        .into_iter()
+        .flat_map(|message| message.content)
+        .collect();
+    assert_eq!(grouped[0].content, grouped_parts);
+}
+
+/// Sends `request` through the adapter built for a local server's base URL
+/// and returns the JSON body the server received.
+async fn captured_request_body(
+    adapter_for: impl FnOnce(String) -> Box<dyn ProviderAdapter>,
+    request: Request,
+) -> Value {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind listener");
+    let address = listener.local_addr().expect("listener addr");
+    let server = std::thread::spawn(move || {
+        let (mut socket, _) = listener.accept().expect("accept");
+        let mut received = Vec::new();
+        let mut buffer = [0_u8; 8192];
+        let body_start = loop {
+            let read = socket.read(&mut buffer).expect("read request");
+            assert!(read > 0, "connection closed before headers ended");
+            received.extend_from_slice(&buffer[..read]);
+            if let Some(end) = received.windows(4).position(|window| window == b"\r\n\r\n") {
+                break end + 4;
+            }
+        };
+        let headers = String::from_utf8_lossy(&received[..body_start]).to_ascii_lowercase();
+        let content_length: usize = headers
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .and_then(|value| value.trim().parse().ok())
+            .expect("content-length header");
+        while received.len() < body_start + content_length {
+            let read = socket.read(&mut buffer).expect("read body");
+            assert!(read > 0, "connection closed before body ended");
+            received.extend_from_slice(&buffer[..read]);
+        }
+        socket
+            .write_all(b"HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            .expect("write response");
+        received[body_start..body_start + content_length].to_vec()
+    });
+
+    // The empty 500 reply fails the call; only the sent body matters.
+    let _ = adapter_for(format!("http://{address}"))
+        .complete(request)
+        .await;
+    let body = server.join().expect("capture server");
+    serde_json::from_slice(&body).expect("request body should be JSON")
+}
+
+fn contains_key(value: &Value, key: &str) -> bool {
+    match value {
+        Value::Object(map) => map
+            .iter()
+            .any(|(name, value)| name == key || contains_key(value, key)),
+        Value::Array(items) => items.iter().any(|item| contains_key(item, key)),
+        _ => false,
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn build_request_prompt_caching_expected_anthropic_markers_only() {
+    let request_for = |profile: Arc<dyn ProviderProfile>| {
+        let (client, _requests) = build_test_client(vec![]);
+        Session::new(
+            profile,
+            Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+            client,
+            SessionConfig::default(),
+        )
+        .expect("new session")
+        .build_request(&SubmitOptions::default())
+        .expect("request should build")
+    };
+
+    let anthropic = crate::AnthropicProviderProfile::with_default_tools("claude-sonnet-4-5");
+    assert!(anthropic.capabilities().supports_prompt_caching);
+    let request = request_for(Arc::new(anthropic));
+    assert_eq!(
+        request.provider_options,
+        Some(serde_json::json!({ "anthropic": { "auto_cache": true } }))
+    );
+    let body = captured_request_body(
+        |base_url| {
+            let mut config = forge_llm::AnthropicAdapterConfig::new("test-key");
+            config.base_url = base_url;
+            Box::new(forge_llm::AnthropicAdapter::new(config).expect("anthropic adapter"))
+        },
+        request,
+    )
+    .await;
+    assert!(contains_key(&body, "cache_control"));
+
+    let opted_out = crate::AnthropicProviderProfile::with_default_tools("claude-sonnet-4-5")
+        .with_provider_options(serde_json::json!({
+            "anthropic": { "auto_cache": false, "thinking": { "type": "enabled" } }
+        }));
+    let request = request_for(Arc::new(opted_out));
+    assert_eq!(
+        request.provider_options,
+        Some(serde_json::json!({
+            "anthropic": { "auto_cache": false, "thinking": { "type": "enabled" } }
+        }))
+    );
+
+    let caching_off = crate::AnthropicProviderProfile::with_default_tools("claude-sonnet-4-5")
+        .with_capabilities(ProviderCapabilities {
+            supports_prompt_caching: false,
+            ..ProviderCapabilities::default()
+        });
+    let request = request_for(Arc::new(caching_off));
+    assert_eq!(
+        request.provider_options,
+        Some(serde_json::json!({ "anthropic": { "auto_cache": false } }))
+    );
+
+    let openai = crate::OpenAiProviderProfile::with_default_tools("gpt-5.2-codex");
+    assert!(!openai.capabilities().supports_prompt_caching);
+    let request = request_for(Arc::new(openai));
+    assert_eq!(request.provider_options, None);
+    let body = captured_request_body(
+        |base_url| {
+            let mut config = forge_llm::OpenAIAdapterConfig::new("test-key");
+            config.base_url = base_url;
+            Box::new(forge_llm::OpenAIAdapter::new(config).expect("openai adapter"))
+        },
+        request,
+    )
+    .await;
+    assert!(!contains_key(&body, "cache_control"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn submit_with_attachment_expected_image_part_for_vision_profile() {
+    let screenshot = crate::Attachment::data("image/png", vec![0x89, b'P', b'N', b'G']);
+    let options = SubmitOptions {
+        attachments: vec![screenshot.clone()],
+        ..SubmitOptions::default()
+    };
+
+    let (client, requests) = build_test_client(vec![text_response("resp-1", "fixed")]);
+    let profile = StaticProviderProfile::minimal("test", "gpt-5.2-codex").with_capabilities(
+        ProviderCapabilities {
+            supports_vision: true,
+            ..ProviderCapabilities::default()
+        },
+    );
+    let mut session = Session::new(
+        Arc::new(profile),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig::default(),
+    )
+    .expect("new session");
+    session
+        .submit_with_result("fix the layout", options.clone())
+        .await
+        .expect("submit should succeed");
+
+    let sent = requests.lock().expect("requests mutex")[0].clone();
+    let user_message = sent
+        .messages
+        .iter()
+        .find(|message| message.role == Role::User)
+        .expect("user message");
+    assert_eq!(
+        user_message.content,
+        vec![
+            ContentPart::text("fix the layout"),
+            ContentPart::image(forge_llm::ImageData {
+                url: None,
+                data: Some(vec![0x89, b'P', b'N', b'G']),
+                media_type: Some("image/png".to_string()),
+                detail: None,
+            }),
+        ]
+    );
+    assert!(matches!(
+        &session.history()[0],
+        Turn::User(turn) if turn.attachments == vec![screenshot]
+    ));
+
+    let (client, requests) = build_test_client(vec![text_response("resp-1", "unused")]);
+    let mut text_only = Session::new(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig::default(),
+    )
+    .expect("new session");
+    let error = text_only
+        .submit_with_result("fix the layout", options)
+        .await
+        .expect_err("attachments need a vision profile");
+    assert!(matches!(
+        error,
+        AgentError::Session(SessionError::AttachmentsUnsupported(_))
+    ));
+    assert!(text_only.history().is_empty());
+    assert!(requests.lock().expect("requests mutex").is_empty());
+}
+
+#[test]
+fn build_request_without_tool_support_expected_tools_described_in_prompt() {
+    let session_with = |on_tools_unsupported| {
+        let (client, _requests) = build_test_client(vec![]);
+        let profile = StaticProviderProfile::minimal("test", "text-only-model")
+            .with_tool_registry(tool_registry_with_echo())
+            .with_capabilities(ProviderCapabilities {
+                supports_tools: false,
+                ..ProviderCapabilities::default()
+            });
+        Session::new(
+            Arc::new(profile),
+            Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+            client,
+            SessionConfig {
+                on_tools_unsupported,
+                ..SessionConfig::default()
+            },
+        )
+        .expect("new session")
+    };
+
+    let request = session_with(crate::ToolsUnsupportedPolicy::DescribeInPrompt)
+        .build_request(&SubmitOptions::default())
+        .expect("request should build");
+    assert_eq!(request.tools, None);
+    assert_eq!(request.tool_choice, None);
+    let system_prompt = request.messages[0].text();
+    assert!(system_prompt.contains("## Tools Unavailable for Direct Calls"));
+    assert!(system_prompt.contains("- echo_tool: echo\n  parameters: {"));
+    assert_eq!(system_prompt.matches("echo_tool").count(), 1);
+
+    let error = session_with(crate::ToolsUnsupportedPolicy::Error)
+        .build_request(&SubmitOptions::default())
+        .expect_err("tools policy should reject the request");
+    assert!(matches!(
+        error,
+        AgentError::Session(SessionError::ToolsUnsupported(profile)) if profile == "test"
+    ));
+}
+
+fn assistant_turn(content: &str, tool_calls: Vec<ToolCall>, reasoning: Option<&str>) -> Turn {
+    Turn::Assistant(AssistantTurn::new(
+        content.to_string(),
+        tool_calls,
+        reasoning.map(str::to_string),
+        Usage::default(),
+        None,
+        current_timestamp(),
+    ))
+}
+
+#[test]
+fn convert_history_reasoning_only_assistant_turn_emits_only_thinking_part() {
+    let messages =
+        convert_history_to_messages(&[assistant_turn("", vec![], Some("hmm"))], false, true);
+
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].role, Role::Assistant);
+    assert_eq!(messages[0].content.len(), 1);
+    assert_eq!(
+        messages[0].content[0]
+            .thinking
+            .as_ref()
+            .map(|data| data.text.as_str()),
+        Some("hmm")
+    );
+    assert!(messages[0].content[0].text.is_none());
+}
+
+#[test]
+fn convert_history_tool_call_only_assistant_turn_emits_only_tool_call_parts() {
+    let call = build_tool_call("call-1", "echo_tool", serde_json::json!({"value":"x"}));
+    let messages =
+        convert_history_to_messages(&[assistant_turn("", vec![call], None)], false, true);
+
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].content.len(), 1);
+    assert_eq!(
+        messages[0].content[0]
+            .tool_call
+            .as_ref()
+            .map(|data| data.id.as_str()),
+        Some("call-1")
+    );
+    assert!(messages[0].content[0].text.is_none());
+}
+
+#[test]
+fn convert_history_skips_fully_empty_assistant_turn() {
+    let history = vec![
+        Turn::User(UserTurn::new("hi", current_timestamp())),
+        assistant_turn("", vec![], None),
+        assistant_turn("", vec![], Some("")),
+    ];
+
+    let messages = convert_history_to_messages(&history, false, true);
 
-    let profile = StaticProviderProfile {
-        id: "openai".to_string(),
-        model: "gpt-5.2-codex".to_string(),
-        base_system_prompt: "base".to_string(),
-        tool_registry: Arc::new(ToolRegistry::default()),
-        provider_options: None,
-        capabilities: ProviderCapabilities::default(),
+    assert_eq!(messages, vec![Message::user("hi")]);
+}
+
+#[test]
+fn convert_history_without_historical_reasoning_keeps_only_last_thinking_part() {
+    let history = vec![
+        Turn::User(UserTurn::new("first", current_timestamp())),
+        assistant_turn("one", vec![], Some("old thoughts")),
+        Turn::User(UserTurn::new("second", current_timestamp())),
+        assistant_turn("two", vec![], Some("new thoughts")),
+    ];
+    let thinking = |messages: &[Message]| -> Vec<Option<String>> {
+        messages
+            .iter()
+            .filter(|message| message.role == Role::Assistant)
+            .map(|message| {
+                message
+                    .content
+                    .iter()
+                    .find_map(|part| part.thinking.as_ref().map(|data| data.text.clone()))
+            })
+            .collect()
     };
 
-    let docs = discover_project_documents(&nested, &profile);
-    assert_eq!(docs.len(), 1);
-    assert!(docs[0].content.contains(PROJECT_DOC_TRUNCATION_MARKER));
-    assert!(docs[0].content.len() <= (32 * 1024) + PROJECT_DOC_TRUNCATION_MARKER.len() + 1);
+    assert_eq!(
+        thinking(&convert_history_to_messages(&history, false, true)),
+        vec![
+            Some("old thoughts".to_string()),
+            Some("new thoughts".to_string())
+        ]
+    );
+    assert_eq!(
+        thinking(&convert_history_to_messages(&history, false, false)),
+        vec![None, Some("new thoughts".to_string())]
+    );
 }
 
 fn build_tool_call(id: &str, name: &str, arguments: Value) -> ToolCall {
@@ -1313,17 +3566,612 @@ async fn spawn_agent_honors_model_override_for_child_requests() {
 
     let wait = session
         .execute_subagent_tool_call(build_tool_call(
-            "call-2",
+            "call-2",
+            "wait",
+            serde_json::json!({ "agent_id": agent_id }),
+        ))
+        .await
+        .expect("wait should execute");
+    assert!(!wait.is_error);
+
+    let seen_requests = requests.lock().expect("requests mutex").clone();
+    assert_eq!(seen_requests.len(), 1);
+    assert_eq!(seen_requests[0].model, "override-model");
+}
+
+async fn spawn_and_wait_for_subagent_output(
+    session: &mut Session,
+    spawn_arguments: Value,
+) -> String {
+    let spawn = session
+        .execute_subagent_tool_call(build_tool_call("call-1", "spawn_agent", spawn_arguments))
+        .await
+        .expect("spawn should execute");
+    let spawn_payload: Value = serde_json::from_str(
+        spawn
+            .content
+            .as_str()
+            .expect("spawn payload should be string JSON"),
+    )
+    .expect("spawn payload should parse");
+    let agent_id = spawn_payload
+        .get("agent_id")
+        .and_then(Value::as_str)
+        .expect("agent_id must exist");
+
+    let wait = session
+        .execute_subagent_tool_call(build_tool_call(
+            "call-2",
+            "wait",
+            serde_json::json!({ "agent_id": agent_id }),
+        ))
+        .await
+        .expect("wait should execute");
+    assert!(!wait.is_error);
+    let wait_payload: Value = serde_json::from_str(
+        wait.content
+            .as_str()
+            .expect("wait payload should be string JSON"),
+    )
+    .expect("wait payload should parse");
+    wait_payload
+        .get("output")
+        .and_then(Value::as_str)
+        .expect("output must exist")
+        .to_string()
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn wait_caps_large_subagent_output_and_persists_full_text() {
+    let large_output = "report line\n".repeat(4_000);
+    let (client, _requests) = build_test_client(vec![text_response("child-resp-1", &large_output)]);
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("test", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let config = SessionConfig {
+        cxdb_persistence: CxdbPersistenceMode::Required,
+        subagent_output_max_bytes: 1_024,
+        ..SessionConfig::default()
+    };
+    let store = Arc::new(RecordingPersistence::default());
+    let mut session =
+        Session::new_with_persistence(profile, env, client, config, Some(store.clone()))
+            .expect("session should initialize");
+
+    let output = spawn_and_wait_for_subagent_output(
+        &mut session,
+        serde_json::json!({ "task": "survey the repository" }),
+    )
+    .await;
+    assert!(output.starts_with("report line\n"));
+    assert!(output.contains("[subagent output truncated: showing 1024 of 48000 bytes]"));
+    assert!(output.len() < 1_200);
+
+    let persisted = store
+        .appended()
+        .into_iter()
+        .find(|request| request.type_id == "forge.link.subagent_output")
+        .expect("full subagent output should be persisted");
+    let record: AgentTurnRecord =
+        decode_typed_record(&persisted.payload).expect("subagent output record should decode");
+    assert_eq!(record.turn["output"], Value::String(large_output));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn wait_summarizes_large_subagent_output_when_enabled() {
+    let large_output = "report line\n".repeat(4_000);
+    let (client, requests) = build_test_client(vec![
+        text_response("child-resp-1", &large_output),
+        text_response("summary-resp-1", "three findings, no errors"),
+    ]);
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("test", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let config = SessionConfig {
+        subagent_output_max_bytes: 1_024,
+        summarize_subagent_output: true,
+        ..SessionConfig::default()
+    };
+    let mut session = Session::new(profile, env, client, config).expect("new session");
+    session.set_request_interceptor(Some(Arc::new(|request: &mut Request| {
+        request
+            .metadata
+            .get_or_insert_with(HashMap::new)
+            .insert("intercepted".to_string(), "yes".to_string());
+    })));
+
+    let output = spawn_and_wait_for_subagent_output(
+        &mut session,
+        serde_json::json!({ "task": "survey the repository" }),
+    )
+    .await;
+    assert_eq!(output, "three findings, no errors");
+    let seen_requests = requests.lock().expect("requests mutex").clone();
+    assert_eq!(seen_requests.len(), 2);
+    assert!(seen_requests[1].tools.is_none());
+    assert_eq!(
+        seen_requests[1]
+            .metadata
+            .as_ref()
+            .map(|metadata| metadata["intercepted"].as_str()),
+        Some("yes")
+    );
+    assert!(session.session_tokens_used() > 0);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn subagent_run_to_completion_persists_spawn_and_complete_links() {
+    let (client, _requests) = build_test_client(vec![text_response("child-resp-1", "all done")]);
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("test", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let config = SessionConfig {
+        cxdb_persistence: CxdbPersistenceMode::Required,
+        ..SessionConfig::default()
+    };
+    let store = Arc::new(RecordingPersistence::default());
+    let mut session =
+        Session::new_with_persistence(profile, env, client, config, Some(store.clone()))
+            .expect("session should initialize");
+
+    let output = spawn_and_wait_for_subagent_output(
+        &mut session,
+        serde_json::json!({ "task": "finish quickly" }),
+    )
+    .await;
+    assert_eq!(output, "all done");
+
+    let link = |type_id: &str| -> AgentTurnRecord {
+        let request = store
+            .appended()
+            .into_iter()
+            .find(|request| request.type_id == type_id)
+            .unwrap_or_else(|| panic!("{type_id} should be persisted"));
+        decode_typed_record(&request.payload).expect("link record should decode")
+    };
+    let spawn = link("forge.link.subagent_spawn");
+    let complete = link("forge.link.subagent_complete");
+    assert_eq!(complete.turn["subagent_id"], spawn.turn["subagent_id"]);
+    assert_eq!(
+        complete.turn["child_session_id"],
+        spawn.turn["child_session_id"]
+    );
+    assert_eq!(complete.turn["status"], "completed");
+    assert_eq!(complete.turn["success"], true);
+    assert_eq!(complete.turn["turns_used"], 2);
+    assert_eq!(
+        complete.turn["output_hash"],
+        blake3::hash(b"all done").to_hex().to_string()
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn shutdown_expected_subagent_session_end_written() {
+    let (client, _requests) = build_test_client(vec![text_response("child-resp-1", "all done")]);
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("test", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let config = SessionConfig {
+        cxdb_persistence: CxdbPersistenceMode::Required,
+        ..SessionConfig::default()
+    };
+    let store = Arc::new(RecordingPersistence::default());
+    let mut session =
+        Session::new_with_persistence(profile, env, client, config, Some(store.clone()))
+            .expect("session should initialize");
+    spawn_and_wait_for_subagent_output(
+        &mut session,
+        serde_json::json!({ "task": "finish quickly" }),
+    )
+    .await;
+
+    session.shutdown().await.expect("shutdown should succeed");
+
+    let ended: Vec<String> = store
+        .appended()
+        .iter()
+        .filter_map(|request| decode_typed_record::<SessionLifecycleRecord>(&request.payload).ok())
+        .filter(|record| record.kind == "ended")
+        .map(|record| record.session_id)
+        .collect();
+    assert_eq!(ended.len(), 2);
+    assert!(ended.contains(&session.id().to_string()));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn drop_with_unwritten_lifecycle_records_expected_warning() {
+    let (client, _requests) = build_test_client(vec![]);
+    let emitter = Arc::new(BufferedEventEmitter::default());
+    let session = Session::new_with_emitter_and_persistence(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig {
+            cxdb_persistence: CxdbPersistenceMode::Required,
+            ..SessionConfig::default()
+        },
+        emitter.clone(),
+        Some(Arc::new(RecordingPersistence::default())),
+    )
+    .expect("session should initialize");
+
+    drop(session);
+
+    let warning = emitter
+        .snapshot()
+        .into_iter()
+        .find(|event| event.kind == EventKind::Warning)
+        .expect("drop should warn about unwritten records");
+    assert!(
+        warning
+            .data
+            .get_str("message")
+            .is_some_and(|message| message.contains("1 queued lifecycle record(s) unwritten"))
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn subagent_complete_write_failure_expected_retried_by_next_wait() {
+    let (client, _requests) = build_test_client(vec![text_response("child-resp-1", "all done")]);
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("test", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let config = SessionConfig {
+        cxdb_persistence: CxdbPersistenceMode::Required,
+        ..SessionConfig::default()
+    };
+    let store = Arc::new(RecordingPersistence::default());
+    let mut session =
+        Session::new_with_persistence(profile, env, client, config, Some(store.clone()))
+            .expect("session should initialize");
+    *store.fail_append_of_type.lock().expect("mutex") =
+        Some("forge.link.subagent_complete".to_string());
+
+    let spawn = session
+        .execute_subagent_tool_call(build_tool_call(
+            "call-1",
+            "spawn_agent",
+            serde_json::json!({ "task": "finish quickly" }),
+        ))
+        .await
+        .expect("spawn should execute");
+    let agent_id = serde_json::from_str::<Value>(spawn.content.as_str().expect("spawn payload"))
+        .expect("spawn payload should parse")["agent_id"]
+        .as_str()
+        .expect("agent_id must exist")
+        .to_string();
+    let wait = |call_id: &'static str| {
+        build_tool_call(call_id, "wait", serde_json::json!({ "agent_id": agent_id }))
+    };
+
+    let failed = session
+        .execute_subagent_tool_call(wait("call-2"))
+        .await
+        .expect("wait should execute");
+    assert!(failed.is_error);
+    let completes = |store: &RecordingPersistence| {
+        store
+            .appended()
+            .iter()
+            .filter(|request| request.type_id == "forge.link.subagent_complete")
+            .count()
+    };
+    assert_eq!(completes(&store), 0);
+
+    *store.fail_append_of_type.lock().expect("mutex") = None;
+    let retried = session
+        .execute_subagent_tool_call(wait("call-3"))
+        .await
+        .expect("wait should execute");
+    assert!(!retried.is_error);
+    assert_eq!(completes(&store), 1);
+    let payload: Value =
+        serde_json::from_str(retried.content.as_str().expect("wait payload")).expect("json");
+    assert_eq!(payload["output"], "all done");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn sequential_subagents_complete_in_spawn_order() {
+    let (client, requests) = build_test_client(
+        (1..=3)
+            .map(|index| text_response(&format!("child-resp-{index}"), &format!("result-{index}")))
+            .collect(),
+    );
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("test", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let config = SessionConfig {
+        subagent_execution: SubagentExecution::Sequential,
+        ..SessionConfig::default()
+    };
+    let mut session = Session::new(profile, env, client, config).expect("new session");
+
+    let mut agent_ids = Vec::new();
+    for index in 1..=3 {
+        let spawn = session
+            .execute_subagent_tool_call(build_tool_call(
+                &format!("spawn-{index}"),
+                "spawn_agent",
+                serde_json::json!({ "task": format!("task-{index}") }),
+            ))
+            .await
+            .expect("spawn should execute");
+        let spawn_payload: Value = serde_json::from_str(
+            spawn
+                .content
+                .as_str()
+                .expect("spawn payload should be string JSON"),
+        )
+        .expect("spawn payload should parse");
+        assert_eq!(spawn_payload["status"], "completed");
+        agent_ids.push(
+            spawn_payload["agent_id"]
+                .as_str()
+                .expect("agent_id must exist")
+                .to_string(),
+        );
+    }
+
+    for (index, agent_id) in agent_ids.iter().enumerate() {
+        let wait = session
+            .execute_subagent_tool_call(build_tool_call(
+                &format!("wait-{index}"),
+                "wait",
+                serde_json::json!({ "agent_id": agent_id }),
+            ))
+            .await
+            .expect("wait should execute");
+        let wait_payload: Value = serde_json::from_str(
+            wait.content
+                .as_str()
+                .expect("wait payload should be string JSON"),
+        )
+        .expect("wait payload should parse");
+        assert_eq!(wait_payload["output"], format!("result-{}", index + 1));
+    }
+
+    let tasks: Vec<String> = requests
+        .lock()
+        .expect("requests mutex")
+        .iter()
+        .map(|request| {
+            request
+                .messages
+                .last()
+                .expect("request should have messages")
+                .text()
+        })
+        .collect();
+    assert_eq!(tasks, vec!["task-1", "task-2", "task-3"]);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn send_input_with_queue_is_delivered_after_busy_subagent_finishes() {
+    let (client, requests) = build_test_client_with_delay(
+        vec![
+            text_response("child-resp-1", "first done"),
+            text_response("child-resp-2", "follow-up done"),
+        ],
+        50,
+    );
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("test", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let mut session =
+        Session::new(profile, env, client, SessionConfig::default()).expect("new session");
+
+    let spawn = session
+        .execute_subagent_tool_call(build_tool_call(
+            "call-1",
+            "spawn_agent",
+            serde_json::json!({ "task": "first task" }),
+        ))
+        .await
+        .expect("spawn should execute");
+    let spawn_payload: Value = serde_json::from_str(
+        spawn
+            .content
+            .as_str()
+            .expect("spawn payload should be string JSON"),
+    )
+    .expect("spawn payload should parse");
+    let agent_id = spawn_payload
+        .get("agent_id")
+        .and_then(Value::as_str)
+        .expect("agent_id must exist")
+        .to_string();
+
+    let rejected = session
+        .execute_subagent_tool_call(build_tool_call(
+            "call-2",
+            "send_input",
+            serde_json::json!({ "agent_id": agent_id, "message": "too early" }),
+        ))
+        .await
+        .expect("send_input should execute");
+    assert!(rejected.is_error);
+
+    let queued = session
+        .execute_subagent_tool_call(build_tool_call(
+            "call-3",
+            "send_input",
+            serde_json::json!({ "agent_id": agent_id, "message": "follow up", "queue": true }),
+        ))
+        .await
+        .expect("queued send_input should execute");
+    assert!(!queued.is_error);
+    let queued_payload: Value = serde_json::from_str(
+        queued
+            .content
+            .as_str()
+            .expect("send_input payload should be string JSON"),
+    )
+    .expect("send_input payload should parse");
+    assert_eq!(queued_payload["queued"], 1);
+
+    let wait = session
+        .execute_subagent_tool_call(build_tool_call(
+            "call-4",
+            "wait",
+            serde_json::json!({ "agent_id": agent_id }),
+        ))
+        .await
+        .expect("wait should execute");
+    let wait_payload: Value = serde_json::from_str(
+        wait.content
+            .as_str()
+            .expect("wait payload should be string JSON"),
+    )
+    .expect("wait payload should parse");
+    assert_eq!(wait_payload["output"], "follow-up done");
+    assert_eq!(wait_payload["status"], "completed");
+    assert_eq!(wait_payload["earlier_results"][0]["output"], "first done");
+    assert_eq!(wait_payload["earlier_results"][0]["success"], true);
+
+    let seen_requests = requests.lock().expect("requests mutex").clone();
+    assert_eq!(seen_requests.len(), 2);
+    let last_message = seen_requests[1]
+        .messages
+        .last()
+        .expect("follow-up request should have messages");
+    assert_eq!(last_message.text(), "follow up");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn queued_send_input_is_dropped_after_subagent_fails() {
+    let (client, requests) = build_test_client_with_delay(
+        vec![
+            text_response("child-resp-1", "too late"),
+            text_response("child-resp-2", "should not run"),
+        ],
+        200,
+    );
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("test", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let mut session =
+        Session::new(profile, env, client, SessionConfig::default()).expect("new session");
+
+    let spawn = session
+        .execute_subagent_tool_call(build_tool_call(
+            "call-1",
+            "spawn_agent",
+            serde_json::json!({ "task": "slow task", "max_runtime_ms": 50 }),
+        ))
+        .await
+        .expect("spawn should execute");
+    let spawn_payload: Value =
+        serde_json::from_str(spawn.content.as_str().expect("spawn content")).expect("json");
+    let agent_id = spawn_payload["agent_id"]
+        .as_str()
+        .expect("agent_id must exist")
+        .to_string();
+    session
+        .execute_subagent_tool_call(build_tool_call(
+            "call-2",
+            "send_input",
+            serde_json::json!({ "agent_id": agent_id, "message": "follow up", "queue": true }),
+        ))
+        .await
+        .expect("queued send_input should execute");
+
+    let wait = session
+        .execute_subagent_tool_call(build_tool_call(
+            "call-3",
             "wait",
             serde_json::json!({ "agent_id": agent_id }),
         ))
         .await
         .expect("wait should execute");
-    assert!(!wait.is_error);
+    let wait_payload: Value =
+        serde_json::from_str(wait.content.as_str().expect("wait content")).expect("json");
+
+    assert_eq!(wait_payload["status"], "failed");
+    assert!(wait_payload.get("earlier_results").is_none());
+    // A delivered follow-up would finish (and be recorded) after 200ms.
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    assert!(
+        requests
+            .lock()
+            .expect("requests mutex")
+            .iter()
+            .all(|request| request.messages.last().map(|m| m.text()) != Some("follow up".into()))
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn wait_on_subagent_past_max_runtime_reports_timeout_failure() {
+    let (client, _requests) =
+        build_test_client_with_delay(vec![text_response("child-resp-1", "too late")], 5_000);
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("test", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let mut session =
+        Session::new(profile, env, client, SessionConfig::default()).expect("new session");
+
+    let output = spawn_and_wait_for_subagent_output(
+        &mut session,
+        serde_json::json!({ "task": "slow task", "max_runtime_ms": 50 }),
+    )
+    .await;
+    assert_eq!(output, "subagent timed out after 50 ms");
+    let handle = session
+        .subagents()
+        .values()
+        .next()
+        .expect("subagent handle should exist");
+    assert_eq!(handle.status, SubAgentStatus::Failed);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn spawn_agent_role_selects_registered_subagent_profile() {
+    let (client, requests) = build_test_client(vec![
+        text_response("child-resp-1", "found it"),
+        text_response("child-resp-2", "did it"),
+    ]);
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("test", "gpt-5.2-codex").with_base_system_prompt("base"),
+    );
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let mut session =
+        Session::new(profile, env, client, SessionConfig::default()).expect("new session");
+    session.register_subagent_profile(
+        "search",
+        Arc::new(
+            StaticProviderProfile::minimal("test", "small-search-model")
+                .with_base_system_prompt("search-base"),
+        ),
+    );
+
+    let output = spawn_and_wait_for_subagent_output(
+        &mut session,
+        serde_json::json!({ "task": "find the config loader", "role": "search" }),
+    )
+    .await;
+    assert_eq!(output, "found it");
+    let output = spawn_and_wait_for_subagent_output(
+        &mut session,
+        serde_json::json!({ "task": "fix the loader", "role": "unregistered" }),
+    )
+    .await;
+    assert_eq!(output, "did it");
 
     let seen_requests = requests.lock().expect("requests mutex").clone();
-    assert_eq!(seen_requests.len(), 1);
-    assert_eq!(seen_requests[0].model, "override-model");
+    assert_eq!(seen_requests.len(), 2);
+    assert_eq!(seen_requests[0].model, "small-search-model");
+    assert!(seen_requests[0].messages[0].text().contains("search-base"));
+    // Unknown roles fall back to the parent profile.
+    assert_eq!(seen_requests[1].model, "gpt-5.2-codex");
+    assert!(!seen_requests[1].messages[0].text().contains("search-base"));
 }
 
 #[tokio::test(flavor = "current_thread")]
@@ -1450,6 +4298,70 @@ async fn spawn_agent_rejects_when_depth_limit_reached() {
     );
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn submit_with_subagents_disabled_omits_subagent_tools_from_request() {
+    let (client, requests) = build_test_client(vec![text_response("resp-1", "done")]);
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("test", "gpt-5.2-codex")
+            .with_tool_registry(Arc::new(build_openai_tool_registry())),
+    );
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let config = SessionConfig {
+        enable_subagents: false,
+        ..SessionConfig::default()
+    };
+    let mut session = Session::new(profile, env, client, config).expect("new session");
+
+    session
+        .submit("hello")
+        .await
+        .expect("submit should succeed");
+
+    let requests = requests.lock().expect("requests mutex");
+    let tool_names: Vec<String> = requests[0]
+        .tools
+        .as_ref()
+        .expect("core tools should remain")
+        .iter()
+        .map(|tool| tool.name.clone())
+        .collect();
+    assert!(tool_names.contains(&"read_file".to_string()));
+    for name in ["spawn_agent", "send_input", "wait", "close_agent"] {
+        assert!(!tool_names.contains(&name.to_string()), "{name} exposed");
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn subagent_tool_call_with_subagents_disabled_returns_unknown_tool() {
+    let (client, _) = build_test_client(vec![]);
+    let profile = Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex"));
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let config = SessionConfig {
+        enable_subagents: false,
+        ..SessionConfig::default()
+    };
+    let mut session = Session::new(profile, env, client, config).expect("new session");
+
+    let result = session
+        .execute_subagent_tool_call(build_tool_call(
+            "call-1",
+            "spawn_agent",
+            serde_json::json!({ "task": "blocked" }),
+        ))
+        .await
+        .expect("tool execution should not panic");
+
+    assert!(result.is_error);
+    assert!(
+        result
+            .content
+            .as_str()
+            .unwrap_or_default()
+            .contains("unknown tool: spawn_agent")
+    );
+    assert!(session.subagents().is_empty());
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn close_closes_all_subagents_and_updates_status() {
     let (client, _) = build_test_client(vec![text_response("child-resp-1", "done")]);
@@ -1489,17 +4401,65 @@ async fn close_closes_all_subagents_and_updates_status() {
     ));
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn closing_subagent_keeps_parent_temp_dir() {
+    let (client, _) = build_test_client(vec![text_response("child-resp-1", "done")]);
+    let dir = tempfile::tempdir().expect("temp dir should be created");
+    let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
+    let mut session = Session::new(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        env.clone(),
+        client,
+        SessionConfig {
+            subagent_execution: SubagentExecution::Sequential,
+            ..SessionConfig::default()
+        },
+    )
+    .expect("new session");
+    session.initialize().await.expect("initialize");
+    let scratch = env
+        .temp_dir()
+        .await
+        .expect("temp dir should be created")
+        .join("notes.txt");
+    std::fs::write(&scratch, "parent").expect("scratch file written");
+
+    let spawn = session
+        .execute_subagent_tool_call(build_tool_call(
+            "call-1",
+            "spawn_agent",
+            serde_json::json!({ "task": "run child" }),
+        ))
+        .await
+        .expect("spawn should execute");
+    let spawn_payload: Value =
+        serde_json::from_str(spawn.content.as_str().expect("spawn content")).expect("json");
+    let agent_id = spawn_payload
+        .get("agent_id")
+        .and_then(Value::as_str)
+        .expect("agent id")
+        .to_string();
+    session
+        .execute_subagent_tool_call(build_tool_call(
+            "call-2",
+            "close_agent",
+            serde_json::json!({ "agent_id": agent_id }),
+        ))
+        .await
+        .expect("close should execute");
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+    assert!(scratch.exists());
+    session.shutdown_to_closed().await.expect("parent closes");
+    assert!(!scratch.exists());
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn submit_with_options_overrides_provider_model_and_reasoning() {
     let (client, requests) = build_test_client(vec![text_response("resp-1", "done")]);
-    let base_profile = Arc::new(StaticProviderProfile {
-        id: "base".to_string(),
-        model: "base-model".to_string(),
-        base_system_prompt: "base".to_string(),
-        tool_registry: Arc::new(ToolRegistry::default()),
-        provider_options: None,
-        capabilities: ProviderCapabilities::default(),
-    });
+    let base_profile = Arc::new(
+        StaticProviderProfile::minimal("base", "base-model").with_base_system_prompt("base"),
+    );
     let alt_profile = Arc::new(StaticProviderProfile {
         id: "test".to_string(),
         model: "alt-model".to_string(),
@@ -1525,6 +4485,7 @@ async fn submit_with_options_overrides_provider_model_and_reasoning() {
                 system_prompt_override: Some("node override".to_string()),
                 provider_options: Some(serde_json::json!({ "x": 1 })),
                 metadata: Some(metadata.clone()),
+                attachments: Vec::new(),
             },
         )
         .await
@@ -1554,6 +4515,236 @@ async fn submit_with_options_overrides_provider_model_and_reasoning() {
     );
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn submit_with_provider_override_groups_tool_results_per_override_profile() {
+    let mut two_calls = tool_call_response(
+        "resp-1",
+        "call-1",
+        "echo_tool",
+        serde_json::json!({ "value": "one" }),
+    );
+    two_calls
+        .message
+        .content
+        .push(ContentPart::tool_call(ToolCallData {
+            id: "call-2".to_string(),
+            name: "echo_tool".to_string(),
+            arguments: serde_json::json!({ "value": "two" }),
+            r#type: "function".to_string(),
+        }));
+    let (client, requests) = build_test_client(vec![two_calls, text_response("resp-2", "done")]);
+    // The session default groups tool results; the override does not.
+    let base_profile = Arc::new(crate::AnthropicProviderProfile::with_default_tools(
+        "claude-sonnet-4-5",
+    ));
+    let alt_profile = Arc::new(StaticProviderProfile {
+        id: "test".to_string(),
+        model: "alt-model".to_string(),
+        base_system_prompt: "alt".to_string(),
+        tool_registry: tool_registry_with_echo(),
+        provider_options: None,
+        capabilities: ProviderCapabilities::default(),
+    });
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let mut session =
+        Session::new(base_profile, env, client, SessionConfig::default()).expect("new session");
+    session.register_provider_profile(alt_profile);
+
+    session
+        .submit_with_options(
+            "hello",
+            SubmitOptions {
+                provider: Some("test".to_string()),
+                ..SubmitOptions::default()
+            },
+        )
+        .await
+        .expect("submit should succeed");
+
+    let seen = requests.lock().expect("requests mutex");
+    assert_eq!(seen.len(), 2);
+    let tool_call_ids: Vec<Option<&str>> = seen[1]
+        .messages
+        .iter()
+        .filter(|message| message.role == Role::Tool)
+        .map(|message| message.tool_call_id.as_deref())
+        .collect();
+    assert_eq!(tool_call_ids, vec![Some("call-1"), Some("call-2")]);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn request_interceptor_mutates_outgoing_request_after_precedence_resolution() {
+    let (client, requests) = build_test_client(vec![text_response("resp-1", "done")]);
+    let profile = Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex"));
+    let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
+    let mut session =
+        Session::new(profile, env, client, SessionConfig::default()).expect("new session");
+    session.set_request_interceptor(Some(Arc::new(|request: &mut Request| {
+        request
+            .metadata
+            .get_or_insert_with(HashMap::new)
+            .insert("trace_id".to_string(), "trace-123".to_string());
+    })));
+
+    session
+        .submit_with_options(
+            "hello",
+            SubmitOptions {
+                metadata: Some(HashMap::from([("origin".to_string(), "test".to_string())])),
+                ..SubmitOptions::default()
+            },
+        )
+        .await
+        .expect("submit should succeed");
+
+    let requests = requests.lock().expect("requests mutex");
+    let metadata = requests[0]
+        .metadata
+        .as_ref()
+        .expect("metadata should exist");
+    assert_eq!(metadata.get("trace_id"), Some(&"trace-123".to_string()));
+    assert_eq!(metadata.get("origin"), Some(&"test".to_string()));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn run_diagnostics_record_recovered_tool_errors() {
+    let (client, _) = build_test_client(vec![
+        tool_call_response(
+            "resp-1",
+            "call-read",
+            "read_file",
+            serde_json::json!({ "file_path": "does-not-exist.txt" }),
+        ),
+        text_response("resp-2", "done"),
+    ]);
+    let profile = StaticProviderProfile::minimal("test", "gpt-5.2-codex")
+        .with_tool_registry(Arc::new(build_openai_tool_registry()));
+    let mut session = Session::new(
+        Arc::new(profile),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig::default(),
+    )
+    .expect("new session");
+    let diagnostics = Arc::new(crate::RunDiagnostics::new());
+    session.set_run_diagnostics(Some(diagnostics.clone()));
+
+    session
+        .submit("read it")
+        .await
+        .expect("submit should succeed");
+
+    let entries = diagnostics.entries();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].kind, crate::RecoveredErrorKind::Tool);
+    assert_eq!(entries[0].source, "read_file");
+    assert!(!entries[0].message.is_empty());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn run_diagnostics_record_session_llm_retries() {
+    let rate_limited = SDKError::Provider(forge_llm::ProviderError::new(
+        "test",
+        forge_llm::ProviderErrorKind::RateLimit,
+        "slow down",
+    ));
+    let (client, requests) = build_test_client_with_results(
+        vec![Err(rate_limited), Ok(text_response("resp-1", "done"))],
+        0,
+    );
+    let mut session = Session::new(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig {
+            llm_retry_attempts: 1,
+            llm_retry_backoff_ms: 1,
+            ..SessionConfig::default()
+        },
+    )
+    .expect("new session");
+    let diagnostics = Arc::new(crate::RunDiagnostics::new());
+    session.set_run_diagnostics(Some(diagnostics.clone()));
+
+    session.submit("hi").await.expect("retry should recover");
+
+    assert_eq!(requests.lock().expect("requests mutex").len(), 2);
+    let entries = diagnostics.entries();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].kind, crate::RecoveredErrorKind::LlmRetry);
+    assert_eq!(entries[0].source, "test");
+    assert!(entries[0].message.contains("slow down"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn set_clock_stamps_events_and_run_diagnostics() {
+    let rate_limited = SDKError::Provider(forge_llm::ProviderError::new(
+        "test",
+        forge_llm::ProviderErrorKind::RateLimit,
+        "slow down",
+    ));
+    let (client, _) = build_test_client_with_results(
+        vec![Err(rate_limited), Ok(text_response("resp-1", "done"))],
+        0,
+    );
+    let emitter = Arc::new(BufferedEventEmitter::default());
+    let mut session = Session::new_with_emitter(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig {
+            llm_retry_attempts: 1,
+            llm_retry_backoff_ms: 1,
+            ..SessionConfig::default()
+        },
+        emitter.clone(),
+    )
+    .expect("new session");
+    let clock = Arc::new(crate::FixedClock::new(std::time::Duration::from_secs(
+        1_700_000_000,
+    )));
+    session.set_clock(clock.clone());
+    let diagnostics = Arc::new(crate::RunDiagnostics::new());
+    session.set_run_diagnostics(Some(diagnostics.clone()));
+
+    session.submit("hi").await.expect("retry should recover");
+
+    let expected = clock.timestamp();
+    let events = emitter.snapshot();
+    assert!(!events.is_empty());
+    assert_eq!(events[0].kind, EventKind::SessionStart);
+    assert!(events[1..].iter().all(|event| event.timestamp == expected));
+    let entries = diagnostics.entries();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].timestamp, expected);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn submit_without_llm_retries_expected_first_error_fails() {
+    let rate_limited = SDKError::Provider(forge_llm::ProviderError::new(
+        "test",
+        forge_llm::ProviderErrorKind::RateLimit,
+        "slow down",
+    ));
+    let (client, requests) = build_test_client_with_results(
+        vec![Err(rate_limited), Ok(text_response("resp-1", "done"))],
+        0,
+    );
+    let mut session = Session::new(
+        Arc::new(StaticProviderProfile::minimal("test", "gpt-5.2-codex")),
+        Arc::new(LocalExecutionEnvironment::new(PathBuf::from("."))),
+        client,
+        SessionConfig::default(),
+    )
+    .expect("new session");
+
+    session
+        .submit("hi")
+        .await
+        .expect_err("default config should not retry");
+    assert_eq!(requests.lock().expect("requests mutex").len(), 1);
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn submit_with_result_returns_tool_ids_usage_and_thread_key() {
     let (client, _requests) = build_test_client(vec![
@@ -1598,14 +4789,9 @@ async fn checkpoint_round_trip_restores_history_and_queues() {
         text_response("resp-1", "first"),
         text_response("resp-2", "second"),
     ]);
-    let profile = Arc::new(StaticProviderProfile {
-        id: "test".to_string(),
-        model: "test-model".to_string(),
-        base_system_prompt: "base".to_string(),
-        tool_registry: Arc::new(ToolRegistry::default()),
-        provider_options: None,
-        capabilities: ProviderCapabilities::default(),
-    });
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("test", "test-model").with_base_system_prompt("base"),
+    );
     let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
     let emitter = Arc::new(BufferedEventEmitter::default());
     let mut session = Session::new_with_emitter(
@@ -1652,14 +4838,9 @@ async fn checkpoint_round_trip_restores_history_and_queues() {
 #[tokio::test(flavor = "current_thread")]
 async fn checkpoint_fails_when_subagent_task_is_running() {
     let (client, _requests) = build_test_client(vec![]);
-    let profile = Arc::new(StaticProviderProfile {
-        id: "test".to_string(),
-        model: "test-model".to_string(),
-        base_system_prompt: "base".to_string(),
-        tool_registry: Arc::new(ToolRegistry::default()),
-        provider_options: None,
-        capabilities: ProviderCapabilities::default(),
-    });
+    let profile = Arc::new(
+        StaticProviderProfile::minimal("test", "test-model").with_base_system_prompt("base"),
+    );
     let env = Arc::new(LocalExecutionEnvironment::new(PathBuf::from(".")));
     let mut session =
         Session::new(profile, env, client, SessionConfig::default()).expect("new session");
@@ -1674,6 +4855,11 @@ async fn checkpoint_fails_when_subagent_task_is_running() {
             session: None,
             active_task: Some(active_task),
             result: None,
+            max_runtime: None,
+            pending_inputs: VecDeque::new(),
+            undelivered_results: Vec::new(),
+            full_output_persisted: false,
+            complete_persisted: false,
         },
     );
 
@@ -1,8 +1,9 @@
 use super::Session;
+use crate::Attachment;
 use forge_cxdb_runtime::CxdbTurnId;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fmt::{self, Display};
 
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -13,6 +14,30 @@ pub struct SubmitOptions {
     pub system_prompt_override: Option<String>,
     pub provider_options: Option<Value>,
     pub metadata: Option<HashMap<String, String>>,
+    /// Images for this input's user turn; follow-ups queued while it runs do
+    /// not inherit them.
+    pub attachments: Vec<Attachment>,
+}
+
+/// Why the most recent `submit` stopped processing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    /// The model answered without requesting further tool calls.
+    #[default]
+    NaturalCompletion,
+    /// `max_tool_rounds_per_input` was reached while the model still wanted tools.
+    RoundLimit,
+    /// `max_turns` was reached for the session history.
+    TurnLimit,
+    /// `submit_deadline_ms` elapsed; the in-flight step finished first.
+    DeadlineExceeded,
+    /// `max_session_tokens` was used up before the next model call.
+    TokenBudgetExceeded,
+    /// An abort was requested and the session closed.
+    Aborted,
+    /// The model asked the user a question and the session is awaiting input.
+    AwaitingInput,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -24,6 +49,8 @@ pub struct SubmitResult {
     pub tool_error_count: usize,
     pub usage: Option<forge_llm::Usage>,
     pub thread_key: Option<String>,
+    #[serde(default)]
+    pub stopped_reason: StopReason,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -35,6 +62,12 @@ pub struct SessionCheckpoint {
     pub followup_queue: Vec<String>,
     pub config: super::SessionConfig,
     pub thread_key: Option<String>,
+    /// Restored so a resumed session keeps counting against
+    /// `max_session_tokens` instead of starting from zero.
+    #[serde(default)]
+    pub session_tokens_used: u64,
+    #[serde(default)]
+    pub last_call_tokens: u64,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -50,6 +83,8 @@ pub enum SessionState {
     Idle,
     Processing,
     AwaitingInput,
+    /// Suspended between submits; queues still accept input.
+    Paused,
     Closed,
 }
 
@@ -59,6 +94,7 @@ impl SessionState {
             Self::Idle => "IDLE",
             Self::Processing => "PROCESSING",
             Self::AwaitingInput => "AWAITING_INPUT",
+            Self::Paused => "PAUSED",
             Self::Closed => "CLOSED",
         }
     }
@@ -73,12 +109,19 @@ impl SessionState {
         }
 
         match self {
-            SessionState::Idle => matches!(next, SessionState::Processing),
+            SessionState::Idle => {
+                matches!(next, SessionState::Processing | SessionState::Paused)
+            }
             SessionState::Processing => matches!(
                 next,
                 SessionState::Processing | SessionState::AwaitingInput | SessionState::Idle
             ),
-            SessionState::AwaitingInput => matches!(next, SessionState::Processing),
+            SessionState::AwaitingInput => {
+                matches!(next, SessionState::Processing | SessionState::Paused)
+            }
+            SessionState::Paused => {
+                matches!(next, SessionState::Idle | SessionState::AwaitingInput)
+            }
             SessionState::Closed => false,
         }
     }
@@ -107,6 +150,19 @@ pub(super) struct SubAgentRecord {
     pub(super) session: Option<Box<Session>>,
     pub(super) active_task: Option<tokio::task::JoinHandle<SubAgentTaskOutput>>,
     pub(super) result: Option<SubAgentResult>,
+    /// Applied to every submit the subagent runs, including `send_input`.
+    pub(super) max_runtime: Option<std::time::Duration>,
+    /// `send_input` messages queued while a submit was running, delivered in
+    /// order as each submit completes.
+    pub(super) pending_inputs: VecDeque<String>,
+    /// Results of submits that finished while a queued input was waiting,
+    /// reported (and cleared) by the next `wait`.
+    pub(super) undelivered_results: Vec<SubAgentResult>,
+    /// Set once the full output of a capped result has been persisted.
+    pub(super) full_output_persisted: bool,
+    /// Set once `result` has its `forge.link.subagent_complete` turn; a
+    /// failed write leaves it unset so the next reconcile retries it.
+    pub(super) complete_persisted: bool,
 }
 
 pub(super) struct SubAgentTaskOutput {
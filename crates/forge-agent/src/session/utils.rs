@@ -1,22 +1,75 @@
 use super::{
-    AgentError, EnvironmentContext, ExecutionEnvironment, Message, ProjectDocument,
-    ProviderProfile, Session, SessionError, SubAgentResult, SubAgentStatus, SubAgentTaskOutput,
-    ToolCall, ToolError, Turn,
+    AgentError, Clock, EnvironmentContext, ExecutionEnvironment, Message, ProjectDocument,
+    ProviderProfile, Session, SessionConfig, SessionError, SubAgentResult, SubAgentStatus,
+    SubAgentTaskOutput, ToolCall, ToolError, Turn, Uuid,
+};
+use crate::profiles::format_text_only_tools_block;
+use crate::{
+    AttachmentSource, EnvironmentContextFields, RecoveredErrorKind, RunDiagnostics,
+    ToolsUnsupportedPolicy, UserTurn,
+};
+use forge_llm::{
+    ContentPart, ImageData, ProviderErrorKind, RetryPolicy, Role, SDKError, ThinkingData,
+    ToolCallData, ToolDefinition, ToolResultData,
 };
-use forge_llm::{ContentPart, Role, ThinkingData, ToolCallData};
 use serde_json::Value;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
+use std::time::Duration;
 
-pub(super) fn is_subagent_tool(tool_name: &str) -> bool {
+pub(crate) fn is_subagent_tool(tool_name: &str) -> bool {
     matches!(
         tool_name,
         "spawn_agent" | "send_input" | "wait" | "close_agent"
     )
 }
 
+/// Tools answered by the `Session` itself rather than the tool registry.
+pub(super) fn is_session_tool(tool_name: &str) -> bool {
+    is_subagent_tool(tool_name) || tool_name == crate::AGENT_STATUS_TOOL
+}
+
+pub(super) const SUBAGENT_SUMMARY_PROMPT: &str = "Condense the following subagent report for the agent that delegated the task. Keep conclusions, file paths, identifiers, errors and anything the parent must act on; drop raw file contents and repetition.";
+
+/// Truncates `output` to at most `max_bytes` on a char boundary and appends
+/// a marker saying how much was kept.
+pub(super) fn cap_output_bytes(output: &str, max_bytes: usize) -> String {
+    if output.len() <= max_bytes {
+        return output.to_string();
+    }
+    let mut end = max_bytes;
+    while !output.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!(
+        "{}\n[subagent output truncated: showing {} of {} bytes]",
+        &output[..end],
+        end,
+        output.len()
+    )
+}
+
+pub(super) fn resolve_session_id(config: &SessionConfig) -> String {
+    if let Some(session_id) = config
+        .session_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        return session_id.to_string();
+    }
+    match config.id_seed.as_deref() {
+        Some(seed) => deterministic_uuid(seed),
+        None => Uuid::new_v4().to_string(),
+    }
+}
+
+pub(super) fn deterministic_uuid(seed: &str) -> String {
+    Uuid::new_v5(&Uuid::NAMESPACE_OID, seed.as_bytes()).to_string()
+}
+
 pub(super) fn parse_tool_call_arguments(tool_call: &ToolCall) -> Result<Value, AgentError> {
     if let Some(raw_arguments) = &tool_call.raw_arguments {
         let parsed = serde_json::from_str::<Value>(raw_arguments).map_err(|error| {
@@ -75,23 +128,39 @@ pub(super) fn latest_assistant_output(history: &[Turn]) -> Option<String> {
     })
 }
 
+/// Runs one subagent submit. Past `max_runtime` the submit is dropped
+/// mid-flight and the session closed, so the result reports a failure.
 pub(super) fn spawn_subagent_submit_task(
     mut session: Box<Session>,
     input: String,
+    max_runtime: Option<Duration>,
 ) -> tokio::task::JoinHandle<SubAgentTaskOutput> {
     tokio::spawn(async move {
-        let completion = session.submit(input).await;
+        let completion = match max_runtime {
+            Some(limit) => tokio::time::timeout(limit, session.submit(input))
+                .await
+                .map_err(|_| limit),
+            None => Ok(session.submit(input).await),
+        };
         let result = match completion {
-            Ok(_) => SubAgentResult {
+            Ok(Ok(_)) => SubAgentResult {
                 output: latest_assistant_output(session.history()).unwrap_or_default(),
                 success: true,
                 turns_used: session.history().len(),
             },
-            Err(error) => SubAgentResult {
+            Ok(Err(error)) => SubAgentResult {
                 output: error.to_string(),
                 success: false,
                 turns_used: session.history().len(),
             },
+            Err(limit) => {
+                let _ = session.shutdown().await;
+                SubAgentResult {
+                    output: format!("subagent timed out after {} ms", limit.as_millis()),
+                    success: false,
+                    turns_used: session.history().len(),
+                }
+            }
         };
         SubAgentTaskOutput { session, result }
     })
@@ -141,19 +210,76 @@ pub(crate) fn should_transition_to_awaiting_input(text: &str) -> bool {
     word_count >= 3
 }
 
-pub(crate) fn convert_history_to_messages(history: &[Turn]) -> Vec<Message> {
+fn user_message(turn: &UserTurn) -> Message {
+    let mut message = Message::user(turn.content.clone());
+    message
+        .content
+        .extend(turn.attachments.iter().map(|attachment| {
+            let (url, data) = match &attachment.data_or_url {
+                AttachmentSource::Url(url) => (Some(url.clone()), None),
+                AttachmentSource::Data(data) => (None, Some(data.clone())),
+            };
+            ContentPart::image(ImageData {
+                url,
+                data,
+                media_type: Some(attachment.mime.clone()),
+                detail: None,
+            })
+        }));
+    message
+}
+
+/// Applies `ToolsUnsupportedPolicy` for profiles without native tool support.
+/// Returns the tools to send natively and, when they are described in the
+/// prompt instead, the block to append to the system prompt. Callers build the
+/// system prompt with no tools in that case so they are listed only once.
+pub(crate) fn gate_native_tools(
+    provider_profile: &dyn ProviderProfile,
+    policy: ToolsUnsupportedPolicy,
+    tools: Vec<ToolDefinition>,
+) -> Result<(Option<Vec<ToolDefinition>>, Option<String>), SessionError> {
+    if tools.is_empty() {
+        return Ok((None, None));
+    }
+    if provider_profile.capabilities().supports_tools {
+        return Ok((Some(tools), None));
+    }
+    match policy {
+        ToolsUnsupportedPolicy::Error => Err(SessionError::ToolsUnsupported(
+            provider_profile.id().to_string(),
+        )),
+        ToolsUnsupportedPolicy::DescribeInPrompt => {
+            Ok((None, Some(format_text_only_tools_block(&tools))))
+        }
+    }
+}
+
+/// Converts session history into request messages. With `group_tool_results`,
+/// each `ToolResultsTurn` becomes a single tool message carrying every result.
+/// Without `include_historical_reasoning`, only the last assistant turn keeps
+/// its reasoning.
+pub(crate) fn convert_history_to_messages(
+    history: &[Turn],
+    group_tool_results: bool,
+    include_historical_reasoning: bool,
+) -> Vec<Message> {
     let mut messages = Vec::new();
+    let last_assistant_index = history
+        .iter()
+        .rposition(|turn| matches!(turn, Turn::Assistant(_)));
 
-    for turn in history {
+    for (index, turn) in history.iter().enumerate() {
         match turn {
-            Turn::User(turn) => messages.push(Message::user(turn.content.clone())),
+            Turn::User(turn) => messages.push(user_message(turn)),
             Turn::Assistant(turn) => {
                 let mut content = Vec::new();
                 if !turn.content.is_empty() {
                     content.push(ContentPart::text(turn.content.clone()));
                 }
 
-                if let Some(reasoning) = &turn.reasoning {
+                let keep_reasoning =
+                    include_historical_reasoning || Some(index) == last_assistant_index;
+                if let Some(reasoning) = turn.reasoning.as_ref().filter(|_| keep_reasoning) {
                     if !reasoning.is_empty() {
                         content.push(ContentPart::thinking(ThinkingData {
                             text: reasoning.clone(),
@@ -172,8 +298,11 @@ pub(crate) fn convert_history_to_messages(history: &[Turn]) -> Vec<Message> {
                     }));
                 }
 
+                // Empty text parts are rejected by some providers, so a turn
+                // with nothing to replay is dropped. The crate has no logging
+                // facility; the turn itself stays in history.
                 if content.is_empty() {
-                    content.push(ContentPart::text(String::new()));
+                    continue;
                 }
 
                 messages.push(Message {
@@ -183,6 +312,27 @@ pub(crate) fn convert_history_to_messages(history: &[Turn]) -> Vec<Message> {
                     tool_call_id: None,
                 });
             }
+            Turn::ToolResults(turn) if group_tool_results && !turn.results.is_empty() => {
+                let content = turn
+                    .results
+                    .iter()
+                    .map(|result| {
+                        ContentPart::tool_result(ToolResultData {
+                            tool_call_id: result.tool_call_id.clone(),
+                            content: result.content.clone(),
+                            is_error: result.is_error,
+                            image_data: None,
+                            image_media_type: None,
+                        })
+                    })
+                    .collect();
+                messages.push(Message {
+                    role: Role::Tool,
+                    content,
+                    name: None,
+                    tool_call_id: None,
+                });
+            }
             Turn::ToolResults(turn) => {
                 for result in &turn.results {
                     messages.push(Message::tool_result(
@@ -200,11 +350,38 @@ pub(crate) fn convert_history_to_messages(history: &[Turn]) -> Vec<Message> {
     messages
 }
 
+/// Retry policy for model calls: `llm_retry_attempts` retries of retryable
+/// errors with doubling backoff from `llm_retry_backoff_ms`. Each retry is
+/// recorded in `diagnostics` under `provider`, stamped by `clock`.
+pub(crate) fn llm_retry_policy(
+    config: &SessionConfig,
+    provider: &str,
+    diagnostics: Option<Arc<RunDiagnostics>>,
+    clock: Arc<dyn Clock>,
+) -> RetryPolicy {
+    let provider = provider.to_string();
+    RetryPolicy {
+        max_retries: config.llm_retry_attempts as usize,
+        base_delay: config.llm_retry_backoff_ms as f64 / 1000.0,
+        jitter: false,
+        on_retry: diagnostics.map(|diagnostics| {
+            Arc::new(move |error: &SDKError, attempt: usize, delay: f64| {
+                diagnostics.record_at(
+                    RecoveredErrorKind::LlmRetry,
+                    provider.clone(),
+                    format!("retry {} in {:.3}s after: {}", attempt + 1, delay, error),
+                    clock.timestamp(),
+                );
+            }) as Arc<dyn Fn(&SDKError, usize, f64) + Send + Sync>
+        }),
+        ..RetryPolicy::default()
+    }
+}
+
+#[cfg(test)]
 pub(crate) fn current_timestamp() -> String {
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default();
-    now.as_secs().to_string()
+    use crate::clock::Clock;
+    crate::clock::SystemClock.timestamp()
 }
 
 pub(super) fn current_date_yyyy_mm_dd() -> String {
@@ -231,18 +408,17 @@ pub(super) fn current_date_yyyy_mm_dd() -> String {
 pub(crate) fn build_environment_context_snapshot(
     provider_profile: &dyn ProviderProfile,
     execution_env: &dyn ExecutionEnvironment,
+    fields: &EnvironmentContextFields,
 ) -> EnvironmentContext {
     let working_directory = canonicalize_or_fallback(execution_env.working_directory());
     let repository_root = find_git_repository_root(&working_directory);
-    let (git_branch, git_status_summary, git_recent_commits) = if let Some(root) = &repository_root
-    {
-        (
+    let (git_branch, git_status_summary, git_recent_commits) = match &repository_root {
+        Some(root) if fields.git_details => (
             git_current_branch(root),
             git_status_summary(root),
             git_recent_commits(root, 5),
-        )
-    } else {
-        (None, None, Vec::new())
+        ),
+        _ => (None, None, Vec::new()),
     };
 
     EnvironmentContext {
@@ -259,21 +435,29 @@ pub(crate) fn build_environment_context_snapshot(
         date_yyyy_mm_dd: current_date_yyyy_mm_dd(),
         model: provider_profile.model().to_string(),
         knowledge_cutoff: provider_profile.knowledge_cutoff().map(str::to_string),
+        fields: fields.clone(),
     }
 }
 
 pub(crate) fn discover_project_documents(
     working_directory: &Path,
     provider_profile: &dyn ProviderProfile,
+    extra_instruction_files: &[String],
 ) -> Vec<ProjectDocument> {
     const PROJECT_DOC_BYTE_BUDGET: usize = 32 * 1024;
     let working_directory = canonicalize_or_fallback(working_directory);
     let root =
         find_git_repository_root(&working_directory).unwrap_or_else(|| working_directory.clone());
     let directories = path_chain_from_root_to_cwd(&root, &working_directory);
-    let instruction_files = provider_profile.project_instruction_files();
+    let mut instruction_files = provider_profile.project_instruction_files();
+    for extra in extra_instruction_files {
+        if !instruction_files.contains(extra) {
+            instruction_files.push(extra.clone());
+        }
+    }
 
     let mut docs = Vec::new();
+    let mut used = 0usize;
     for directory in directories {
         for instruction_file in &instruction_files {
             let candidate = directory.join(instruction_file);
@@ -283,6 +467,15 @@ pub(crate) fn discover_project_documents(
             let Ok(content) = std::fs::read_to_string(&candidate) else {
                 continue;
             };
+            let doc_path = canonicalize_or_fallback(&candidate);
+            let content = expand_project_doc_includes(
+                &content,
+                &doc_path,
+                &root,
+                &mut vec![doc_path.clone()],
+                PROJECT_DOC_BYTE_BUDGET.saturating_sub(used),
+            );
+            used += content.len();
             let relative = candidate
                 .strip_prefix(&root)
                 .unwrap_or(&candidate)
@@ -298,6 +491,98 @@ pub(crate) fn discover_project_documents(
     truncate_project_documents_to_budget(docs, PROJECT_DOC_BYTE_BUDGET)
 }
 
+const PROJECT_DOC_INCLUDE_MAX_DEPTH: usize = 8;
+const PROJECT_DOC_INLINE_INCLUDE_OPEN: &str = "{{include:";
+
+/// Expands `@include <path>` lines and inline `{{include:<path>}}` directives,
+/// resolving paths against the including file's directory. Includes must stay
+/// inside `root`; missing files, cycles, and nesting beyond
+/// `PROJECT_DOC_INCLUDE_MAX_DEPTH` leave a visible marker instead.
+/// `stack` holds the canonical paths of the files currently being expanded.
+/// Once the expanded text reaches `budget` bytes, further includes are not
+/// read, so fan-out include graphs cannot grow far past the project-doc budget.
+pub(super) fn expand_project_doc_includes(
+    content: &str,
+    doc_path: &Path,
+    root: &Path,
+    stack: &mut Vec<PathBuf>,
+    budget: usize,
+) -> String {
+    let base_dir = doc_path.parent().unwrap_or(root);
+    let mut expanded = String::with_capacity(content.len().min(budget));
+    for line in content.split_inclusive('\n') {
+        let body = line.trim_end_matches(['\n', '\r']);
+        if let Some(target) = body.trim_start().strip_prefix("@include ") {
+            expanded.push_str(&resolve_project_doc_include(
+                target.trim(),
+                base_dir,
+                root,
+                stack,
+                budget.saturating_sub(expanded.len()),
+            ));
+            expanded.push_str(&line[body.len()..]);
+            continue;
+        }
+
+        let mut rest = line;
+        while let Some(start) = rest.find(PROJECT_DOC_INLINE_INCLUDE_OPEN) {
+            let after_open = &rest[start + PROJECT_DOC_INLINE_INCLUDE_OPEN.len()..];
+            let Some(end) = after_open.find("}}") else {
+                break;
+            };
+            expanded.push_str(&rest[..start]);
+            expanded.push_str(&resolve_project_doc_include(
+                after_open[..end].trim(),
+                base_dir,
+                root,
+                stack,
+                budget.saturating_sub(expanded.len()),
+            ));
+            rest = &after_open[end + 2..];
+        }
+        expanded.push_str(rest);
+    }
+    expanded
+}
+
+fn resolve_project_doc_include(
+    target: &str,
+    base_dir: &Path,
+    root: &Path,
+    stack: &mut Vec<PathBuf>,
+    budget: usize,
+) -> String {
+    let skipped = |reason: &str| format!("<!-- include '{target}' skipped: {reason} -->");
+    if budget == 0 {
+        return skipped("project doc budget exhausted");
+    }
+    let candidate = base_dir.join(target);
+    if target.is_empty() || !candidate.is_file() {
+        return skipped("file not found");
+    }
+    let candidate = canonicalize_or_fallback(&candidate);
+    if !candidate.starts_with(root) {
+        return skipped("outside the repository");
+    }
+    if stack.contains(&candidate) {
+        return skipped("include cycle");
+    }
+    if stack.len() > PROJECT_DOC_INCLUDE_MAX_DEPTH {
+        return skipped("include depth limit reached");
+    }
+    let Ok(content) = std::fs::read_to_string(&candidate) else {
+        return skipped("file not readable");
+    };
+
+    stack.push(candidate.clone());
+    let expanded = expand_project_doc_includes(&content, &candidate, root, stack, budget);
+    stack.pop();
+    match expanded.strip_suffix('\n') {
+        Some(trimmed) => trimmed.to_string(),
+        None => expanded,
+    }
+}
+
 pub(super) fn truncate_project_documents_to_budget(
     docs: Vec<ProjectDocument>,
     byte_budget: usize,
@@ -526,6 +811,73 @@ pub(crate) fn tool_call_signature(tool_call: &forge_llm::ToolCall) -> u64 {
     hasher.finish()
 }
 
+/// Lower-cased message fragments providers use when a request overflows the
+/// context window (OpenAI, Anthropic, Gemini).
+const CONTEXT_OVERFLOW_SIGNATURES: &[&str] = &[
+    "context_length_exceeded",
+    "maximum context length",
+    "prompt is too long",
+    "exceeds the maximum number of tokens",
+    "exceed context limit",
+];
+
+/// Detects provider context-overflow errors. Returns the `(tokens, limit)`
+/// pair when the message states both counts, `(None, None)` otherwise.
+pub(crate) fn classify_context_overflow(
+    error: &SDKError,
+) -> Option<(Option<usize>, Option<usize>)> {
+    let (message, flagged) = match error {
+        SDKError::Provider(provider) => (
+            provider.info.message.as_str(),
+            provider.kind == ProviderErrorKind::ContextLength
+                || provider.error_code.as_deref() == Some("context_length_exceeded"),
+        ),
+        SDKError::Other(message) => (message.as_str(), false),
+        _ => return None,
+    };
+    let lower = message.to_ascii_lowercase();
+    if !flagged
+        && !CONTEXT_OVERFLOW_SIGNATURES
+            .iter()
+            .any(|signature| lower.contains(signature))
+    {
+        return None;
+    }
+
+    // Only the known phrasings are parsed; other numbers in the message
+    // (e.g. OpenAI's per-part breakdown) are not counts of the whole request.
+    let (tokens, limit) = if let Some(limit) = number_after(&lower, "maximum context length is") {
+        let tokens =
+            number_after(&lower, "you requested").or_else(|| number_after(&lower, "resulted in"));
+        (tokens, Some(limit))
+    } else if lower.contains("prompt is too long") {
+        (
+            number_after(&lower, "prompt is too long:"),
+            number_after(&lower, "tokens >"),
+        )
+    } else {
+        (
+            number_after(&lower, "input token count"),
+            number_after(&lower, "maximum number of tokens allowed"),
+        )
+    };
+    match (tokens, limit) {
+        (Some(tokens), Some(limit)) => Some((Some(tokens), Some(limit))),
+        _ => Some((None, None)),
+    }
+}
+
+/// The integer right after `marker`, skipping spaces, `(` and `:`.
+fn number_after(text: &str, marker: &str) -> Option<usize> {
+    let start = text.find(marker)? + marker.len();
+    let digits: String = text[start..]
+        .trim_start_matches([' ', '(', ':'])
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    digits.parse().ok()
+}
+
 pub(crate) fn approximate_context_tokens(history: &[Turn]) -> usize {
     total_chars_in_history(history) / 4
 }
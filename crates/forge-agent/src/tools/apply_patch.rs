@@ -4,7 +4,10 @@ use std::sync::Arc;
 
 use crate::patch;
 
-use super::{APPLY_PATCH_TOOL, RegisteredTool, required_string_argument};
+use super::{
+    APPLY_PATCH_TOOL, RegisteredTool, ToolMetadata, fuzzy_match_min_confidence,
+    optional_bool_argument, required_string_argument,
+};
 
 pub(super) fn apply_patch_tool() -> RegisteredTool {
     RegisteredTool {
@@ -15,7 +18,8 @@ pub(super) fn apply_patch_tool() -> RegisteredTool {
                 "type": "object",
                 "required": ["patch"],
                 "properties": {
-                    "patch": { "type": "string" }
+                    "patch": { "type": "string" },
+                    "partial": { "type": "boolean" }
                 },
                 "additionalProperties": false
             }),
@@ -23,10 +27,18 @@ pub(super) fn apply_patch_tool() -> RegisteredTool {
         executor: Arc::new(|args, env| {
             Box::pin(async move {
                 let patch = required_string_argument(&args, "patch")?;
+                let partial = optional_bool_argument(&args, "partial")?.unwrap_or(false);
                 let operations = patch::parse_apply_patch(&patch)?;
-                patch::apply_patch_operations(&operations, env).await
+                let min_confidence = fuzzy_match_min_confidence(&args);
+                patch::apply_patch_operations(&operations, env, partial, min_confidence).await
             })
         }),
+        structured_output: false,
+        output_envelope: false,
+        metadata: ToolMetadata {
+            destructive: true,
+            ..ToolMetadata::default()
+        },
     }
 }
 
@@ -114,4 +126,65 @@ mod tests {
         .expect_err("executor should fail");
         assert!(err.to_string().contains("must end with '*** End Patch'"));
     }
+
+    fn two_file_patch() -> String {
+        [
+            "*** Begin Patch",
+            "*** Update File: good.txt",
+            "@@",
+            "-alpha",
+            "+ALPHA",
+            "*** Update File: bad.txt",
+            "@@",
+            "-missing",
+            "+replacement",
+            "*** End Patch",
+        ]
+        .join("\n")
+    }
+
+    fn two_file_workspace() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::write(dir.path().join("good.txt"), "alpha\n").expect("good file written");
+        std::fs::write(dir.path().join("bad.txt"), "beta\n").expect("bad file written");
+        dir
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn apply_patch_tool_partial_mode_reports_applied_and_skipped_files() {
+        let dir = two_file_workspace();
+        let env = Arc::new(crate::LocalExecutionEnvironment::new(dir.path()));
+
+        let output =
+            (apply_patch_tool().executor)(json!({"patch": two_file_patch(), "partial": true}), env)
+                .await
+                .expect("partial apply should succeed");
+
+        assert!(
+            output
+                .starts_with("Partially applied patch:\nApplied:\nM good.txt (+1 -1)\nSkipped:\n")
+        );
+        assert!(output.contains("bad.txt: "));
+        assert!(output.contains("failed to match hunk"));
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("good.txt")).expect("good file read"),
+            "ALPHA\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("bad.txt")).expect("bad file read"),
+            "beta\n"
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn apply_patch_tool_defaults_to_all_or_nothing_failure() {
+        let dir = two_file_workspace();
+        let env = Arc::new(crate::LocalExecutionEnvironment::new(dir.path()));
+
+        let err = (apply_patch_tool().executor)(json!({"patch": two_file_patch()}), env)
+            .await
+            .expect_err("strict apply should fail");
+
+        assert!(err.to_string().contains("failed to match hunk"));
+    }
 }
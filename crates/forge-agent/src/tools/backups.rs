@@ -0,0 +1,338 @@
+use forge_llm::ToolDefinition;
+use serde_json::json;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use super::recording::{FileChange, PathSnapshot};
+use super::{RegisteredTool, ToolExecutor, ToolMetadata, UNDO_EDIT_TOOL};
+use crate::{AgentError, ExecutionEnvironment, SessionConfig, ToolError};
+
+/// In-session undo stack for file-mutating tools. Each entry holds the prior
+/// state of every path touched by one tool call; `undo_edit` restores the
+/// most recent entry. The oldest entries are evicted once either cap is hit.
+pub struct EditBackups {
+    max_entries: usize,
+    max_bytes: usize,
+    entries: Mutex<VecDeque<Vec<FileBackup>>>,
+}
+
+#[derive(Clone, Debug)]
+struct FileBackup {
+    path: String,
+    /// `None` when the path did not exist before the edit.
+    previous: Option<PathSnapshot>,
+}
+
+impl EditBackups {
+    pub(crate) fn for_config(config: &SessionConfig) -> Option<Arc<Self>> {
+        config.keep_edit_backups.then(|| {
+            Arc::new(Self::new(
+                config.edit_backup_max_entries,
+                config.edit_backup_max_bytes,
+            ))
+        })
+    }
+
+    pub fn new(max_entries: usize, max_bytes: usize) -> Self {
+        Self {
+            max_entries,
+            max_bytes,
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().expect("edit backups mutex").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(super) fn commit(&self, changes: &[FileChange]) {
+        if changes.is_empty() {
+            return;
+        }
+        let entry = changes
+            .iter()
+            .map(|change| FileBackup {
+                path: change.path.clone(),
+                previous: change.previous.clone(),
+            })
+            .collect();
+        let mut entries = self.entries.lock().expect("edit backups mutex");
+        entries.push_back(entry);
+        while entries.len() > self.max_entries || stored_bytes(&entries) > self.max_bytes {
+            if entries.pop_front().is_none() {
+                break;
+            }
+        }
+    }
+
+    pub(super) fn undo_executor(self: &Arc<Self>) -> ToolExecutor {
+        let backups = self.clone();
+        Arc::new(move |_args, env| {
+            let backups = backups.clone();
+            Box::pin(async move { backups.undo(env.as_ref()).await })
+        })
+    }
+
+    async fn undo(&self, env: &dyn ExecutionEnvironment) -> Result<String, AgentError> {
+        let entry = self.entries.lock().expect("edit backups mutex").pop_back();
+        let Some(entry) = entry else {
+            return Err(ToolError::Execution("no edits to undo".to_string()).into());
+        };
+
+        let mut restored = Vec::with_capacity(entry.len());
+        for backup in entry.iter().rev() {
+            match &backup.previous {
+                Some(PathSnapshot::File(content)) => {
+                    env.write_file_bytes(&backup.path, content).await?;
+                    restored.push(format!("restored {}", backup.path));
+                }
+                Some(PathSnapshot::Directory) => {
+                    env.create_directory(&backup.path, true).await?;
+                    restored.push(format!("restored {}", backup.path));
+                }
+                None => {
+                    if env.is_directory(&backup.path).await? {
+                        // Paths below it were recorded later, so they are gone already.
+                        env.delete_directory(&backup.path, false).await?;
+                    } else if env.file_exists(&backup.path).await? {
+                        env.delete_file(&backup.path).await?;
+                    }
+                    restored.push(format!("removed {}", backup.path));
+                }
+            }
+        }
+        Ok(format!("Undid last edit: {}", restored.join(", ")))
+    }
+}
+
+fn stored_bytes(entries: &VecDeque<Vec<FileBackup>>) -> usize {
+    entries
+        .iter()
+        .flatten()
+        .map(|backup| backup.previous.as_ref().map_or(0, PathSnapshot::len))
+        .sum()
+}
+
+pub(super) fn undo_edit_tool() -> RegisteredTool {
+    RegisteredTool {
+        definition: ToolDefinition {
+            name: UNDO_EDIT_TOOL.to_string(),
+            description:
+                "Revert the most recent file change made by write_file, edit_file, apply_patch, move_file, delete, or create_directory."
+                    .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }),
+        },
+        executor: Arc::new(|_args, _env| {
+            Box::pin(async move {
+                Err(ToolError::Execution(
+                    "undo_edit requires SessionConfig::keep_edit_backups".to_string(),
+                )
+                .into())
+            })
+        }),
+        structured_output: false,
+        output_envelope: false,
+        metadata: ToolMetadata {
+            destructive: true,
+            ..ToolMetadata::default()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EditBackups;
+    use crate::{
+        LocalExecutionEnvironment, NoopEventEmitter, SessionConfig, ToolDispatchOptions,
+        build_anthropic_tool_registry,
+    };
+    use forge_llm::{ToolCall, ToolResult};
+    use serde_json::{Value, json};
+    use std::sync::Arc;
+
+    async fn dispatch(
+        env: &Arc<LocalExecutionEnvironment>,
+        backups: &Arc<EditBackups>,
+        name: &str,
+        arguments: Value,
+    ) -> ToolResult {
+        let mut results = build_anthropic_tool_registry()
+            .dispatch(
+                vec![ToolCall {
+                    id: "call-1".to_string(),
+                    name: name.to_string(),
+                    arguments,
+                    raw_arguments: None,
+                }],
+                env.clone(),
+                &SessionConfig::default(),
+                Arc::new(NoopEventEmitter),
+                ToolDispatchOptions {
+                    session_id: "session-1".to_string(),
+                    supports_parallel_tool_calls: false,
+                    force_sequential: false,
+                    hook: None,
+                    hook_strict: false,
+                    edit_backups: Some(backups.clone()),
+                },
+            )
+            .await
+            .expect("dispatch should succeed");
+        results.pop().expect("one result")
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn undo_edit_restores_intermediate_then_original_content() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        let path = dir.path().join("f.txt");
+        std::fs::write(&path, "original\n").expect("file should be written");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
+        let backups = Arc::new(EditBackups::new(10, 1024));
+
+        for (old, new) in [("original", "intermediate"), ("intermediate", "final")] {
+            let result = dispatch(
+                &env,
+                &backups,
+                "edit_file",
+                json!({"file_path":"f.txt","old_string":old,"new_string":new}),
+            )
+            .await;
+            assert!(!result.is_error, "{:?}", result.content);
+        }
+        assert_eq!(backups.len(), 2);
+
+        let read = || std::fs::read_to_string(&path).expect("file should be readable");
+        assert!(
+            !dispatch(&env, &backups, "undo_edit", json!({}))
+                .await
+                .is_error
+        );
+        assert_eq!(read(), "intermediate\n");
+        assert!(
+            !dispatch(&env, &backups, "undo_edit", json!({}))
+                .await
+                .is_error
+        );
+        assert_eq!(read(), "original\n");
+
+        let exhausted = dispatch(&env, &backups, "undo_edit", json!({})).await;
+        assert!(exhausted.is_error);
+        assert!(backups.is_empty());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn undo_edit_removes_file_created_by_write_and_respects_entry_cap() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
+        let backups = Arc::new(EditBackups::new(1, 1024));
+
+        for content in ["one", "two"] {
+            dispatch(
+                &env,
+                &backups,
+                "write_file",
+                json!({"file_path":"new.txt","content":content}),
+            )
+            .await;
+        }
+        assert_eq!(backups.len(), 1);
+
+        assert!(
+            !dispatch(&env, &backups, "undo_edit", json!({}))
+                .await
+                .is_error
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("new.txt")).expect("file should exist"),
+            "one"
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn undo_edit_restores_deleted_non_utf8_file() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        let bytes = vec![0xff, 0xfe, 0x00, b'x'];
+        std::fs::write(dir.path().join("blob.bin"), &bytes).expect("file should be written");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
+        let backups = Arc::new(EditBackups::new(10, 1024));
+
+        let deleted = dispatch(&env, &backups, "delete", json!({"path":"blob.bin"})).await;
+        assert!(!deleted.is_error, "{:?}", deleted.content);
+        assert!(!dir.path().join("blob.bin").exists());
+
+        assert!(
+            !dispatch(&env, &backups, "undo_edit", json!({}))
+                .await
+                .is_error
+        );
+        assert_eq!(
+            std::fs::read(dir.path().join("blob.bin")).expect("file should be restored"),
+            bytes
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn undo_edit_reverses_directory_move() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::create_dir_all(dir.path().join("src/nested")).expect("dirs should be created");
+        std::fs::write(dir.path().join("src/a.txt"), "alpha").expect("file should be written");
+        std::fs::write(dir.path().join("src/nested/b.txt"), "beta").expect("file written");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
+        let backups = Arc::new(EditBackups::new(10, 1024));
+
+        let moved = dispatch(
+            &env,
+            &backups,
+            "move_file",
+            json!({"from":"src","to":"dst"}),
+        )
+        .await;
+        assert!(!moved.is_error, "{:?}", moved.content);
+        assert!(dir.path().join("dst/nested/b.txt").exists());
+
+        let undone = dispatch(&env, &backups, "undo_edit", json!({})).await;
+        assert!(!undone.is_error, "{:?}", undone.content);
+        assert!(!dir.path().join("dst").exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("src/a.txt")).expect("file should exist"),
+            "alpha"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("src/nested/b.txt"))
+                .expect("file should exist"),
+            "beta"
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn undo_edit_removes_directories_created_recursively() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
+        let backups = Arc::new(EditBackups::new(10, 1024));
+
+        let created = dispatch(
+            &env,
+            &backups,
+            "create_directory",
+            json!({"path":"a/b/c","recursive":true}),
+        )
+        .await;
+        assert!(!created.is_error, "{:?}", created.content);
+        assert!(dir.path().join("a/b/c").is_dir());
+
+        assert!(
+            !dispatch(&env, &backups, "undo_edit", json!({}))
+                .await
+                .is_error
+        );
+        assert!(!dir.path().join("a").exists());
+    }
+}
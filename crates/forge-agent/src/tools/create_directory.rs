@@ -0,0 +1,105 @@
+use forge_llm::ToolDefinition;
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::ToolError;
+
+use super::{
+    CREATE_DIRECTORY_TOOL, RegisteredTool, ToolMetadata, is_confined, optional_bool_argument,
+    required_string_argument,
+};
+
+pub(super) fn create_directory_tool() -> RegisteredTool {
+    RegisteredTool {
+        definition: ToolDefinition {
+            name: CREATE_DIRECTORY_TOOL.to_string(),
+            description: "Create a directory; recursive also creates missing parents.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "required": ["path"],
+                "properties": {
+                    "path": { "type": "string" },
+                    "recursive": { "type": "boolean" }
+                },
+                "additionalProperties": false
+            }),
+        },
+        executor: Arc::new(|args, env| {
+            Box::pin(async move {
+                let path = required_string_argument(&args, "path")?;
+                let recursive = optional_bool_argument(&args, "recursive")?.unwrap_or(false);
+
+                if !is_confined(env.working_directory(), &path) {
+                    return Err(ToolError::Validation(format!(
+                        "refusing to create '{}': path must be inside the working directory",
+                        path
+                    ))
+                    .into());
+                }
+                env.create_directory(&path, recursive).await?;
+                Ok(format!("Created directory {}", path))
+            })
+        }),
+        structured_output: false,
+        output_envelope: false,
+        metadata: ToolMetadata {
+            destructive: true,
+            ..ToolMetadata::default()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::create_directory_tool;
+    use crate::LocalExecutionEnvironment;
+    use serde_json::json;
+    use std::fs;
+    use std::sync::Arc;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn create_directory_tool_creates_nested_dirs_when_recursive() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
+
+        let err = (create_directory_tool().executor)(json!({"path": "build/out"}), env.clone())
+            .await
+            .expect_err("missing parent should fail without recursive");
+        assert!(err.to_string().contains("failed to create directory"));
+
+        for _ in 0..2 {
+            let output = (create_directory_tool().executor)(
+                json!({"path": "build/out", "recursive": true}),
+                env.clone(),
+            )
+            .await
+            .expect("recursive create should succeed and be idempotent");
+            assert_eq!(output, "Created directory build/out");
+        }
+        assert!(dir.path().join("build/out").is_dir());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn create_directory_tool_fails_when_a_file_exists_at_path() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        fs::write(dir.path().join("build"), "not a dir").expect("file written");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
+
+        let err = (create_directory_tool().executor)(
+            json!({"path": "build", "recursive": true}),
+            env.clone(),
+        )
+        .await
+        .expect_err("file collision should fail");
+        assert!(err.to_string().contains("a file already exists"));
+        assert!(dir.path().join("build").is_file());
+
+        let err = (create_directory_tool().executor)(json!({"path": "../outside"}), env)
+            .await
+            .expect_err("escaping path should fail");
+        assert!(
+            err.to_string()
+                .contains("must be inside the working directory")
+        );
+    }
+}
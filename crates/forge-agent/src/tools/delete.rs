@@ -0,0 +1,131 @@
+use forge_llm::ToolDefinition;
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::ToolError;
+
+use super::{
+    DELETE_TOOL, RegisteredTool, ToolMetadata, is_confined, optional_bool_argument,
+    required_string_argument,
+};
+
+pub(super) fn delete_tool() -> RegisteredTool {
+    RegisteredTool {
+        definition: ToolDefinition {
+            name: DELETE_TOOL.to_string(),
+            description: "Delete a file or directory. Non-empty directories require recursive."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "required": ["path"],
+                "properties": {
+                    "path": { "type": "string" },
+                    "recursive": { "type": "boolean" }
+                },
+                "additionalProperties": false
+            }),
+        },
+        executor: Arc::new(|args, env| {
+            Box::pin(async move {
+                let path = required_string_argument(&args, "path")?;
+                let recursive = optional_bool_argument(&args, "recursive")?.unwrap_or(false);
+
+                if !is_confined(env.working_directory(), &path) {
+                    return Err(ToolError::Validation(format!(
+                        "refusing to delete '{}': path must be inside the working directory",
+                        path
+                    ))
+                    .into());
+                }
+                if !env.file_exists(&path).await? {
+                    return Err(ToolError::Execution(format!("path not found: {}", path)).into());
+                }
+
+                if env.is_directory(&path).await? {
+                    if !recursive && !env.list_directory(&path, 0).await?.is_empty() {
+                        return Err(ToolError::Execution(format!(
+                            "directory is not empty: {} (set recursive to delete it)",
+                            path
+                        ))
+                        .into());
+                    }
+                    env.delete_directory(&path, recursive).await?;
+                    Ok(format!("Deleted directory {}", path))
+                } else {
+                    env.delete_file(&path).await?;
+                    Ok(format!("Deleted {}", path))
+                }
+            })
+        }),
+        structured_output: false,
+        output_envelope: false,
+        metadata: ToolMetadata {
+            destructive: true,
+            ..ToolMetadata::default()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::delete_tool;
+    use crate::LocalExecutionEnvironment;
+    use serde_json::json;
+    use std::fs;
+    use std::sync::Arc;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn delete_tool_removes_file() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        fs::write(dir.path().join("a.txt"), "alpha\n").expect("file written");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
+
+        let output = (delete_tool().executor)(json!({"path": "a.txt"}), env)
+            .await
+            .expect("delete should succeed");
+
+        assert_eq!(output, "Deleted a.txt");
+        assert!(!dir.path().join("a.txt").exists());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn delete_tool_requires_recursive_for_non_empty_directory() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        fs::create_dir_all(dir.path().join("build/out")).expect("dirs created");
+        fs::write(dir.path().join("build/out/a.o"), "obj").expect("file written");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
+
+        let err = (delete_tool().executor)(json!({"path": "build"}), env.clone())
+            .await
+            .expect_err("non-recursive delete should fail");
+        assert!(err.to_string().contains("directory is not empty: build"));
+        assert!(dir.path().join("build/out/a.o").exists());
+
+        let output = (delete_tool().executor)(json!({"path": "build", "recursive": true}), env)
+            .await
+            .expect("recursive delete should succeed");
+        assert_eq!(output, "Deleted directory build");
+        assert!(!dir.path().join("build").exists());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn delete_tool_rejects_paths_outside_working_directory() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        fs::create_dir(dir.path().join("work")).expect("work dir created");
+        fs::write(dir.path().join("keep.txt"), "keep").expect("file written");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path().join("work")));
+
+        for path in ["../keep.txt", ".", "sub/../.."] {
+            let err =
+                (delete_tool().executor)(json!({"path": path, "recursive": true}), env.clone())
+                    .await
+                    .expect_err("escaping delete should fail");
+            assert!(
+                err.to_string()
+                    .contains("must be inside the working directory")
+            );
+        }
+        assert!(dir.path().join("keep.txt").exists());
+        assert!(dir.path().join("work").exists());
+    }
+}
@@ -0,0 +1,216 @@
+const CONTEXT_LINES: usize = 3;
+// Bounds the LCS table; larger changed regions are shown as a full replace.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DiffOp<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// Renders a unified diff between two versions of `path`; `None` stands for a
+/// file that does not exist on that side.
+pub(super) fn unified_diff(path: &str, old: Option<&str>, new: Option<&str>) -> String {
+    let old_lines: Vec<&str> = old
+        .map(|content| content.lines().collect())
+        .unwrap_or_default();
+    let new_lines: Vec<&str> = new
+        .map(|content| content.lines().collect())
+        .unwrap_or_default();
+    let ops = diff_lines(&old_lines, &new_lines);
+
+    let mut output = vec![
+        match old {
+            Some(_) => format!("--- a/{}", path),
+            None => "--- /dev/null".to_string(),
+        },
+        match new {
+            Some(_) => format!("+++ b/{}", path),
+            None => "+++ /dev/null".to_string(),
+        },
+    ];
+    for (start, stop) in hunk_ranges(&ops) {
+        output.push(render_hunk(&ops, start, stop));
+    }
+    output.join("\n")
+}
+
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffOp<'a>> {
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(left, right)| left == right)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(left, right)| left == right)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<DiffOp<'a>> = old[..prefix]
+        .iter()
+        .map(|line| DiffOp::Equal(line))
+        .collect();
+    if old_middle.len().saturating_mul(new_middle.len()) > MAX_LCS_CELLS {
+        ops.extend(old_middle.iter().map(|line| DiffOp::Delete(line)));
+        ops.extend(new_middle.iter().map(|line| DiffOp::Insert(line)));
+    } else {
+        ops.extend(lcs_ops(old_middle, new_middle));
+    }
+    ops.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| DiffOp::Equal(line)),
+    );
+    ops
+}
+
+fn lcs_ops<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffOp<'a>> {
+    let width = new.len() + 1;
+    let mut table = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            table[i * width + j] = if old[i] == new[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            ops.push(DiffOp::Equal(old[i]));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            ops.push(DiffOp::Delete(old[i]));
+            i += 1;
+        } else {
+            ops.push(DiffOp::Insert(new[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old[i..].iter().map(|line| DiffOp::Delete(line)));
+    ops.extend(new[j..].iter().map(|line| DiffOp::Insert(line)));
+    ops
+}
+
+/// Groups changed ops into `[start, stop)` ranges padded with context lines,
+/// merging changes separated by at most twice the context.
+fn hunk_ranges(ops: &[DiffOp<'_>]) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut index = 0;
+    while index < ops.len() {
+        if matches!(ops[index], DiffOp::Equal(_)) {
+            index += 1;
+            continue;
+        }
+        let start = index.saturating_sub(CONTEXT_LINES);
+        let mut end = index;
+        let mut cursor = index;
+        while cursor < ops.len() {
+            if !matches!(ops[cursor], DiffOp::Equal(_)) {
+                cursor += 1;
+                end = cursor;
+                continue;
+            }
+            let run_start = cursor;
+            while cursor < ops.len() && matches!(ops[cursor], DiffOp::Equal(_)) {
+                cursor += 1;
+            }
+            if cursor == ops.len() || cursor - run_start > 2 * CONTEXT_LINES {
+                break;
+            }
+        }
+        let stop = (end + CONTEXT_LINES).min(ops.len());
+        ranges.push((start, stop));
+        index = stop;
+    }
+    ranges
+}
+
+fn render_hunk(ops: &[DiffOp<'_>], start: usize, stop: usize) -> String {
+    let (old_before, new_before) =
+        ops[..start]
+            .iter()
+            .fold((0, 0), |(old_count, new_count), op| match op {
+                DiffOp::Equal(_) => (old_count + 1, new_count + 1),
+                DiffOp::Delete(_) => (old_count + 1, new_count),
+                DiffOp::Insert(_) => (old_count, new_count + 1),
+            });
+    let mut lines = Vec::with_capacity(stop - start);
+    let (mut old_count, mut new_count) = (0, 0);
+    for op in &ops[start..stop] {
+        match op {
+            DiffOp::Equal(line) => {
+                old_count += 1;
+                new_count += 1;
+                lines.push(format!(" {}", line));
+            }
+            DiffOp::Delete(line) => {
+                old_count += 1;
+                lines.push(format!("-{}", line));
+            }
+            DiffOp::Insert(line) => {
+                new_count += 1;
+                lines.push(format!("+{}", line));
+            }
+        }
+    }
+    format!(
+        "@@ -{} +{} @@\n{}",
+        hunk_range(old_before, old_count),
+        hunk_range(new_before, new_count),
+        lines.join("\n")
+    )
+}
+
+fn hunk_range(lines_before: usize, count: usize) -> String {
+    if count == 0 {
+        format!("{},0", lines_before)
+    } else {
+        format!("{},{}", lines_before + 1, count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unified_diff;
+
+    #[test]
+    fn unified_diff_shows_changed_lines_with_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\n";
+        let new = "a\nb\nc\nd\nE\nf\ng\nh\n";
+        assert_eq!(
+            unified_diff("f.txt", Some(old), Some(new)),
+            "--- a/f.txt\n+++ b/f.txt\n@@ -2,7 +2,7 @@\n b\n c\n d\n-e\n+E\n f\n g\n h"
+        );
+    }
+
+    #[test]
+    fn unified_diff_splits_distant_changes_into_hunks() {
+        let old: Vec<String> = (1..=20).map(|n| n.to_string()).collect();
+        let mut new = old.clone();
+        new[0] = "one".to_string();
+        new[19] = "twenty".to_string();
+        let diff = unified_diff("n.txt", Some(&old.join("\n")), Some(&new.join("\n")));
+        assert_eq!(diff.matches("@@ -").count(), 2);
+        assert!(diff.contains("@@ -1,4 +1,4 @@\n-1\n+one\n 2\n 3\n 4"));
+        assert!(diff.contains("@@ -17,4 +17,4 @@\n 17\n 18\n 19\n-20\n+twenty"));
+    }
+
+    #[test]
+    fn unified_diff_new_file_is_all_additions() {
+        assert_eq!(
+            unified_diff("new.txt", None, Some("one\ntwo\n")),
+            "--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1,2 @@\n+one\n+two"
+        );
+    }
+}
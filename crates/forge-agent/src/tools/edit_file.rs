@@ -4,7 +4,10 @@ use std::sync::Arc;
 
 use crate::{ToolError, patch};
 
-use super::{EDIT_FILE_TOOL, RegisteredTool, optional_bool_argument, required_string_argument};
+use super::{
+    EDIT_FILE_TOOL, RegisteredTool, ToolMetadata, fuzzy_match_min_confidence,
+    optional_bool_argument, optional_usize_argument, required_string_argument,
+};
 
 pub(super) fn edit_file_tool() -> RegisteredTool {
     RegisteredTool {
@@ -18,7 +21,8 @@ pub(super) fn edit_file_tool() -> RegisteredTool {
                     "file_path": { "type": "string" },
                     "old_string": { "type": "string" },
                     "new_string": { "type": "string" },
-                    "replace_all": { "type": "boolean" }
+                    "replace_all": { "type": "boolean" },
+                    "occurrence": { "type": "integer", "minimum": 1 }
                 },
                 "additionalProperties": false
             }),
@@ -29,6 +33,7 @@ pub(super) fn edit_file_tool() -> RegisteredTool {
                 let old_string = required_string_argument(&args, "old_string")?;
                 let new_string = required_string_argument(&args, "new_string")?;
                 let replace_all = optional_bool_argument(&args, "replace_all")?.unwrap_or(false);
+                let occurrence = optional_usize_argument(&args, "occurrence")?;
                 if old_string.is_empty() {
                     return Err(
                         ToolError::Execution("old_string must not be empty".to_string()).into(),
@@ -36,8 +41,15 @@ pub(super) fn edit_file_tool() -> RegisteredTool {
                 }
 
                 let content = env.read_file(&file_path, None, None).await?;
-                let (next_content, replacement_count) =
-                    patch::apply_edit(&content, &file_path, &old_string, &new_string, replace_all)?;
+                let (next_content, replacement_count) = patch::apply_edit(
+                    &content,
+                    &file_path,
+                    &old_string,
+                    &new_string,
+                    replace_all,
+                    occurrence,
+                    fuzzy_match_min_confidence(&args),
+                )?;
                 env.write_file(&file_path, &next_content).await?;
 
                 Ok(format!(
@@ -48,6 +60,12 @@ pub(super) fn edit_file_tool() -> RegisteredTool {
                 ))
             })
         }),
+        structured_output: false,
+        output_envelope: false,
+        metadata: ToolMetadata {
+            destructive: true,
+            ..ToolMetadata::default()
+        },
     }
 }
 
@@ -148,4 +166,21 @@ mod tests {
         assert!(output.contains("Updated f.txt"));
         assert_eq!(*env.content.lock().expect("content mutex"), "beta\n");
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn edit_file_tool_occurrence_edits_selected_match() {
+        let tool = edit_file_tool();
+        let env = Arc::new(EditEnv::new("x = 1;\nx = 1;\nx = 1;\n"));
+        (tool.executor)(
+            json!({"file_path":"f.txt","old_string":"x = 1;","new_string":"x = 2;","occurrence":2}),
+            env.clone(),
+        )
+        .await
+        .expect("executor should succeed");
+
+        assert_eq!(
+            *env.content.lock().expect("content mutex"),
+            "x = 1;\nx = 2;\nx = 1;\n"
+        );
+    }
 }
@@ -2,19 +2,25 @@ use forge_llm::ToolDefinition;
 use serde_json::json;
 use std::sync::Arc;
 
-use super::{GLOB_TOOL, RegisteredTool, optional_string_argument, required_string_argument};
+use crate::{EntryType, ToolError};
+
+use super::{
+    GLOB_TOOL, RegisteredTool, ToolMetadata, optional_string_argument, required_string_argument,
+};
 
 pub(super) fn glob_tool() -> RegisteredTool {
     RegisteredTool {
         definition: ToolDefinition {
             name: GLOB_TOOL.to_string(),
-            description: "Find files matching a glob pattern.".to_string(),
+            description: "Find files matching a glob pattern; type limits results to one kind."
+                .to_string(),
             parameters: json!({
                 "type": "object",
                 "required": ["pattern"],
                 "properties": {
                     "pattern": { "type": "string" },
-                    "path": { "type": "string" }
+                    "path": { "type": "string" },
+                    "type": { "type": "string", "enum": ["file", "dir", "symlink"] }
                 },
                 "additionalProperties": false
             }),
@@ -23,7 +29,20 @@ pub(super) fn glob_tool() -> RegisteredTool {
             Box::pin(async move {
                 let pattern = required_string_argument(&args, "pattern")?;
                 let path = optional_string_argument(&args, "path")?.unwrap_or(".".to_string());
-                let matches = env.glob(&pattern, &path).await?;
+                let entry_type = match optional_string_argument(&args, "type")?.as_deref() {
+                    None => None,
+                    Some("file") => Some(EntryType::File),
+                    Some("dir") => Some(EntryType::Dir),
+                    Some("symlink") => Some(EntryType::Symlink),
+                    Some(other) => {
+                        return Err(ToolError::Validation(format!(
+                            "argument 'type' must be \"file\", \"dir\", or \"symlink\", got '{}'",
+                            other
+                        ))
+                        .into());
+                    }
+                };
+                let matches = env.glob_with_type(&pattern, &path, entry_type).await?;
                 if matches.is_empty() {
                     Ok("No files matched".to_string())
                 } else {
@@ -31,6 +50,9 @@ pub(super) fn glob_tool() -> RegisteredTool {
                 }
             })
         }),
+        structured_output: false,
+        output_envelope: false,
+        metadata: ToolMetadata::default(),
     }
 }
 
@@ -115,4 +137,38 @@ mod tests {
             .expect("executor should succeed");
         assert_eq!(output, "a.txt\nb.txt");
     }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "current_thread")]
+    async fn glob_tool_type_filter_returns_only_matching_kind() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::write(dir.path().join("file.txt"), "x").expect("file written");
+        std::fs::create_dir(dir.path().join("subdir")).expect("dir created");
+        std::os::unix::fs::symlink(dir.path().join("file.txt"), dir.path().join("link"))
+            .expect("symlink created");
+        let env = Arc::new(crate::LocalExecutionEnvironment::new(dir.path()));
+
+        for (entry_type, expected) in [("file", "file.txt"), ("dir", "subdir"), ("symlink", "link")]
+        {
+            let output =
+                (glob_tool().executor)(json!({"pattern": "*", "type": entry_type}), env.clone())
+                    .await
+                    .expect("executor should succeed");
+            let names: Vec<&str> = output
+                .lines()
+                .map(|line| line.rsplit('/').next().unwrap_or(line))
+                .collect();
+            assert_eq!(names, vec![expected], "type {entry_type}");
+        }
+
+        let output = (glob_tool().executor)(json!({"pattern": "*"}), env.clone())
+            .await
+            .expect("executor should succeed");
+        assert_eq!(output.lines().count(), 3);
+
+        let err = (glob_tool().executor)(json!({"pattern": "*", "type": "socket"}), env)
+            .await
+            .expect_err("unknown type should fail");
+        assert!(err.to_string().contains("argument 'type'"));
+    }
 }
@@ -4,7 +4,7 @@ use serde_json::json;
 use std::sync::Arc;
 
 use super::{
-    GREP_TOOL, RegisteredTool, optional_bool_argument, optional_string_argument,
+    GREP_TOOL, RegisteredTool, ToolMetadata, optional_bool_argument, optional_string_argument,
     optional_usize_argument, required_string_argument,
 };
 
@@ -21,7 +21,14 @@ pub(super) fn grep_tool() -> RegisteredTool {
                     "path": { "type": "string" },
                     "glob_filter": { "type": "string" },
                     "case_insensitive": { "type": "boolean" },
-                    "max_results": { "type": "integer" }
+                    "whole_word": { "type": "boolean" },
+                    "fixed_string": {
+                        "type": "boolean",
+                        "description": "Match the pattern literally instead of as a regex."
+                    },
+                    "max_results": { "type": "integer" },
+                    "max_matches": { "type": "integer", "minimum": 1 },
+                    "max_files": { "type": "integer", "minimum": 1 }
                 },
                 "additionalProperties": false
             }),
@@ -34,7 +41,11 @@ pub(super) fn grep_tool() -> RegisteredTool {
                     glob_filter: optional_string_argument(&args, "glob_filter")?,
                     case_insensitive: optional_bool_argument(&args, "case_insensitive")?
                         .unwrap_or(false),
+                    whole_word: optional_bool_argument(&args, "whole_word")?.unwrap_or(false),
+                    fixed_string: optional_bool_argument(&args, "fixed_string")?.unwrap_or(false),
                     max_results: optional_usize_argument(&args, "max_results")?.or(Some(100)),
+                    max_matches: optional_usize_argument(&args, "max_matches")?,
+                    max_files: optional_usize_argument(&args, "max_files")?,
                 };
 
                 let output = env.grep(&pattern, &path, options).await?;
@@ -45,6 +56,9 @@ pub(super) fn grep_tool() -> RegisteredTool {
                 }
             })
         }),
+        structured_output: false,
+        output_envelope: false,
+        metadata: ToolMetadata::default(),
     }
 }
 
@@ -61,6 +75,7 @@ mod tests {
     #[derive(Default)]
     struct GrepEnv {
         path_seen: Mutex<Option<String>>,
+        options_seen: Mutex<Option<GrepOptions>>,
     }
 
     #[async_trait]
@@ -105,9 +120,10 @@ mod tests {
             &self,
             _pattern: &str,
             path: &str,
-            _options: GrepOptions,
+            options: GrepOptions,
         ) -> Result<String, AgentError> {
             *self.path_seen.lock().expect("path mutex") = Some(path.to_string());
+            *self.options_seen.lock().expect("options mutex") = Some(options);
             Ok(String::new())
         }
         async fn glob(&self, _pattern: &str, _path: &str) -> Result<Vec<String>, AgentError> {
@@ -138,4 +154,33 @@ mod tests {
             Some(".")
         );
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn grep_tool_passes_match_and_file_caps() {
+        let env = Arc::new(GrepEnv::default());
+        (grep_tool().executor)(
+            json!({
+                "pattern": "abc",
+                "whole_word": true,
+                "fixed_string": true,
+                "max_matches": 5,
+                "max_files": 2
+            }),
+            env.clone(),
+        )
+        .await
+        .expect("executor should succeed");
+
+        let options = env
+            .options_seen
+            .lock()
+            .expect("options mutex")
+            .clone()
+            .expect("options set");
+        assert!(options.whole_word);
+        assert!(options.fixed_string);
+        assert!(!options.case_insensitive);
+        assert_eq!(options.max_matches, Some(5));
+        assert_eq!(options.max_files, Some(2));
+    }
 }
@@ -0,0 +1,287 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::{APPLY_PATCH_TOOL, EDIT_FILE_TOOL, WRITE_FILE_TOOL};
+use crate::{AgentError, DirEntry, ExecResult, ExecutionEnvironment, GrepOptions, LineEndingMode};
+
+/// Wraps the environment of file-editing tools so they see LF-only content
+/// and write back using the configured line-ending convention.
+pub(super) fn wrap_for_tool(
+    tool_name: &str,
+    env: Arc<dyn ExecutionEnvironment>,
+    mode: LineEndingMode,
+) -> Arc<dyn ExecutionEnvironment> {
+    let edits_files = matches!(
+        tool_name,
+        WRITE_FILE_TOOL | EDIT_FILE_TOOL | APPLY_PATCH_TOOL
+    );
+    if !edits_files || mode == LineEndingMode::Preserve {
+        return env;
+    }
+    Arc::new(LineEndingExecutionEnvironment {
+        inner: env,
+        mode,
+        detected_crlf: Mutex::new(HashMap::new()),
+    })
+}
+
+struct LineEndingExecutionEnvironment {
+    inner: Arc<dyn ExecutionEnvironment>,
+    mode: LineEndingMode,
+    detected_crlf: Mutex<HashMap<String, bool>>,
+}
+
+impl LineEndingExecutionEnvironment {
+    async fn target_uses_crlf(&self, path: &str) -> Result<bool, AgentError> {
+        match self.mode {
+            LineEndingMode::Lf | LineEndingMode::Preserve => Ok(false),
+            LineEndingMode::Crlf => Ok(true),
+            LineEndingMode::Auto => {
+                let detected = self
+                    .detected_crlf
+                    .lock()
+                    .expect("line ending mutex")
+                    .get(path)
+                    .copied();
+                if let Some(detected) = detected {
+                    return Ok(detected);
+                }
+                if !self.inner.file_exists(path).await? {
+                    return Ok(false);
+                }
+                let existing = self.inner.read_file(path, None, None).await?;
+                Ok(uses_crlf(&existing))
+            }
+        }
+    }
+}
+
+fn uses_crlf(content: &str) -> bool {
+    let crlf = content.matches("\r\n").count();
+    let lf_only = content.matches('\n').count() - crlf;
+    crlf > lf_only
+}
+
+fn to_lf(content: &str) -> String {
+    content.replace("\r\n", "\n")
+}
+
+#[async_trait]
+impl ExecutionEnvironment for LineEndingExecutionEnvironment {
+    async fn read_file(
+        &self,
+        path: &str,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<String, AgentError> {
+        let content = self.inner.read_file(path, offset, limit).await?;
+        // Windowed reads are re-joined with LF, so only full reads are trusted.
+        if offset.is_none() && limit.is_none() {
+            self.detected_crlf
+                .lock()
+                .expect("line ending mutex")
+                .insert(path.to_string(), uses_crlf(&content));
+        }
+        Ok(to_lf(&content))
+    }
+
+    async fn read_file_with_encoding(
+        &self,
+        path: &str,
+        offset: Option<usize>,
+        limit: Option<usize>,
+        encoding: &str,
+    ) -> Result<String, AgentError> {
+        self.inner
+            .read_file_with_encoding(path, offset, limit, encoding)
+            .await
+    }
+
+    async fn write_file(&self, path: &str, content: &str) -> Result<(), AgentError> {
+        let content = to_lf(content);
+        let content = if self.target_uses_crlf(path).await? {
+            content.replace('\n', "\r\n")
+        } else {
+            content
+        };
+        self.inner.write_file(path, &content).await
+    }
+
+    async fn read_file_bytes(&self, path: &str) -> Result<Vec<u8>, AgentError> {
+        self.inner.read_file_bytes(path).await
+    }
+
+    async fn write_file_bytes(&self, path: &str, content: &[u8]) -> Result<(), AgentError> {
+        self.inner.write_file_bytes(path, content).await
+    }
+
+    async fn delete_file(&self, path: &str) -> Result<(), AgentError> {
+        self.inner.delete_file(path).await
+    }
+
+    async fn move_file(&self, from: &str, to: &str) -> Result<(), AgentError> {
+        self.inner.move_file(from, to).await
+    }
+
+    async fn file_exists(&self, path: &str) -> Result<bool, AgentError> {
+        self.inner.file_exists(path).await
+    }
+
+    async fn is_directory(&self, path: &str) -> Result<bool, AgentError> {
+        self.inner.is_directory(path).await
+    }
+
+    async fn delete_directory(&self, path: &str, recursive: bool) -> Result<(), AgentError> {
+        self.inner.delete_directory(path, recursive).await
+    }
+
+    async fn create_directory(&self, path: &str, recursive: bool) -> Result<(), AgentError> {
+        self.inner.create_directory(path, recursive).await
+    }
+
+    async fn list_directory(&self, path: &str, depth: usize) -> Result<Vec<DirEntry>, AgentError> {
+        self.inner.list_directory(path, depth).await
+    }
+
+    async fn exec_command(
+        &self,
+        command: &str,
+        timeout_ms: u64,
+        working_dir: Option<&str>,
+        env_vars: Option<HashMap<String, String>>,
+    ) -> Result<ExecResult, AgentError> {
+        self.inner
+            .exec_command(command, timeout_ms, working_dir, env_vars)
+            .await
+    }
+
+    async fn exec_command_with_output_limit(
+        &self,
+        command: &str,
+        timeout_ms: u64,
+        working_dir: Option<&str>,
+        env_vars: Option<HashMap<String, String>>,
+        max_output_bytes: usize,
+    ) -> Result<ExecResult, AgentError> {
+        self.inner
+            .exec_command_with_output_limit(
+                command,
+                timeout_ms,
+                working_dir,
+                env_vars,
+                max_output_bytes,
+            )
+            .await
+    }
+
+    async fn grep(
+        &self,
+        pattern: &str,
+        path: &str,
+        options: GrepOptions,
+    ) -> Result<String, AgentError> {
+        self.inner.grep(pattern, path, options).await
+    }
+
+    async fn glob(&self, pattern: &str, path: &str) -> Result<Vec<String>, AgentError> {
+        self.inner.glob(pattern, path).await
+    }
+
+    async fn glob_with_type(
+        &self,
+        pattern: &str,
+        path: &str,
+        entry_type: Option<crate::EntryType>,
+    ) -> Result<Vec<String>, AgentError> {
+        self.inner.glob_with_type(pattern, path, entry_type).await
+    }
+
+    async fn temp_dir(&self) -> Result<PathBuf, AgentError> {
+        self.inner.temp_dir().await
+    }
+
+    async fn initialize(&self) -> Result<(), AgentError> {
+        self.inner.initialize().await
+    }
+
+    async fn cleanup(&self) -> Result<(), AgentError> {
+        self.inner.cleanup().await
+    }
+
+    async fn terminate_all_commands(&self) -> Result<(), AgentError> {
+        self.inner.terminate_all_commands().await
+    }
+
+    fn working_directory(&self) -> &Path {
+        self.inner.working_directory()
+    }
+
+    fn platform(&self) -> &str {
+        self.inner.platform()
+    }
+
+    fn os_version(&self) -> &str {
+        self.inner.os_version()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::wrap_for_tool;
+    use crate::{EDIT_FILE_TOOL, LineEndingMode, LocalExecutionEnvironment};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    async fn edit_crlf_file(mode: LineEndingMode) -> String {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::write(dir.path().join("f.txt"), "one\r\ntwo\r\nthree\r\n")
+            .expect("crlf file should be written");
+        let env = wrap_for_tool(
+            EDIT_FILE_TOOL,
+            Arc::new(LocalExecutionEnvironment::new(dir.path())),
+            mode,
+        );
+
+        (super::super::edit_file::edit_file_tool().executor)(
+            json!({"file_path":"f.txt","old_string":"one\ntwo","new_string":"uno\ndos"}),
+            env,
+        )
+        .await
+        .expect("edit should succeed");
+        std::fs::read_to_string(dir.path().join("f.txt")).expect("file should be readable")
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn edit_crlf_file_with_lf_old_string_auto_keeps_crlf() {
+        assert_eq!(
+            edit_crlf_file(LineEndingMode::Auto).await,
+            "uno\r\ndos\r\nthree\r\n"
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn edit_crlf_file_with_lf_old_string_lf_converts_to_lf() {
+        assert_eq!(
+            edit_crlf_file(LineEndingMode::Lf).await,
+            "uno\ndos\nthree\n"
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn edit_crlf_file_with_lf_old_string_crlf_writes_crlf() {
+        assert_eq!(
+            edit_crlf_file(LineEndingMode::Crlf).await,
+            "uno\r\ndos\r\nthree\r\n"
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn edit_crlf_file_with_lf_old_string_preserve_leaves_content_untouched() {
+        assert_eq!(
+            edit_crlf_file(LineEndingMode::Preserve).await,
+            "uno\ndos\r\nthree\r\n"
+        );
+    }
+}
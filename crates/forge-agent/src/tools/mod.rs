@@ -1,26 +1,40 @@
 mod apply_patch;
+mod backups;
+mod create_directory;
+mod delete;
+mod edit_diff;
 mod edit_file;
 mod glob;
 mod grep;
+mod line_endings;
+mod move_file;
 mod read_file;
+mod recording;
 mod registry;
 mod shell;
+mod status;
 mod subagents;
 mod write_file;
 
-use crate::{SessionConfig, ToolError};
+use crate::{AgentError, ExecutionEnvironment, SessionConfig, ToolError};
 use forge_llm::{ToolCall, ToolResult};
 use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
 
+pub use backups::EditBackups;
 pub use registry::{
-    RegisteredTool, ToolCallHook, ToolDispatchOptions, ToolExecutor, ToolFuture, ToolHookContext,
-    ToolPostHookContext, ToolPreHookOutcome, ToolRegistry,
+    RegisteredTool, StructuredToolOutput, ToolCallHook, ToolDescriptor, ToolDispatchOptions,
+    ToolExecutor, ToolFuture, ToolHookContext, ToolMetadata, ToolPostHookContext,
+    ToolPreHookOutcome, ToolRegistry,
 };
 
 pub const READ_FILE_TOOL: &str = "read_file";
 pub const WRITE_FILE_TOOL: &str = "write_file";
 pub const EDIT_FILE_TOOL: &str = "edit_file";
 pub const APPLY_PATCH_TOOL: &str = "apply_patch";
+pub const MOVE_FILE_TOOL: &str = "move_file";
+pub const DELETE_TOOL: &str = "delete";
+pub const CREATE_DIRECTORY_TOOL: &str = "create_directory";
 pub const SHELL_TOOL: &str = "shell";
 pub const GREP_TOOL: &str = "grep";
 pub const GLOB_TOOL: &str = "glob";
@@ -28,6 +42,8 @@ pub const SPAWN_AGENT_TOOL: &str = "spawn_agent";
 pub const SEND_INPUT_TOOL: &str = "send_input";
 pub const WAIT_TOOL: &str = "wait";
 pub const CLOSE_AGENT_TOOL: &str = "close_agent";
+pub const UNDO_EDIT_TOOL: &str = "undo_edit";
+pub const AGENT_STATUS_TOOL: &str = "agent_status";
 
 pub fn build_openai_tool_registry() -> ToolRegistry {
     let mut registry = ToolRegistry::default();
@@ -56,9 +72,14 @@ pub fn build_gemini_tool_registry() -> ToolRegistry {
 pub fn register_shared_core_tools(registry: &mut ToolRegistry) {
     registry.register(read_file::read_file_tool());
     registry.register(write_file::write_file_tool());
+    registry.register(move_file::move_file_tool());
+    registry.register(delete::delete_tool());
+    registry.register(create_directory::create_directory_tool());
     registry.register(shell::shell_tool());
     registry.register(grep::grep_tool());
     registry.register(glob::glob_tool());
+    registry.register(backups::undo_edit_tool());
+    registry.register(status::agent_status_tool());
 }
 
 pub fn register_subagent_tools(registry: &mut ToolRegistry) {
@@ -74,6 +95,7 @@ fn normalize_tool_arguments_for_dispatch(
     schema: &Value,
     config: &SessionConfig,
 ) -> Value {
+    let arguments = drop_null_optional_arguments(arguments, schema);
     if tool_name != SHELL_TOOL {
         return arguments;
     }
@@ -109,6 +131,62 @@ fn normalize_tool_arguments_for_dispatch(
     Value::Object(normalized)
 }
 
+// Strict-mode schemas make optional arguments nullable; a null optional
+// argument means the same as omitting it.
+fn drop_null_optional_arguments(arguments: Value, schema: &Value) -> Value {
+    let Value::Object(mut object) = arguments else {
+        return arguments;
+    };
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    object.retain(|key, value| !value.is_null() || required.contains(&key.as_str()));
+    Value::Object(object)
+}
+
+/// Argument the dispatcher adds to `edit_file`/`apply_patch` calls after schema
+/// validation, so the model cannot set it.
+const FUZZY_MATCH_MIN_CONFIDENCE_ARGUMENT: &str = "fuzzy_match_min_confidence";
+
+/// Argument the dispatcher adds to `shell` calls so the session's
+/// `max_command_output_bytes` applies whatever environment runs the command.
+const MAX_OUTPUT_BYTES_ARGUMENT: &str = "max_output_bytes";
+
+fn inject_dispatch_arguments(tool_name: &str, arguments: Value, config: &SessionConfig) -> Value {
+    let (name, value) = match tool_name {
+        SHELL_TOOL => (
+            MAX_OUTPUT_BYTES_ARGUMENT,
+            Value::from(config.max_command_output_bytes),
+        ),
+        EDIT_FILE_TOOL | APPLY_PATCH_TOOL if config.fuzzy_match_min_confidence > 0.0 => (
+            FUZZY_MATCH_MIN_CONFIDENCE_ARGUMENT,
+            Value::from(config.fuzzy_match_min_confidence.min(1.0)),
+        ),
+        _ => return arguments,
+    };
+    let Value::Object(mut object) = arguments else {
+        return arguments;
+    };
+    object.insert(name.to_string(), value);
+    Value::Object(object)
+}
+
+fn max_output_bytes(arguments: &Value) -> Option<usize> {
+    arguments
+        .get(MAX_OUTPUT_BYTES_ARGUMENT)
+        .and_then(Value::as_u64)
+        .map(|bytes| bytes as usize)
+}
+
+fn fuzzy_match_min_confidence(arguments: &Value) -> f64 {
+    arguments
+        .get(FUZZY_MATCH_MIN_CONFIDENCE_ARGUMENT)
+        .and_then(Value::as_f64)
+        .unwrap_or(0.0)
+}
+
 fn effective_shell_timeout_policy(config: &SessionConfig) -> (u64, u64) {
     let default_timeout_ms = if config.default_command_timeout_ms == 0 {
         10_000
@@ -123,6 +201,61 @@ fn effective_shell_timeout_policy(config: &SessionConfig) -> (u64, u64) {
     let max_timeout_ms = max_timeout_ms.max(default_timeout_ms);
     (default_timeout_ms, max_timeout_ms)
 }
+/// Whether `path`, resolved lexically against `working_directory`, lies
+/// strictly inside it. Symlinks are not followed.
+fn is_confined(working_directory: &Path, path: &str) -> bool {
+    let root = normalize_lexically(working_directory);
+    let resolved = normalize_lexically(&working_directory.join(path));
+    match (root, resolved) {
+        (Some(root), Some(resolved)) => resolved.starts_with(&root) && resolved != root,
+        _ => false,
+    }
+}
+
+/// Resolves the target of a file tool: `file_path` as given, or `temp_path`
+/// inside the environment's temp directory. Exactly one must be set.
+async fn resolve_file_path_argument(
+    arguments: &Value,
+    env: &dyn ExecutionEnvironment,
+) -> Result<String, AgentError> {
+    let file_path = optional_string_argument(arguments, "file_path")?;
+    let temp_path = optional_string_argument(arguments, "temp_path")?;
+    match (file_path, temp_path) {
+        (Some(file_path), None) => Ok(file_path),
+        (None, Some(temp_path)) => {
+            let temp_dir = env.temp_dir().await?;
+            if !is_confined(&temp_dir, &temp_path) {
+                return Err(ToolError::Validation(format!(
+                    "argument 'temp_path' must stay inside the temp directory, got '{}'",
+                    temp_path
+                ))
+                .into());
+            }
+            Ok(temp_dir.join(temp_path).to_string_lossy().to_string())
+        }
+        _ => Err(ToolError::Validation(
+            "exactly one of 'file_path' or 'temp_path' is required".to_string(),
+        )
+        .into()),
+    }
+}
+
+fn normalize_lexically(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return None;
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    Some(normalized)
+}
+
 fn required_string_argument(arguments: &Value, key: &str) -> Result<String, ToolError> {
     optional_string_argument(arguments, key)?
         .ok_or_else(|| ToolError::Validation(format!("missing required argument '{}'", key)))
@@ -188,6 +321,17 @@ fn format_exec_result(result: &crate::ExecResult) -> String {
         "exit_code: {}\nduration_ms: {}",
         result.exit_code, result.duration_ms
     );
+    if result.timed_out && result.timeout_ms > 0 {
+        output.push_str(&format!(
+            "\ntimed_out: true (after {} ms)",
+            result.timeout_ms
+        ));
+    } else if result.timed_out {
+        output.push_str("\ntimed_out: true");
+    }
+    if result.output_truncated {
+        output.push_str("\noutput_truncated: true");
+    }
     if !result.stdout.is_empty() {
         output.push_str("\nstdout:\n");
         output.push_str(&result.stdout);
@@ -335,6 +479,36 @@ mod tests {
         Arc::new(|_args, _env| Box::pin(async move { Ok("ok".to_string()) }))
     }
 
+    #[test]
+    fn describe_reports_builtin_tool_metadata() {
+        let descriptors = build_openai_tool_registry().describe();
+        let metadata = |name: &str| {
+            descriptors
+                .iter()
+                .find(|descriptor| descriptor.name == name)
+                .map(|descriptor| descriptor.metadata)
+                .expect("tool should be described")
+        };
+
+        assert!(metadata(APPLY_PATCH_TOOL).destructive);
+        assert!(metadata(SHELL_TOOL).destructive);
+        assert_eq!(metadata(READ_FILE_TOOL), ToolMetadata::default());
+        assert!(metadata(SPAWN_AGENT_TOOL).is_subagent);
+        assert!(!metadata(SPAWN_AGENT_TOOL).destructive);
+
+        let names: Vec<&str> = descriptors
+            .iter()
+            .map(|descriptor| descriptor.name.as_str())
+            .collect();
+        assert_eq!(names, build_openai_tool_registry().names());
+        let read_file = descriptors
+            .iter()
+            .find(|descriptor| descriptor.name == READ_FILE_TOOL)
+            .expect("read_file should be described");
+        assert!(!read_file.description.is_empty());
+        assert_eq!(read_file.parameters["type"], "object");
+    }
+
     #[test]
     fn tool_registry_latest_registration_wins() {
         let mut registry = ToolRegistry::default();
@@ -346,6 +520,9 @@ mod tests {
                 parameters: serde_json::json!({ "type": "object" }),
             },
             executor: dummy_executor(),
+            structured_output: false,
+            output_envelope: false,
+            metadata: ToolMetadata::default(),
         };
         registry.register(first);
 
@@ -356,6 +533,9 @@ mod tests {
                 parameters: serde_json::json!({ "type": "object" }),
             },
             executor: dummy_executor(),
+            structured_output: false,
+            output_envelope: false,
+            metadata: ToolMetadata::default(),
         };
         registry.register(second);
 
@@ -365,6 +545,26 @@ mod tests {
         assert_eq!(registered.definition.description, "second");
     }
 
+    #[test]
+    fn normalize_tool_arguments_drops_null_optional_arguments() {
+        let schema = json!({
+            "type": "object",
+            "required": ["file_path"],
+            "properties": {
+                "file_path": { "type": "string" },
+                "offset": { "type": "integer" }
+            }
+        });
+        let normalized = normalize_tool_arguments_for_dispatch(
+            READ_FILE_TOOL,
+            json!({ "file_path": "a.txt", "offset": null }),
+            &schema,
+            &SessionConfig::default(),
+        );
+        assert_eq!(normalized, json!({ "file_path": "a.txt" }));
+        assert!(validate_tool_arguments(&schema, &normalized).is_ok());
+    }
+
     #[test]
     fn tool_registry_definitions_are_sorted_by_name() {
         let mut registry = ToolRegistry::default();
@@ -375,6 +575,9 @@ mod tests {
                 parameters: serde_json::json!({ "type": "object" }),
             },
             executor: dummy_executor(),
+            structured_output: false,
+            output_envelope: false,
+            metadata: ToolMetadata::default(),
         });
         registry.register(RegisteredTool {
             definition: ToolDefinition {
@@ -383,6 +586,9 @@ mod tests {
                 parameters: serde_json::json!({ "type": "object" }),
             },
             executor: dummy_executor(),
+            structured_output: false,
+            output_envelope: false,
+            metadata: ToolMetadata::default(),
         });
 
         let names: Vec<String> = registry
@@ -468,6 +674,8 @@ mod tests {
                 exit_code: 0,
                 timed_out: false,
                 duration_ms: 1,
+                output_truncated: false,
+                timeout_ms: 0,
             })
         }
 
@@ -583,6 +791,9 @@ mod tests {
                 }),
             },
             executor,
+            structured_output: false,
+            output_envelope: false,
+            metadata: ToolMetadata::default(),
         }
     }
 
@@ -603,8 +814,10 @@ mod tests {
                 ToolDispatchOptions {
                     session_id: "session-1".to_string(),
                     supports_parallel_tool_calls: false,
+                    force_sequential: false,
                     hook: None,
                     hook_strict: false,
+                    edit_backups: None,
                 },
             )
             .await
@@ -651,8 +864,10 @@ mod tests {
                 ToolDispatchOptions {
                     session_id: "session-1".to_string(),
                     supports_parallel_tool_calls: false,
+                    force_sequential: false,
                     hook: None,
                     hook_strict: false,
+                    edit_backups: None,
                 },
             )
             .await
@@ -670,6 +885,88 @@ mod tests {
         );
     }
 
+    async fn dispatch_shell_with_failures(
+        failures: Vec<ToolError>,
+    ) -> (forge_llm::ToolResult, usize) {
+        let execution_count = Arc::new(AtomicUsize::new(0));
+        let count = execution_count.clone();
+        let failures = Arc::new(std::sync::Mutex::new(failures));
+        let executor: ToolExecutor = Arc::new(move |_args, _env| {
+            let count = count.clone();
+            let failures = failures.clone();
+            Box::pin(async move {
+                count.fetch_add(1, Ordering::SeqCst);
+                let mut failures = failures.lock().expect("failures mutex");
+                if failures.is_empty() {
+                    Ok("done".to_string())
+                } else {
+                    Err(failures.remove(0).into())
+                }
+            })
+        });
+        let mut registry = ToolRegistry::default();
+        registry.register(command_tool(executor));
+        let config = SessionConfig {
+            tool_rate_limit_max_backoff_ms: 10,
+            ..SessionConfig::default()
+        };
+
+        let mut results = registry
+            .dispatch(
+                vec![ToolCall {
+                    id: "call-1".to_string(),
+                    name: "shell".to_string(),
+                    arguments: serde_json::json!({ "command": "curl api" }),
+                    raw_arguments: None,
+                }],
+                Arc::new(TestExecutionEnvironment::default()),
+                &config,
+                Arc::new(NoopEventEmitter),
+                ToolDispatchOptions {
+                    session_id: "session-1".to_string(),
+                    supports_parallel_tool_calls: false,
+                    force_sequential: false,
+                    hook: None,
+                    hook_strict: false,
+                    edit_backups: None,
+                },
+            )
+            .await
+            .expect("dispatch should not fail");
+        (results.remove(0), execution_count.load(Ordering::SeqCst))
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn dispatch_retries_rate_limited_tool_once_with_capped_backoff() {
+        let started = Instant::now();
+        let (result, executions) = dispatch_shell_with_failures(vec![ToolError::RateLimited {
+            retry_after_ms: 60_000,
+        }])
+        .await;
+
+        assert!(!result.is_error);
+        assert_eq!(result.content, json!("done"));
+        assert_eq!(executions, 2);
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let (result, executions) = dispatch_shell_with_failures(vec![
+            ToolError::RateLimited { retry_after_ms: 1 },
+            ToolError::RateLimited { retry_after_ms: 1 },
+        ])
+        .await;
+        assert!(result.is_error);
+        assert_eq!(executions, 2);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn dispatch_does_not_retry_non_rate_limited_tool_errors() {
+        let (result, executions) =
+            dispatch_shell_with_failures(vec![ToolError::Execution("boom".to_string())]).await;
+
+        assert!(result.is_error);
+        assert_eq!(executions, 1);
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn dispatch_parses_raw_json_arguments_and_validates_schema() {
         let executor: ToolExecutor = Arc::new(move |args, _env| {
@@ -699,8 +996,10 @@ mod tests {
                 ToolDispatchOptions {
                     session_id: "session-1".to_string(),
                     supports_parallel_tool_calls: false,
+                    force_sequential: false,
                     hook: None,
                     hook_strict: false,
+                    edit_backups: None,
                 },
             )
             .await
@@ -745,6 +1044,9 @@ mod tests {
                 }),
             },
             executor,
+            structured_output: false,
+            output_envelope: false,
+            metadata: ToolMetadata::default(),
         });
 
         let calls = vec![
@@ -778,8 +1080,10 @@ mod tests {
                 ToolDispatchOptions {
                     session_id: "session-1".to_string(),
                     supports_parallel_tool_calls: true,
+                    force_sequential: false,
                     hook: None,
                     hook_strict: false,
+                    edit_backups: None,
                 },
             )
             .await
@@ -796,6 +1100,95 @@ mod tests {
         assert!(elapsed < Duration::from_millis(170));
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn dispatch_force_sequential_matches_parallel_results_and_runs_in_input_order() {
+        let completed = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let completed_by_executor = completed.clone();
+        let executor: ToolExecutor = Arc::new(move |args, _env| {
+            let completed = completed_by_executor.clone();
+            Box::pin(async move {
+                let delay_ms = args
+                    .get("delay_ms")
+                    .and_then(Value::as_u64)
+                    .expect("delay_ms should be present");
+                let output = args
+                    .get("output")
+                    .and_then(Value::as_str)
+                    .expect("output should be present")
+                    .to_string();
+                sleep(Duration::from_millis(delay_ms)).await;
+                completed
+                    .lock()
+                    .expect("completed mutex")
+                    .push(output.clone());
+                Ok(output)
+            })
+        });
+        let mut registry = ToolRegistry::default();
+        registry.register(RegisteredTool {
+            definition: ToolDefinition {
+                name: "sleep_echo".to_string(),
+                description: "sleep and echo".to_string(),
+                parameters: serde_json::json!({
+                    "type": "object",
+                    "required": ["delay_ms", "output"],
+                    "properties": {
+                        "delay_ms": { "type": "integer" },
+                        "output": { "type": "string" }
+                    },
+                    "additionalProperties": false
+                }),
+            },
+            executor,
+            structured_output: false,
+            output_envelope: false,
+            metadata: ToolMetadata::default(),
+        });
+        let calls: Vec<ToolCall> = [("call-a", 30, "a"), ("call-b", 5, "b"), ("call-c", 15, "c")]
+            .into_iter()
+            .map(|(id, delay_ms, output)| ToolCall {
+                id: id.to_string(),
+                name: "sleep_echo".to_string(),
+                arguments: serde_json::json!({"delay_ms": delay_ms, "output": output}),
+                raw_arguments: None,
+            })
+            .collect();
+
+        let mut outcomes = Vec::new();
+        for force_sequential in [false, true] {
+            completed.lock().expect("completed mutex").clear();
+            let results = registry
+                .dispatch(
+                    calls.clone(),
+                    Arc::new(TestExecutionEnvironment::default()),
+                    &SessionConfig::default(),
+                    Arc::new(NoopEventEmitter),
+                    ToolDispatchOptions {
+                        session_id: "session-1".to_string(),
+                        supports_parallel_tool_calls: true,
+                        force_sequential,
+                        hook: None,
+                        hook_strict: false,
+                        edit_backups: None,
+                    },
+                )
+                .await
+                .expect("dispatch should not fail");
+            outcomes.push(results);
+        }
+
+        assert_eq!(outcomes[0], outcomes[1]);
+        let ids: Vec<&str> = outcomes[1]
+            .iter()
+            .map(|result| result.tool_call_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["call-a", "call-b", "call-c"]);
+        assert_eq!(
+            *completed.lock().expect("completed mutex"),
+            vec!["a".to_string(), "b".to_string(), "c".to_string()]
+        );
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn dispatch_emits_tool_call_start_and_end_events_in_order() {
         let mut registry = ToolRegistry::default();
@@ -818,8 +1211,10 @@ mod tests {
                 ToolDispatchOptions {
                     session_id: "session-1".to_string(),
                     supports_parallel_tool_calls: false,
+                    force_sequential: false,
                     hook: None,
                     hook_strict: false,
+                    edit_backups: None,
                 },
             )
             .await
@@ -838,6 +1233,102 @@ mod tests {
         assert_eq!(events[2].data.get_str("output"), Some("done"));
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn dispatch_structured_output_tool_emits_json_event_but_returns_text_to_llm() {
+        let mut registry = ToolRegistry::default();
+        registry.register(RegisteredTool {
+            structured_output: true,
+            ..command_tool(Arc::new(|_args, _env| {
+                Box::pin(async move { Ok(r#"{"matches":[{"path":"a.rs","line":3}]}"#.to_string()) })
+            }))
+        });
+
+        let emitter = Arc::new(BufferedEventEmitter::default());
+        let results = registry
+            .dispatch(
+                vec![ToolCall {
+                    id: "call-1".to_string(),
+                    name: "shell".to_string(),
+                    arguments: serde_json::json!({"command": "search"}),
+                    raw_arguments: None,
+                }],
+                Arc::new(TestExecutionEnvironment::default()),
+                &SessionConfig::default(),
+                emitter.clone(),
+                ToolDispatchOptions {
+                    session_id: "session-1".to_string(),
+                    supports_parallel_tool_calls: false,
+                    force_sequential: false,
+                    hook: None,
+                    hook_strict: false,
+                    edit_backups: None,
+                },
+            )
+            .await
+            .expect("dispatch should succeed");
+
+        assert_eq!(
+            results[0].content,
+            json!(r#"{"matches":[{"path":"a.rs","line":3}]}"#)
+        );
+        let end = emitter
+            .snapshot()
+            .into_iter()
+            .find(|event| event.kind == EventKind::ToolCallEnd)
+            .expect("tool call end event");
+        assert_eq!(
+            end.data.get("output"),
+            Some(&json!({"matches":[{"path":"a.rs","line":3}]}))
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn dispatch_structured_output_without_envelope_opt_in_expected_raw_text() {
+        let raw = r#"{"text":"summary","data":{"n":1}}"#;
+        let mut registry = ToolRegistry::default();
+        registry.register(RegisteredTool {
+            structured_output: true,
+            ..command_tool(Arc::new(move |_args, _env| {
+                Box::pin(async move { Ok(raw.to_string()) })
+            }))
+        });
+
+        let emitter = Arc::new(BufferedEventEmitter::default());
+        let results = registry
+            .dispatch(
+                vec![ToolCall {
+                    id: "call-1".to_string(),
+                    name: "shell".to_string(),
+                    arguments: serde_json::json!({"command": "search"}),
+                    raw_arguments: None,
+                }],
+                Arc::new(TestExecutionEnvironment::default()),
+                &SessionConfig::default(),
+                emitter.clone(),
+                ToolDispatchOptions {
+                    session_id: "session-1".to_string(),
+                    supports_parallel_tool_calls: false,
+                    force_sequential: false,
+                    hook: None,
+                    hook_strict: false,
+                    edit_backups: None,
+                },
+            )
+            .await
+            .expect("dispatch should succeed");
+
+        assert_eq!(results[0].content, json!(raw));
+        let end = emitter
+            .snapshot()
+            .into_iter()
+            .find(|event| event.kind == EventKind::ToolCallEnd)
+            .expect("tool call end event");
+        assert_eq!(
+            end.data.get("output"),
+            Some(&json!({"text":"summary","data":{"n":1}}))
+        );
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn dispatch_returns_truncated_result_to_llm_but_emits_full_output_event() {
         let full_output = "x".repeat(40_000);
@@ -862,8 +1353,10 @@ mod tests {
                 ToolDispatchOptions {
                     session_id: "session-1".to_string(),
                     supports_parallel_tool_calls: false,
+                    force_sequential: false,
                     hook: None,
                     hook_strict: false,
+                    edit_backups: None,
                 },
             )
             .await
@@ -914,8 +1407,10 @@ mod tests {
                 ToolDispatchOptions {
                     session_id: "session-1".to_string(),
                     supports_parallel_tool_calls: false,
+                    force_sequential: false,
                     hook: None,
                     hook_strict: false,
+                    edit_backups: None,
                 },
             )
             .await
@@ -950,8 +1445,10 @@ mod tests {
                 ToolDispatchOptions {
                     session_id: "session-1".to_string(),
                     supports_parallel_tool_calls: false,
+                    force_sequential: false,
                     hook: None,
                     hook_strict: false,
+                    edit_backups: None,
                 },
             )
             .await
@@ -961,6 +1458,111 @@ mod tests {
         assert_eq!(observed_timeout.load(Ordering::SeqCst), 1_500);
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn shell_dispatch_applies_session_output_limit() {
+        let dir = tempdir().expect("temp dir should be created");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
+        let mut registry = ToolRegistry::default();
+        registry.register(shell::shell_tool());
+        let config = SessionConfig {
+            max_command_output_bytes: 4 * 1024,
+            ..SessionConfig::default()
+        };
+
+        let results = registry
+            .dispatch(
+                vec![ToolCall {
+                    id: "call-1".to_string(),
+                    name: "shell".to_string(),
+                    arguments: json!({ "command": "head -c 200000 /dev/zero | tr '\\0' a" }),
+                    raw_arguments: None,
+                }],
+                env,
+                &config,
+                Arc::new(NoopEventEmitter),
+                ToolDispatchOptions {
+                    session_id: "session-1".to_string(),
+                    supports_parallel_tool_calls: false,
+                    force_sequential: false,
+                    hook: None,
+                    hook_strict: false,
+                    edit_backups: None,
+                },
+            )
+            .await
+            .expect("dispatch should succeed");
+
+        let output = results[0].content.as_str().unwrap_or_default();
+        assert!(output.contains("output_truncated: true"), "{output}");
+        assert!(output.contains("exceeded 4096 bytes"), "{output}");
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn edit_file_dispatch_applies_session_fuzzy_match_min_confidence() {
+        let dir = tempdir().expect("temp dir should be created");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
+        env.write_file("q.txt", "\u{2018}a\u{2019} \u{2018}b\u{2019} c\n")
+            .await
+            .expect("seed q.txt");
+        let registry = build_anthropic_tool_registry();
+        let mut config = SessionConfig::default();
+        config.fuzzy_match_min_confidence = 0.9;
+
+        let dispatch = |arguments: Value| {
+            registry.dispatch(
+                vec![ToolCall {
+                    id: "call-1".to_string(),
+                    name: EDIT_FILE_TOOL.to_string(),
+                    arguments,
+                    raw_arguments: None,
+                }],
+                env.clone(),
+                &config,
+                Arc::new(NoopEventEmitter),
+                ToolDispatchOptions {
+                    session_id: "session-1".to_string(),
+                    supports_parallel_tool_calls: false,
+                    force_sequential: false,
+                    hook: None,
+                    hook_strict: false,
+                    edit_backups: None,
+                },
+            )
+        };
+
+        let results = dispatch(json!({
+            "file_path": "q.txt",
+            "old_string": "'a' 'b' c",
+            "new_string": "x"
+        }))
+        .await
+        .expect("dispatch should succeed");
+        assert!(results[0].is_error);
+        assert!(
+            results[0]
+                .content
+                .as_str()
+                .unwrap_or_default()
+                .contains("low-confidence match")
+        );
+
+        let results = dispatch(json!({
+            "file_path": "q.txt",
+            "old_string": "'a' 'b' c",
+            "new_string": "x",
+            "fuzzy_match_min_confidence": 0.0
+        }))
+        .await
+        .expect("dispatch should succeed");
+        assert!(results[0].is_error, "the model cannot lower the threshold");
+        assert_eq!(
+            env.read_file("q.txt", None, None)
+                .await
+                .expect("q.txt should read"),
+            "\u{2018}a\u{2019} \u{2018}b\u{2019} c\n"
+        );
+    }
+
     #[test]
     fn build_openai_registry_uses_apply_patch_variant() {
         let openai = build_openai_tool_registry();
@@ -1006,8 +1608,10 @@ mod tests {
                 ToolDispatchOptions {
                     session_id: "session-1".to_string(),
                     supports_parallel_tool_calls: false,
+                    force_sequential: false,
                     hook: None,
                     hook_strict: false,
+                    edit_backups: None,
                 },
             )
             .await
@@ -1050,8 +1654,10 @@ mod tests {
                 ToolDispatchOptions {
                     session_id: "session-1".to_string(),
                     supports_parallel_tool_calls: false,
+                    force_sequential: false,
                     hook: None,
                     hook_strict: false,
+                    edit_backups: None,
                 },
             )
             .await
@@ -1091,8 +1697,10 @@ mod tests {
                 ToolDispatchOptions {
                     session_id: "session-1".to_string(),
                     supports_parallel_tool_calls: false,
+                    force_sequential: false,
                     hook: None,
                     hook_strict: false,
+                    edit_backups: None,
                 },
             )
             .await
@@ -1157,8 +1765,10 @@ mod tests {
                 ToolDispatchOptions {
                     session_id: "session-1".to_string(),
                     supports_parallel_tool_calls: false,
+                    force_sequential: false,
                     hook: None,
                     hook_strict: false,
+                    edit_backups: None,
                 },
             )
             .await
@@ -1166,10 +1776,10 @@ mod tests {
 
         assert!(!results[0].is_error);
         let summary = results[0].content.as_str().unwrap_or_default();
-        assert!(summary.contains("A new_file.txt"));
-        assert!(summary.contains("M a.txt"));
-        assert!(summary.contains("R old_name.txt -> new_name.txt"));
-        assert!(summary.contains("D delete_me.txt"));
+        assert_eq!(
+            summary,
+            "Applied patch:\nA new_file.txt (+2)\nM a.txt (+1 -1)\nR old_name.txt -> new_name.txt (+1 -1)\nD delete_me.txt (-1)"
+        );
 
         let updated_a = env
             .read_file("a.txt", None, None)
@@ -1235,8 +1845,10 @@ mod tests {
                 ToolDispatchOptions {
                     session_id: "session-1".to_string(),
                     supports_parallel_tool_calls: false,
+                    force_sequential: false,
                     hook: None,
                     hook_strict: false,
+                    edit_backups: None,
                 },
             )
             .await
@@ -1249,4 +1861,84 @@ mod tests {
             .expect("updated file should read");
         assert!(updated.contains("println!(\"hello\")"));
     }
+
+    async fn dispatch_with_edit_diffs(
+        env: Arc<LocalExecutionEnvironment>,
+        name: &str,
+        arguments: Value,
+    ) -> forge_llm::ToolResult {
+        let config = SessionConfig {
+            include_edit_diffs: true,
+            ..SessionConfig::default()
+        };
+        let mut results = build_anthropic_tool_registry()
+            .dispatch(
+                vec![ToolCall {
+                    id: "call-1".to_string(),
+                    name: name.to_string(),
+                    arguments,
+                    raw_arguments: None,
+                }],
+                env,
+                &config,
+                Arc::new(NoopEventEmitter),
+                ToolDispatchOptions {
+                    session_id: "session-1".to_string(),
+                    supports_parallel_tool_calls: false,
+                    force_sequential: false,
+                    hook: None,
+                    hook_strict: false,
+                    edit_backups: None,
+                },
+            )
+            .await
+            .expect("dispatch should succeed");
+        results.pop().expect("one result")
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn edit_file_with_edit_diffs_reports_changed_lines() {
+        let dir = tempdir().expect("temp dir should be created");
+        std::fs::write(dir.path().join("f.txt"), "one\ntwo\nthree\n").expect("file written");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
+
+        let result = dispatch_with_edit_diffs(
+            env,
+            EDIT_FILE_TOOL,
+            json!({"file_path":"f.txt","old_string":"two","new_string":"2"}),
+        )
+        .await;
+
+        assert!(!result.is_error);
+        let output = result.content.as_str().expect("string output");
+        assert!(output.starts_with("Updated f.txt (1 replacement)\n\n--- a/f.txt\n+++ b/f.txt\n"));
+        assert!(output.ends_with("@@ -1,3 +1,3 @@\n one\n-two\n+2\n three"));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn write_file_with_edit_diffs_shows_new_file_as_additions() {
+        let dir = tempdir().expect("temp dir should be created");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
+
+        let result = dispatch_with_edit_diffs(
+            env,
+            WRITE_FILE_TOOL,
+            json!({"file_path":"new.txt","content":"alpha\nbeta\n"}),
+        )
+        .await;
+
+        assert!(!result.is_error);
+        let output = result.content.as_str().expect("string output");
+        assert!(output.ends_with("--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1,2 @@\n+alpha\n+beta"));
+    }
+
+    #[test]
+    fn is_confined_resolves_relative_and_absolute_paths() {
+        let root = Path::new("/repo");
+        assert!(is_confined(root, "src/../lib.rs"));
+        assert!(is_confined(root, "/repo/src"));
+        assert!(!is_confined(root, "/etc/passwd"));
+        assert!(!is_confined(root, "/repo"));
+        assert!(!is_confined(root, "src/../../etc"));
+    }
 }
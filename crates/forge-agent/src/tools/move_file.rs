@@ -0,0 +1,209 @@
+use forge_llm::ToolDefinition;
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::ToolError;
+
+use super::{
+    MOVE_FILE_TOOL, RegisteredTool, ToolMetadata, is_confined, optional_bool_argument,
+    required_string_argument,
+};
+
+pub(super) fn move_file_tool() -> RegisteredTool {
+    RegisteredTool {
+        definition: ToolDefinition {
+            name: MOVE_FILE_TOOL.to_string(),
+            description:
+                "Move or rename a file. Fails if the destination exists unless overwrite is set."
+                    .to_string(),
+            parameters: json!({
+                "type": "object",
+                "required": ["from", "to"],
+                "properties": {
+                    "from": { "type": "string" },
+                    "to": { "type": "string" },
+                    "overwrite": { "type": "boolean" },
+                    "create_parents": {
+                        "type": "boolean",
+                        "description": "Create missing parent directories of the destination."
+                    }
+                },
+                "additionalProperties": false
+            }),
+        },
+        executor: Arc::new(|args, env| {
+            Box::pin(async move {
+                let from = required_string_argument(&args, "from")?;
+                let to = required_string_argument(&args, "to")?;
+                let overwrite = optional_bool_argument(&args, "overwrite")?.unwrap_or(false);
+                let create_parents =
+                    optional_bool_argument(&args, "create_parents")?.unwrap_or(false);
+
+                for path in [&from, &to] {
+                    if !is_confined(env.working_directory(), path) {
+                        return Err(ToolError::Validation(format!(
+                            "refusing to move '{}': path must be inside the working directory",
+                            path
+                        ))
+                        .into());
+                    }
+                }
+                if !env.file_exists(&from).await? {
+                    return Err(ToolError::Execution(format!("source not found: {}", from)).into());
+                }
+                if !overwrite && env.file_exists(&to).await? {
+                    return Err(ToolError::Execution(format!(
+                        "destination already exists: {} (set overwrite to replace it)",
+                        to
+                    ))
+                    .into());
+                }
+                let required_parent = Path::new(&to)
+                    .parent()
+                    .map(|parent| parent.to_string_lossy().to_string())
+                    .filter(|parent| !create_parents && !parent.is_empty());
+                match required_parent {
+                    Some(parent) if !env.file_exists(&parent).await? => {
+                        return Err(ToolError::Execution(format!(
+                            "destination directory does not exist: {} (set create_parents)",
+                            parent
+                        ))
+                        .into());
+                    }
+                    _ => {}
+                }
+
+                env.move_file(&from, &to).await?;
+                Ok(format!("Moved {} to {}", from, to))
+            })
+        }),
+        structured_output: false,
+        output_envelope: false,
+        metadata: ToolMetadata {
+            destructive: true,
+            ..ToolMetadata::default()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::move_file_tool;
+    use crate::LocalExecutionEnvironment;
+    use serde_json::json;
+    use std::fs;
+    use std::sync::Arc;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn move_file_tool_renames_file() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        fs::write(dir.path().join("a.txt"), "alpha\n").expect("source written");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
+
+        let output = (move_file_tool().executor)(json!({"from": "a.txt", "to": "b.txt"}), env)
+            .await
+            .expect("move should succeed");
+
+        assert_eq!(output, "Moved a.txt to b.txt");
+        assert!(!dir.path().join("a.txt").exists());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("b.txt")).expect("destination read"),
+            "alpha\n"
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn move_file_tool_refuses_to_overwrite_without_flag() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        fs::write(dir.path().join("a.txt"), "alpha\n").expect("source written");
+        fs::write(dir.path().join("b.txt"), "beta\n").expect("destination written");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
+
+        let err = (move_file_tool().executor)(json!({"from": "a.txt", "to": "b.txt"}), env.clone())
+            .await
+            .expect_err("overwrite should be blocked");
+
+        assert!(
+            err.to_string()
+                .contains("destination already exists: b.txt")
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("b.txt")).expect("destination read"),
+            "beta\n"
+        );
+
+        (move_file_tool().executor)(
+            json!({"from": "a.txt", "to": "b.txt", "overwrite": true}),
+            env,
+        )
+        .await
+        .expect("overwrite should succeed when allowed");
+        assert_eq!(
+            fs::read_to_string(dir.path().join("b.txt")).expect("destination read"),
+            "alpha\n"
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn move_file_tool_creates_missing_parents_only_when_requested() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        fs::create_dir(dir.path().join("src")).expect("source dir created");
+        fs::write(dir.path().join("src/a.txt"), "alpha\n").expect("source written");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
+
+        let err = (move_file_tool().executor)(
+            json!({"from": "src/a.txt", "to": "dest/nested/a.txt"}),
+            env.clone(),
+        )
+        .await
+        .expect_err("missing parent should fail without create_parents");
+        assert!(
+            err.to_string()
+                .contains("destination directory does not exist")
+        );
+        assert!(dir.path().join("src/a.txt").exists());
+
+        (move_file_tool().executor)(
+            json!({"from": "src/a.txt", "to": "dest/nested/a.txt", "create_parents": true}),
+            env,
+        )
+        .await
+        .expect("move with create_parents should succeed");
+        assert!(!dir.path().join("src/a.txt").exists());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("dest/nested/a.txt")).expect("destination read"),
+            "alpha\n"
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn move_file_tool_rejects_paths_outside_working_directory() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        fs::create_dir(dir.path().join("work")).expect("work dir created");
+        fs::write(dir.path().join("keep.txt"), "keep").expect("file written");
+        fs::write(dir.path().join("work/a.txt"), "alpha").expect("file written");
+        let env = Arc::new(LocalExecutionEnvironment::new(dir.path().join("work")));
+
+        for (from, to) in [
+            ("../keep.txt", "stolen.txt"),
+            ("a.txt", "../a.txt"),
+            ("a.txt", "sub/../.."),
+            ("/etc/hosts", "hosts"),
+        ] {
+            let err = (move_file_tool().executor)(
+                json!({"from": from, "to": to, "overwrite": true, "create_parents": true}),
+                env.clone(),
+            )
+            .await
+            .expect_err("escaping move should fail");
+            assert!(
+                err.to_string()
+                    .contains("must be inside the working directory")
+            );
+        }
+        assert!(dir.path().join("keep.txt").exists());
+        assert!(dir.path().join("work/a.txt").exists());
+        assert!(!dir.path().join("a.txt").exists());
+    }
+}
@@ -2,7 +2,8 @@ use forge_llm::ToolDefinition;
 use serde_json::json;
 use std::sync::Arc;
 
-use super::{READ_FILE_TOOL, RegisteredTool, required_string_argument};
+use super::{READ_FILE_TOOL, RegisteredTool, ToolMetadata, required_string_argument};
+use crate::execution::is_utf8_label;
 
 pub(super) fn read_file_tool() -> RegisteredTool {
     RegisteredTool {
@@ -16,7 +17,8 @@ pub(super) fn read_file_tool() -> RegisteredTool {
                 "properties": {
                     "file_path": { "type": "string" },
                     "offset": { "type": "integer" },
-                    "limit": { "type": "integer" }
+                    "limit": { "type": "integer" },
+                    "encoding": { "type": "string" }
                 },
                 "additionalProperties": false
             }),
@@ -26,14 +28,28 @@ pub(super) fn read_file_tool() -> RegisteredTool {
                 let file_path = required_string_argument(&args, "file_path")?;
                 let offset = super::optional_usize_argument(&args, "offset")?;
                 let limit = super::optional_usize_argument(&args, "limit")?;
+                let encoding = super::optional_string_argument(&args, "encoding")?;
 
-                let content = env.read_file(&file_path, offset, limit).await?;
-                Ok(super::format_line_numbered_content(
-                    &content,
-                    offset.unwrap_or(1),
+                let Some(encoding) = encoding.filter(|encoding| !is_utf8_label(encoding)) else {
+                    let content = env.read_file(&file_path, offset, limit).await?;
+                    return Ok(super::format_line_numbered_content(
+                        &content,
+                        offset.unwrap_or(1),
+                    ));
+                };
+                let content = env
+                    .read_file_with_encoding(&file_path, offset, limit, &encoding)
+                    .await?;
+                Ok(format!(
+                    "[decoded from {}]\n{}",
+                    encoding,
+                    super::format_line_numbered_content(&content, offset.unwrap_or(1))
                 ))
             })
         }),
+        structured_output: false,
+        output_envelope: false,
+        metadata: ToolMetadata::default(),
     }
 }
 
@@ -135,4 +151,21 @@ mod tests {
         assert_eq!(call.1, Some(2));
         assert_eq!(call.2, Some(2));
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn read_file_tool_labels_content_decoded_from_explicit_encoding() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::write(dir.path().join("latin1.txt"), [b'c', b'a', b'f', 0xE9])
+            .expect("latin-1 file should be written");
+        let env = Arc::new(crate::LocalExecutionEnvironment::new(dir.path()));
+
+        let output = (read_file_tool().executor)(
+            json!({"file_path":"latin1.txt","encoding":"iso-8859-1"}),
+            env,
+        )
+        .await
+        .expect("executor should succeed");
+
+        assert_eq!(output, "[decoded from iso-8859-1]\n1 | café");
+    }
 }
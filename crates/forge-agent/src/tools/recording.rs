@@ -0,0 +1,332 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::{
+    APPLY_PATCH_TOOL, CREATE_DIRECTORY_TOOL, DELETE_TOOL, EDIT_FILE_TOOL, MOVE_FILE_TOOL,
+    WRITE_FILE_TOOL,
+};
+use crate::{AgentError, DirEntry, ExecResult, ExecutionEnvironment, GrepOptions};
+
+/// What exists at a path: a file's raw bytes or a directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) enum PathSnapshot {
+    File(Vec<u8>),
+    Directory,
+}
+
+impl PathSnapshot {
+    /// The file content as UTF-8, or `None` for directories and binary files.
+    pub(super) fn text(&self) -> Option<&str> {
+        match self {
+            Self::File(bytes) => std::str::from_utf8(bytes).ok(),
+            Self::Directory => None,
+        }
+    }
+
+    pub(super) fn len(&self) -> usize {
+        match self {
+            Self::File(bytes) => bytes.len(),
+            Self::Directory => 0,
+        }
+    }
+}
+
+/// State of one path before and after a tool call; `None` means absent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct FileChange {
+    pub(super) path: String,
+    pub(super) previous: Option<PathSnapshot>,
+    pub(super) current: Option<PathSnapshot>,
+}
+
+pub(super) fn mutates_files(tool_name: &str) -> bool {
+    matches!(
+        tool_name,
+        WRITE_FILE_TOOL
+            | EDIT_FILE_TOOL
+            | APPLY_PATCH_TOOL
+            | MOVE_FILE_TOOL
+            | DELETE_TOOL
+            | CREATE_DIRECTORY_TOOL
+    )
+}
+
+/// Records the state of each path a tool call mutates, as it was before the
+/// first mutation and after the last one. Changes are kept in the order paths
+/// were first touched, so replaying them in reverse restores parents last.
+pub(super) struct RecordingExecutionEnvironment {
+    inner: Arc<dyn ExecutionEnvironment>,
+    changes: Mutex<Vec<FileChange>>,
+}
+
+impl RecordingExecutionEnvironment {
+    pub(super) fn new(inner: Arc<dyn ExecutionEnvironment>) -> Self {
+        Self {
+            inner,
+            changes: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(super) fn take_changes(&self) -> Vec<FileChange> {
+        std::mem::take(&mut *self.changes.lock().expect("changes mutex"))
+    }
+
+    fn recorded(&self, path: &str) -> Option<Option<PathSnapshot>> {
+        self.changes
+            .lock()
+            .expect("changes mutex")
+            .iter()
+            .find(|change| change.path == path)
+            .map(|change| change.current.clone())
+    }
+
+    fn push(&self, path: &str, previous: Option<PathSnapshot>, current: Option<PathSnapshot>) {
+        self.changes
+            .lock()
+            .expect("changes mutex")
+            .push(FileChange {
+                path: path.to_string(),
+                previous,
+                current,
+            });
+    }
+
+    /// Returns the state the path had after previously recorded mutations,
+    /// reading it from the inner environment on first touch.
+    async fn capture(&self, path: &str) -> Result<Option<PathSnapshot>, AgentError> {
+        if let Some(current) = self.recorded(path) {
+            return Ok(current);
+        }
+        let previous = if !self.inner.file_exists(path).await? {
+            None
+        } else if self.inner.is_directory(path).await? {
+            Some(PathSnapshot::Directory)
+        } else {
+            Some(PathSnapshot::File(self.inner.read_file_bytes(path).await?))
+        };
+        self.push(path, previous.clone(), previous.clone());
+        Ok(previous)
+    }
+
+    /// Captures the path and, for a directory, everything below it.
+    async fn capture_tree(&self, path: &str) -> Result<Vec<String>, AgentError> {
+        let mut paths = vec![path.to_string()];
+        if self.capture(path).await? == Some(PathSnapshot::Directory) {
+            for entry in self.inner.list_directory(path, usize::MAX).await? {
+                let child = join(path, &entry.name);
+                self.capture(&child).await?;
+                paths.push(child);
+            }
+        }
+        Ok(paths)
+    }
+
+    fn set_current(&self, path: &str, current: Option<PathSnapshot>) {
+        if let Some(change) = self
+            .changes
+            .lock()
+            .expect("changes mutex")
+            .iter_mut()
+            .find(|change| change.path == path)
+        {
+            change.current = current;
+        }
+    }
+}
+
+fn join(parent: &str, child: &str) -> String {
+    Path::new(parent).join(child).to_string_lossy().to_string()
+}
+
+#[async_trait]
+impl ExecutionEnvironment for RecordingExecutionEnvironment {
+    async fn read_file(
+        &self,
+        path: &str,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<String, AgentError> {
+        self.inner.read_file(path, offset, limit).await
+    }
+
+    async fn read_file_with_encoding(
+        &self,
+        path: &str,
+        offset: Option<usize>,
+        limit: Option<usize>,
+        encoding: &str,
+    ) -> Result<String, AgentError> {
+        self.inner
+            .read_file_with_encoding(path, offset, limit, encoding)
+            .await
+    }
+
+    async fn read_file_bytes(&self, path: &str) -> Result<Vec<u8>, AgentError> {
+        self.inner.read_file_bytes(path).await
+    }
+
+    async fn write_file(&self, path: &str, content: &str) -> Result<(), AgentError> {
+        self.write_file_bytes(path, content.as_bytes()).await
+    }
+
+    async fn write_file_bytes(&self, path: &str, content: &[u8]) -> Result<(), AgentError> {
+        self.capture(path).await?;
+        self.inner.write_file_bytes(path, content).await?;
+        self.set_current(path, Some(PathSnapshot::File(content.to_vec())));
+        Ok(())
+    }
+
+    async fn delete_file(&self, path: &str) -> Result<(), AgentError> {
+        self.capture(path).await?;
+        self.inner.delete_file(path).await?;
+        self.set_current(path, None);
+        Ok(())
+    }
+
+    async fn move_file(&self, from: &str, to: &str) -> Result<(), AgentError> {
+        self.capture_tree(to).await?;
+        let sources = self.capture_tree(from).await?;
+        let mut moved = Vec::with_capacity(sources.len());
+        for source in &sources {
+            moved.push(self.recorded(source).flatten());
+        }
+        self.inner.move_file(from, to).await?;
+        for (source, snapshot) in sources.iter().zip(moved) {
+            self.set_current(source, None);
+            let target = match Path::new(source).strip_prefix(from) {
+                Ok(relative) if !relative.as_os_str().is_empty() => {
+                    join(to, &relative.to_string_lossy())
+                }
+                _ => to.to_string(),
+            };
+            if self.recorded(&target).is_some() {
+                self.set_current(&target, snapshot);
+            } else {
+                self.push(&target, None, snapshot);
+            }
+        }
+        Ok(())
+    }
+    async fn file_exists(&self, path: &str) -> Result<bool, AgentError> {
+        self.inner.file_exists(path).await
+    }
+
+    async fn is_directory(&self, path: &str) -> Result<bool, AgentError> {
+        self.inner.is_directory(path).await
+    }
+
+    async fn delete_directory(&self, path: &str, recursive: bool) -> Result<(), AgentError> {
+        let removed = self.capture_tree(path).await?;
+        self.inner.delete_directory(path, recursive).await?;
+        for removed in removed {
+            self.set_current(&removed, None);
+        }
+        Ok(())
+    }
+
+    async fn create_directory(&self, path: &str, recursive: bool) -> Result<(), AgentError> {
+        let mut missing = Vec::new();
+        for ancestor in Path::new(path).ancestors() {
+            let ancestor = ancestor.to_string_lossy().to_string();
+            if ancestor.is_empty() || self.inner.file_exists(&ancestor).await? {
+                break;
+            }
+            missing.push(ancestor);
+        }
+        for created in missing.iter().rev() {
+            self.capture(created).await?;
+        }
+        self.inner.create_directory(path, recursive).await?;
+        for created in missing {
+            self.set_current(&created, Some(PathSnapshot::Directory));
+        }
+        Ok(())
+    }
+
+    async fn list_directory(&self, path: &str, depth: usize) -> Result<Vec<DirEntry>, AgentError> {
+        self.inner.list_directory(path, depth).await
+    }
+
+    async fn exec_command(
+        &self,
+        command: &str,
+        timeout_ms: u64,
+        working_dir: Option<&str>,
+        env_vars: Option<HashMap<String, String>>,
+    ) -> Result<ExecResult, AgentError> {
+        self.inner
+            .exec_command(command, timeout_ms, working_dir, env_vars)
+            .await
+    }
+
+    async fn exec_command_with_output_limit(
+        &self,
+        command: &str,
+        timeout_ms: u64,
+        working_dir: Option<&str>,
+        env_vars: Option<HashMap<String, String>>,
+        max_output_bytes: usize,
+    ) -> Result<ExecResult, AgentError> {
+        self.inner
+            .exec_command_with_output_limit(
+                command,
+                timeout_ms,
+                working_dir,
+                env_vars,
+                max_output_bytes,
+            )
+            .await
+    }
+
+    async fn grep(
+        &self,
+        pattern: &str,
+        path: &str,
+        options: GrepOptions,
+    ) -> Result<String, AgentError> {
+        self.inner.grep(pattern, path, options).await
+    }
+
+    async fn glob(&self, pattern: &str, path: &str) -> Result<Vec<String>, AgentError> {
+        self.inner.glob(pattern, path).await
+    }
+
+    async fn glob_with_type(
+        &self,
+        pattern: &str,
+        path: &str,
+        entry_type: Option<crate::EntryType>,
+    ) -> Result<Vec<String>, AgentError> {
+        self.inner.glob_with_type(pattern, path, entry_type).await
+    }
+
+    async fn temp_dir(&self) -> Result<PathBuf, AgentError> {
+        self.inner.temp_dir().await
+    }
+
+    async fn initialize(&self) -> Result<(), AgentError> {
+        self.inner.initialize().await
+    }
+
+    async fn cleanup(&self) -> Result<(), AgentError> {
+        self.inner.cleanup().await
+    }
+
+    async fn terminate_all_commands(&self) -> Result<(), AgentError> {
+        self.inner.terminate_all_commands().await
+    }
+
+    fn working_directory(&self) -> &Path {
+        self.inner.working_directory()
+    }
+
+    fn platform(&self) -> &str {
+        self.inner.platform()
+    }
+
+    fn os_version(&self) -> &str {
+        self.inner.os_version()
+    }
+}
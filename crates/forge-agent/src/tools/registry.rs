@@ -1,10 +1,13 @@
+use super::recording::{PathSnapshot, RecordingExecutionEnvironment, mutates_files};
+use super::{EDIT_FILE_TOOL, EditBackups, UNDO_EDIT_TOOL, WRITE_FILE_TOOL};
 use crate::{
-    AgentError, EventEmitter, ExecutionEnvironment, SessionConfig, SessionEvent,
+    AgentError, EventEmitter, ExecutionEnvironment, SessionConfig, SessionEvent, ToolError,
     truncate_tool_output,
 };
 use async_trait::async_trait;
 use forge_llm::{ToolCall, ToolDefinition, ToolResult};
 use futures::future::join_all;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::future::Future;
@@ -57,14 +60,59 @@ pub trait ToolCallHook: Send + Sync {
 pub struct ToolDispatchOptions {
     pub session_id: String,
     pub supports_parallel_tool_calls: bool,
+    /// Run calls one at a time in input order even when
+    /// `supports_parallel_tool_calls` is set.
+    pub force_sequential: bool,
     pub hook: Option<Arc<dyn ToolCallHook>>,
     pub hook_strict: bool,
+    pub edit_backups: Option<Arc<EditBackups>>,
 }
 
 #[derive(Clone)]
 pub struct RegisteredTool {
     pub definition: ToolDefinition,
     pub executor: ToolExecutor,
+    /// The executor returns serialized JSON. Events carry the parsed value;
+    /// the LLM still receives the text.
+    pub structured_output: bool,
+    /// The executor returns a serialized [`StructuredToolOutput`]: the LLM
+    /// gets its `text` and events its `data`. Takes precedence over
+    /// `structured_output`.
+    pub output_envelope: bool,
+    pub metadata: ToolMetadata,
+}
+
+/// Executor result for an `output_envelope` tool whose LLM-facing text
+/// differs from the JSON it reports in `tool_call_end` events.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StructuredToolOutput {
+    pub text: String,
+    /// Event payload; `null` leaves the `output` field out.
+    pub data: Value,
+    /// Report the call as failed while still emitting `data`.
+    #[serde(default)]
+    pub is_error: bool,
+}
+
+/// Host-facing flags describing what a tool may do.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolMetadata {
+    /// Mutates the workspace or runs arbitrary commands.
+    pub destructive: bool,
+    pub requires_network: bool,
+    pub is_subagent: bool,
+}
+
+/// Description of a registered tool for host UIs; see [`ToolRegistry::describe`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ToolDescriptor {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+    pub structured_output: bool,
+    pub output_envelope: bool,
+    pub metadata: ToolMetadata,
 }
 
 #[derive(Clone, Default)]
@@ -95,6 +143,24 @@ impl ToolRegistry {
         definitions
     }
 
+    /// Describes every registered tool, sorted by name.
+    pub fn describe(&self) -> Vec<ToolDescriptor> {
+        let mut descriptors: Vec<ToolDescriptor> = self
+            .tools
+            .values()
+            .map(|tool| ToolDescriptor {
+                name: tool.definition.name.clone(),
+                description: tool.definition.description.clone(),
+                parameters: tool.definition.parameters.clone(),
+                structured_output: tool.structured_output,
+                output_envelope: tool.output_envelope,
+                metadata: tool.metadata,
+            })
+            .collect();
+        descriptors.sort_by(|a, b| a.name.cmp(&b.name));
+        descriptors
+    }
+
     pub fn names(&self) -> Vec<String> {
         let mut names: Vec<String> = self.tools.keys().cloned().collect();
         names.sort_unstable();
@@ -109,7 +175,8 @@ impl ToolRegistry {
         event_emitter: Arc<dyn EventEmitter>,
         options: ToolDispatchOptions,
     ) -> Result<Vec<ToolResult>, AgentError> {
-        if options.supports_parallel_tool_calls && tool_calls.len() > 1 {
+        if options.supports_parallel_tool_calls && !options.force_sequential && tool_calls.len() > 1
+        {
             let futures = tool_calls.into_iter().map(|tool_call| {
                 self.dispatch_single(
                     tool_call,
@@ -286,20 +353,99 @@ impl ToolRegistry {
             ))?;
             return Ok(super::tool_error_result(tool_call.id, error.to_string()));
         }
+        let parsed_arguments =
+            super::inject_dispatch_arguments(&tool_call.name, parsed_arguments, config);
 
-        let raw_output = match (registered.executor)(parsed_arguments, execution_env).await {
+        let executor = match &options.edit_backups {
+            Some(backups) if tool_call.name == UNDO_EDIT_TOOL => backups.undo_executor(),
+            _ => registered.executor.clone(),
+        };
+        let include_diff = config.include_edit_diffs
+            && matches!(tool_call.name.as_str(), WRITE_FILE_TOOL | EDIT_FILE_TOOL);
+        let recorder = ((options.edit_backups.is_some() && mutates_files(&tool_call.name))
+            || include_diff)
+            .then(|| Arc::new(RecordingExecutionEnvironment::new(execution_env.clone())));
+        let execution_env: Arc<dyn ExecutionEnvironment> = match &recorder {
+            Some(recorder) => recorder.clone(),
+            None => execution_env,
+        };
+        let execution_env = super::line_endings::wrap_for_tool(
+            &tool_call.name,
+            execution_env,
+            config.normalize_line_endings,
+        );
+        let mut outcome = (executor)(parsed_arguments.clone(), execution_env.clone()).await;
+        if let Err(AgentError::Tool(ToolError::RateLimited { retry_after_ms })) = &outcome {
+            let delay_ms = (*retry_after_ms).min(config.tool_rate_limit_max_backoff_ms);
+            event_emitter.emit(SessionEvent::warning(
+                session_id.to_string(),
+                format!(
+                    "tool '{}' rate limited; retrying once in {}ms",
+                    tool_call.name, delay_ms
+                ),
+            ))?;
+            tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+            outcome = (executor)(parsed_arguments, execution_env).await;
+        }
+        let changes = recorder
+            .map(|recorder| recorder.take_changes())
+            .unwrap_or_default();
+        if let Some(backups) = &options.edit_backups {
+            backups.commit(&changes);
+        }
+        let outcome = match outcome {
+            Ok(output) if include_diff => Ok(changes.iter().fold(output, |output, change| {
+                format!(
+                    "{}\n\n{}",
+                    output,
+                    super::edit_diff::unified_diff(
+                        &change.path,
+                        change.previous.as_ref().and_then(PathSnapshot::text),
+                        change.current.as_ref().and_then(PathSnapshot::text),
+                    )
+                )
+            })),
+            other => other,
+        };
+
+        let outcome = match outcome {
+            Ok(output) if registered.output_envelope => {
+                match serde_json::from_str::<StructuredToolOutput>(&output) {
+                    Ok(envelope) => {
+                        let data = (!envelope.data.is_null()).then_some(envelope.data);
+                        if envelope.is_error {
+                            Err((envelope.text, data))
+                        } else {
+                            Ok((envelope.text, data))
+                        }
+                    }
+                    Err(_) => Ok((output, None)),
+                }
+            }
+            Ok(output) if registered.structured_output => {
+                let structured = serde_json::from_str::<Value>(&output).ok();
+                Ok((output, structured))
+            }
+            Ok(output) => Ok((output, None)),
+            Err(error) => Err((error.to_string(), None)),
+        };
+
+        let (raw_output, structured) = match outcome {
             Ok(output) => output,
-            Err(error) => {
-                let error_text = error.to_string();
+            Err((error_text, structured)) => {
                 let duration_ms = start_time.elapsed().as_millis();
-                event_emitter.emit(SessionEvent::tool_call_end(
+                let mut end_event = SessionEvent::tool_call_end(
                     session_id.to_string(),
                     tool_call.id.clone(),
                     None,
                     Some(error_text.clone()),
                     duration_ms,
                     true,
-                ))?;
+                );
+                if let Some(structured) = structured {
+                    end_event.data.insert_value("output", structured);
+                }
+                event_emitter.emit(end_event)?;
 
                 if let Some(hook) = &options.hook {
                     let post_ctx = ToolPostHookContext {
@@ -342,14 +488,18 @@ impl ToolRegistry {
         }
         let truncated = truncate_tool_output(&raw_output, &tool_call.name, config);
         let duration_ms = start_time.elapsed().as_millis();
-        event_emitter.emit(SessionEvent::tool_call_end(
+        let mut end_event = SessionEvent::tool_call_end(
             session_id.to_string(),
             tool_call.id.clone(),
             Some(raw_output.clone()),
             None,
             duration_ms,
             false,
-        ))?;
+        );
+        if let Some(structured) = structured {
+            end_event.data.insert_value("output", structured);
+        }
+        event_emitter.emit(end_event)?;
 
         if let Some(hook) = &options.hook {
             let post_ctx = ToolPostHookContext {
@@ -2,13 +2,18 @@ use forge_llm::ToolDefinition;
 use serde_json::json;
 use std::sync::Arc;
 
-use super::{RegisteredTool, SHELL_TOOL, optional_u64_argument, required_string_argument};
+use crate::ToolError;
+
+use super::{
+    RegisteredTool, SHELL_TOOL, StructuredToolOutput, ToolMetadata, optional_string_argument,
+    optional_u64_argument, required_string_argument,
+};
 
 pub(super) fn shell_tool() -> RegisteredTool {
     RegisteredTool {
         definition: ToolDefinition {
             name: SHELL_TOOL.to_string(),
-            description: "Execute a shell command. Returns stdout, stderr, and exit code."
+            description: "Execute a shell command. Returns stdout, stderr, and exit code; output \"json\" also reports them as a JSON object in the tool event."
                 .to_string(),
             parameters: json!({
                 "type": "object",
@@ -16,7 +21,8 @@ pub(super) fn shell_tool() -> RegisteredTool {
                 "properties": {
                     "command": { "type": "string" },
                     "timeout_ms": { "type": "integer" },
-                    "description": { "type": "string" }
+                    "description": { "type": "string" },
+                    "output": { "type": "string", "description": "\"text\" (default) or \"json\"" }
                 },
                 "additionalProperties": false
             }),
@@ -25,18 +31,94 @@ pub(super) fn shell_tool() -> RegisteredTool {
             Box::pin(async move {
                 let command = required_string_argument(&args, "command")?;
                 let timeout_ms = optional_u64_argument(&args, "timeout_ms")?.unwrap_or(0);
-                let result = env.exec_command(&command, timeout_ms, None, None).await?;
-                Ok(super::format_exec_result(&result))
+                let json_output = match optional_string_argument(&args, "output")?.as_deref() {
+                    None | Some("text") => false,
+                    Some("json") => true,
+                    Some(other) => {
+                        return Err(ToolError::Validation(format!(
+                            "argument 'output' must be \"text\" or \"json\", got '{}'",
+                            other
+                        ))
+                        .into());
+                    }
+                };
+                let mut result = match super::max_output_bytes(&args) {
+                    Some(max_output_bytes) => {
+                        env.exec_command_with_output_limit(
+                            &command,
+                            timeout_ms,
+                            None,
+                            None,
+                            max_output_bytes,
+                        )
+                        .await?
+                    }
+                    None => env.exec_command(&command, timeout_ms, None, None).await?,
+                };
+                // Environments that do not report the limit they applied ran
+                // under the requested one, if any.
+                if result.timeout_ms == 0 {
+                    result.timeout_ms = timeout_ms;
+                }
+                let output = super::format_exec_result(&result);
+                let data = if json_output {
+                    format_exec_result_json(&result)
+                } else {
+                    serde_json::Value::Null
+                };
+                // A timeout fails the call but still reports the JSON, so
+                // consumers see `timed_out: true` in the event payload.
+                let envelope = if result.timed_out {
+                    StructuredToolOutput {
+                        text: crate::AgentError::from(ToolError::Execution(format!(
+                            "command killed after timeout\n{}",
+                            output
+                        )))
+                        .to_string(),
+                        data,
+                        is_error: true,
+                    }
+                } else {
+                    StructuredToolOutput {
+                        text: output,
+                        data,
+                        is_error: false,
+                    }
+                };
+                Ok(serde_json::to_string(&envelope).unwrap_or_default())
             })
         }),
+        structured_output: false,
+        output_envelope: true,
+        metadata: ToolMetadata {
+            destructive: true,
+            ..ToolMetadata::default()
+        },
     }
 }
 
+// The model gets the same text as in text mode; the JSON only goes to the
+// `tool_call_end` event payload.
+fn format_exec_result_json(result: &crate::ExecResult) -> serde_json::Value {
+    json!({
+        "exit_code": result.exit_code,
+        "duration_ms": result.duration_ms,
+        "timed_out": result.timed_out,
+        "output_truncated": result.output_truncated,
+        "stdout": result.stdout,
+        "stderr": result.stderr,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::shell_tool;
-    use crate::{AgentError, ExecResult, ExecutionEnvironment, GrepOptions};
+    use crate::{
+        AgentError, BufferedEventEmitter, EventKind, ExecResult, ExecutionEnvironment, GrepOptions,
+        SessionConfig, StructuredToolOutput, ToolDispatchOptions, ToolRegistry,
+    };
     use async_trait::async_trait;
+    use forge_llm::ToolCall;
     use serde_json::json;
     use std::collections::HashMap;
     use std::path::Path;
@@ -45,6 +127,7 @@ mod tests {
     #[derive(Default)]
     struct ShellEnv {
         timeout_seen: Mutex<Option<u64>>,
+        times_out: bool,
     }
 
     #[async_trait]
@@ -84,12 +167,25 @@ mod tests {
             _env_vars: Option<HashMap<String, String>>,
         ) -> Result<ExecResult, AgentError> {
             *self.timeout_seen.lock().expect("timeout mutex") = Some(timeout_ms);
+            if self.times_out {
+                return Ok(ExecResult {
+                    stdout: "partial".to_string(),
+                    stderr: String::new(),
+                    exit_code: -1,
+                    timed_out: true,
+                    duration_ms: timeout_ms as u128,
+                    output_truncated: false,
+                    timeout_ms: 0,
+                });
+            }
             Ok(ExecResult {
                 stdout: "ok".to_string(),
                 stderr: String::new(),
                 exit_code: 0,
                 timed_out: false,
                 duration_ms: 5,
+                output_truncated: false,
+                timeout_ms: 0,
             })
         }
         async fn grep(
@@ -114,16 +210,152 @@ mod tests {
         }
     }
 
+    async fn run_shell(
+        args: serde_json::Value,
+        env: Arc<dyn ExecutionEnvironment>,
+    ) -> StructuredToolOutput {
+        let output = (shell_tool().executor)(args, env)
+            .await
+            .expect("executor should succeed");
+        serde_json::from_str(&output).expect("shell output should be an envelope")
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn shell_tool_passes_timeout_and_formats_result() {
-        let tool = shell_tool();
         let env = Arc::new(ShellEnv::default());
-        let output = (tool.executor)(json!({"command":"echo hi","timeout_ms":42}), env.clone())
-            .await
-            .expect("executor should succeed");
+        let output = run_shell(json!({"command":"echo hi","timeout_ms":42}), env.clone()).await;
 
+        assert!(!output.is_error);
+        assert!(output.data.is_null());
+        let output = output.text;
         assert!(output.contains("exit_code: 0"));
         assert!(output.contains("stdout:"));
         assert_eq!(*env.timeout_seen.lock().expect("timeout mutex"), Some(42));
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn shell_tool_timeout_expected_error_explaining_kill() {
+        let env = Arc::new(ShellEnv {
+            times_out: true,
+            ..ShellEnv::default()
+        });
+        let output = run_shell(json!({"command":"sleep 60","timeout_ms":250}), env).await;
+
+        assert!(output.is_error);
+        let message = output.text;
+        assert!(message.contains("command killed after timeout"));
+        assert!(message.contains("timed_out: true (after 250 ms)"));
+        assert!(message.contains("stdout:\npartial"));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn shell_tool_timeout_without_timeout_ms_expected_effective_limit_reported() {
+        let env = Arc::new(
+            crate::LocalExecutionEnvironment::new(std::env::temp_dir())
+                .with_command_timeout_limits(100, 1_000),
+        );
+        let output = run_shell(json!({"command":"sleep 5"}), env).await;
+
+        assert!(output.is_error);
+        assert!(output.text.contains("timed_out: true (after 100 ms)"));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn shell_tool_json_output_expected_structured_event_and_text_result() {
+        let mut registry = ToolRegistry::default();
+        registry.register(shell_tool());
+        let emitter = Arc::new(BufferedEventEmitter::default());
+        let results = registry
+            .dispatch(
+                vec![ToolCall {
+                    id: "call-1".to_string(),
+                    name: "shell".to_string(),
+                    arguments: json!({"command": "echo ok", "output": "json"}),
+                    raw_arguments: None,
+                }],
+                Arc::new(ShellEnv::default()),
+                &SessionConfig::default(),
+                emitter.clone(),
+                ToolDispatchOptions {
+                    session_id: "session-1".to_string(),
+                    supports_parallel_tool_calls: false,
+                    force_sequential: false,
+                    hook: None,
+                    hook_strict: false,
+                    edit_backups: None,
+                },
+            )
+            .await
+            .expect("dispatch should succeed");
+
+        let expected = json!({
+            "exit_code": 0,
+            "duration_ms": 5,
+            "timed_out": false,
+            "output_truncated": false,
+            "stdout": "ok",
+            "stderr": "",
+        });
+        let end = emitter
+            .snapshot()
+            .into_iter()
+            .find(|event| event.kind == EventKind::ToolCallEnd)
+            .expect("tool call end event");
+        assert_eq!(end.data.get("output"), Some(&expected));
+
+        assert!(!results[0].is_error);
+        let text = results[0]
+            .content
+            .as_str()
+            .expect("text result for the model");
+        assert!(text.contains("exit_code: 0"));
+        assert!(text.contains("stdout:\nok"));
+        assert!(serde_json::from_str::<serde_json::Value>(text).is_err());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn shell_tool_json_output_timeout_expected_error_result_and_timed_out_event() {
+        let mut registry = ToolRegistry::default();
+        registry.register(shell_tool());
+        let emitter = Arc::new(BufferedEventEmitter::default());
+        let results = registry
+            .dispatch(
+                vec![ToolCall {
+                    id: "call-1".to_string(),
+                    name: "shell".to_string(),
+                    arguments: json!({"command": "sleep 60", "timeout_ms": 250, "output": "json"}),
+                    raw_arguments: None,
+                }],
+                Arc::new(ShellEnv {
+                    times_out: true,
+                    ..ShellEnv::default()
+                }),
+                &SessionConfig::default(),
+                emitter.clone(),
+                ToolDispatchOptions {
+                    session_id: "session-1".to_string(),
+                    supports_parallel_tool_calls: false,
+                    force_sequential: false,
+                    hook: None,
+                    hook_strict: false,
+                    edit_backups: None,
+                },
+            )
+            .await
+            .expect("dispatch should succeed");
+
+        assert!(results[0].is_error);
+        let text = results[0].content.as_str().expect("error text");
+        assert!(text.contains("command killed after timeout"));
+
+        let end = emitter
+            .snapshot()
+            .into_iter()
+            .find(|event| event.kind == EventKind::ToolCallEnd)
+            .expect("tool call end event");
+        assert_eq!(end.data.get("is_error"), Some(&json!(true)));
+        let output = end.data.get("output").expect("structured output");
+        assert_eq!(output["timed_out"], json!(true));
+        assert_eq!(output["stdout"], json!("partial"));
+    }
 }
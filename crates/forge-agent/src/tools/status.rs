@@ -0,0 +1,35 @@
+use forge_llm::ToolDefinition;
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::ToolError;
+
+use super::{AGENT_STATUS_TOOL, RegisteredTool, ToolMetadata};
+
+/// Read-only report of the session's remaining budgets. The answer lives in
+/// `Session`, which intercepts the call; the registry executor only errors.
+pub(super) fn agent_status_tool() -> RegisteredTool {
+    RegisteredTool {
+        definition: ToolDefinition {
+            name: AGENT_STATUS_TOOL.to_string(),
+            description: "Report tool rounds, turns, and tokens used so far against the session's limits. Read-only.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {},
+                "additionalProperties": false
+            }),
+        },
+        executor: Arc::new(|_args, _env| {
+            Box::pin(async move {
+                Err(ToolError::Execution(format!(
+                    "{} can only run inside a live Session dispatcher",
+                    AGENT_STATUS_TOOL
+                ))
+                .into())
+            })
+        }),
+        structured_output: false,
+        output_envelope: false,
+        metadata: ToolMetadata::default(),
+    }
+}
@@ -5,7 +5,8 @@ use std::sync::Arc;
 use crate::ToolError;
 
 use super::{
-    CLOSE_AGENT_TOOL, RegisteredTool, SEND_INPUT_TOOL, SPAWN_AGENT_TOOL, ToolExecutor, WAIT_TOOL,
+    CLOSE_AGENT_TOOL, RegisteredTool, SEND_INPUT_TOOL, SPAWN_AGENT_TOOL, ToolExecutor,
+    ToolMetadata, WAIT_TOOL,
 };
 
 pub(super) fn spawn_agent_tool() -> RegisteredTool {
@@ -20,12 +21,20 @@ pub(super) fn spawn_agent_tool() -> RegisteredTool {
                     "task": { "type": "string" },
                     "working_dir": { "type": "string" },
                     "model": { "type": "string" },
-                    "max_turns": { "type": "integer" }
+                    "role": { "type": "string" },
+                    "max_turns": { "type": "integer" },
+                    "max_runtime_ms": { "type": "integer" }
                 },
                 "additionalProperties": false
             }),
         },
         executor: unsupported_subagent_executor(SPAWN_AGENT_TOOL),
+        structured_output: false,
+        output_envelope: false,
+        metadata: ToolMetadata {
+            is_subagent: true,
+            ..ToolMetadata::default()
+        },
     }
 }
 
@@ -39,12 +48,19 @@ pub(super) fn send_input_tool() -> RegisteredTool {
                 "required": ["agent_id", "message"],
                 "properties": {
                     "agent_id": { "type": "string" },
-                    "message": { "type": "string" }
+                    "message": { "type": "string" },
+                    "queue": { "type": "boolean" }
                 },
                 "additionalProperties": false
             }),
         },
         executor: unsupported_subagent_executor(SEND_INPUT_TOOL),
+        structured_output: false,
+        output_envelope: false,
+        metadata: ToolMetadata {
+            is_subagent: true,
+            ..ToolMetadata::default()
+        },
     }
 }
 
@@ -63,6 +79,12 @@ pub(super) fn wait_tool() -> RegisteredTool {
             }),
         },
         executor: unsupported_subagent_executor(WAIT_TOOL),
+        structured_output: false,
+        output_envelope: false,
+        metadata: ToolMetadata {
+            is_subagent: true,
+            ..ToolMetadata::default()
+        },
     }
 }
 
@@ -81,6 +103,12 @@ pub(super) fn close_agent_tool() -> RegisteredTool {
             }),
         },
         executor: unsupported_subagent_executor(CLOSE_AGENT_TOOL),
+        structured_output: false,
+        output_envelope: false,
+        metadata: ToolMetadata {
+            is_subagent: true,
+            ..ToolMetadata::default()
+        },
     }
 }
 
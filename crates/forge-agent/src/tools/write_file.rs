@@ -2,7 +2,10 @@ use forge_llm::ToolDefinition;
 use serde_json::json;
 use std::sync::Arc;
 
-use super::{RegisteredTool, WRITE_FILE_TOOL, required_string_argument};
+use super::{
+    RegisteredTool, ToolMetadata, WRITE_FILE_TOOL, required_string_argument,
+    resolve_file_path_argument,
+};
 
 pub(super) fn write_file_tool() -> RegisteredTool {
     RegisteredTool {
@@ -13,9 +16,13 @@ pub(super) fn write_file_tool() -> RegisteredTool {
                     .to_string(),
             parameters: json!({
                 "type": "object",
-                "required": ["file_path", "content"],
+                "required": ["content"],
                 "properties": {
                     "file_path": { "type": "string" },
+                    "temp_path": {
+                        "type": "string",
+                        "description": "Path in the session scratch directory, instead of file_path."
+                    },
                     "content": { "type": "string" }
                 },
                 "additionalProperties": false
@@ -23,12 +30,18 @@ pub(super) fn write_file_tool() -> RegisteredTool {
         },
         executor: Arc::new(|args, env| {
             Box::pin(async move {
-                let file_path = required_string_argument(&args, "file_path")?;
+                let file_path = resolve_file_path_argument(&args, env.as_ref()).await?;
                 let content = required_string_argument(&args, "content")?;
                 env.write_file(&file_path, &content).await?;
                 Ok(format!("Wrote {} bytes to {}", content.len(), file_path))
             })
         }),
+        structured_output: false,
+        output_envelope: false,
+        metadata: ToolMetadata {
+            destructive: true,
+            ..ToolMetadata::default()
+        },
     }
 }
 
@@ -127,4 +140,41 @@ mod tests {
         assert_eq!(write.0, "f.txt");
         assert_eq!(write.1, "abc");
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn write_file_tool_resolves_temp_path_inside_temp_dir() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        let env = Arc::new(crate::LocalExecutionEnvironment::new(dir.path()));
+
+        let output = (write_file_tool().executor)(
+            json!({"temp_path": "notes/a.txt", "content": "abc"}),
+            env.clone(),
+        )
+        .await
+        .expect("executor should succeed");
+
+        let temp_dir = env.temp_dir().await.expect("temp dir should exist");
+        let written = temp_dir.join("notes/a.txt");
+        assert_eq!(output, format!("Wrote 3 bytes to {}", written.display()));
+        assert_eq!(std::fs::read_to_string(&written).expect("file read"), "abc");
+
+        let err = (write_file_tool().executor)(
+            json!({"temp_path": "../escape.txt", "content": "abc"}),
+            env.clone(),
+        )
+        .await
+        .expect_err("escaping temp path should fail");
+        assert!(
+            err.to_string()
+                .contains("must stay inside the temp directory")
+        );
+
+        let err = (write_file_tool().executor)(json!({"content": "abc"}), env.clone())
+            .await
+            .expect_err("missing path should fail");
+        assert!(err.to_string().contains("exactly one of"));
+
+        env.cleanup().await.expect("cleanup should succeed");
+        assert!(!temp_dir.exists());
+    }
 }
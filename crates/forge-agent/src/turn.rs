@@ -7,9 +7,61 @@ pub type Timestamp = String;
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct UserTurn {
     pub content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
     pub timestamp: Timestamp,
 }
 
+/// Image sent alongside a user message. Only profiles whose capabilities report
+/// `supports_vision` accept them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    pub mime: String,
+    pub data_or_url: AttachmentSource,
+}
+
+/// Inline bytes serialize as a base64 string, not a JSON array of numbers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentSource {
+    #[serde(with = "base64_bytes")]
+    Data(Vec<u8>),
+    Url(String),
+}
+
+mod base64_bytes {
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(data))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD.decode(encoded).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Attachment {
+    pub fn data(mime: impl Into<String>, data: Vec<u8>) -> Self {
+        Self {
+            mime: mime.into(),
+            data_or_url: AttachmentSource::Data(data),
+        }
+    }
+
+    pub fn url(mime: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            mime: mime.into(),
+            data_or_url: AttachmentSource::Url(url.into()),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct AssistantTurn {
     pub content: String,
@@ -58,9 +110,15 @@ impl UserTurn {
     pub fn new(content: impl Into<String>, timestamp: Timestamp) -> Self {
         Self {
             content: content.into(),
+            attachments: Vec::new(),
             timestamp,
         }
     }
+
+    pub fn with_attachments(mut self, attachments: Vec<Attachment>) -> Self {
+        self.attachments = attachments;
+        self
+    }
 }
 
 impl AssistantTurn {
@@ -107,11 +165,497 @@ impl SteeringTurn {
     }
 }
 
+/// Short-field wire form of a [`Turn`] used to keep persisted payloads small.
+///
+/// Conversion through [`Turn::to_compact`] / [`Turn::from_compact`] is
+/// loss-free; checkpoints keep using the verbose derive form.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "k")]
+pub enum CompactTurn {
+    #[serde(rename = "u")]
+    User {
+        #[serde(rename = "c")]
+        content: String,
+        #[serde(rename = "at", default, skip_serializing_if = "Vec::is_empty")]
+        attachments: Vec<Attachment>,
+        #[serde(rename = "ts")]
+        timestamp: Timestamp,
+    },
+    #[serde(rename = "a")]
+    Assistant {
+        #[serde(rename = "c")]
+        content: String,
+        #[serde(rename = "tc", default, skip_serializing_if = "Vec::is_empty")]
+        tool_calls: Vec<CompactToolCall>,
+        #[serde(rename = "r", default, skip_serializing_if = "Option::is_none")]
+        reasoning: Option<String>,
+        #[serde(rename = "us")]
+        usage: CompactUsage,
+        #[serde(rename = "rid", default, skip_serializing_if = "Option::is_none")]
+        response_id: Option<String>,
+        #[serde(rename = "ts")]
+        timestamp: Timestamp,
+    },
+    #[serde(rename = "tr")]
+    ToolResults {
+        #[serde(rename = "rs")]
+        results: Vec<CompactToolResult>,
+        #[serde(rename = "ts")]
+        timestamp: Timestamp,
+    },
+    #[serde(rename = "s")]
+    System {
+        #[serde(rename = "c")]
+        content: String,
+        #[serde(rename = "ts")]
+        timestamp: Timestamp,
+    },
+    #[serde(rename = "st")]
+    Steering {
+        #[serde(rename = "c")]
+        content: String,
+        #[serde(rename = "ts")]
+        timestamp: Timestamp,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompactToolCall {
+    #[serde(rename = "i")]
+    pub id: String,
+    #[serde(rename = "n")]
+    pub name: String,
+    #[serde(rename = "a")]
+    pub arguments: Value,
+    #[serde(rename = "ra", default, skip_serializing_if = "Option::is_none")]
+    pub raw_arguments: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompactToolResult {
+    #[serde(rename = "i")]
+    pub tool_call_id: String,
+    #[serde(rename = "c")]
+    pub content: Value,
+    #[serde(rename = "e", default, skip_serializing_if = "is_false")]
+    pub is_error: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompactUsage {
+    #[serde(rename = "i")]
+    pub input_tokens: u64,
+    #[serde(rename = "o")]
+    pub output_tokens: u64,
+    #[serde(rename = "t")]
+    pub total_tokens: u64,
+    #[serde(rename = "rt", default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_tokens: Option<u64>,
+    #[serde(rename = "cr", default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_tokens: Option<u64>,
+    #[serde(rename = "cw", default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_tokens: Option<u64>,
+    #[serde(rename = "raw", default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<Value>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+impl From<&Usage> for CompactUsage {
+    fn from(usage: &Usage) -> Self {
+        Self {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            total_tokens: usage.total_tokens,
+            reasoning_tokens: usage.reasoning_tokens,
+            cache_read_tokens: usage.cache_read_tokens,
+            cache_write_tokens: usage.cache_write_tokens,
+            raw: usage.raw.clone(),
+        }
+    }
+}
+
+impl From<CompactUsage> for Usage {
+    fn from(usage: CompactUsage) -> Self {
+        Self {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            total_tokens: usage.total_tokens,
+            reasoning_tokens: usage.reasoning_tokens,
+            cache_read_tokens: usage.cache_read_tokens,
+            cache_write_tokens: usage.cache_write_tokens,
+            raw: usage.raw,
+        }
+    }
+}
+
+impl Turn {
+    pub fn to_compact(&self) -> CompactTurn {
+        match self {
+            Turn::User(turn) => CompactTurn::User {
+                content: turn.content.clone(),
+                attachments: turn.attachments.clone(),
+                timestamp: turn.timestamp.clone(),
+            },
+            Turn::Assistant(turn) => CompactTurn::Assistant {
+                content: turn.content.clone(),
+                tool_calls: turn
+                    .tool_calls
+                    .iter()
+                    .map(|call| CompactToolCall {
+                        id: call.id.clone(),
+                        name: call.name.clone(),
+                        arguments: call.arguments.clone(),
+                        raw_arguments: call.raw_arguments.clone(),
+                    })
+                    .collect(),
+                reasoning: turn.reasoning.clone(),
+                usage: CompactUsage::from(&turn.usage),
+                response_id: turn.response_id.clone(),
+                timestamp: turn.timestamp.clone(),
+            },
+            Turn::ToolResults(turn) => CompactTurn::ToolResults {
+                results: turn
+                    .results
+                    .iter()
+                    .map(|result| CompactToolResult {
+                        tool_call_id: result.tool_call_id.clone(),
+                        content: result.content.clone(),
+                        is_error: result.is_error,
+                    })
+                    .collect(),
+                timestamp: turn.timestamp.clone(),
+            },
+            Turn::System(turn) => CompactTurn::System {
+                content: turn.content.clone(),
+                timestamp: turn.timestamp.clone(),
+            },
+            Turn::Steering(turn) => CompactTurn::Steering {
+                content: turn.content.clone(),
+                timestamp: turn.timestamp.clone(),
+            },
+        }
+    }
+
+    pub fn from_compact(compact: CompactTurn) -> Self {
+        match compact {
+            CompactTurn::User {
+                content,
+                attachments,
+                timestamp,
+            } => Turn::User(UserTurn {
+                content,
+                attachments,
+                timestamp,
+            }),
+            CompactTurn::Assistant {
+                content,
+                tool_calls,
+                reasoning,
+                usage,
+                response_id,
+                timestamp,
+            } => Turn::Assistant(AssistantTurn {
+                content,
+                tool_calls: tool_calls
+                    .into_iter()
+                    .map(|call| ToolCall {
+                        id: call.id,
+                        name: call.name,
+                        arguments: call.arguments,
+                        raw_arguments: call.raw_arguments,
+                    })
+                    .collect(),
+                reasoning,
+                usage: usage.into(),
+                response_id,
+                timestamp,
+            }),
+            CompactTurn::ToolResults { results, timestamp } => Turn::ToolResults(ToolResultsTurn {
+                results: results
+                    .into_iter()
+                    .map(|result| ToolResultTurn {
+                        tool_call_id: result.tool_call_id,
+                        content: result.content,
+                        is_error: result.is_error,
+                    })
+                    .collect(),
+                timestamp,
+            }),
+            CompactTurn::System { content, timestamp } => {
+                Turn::System(SystemTurn { content, timestamp })
+            }
+            CompactTurn::Steering { content, timestamp } => {
+                Turn::Steering(SteeringTurn { content, timestamp })
+            }
+        }
+    }
+}
+
+/// Iterates assistant turns in history order.
+pub fn assistant_turns(history: &[Turn]) -> impl Iterator<Item = &AssistantTurn> {
+    history.iter().filter_map(|turn| match turn {
+        Turn::Assistant(turn) => Some(turn),
+        _ => None,
+    })
+}
+
+/// Iterates individual tool results across every tool-results turn.
+pub fn tool_results(history: &[Turn]) -> impl Iterator<Item = &ToolResultTurn> {
+    history.iter().flat_map(|turn| match turn {
+        Turn::ToolResults(turn) => turn.results.as_slice(),
+        _ => &[],
+    })
+}
+
+/// Returns the first tool result recorded for `call_id`.
+pub fn find_tool_result<'a>(history: &'a [Turn], call_id: &str) -> Option<&'a ToolResultTurn> {
+    tool_results(history).find(|result| result.tool_call_id == call_id)
+}
+
+/// Returns the content of the most recent assistant turn.
+pub fn last_assistant_text(history: &[Turn]) -> Option<&str> {
+    history.iter().rev().find_map(|turn| match turn {
+        Turn::Assistant(turn) => Some(turn.content.as_str()),
+        _ => None,
+    })
+}
+
+/// Checks that `turns` form a coherent transcript: assistant turns follow a
+/// user turn, every tool call is answered by the next turn, and tool results
+/// only answer the calls of the assistant turn right before them.
+pub(crate) fn validate_turn_sequence(turns: &[Turn]) -> Result<(), String> {
+    let mut seen_user = false;
+    let mut pending_calls: Option<(usize, Vec<&str>)> = None;
+    for (index, turn) in turns.iter().enumerate() {
+        if let Some((call_index, call_ids)) = pending_calls.take() {
+            let Turn::ToolResults(results) = turn else {
+                return Err(format!(
+                    "turn {call_index} has tool calls but turn {index} is not their tool results"
+                ));
+            };
+            let mut result_ids: Vec<&str> = results
+                .results
+                .iter()
+                .map(|result| result.tool_call_id.as_str())
+                .collect();
+            let mut expected = call_ids;
+            result_ids.sort_unstable();
+            expected.sort_unstable();
+            if result_ids != expected {
+                return Err(format!(
+                    "tool results in turn {index} do not match the calls of turn {call_index}"
+                ));
+            }
+            continue;
+        }
+        match turn {
+            Turn::User(_) => seen_user = true,
+            Turn::Assistant(assistant) => {
+                if !seen_user {
+                    return Err(format!("assistant turn {index} precedes any user turn"));
+                }
+                if !assistant.tool_calls.is_empty() {
+                    let call_ids = assistant
+                        .tool_calls
+                        .iter()
+                        .map(|call| call.id.as_str())
+                        .collect();
+                    pending_calls = Some((index, call_ids));
+                }
+            }
+            Turn::ToolResults(_) => {
+                return Err(format!(
+                    "tool results turn {index} does not follow an assistant turn with tool calls"
+                ));
+            }
+            Turn::System(_) | Turn::Steering(_) => {}
+        }
+    }
+    if let Some((call_index, _)) = pending_calls {
+        return Err(format!(
+            "turn {call_index} has tool calls without tool results"
+        ));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
 
+    fn assistant(content: &str) -> Turn {
+        Turn::Assistant(AssistantTurn::new(
+            content,
+            Vec::new(),
+            None,
+            Usage::default(),
+            None,
+            "t".to_string(),
+        ))
+    }
+
+    fn results(call_ids: &[&str]) -> Turn {
+        Turn::ToolResults(ToolResultsTurn::new(
+            call_ids
+                .iter()
+                .map(|call_id| ToolResultTurn {
+                    tool_call_id: (*call_id).to_string(),
+                    content: json!(call_id),
+                    is_error: false,
+                })
+                .collect(),
+            "t".to_string(),
+        ))
+    }
+
+    fn every_turn_variant() -> Vec<Turn> {
+        vec![
+            Turn::User(UserTurn::new("hello", "2026-01-01T00:00:00Z".to_string())),
+            Turn::Assistant(AssistantTurn::new(
+                "working",
+                vec![
+                    ToolCall {
+                        id: "call-1".to_string(),
+                        name: "read_file".to_string(),
+                        arguments: json!({"file_path":"a.txt"}),
+                        raw_arguments: Some("{\"file_path\":\"a.txt\"}".to_string()),
+                    },
+                    ToolCall {
+                        id: "call-2".to_string(),
+                        name: "shell".to_string(),
+                        arguments: json!({"command":"ls"}),
+                        raw_arguments: None,
+                    },
+                ],
+                Some("thinking it through".to_string()),
+                Usage {
+                    input_tokens: 10,
+                    output_tokens: 5,
+                    total_tokens: 15,
+                    reasoning_tokens: Some(3),
+                    cache_read_tokens: Some(2),
+                    cache_write_tokens: None,
+                    raw: Some(json!({"provider":"x"})),
+                },
+                Some("resp-1".to_string()),
+                "2026-01-01T00:00:01Z".to_string(),
+            )),
+            assistant(""),
+            Turn::ToolResults(ToolResultsTurn::new(
+                vec![
+                    ToolResultTurn {
+                        tool_call_id: "call-1".to_string(),
+                        content: json!("file body"),
+                        is_error: false,
+                    },
+                    ToolResultTurn {
+                        tool_call_id: "call-2".to_string(),
+                        content: json!({"exit_code":1}),
+                        is_error: true,
+                    },
+                ],
+                "2026-01-01T00:00:02Z".to_string(),
+            )),
+            Turn::System(SystemTurn::new("system note", "t".to_string())),
+            Turn::Steering(SteeringTurn::new("steer", "t".to_string())),
+        ]
+    }
+
+    #[test]
+    fn compact_turn_round_trips_every_variant_through_json_and_msgpack() {
+        for turn in every_turn_variant() {
+            let compact = turn.to_compact();
+
+            let json = serde_json::to_string(&compact).expect("compact json encode");
+            let decoded: CompactTurn = serde_json::from_str(&json).expect("compact json decode");
+            assert_eq!(Turn::from_compact(decoded), turn);
+
+            let msgpack = rmp_serde::to_vec_named(&compact).expect("compact msgpack encode");
+            let decoded: CompactTurn =
+                rmp_serde::from_slice(&msgpack).expect("compact msgpack decode");
+            assert_eq!(Turn::from_compact(decoded), turn);
+        }
+    }
+
+    #[test]
+    fn attachment_data_serializes_as_base64_and_round_trips() {
+        let attachment = Attachment::data("image/png", vec![0x89, b'P', b'N', b'G']);
+
+        let value = serde_json::to_value(&attachment).expect("attachment encode");
+        assert_eq!(
+            value,
+            json!({ "mime": "image/png", "data_or_url": { "data": "iVBORw==" } })
+        );
+        let decoded: Attachment = serde_json::from_value(value).expect("attachment decode");
+        assert_eq!(decoded, attachment);
+
+        let msgpack = rmp_serde::to_vec_named(&attachment).expect("attachment msgpack encode");
+        let decoded: Attachment =
+            rmp_serde::from_slice(&msgpack).expect("attachment msgpack decode");
+        assert_eq!(decoded, attachment);
+    }
+
+    #[test]
+    fn compact_turn_is_smaller_than_verbose_form() {
+        for turn in every_turn_variant() {
+            let verbose = serde_json::to_string(&turn).expect("verbose encode");
+            let compact = serde_json::to_string(&turn.to_compact()).expect("compact encode");
+            assert!(compact.len() < verbose.len(), "{compact} vs {verbose}");
+        }
+    }
+
+    #[test]
+    fn history_queries_on_empty_history_return_nothing() {
+        let history: Vec<Turn> = Vec::new();
+        assert_eq!(assistant_turns(&history).count(), 0);
+        assert_eq!(tool_results(&history).count(), 0);
+        assert!(find_tool_result(&history, "call-1").is_none());
+        assert!(last_assistant_text(&history).is_none());
+    }
+
+    #[test]
+    fn history_queries_on_single_turns_match_variant() {
+        let history = vec![assistant("only")];
+        assert_eq!(assistant_turns(&history).count(), 1);
+        assert_eq!(last_assistant_text(&history), Some("only"));
+        assert_eq!(tool_results(&history).count(), 0);
+
+        let history = vec![results(&["call-1"])];
+        assert_eq!(assistant_turns(&history).count(), 0);
+        assert_eq!(
+            find_tool_result(&history, "call-1").map(|result| &result.content),
+            Some(&json!("call-1"))
+        );
+    }
+
+    #[test]
+    fn history_queries_on_interleaved_turns_preserve_order() {
+        let history = vec![
+            Turn::User(UserTurn::new("go", "t".to_string())),
+            assistant("first"),
+            results(&["call-1", "call-2"]),
+            Turn::Steering(SteeringTurn::new("steer", "t".to_string())),
+            assistant("second"),
+            results(&["call-3"]),
+        ];
+
+        let contents: Vec<&str> = assistant_turns(&history)
+            .map(|turn| turn.content.as_str())
+            .collect();
+        assert_eq!(contents, vec!["first", "second"]);
+        let call_ids: Vec<&str> = tool_results(&history)
+            .map(|result| result.tool_call_id.as_str())
+            .collect();
+        assert_eq!(call_ids, vec!["call-1", "call-2", "call-3"]);
+        assert!(find_tool_result(&history, "call-3").is_some());
+        assert!(find_tool_result(&history, "call-4").is_none());
+        assert_eq!(last_assistant_text(&history), Some("second"));
+    }
+
     #[test]
     fn tool_result_turn_preserves_structured_content() {
         let result = ToolResultTurn {
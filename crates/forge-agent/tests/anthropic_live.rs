@@ -155,6 +155,7 @@ async fn anthropic_live_submit_with_options_smoke_applies_request_overrides() {
                     system_prompt_override: Some(override_marker.to_string()),
                     provider_options: Some(provider_options.clone()),
                     metadata: None,
+                    attachments: Vec::new(),
                 },
             )
             .await?;
@@ -4,8 +4,7 @@ use forge_agent::{ExecutionEnvironment, LocalExecutionEnvironment, Session, Sess
 use serde_json::json;
 use std::sync::Arc;
 use support::{
-    FixtureKind, all_fixtures, client_with_adapter, enqueue, last_assistant_text, text_response,
-    tool_call_response, tool_result_by_call_id,
+    FixtureKind, all_fixtures, client_with_adapter, enqueue, text_response, tool_call_response,
 };
 use tempfile::tempdir;
 
@@ -116,7 +115,8 @@ async fn cross_profile_file_creation_read_edit_and_native_variant() {
         assert!(content.contains("hello"));
         assert!(content.contains("goodbye"));
 
-        let edit_result = tool_result_by_call_id(session.history(), "call-edit")
+        let edit_result = session
+            .find_tool_result("call-edit")
             .expect("edit tool result should exist");
         assert!(!edit_result.is_error);
     }
@@ -158,7 +158,8 @@ async fn cross_profile_shell_search_and_timeout_flows() {
             .await
             .expect("shell submit should succeed");
 
-        let shell_ok = tool_result_by_call_id(session.history(), "call-shell-ok")
+        let shell_ok = session
+            .find_tool_result("call-shell-ok")
             .expect("shell result should exist");
         let shell_ok_text = shell_ok.content.as_str().unwrap_or_default();
         assert!(shell_ok_text.contains("exit_code: 0"));
@@ -192,11 +193,13 @@ async fn cross_profile_shell_search_and_timeout_flows() {
             .await
             .expect("search submit should succeed");
 
-        let grep = tool_result_by_call_id(session.history(), "call-grep")
+        let grep = session
+            .find_tool_result("call-grep")
             .expect("grep result should exist");
         assert!(grep.content.as_str().unwrap_or_default().contains("beta"));
 
-        let glob = tool_result_by_call_id(session.history(), "call-glob")
+        let glob = session
+            .find_tool_result("call-glob")
             .expect("glob result should exist");
         assert!(
             glob.content
@@ -227,7 +230,8 @@ async fn cross_profile_shell_search_and_timeout_flows() {
             .await
             .expect("timeout submit should succeed");
 
-        let timeout_result = tool_result_by_call_id(session.history(), "call-timeout")
+        let timeout_result = session
+            .find_tool_result("call-timeout")
             .expect("timeout result should exist");
         assert!(
             timeout_result
@@ -283,8 +287,8 @@ async fn cross_profile_parallel_tool_calls_and_subagent_spawn_wait() {
             .await
             .expect("parallel submit should succeed");
 
-        assert!(tool_result_by_call_id(session.history(), "call-parallel-a").is_some());
-        assert!(tool_result_by_call_id(session.history(), "call-parallel-b").is_some());
+        assert!(session.find_tool_result("call-parallel-a").is_some());
+        assert!(session.find_tool_result("call-parallel-b").is_some());
 
         // Spawn flow: parent tool call -> child text response -> parent final text.
         enqueue(
@@ -345,13 +349,17 @@ async fn cross_profile_parallel_tool_calls_and_subagent_spawn_wait() {
             .await
             .expect("wait submit should succeed");
 
-        let wait_result = tool_result_by_call_id(session.history(), "call-wait")
+        let wait_result = session
+            .find_tool_result("call-wait")
             .expect("wait result should exist");
         let wait_payload = wait_result.content.as_str().unwrap_or_default();
         assert!(wait_payload.contains("\"status\":\"completed\""));
         assert!(wait_payload.contains("\"success\":true"));
 
-        let final_text = last_assistant_text(session.history()).unwrap_or_default();
+        let final_text = session
+            .last_assistant_text()
+            .map(str::to_string)
+            .unwrap_or_default();
         assert_eq!(final_text, "waited");
     }
 }
@@ -447,12 +455,13 @@ async fn cross_profile_multi_file_edit_flow() {
         assert!(b_content.contains("echo"));
 
         if matches!(fixture, FixtureKind::OpenAi) {
-            let result = tool_result_by_call_id(session.history(), "call-multi")
+            let result = session
+                .find_tool_result("call-multi")
                 .expect("patch result should exist");
             assert!(!result.is_error);
         } else {
-            assert!(tool_result_by_call_id(session.history(), "call-edit-a").is_some());
-            assert!(tool_result_by_call_id(session.history(), "call-edit-b").is_some());
+            assert!(session.find_tool_result("call-edit-a").is_some());
+            assert!(session.find_tool_result("call-edit-b").is_some());
         }
     }
 }
@@ -528,7 +537,8 @@ async fn cross_profile_multi_step_read_analyze_edit_flow() {
             .await
             .expect("multi-step submit should succeed");
 
-        let read_result = tool_result_by_call_id(session.history(), "call-read")
+        let read_result = session
+            .find_tool_result("call-read")
             .expect("read tool result should exist");
         assert!(!read_result.is_error);
         assert!(
@@ -539,7 +549,8 @@ async fn cross_profile_multi_step_read_analyze_edit_flow() {
                 .contains("one")
         );
 
-        let edit_result = tool_result_by_call_id(session.history(), "call-edit")
+        let edit_result = session
+            .find_tool_result("call-edit")
             .expect("edit tool result should exist");
         assert!(!edit_result.is_error);
 
@@ -13,10 +13,7 @@ use forge_llm::Role;
 use serde_json::json;
 use std::sync::Arc;
 use std::sync::Mutex;
-use support::{
-    all_fixtures, client_with_adapter, enqueue, text_response, tool_call_response,
-    tool_result_by_call_id,
-};
+use support::{all_fixtures, client_with_adapter, enqueue, text_response, tool_call_response};
 use tempfile::tempdir;
 
 fn decode_persisted_record(payload: &[u8]) -> Option<serde_json::Value> {
@@ -298,7 +295,8 @@ async fn cross_profile_error_recovery_after_tool_failure() {
             .await
             .expect("submit should succeed");
 
-        let bad = tool_result_by_call_id(session.history(), "call-bad")
+        let bad = session
+            .find_tool_result("call-bad")
             .expect("bad call result should exist");
         assert!(bad.is_error);
         assert!(
@@ -308,7 +306,8 @@ async fn cross_profile_error_recovery_after_tool_failure() {
                 .contains("Unknown tool")
         );
 
-        let good = tool_result_by_call_id(session.history(), "call-good")
+        let good = session
+            .find_tool_result("call-good")
             .expect("good call result should exist");
         assert!(!good.is_error);
         assert!(good.content.as_str().unwrap_or_default().contains("ready"));
@@ -350,7 +349,8 @@ async fn cross_profile_subagent_depth_limit_returns_tool_error() {
             .await
             .expect("submit should succeed");
 
-        let result = tool_result_by_call_id(session.history(), "call-spawn")
+        let result = session
+            .find_tool_result("call-spawn")
             .expect("spawn result should exist");
         assert!(result.is_error);
         assert!(
@@ -448,7 +448,8 @@ async fn cross_profile_large_output_truncation_behavior() {
         );
 
         session.submit("read big file").await.expect("submit");
-        let result = tool_result_by_call_id(session.history(), "call-read-big")
+        let result = session
+            .find_tool_result("call-read-big")
             .expect("result should exist");
         let text = result.content.as_str().unwrap_or_default();
         assert!(text.contains("[WARNING: Tool output was truncated."));
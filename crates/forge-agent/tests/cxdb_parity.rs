@@ -185,7 +185,7 @@ async fn cxdb_mode_off_create_failure_expected_submit_succeeds() {
 }
 
 #[tokio::test(flavor = "current_thread")]
-async fn cxdb_mode_required_create_failure_expected_constructor_error() {
+async fn cxdb_mode_required_create_failure_expected_initialize_error() {
     let fixture = all_fixtures()[0].clone();
     let dir = tempdir().expect("temp dir should create");
     let env = Arc::new(LocalExecutionEnvironment::new(dir.path()));
@@ -194,17 +194,18 @@ async fn cxdb_mode_required_create_failure_expected_constructor_error() {
     let mut config = SessionConfig::default();
     config.cxdb_persistence = CxdbPersistenceMode::Required;
 
-    let result = Session::new_with_cxdb_persistence(
+    let mut session = Session::new_with_cxdb_persistence(
         profile,
         env,
         client,
         config,
         Arc::new(FailingCxdb),
         Arc::new(FailingCxdb),
-    );
-    let error = match result {
-        Ok(_) => panic!("required mode should fail constructor"),
-        Err(error) => error,
-    };
+    )
+    .expect("constructor should defer persistence");
+    let error = session
+        .initialize()
+        .await
+        .expect_err("required mode should fail initialize");
     assert!(error.to_string().contains("create_context failed"));
 }
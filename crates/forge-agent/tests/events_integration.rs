@@ -5,9 +5,7 @@ use forge_agent::{
     SessionConfig,
 };
 use std::sync::Arc;
-use support::{
-    client_with_adapter, enqueue, text_response, tool_call_response, tool_result_by_call_id,
-};
+use support::{client_with_adapter, enqueue, text_response, tool_call_response};
 use tempfile::tempdir;
 
 fn event_index(events: &[forge_agent::SessionEvent], kind: EventKind) -> Option<usize> {
@@ -116,7 +114,8 @@ async fn tool_call_end_event_carries_full_output_when_tool_result_is_truncated()
 
     session.submit("read long file").await.expect("submit");
 
-    let tool_result = tool_result_by_call_id(session.history(), "call-long")
+    let tool_result = session
+        .find_tool_result("call-long")
         .expect("tool result should exist")
         .content
         .as_str()
@@ -159,6 +159,7 @@ async fn openai_live_submit_with_options_smoke_applies_request_overrides() {
                     system_prompt_override: Some(override_marker.to_string()),
                     provider_options: Some(provider_options.clone()),
                     metadata: Some(metadata),
+                    attachments: Vec::new(),
                 },
             )
             .await?;
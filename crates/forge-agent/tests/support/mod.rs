@@ -4,8 +4,7 @@ pub mod live;
 
 use async_trait::async_trait;
 use forge_agent::{
-    AnthropicProviderProfile, AssistantTurn, GeminiProviderProfile, OpenAiProviderProfile,
-    ProviderProfile, ToolResultTurn, ToolResultsTurn, Turn,
+    AnthropicProviderProfile, GeminiProviderProfile, OpenAiProviderProfile, ProviderProfile,
 };
 use forge_llm::{
     Client, ConfigurationError, ContentPart, FinishReason, Message, ProviderAdapter, Request,
@@ -185,25 +184,3 @@ pub fn enqueue(responses: &Arc<Mutex<VecDeque<Response>>>, response: Response) {
         .expect("responses mutex")
         .push_back(response);
 }
-
-pub fn tool_result_by_call_id<'a>(
-    history: &'a [Turn],
-    call_id: &str,
-) -> Option<&'a ToolResultTurn> {
-    history.iter().find_map(|turn| {
-        if let Turn::ToolResults(ToolResultsTurn { results, .. }) = turn {
-            return results.iter().find(|result| result.tool_call_id == call_id);
-        }
-        None
-    })
-}
-
-pub fn last_assistant_text(history: &[Turn]) -> Option<String> {
-    history.iter().rev().find_map(|turn| {
-        if let Turn::Assistant(AssistantTurn { content, .. }) = turn {
-            Some(content.clone())
-        } else {
-            None
-        }
-    })
-}
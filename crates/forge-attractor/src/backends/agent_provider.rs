@@ -5,10 +5,10 @@
 use crate::backends::forge_agent::AgentSubmitter;
 use async_trait::async_trait;
 use forge_agent::{
-    AgentError, SessionPersistenceSnapshot, SessionState, SubmitOptions, SubmitResult,
-    ToolCallHook,
+    AgentError, SessionPersistenceSnapshot, SessionState, StopReason, SubmitOptions,
+    SubmitResult, ToolCallHook,
 };
-use forge_llm::agent_provider::{AgentProvider, AgentRunOptions};
+use forge_llm::agent_provider::{AgentProvider, AgentRunOptions, AgentRunStopReason};
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -71,6 +71,7 @@ impl AgentSubmitter for AgentProviderSubmitter {
             tool_error_count,
             usage: Some(result.usage),
             thread_key: self.thread_key.clone(),
+            stopped_reason: map_stop_reason(result.stop_reason),
         })
     }
 
@@ -94,3 +95,11 @@ impl AgentSubmitter for AgentProviderSubmitter {
         Ok(SessionPersistenceSnapshot::default())
     }
 }
+
+fn map_stop_reason(reason: AgentRunStopReason) -> StopReason {
+    match reason {
+        AgentRunStopReason::Completed => StopReason::NaturalCompletion,
+        AgentRunStopReason::RoundLimit => StopReason::RoundLimit,
+        AgentRunStopReason::TurnLimit => StopReason::TurnLimit,
+    }
+}
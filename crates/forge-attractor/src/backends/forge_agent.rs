@@ -1,13 +1,16 @@
 use crate::storage::{ContextId, TurnId};
 use crate::{
-    AttractorError, AttractorStageToAgentLinkRecord, AttractorStorageWriter, CxdbPersistenceMode,
-    Graph, Node, NodeOutcome, NodeStatus, RuntimeContext,
-    handlers::codergen::{CodergenBackend, CodergenBackendResult},
+    AttractorError, AttractorStageToAgentLinkRecord, AttractorStorageReader,
+    AttractorStorageWriter, CxdbPersistenceMode, Graph, Node, NodeOutcome, NodeStatus,
+    RuntimeContext,
+    handlers::codergen::{CodergenBackend, CodergenBackendResult, capture_node_output},
     hooks::{ToolHookBridge, ToolHookSummary, resolve_tool_hook_commands},
+    query_completed_agent_output, query_stage_to_agent_linkage,
 };
 use async_trait::async_trait;
 use forge_agent::{
-    AgentError, Session, SessionPersistenceSnapshot, SubmitOptions, SubmitResult, ToolCallHook,
+    AgentError, Session, SessionPersistenceSnapshot, SessionState, StopReason, SubmitOptions,
+    SubmitResult, ToolCallHook,
 };
 use serde_json::Value;
 use std::collections::HashMap;
@@ -95,10 +98,20 @@ impl ForgeAgentCodergenAdapter {
                 options.model = Some(model.trim().to_string());
             }
         }
-        if let Some(reasoning) = node.attrs.get_str("reasoning_effort") {
-            if !reasoning.trim().is_empty() {
-                options.reasoning_effort = Some(reasoning.trim().to_ascii_lowercase());
-            }
+        // An explicit node attribute beats one inherited from the stylesheet
+        // or node defaults; `reasoning_effort` beats `reasoning` otherwise.
+        let non_empty = |key: &str| {
+            node.attrs
+                .get_str(key)
+                .filter(|value| !value.trim().is_empty())
+        };
+        let explicit = |key: &str| non_empty(key).filter(|_| node.attrs.is_explicit(key));
+        let reasoning = explicit("reasoning_effort")
+            .or_else(|| explicit("reasoning"))
+            .or_else(|| non_empty("reasoning_effort"))
+            .or_else(|| non_empty("reasoning"));
+        if let Some(reasoning) = reasoning {
+            options.reasoning_effort = Some(reasoning.trim().to_ascii_lowercase());
         }
         options
     }
@@ -171,6 +184,7 @@ pub struct ForgeAgentSessionBackend {
     adapter: ForgeAgentCodergenAdapter,
     submitter: Mutex<Box<dyn AgentSubmitter + Send>>,
     stage_link: Option<StageLinkConfig>,
+    resume_reader: Option<Arc<dyn AttractorStorageReader>>,
 }
 
 #[derive(Clone)]
@@ -188,6 +202,7 @@ impl ForgeAgentSessionBackend {
             adapter,
             submitter: Mutex::new(submitter),
             stage_link: None,
+            resume_reader: None,
         }
     }
 
@@ -199,6 +214,15 @@ impl ForgeAgentSessionBackend {
         self.stage_link = Some(StageLinkConfig { writer, mode });
         self
     }
+
+    /// On the first node of a resumed run, look up the interrupted run's
+    /// stage-to-agent link for the same stage attempt and reuse the agent's
+    /// final answer instead of submitting the prompt again. Lookup failures
+    /// fall back to a normal submit and leave a warning in the outcome notes.
+    pub fn with_resume_reader(mut self, reader: Arc<dyn AttractorStorageReader>) -> Self {
+        self.resume_reader = Some(reader);
+        self
+    }
 }
 
 #[async_trait]
@@ -225,6 +249,36 @@ impl CodergenBackend for ForgeAgentSessionBackend {
                     .map(ToOwned::to_owned)
             })
             .unwrap_or_else(|| "unknown-run".to_string());
+        let mut resume_warning = None;
+        if let Some(reader) = self.resume_reader.as_ref() {
+            match find_reusable_agent_output(reader.as_ref(), node, context, stage_attempt_id).await
+            {
+                Ok(Some(reused)) => {
+                    if let Some(stage_link) = self.stage_link.as_ref()
+                        && let Err(error) = emit_reused_stage_link(
+                            stage_link,
+                            &reused.link,
+                            context,
+                            run_id.as_str(),
+                            stage_attempt_id,
+                        )
+                        .await
+                        && stage_link.mode == CxdbPersistenceMode::Required
+                    {
+                        return Err(error);
+                    }
+                    return Ok(CodergenBackendResult::Outcome(
+                        map_reused_output_to_outcome(node, reused),
+                    ));
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    resume_warning = Some(format!(
+                        "warning: resume lookup failed, prompt resubmitted: {error}"
+                    ));
+                }
+            }
+        }
         let hook_commands = resolve_tool_hook_commands(node, graph);
         let mut submitter = self.submitter.lock().await;
         let hook_bridge = if hook_commands.is_empty() {
@@ -275,6 +329,10 @@ impl CodergenBackend for ForgeAgentSessionBackend {
         } else {
             outcome
         };
+        let outcome = match resume_warning {
+            Some(warning) => append_note(outcome, warning),
+            None => outcome,
+        };
         Ok(CodergenBackendResult::Outcome(outcome))
     }
 }
@@ -393,6 +451,111 @@ async fn emit_stage_link_if_available(
     .await
 }
 
+struct ReusedAgentOutput {
+    link: AttractorStageToAgentLinkRecord,
+    assistant_text: String,
+}
+
+async fn find_reusable_agent_output(
+    reader: &dyn AttractorStorageReader,
+    node: &Node,
+    context: &RuntimeContext,
+    stage_attempt_id: &str,
+) -> Result<Option<ReusedAgentOutput>, AttractorError> {
+    let resumed_node_id = context
+        .get("internal.resume.node_id")
+        .and_then(Value::as_str);
+    if resumed_node_id != Some(node.id.as_str()) {
+        return Ok(None);
+    }
+    let Some(previous_context_id) = context
+        .get("internal.resume.pipeline_context_id")
+        .and_then(Value::as_str)
+        .map(ToOwned::to_owned)
+    else {
+        return Ok(None);
+    };
+
+    let Some(link) = query_stage_to_agent_linkage(reader, &previous_context_id)
+        .await?
+        .into_iter()
+        .rev()
+        .find(|link| link.node_id == node.id && link.stage_attempt_id == stage_attempt_id)
+    else {
+        return Ok(None);
+    };
+    let Some(head_turn_id) = link.agent_head_turn_id.as_ref() else {
+        return Ok(None);
+    };
+    let Some(assistant_text) =
+        query_completed_agent_output(reader, &link.agent_context_id, head_turn_id).await?
+    else {
+        return Ok(None);
+    };
+    Ok(Some(ReusedAgentOutput {
+        link,
+        assistant_text,
+    }))
+}
+
+async fn emit_reused_stage_link(
+    config: &StageLinkConfig,
+    link: &AttractorStageToAgentLinkRecord,
+    context: &RuntimeContext,
+    run_id: &str,
+    stage_attempt_id: &str,
+) -> Result<(), AttractorError> {
+    let Some(pipeline_context_id) = context
+        .get("pipeline_context_id")
+        .and_then(Value::as_str)
+        .map(ToOwned::to_owned)
+    else {
+        return Ok(());
+    };
+    emit_stage_to_agent_link(StageLinkEmission {
+        writer: config.writer.clone(),
+        context_id: &pipeline_context_id,
+        run_id,
+        node_id: link.node_id.as_str(),
+        stage_attempt_id,
+        agent_session_id: link.agent_session_id.as_str(),
+        agent_context_id: &link.agent_context_id,
+        agent_head_turn_id: link.agent_head_turn_id.clone(),
+        parent_turn_id: context
+            .get("pipeline_parent_turn_id")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned),
+        sequence_no: context
+            .get("pipeline_stage_link_sequence_no")
+            .and_then(Value::as_u64)
+            .unwrap_or(0),
+        thread_key: link.thread_key.clone(),
+    })
+    .await
+}
+
+fn map_reused_output_to_outcome(node: &Node, reused: ReusedAgentOutput) -> NodeOutcome {
+    let mut outcome = map_submit_result_to_outcome(
+        node,
+        None,
+        SubmitResult {
+            final_state: SessionState::Idle,
+            assistant_text: reused.assistant_text,
+            tool_call_count: 0,
+            tool_call_ids: vec![],
+            tool_error_count: 0,
+            usage: None,
+            thread_key: reused.link.thread_key,
+            stopped_reason: StopReason::NaturalCompletion,
+        },
+    );
+    outcome.notes = Some(format!(
+        "Stage completed: {} (reused agent output from context {})",
+        node.id, reused.link.agent_context_id
+    ));
+    outcome
+}
+
 fn stage_metadata(node: &Node, stage_attempt_id: &str) -> HashMap<String, String> {
     HashMap::from([
         ("node_id".to_string(), node.id.clone()),
@@ -469,12 +632,14 @@ fn map_submit_result_to_outcome(
         Some(format!("Stage completed: {}", node.id))
     };
 
-    NodeOutcome {
+    let mut outcome = NodeOutcome {
         status,
         notes,
         context_updates: updates,
         ..Default::default()
-    }
+    };
+    capture_node_output(node, &result.assistant_text, &mut outcome);
+    outcome
 }
 
 fn apply_tool_hook_summary(mut outcome: NodeOutcome, summary: ToolHookSummary) -> NodeOutcome {
@@ -491,9 +656,13 @@ fn apply_tool_hook_summary(mut outcome: NodeOutcome, summary: ToolHookSummary) -
         summary.post_non_zero,
         summary.post_error
     );
+    append_note(outcome, summary_suffix)
+}
+
+fn append_note(mut outcome: NodeOutcome, note: String) -> NodeOutcome {
     outcome.notes = Some(match outcome.notes.take() {
-        Some(notes) if !notes.trim().is_empty() => format!("{notes}; {summary_suffix}"),
-        _ => summary_suffix,
+        Some(notes) if !notes.trim().is_empty() => format!("{notes}; {note}"),
+        _ => note,
     });
     outcome
 }
@@ -521,9 +690,9 @@ mod tests {
         AttractorCheckpointSavedRecord, AttractorDotSourceRecord, AttractorGraphSnapshotRecord,
         AttractorInterviewLifecycleRecord, AttractorParallelLifecycleRecord,
         AttractorRouteDecisionRecord, AttractorRunLifecycleRecord, AttractorStageLifecycleRecord,
-        parse_dot,
+        apply_model_stylesheet, parse_dot,
     };
-    use forge_agent::{SessionState, ToolCallHook};
+    use forge_agent::{SessionState, StopReason, ToolCallHook};
     use serde_json::json;
 
     struct StubSubmitter {
@@ -681,6 +850,70 @@ mod tests {
         }
     }
 
+    #[derive(Default)]
+    struct TurnListingReader {
+        turns: HashMap<ContextId, Vec<StoredTurn>>,
+    }
+
+    impl TurnListingReader {
+        fn push(&mut self, context_id: &str, type_id: &str, payload: Value) -> TurnId {
+            let turns = self.turns.entry(context_id.to_string()).or_default();
+            let turn_id = format!("{context_id}-{}", turns.len() + 1);
+            turns.push(StoredTurn {
+                context_id: context_id.to_string(),
+                turn_id: turn_id.clone(),
+                parent_turn_id: "0".to_string(),
+                depth: turns.len() as u32 + 1,
+                type_id: type_id.to_string(),
+                type_version: 1,
+                payload: serde_json::to_vec(&payload).expect("payload should encode"),
+                idempotency_key: None,
+                content_hash: None,
+            });
+            turn_id
+        }
+    }
+
+    #[async_trait]
+    impl AttractorStorageReader for TurnListingReader {
+        async fn get_head(
+            &self,
+            _context_id: &ContextId,
+        ) -> Result<crate::storage::StoredTurnRef, StorageError> {
+            Err(StorageError::Unsupported("unused".to_string()))
+        }
+
+        async fn list_turns(
+            &self,
+            context_id: &ContextId,
+            _before_turn_id: Option<&TurnId>,
+            _limit: usize,
+        ) -> Result<Vec<StoredTurn>, StorageError> {
+            Ok(self.turns.get(context_id).cloned().unwrap_or_default())
+        }
+    }
+
+    struct FailingReader;
+
+    #[async_trait]
+    impl AttractorStorageReader for FailingReader {
+        async fn get_head(
+            &self,
+            _context_id: &ContextId,
+        ) -> Result<crate::storage::StoredTurnRef, StorageError> {
+            Err(StorageError::Backend("connection refused".to_string()))
+        }
+
+        async fn list_turns(
+            &self,
+            _context_id: &ContextId,
+            _before_turn_id: Option<&TurnId>,
+            _limit: usize,
+        ) -> Result<Vec<StoredTurn>, StorageError> {
+            Err(StorageError::Backend("connection refused".to_string()))
+        }
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn submit_options_for_node_maps_overrides_expected_fields_set() {
         let graph = parse_dot(
@@ -723,6 +956,7 @@ mod tests {
                 tool_error_count: 1,
                 usage: None,
                 thread_key: Some("thread-main".to_string()),
+                stopped_reason: StopReason::NaturalCompletion,
             },
             hook_set_calls: 0,
             persistence_snapshot: SessionPersistenceSnapshot::default(),
@@ -744,6 +978,57 @@ mod tests {
         assert_eq!(metadata.get("node_id").map(String::as_str), Some("n1"));
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn execute_with_submitter_reasoning_attr_expected_submit_options_effort() {
+        let graph = parse_dot(r#"digraph G { n1 [prompt="think", reasoning="High"] }"#)
+            .expect("graph should parse");
+        let node = graph.nodes.get("n1").expect("node");
+        let mut submitter = StubSubmitter {
+            thread_key: None,
+            last_input: None,
+            last_options: None,
+            result: SubmitResult {
+                final_state: SessionState::Idle,
+                assistant_text: "done".to_string(),
+                tool_call_count: 0,
+                tool_call_ids: vec![],
+                tool_error_count: 0,
+                usage: None,
+                thread_key: None,
+                stopped_reason: StopReason::NaturalCompletion,
+            },
+            hook_set_calls: 0,
+            persistence_snapshot: SessionPersistenceSnapshot::default(),
+        };
+        let adapter = ForgeAgentCodergenAdapter::default();
+        adapter
+            .execute_with_submitter(&mut submitter, node, &RuntimeContext::new(), &graph, "a1")
+            .await
+            .expect("execution should succeed");
+
+        let options = submitter.last_options.expect("submit options recorded");
+        assert_eq!(options.reasoning_effort.as_deref(), Some("high"));
+    }
+
+    #[test]
+    fn submit_options_for_node_explicit_reasoning_over_stylesheet_effort_expected_node_value() {
+        let mut graph = parse_dot(
+            r#"digraph G {
+                graph [model_stylesheet="* { reasoning_effort: high; }"]
+                n1 [prompt="think", reasoning="low"]
+                n2 [prompt="think"]
+            }"#,
+        )
+        .expect("graph should parse");
+        apply_model_stylesheet(&mut graph).expect("stylesheet should apply");
+        let adapter = ForgeAgentCodergenAdapter::default();
+
+        let explicit = adapter.submit_options_for_node(graph.nodes.get("n1").expect("node"));
+        assert_eq!(explicit.reasoning_effort.as_deref(), Some("low"));
+        let inherited = adapter.submit_options_for_node(graph.nodes.get("n2").expect("node"));
+        assert_eq!(inherited.reasoning_effort.as_deref(), Some("high"));
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn forge_agent_session_backend_run_expected_codergen_outcome_variant() {
         let graph = parse_dot("digraph G { n1 [prompt=\"hi\"] }").expect("graph should parse");
@@ -760,6 +1045,7 @@ mod tests {
                 tool_error_count: 0,
                 usage: None,
                 thread_key: None,
+                stopped_reason: StopReason::NaturalCompletion,
             },
             hook_set_calls: 0,
             persistence_snapshot: SessionPersistenceSnapshot::default(),
@@ -805,6 +1091,7 @@ mod tests {
                 tool_error_count: 0,
                 usage: None,
                 thread_key: None,
+                stopped_reason: StopReason::NaturalCompletion,
             },
             hook_set_calls: 0,
             persistence_snapshot: SessionPersistenceSnapshot::default(),
@@ -874,6 +1161,7 @@ mod tests {
                 tool_error_count: 0,
                 usage: None,
                 thread_key: None,
+                stopped_reason: StopReason::NaturalCompletion,
             },
             hook_set_calls: 0,
             persistence_snapshot: SessionPersistenceSnapshot {
@@ -921,6 +1209,164 @@ mod tests {
         assert_eq!(calls[0].pipeline_context_id, "pipeline-ctx");
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn forge_agent_session_backend_resume_with_completed_agent_output_expected_no_submit() {
+        let graph = parse_dot("digraph G { n1 [prompt=\"hi\"] }").expect("graph should parse");
+        let node = graph.nodes.get("n1").expect("node");
+        let mut reader = TurnListingReader::default();
+        reader.push(
+            "agent-ctx",
+            "forge.agent.user_turn",
+            json!({"session_id": "session-1", "turn": {"k": "u", "c": "hi", "ts": "1"}}),
+        );
+        let head_turn_id = reader.push(
+            "agent-ctx",
+            "forge.agent.assistant_turn",
+            json!({
+                "session_id": "session-1",
+                "turn": {"k": "a", "c": "already done", "us": {}, "ts": "2"},
+            }),
+        );
+        let link = AttractorStageToAgentLinkRecord {
+            timestamp: "1".to_string(),
+            run_id: "run-1".to_string(),
+            pipeline_context_id: "old-pipeline-ctx".to_string(),
+            node_id: "n1".to_string(),
+            stage_attempt_id: "n1:attempt:1".to_string(),
+            agent_session_id: "session-1".to_string(),
+            agent_context_id: "agent-ctx".to_string(),
+            agent_head_turn_id: Some(head_turn_id),
+            parent_turn_id: None,
+            sequence_no: 4,
+            thread_key: None,
+        };
+        reader.push(
+            "old-pipeline-ctx",
+            crate::storage::types::ATTRACTOR_STAGE_TO_AGENT_LINK_TYPE_ID,
+            serde_json::to_value(&link).expect("link should encode"),
+        );
+        let submitter = StubSubmitter {
+            thread_key: None,
+            last_input: None,
+            last_options: None,
+            result: SubmitResult {
+                final_state: SessionState::Idle,
+                assistant_text: "fresh llm call".to_string(),
+                tool_call_count: 0,
+                tool_call_ids: vec![],
+                tool_error_count: 0,
+                usage: None,
+                thread_key: None,
+                stopped_reason: StopReason::NaturalCompletion,
+            },
+            hook_set_calls: 0,
+            persistence_snapshot: SessionPersistenceSnapshot {
+                session_id: "session-1".to_string(),
+                context_id: None,
+                head_turn_id: None,
+            },
+        };
+        let backend = ForgeAgentSessionBackend::new(
+            ForgeAgentCodergenAdapter::default(),
+            Box::new(submitter),
+        )
+        .with_resume_reader(Arc::new(reader));
+        let mut context = RuntimeContext::new();
+        context.insert(
+            "stage_attempt_id".to_string(),
+            Value::String("n1:attempt:1".to_string()),
+        );
+        context.insert(
+            "internal.resume.node_id".to_string(),
+            Value::String("n1".to_string()),
+        );
+        context.insert(
+            "internal.resume.pipeline_context_id".to_string(),
+            Value::String("old-pipeline-ctx".to_string()),
+        );
+
+        let result = backend
+            .run(node, "hi", &context, &graph)
+            .await
+            .expect("backend run should succeed");
+
+        let CodergenBackendResult::Outcome(outcome) = result else {
+            panic!("expected outcome result");
+        };
+        assert_eq!(outcome.status, NodeStatus::Success);
+        assert_eq!(
+            outcome.context_updates.get("last_response"),
+            Some(&Value::String("already done".to_string()))
+        );
+        assert!(
+            outcome
+                .notes
+                .as_deref()
+                .is_some_and(|notes| notes.contains("reused agent output"))
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn forge_agent_session_backend_resume_lookup_error_expected_submit_with_warning() {
+        let graph = parse_dot("digraph G { n1 [prompt=\"hi\"] }").expect("graph should parse");
+        let node = graph.nodes.get("n1").expect("node");
+        let submitter = StubSubmitter {
+            thread_key: None,
+            last_input: None,
+            last_options: None,
+            result: SubmitResult {
+                final_state: SessionState::Idle,
+                assistant_text: "fresh llm call".to_string(),
+                tool_call_count: 0,
+                tool_call_ids: vec![],
+                tool_error_count: 0,
+                usage: None,
+                thread_key: None,
+                stopped_reason: StopReason::NaturalCompletion,
+            },
+            hook_set_calls: 0,
+            persistence_snapshot: SessionPersistenceSnapshot {
+                session_id: "session-1".to_string(),
+                context_id: None,
+                head_turn_id: None,
+            },
+        };
+        let backend = ForgeAgentSessionBackend::new(
+            ForgeAgentCodergenAdapter::default(),
+            Box::new(submitter),
+        )
+        .with_resume_reader(Arc::new(FailingReader));
+        let mut context = RuntimeContext::new();
+        context.insert(
+            "internal.resume.node_id".to_string(),
+            Value::String("n1".to_string()),
+        );
+        context.insert(
+            "internal.resume.pipeline_context_id".to_string(),
+            Value::String("old-pipeline-ctx".to_string()),
+        );
+
+        let result = backend
+            .run(node, "hi", &context, &graph)
+            .await
+            .expect("lookup errors should not fail the node");
+
+        let CodergenBackendResult::Outcome(outcome) = result else {
+            panic!("expected outcome result");
+        };
+        assert_eq!(
+            outcome.context_updates.get("last_response"),
+            Some(&Value::String("fresh llm call".to_string()))
+        );
+        assert!(
+            outcome
+                .notes
+                .as_deref()
+                .is_some_and(|notes| notes.contains("resume lookup failed")
+                    && notes.contains("connection refused"))
+        );
+    }
+
     #[test]
     fn resolve_thread_key_prefers_node_thread_id_expected_node_value() {
         let graph = parse_dot("digraph G { n1 [thread_id=\"t1\"] }").expect("graph should parse");
@@ -13,6 +13,10 @@ pub struct CheckpointMetadata {
     pub checkpoint_id: String,
     pub sequence_no: u64,
     pub timestamp: String,
+    /// Context keys whose persisted values are redaction or truncation
+    /// placeholders; resume drops them rather than restoring the placeholder.
+    #[serde(default)]
+    pub sanitized_context_keys: Vec<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -160,6 +164,7 @@ mod tests {
                 checkpoint_id: "cp-1".to_string(),
                 sequence_no: 5,
                 timestamp: "123.000Z".to_string(),
+                sanitized_context_keys: Vec::new(),
             },
             current_node: "plan".to_string(),
             next_node: Some("review".to_string()),
@@ -5,9 +5,41 @@ use serde_json::Value;
 enum Operator {
     Eq,
     Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
     Exists,
 }
 
+impl Operator {
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Eq => "=",
+            Self::Ne => "!=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Exists => "",
+        }
+    }
+
+    fn is_ordering(self) -> bool {
+        matches!(self, Self::Gt | Self::Ge | Self::Lt | Self::Le)
+    }
+}
+
+// Two-character operators come first so `>=` is not read as `>`.
+const OPERATORS: [(&str, Operator); 6] = [
+    ("!=", Operator::Ne),
+    (">=", Operator::Ge),
+    ("<=", Operator::Le),
+    (">", Operator::Gt),
+    ("<", Operator::Lt),
+    ("=", Operator::Eq),
+];
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 struct Clause<'a> {
     key: &'a str,
@@ -20,17 +52,20 @@ pub fn validate_condition_expression(condition: &str) -> Result<(), String> {
         if !is_condition_key(clause.key) {
             return Err(format!("condition key '{}' is invalid", clause.key));
         }
-        if matches!(clause.operator, Operator::Eq | Operator::Ne)
-            && clause.value.unwrap_or_default().trim().is_empty()
-        {
+        let value = clause.value.unwrap_or_default().trim();
+        if clause.operator != Operator::Exists && value.is_empty() {
             return Err(format!(
                 "condition clause '{}{}' has empty value",
                 clause.key,
-                if clause.operator == Operator::Eq {
-                    "="
-                } else {
-                    "!="
-                }
+                clause.operator.symbol()
+            ));
+        }
+        if clause.operator.is_ordering() && !parse_literal(value).is_number() {
+            return Err(format!(
+                "condition clause '{}{}{}' must compare against a number",
+                clause.key,
+                clause.operator.symbol(),
+                value
             ));
         }
     }
@@ -49,6 +84,9 @@ pub fn evaluate_condition_expression(
             Operator::Exists => is_truthy(actual),
             Operator::Eq => equals(actual, clause.value.unwrap_or_default()),
             Operator::Ne => !equals(actual, clause.value.unwrap_or_default()),
+            Operator::Gt | Operator::Ge | Operator::Lt | Operator::Le => {
+                compare_numbers(&clause, actual)?
+            }
         };
         if !passed {
             return Ok(false);
@@ -64,18 +102,10 @@ fn parse_clauses(condition: &str) -> Result<Vec<Clause<'_>>, String> {
         if clause.is_empty() {
             continue;
         }
-        if let Some((left, right)) = clause.split_once("!=") {
+        if let Some((left, operator, right)) = split_operator(clause) {
             out.push(Clause {
                 key: left.trim(),
-                operator: Operator::Ne,
-                value: Some(right.trim()),
-            });
-            continue;
-        }
-        if let Some((left, right)) = clause.split_once('=') {
-            out.push(Clause {
-                key: left.trim(),
-                operator: Operator::Eq,
+                operator,
                 value: Some(right.trim()),
             });
             continue;
@@ -95,6 +125,16 @@ fn parse_clauses(condition: &str) -> Result<Vec<Clause<'_>>, String> {
     Ok(out)
 }
 
+fn split_operator(clause: &str) -> Option<(&str, Operator, &str)> {
+    clause.char_indices().find_map(|(index, _)| {
+        let rest = &clause[index..];
+        OPERATORS.iter().find_map(|(symbol, operator)| {
+            rest.strip_prefix(symbol)
+                .map(|right| (&clause[..index], *operator, right))
+        })
+    })
+}
+
 fn is_condition_key(key: &str) -> bool {
     if key == "outcome" || key == "preferred_label" {
         return true;
@@ -127,25 +167,100 @@ fn resolve_key(
         _ if key.starts_with("context.") => {
             let suffix = &key["context.".len()..];
             // Try with the suffix first, then with full key
-            if let Some(value) = context.get(suffix) {
-                return Ok(Some(value.clone()));
+            if let Some(value) = lookup_context_path(suffix, context) {
+                return Ok(Some(value));
             }
-            if let Some(value) = context.get(key) {
-                return Ok(Some(value.clone()));
+            if let Some(value) = lookup_context_path(key, context) {
+                return Ok(Some(value));
             }
             // Missing keys compare as empty strings
             Ok(Some(Value::String(String::new())))
         }
         _ => {
             // Direct context lookup for unqualified keys
-            if let Some(value) = context.get(key) {
-                return Ok(Some(value.clone()));
+            if let Some(value) = lookup_context_path(key, context) {
+                return Ok(Some(value));
             }
             Ok(Some(Value::String(String::new())))
         }
     }
 }
 
+/// Resolves a dotted key against the context.
+///
+/// An exact key match wins. Otherwise the longest dotted prefix that names a
+/// context value is taken as the root, and the remaining segments walk into
+/// it as JSON object fields or array indices. String values are parsed as
+/// JSON first, so `stage.result.score` works whether `stage.result` holds an
+/// object or the raw JSON text a stage produced. A path that cannot be
+/// followed (missing field, index out of range, non-JSON string root)
+/// resolves to `None`, like a missing key.
+fn lookup_context_path(path: &str, context: &RuntimeContext) -> Option<Value> {
+    if let Some(value) = context.get(path) {
+        return Some(value.clone());
+    }
+
+    let mut end = path.len();
+    while let Some(dot) = path[..end].rfind('.') {
+        if let Some(value) = context.get(&path[..dot]) {
+            return extract_json_path(value, &path[dot + 1..]);
+        }
+        end = dot;
+    }
+    None
+}
+
+fn extract_json_path(value: &Value, rest: &str) -> Option<Value> {
+    let parsed;
+    let mut current = match value {
+        Value::String(text) => {
+            parsed = serde_json::from_str::<Value>(text).ok()?;
+            &parsed
+        }
+        other => other,
+    };
+
+    for segment in rest.split('.') {
+        current = match current {
+            Value::Object(map) => map.get(segment)?,
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(current.clone())
+}
+
+fn compare_numbers(clause: &Clause<'_>, actual: Option<Value>) -> Result<bool, String> {
+    let raw = clause.value.unwrap_or_default();
+    let describe = || format!("{}{}{}", clause.key, clause.operator.symbol(), raw);
+    let expected = parse_literal(raw).as_f64().ok_or_else(|| {
+        format!(
+            "condition clause '{}' must compare against a number",
+            describe()
+        )
+    })?;
+    let actual = match &actual {
+        Some(Value::Number(number)) => number.as_f64(),
+        Some(Value::String(text)) => text.trim().parse::<f64>().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| {
+        format!(
+            "condition clause '{}' needs a numeric value for '{}'",
+            describe(),
+            clause.key
+        )
+    })?;
+
+    Ok(match clause.operator {
+        Operator::Gt => actual > expected,
+        Operator::Ge => actual >= expected,
+        Operator::Lt => actual < expected,
+        Operator::Le => actual <= expected,
+        _ => false,
+    })
+}
+
 fn equals(actual: Option<Value>, expected_raw: &str) -> bool {
     let expected = parse_literal(expected_raw);
     match (actual, expected) {
@@ -306,4 +421,37 @@ mod tests {
             .expect("evaluation should succeed");
         assert!(ok);
     }
+
+    #[test]
+    fn evaluate_condition_expression_nested_numeric_path_expected_true() {
+        let mut context = RuntimeContext::new();
+        context.insert(
+            "stage.result".to_string(),
+            serde_json::json!({"scores": [0.5, 0.95]}),
+        );
+        let ok = evaluate_condition_expression(
+            "context.stage.result.scores.1>=0.9 && context.stage.result.scores.0<0.9",
+            &outcome(),
+            &context,
+        )
+        .expect("evaluation should succeed");
+        assert!(ok);
+    }
+
+    #[test]
+    fn evaluate_condition_expression_path_into_non_json_string_not_equal_expected_true() {
+        // A path under a plain string resolves like a missing key, so != holds
+        let mut context = RuntimeContext::new();
+        context.insert("review".to_string(), Value::String("approve".to_string()));
+        let ok = evaluate_condition_expression("context.review.x!=y", &outcome(), &context)
+            .expect("evaluation should succeed");
+        assert!(ok);
+    }
+
+    #[test]
+    fn validate_condition_expression_ordering_non_numeric_expected_error() {
+        let error = validate_condition_expression("context.score>high")
+            .expect_err("validation should fail");
+        assert!(error.contains("must compare against a number"));
+    }
 }
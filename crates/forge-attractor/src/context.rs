@@ -8,6 +8,9 @@ pub type RuntimeContext = BTreeMap<String, Value>;
 
 const MAX_KEY_LENGTH: usize = 256;
 
+/// Placeholder written to disk for context keys listed in `RunConfig::redact_keys`.
+pub const REDACTED_CONTEXT_VALUE: &str = "<redacted>";
+
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct ContextSnapshot {
     pub values: RuntimeContext,
@@ -140,6 +143,83 @@ pub fn validate_context_key(key: &str) -> Result<(), AttractorError> {
     Ok(())
 }
 
+/// Returns a copy of `values` that is safe to persist.
+///
+/// Keys listed in `redact_keys`, or nested under one (`secrets` covers
+/// `secrets.api_key`), become [`REDACTED_CONTEXT_VALUE`]. Values whose JSON
+/// encoding exceeds `max_value_bytes` are truncated: strings keep a prefix and
+/// gain a marker, other values are replaced by the marker alone.
+///
+/// Both are one-way: checkpoints list the affected keys in
+/// `CheckpointMetadata::sanitized_context_keys`, and resuming drops those keys
+/// instead of restoring the placeholders.
+pub fn sanitize_context_for_persistence(
+    values: &RuntimeContext,
+    redact_keys: &[String],
+    max_value_bytes: Option<usize>,
+) -> RuntimeContext {
+    sanitize_context_with_keys(values, redact_keys, max_value_bytes).0
+}
+
+/// Like [`sanitize_context_for_persistence`], also returning the keys whose
+/// values were redacted or truncated, in key order.
+pub fn sanitize_context_with_keys(
+    values: &RuntimeContext,
+    redact_keys: &[String],
+    max_value_bytes: Option<usize>,
+) -> (RuntimeContext, Vec<String>) {
+    let mut sanitized_keys = Vec::new();
+    let sanitized = values
+        .iter()
+        .map(|(key, value)| {
+            let redacted = redact_keys.iter().any(|redact_key| {
+                key == redact_key
+                    || key
+                        .strip_prefix(redact_key.as_str())
+                        .is_some_and(|rest| rest.starts_with('.'))
+            });
+            let replacement = if redacted {
+                Some(Value::String(REDACTED_CONTEXT_VALUE.to_string()))
+            } else {
+                max_value_bytes.and_then(|max_bytes| truncate_context_value(value, max_bytes))
+            };
+            let value = match replacement {
+                Some(replacement) => {
+                    sanitized_keys.push(key.clone());
+                    replacement
+                }
+                None => value.clone(),
+            };
+            (key.clone(), value)
+        })
+        .collect();
+    (sanitized, sanitized_keys)
+}
+
+/// Returns the truncated replacement, or `None` when the value fits.
+fn truncate_context_value(value: &Value, max_bytes: usize) -> Option<Value> {
+    let encoded_len = serde_json::to_string(value)
+        .map(|encoded| encoded.len())
+        .unwrap_or_default();
+    if encoded_len <= max_bytes {
+        return None;
+    }
+    Some(match value {
+        Value::String(text) => {
+            let mut end = max_bytes.min(text.len());
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            Value::String(format!(
+                "{}...<truncated {} bytes>",
+                &text[..end],
+                text.len() - end
+            ))
+        }
+        _ => Value::String(format!("<truncated {encoded_len} bytes>")),
+    })
+}
+
 fn validate_key_segment(segment: &str, full_key: &str) -> Result<(), AttractorError> {
     let mut chars = segment.chars();
     let Some(first) = chars.next() else {
@@ -170,6 +250,44 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn sanitize_context_for_persistence_expected_redacted_and_truncated() {
+        let values = RuntimeContext::from([
+            ("secrets.api_key".to_string(), json!("sk-123")),
+            ("api_token".to_string(), json!("t-456")),
+            ("api_token_hint".to_string(), json!("keep")),
+            ("response".to_string(), json!("x".repeat(100))),
+            ("payload".to_string(), json!({"items": vec![1; 50]})),
+            ("small".to_string(), json!(7)),
+        ]);
+
+        let sanitized = sanitize_context_for_persistence(
+            &values,
+            &["secrets".to_string(), "api_token".to_string()],
+            Some(32),
+        );
+
+        assert_eq!(sanitized["secrets.api_key"], json!(REDACTED_CONTEXT_VALUE));
+        assert_eq!(sanitized["api_token"], json!(REDACTED_CONTEXT_VALUE));
+        assert_eq!(sanitized["api_token_hint"], json!("keep"));
+        assert_eq!(
+            sanitized["response"],
+            json!(format!("{}...<truncated 68 bytes>", "x".repeat(32)))
+        );
+        assert_eq!(sanitized["payload"], json!("<truncated 111 bytes>"));
+        assert_eq!(sanitized["small"], json!(7));
+
+        let (_, sanitized_keys) = sanitize_context_with_keys(
+            &values,
+            &["secrets".to_string(), "api_token".to_string()],
+            Some(32),
+        );
+        assert_eq!(
+            sanitized_keys,
+            vec!["api_token", "payload", "response", "secrets.api_key"]
+        );
+    }
+
     #[test]
     fn set_and_snapshot_round_trip() {
         let store = ContextStore::new();
@@ -0,0 +1,74 @@
+use crate::{AttractorError, RuntimeContext};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// One failed execution of a node, as seen by the retry loop.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetterAttempt {
+    pub attempt: u32,
+    pub stage_attempt_id: String,
+    pub status: String,
+    pub failure_reason: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// Triage record for a node whose failure ended the run.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetterRecord {
+    pub run_id: String,
+    pub graph_id: String,
+    pub node_id: String,
+    pub terminal_reason: String,
+    pub attempts: Vec<DeadLetterAttempt>,
+    pub final_context: RuntimeContext,
+    pub recorded_at: String,
+}
+
+#[async_trait]
+pub trait DeadLetterWriter: Send + Sync {
+    async fn write_dead_letter(&self, record: &DeadLetterRecord) -> Result<(), AttractorError>;
+}
+
+/// Writes each record as pretty JSON to `<root>/<run_id>/<node_id>.json`.
+#[derive(Clone, Debug)]
+pub struct FsDeadLetterWriter {
+    root: PathBuf,
+}
+
+impl FsDeadLetterWriter {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn record_path(&self, run_id: &str, node_id: &str) -> PathBuf {
+        self.root
+            .join(run_id.replace(':', "_"))
+            .join(format!("{node_id}.json"))
+    }
+}
+
+#[async_trait]
+impl DeadLetterWriter for FsDeadLetterWriter {
+    async fn write_dead_letter(&self, record: &DeadLetterRecord) -> Result<(), AttractorError> {
+        let path = self.record_path(&record.run_id, &record.node_id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|error| {
+                AttractorError::Runtime(format!(
+                    "failed to create dead-letter directory '{}': {error}",
+                    parent.display()
+                ))
+            })?;
+        }
+        let bytes = serde_json::to_vec_pretty(record).map_err(|error| {
+            AttractorError::Runtime(format!("failed to serialize dead-letter record: {error}"))
+        })?;
+        fs::write(&path, bytes).map_err(|error| {
+            AttractorError::Runtime(format!(
+                "failed to write dead-letter record '{}': {error}",
+                path.display()
+            ))
+        })
+    }
+}
@@ -44,6 +44,19 @@ pub enum PipelineEvent {
         lineage_attempt: u32,
         reason: String,
     },
+    Aborted {
+        run_id: String,
+        graph_id: String,
+        lineage_attempt: u32,
+    },
+    /// A problem that did not change the run's outcome, such as a failed
+    /// best-effort write or an edge condition that could not be evaluated.
+    Warning {
+        run_id: String,
+        graph_id: String,
+        lineage_attempt: u32,
+        message: String,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -80,6 +93,15 @@ pub enum StageEvent {
         next_attempt: u32,
         delay_ms: u64,
     },
+    Notification {
+        run_id: String,
+        node_id: String,
+        url: String,
+        delivered: bool,
+        attempts: u32,
+        status_code: Option<u16>,
+        error: Option<String>,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -159,11 +181,13 @@ where
 pub type SharedRuntimeEventObserver = Arc<dyn RuntimeEventObserver>;
 pub type RuntimeEventSender = mpsc::UnboundedSender<RuntimeEvent>;
 pub type RuntimeEventReceiver = mpsc::UnboundedReceiver<RuntimeEvent>;
+pub type RuntimeEventFilter = Arc<dyn Fn(&RuntimeEvent) -> bool + Send + Sync>;
 
 #[derive(Clone, Default)]
 pub struct RuntimeEventSink {
     observer: Option<SharedRuntimeEventObserver>,
     sender: Option<RuntimeEventSender>,
+    filter: Option<RuntimeEventFilter>,
 }
 
 impl RuntimeEventSink {
@@ -171,6 +195,7 @@ impl RuntimeEventSink {
         Self {
             observer: Some(observer),
             sender: None,
+            filter: None,
         }
     }
 
@@ -178,6 +203,7 @@ impl RuntimeEventSink {
         Self {
             observer: None,
             sender: Some(sender),
+            filter: None,
         }
     }
 
@@ -191,11 +217,26 @@ impl RuntimeEventSink {
         self
     }
 
+    /// Drops events for which `filter` returns false before they reach the
+    /// observer or sender.
+    pub fn with_filter(
+        mut self,
+        filter: impl Fn(&RuntimeEvent) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+
     pub fn is_enabled(&self) -> bool {
         self.observer.is_some() || self.sender.is_some()
     }
 
     pub fn emit(&self, event: RuntimeEvent) {
+        if let Some(filter) = self.filter.as_ref()
+            && !filter(&event)
+        {
+            return;
+        }
         if let Some(observer) = self.observer.as_ref() {
             observer.on_event(&event);
         }
@@ -243,4 +284,35 @@ mod tests {
             &[7]
         );
     }
+
+    #[test]
+    fn runtime_event_sink_filter_expected_rejected_events_dropped() {
+        let (tx, mut rx) = runtime_event_channel();
+        let sink = RuntimeEventSink::with_sender(tx)
+            .with_filter(|event| matches!(event.kind, RuntimeEventKind::Checkpoint(_)));
+        sink.emit(RuntimeEvent {
+            sequence_no: 1,
+            timestamp: "1.000Z".to_string(),
+            kind: RuntimeEventKind::Pipeline(PipelineEvent::Started {
+                run_id: "run-1".to_string(),
+                graph_id: "g".to_string(),
+                lineage_attempt: 1,
+            }),
+        });
+        sink.emit(RuntimeEvent {
+            sequence_no: 2,
+            timestamp: "2.000Z".to_string(),
+            kind: RuntimeEventKind::Checkpoint(CheckpointEvent::Saved {
+                run_id: "run-1".to_string(),
+                node_id: "start".to_string(),
+                checkpoint_id: "cp-1".to_string(),
+            }),
+        });
+
+        let streamed = rx
+            .try_recv()
+            .expect("checkpoint event should pass the filter");
+        assert_eq!(streamed.sequence_no, 2);
+        assert!(rx.try_recv().is_err());
+    }
 }
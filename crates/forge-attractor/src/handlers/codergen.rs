@@ -67,13 +67,22 @@ impl NodeHandler for CodergenHandler {
             prompt = prompt.replace("$goal", goal);
         }
 
-        let (response_text, outcome) = if let Some(backend) = self.backend.as_ref() {
+        let (response_text, mut outcome) = if let Some(backend) = self.backend.as_ref() {
             match backend.run(node, &prompt, context, graph).await {
-                Ok(CodergenBackendResult::Outcome(outcome)) => {
-                    (outcome.notes.clone().unwrap_or_default(), outcome)
+                Ok(CodergenBackendResult::Outcome(mut outcome)) => {
+                    let response = outcome.notes.clone().unwrap_or_default();
+                    let captured = outcome
+                        .context_updates
+                        .get("last_response")
+                        .and_then(Value::as_str)
+                        .map(ToOwned::to_owned)
+                        .unwrap_or_else(|| response.clone());
+                    capture_node_output(node, &captured, &mut outcome);
+                    (response, outcome)
                 }
                 Ok(CodergenBackendResult::Text(response)) => {
-                    let outcome = simulated_success(node, response.clone());
+                    let mut outcome = simulated_success(node, response.clone());
+                    capture_node_output(node, &response, &mut outcome);
                     (response, outcome)
                 }
                 Err(error) => {
@@ -86,12 +95,44 @@ impl NodeHandler for CodergenHandler {
             let outcome = simulated_success(node, response.clone());
             (response, outcome)
         };
+        if self.backend.is_none() {
+            capture_node_output(node, &response_text, &mut outcome);
+        }
 
         write_artifacts_if_configured(node, context, &prompt, &response_text)?;
         Ok(outcome)
     }
 }
 
+/// Stores a stage's final assistant text under the node's `output_key`.
+///
+/// Writes `<key>` (the trimmed text), `<key>.outcome` (the stage status) and
+/// `<key>.json` (the typed value when the text parses as JSON, else null). Keys the
+/// backend already set in `context_updates` are left untouched, so a backend
+/// with access to the untruncated reply can capture it first.
+pub(crate) fn capture_node_output(node: &Node, response_text: &str, outcome: &mut NodeOutcome) {
+    let Some(key) = node
+        .attrs
+        .get_str("output_key")
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+    else {
+        return;
+    };
+
+    let updates = &mut outcome.context_updates;
+    let text = response_text.trim();
+    updates
+        .entry(key.to_string())
+        .or_insert_with(|| Value::String(text.to_string()));
+    updates
+        .entry(format!("{key}.outcome"))
+        .or_insert_with(|| Value::String(outcome.status.as_str().to_string()));
+    updates
+        .entry(format!("{key}.json"))
+        .or_insert_with(|| serde_json::from_str(text).unwrap_or(Value::Null));
+}
+
 fn write_artifacts_if_configured(
     node: &Node,
     context: &RuntimeContext,
@@ -211,6 +252,47 @@ mod tests {
         assert_eq!(outcome.status, NodeStatus::Fail);
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn codergen_handler_output_key_expected_text_outcome_and_json_captured() {
+        struct JsonBackend;
+        #[async_trait]
+        impl CodergenBackend for JsonBackend {
+            async fn run(
+                &self,
+                _node: &Node,
+                _prompt: &str,
+                _context: &RuntimeContext,
+                _graph: &Graph,
+            ) -> Result<CodergenBackendResult, AttractorError> {
+                Ok(CodergenBackendResult::Text(
+                    " {\"verdict\": \"approve\"} ".to_string(),
+                ))
+            }
+        }
+
+        let graph = parse_dot("digraph G { n1 [shape=box, output_key=\"review\"] }")
+            .expect("graph should parse");
+        let node = graph.nodes.get("n1").expect("node should exist");
+        let handler = CodergenHandler::new(Some(Arc::new(JsonBackend)));
+        let outcome = handler
+            .execute(node, &RuntimeContext::new(), &graph)
+            .await
+            .expect("execution should succeed");
+
+        assert_eq!(
+            outcome.context_updates.get("review"),
+            Some(&Value::String("{\"verdict\": \"approve\"}".to_string()))
+        );
+        assert_eq!(
+            outcome.context_updates.get("review.outcome"),
+            Some(&Value::String("success".to_string()))
+        );
+        assert_eq!(
+            outcome.context_updates.get("review.json"),
+            Some(&serde_json::json!({"verdict": "approve"}))
+        );
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn codergen_handler_with_logs_root_expected_writes_prompt_response_files() {
         let graph = parse_dot(
@@ -0,0 +1,224 @@
+use crate::{
+    AttrValue, AttractorError, Graph, Node, NodeOutcome, NodeStatus, RuntimeContext,
+    handlers::{NodeHandler, tool::resolve_tool_timeout},
+};
+use async_trait::async_trait;
+use forge_agent::{ExecutionEnvironment, LocalExecutionEnvironment};
+use serde_json::Value;
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+/// Timeout applied when a command node sets no `timeout` attribute.
+pub const DEFAULT_COMMAND_TIMEOUT_MS: u64 = 600_000;
+const MAX_COMMAND_TIMEOUT_MS: u64 = 86_400_000;
+
+/// Runs a node's `command` attribute through an [`ExecutionEnvironment`] and
+/// maps the exit code to the outcome: zero is success, anything else is a
+/// failure that edges can route on with `outcome=fail`.
+///
+/// Nodes may set `working_dir`, `timeout`, and environment variables on top of
+/// the environment's own policy, either as `env="NAME=value,OTHER=value"` or as
+/// individual quoted `"env.NAME"="value"` attributes (which win on conflict).
+pub struct CommandHandler {
+    environment: Arc<dyn ExecutionEnvironment>,
+}
+
+impl CommandHandler {
+    pub fn new(environment: Arc<dyn ExecutionEnvironment>) -> Self {
+        Self { environment }
+    }
+}
+
+impl Default for CommandHandler {
+    fn default() -> Self {
+        let working_directory = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        Self::new(Arc::new(
+            LocalExecutionEnvironment::new(working_directory)
+                .with_command_timeout_limits(DEFAULT_COMMAND_TIMEOUT_MS, MAX_COMMAND_TIMEOUT_MS),
+        ))
+    }
+}
+
+#[async_trait]
+impl NodeHandler for CommandHandler {
+    async fn execute(
+        &self,
+        node: &Node,
+        context: &RuntimeContext,
+        _graph: &Graph,
+    ) -> Result<NodeOutcome, AttractorError> {
+        let command = node.attrs.get_str("command").unwrap_or_default().trim();
+        if command.is_empty() {
+            return Ok(NodeOutcome::failure("No command specified"));
+        }
+
+        let timeout_ms = resolve_tool_timeout(node)
+            .map(|timeout| timeout.as_millis() as u64)
+            .unwrap_or(DEFAULT_COMMAND_TIMEOUT_MS);
+        let working_dir = node
+            .attrs
+            .get_str("working_dir")
+            .map(str::trim)
+            .filter(|dir| !dir.is_empty());
+        let env_vars = command_env_vars(node);
+
+        let result = match self
+            .environment
+            .exec_command(command, timeout_ms, working_dir, env_vars)
+            .await
+        {
+            Ok(result) => result,
+            Err(error) => {
+                return Ok(NodeOutcome::failure(format!(
+                    "failed to run command: {error}"
+                )));
+            }
+        };
+
+        if let Some(logs_root) = context.get("runtime.logs_root").and_then(Value::as_str) {
+            let stage_dir = PathBuf::from(logs_root).join(&node.id);
+            let _ = std::fs::create_dir_all(&stage_dir);
+            let _ = std::fs::write(stage_dir.join("stdout.txt"), &result.stdout);
+            let _ = std::fs::write(stage_dir.join("stderr.txt"), &result.stderr);
+        }
+
+        let mut updates = RuntimeContext::new();
+        updates.insert(
+            "command.stdout".to_string(),
+            Value::String(result.stdout.clone()),
+        );
+        updates.insert(
+            "command.stderr".to_string(),
+            Value::String(result.stderr.clone()),
+        );
+        updates.insert(
+            "command.exit_code".to_string(),
+            Value::Number(result.exit_code.into()),
+        );
+        updates.insert(
+            "command.timed_out".to_string(),
+            Value::Bool(result.timed_out),
+        );
+
+        if result.timed_out {
+            return Ok(NodeOutcome {
+                status: NodeStatus::Fail,
+                notes: Some(format!("Command timed out: {command}")),
+                failure_reason: Some(format!("command timed out after {timeout_ms}ms")),
+                context_updates: updates,
+                ..Default::default()
+            });
+        }
+        if result.exit_code != 0 {
+            return Ok(NodeOutcome {
+                status: NodeStatus::Fail,
+                notes: Some(format!(
+                    "Command failed with exit code {}: {command}",
+                    result.exit_code
+                )),
+                failure_reason: Some(format!("exit code {}", result.exit_code)),
+                context_updates: updates,
+                ..Default::default()
+            });
+        }
+        Ok(NodeOutcome {
+            status: NodeStatus::Success,
+            notes: Some(format!("Command completed: {command}")),
+            context_updates: updates,
+            ..Default::default()
+        })
+    }
+}
+
+fn command_env_vars(node: &Node) -> Option<HashMap<String, String>> {
+    let mut vars: HashMap<String, String> = node
+        .attrs
+        .get_str("env")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|pair| {
+            let (name, value) = pair.split_once('=')?;
+            let name = name.trim();
+            (!name.is_empty()).then(|| (name.to_string(), value.trim().to_string()))
+        })
+        .collect();
+    for (key, value) in node.attrs.values() {
+        if let Some(name) = key.strip_prefix("env.") {
+            let value = match value {
+                AttrValue::String(value) => value.clone(),
+                other => other.to_string_value(),
+            };
+            vars.insert(name.to_string(), value);
+        }
+    }
+    (!vars.is_empty()).then_some(vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_dot;
+    use tempfile::tempdir;
+
+    fn handler_in(dir: &std::path::Path) -> CommandHandler {
+        CommandHandler::new(Arc::new(LocalExecutionEnvironment::new(dir)))
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn command_handler_env_attrs_expected_visible_to_command() {
+        let dir = tempdir().expect("tempdir");
+        let graph = parse_dot(
+            r#"
+            digraph G {
+                c [
+                    type="command",
+                    command="printf '%s %s' \"$GREETING\" \"$TARGET\"",
+                    env="GREETING=hi, TARGET=there",
+                    "env.GREETING"="hello"
+                ]
+            }
+            "#,
+        )
+        .expect("graph should parse");
+        let node = graph.nodes.get("c").expect("node should exist");
+        let outcome = handler_in(dir.path())
+            .execute(node, &RuntimeContext::new(), &graph)
+            .await
+            .expect("execution should succeed");
+
+        assert_eq!(outcome.status, NodeStatus::Success);
+        assert_eq!(
+            outcome.context_updates.get("command.stdout"),
+            Some(&Value::String("hello there".to_string()))
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn command_handler_timeout_expected_fail_with_timed_out_flag() {
+        let dir = tempdir().expect("tempdir");
+        let graph =
+            parse_dot(r#"digraph G { c [type="command", command="sleep 5", timeout="100ms"] }"#)
+                .expect("graph should parse");
+        let node = graph.nodes.get("c").expect("node should exist");
+        let outcome = handler_in(dir.path())
+            .execute(node, &RuntimeContext::new(), &graph)
+            .await
+            .expect("execution should succeed");
+
+        assert_eq!(outcome.status, NodeStatus::Fail);
+        assert_eq!(
+            outcome.context_updates.get("command.timed_out"),
+            Some(&Value::Bool(true))
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn command_handler_missing_command_expected_fail() {
+        let graph = parse_dot(r#"digraph G { c [type="command"] }"#).expect("graph should parse");
+        let node = graph.nodes.get("c").expect("node should exist");
+        let outcome = CommandHandler::default()
+            .execute(node, &RuntimeContext::new(), &graph)
+            .await
+            .expect("execution should succeed");
+        assert_eq!(outcome.status, NodeStatus::Fail);
+    }
+}
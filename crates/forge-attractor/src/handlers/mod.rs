@@ -3,8 +3,10 @@ use async_trait::async_trait;
 use std::sync::Arc;
 
 pub mod codergen;
+pub mod command;
 pub mod conditional;
 pub mod exit;
+pub mod notify;
 pub mod parallel;
 pub mod parallel_fan_in;
 pub mod registry;
@@ -71,5 +73,7 @@ pub fn core_registry_with_codergen_backend(
         Arc::new(stack_manager_loop::StackManagerLoopHandler),
     );
     registry.register_type("tool", Arc::new(tool::ToolHandler));
+    registry.register_type("command", Arc::new(command::CommandHandler::default()));
+    registry.register_type("notify", Arc::new(notify::NotifyHandler::default()));
     registry
 }
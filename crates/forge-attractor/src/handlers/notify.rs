@@ -0,0 +1,159 @@
+use crate::{
+    AttractorError, Graph, Node, NodeOutcome, NodeStatus, RuntimeContext,
+    handlers::{NodeHandler, tool::resolve_tool_timeout},
+};
+use async_trait::async_trait;
+use serde_json::{Value, json};
+use std::time::Duration;
+
+const DEFAULT_NOTIFY_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_NOTIFY_MAX_ATTEMPTS: u32 = 3;
+const NOTIFY_RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+
+/// POSTs a JSON notification for the current pipeline position to the node's
+/// `notify_url`.
+///
+/// Delivery is retried up to `notify_max_attempts` times, each attempt bounded
+/// by the node `timeout`. Failures are non-fatal unless `notify_required=true`;
+/// either way the delivery result is recorded under `notify.*` so the runner
+/// can report it as a stage event.
+#[derive(Clone, Debug, Default)]
+pub struct NotifyHandler {
+    client: reqwest::Client,
+}
+
+impl NotifyHandler {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl NodeHandler for NotifyHandler {
+    async fn execute(
+        &self,
+        node: &Node,
+        context: &RuntimeContext,
+        graph: &Graph,
+    ) -> Result<NodeOutcome, AttractorError> {
+        let url = node.attrs.get_str("notify_url").unwrap_or_default().trim();
+        if url.is_empty() {
+            return Ok(NodeOutcome::failure("No notify_url specified"));
+        }
+
+        let timeout = resolve_tool_timeout(node).unwrap_or(DEFAULT_NOTIFY_TIMEOUT);
+        let max_attempts = node
+            .attrs
+            .get("notify_max_attempts")
+            .and_then(|value| value.as_i64())
+            .map(|value| value.clamp(1, u32::MAX as i64) as u32)
+            .unwrap_or(DEFAULT_NOTIFY_MAX_ATTEMPTS);
+        let required = node.attrs.get_bool("notify_required").unwrap_or(false);
+        let body = notification_body(node, context, graph);
+
+        let mut attempts = 0;
+        let mut status_code = None;
+        let mut error = None;
+        while attempts < max_attempts {
+            if attempts > 0 {
+                tokio::time::sleep(NOTIFY_RETRY_BASE_DELAY * 2u32.saturating_pow(attempts - 1))
+                    .await;
+            }
+            attempts += 1;
+            match self
+                .client
+                .post(url)
+                .timeout(timeout)
+                .json(&body)
+                .send()
+                .await
+            {
+                Ok(response) => {
+                    status_code = Some(response.status().as_u16());
+                    if response.status().is_success() {
+                        error = None;
+                        break;
+                    }
+                    error = Some(format!("webhook returned HTTP {}", response.status()));
+                }
+                Err(send_error) => {
+                    status_code = None;
+                    error = Some(format!("webhook request failed: {send_error}"));
+                }
+            }
+        }
+
+        let delivered = error.is_none();
+        let mut updates = RuntimeContext::new();
+        updates.insert("notify.url".to_string(), Value::String(url.to_string()));
+        updates.insert("notify.delivered".to_string(), Value::Bool(delivered));
+        updates.insert(
+            "notify.attempts".to_string(),
+            Value::Number(attempts.into()),
+        );
+        updates.insert(
+            "notify.status_code".to_string(),
+            status_code.map_or(Value::Null, |code| Value::Number(code.into())),
+        );
+        updates.insert(
+            "notify.error".to_string(),
+            error.clone().map_or(Value::Null, Value::String),
+        );
+
+        let (status, notes, failure_reason) = match error {
+            None => (
+                NodeStatus::Success,
+                format!("Notification delivered to {url}"),
+                None,
+            ),
+            Some(error) if required => (
+                NodeStatus::Fail,
+                format!("Notification failed after {attempts} attempt(s): {error}"),
+                Some(error),
+            ),
+            Some(error) => (
+                NodeStatus::Success,
+                format!("Notification not delivered (non-fatal): {error}"),
+                None,
+            ),
+        };
+        Ok(NodeOutcome {
+            status,
+            notes: Some(notes),
+            failure_reason,
+            context_updates: updates,
+            ..Default::default()
+        })
+    }
+}
+
+fn notification_body(node: &Node, context: &RuntimeContext, graph: &Graph) -> Value {
+    let root_run_id = context
+        .get("internal.lineage.root_run_id")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let lineage_attempt = context
+        .get("internal.lineage.attempt")
+        .and_then(Value::as_u64)
+        .unwrap_or(1);
+    let run_id = if lineage_attempt > 1 {
+        format!("{root_run_id}:attempt:{lineage_attempt}")
+    } else {
+        root_run_id.to_string()
+    };
+    let context_str = |key: &str| context.get(key).cloned().unwrap_or(Value::Null);
+
+    json!({
+        "run_id": run_id,
+        "graph_id": graph.id,
+        "node_id": node.id,
+        "status": context_str("outcome"),
+        "last_stage": context_str("last_stage"),
+        "message": node.attrs.get_str("notify_message"),
+        "correlation": {
+            "root_run_id": root_run_id,
+            "lineage_attempt": lineage_attempt,
+            "parent_run_id": context_str("internal.lineage.parent_run_id"),
+        },
+    })
+}
@@ -5,6 +5,23 @@ use crate::{
 use async_trait::async_trait;
 use serde_json::{Value, json};
 use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+tokio::task_local! {
+    /// Permits shared by every parallel node in a run, installed by the runner
+    /// from `RunConfig::max_parallelism`.
+    pub(crate) static BRANCH_PERMITS: Arc<Semaphore>;
+}
+
+/// Waits for a run-wide branch permit when a limit is installed. Nested
+/// parallel targets skip the wait so they cannot deadlock on their parent.
+async fn acquire_branch_permit(target: &Node) -> Option<OwnedSemaphorePermit> {
+    if crate::handlers::registry::resolve_handler_type_from_node(target) == "parallel" {
+        return None;
+    }
+    let permits = BRANCH_PERMITS.try_with(Arc::clone).ok()?;
+    permits.acquire_owned().await.ok()
+}
 
 #[derive(Clone, Debug)]
 struct BranchResult {
@@ -242,6 +259,7 @@ async fn run_branch_batches_with_executor(
 
             if let Some(target_node_ref) = target {
                 futures.push(async move {
+                    let _permit = acquire_branch_permit(target_node_ref).await;
                     match executor.execute(target_node_ref, &local_context, graph).await {
                         Ok(outcome) => BranchResult {
                             branch_id,
@@ -130,7 +130,7 @@ impl NodeHandler for ToolHandler {
     }
 }
 
-fn resolve_tool_timeout(node: &Node) -> Option<Duration> {
+pub(crate) fn resolve_tool_timeout(node: &Node) -> Option<Duration> {
     for key in &["timeout", "timeout_seconds"] {
         if let Some(value) = node.attrs.get(key) {
             let seconds = match value {
@@ -8,6 +8,7 @@ pub mod backends;
 pub mod checkpoint;
 pub mod condition;
 pub mod context;
+pub mod dead_letter;
 pub mod diagnostics;
 pub mod errors;
 pub mod events;
@@ -17,6 +18,7 @@ pub mod handlers;
 pub mod hooks;
 pub mod interviewer;
 pub mod lint;
+pub mod middleware;
 pub mod outcome;
 pub mod parse;
 pub mod queries;
@@ -37,6 +39,7 @@ pub use backends::*;
 pub use checkpoint::*;
 pub use condition::*;
 pub use context::*;
+pub use dead_letter::*;
 pub use diagnostics::*;
 pub use errors::*;
 pub use events::*;
@@ -46,6 +49,7 @@ pub use handlers::*;
 pub use hooks::*;
 pub use interviewer::*;
 pub use lint::*;
+pub use middleware::*;
 pub use parse::*;
 pub use queries::*;
 pub use resume::*;
@@ -1,9 +1,9 @@
 use crate::{
     Diagnostic, Graph, Severity, ValidationError,
     handlers::registry::resolve_handler_type_from_node, parse_stylesheet,
-    validate_condition_expression,
+    validate_condition_expression, validate_context_key,
 };
-use std::collections::{BTreeSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 
 pub trait LintRule {
     fn name(&self) -> &str;
@@ -23,6 +23,8 @@ pub fn validate(graph: &Graph, extra_rules: &[&dyn LintRule]) -> Vec<Diagnostic>
     diagnostics.extend(rule_stylesheet_syntax(graph));
     diagnostics.extend(rule_type_known(graph));
     diagnostics.extend(rule_fidelity_valid(graph));
+    diagnostics.extend(rule_reasoning_effort_valid(graph));
+    diagnostics.extend(rule_output_key_valid(graph));
     diagnostics.extend(rule_retry_target_exists(graph));
     diagnostics.extend(rule_goal_gate_has_retry(graph));
     diagnostics.extend(rule_prompt_on_llm_nodes(graph));
@@ -277,6 +279,64 @@ fn rule_fidelity_valid(graph: &Graph) -> Vec<Diagnostic> {
     diagnostics
 }
 
+fn rule_reasoning_effort_valid(graph: &Graph) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for node in graph.nodes.values() {
+        if let Some(value) = node.attrs.get_str("reasoning_effort") {
+            let normalized = value.trim().to_ascii_lowercase();
+            if !normalized.is_empty() && !matches!(normalized.as_str(), "low" | "medium" | "high") {
+                diagnostics.push(
+                    Diagnostic::new(
+                        "reasoning_effort_valid",
+                        Severity::Error,
+                        format!("node reasoning_effort '{value}' must be low|medium|high"),
+                    )
+                    .with_node_id(node.id.clone()),
+                );
+            }
+        }
+    }
+    diagnostics
+}
+
+fn rule_output_key_valid(graph: &Graph) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut writers: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+
+    for node in graph.nodes.values() {
+        let Some(key) = node.attrs.get_str("output_key").map(str::trim) else {
+            continue;
+        };
+        if let Err(error) = validate_context_key(key) {
+            diagnostics.push(
+                Diagnostic::new(
+                    "output_key_valid",
+                    Severity::Error,
+                    format!("node output_key is invalid: {error}"),
+                )
+                .with_node_id(node.id.clone()),
+            );
+            continue;
+        }
+        writers.entry(key).or_default().push(node.id.as_str());
+    }
+
+    for (key, node_ids) in writers {
+        if node_ids.len() > 1 {
+            diagnostics.push(Diagnostic::new(
+                "output_key_valid",
+                Severity::Info,
+                format!(
+                    "output_key '{key}' is written by nodes {}; the most recently completed stage wins",
+                    node_ids.join(", ")
+                ),
+            ));
+        }
+    }
+
+    diagnostics
+}
+
 fn rule_retry_target_exists(graph: &Graph) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
 
@@ -389,6 +449,8 @@ fn known_types() -> BTreeSet<&'static str> {
         "parallel",
         "parallel.fan_in",
         "tool",
+        "command",
+        "notify",
         "stack.manager_loop",
     ]
     .into_iter()
@@ -504,4 +566,53 @@ mod tests {
                 .any(|d| d.rule == "prompt_on_llm_nodes" && d.severity == Severity::Warning)
         );
     }
+
+    #[test]
+    fn validate_output_key_invalid_and_shared_expected_error_and_info() {
+        let graph = parse_dot(
+            r#"
+            digraph G {
+                start [shape=Mdiamond]
+                a [prompt="a", output_key="plan"]
+                b [prompt="b", output_key="plan"]
+                c [prompt="c", output_key="bad..key"]
+                exit [shape=Msquare]
+                start -> a -> b -> c -> exit
+            }
+            "#,
+        )
+        .expect("graph should parse");
+
+        let diagnostics = validate(&graph, &[]);
+        assert!(diagnostics.iter().any(|d| d.rule == "output_key_valid"
+            && d.is_error()
+            && d.node_id.as_deref() == Some("c")));
+        assert!(diagnostics.iter().any(|d| d.rule == "output_key_valid"
+            && d.severity == Severity::Info
+            && d.message.contains("'plan'")));
+    }
+
+    #[test]
+    fn validate_node_reasoning_effort_invalid_expected_error() {
+        let graph = parse_dot(
+            r#"
+            digraph G {
+                start [shape=Mdiamond]
+                a [prompt="a", reasoning_effort="extreme"]
+                b [prompt="b", reasoning_effort="Medium"]
+                exit [shape=Msquare]
+                start -> a -> b -> exit
+            }
+            "#,
+        )
+        .expect("graph should parse");
+
+        let diagnostics: Vec<_> = validate(&graph, &[])
+            .into_iter()
+            .filter(|d| d.rule == "reasoning_effort_valid")
+            .collect();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].is_error());
+        assert_eq!(diagnostics[0].node_id.as_deref(), Some("a"));
+    }
 }
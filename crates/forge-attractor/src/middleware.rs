@@ -0,0 +1,204 @@
+use crate::{AttractorError, Graph, Node, NodeExecutor, NodeOutcome, RuntimeContext};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Cross-cutting layer around node execution (logging, timing, rate limits).
+///
+/// Implementations call `next.run(..)` to continue down the chain; skipping it
+/// short-circuits the node with the returned outcome.
+#[async_trait]
+pub trait NodeMiddleware: Send + Sync {
+    async fn around(
+        &self,
+        node: &Node,
+        context: &RuntimeContext,
+        graph: &Graph,
+        next: Next<'_>,
+    ) -> Result<NodeOutcome, AttractorError>;
+}
+
+/// The remainder of a middleware chain, ending at the wrapped executor.
+pub struct Next<'a> {
+    middlewares: &'a [Arc<dyn NodeMiddleware>],
+    inner: &'a dyn NodeExecutor,
+}
+
+impl Next<'_> {
+    pub async fn run(
+        self,
+        node: &Node,
+        context: &RuntimeContext,
+        graph: &Graph,
+    ) -> Result<NodeOutcome, AttractorError> {
+        match self.middlewares.split_first() {
+            Some((middleware, rest)) => {
+                let next = Next {
+                    middlewares: rest,
+                    inner: self.inner,
+                };
+                middleware.around(node, context, graph, next).await
+            }
+            None => self.inner.execute(node, context, graph).await,
+        }
+    }
+}
+
+/// Wraps a [`NodeExecutor`] with middlewares; the first one added is the
+/// outermost layer.
+pub struct ChainedExecutor {
+    inner: Arc<dyn NodeExecutor>,
+    middlewares: Vec<Arc<dyn NodeMiddleware>>,
+}
+
+impl ChainedExecutor {
+    pub fn new(inner: Arc<dyn NodeExecutor>) -> Self {
+        Self {
+            inner,
+            middlewares: Vec::new(),
+        }
+    }
+
+    pub fn with(mut self, middleware: impl NodeMiddleware + 'static) -> Self {
+        self.middlewares.push(Arc::new(middleware));
+        self
+    }
+}
+
+#[async_trait]
+impl NodeExecutor for ChainedExecutor {
+    async fn execute(
+        &self,
+        node: &Node,
+        context: &RuntimeContext,
+        graph: &Graph,
+    ) -> Result<NodeOutcome, AttractorError> {
+        Next {
+            middlewares: &self.middlewares,
+            inner: self.inner.as_ref(),
+        }
+        .run(node, context, graph)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PipelineRunner, PipelineStatus, RunConfig, parse_dot};
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct CountingExecutor {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl NodeExecutor for CountingExecutor {
+        async fn execute(
+            &self,
+            _node: &Node,
+            _context: &RuntimeContext,
+            _graph: &Graph,
+        ) -> Result<NodeOutcome, AttractorError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(NodeOutcome::success())
+        }
+    }
+
+    struct CountingMiddleware {
+        seen: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl NodeMiddleware for CountingMiddleware {
+        async fn around(
+            &self,
+            node: &Node,
+            context: &RuntimeContext,
+            graph: &Graph,
+            next: Next<'_>,
+        ) -> Result<NodeOutcome, AttractorError> {
+            self.seen.lock().expect("mutex").push(node.id.clone());
+            next.run(node, context, graph).await
+        }
+    }
+
+    struct TaggingMiddleware {
+        tag: &'static str,
+        order: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    #[async_trait]
+    impl NodeMiddleware for TaggingMiddleware {
+        async fn around(
+            &self,
+            node: &Node,
+            context: &RuntimeContext,
+            graph: &Graph,
+            next: Next<'_>,
+        ) -> Result<NodeOutcome, AttractorError> {
+            self.order.lock().expect("mutex").push(self.tag);
+            next.run(node, context, graph).await
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn chained_executor_counting_middleware_expected_wraps_each_node_once() {
+        let graph = parse_dot(
+            r#"
+            digraph G {
+                start [shape=Mdiamond]
+                plan
+                build
+                exit [shape=Msquare]
+                start -> plan -> build -> exit
+            }
+            "#,
+        )
+        .expect("graph should parse");
+        let inner = Arc::new(CountingExecutor::default());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let executor =
+            ChainedExecutor::new(inner.clone()).with(CountingMiddleware { seen: seen.clone() });
+
+        let result = PipelineRunner
+            .run(
+                &graph,
+                RunConfig {
+                    executor: Arc::new(executor),
+                    ..RunConfig::default()
+                },
+            )
+            .await
+            .expect("run should succeed");
+
+        assert_eq!(result.status, PipelineStatus::Success);
+        let seen = seen.lock().expect("mutex");
+        assert_eq!(seen.len(), inner.calls.load(Ordering::SeqCst));
+        assert_eq!(*seen, result.completed_nodes);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn chained_executor_multiple_middlewares_expected_first_added_outermost() {
+        let graph = parse_dot("digraph G { n1 }").expect("graph should parse");
+        let node = graph.nodes.get("n1").expect("node");
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let executor = ChainedExecutor::new(Arc::new(CountingExecutor::default()))
+            .with(TaggingMiddleware {
+                tag: "outer",
+                order: order.clone(),
+            })
+            .with(TaggingMiddleware {
+                tag: "inner",
+                order: order.clone(),
+            });
+
+        executor
+            .execute(node, &RuntimeContext::new(), &graph)
+            .await
+            .expect("execution should succeed");
+
+        assert_eq!(*order.lock().expect("mutex"), vec!["outer", "inner"]);
+    }
+}
@@ -167,6 +167,7 @@ fn process_node_stmt(
     let mut attrs = scope.node_defaults.without_explicit();
     let parsed = parse_attributes(&node.attributes)?;
     attrs.merge_with_explicit_tracking(&parsed);
+    validate_reasoning_attr(&node_id, &attrs)?;
 
     if !scope.classes.is_empty() {
         let mut classes = parse_class_list(attrs.get_str("class").unwrap_or_default());
@@ -189,6 +190,19 @@ fn process_node_stmt(
     Ok(())
 }
 
+fn validate_reasoning_attr(node_id: &str, attrs: &Attributes) -> Result<(), AttractorError> {
+    let Some(value) = attrs.get_str("reasoning") else {
+        return Ok(());
+    };
+    let normalized = value.trim().to_ascii_lowercase();
+    if normalized.is_empty() || matches!(normalized.as_str(), "low" | "medium" | "high") {
+        return Ok(());
+    }
+    Err(AttractorError::InvalidGraph(format!(
+        "node '{node_id}' reasoning '{value}' must be low|medium|high"
+    )))
+}
+
 fn process_edge_stmt(
     state: &mut ParseState,
     edge: &DotEdge,
@@ -694,6 +708,12 @@ mod tests {
         assert!(error.to_string().contains("HTML"));
     }
 
+    #[test]
+    fn parse_dot_invalid_reasoning_rejected_expected_error() {
+        let error = parse_dot("digraph G { a [reasoning=\"extreme\"] }").expect_err("must fail");
+        assert!(error.to_string().contains("must be low|medium|high"));
+    }
+
     #[test]
     fn parse_duration_value_valid_expected_millis() {
         let duration = parse_duration("2h").expect("duration must parse");
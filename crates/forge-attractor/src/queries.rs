@@ -8,6 +8,12 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 const QUERY_PAGE_SIZE: usize = 256;
+const AGENT_ASSISTANT_TURN_TYPE_ID: &str = "forge.agent.assistant_turn";
+
+#[derive(Deserialize)]
+struct AgentTurnPayload {
+    turn: Value,
+}
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct RunMetadata {
@@ -176,6 +182,45 @@ pub async fn query_stage_to_agent_linkage(
     Ok(links)
 }
 
+/// Returns the text of the agent's final answer as of `head_turn_id`, or `None`
+/// when the agent had not finished (no assistant turn yet, or the latest one
+/// still requested tool calls) or the head is not in the context.
+pub async fn query_completed_agent_output(
+    reader: &dyn AttractorStorageReader,
+    agent_context_id: &ContextId,
+    head_turn_id: &TurnId,
+) -> Result<Option<String>, AttractorError> {
+    let turns = collect_all_turns(reader, agent_context_id).await?;
+    let Some(head_index) = turns.iter().position(|turn| &turn.turn_id == head_turn_id) else {
+        return Ok(None);
+    };
+    let Some(turn) = turns[..=head_index]
+        .iter()
+        .rev()
+        .find(|turn| turn.type_id == AGENT_ASSISTANT_TURN_TYPE_ID)
+    else {
+        return Ok(None);
+    };
+    let record: AgentTurnPayload = decode_record(turn)?;
+    // Agent turns are persisted either verbose or in the compact wire form.
+    let pending_tool_calls = ["tool_calls", "tc"].iter().any(|key| {
+        record
+            .turn
+            .get(key)
+            .and_then(Value::as_array)
+            .is_some_and(|calls| !calls.is_empty())
+    });
+    if pending_tool_calls {
+        return Ok(None);
+    }
+    Ok(record
+        .turn
+        .get("content")
+        .or_else(|| record.turn.get("c"))
+        .and_then(Value::as_str)
+        .map(ToOwned::to_owned))
+}
+
 async fn collect_all_turns(
     reader: &dyn AttractorStorageReader,
     context_id: &ContextId,
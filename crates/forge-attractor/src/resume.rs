@@ -79,6 +79,30 @@ pub fn apply_resume_fidelity_override(
     Ok(())
 }
 
+/// Records which node a resumed run starts at and the pipeline context of the
+/// interrupted run, so a codergen backend can look up agent work that finished
+/// before the checkpoint caught up. Passing `None` clears both markers.
+pub fn apply_resume_agent_reuse_markers(
+    context_store: &ContextStore,
+    resumed_node_id: Option<&str>,
+) -> Result<(), AttractorError> {
+    let previous_context_id = context_store.get("pipeline_context_id")?;
+    match (resumed_node_id, previous_context_id) {
+        (Some(node_id), Some(previous_context_id)) => {
+            context_store.set(
+                "internal.resume.node_id",
+                Value::String(node_id.to_string()),
+            )?;
+            context_store.set("internal.resume.pipeline_context_id", previous_context_id)?;
+        }
+        _ => {
+            context_store.remove("internal.resume.node_id")?;
+            context_store.remove("internal.resume.pipeline_context_id")?;
+        }
+    }
+    Ok(())
+}
+
 pub fn build_resume_runtime_state(
     graph: &Graph,
     checkpoint_path: &Path,
@@ -90,13 +114,41 @@ pub fn build_resume_runtime_state(
         node_outcomes.insert(node_id.clone(), stored.to_runtime()?);
     }
 
+    let mut completed_nodes = resume.checkpoint.completed_nodes.clone();
+    let mut replay_node_ids: Vec<String> = Vec::new();
+    if resume.next_node_id.is_some() {
+        for node_id in &completed_nodes {
+            let always = graph
+                .nodes
+                .get(node_id)
+                .and_then(|node| node.attrs.get_str("resume"))
+                .is_some_and(|value| value.trim().eq_ignore_ascii_case("always"));
+            if always && !replay_node_ids.contains(node_id) {
+                replay_node_ids.push(node_id.clone());
+            }
+        }
+        completed_nodes.retain(|node_id| !replay_node_ids.contains(node_id));
+        for node_id in &replay_node_ids {
+            node_outcomes.remove(node_id);
+        }
+    }
+
+    // Redacted and truncated values are placeholders, not data; restoring
+    // them would hand "<redacted>" or a truncation marker to later stages as
+    // if it were the real value.
+    let mut context = resume.checkpoint.context_values.clone();
+    for key in &resume.checkpoint.metadata.sanitized_context_keys {
+        context.remove(key);
+    }
+
     Ok(ResumeRuntimeState {
         checkpoint_run_id: resume.checkpoint.metadata.run_id.clone(),
-        context: resume.checkpoint.context_values.clone(),
-        completed_nodes: resume.checkpoint.completed_nodes.clone(),
+        context,
+        completed_nodes,
         node_retries: resume.checkpoint.node_retries.clone(),
         node_outcomes,
         next_node_id: resume.next_node_id,
+        replay_node_ids,
         terminal_status: resume.terminal_status,
         terminal_failure_reason: resume.terminal_failure_reason,
         degrade_fidelity_once: resume.degrade_fidelity_once,
@@ -106,11 +158,17 @@ pub fn build_resume_runtime_state(
 #[derive(Clone, Debug)]
 pub struct ResumeRuntimeState {
     pub checkpoint_run_id: String,
+    /// Checkpoint context values, minus the keys listed in
+    /// `CheckpointMetadata::sanitized_context_keys`; those are absent after a
+    /// resume.
     pub context: RuntimeContext,
     pub completed_nodes: Vec<String>,
     pub node_retries: std::collections::BTreeMap<String, u32>,
     pub node_outcomes: std::collections::BTreeMap<String, NodeOutcome>,
     pub next_node_id: Option<String>,
+    /// Completed nodes marked `resume=always`, in their original order. They
+    /// are removed from `completed_nodes` and re-executed before `next_node_id`.
+    pub replay_node_ids: Vec<String>,
     pub terminal_status: Option<PipelineStatus>,
     pub terminal_failure_reason: Option<String>,
     pub degrade_fidelity_once: bool,
@@ -147,7 +205,7 @@ pub fn infer_next_node_from_checkpoint(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{CheckpointMetadata, CheckpointNodeOutcome, parse_dot};
+    use crate::{CheckpointMetadata, CheckpointNodeOutcome, REDACTED_CONTEXT_VALUE, parse_dot};
     use std::collections::BTreeMap;
     use tempfile::TempDir;
 
@@ -189,6 +247,7 @@ mod tests {
                     checkpoint_id: "cp-7".to_string(),
                     sequence_no: 7,
                     timestamp: "1.000Z".to_string(),
+                    sanitized_context_keys: Vec::new(),
                 },
                 current_node: "plan".to_string(),
                 next_node: Some("review".to_string()),
@@ -224,4 +283,72 @@ mod tests {
         assert!(resolved.degrade_fidelity_once);
         assert_eq!(resolved.next_node_id.as_deref(), Some("review"));
     }
+
+    #[test]
+    fn build_resume_runtime_state_sanitized_keys_expected_dropped_others_kept() {
+        let temp = TempDir::new().expect("temp dir should create");
+        let path = checkpoint_file_path(temp.path());
+        write_checkpoint(
+            &path,
+            CheckpointState {
+                metadata: CheckpointMetadata {
+                    schema_version: 1,
+                    run_id: "run-1".to_string(),
+                    checkpoint_id: "cp-2".to_string(),
+                    sequence_no: 2,
+                    timestamp: "1.000Z".to_string(),
+                    sanitized_context_keys: vec![
+                        "api.secret".to_string(),
+                        "fetch.payload".to_string(),
+                    ],
+                },
+                current_node: "fetch".to_string(),
+                next_node: Some("use".to_string()),
+                completed_nodes: vec!["start".to_string(), "fetch".to_string()],
+                node_retries: BTreeMap::new(),
+                node_outcomes: BTreeMap::new(),
+                context_values: BTreeMap::from([
+                    (
+                        "api.secret".to_string(),
+                        Value::String(REDACTED_CONTEXT_VALUE.to_string()),
+                    ),
+                    (
+                        "fetch.payload".to_string(),
+                        Value::String("<truncated 4096 bytes>".to_string()),
+                    ),
+                    // A genuine value that happens to equal the placeholder.
+                    (
+                        "user.note".to_string(),
+                        Value::String(REDACTED_CONTEXT_VALUE.to_string()),
+                    ),
+                    ("fetch.status".to_string(), Value::String("ok".to_string())),
+                ]),
+                logs: vec![],
+                current_node_fidelity: None,
+                terminal_status: None,
+                terminal_failure_reason: None,
+                graph_dot_source_hash: None,
+                graph_dot_source_ref: None,
+                graph_snapshot_hash: None,
+                graph_snapshot_ref: None,
+            },
+        );
+
+        let state = build_resume_runtime_state(
+            &parse_dot("digraph G { start fetch use }").expect("graph parse"),
+            &path,
+        )
+        .expect("resume should build");
+
+        assert!(!state.context.contains_key("api.secret"));
+        assert!(!state.context.contains_key("fetch.payload"));
+        assert_eq!(
+            state.context.get("user.note"),
+            Some(&Value::String(REDACTED_CONTEXT_VALUE.to_string()))
+        );
+        assert_eq!(
+            state.context.get("fetch.status"),
+            Some(&Value::String("ok".to_string()))
+        );
+    }
 }
@@ -76,6 +76,29 @@ pub fn select_next_edge<'a>(
     best_by_weight_then_lexical(edges.iter().copied())
 }
 
+/// Conditions on `from_node_id`'s outgoing edges that fail to evaluate, such
+/// as a numeric comparison against a missing or non-numeric value, paired with
+/// the error. `select_next_edge` treats these conditions as false.
+pub fn condition_evaluation_errors<'a>(
+    graph: &'a Graph,
+    from_node_id: &'a str,
+    outcome: &NodeOutcome,
+    context: &RuntimeContext,
+) -> Vec<(&'a Edge, String)> {
+    graph
+        .outgoing_edges(from_node_id)
+        .filter_map(|edge| {
+            let condition = edge.attrs.get_str("condition").unwrap_or_default().trim();
+            if condition.is_empty() {
+                return None;
+            }
+            evaluate_condition_expression(condition, outcome, context)
+                .err()
+                .map(|error| (edge, error))
+        })
+        .collect()
+}
+
 fn best_by_weight_then_lexical<'a, I>(edges: I) -> Option<&'a Edge>
 where
     I: IntoIterator<Item = &'a Edge>,
@@ -150,6 +173,41 @@ mod tests {
         assert_eq!(selected.to, "b");
     }
 
+    #[test]
+    fn select_next_edge_non_numeric_operand_expected_condition_false_and_error_reported() {
+        let graph = parse_dot(
+            r#"
+            digraph G {
+                n1
+                high
+                low
+                missing
+                n1 -> high [condition="context.score>0.8"]
+                n1 -> missing [condition="context.absent>=1"]
+                n1 -> low
+            }
+            "#,
+        )
+        .expect("graph should parse");
+        let outcome = base_outcome();
+        let mut context = RuntimeContext::new();
+        context.insert("score".to_string(), serde_json::json!("high"));
+
+        let selected = select_next_edge(&graph, "n1", &outcome, &context).expect("edge expected");
+        assert_eq!(selected.to, "low");
+
+        let errors: Vec<(&str, String)> =
+            condition_evaluation_errors(&graph, "n1", &outcome, &context)
+                .into_iter()
+                .map(|(edge, error)| (edge.to.as_str(), error))
+                .collect();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].0, "high");
+        assert!(errors[0].1.contains("context.score>0.8"), "{}", errors[0].1);
+        assert_eq!(errors[1].0, "missing");
+        assert!(errors[1].1.contains("context.absent>=1"), "{}", errors[1].1);
+    }
+
     #[test]
     fn select_next_edge_preferred_label_normalized_expected_match() {
         let graph = parse_dot(
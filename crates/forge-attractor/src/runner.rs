@@ -1,31 +1,34 @@
+use crate::handlers::parallel::BRANCH_PERMITS;
 use crate::storage::{AttractorArtifactWriter, ContextId, StorageError, TurnId};
 use crate::{
     AttrValue, AttractorCheckpointSavedRecord, AttractorDotSourceRecord, AttractorError,
     AttractorFsSnapshotStats, AttractorGraphSnapshotRecord, AttractorInterviewLifecycleRecord,
     AttractorParallelLifecycleRecord, AttractorRouteDecisionRecord, AttractorRunLifecycleRecord,
     AttractorStageLifecycleRecord, CheckpointEvent, CheckpointMetadata, CheckpointNodeOutcome,
-    CheckpointState, ContextStore, CxdbPersistenceMode, Graph, InterviewEvent, Node, NodeOutcome,
-    NodeStatus, ParallelEvent, PipelineEvent, PipelineRunResult, PipelineStatus, RetryPolicy,
-    RunConfig, RuntimeContext, RuntimeEvent, RuntimeEventKind, RuntimeEventSink, StageEvent,
+    CheckpointState, ContextStore, CxdbPersistenceMode, DeadLetterAttempt, DeadLetterRecord, Graph,
+    InterviewEvent, Node, NodeOutcome, NodeStatus, NodeTiming, ParallelEvent, PipelineAbortHandle,
+    PipelineEvent, PipelineRunResult, PipelineStatus, RetryPolicy, RunConfig, RuntimeContext,
+    RuntimeEvent, RuntimeEventKind, RuntimeEventSink, StageEvent, apply_resume_agent_reuse_markers,
     apply_resume_fidelity_override, build_resume_runtime_state, build_retry_policy,
-    checkpoint_path_for_run, delay_for_attempt_ms, finalize_retry_exhausted, find_incoming_edge,
-    resolve_fidelity_mode, resolve_thread_key, select_next_edge, should_retry_outcome,
-    validate_or_raise,
+    checkpoint_path_for_run, condition_evaluation_errors, delay_for_attempt_ms,
+    finalize_retry_exhausted, find_incoming_edge, resolve_fidelity_mode, resolve_thread_key,
+    sanitize_context_for_persistence, sanitize_context_with_keys, select_next_edge,
+    should_retry_outcome, validate_or_raise,
 };
 use async_trait::async_trait;
 use forge_cxdb_runtime::{
     CxdbBinaryClient, CxdbClientError, CxdbFsSnapshotCapture, CxdbHttpClient, CxdbRuntimeStore,
 };
 use serde_json::{Value, json};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread::sleep;
 use std::time::Duration;
-use std::time::{SystemTime, UNIX_EPOCH};
-use tokio::sync::Mutex as AsyncMutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{Mutex as AsyncMutex, Semaphore};
 
 #[derive(Clone, Debug, Default)]
 struct PersistedRunGraphMetadata {
@@ -369,7 +372,10 @@ fn cxdb_error_to_storage(error: CxdbClientError) -> StorageError {
         CxdbClientError::NotFound { resource, id } => StorageError::NotFound { resource, id },
         CxdbClientError::Conflict(message) => StorageError::Conflict(message),
         CxdbClientError::InvalidInput(message) => StorageError::InvalidInput(message),
-        CxdbClientError::Backend(message) => StorageError::Backend(message),
+        CxdbClientError::Unsupported(message) => StorageError::Unsupported(message),
+        CxdbClientError::Backend(message) | CxdbClientError::Unavailable(message) => {
+            StorageError::Backend(message)
+        }
     }
 }
 
@@ -392,6 +398,9 @@ impl PipelineRunner {
         let mut resume_path_for_attempt = config.resume_from_checkpoint.take();
         let mut restart_start_node: Option<String> = None;
         let mut lineage_attempt = 1u32;
+        let branch_permits = config
+            .max_parallelism
+            .map(|limit| Arc::new(Semaphore::new(limit.max(1))));
 
         loop {
             let active_run_id = if lineage_attempt == 1 {
@@ -438,6 +447,7 @@ impl PipelineRunner {
 
             let mut completed_nodes: Vec<String> = Vec::new();
             let mut node_outcomes: BTreeMap<String, NodeOutcome> = BTreeMap::new();
+            let mut node_timings: Vec<NodeTiming> = Vec::new();
             let mut node_retry_counts: BTreeMap<String, u32> = BTreeMap::new();
             let mut current_node_id = restart_start_node
                 .clone()
@@ -445,7 +455,10 @@ impl PipelineRunner {
             let mut terminal_failure: Option<String> = None;
             let mut forced_terminal_status: Option<PipelineStatus> = None;
             let mut resume_fidelity_degrade_pending = false;
+            let mut resume_agent_reuse_pending = false;
+            let mut resume_replay: VecDeque<String> = VecDeque::new();
             let mut restart_target: Option<String> = None;
+            let mut aborted = false;
 
             if let Some(resume_path) = resume_path_for_attempt.as_ref() {
                 let resume = build_resume_runtime_state(graph, resume_path)?;
@@ -483,8 +496,23 @@ impl PipelineRunner {
                 forced_terminal_status = resume.terminal_status;
                 resume_fidelity_degrade_pending = resume.degrade_fidelity_once;
                 apply_resume_fidelity_override(&context_store, resume_fidelity_degrade_pending)?;
+                // Agent output reuse only applies when the resumed hop is the
+                // checkpoint's next node, not a replayed `resume=always` node.
+                let reuse_node_id = resume
+                    .next_node_id
+                    .as_deref()
+                    .filter(|_| resume.replay_node_ids.is_empty());
+                apply_resume_agent_reuse_markers(&context_store, reuse_node_id)?;
+                resume_agent_reuse_pending = reuse_node_id.is_some();
                 if let Some(next_node_id) = resume.next_node_id {
-                    current_node_id = next_node_id;
+                    let mut replay = resume.replay_node_ids.into_iter();
+                    current_node_id = match replay.next() {
+                        Some(first_replay) => {
+                            resume_replay = replay.chain([next_node_id]).collect();
+                            first_replay
+                        }
+                        None => next_node_id,
+                    };
                 } else if forced_terminal_status.is_none() {
                     return Err(AttractorError::Runtime(
                         "resume checkpoint has no next node and no terminal status".to_string(),
@@ -665,7 +693,9 @@ impl PipelineRunner {
                 )
                 .await?;
                 let context_snapshot = context_store.snapshot()?;
-                let (outcome, attempts_used) = execute_with_retry(
+                let node_started = timestamp_now();
+                let node_clock = Instant::now();
+                let execution = execute_with_retry(
                     node,
                     graph,
                     &context_snapshot.values,
@@ -675,8 +705,18 @@ impl PipelineRunner {
                     &active_run_id,
                     &event_sink,
                     &mut event_sequence_no,
-                )
-                .await?;
+                );
+                let (outcome, attempts_used, attempt_failures) = match branch_permits.as_ref() {
+                    Some(permits) => BRANCH_PERMITS.scope(permits.clone(), execution).await?,
+                    None => execution.await?,
+                };
+                node_timings.push(NodeTiming {
+                    node_id: node.id.clone(),
+                    attempt: attempts_used,
+                    started: node_started,
+                    duration_ms: node_clock.elapsed().as_millis() as u64,
+                    status: outcome.status,
+                });
                 emit_parallel_completion_events(
                     &event_sink,
                     &mut event_sequence_no,
@@ -695,6 +735,13 @@ impl PipelineRunner {
                     &mut storage,
                 )
                 .await?;
+                emit_notification_event(
+                    &event_sink,
+                    &mut event_sequence_no,
+                    &active_run_id,
+                    node,
+                    &outcome,
+                );
 
                 completed_nodes.push(node.id.clone());
                 node_outcomes.insert(node.id.clone(), outcome.clone());
@@ -707,7 +754,11 @@ impl PipelineRunner {
                         "outcome": outcome.status.as_str(),
                         "notes": outcome.notes,
                         "failure_reason": outcome.failure_reason,
-                        "context_updates": outcome.context_updates,
+                        "context_updates": sanitize_context_for_persistence(
+                            &outcome.context_updates,
+                            &config.redact_keys,
+                            config.context_value_max_bytes,
+                        ),
                         "preferred_next_label": outcome.preferred_label,
                         "suggested_next_ids": outcome.suggested_next_ids,
                     });
@@ -724,12 +775,44 @@ impl PipelineRunner {
                 )?;
                 apply_outcome_to_context(&context_store, &outcome)?;
 
-                let route_decision = decide_route_after_outcome(
-                    graph,
-                    node,
-                    &outcome,
-                    &context_store.snapshot()?.values,
-                );
+                let route_context = context_store.snapshot()?.values;
+                let mut route_decision =
+                    decide_route_after_outcome(graph, node, &outcome, &route_context);
+                if outcome.status != NodeStatus::Fail {
+                    for (edge, error) in
+                        condition_evaluation_errors(graph, &node.id, &outcome, &route_context)
+                    {
+                        emit_runtime_event(
+                            &event_sink,
+                            &mut event_sequence_no,
+                            RuntimeEventKind::Pipeline(PipelineEvent::Warning {
+                                run_id: active_run_id.clone(),
+                                graph_id: graph.id.clone(),
+                                lineage_attempt,
+                                message: format!(
+                                    "condition '{}' on edge {} -> {} treated as false: {}",
+                                    edge.attrs.get_str("condition").unwrap_or_default(),
+                                    edge.from,
+                                    edge.to,
+                                    error
+                                ),
+                            }),
+                        );
+                    }
+                }
+                // While replaying `resume=always` nodes, a successful hop goes
+                // straight to the next replayed node (or the checkpoint's next
+                // node), whatever edge the replayed outcome selected, so the
+                // resume lands where the checkpoint left off. A failed replay
+                // abandons the replay and takes normal failure routing.
+                if outcome.status == NodeStatus::Fail {
+                    resume_replay.clear();
+                } else if let Some(node_id) = resume_replay.pop_front() {
+                    route_decision = RouteDecision::Next {
+                        node_id,
+                        loop_restart: false,
+                    };
+                }
                 let checkpoint_terminal_status = match &route_decision {
                     RouteDecision::TerminateSuccess => Some("success".to_string()),
                     RouteDecision::TerminateFail(_) => Some("fail".to_string()),
@@ -746,6 +829,11 @@ impl PipelineRunner {
                 let route_stage_attempt_id = stage_attempt_id(node, attempts_used);
                 if let Some(path) = checkpoint_path.as_ref() {
                     let context_snapshot = context_store.snapshot()?;
+                    let (context_values, sanitized_context_keys) = sanitize_context_with_keys(
+                        &context_snapshot.values,
+                        &config.redact_keys,
+                        config.context_value_max_bytes,
+                    );
                     let checkpoint = CheckpointState {
                         metadata: CheckpointMetadata {
                             schema_version: 1,
@@ -753,6 +841,7 @@ impl PipelineRunner {
                             checkpoint_id: format!("cp-{}", completed_nodes.len()),
                             sequence_no: completed_nodes.len() as u64,
                             timestamp: timestamp_now(),
+                            sanitized_context_keys,
                         },
                         current_node: node.id.clone(),
                         next_node: checkpoint_next_node.clone(),
@@ -767,7 +856,7 @@ impl PipelineRunner {
                                 )
                             })
                             .collect(),
-                        context_values: context_snapshot.values.clone(),
+                        context_values,
                         logs: context_snapshot.logs,
                         current_node_fidelity: Some(effective_fidelity.clone()),
                         terminal_status: checkpoint_terminal_status.clone(),
@@ -830,6 +919,10 @@ impl PipelineRunner {
                     resume_fidelity_degrade_pending = false;
                     apply_resume_fidelity_override(&context_store, false)?;
                 }
+                if resume_agent_reuse_pending {
+                    resume_agent_reuse_pending = false;
+                    apply_resume_agent_reuse_markers(&context_store, None)?;
+                }
 
                 match route_decision {
                     RouteDecision::Next {
@@ -841,9 +934,44 @@ impl PipelineRunner {
                             break;
                         }
                         current_node_id = node_id;
+                        if config
+                            .abort
+                            .as_ref()
+                            .is_some_and(PipelineAbortHandle::is_abort_requested)
+                        {
+                            aborted = true;
+                            break;
+                        }
                     }
                     RouteDecision::TerminateSuccess => break,
                     RouteDecision::TerminateFail(reason) => {
+                        if let Some(writer) = config.dead_letter_writer.as_ref() {
+                            let record = DeadLetterRecord {
+                                run_id: active_run_id.clone(),
+                                graph_id: graph.id.clone(),
+                                node_id: node.id.clone(),
+                                terminal_reason: reason.clone(),
+                                attempts: attempt_failures,
+                                final_context: sanitize_context_for_persistence(
+                                    &context_store.snapshot()?.values,
+                                    &config.redact_keys,
+                                    config.context_value_max_bytes,
+                                ),
+                                recorded_at: timestamp_now(),
+                            };
+                            if let Err(error) = writer.write_dead_letter(&record).await {
+                                emit_runtime_event(
+                                    &event_sink,
+                                    &mut event_sequence_no,
+                                    RuntimeEventKind::Pipeline(PipelineEvent::Warning {
+                                        run_id: active_run_id.clone(),
+                                        graph_id: graph.id.clone(),
+                                        lineage_attempt,
+                                        message: format!("dead-letter write failed: {error}"),
+                                    }),
+                                );
+                            }
+                        }
                         terminal_failure = Some(reason);
                         break;
                     }
@@ -852,6 +980,8 @@ impl PipelineRunner {
 
             let status = if restart_target.is_some() {
                 PipelineStatus::Success
+            } else if aborted {
+                PipelineStatus::Aborted
             } else {
                 forced_terminal_status.unwrap_or_else(|| {
                     if terminal_failure.is_some() {
@@ -874,6 +1004,7 @@ impl PipelineRunner {
                         match status {
                             PipelineStatus::Success => "success".to_string(),
                             PipelineStatus::Fail => "fail".to_string(),
+                            PipelineStatus::Aborted => "aborted".to_string(),
                         }
                     }),
                     terminal_failure.clone(),
@@ -905,6 +1036,15 @@ impl PipelineRunner {
                         reason: reason.clone(),
                     }),
                 ),
+                (None, PipelineStatus::Aborted, _) => emit_runtime_event(
+                    &event_sink,
+                    &mut event_sequence_no,
+                    RuntimeEventKind::Pipeline(PipelineEvent::Aborted {
+                        run_id: active_run_id.clone(),
+                        graph_id: graph.id.clone(),
+                        lineage_attempt,
+                    }),
+                ),
                 (None, PipelineStatus::Fail, None) => emit_runtime_event(
                     &event_sink,
                     &mut event_sequence_no,
@@ -938,6 +1078,7 @@ impl PipelineRunner {
                 completed_nodes,
                 node_outcomes,
                 context: context_store.snapshot()?.values,
+                node_timings,
             });
         }
     }
@@ -1163,7 +1304,8 @@ async fn execute_with_retry(
     run_id: &str,
     event_sink: &RuntimeEventSink,
     event_sequence_no: &mut u64,
-) -> Result<(NodeOutcome, u32), AttractorError> {
+) -> Result<(NodeOutcome, u32, Vec<DeadLetterAttempt>), AttractorError> {
+    let mut attempt_failures = Vec::new();
     for attempt in 1..=retry_policy.max_attempts {
         let stage_attempt_id = stage_attempt_id(node, attempt);
         let mut attempt_context = context.clone();
@@ -1293,8 +1435,15 @@ async fn execute_with_retry(
         }
 
         if outcome.status.is_success_like() {
-            return Ok((outcome, attempt));
+            return Ok((outcome, attempt, attempt_failures));
         }
+        attempt_failures.push(DeadLetterAttempt {
+            attempt,
+            stage_attempt_id: stage_attempt_id.clone(),
+            status: outcome.status.as_str().to_string(),
+            failure_reason: outcome.failure_reason.clone(),
+            notes: outcome.notes.clone(),
+        });
 
         if will_retry {
             let delay_ms = delay_for_attempt_ms(
@@ -1334,15 +1483,16 @@ async fn execute_with_retry(
         }
 
         if outcome.status == NodeStatus::Retry && attempt >= retry_policy.max_attempts {
-            return Ok((finalize_retry_exhausted(node), attempt));
+            return Ok((finalize_retry_exhausted(node), attempt, attempt_failures));
         }
 
-        return Ok((outcome, attempt));
+        return Ok((outcome, attempt, attempt_failures));
     }
 
     Ok((
         NodeOutcome::failure("max retries exceeded"),
         retry_policy.max_attempts,
+        attempt_failures,
     ))
 }
 
@@ -1619,6 +1769,47 @@ async fn emit_interview_completion_event(
     Ok(())
 }
 
+fn emit_notification_event(
+    sink: &RuntimeEventSink,
+    sequence_no: &mut u64,
+    run_id: &str,
+    node: &Node,
+    outcome: &NodeOutcome,
+) {
+    if infer_node_handler_type(node) != "notify" {
+        return;
+    }
+    let updates = &outcome.context_updates;
+    let Some(url) = updates.get("notify.url").and_then(Value::as_str) else {
+        return;
+    };
+    emit_runtime_event(
+        sink,
+        sequence_no,
+        RuntimeEventKind::Stage(StageEvent::Notification {
+            run_id: run_id.to_string(),
+            node_id: node.id.clone(),
+            url: url.to_string(),
+            delivered: updates
+                .get("notify.delivered")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            attempts: updates
+                .get("notify.attempts")
+                .and_then(Value::as_u64)
+                .unwrap_or(0) as u32,
+            status_code: updates
+                .get("notify.status_code")
+                .and_then(Value::as_u64)
+                .map(|code| code as u16),
+            error: updates
+                .get("notify.error")
+                .and_then(Value::as_str)
+                .map(ToOwned::to_owned),
+        }),
+    );
+}
+
 fn is_parallel_node(node: &Node) -> bool {
     infer_node_handler_type(node) == "parallel"
 }
@@ -1638,6 +1829,8 @@ fn infer_node_handler_type(node: &Node) -> &'static str {
                 "parallel" => "parallel",
                 "parallel.fan_in" => "parallel.fan_in",
                 "tool" => "tool",
+                "command" => "command",
+                "notify" => "notify",
                 "stack.manager_loop" => "stack.manager_loop",
                 _ => "codergen",
             };
@@ -2653,6 +2846,47 @@ mod tests {
         }
     }
 
+    struct SecretEmittingExecutor;
+
+    #[async_trait]
+    impl NodeExecutor for SecretEmittingExecutor {
+        async fn execute(
+            &self,
+            node: &Node,
+            _context: &RuntimeContext,
+            _graph: &Graph,
+        ) -> Result<NodeOutcome, AttractorError> {
+            if node.id != "fetch" {
+                return Ok(NodeOutcome::success());
+            }
+            let mut context_updates = RuntimeContext::new();
+            context_updates.insert("api.secret".to_string(), json!("s3cret"));
+            context_updates.insert("fetch.body".to_string(), json!("x".repeat(1000)));
+            Ok(NodeOutcome {
+                status: NodeStatus::Success,
+                context_updates,
+                ..Default::default()
+            })
+        }
+    }
+
+    struct SlowNodeExecutor;
+
+    #[async_trait]
+    impl NodeExecutor for SlowNodeExecutor {
+        async fn execute(
+            &self,
+            node: &Node,
+            _context: &RuntimeContext,
+            _graph: &Graph,
+        ) -> Result<NodeOutcome, AttractorError> {
+            if node.id == "slow" {
+                tokio::time::sleep(Duration::from_millis(40)).await;
+            }
+            Ok(NodeOutcome::success())
+        }
+    }
+
     struct FailThenSuccessExecutor {
         calls: AtomicUsize,
     }
@@ -2886,6 +3120,60 @@ mod tests {
         );
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_condition_on_missing_context_value_expected_warning_and_edge_skipped() {
+        let graph = parse_dot(
+            r#"
+            digraph G {
+                start [shape=Mdiamond]
+                gate
+                scored_path
+                fallback_path
+                exit [shape=Msquare]
+                start -> gate
+                gate -> scored_path [condition="context.score>0.8"]
+                gate -> fallback_path
+                scored_path -> exit
+                fallback_path -> exit
+            }
+            "#,
+        )
+        .expect("graph should parse");
+        let (tx, mut rx) = runtime_event_channel();
+
+        let result = PipelineRunner
+            .run(
+                &graph,
+                RunConfig {
+                    events: RuntimeEventSink::with_sender(tx),
+                    ..RunConfig::default()
+                },
+            )
+            .await
+            .expect("run should succeed");
+
+        assert!(
+            result
+                .completed_nodes
+                .iter()
+                .any(|node| node == "fallback_path")
+        );
+        let mut warnings = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if let RuntimeEventKind::Pipeline(PipelineEvent::Warning { message, .. }) = event.kind {
+                warnings.push(message);
+            }
+        }
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(
+            warnings[0].starts_with(
+                "condition 'context.score>0.8' on edge gate -> scored_path treated as false:"
+            ),
+            "{}",
+            warnings[0]
+        );
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn run_retries_on_retry_status_expected_attempts_and_success() {
         let graph = parse_dot(
@@ -2924,6 +3212,120 @@ mod tests {
         assert_eq!(executor.calls.load(Ordering::SeqCst), 3);
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_multi_node_expected_node_timings_recorded() {
+        let graph = parse_dot(
+            r#"
+            digraph G {
+                start [shape=Mdiamond]
+                fast
+                slow
+                exit [shape=Msquare]
+                start -> fast -> slow -> exit
+            }
+            "#,
+        )
+        .expect("graph should parse");
+
+        let result = PipelineRunner
+            .run(
+                &graph,
+                RunConfig {
+                    executor: Arc::new(SlowNodeExecutor),
+                    ..RunConfig::default()
+                },
+            )
+            .await
+            .expect("run should succeed");
+
+        let timed_nodes: Vec<&str> = result
+            .node_timings
+            .iter()
+            .map(|timing| timing.node_id.as_str())
+            .collect();
+        assert_eq!(timed_nodes, result.completed_nodes);
+        let timing = |id: &str| {
+            result
+                .node_timings
+                .iter()
+                .find(|timing| timing.node_id == id)
+                .expect("node should be timed")
+        };
+        let slow = timing("slow");
+        assert_eq!(slow.attempt, 1);
+        assert_eq!(slow.status, NodeStatus::Success);
+        assert!(!slow.started.is_empty());
+        assert!(
+            (40..10_000).contains(&slow.duration_ms),
+            "slow node took {}ms",
+            slow.duration_ms
+        );
+        assert!(timing("fast").duration_ms < slow.duration_ms);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_redact_and_size_limit_expected_checkpoint_sanitized_context_intact() {
+        let temp = TempDir::new().expect("temp dir should be created");
+        let graph = parse_dot(
+            r#"
+            digraph G {
+                start [shape=Mdiamond]
+                fetch
+                exit [shape=Msquare]
+                start -> fetch -> exit
+            }
+            "#,
+        )
+        .expect("graph should parse");
+
+        let result = PipelineRunner
+            .run(
+                &graph,
+                RunConfig {
+                    executor: Arc::new(SecretEmittingExecutor),
+                    logs_root: Some(temp.path().to_path_buf()),
+                    context_value_max_bytes: Some(64),
+                    redact_keys: vec!["api.secret".to_string()],
+                    ..RunConfig::default()
+                },
+            )
+            .await
+            .expect("run should succeed");
+
+        assert_eq!(result.context.get("api.secret"), Some(&json!("s3cret")));
+        assert_eq!(
+            result.context.get("fetch.body"),
+            Some(&json!("x".repeat(1000)))
+        );
+
+        let checkpoint = CheckpointState::load_from_path(&crate::checkpoint_file_path(temp.path()))
+            .expect("checkpoint should load");
+        assert_eq!(
+            checkpoint.context_values.get("api.secret"),
+            Some(&json!(crate::REDACTED_CONTEXT_VALUE))
+        );
+        let body = checkpoint
+            .context_values
+            .get("fetch.body")
+            .and_then(Value::as_str)
+            .expect("truncated body should be a string");
+        assert!(body.ends_with("...<truncated 936 bytes>"), "{body}");
+        assert_eq!(
+            checkpoint.metadata.sanitized_context_keys,
+            vec!["api.secret", "fetch.body"]
+        );
+
+        let status: Value = serde_json::from_slice(
+            &fs::read(temp.path().join("fetch").join("status.json"))
+                .expect("status.json should exist"),
+        )
+        .expect("status.json should parse");
+        assert_eq!(
+            status["context_updates"]["api.secret"],
+            json!(crate::REDACTED_CONTEXT_VALUE)
+        );
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn run_fail_status_no_retry_expected_pipeline_fail() {
         // Per spec: only RETRY status triggers retry. FAIL goes to failure routing.
@@ -3153,65 +3555,216 @@ mod tests {
         assert!(result.failure_reason.is_some());
     }
 
-    #[derive(Default)]
-    struct RecordingExecutor {
-        calls: Mutex<Vec<(String, RuntimeContext)>>,
+    struct AlwaysFailingWorkExecutor {
+        calls: AtomicUsize,
     }
 
     #[async_trait]
-    impl NodeExecutor for RecordingExecutor {
+    impl NodeExecutor for AlwaysFailingWorkExecutor {
         async fn execute(
             &self,
             node: &Node,
-            context: &RuntimeContext,
+            _context: &RuntimeContext,
             _graph: &Graph,
         ) -> Result<NodeOutcome, AttractorError> {
-            self.calls
-                .lock()
-                .expect("calls mutex should lock")
-                .push((node.id.clone(), context.clone()));
-            Ok(NodeOutcome::success())
+            if node.id != "work" {
+                return Ok(NodeOutcome::success());
+            }
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            let mut context_updates = RuntimeContext::new();
+            context_updates.insert("work.last_call".to_string(), json!(call));
+            Ok(NodeOutcome {
+                status: NodeStatus::Retry,
+                failure_reason: Some(format!("upstream unavailable (call {call})")),
+                context_updates,
+                ..Default::default()
+            })
         }
     }
 
     #[tokio::test(flavor = "current_thread")]
-    async fn run_resume_from_checkpoint_expected_continuation_without_reexecuting_completed_node() {
-        let temp = TempDir::new().expect("temp dir should be created");
+    async fn run_node_exhausts_retries_expected_dead_letter_with_all_attempt_errors() {
         let graph = parse_dot(
             r#"
             digraph G {
                 start [shape=Mdiamond]
-                plan
-                review
+                work [max_retries=2]
                 exit [shape=Msquare]
-                start -> plan -> review -> exit
+                start -> work -> exit
             }
             "#,
         )
         .expect("graph should parse");
-        let checkpoint_path = crate::checkpoint_file_path(temp.path());
-        CheckpointState {
-            metadata: CheckpointMetadata {
-                schema_version: 1,
-                run_id: "G-run".to_string(),
-                checkpoint_id: "cp-2".to_string(),
-                sequence_no: 2,
-                timestamp: "1.000Z".to_string(),
-            },
-            current_node: "plan".to_string(),
-            next_node: Some("review".to_string()),
-            completed_nodes: vec!["start".to_string(), "plan".to_string()],
-            node_retries: BTreeMap::new(),
-            node_outcomes: BTreeMap::from([(
-                "plan".to_string(),
-                CheckpointNodeOutcome {
-                    status: "success".to_string(),
-                    notes: None,
-                    preferred_label: None,
-                    suggested_next_ids: vec![],
+        let temp = TempDir::new().expect("temp dir should be created");
+        let writer = crate::FsDeadLetterWriter::new(temp.path());
+
+        let result = PipelineRunner
+            .run(
+                &graph,
+                RunConfig {
+                    run_id: Some("run-dead-letter".to_string()),
+                    executor: Arc::new(AlwaysFailingWorkExecutor {
+                        calls: AtomicUsize::new(0),
+                    }),
+                    retry_backoff: crate::RetryBackoffConfig {
+                        initial_delay_ms: 0,
+                        backoff_factor: 1.0,
+                        max_delay_ms: 0,
+                        jitter: false,
+                    },
+                    dead_letter_writer: Some(Arc::new(writer.clone())),
+                    ..RunConfig::default()
                 },
-            )]),
-            context_values: BTreeMap::from([
+            )
+            .await
+            .expect("run should complete");
+        assert_eq!(result.status, PipelineStatus::Fail);
+
+        let bytes = std::fs::read(writer.record_path("run-dead-letter", "work"))
+            .expect("dead-letter record should be written");
+        let record: crate::DeadLetterRecord =
+            serde_json::from_slice(&bytes).expect("dead-letter record should decode");
+        assert_eq!(record.run_id, "run-dead-letter");
+        assert_eq!(record.graph_id, "G");
+        assert_eq!(record.node_id, "work");
+        assert_eq!(
+            record
+                .attempts
+                .iter()
+                .map(|attempt| (attempt.attempt, attempt.failure_reason.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, Some("upstream unavailable (call 1)")),
+                (2, Some("upstream unavailable (call 2)")),
+                (3, Some("upstream unavailable (call 3)")),
+            ]
+        );
+        assert_eq!(record.attempts[2].stage_attempt_id, "work:attempt:3");
+        assert_eq!(
+            record.final_context.get("internal.retry_count.work"),
+            Some(&json!(2))
+        );
+        assert_eq!(
+            Some(record.terminal_reason.as_str()),
+            result.failure_reason.as_deref()
+        );
+    }
+
+    struct RejectingDeadLetterWriter;
+
+    #[async_trait]
+    impl crate::DeadLetterWriter for RejectingDeadLetterWriter {
+        async fn write_dead_letter(
+            &self,
+            _record: &crate::DeadLetterRecord,
+        ) -> Result<(), AttractorError> {
+            Err(AttractorError::Runtime("disk full".to_string()))
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_dead_letter_write_failure_expected_warning_and_fail_status() {
+        let graph = parse_dot(
+            r#"
+            digraph G {
+                start [shape=Mdiamond]
+                work
+                exit [shape=Msquare]
+                start -> work -> exit
+            }
+            "#,
+        )
+        .expect("graph should parse");
+        let (tx, mut rx) = runtime_event_channel();
+
+        let result = PipelineRunner
+            .run(
+                &graph,
+                RunConfig {
+                    events: RuntimeEventSink::with_sender(tx),
+                    executor: Arc::new(AlwaysFailingWorkExecutor {
+                        calls: AtomicUsize::new(0),
+                    }),
+                    dead_letter_writer: Some(Arc::new(RejectingDeadLetterWriter)),
+                    ..RunConfig::default()
+                },
+            )
+            .await
+            .expect("a failed dead-letter write should not fail the run");
+        assert_eq!(result.status, PipelineStatus::Fail);
+
+        let mut warnings = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if let RuntimeEventKind::Pipeline(PipelineEvent::Warning { message, .. }) = event.kind {
+                warnings.push(message);
+            }
+        }
+        assert_eq!(
+            warnings,
+            vec!["dead-letter write failed: runtime error: disk full"]
+        );
+    }
+
+    #[derive(Default)]
+    struct RecordingExecutor {
+        calls: Mutex<Vec<(String, RuntimeContext)>>,
+    }
+
+    #[async_trait]
+    impl NodeExecutor for RecordingExecutor {
+        async fn execute(
+            &self,
+            node: &Node,
+            context: &RuntimeContext,
+            _graph: &Graph,
+        ) -> Result<NodeOutcome, AttractorError> {
+            self.calls
+                .lock()
+                .expect("calls mutex should lock")
+                .push((node.id.clone(), context.clone()));
+            Ok(NodeOutcome::success())
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_resume_from_checkpoint_expected_continuation_without_reexecuting_completed_node() {
+        let temp = TempDir::new().expect("temp dir should be created");
+        let graph = parse_dot(
+            r#"
+            digraph G {
+                start [shape=Mdiamond]
+                plan
+                review
+                exit [shape=Msquare]
+                start -> plan -> review -> exit
+            }
+            "#,
+        )
+        .expect("graph should parse");
+        let checkpoint_path = crate::checkpoint_file_path(temp.path());
+        CheckpointState {
+            metadata: CheckpointMetadata {
+                schema_version: 1,
+                run_id: "G-run".to_string(),
+                checkpoint_id: "cp-2".to_string(),
+                sequence_no: 2,
+                timestamp: "1.000Z".to_string(),
+                sanitized_context_keys: Vec::new(),
+            },
+            current_node: "plan".to_string(),
+            next_node: Some("review".to_string()),
+            completed_nodes: vec!["start".to_string(), "plan".to_string()],
+            node_retries: BTreeMap::new(),
+            node_outcomes: BTreeMap::from([(
+                "plan".to_string(),
+                CheckpointNodeOutcome {
+                    status: "success".to_string(),
+                    notes: None,
+                    preferred_label: None,
+                    suggested_next_ids: vec![],
+                },
+            )]),
+            context_values: BTreeMap::from([
                 ("graph.goal".to_string(), json!("ship")),
                 ("outcome".to_string(), json!("success")),
             ]),
@@ -3249,6 +3802,380 @@ mod tests {
         assert!(result.completed_nodes.iter().any(|node| node == "review"));
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_resume_with_resume_always_node_expected_node_reexecuted_others_skipped() {
+        let temp = TempDir::new().expect("temp dir should be created");
+        let graph = parse_dot(
+            r#"
+            digraph G {
+                start [shape=Mdiamond]
+                fetch [resume="always"]
+                plan
+                review
+                exit [shape=Msquare]
+                start -> fetch -> plan -> review -> exit
+            }
+            "#,
+        )
+        .expect("graph should parse");
+        let checkpoint_path = crate::checkpoint_file_path(temp.path());
+        let success = || CheckpointNodeOutcome {
+            status: "success".to_string(),
+            notes: None,
+            preferred_label: None,
+            suggested_next_ids: vec![],
+        };
+        CheckpointState {
+            metadata: CheckpointMetadata {
+                schema_version: 1,
+                run_id: "G-run".to_string(),
+                checkpoint_id: "cp-3".to_string(),
+                sequence_no: 3,
+                timestamp: "1.000Z".to_string(),
+                sanitized_context_keys: Vec::new(),
+            },
+            current_node: "plan".to_string(),
+            next_node: Some("review".to_string()),
+            completed_nodes: vec!["start".to_string(), "fetch".to_string(), "plan".to_string()],
+            node_retries: BTreeMap::new(),
+            node_outcomes: BTreeMap::from([
+                ("fetch".to_string(), success()),
+                ("plan".to_string(), success()),
+            ]),
+            context_values: BTreeMap::from([("outcome".to_string(), json!("success"))]),
+            logs: vec![],
+            current_node_fidelity: Some("compact".to_string()),
+            terminal_status: None,
+            terminal_failure_reason: None,
+            graph_dot_source_hash: None,
+            graph_dot_source_ref: None,
+            graph_snapshot_hash: None,
+            graph_snapshot_ref: None,
+        }
+        .save_to_path(&checkpoint_path)
+        .expect("checkpoint save should succeed");
+
+        let executor = Arc::new(RecordingExecutor::default());
+        let result = PipelineRunner
+            .run(
+                &graph,
+                RunConfig {
+                    executor: executor.clone(),
+                    logs_root: Some(temp.path().to_path_buf()),
+                    resume_from_checkpoint: Some(checkpoint_path),
+                    ..RunConfig::default()
+                },
+            )
+            .await
+            .expect("run should succeed");
+
+        assert_eq!(result.status, PipelineStatus::Success);
+        let calls = executor.calls.lock().expect("calls mutex should lock");
+        let executed: Vec<&str> = calls.iter().map(|call| call.0.as_str()).collect();
+        assert_eq!(executed, vec!["fetch", "review"]);
+        assert_eq!(
+            result.completed_nodes,
+            vec!["start", "plan", "fetch", "review"]
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_resume_replay_through_conditional_edge_expected_checkpoint_next_node() {
+        let temp = TempDir::new().expect("temp dir should be created");
+        let graph = parse_dot(
+            r#"
+            digraph G {
+                start [shape=Mdiamond]
+                fetch [resume="always"]
+                plan
+                exit [shape=Msquare]
+                start -> fetch
+                fetch -> plan [condition="outcome=partial_success"]
+                plan -> exit
+            }
+            "#,
+        )
+        .expect("graph should parse");
+        let checkpoint_path = crate::checkpoint_file_path(temp.path());
+        CheckpointState {
+            metadata: CheckpointMetadata {
+                schema_version: 1,
+                run_id: "G-run".to_string(),
+                checkpoint_id: "cp-2".to_string(),
+                sequence_no: 2,
+                timestamp: "1.000Z".to_string(),
+                sanitized_context_keys: Vec::new(),
+            },
+            current_node: "fetch".to_string(),
+            next_node: Some("plan".to_string()),
+            completed_nodes: vec!["start".to_string(), "fetch".to_string()],
+            node_retries: BTreeMap::new(),
+            node_outcomes: BTreeMap::from([(
+                "fetch".to_string(),
+                CheckpointNodeOutcome {
+                    status: "partial_success".to_string(),
+                    notes: None,
+                    preferred_label: None,
+                    suggested_next_ids: vec![],
+                },
+            )]),
+            context_values: BTreeMap::from([("outcome".to_string(), json!("partial_success"))]),
+            logs: vec![],
+            current_node_fidelity: Some("compact".to_string()),
+            terminal_status: None,
+            terminal_failure_reason: None,
+            graph_dot_source_hash: None,
+            graph_dot_source_ref: None,
+            graph_snapshot_hash: None,
+            graph_snapshot_ref: None,
+        }
+        .save_to_path(&checkpoint_path)
+        .expect("checkpoint save should succeed");
+
+        // The replayed fetch now succeeds, which matches no outgoing edge and
+        // would end the run; the resume still continues at `plan`.
+        let executor = Arc::new(RecordingExecutor::default());
+        let result = PipelineRunner
+            .run(
+                &graph,
+                RunConfig {
+                    executor: executor.clone(),
+                    logs_root: Some(temp.path().to_path_buf()),
+                    resume_from_checkpoint: Some(checkpoint_path),
+                    ..RunConfig::default()
+                },
+            )
+            .await
+            .expect("run should succeed");
+
+        assert_eq!(result.status, PipelineStatus::Success);
+        let calls = executor.calls.lock().expect("calls mutex should lock");
+        let executed: Vec<&str> = calls.iter().map(|call| call.0.as_str()).collect();
+        assert_eq!(executed, vec!["fetch", "plan"]);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_resume_replay_node_fails_expected_failure_routing_not_checkpoint_next_node() {
+        let temp = TempDir::new().expect("temp dir should be created");
+        let graph = parse_dot(
+            r#"
+            digraph G {
+                start [shape=Mdiamond]
+                gate [resume="always"]
+                plan
+                exit [shape=Msquare]
+                start -> gate -> plan -> exit
+            }
+            "#,
+        )
+        .expect("graph should parse");
+        let checkpoint_path = crate::checkpoint_file_path(temp.path());
+        CheckpointState {
+            metadata: CheckpointMetadata {
+                schema_version: 1,
+                run_id: "G-run".to_string(),
+                checkpoint_id: "cp-2".to_string(),
+                sequence_no: 2,
+                timestamp: "1.000Z".to_string(),
+                sanitized_context_keys: Vec::new(),
+            },
+            current_node: "gate".to_string(),
+            next_node: Some("plan".to_string()),
+            completed_nodes: vec!["start".to_string(), "gate".to_string()],
+            node_retries: BTreeMap::new(),
+            node_outcomes: BTreeMap::from([(
+                "gate".to_string(),
+                CheckpointNodeOutcome {
+                    status: "success".to_string(),
+                    notes: None,
+                    preferred_label: None,
+                    suggested_next_ids: vec![],
+                },
+            )]),
+            context_values: BTreeMap::from([("outcome".to_string(), json!("success"))]),
+            logs: vec![],
+            current_node_fidelity: Some("compact".to_string()),
+            terminal_status: None,
+            terminal_failure_reason: None,
+            graph_dot_source_hash: None,
+            graph_dot_source_ref: None,
+            graph_snapshot_hash: None,
+            graph_snapshot_ref: None,
+        }
+        .save_to_path(&checkpoint_path)
+        .expect("checkpoint save should succeed");
+
+        // The replayed gate fails and has no failure route, so the run ends
+        // in failure instead of continuing at `plan`.
+        let result = PipelineRunner
+            .run(
+                &graph,
+                RunConfig {
+                    executor: Arc::new(ConditionFailExecutor),
+                    logs_root: Some(temp.path().to_path_buf()),
+                    resume_from_checkpoint: Some(checkpoint_path),
+                    ..RunConfig::default()
+                },
+            )
+            .await
+            .expect("run should complete");
+
+        assert_eq!(result.status, PipelineStatus::Fail);
+        assert!(!result.completed_nodes.iter().any(|node| node == "plan"));
+    }
+
+    #[derive(Default)]
+    struct OverlapTrackingExecutor {
+        active: AtomicUsize,
+        max_active: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl NodeExecutor for OverlapTrackingExecutor {
+        async fn execute(
+            &self,
+            _node: &Node,
+            _context: &RuntimeContext,
+            _graph: &Graph,
+        ) -> Result<NodeOutcome, AttractorError> {
+            let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_active.fetch_max(active, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.active.fetch_sub(1, Ordering::SeqCst);
+            Ok(NodeOutcome::success())
+        }
+    }
+
+    struct ParallelDispatchExecutor {
+        branches: Arc<OverlapTrackingExecutor>,
+    }
+
+    #[async_trait]
+    impl NodeExecutor for ParallelDispatchExecutor {
+        async fn execute(
+            &self,
+            node: &Node,
+            context: &RuntimeContext,
+            graph: &Graph,
+        ) -> Result<NodeOutcome, AttractorError> {
+            if node.id == "fan" {
+                let handler = crate::handlers::parallel::ParallelHandler::with_executor(
+                    self.branches.clone(),
+                );
+                return crate::handlers::NodeHandler::execute(&handler, node, context, graph).await;
+            }
+            Ok(NodeOutcome::success())
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_max_parallelism_one_expected_branches_do_not_overlap() {
+        let graph = parse_dot(
+            r#"
+            digraph G {
+                start [shape=Mdiamond]
+                fan [shape=component, max_parallel=4]
+                a
+                b
+                c
+                exit [shape=Msquare]
+                start -> fan
+                fan -> a
+                fan -> b
+                fan -> c
+                fan -> exit [condition="outcome=success"]
+            }
+            "#,
+        )
+        .expect("graph should parse");
+        let run = |max_parallelism| {
+            let branches = Arc::new(OverlapTrackingExecutor::default());
+            let config = RunConfig {
+                executor: Arc::new(ParallelDispatchExecutor {
+                    branches: branches.clone(),
+                }),
+                max_parallelism,
+                ..RunConfig::default()
+            };
+            (branches, config)
+        };
+
+        let (branches, config) = run(Some(1));
+        let result = PipelineRunner
+            .run(&graph, config)
+            .await
+            .expect("run should succeed");
+        assert_eq!(result.status, PipelineStatus::Success);
+        assert_eq!(branches.max_active.load(Ordering::SeqCst), 1);
+
+        let (branches, config) = run(None);
+        PipelineRunner
+            .run(&graph, config)
+            .await
+            .expect("run should succeed");
+        assert!(branches.max_active.load(Ordering::SeqCst) > 1);
+    }
+
+    struct AbortingExecutor {
+        abort: PipelineAbortHandle,
+    }
+
+    #[async_trait]
+    impl NodeExecutor for AbortingExecutor {
+        async fn execute(
+            &self,
+            _node: &Node,
+            _context: &RuntimeContext,
+            _graph: &Graph,
+        ) -> Result<NodeOutcome, AttractorError> {
+            self.abort.request_abort();
+            Ok(NodeOutcome::success())
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn run_abort_requested_expected_resumable_checkpoint_after_current_stage() {
+        let temp = TempDir::new().expect("temp dir should be created");
+        let graph = linear_graph();
+        let abort = PipelineAbortHandle::new();
+        let result = PipelineRunner
+            .run(
+                &graph,
+                RunConfig {
+                    executor: Arc::new(AbortingExecutor {
+                        abort: abort.clone(),
+                    }),
+                    logs_root: Some(temp.path().to_path_buf()),
+                    abort: Some(abort),
+                    ..RunConfig::default()
+                },
+            )
+            .await
+            .expect("run should succeed");
+        assert_eq!(result.status, PipelineStatus::Aborted);
+        assert_eq!(result.completed_nodes, vec!["start".to_string()]);
+
+        let checkpoint_path = crate::checkpoint_file_path(temp.path());
+        let checkpoint =
+            CheckpointState::load_from_path(&checkpoint_path).expect("checkpoint should exist");
+        assert_eq!(checkpoint.next_node.as_deref(), Some("plan"));
+        assert_eq!(checkpoint.terminal_status, None);
+
+        let resumed = PipelineRunner
+            .run(
+                &graph,
+                RunConfig {
+                    logs_root: Some(temp.path().to_path_buf()),
+                    resume_from_checkpoint: Some(checkpoint_path),
+                    ..RunConfig::default()
+                },
+            )
+            .await
+            .expect("resume should succeed");
+        assert_eq!(resumed.status, PipelineStatus::Success);
+        assert!(resumed.completed_nodes.iter().any(|node| node == "plan"));
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn run_resume_full_fidelity_expected_degrade_marker_first_hop_only() {
         let temp = TempDir::new().expect("temp dir should be created");
@@ -3273,6 +4200,7 @@ mod tests {
                 checkpoint_id: "cp-2".to_string(),
                 sequence_no: 2,
                 timestamp: "1.000Z".to_string(),
+                sanitized_context_keys: Vec::new(),
             },
             current_node: "review".to_string(),
             next_node: Some("synth".to_string()),
@@ -2,7 +2,14 @@ use crate::storage::AttractorArtifactWriter;
 use crate::{AttractorError, Graph, Node, RuntimeContext, handlers};
 use async_trait::async_trait;
 use forge_cxdb_runtime::{CxdbFsSnapshotPolicy, CxdbTurnId as TurnId};
-use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum NodeStatus {
@@ -99,6 +106,41 @@ pub struct RunConfig {
     pub workspace_root: Option<PathBuf>,
     pub resume_from_checkpoint: Option<PathBuf>,
     pub max_loop_restarts: u32,
+    pub abort: Option<PipelineAbortHandle>,
+    /// Run-wide cap on concurrently executing parallel branches; `Some(1)`
+    /// runs them one at a time. `None` leaves only each node's `max_parallel`.
+    pub max_parallelism: Option<usize>,
+    /// Receives a [`crate::DeadLetterRecord`] when a failed node ends the run.
+    pub dead_letter_writer: Option<Arc<dyn crate::DeadLetterWriter>>,
+    /// Context values whose JSON encoding exceeds this many bytes are
+    /// truncated in checkpoints, `status.json`, and dead-letter records.
+    pub context_value_max_bytes: Option<usize>,
+    /// Context keys (and keys nested under them) written as `"<redacted>"` to
+    /// checkpoints, `status.json`, and dead-letter records. Execution still
+    /// sees the real values; redacted keys are dropped from the context when
+    /// resuming, so stages after a resume must not rely on them.
+    pub redact_keys: Vec<String>,
+}
+
+/// Cooperative stop request for a run. The runner checks it after each
+/// stage's checkpoint is written, so an aborted run can be resumed.
+#[derive(Clone, Debug, Default)]
+pub struct PipelineAbortHandle {
+    requested: Arc<AtomicBool>,
+}
+
+impl PipelineAbortHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn request_abort(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_abort_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -125,6 +167,11 @@ impl Default for RunConfig {
             workspace_root: None,
             resume_from_checkpoint: None,
             max_loop_restarts: 16,
+            abort: None,
+            max_parallelism: None,
+            dead_letter_writer: None,
+            context_value_max_bytes: None,
+            redact_keys: Vec::new(),
         }
     }
 }
@@ -133,6 +180,8 @@ impl Default for RunConfig {
 pub enum PipelineStatus {
     Success,
     Fail,
+    /// Stopped at a stage boundary by a [`PipelineAbortHandle`].
+    Aborted,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -143,4 +192,18 @@ pub struct PipelineRunResult {
     pub completed_nodes: Vec<String>,
     pub node_outcomes: BTreeMap<String, NodeOutcome>,
     pub context: RuntimeContext,
+    /// One entry per node execution in traversal order; nodes restored from a
+    /// resume checkpoint have no timing.
+    pub node_timings: Vec<NodeTiming>,
+}
+
+/// Wall-clock timing of one node execution, covering every retry attempt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeTiming {
+    pub node_id: String,
+    /// Attempts used, including the final one.
+    pub attempt: u32,
+    pub started: String,
+    pub duration_ms: u64,
+    pub status: NodeStatus,
 }
@@ -219,6 +219,15 @@ pub trait AttractorArtifactWriter: Send + Sync {
             "attach_fs is not supported by this artifact writer".to_string(),
         ))
     }
+
+    /// Lets callers skip re-uploading blobs the store already holds, even
+    /// across process restarts.
+    async fn blob_exists(&self, content_hash: &BlobHash) -> Result<bool, StorageError> {
+        let _ = content_hash;
+        Err(StorageError::Unsupported(
+            "blob_exists is not supported by this artifact writer".to_string(),
+        ))
+    }
 }
 
 #[async_trait::async_trait]
@@ -450,6 +459,12 @@ where
             .await
             .map_err(cxdb_error_to_storage)
     }
+
+    async fn blob_exists(&self, content_hash: &BlobHash) -> Result<bool, StorageError> {
+        CxdbRuntimeStore::blob_exists(self, content_hash)
+            .await
+            .map_err(cxdb_error_to_storage)
+    }
 }
 
 async fn append_record_runtime<B, H, R>(
@@ -508,7 +523,10 @@ fn cxdb_error_to_storage(error: CxdbClientError) -> StorageError {
         CxdbClientError::NotFound { resource, id } => StorageError::NotFound { resource, id },
         CxdbClientError::Conflict(message) => StorageError::Conflict(message),
         CxdbClientError::InvalidInput(message) => StorageError::InvalidInput(message),
-        CxdbClientError::Backend(message) => StorageError::Backend(message),
+        CxdbClientError::Unsupported(message) => StorageError::Unsupported(message),
+        CxdbClientError::Backend(message) | CxdbClientError::Unavailable(message) => {
+            StorageError::Backend(message)
+        }
     }
 }
 
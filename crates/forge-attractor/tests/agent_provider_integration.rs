@@ -6,10 +6,14 @@
 
 use async_trait::async_trait;
 use forge_attractor::agent_provider::AgentProviderSubmitter;
-use forge_attractor::forge_agent::{ForgeAgentCodergenAdapter, ForgeAgentSessionBackend};
+use forge_attractor::forge_agent::{
+    AgentSubmitter, ForgeAgentCodergenAdapter, ForgeAgentSessionBackend,
+};
 use forge_attractor::handlers::registry::RegistryNodeExecutor;
 use forge_attractor::{PipelineRunner, PipelineStatus, RunConfig, prepare_pipeline};
-use forge_llm::agent_provider::{AgentProvider, AgentRunOptions, AgentRunResult, ToolActivityRecord};
+use forge_llm::agent_provider::{
+    AgentProvider, AgentRunOptions, AgentRunResult, AgentRunStopReason, ToolActivityRecord,
+};
 use forge_llm::errors::{ErrorInfo, ProviderError, ProviderErrorKind, SDKError};
 use forge_llm::types::Usage;
 use std::path::PathBuf;
@@ -22,6 +26,7 @@ struct MockAgentProvider {
     response: String,
     model: String,
     tool_activity: Vec<ToolActivityRecord>,
+    stop_reason: AgentRunStopReason,
 }
 
 impl MockAgentProvider {
@@ -31,6 +36,7 @@ impl MockAgentProvider {
             response: response.to_string(),
             model: "mock-model-v1".to_string(),
             tool_activity: Vec::new(),
+            stop_reason: AgentRunStopReason::Completed,
         }
     }
 
@@ -40,6 +46,7 @@ impl MockAgentProvider {
             response: response.to_string(),
             model: "mock-model-v1".to_string(),
             tool_activity: tools,
+            stop_reason: AgentRunStopReason::Completed,
         }
     }
 }
@@ -72,6 +79,7 @@ impl AgentProvider for MockAgentProvider {
             provider: self.name.to_string(),
             cost_usd: Some(0.001),
             duration_ms: Some(42),
+            stop_reason: self.stop_reason,
         })
     }
 }
@@ -349,6 +357,7 @@ async fn pipeline_goal_expansion_through_agent_provider_expected_prompt_expanded
                 provider: "echo-prompt".to_string(),
                 cost_usd: None,
                 duration_ms: Some(1),
+                stop_reason: AgentRunStopReason::Completed,
             })
         }
     }
@@ -404,3 +413,20 @@ async fn pipeline_goal_expansion_through_agent_provider_expected_prompt_expanded
         prompt_content
     );
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn submitter_maps_provider_stop_reason_expected_round_limit() {
+    let provider = MockAgentProvider {
+        stop_reason: AgentRunStopReason::RoundLimit,
+        ..MockAgentProvider::simple("Ran out of rounds.")
+    };
+    let mut submitter =
+        AgentProviderSubmitter::new(Arc::new(provider), PathBuf::from("/tmp/test-workspace"));
+
+    let result = submitter
+        .submit_with_result("do the thing".to_string(), Default::default())
+        .await
+        .expect("submit should succeed");
+
+    assert_eq!(result.stopped_reason, forge_agent::StopReason::RoundLimit);
+}
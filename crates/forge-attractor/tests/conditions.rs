@@ -90,3 +90,69 @@ fn condition_routing_by_expression_expected_matching_edge_selected() {
     .expect("edge expected");
     assert_eq!(selected.to, "pass");
 }
+
+#[test]
+fn condition_routing_by_nested_json_score_expected_threshold_edge_selected() {
+    let graph = parse_dot(
+        r#"
+        digraph G {
+            grade
+            accept
+            revise
+            grade -> accept [condition="context.stage.result.score>0.8"]
+            grade -> revise [condition="context.stage.result.score<=0.8"]
+        }
+        "#,
+    )
+    .expect("graph should parse");
+
+    let mut context = RuntimeContext::new();
+    context.insert(
+        "stage".to_string(),
+        serde_json::json!({"result": {"score": 0.92, "notes": ["tight"]}}),
+    );
+    let selected = select_next_edge(
+        &graph,
+        "grade",
+        &outcome(NodeStatus::Success, None),
+        &context,
+    )
+    .expect("edge expected");
+    assert_eq!(selected.to, "accept");
+
+    context.insert(
+        "stage".to_string(),
+        Value::String(r#"{"result": {"score": 0.4}}"#.to_string()),
+    );
+    let selected = select_next_edge(
+        &graph,
+        "grade",
+        &outcome(NodeStatus::Success, None),
+        &context,
+    )
+    .expect("edge expected");
+    assert_eq!(selected.to, "revise");
+}
+
+#[test]
+fn condition_evaluate_missing_json_path_expected_empty_string() {
+    let mut context = RuntimeContext::new();
+    context.insert(
+        "stage".to_string(),
+        serde_json::json!({"result": {"score": 0.92}}),
+    );
+    let matched = evaluate_condition_expression(
+        "context.stage.result.grade=A",
+        &outcome(NodeStatus::Success, None),
+        &context,
+    )
+    .expect("missing path should evaluate");
+    assert!(!matched);
+    let matched = evaluate_condition_expression(
+        "context.stage.result.grade!=A",
+        &outcome(NodeStatus::Success, None),
+        &context,
+    )
+    .expect("missing path should evaluate");
+    assert!(matched);
+}
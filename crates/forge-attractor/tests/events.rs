@@ -130,3 +130,142 @@ async fn event_payload_shape_expected_category_and_kind_tags() {
         Some("started")
     );
 }
+
+/// Accepts one request per entry in `statuses`, answers with that status, and
+/// forwards each request body to the returned channel.
+fn spawn_webhook_server(statuses: Vec<u16>) -> (String, std::sync::mpsc::Receiver<String>) {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind listener");
+    let address = listener.local_addr().expect("listener addr");
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for status in statuses {
+            let (mut socket, _) = listener.accept().expect("accept");
+            let mut reader = BufReader::new(socket.try_clone().expect("clone socket"));
+            let mut content_length = 0usize;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).expect("read header");
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':')
+                    && name.eq_ignore_ascii_case("content-length")
+                {
+                    content_length = value.trim().parse().expect("content length");
+                }
+            }
+            let mut body = vec![0_u8; content_length];
+            reader.read_exact(&mut body).expect("read body");
+            tx.send(String::from_utf8_lossy(&body).to_string())
+                .expect("send body");
+            let response =
+                format!("HTTP/1.1 {status} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+            socket
+                .write_all(response.as_bytes())
+                .expect("write response");
+        }
+    });
+    (format!("http://{address}/hook"), rx)
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn notify_node_expected_webhook_body_and_stage_notification_event() {
+    let (url, bodies) = spawn_webhook_server(vec![500, 200]);
+    let graph = parse_dot(&format!(
+        r#"
+        digraph release {{
+            start [shape=Mdiamond]
+            build [shape=box, prompt="build it"]
+            announce [type="notify", notify_url="{url}", notify_message="release ready", timeout="2s"]
+            exit [shape=Msquare]
+            start -> build -> announce -> exit
+        }}
+        "#
+    ))
+    .expect("graph should parse");
+
+    let (tx, mut rx) = runtime_event_channel();
+    let result = PipelineRunner
+        .run(
+            &graph,
+            RunConfig {
+                run_id: Some("run-notify".to_string()),
+                events: forge_attractor::RuntimeEventSink::with_sender(tx),
+                ..RunConfig::default()
+            },
+        )
+        .await
+        .expect("run should succeed");
+    assert_eq!(result.status, forge_attractor::PipelineStatus::Success);
+
+    let first: serde_json::Value =
+        serde_json::from_str(&bodies.recv().expect("first attempt")).expect("json body");
+    let second: serde_json::Value =
+        serde_json::from_str(&bodies.recv().expect("retry attempt")).expect("json body");
+    assert_eq!(first, second);
+    assert_eq!(second["run_id"], "run-notify");
+    assert_eq!(second["graph_id"], "release");
+    assert_eq!(second["node_id"], "announce");
+    assert_eq!(second["status"], "success");
+    assert_eq!(second["last_stage"], "build");
+    assert_eq!(second["message"], "release ready");
+    assert_eq!(second["correlation"]["root_run_id"], "run-notify");
+    assert_eq!(second["correlation"]["lineage_attempt"], 1);
+
+    let mut notification = None;
+    while let Ok(event) = rx.try_recv() {
+        if let RuntimeEventKind::Stage(event @ StageEvent::Notification { .. }) = event.kind {
+            notification = Some(event);
+        }
+    }
+    assert_eq!(
+        notification,
+        Some(StageEvent::Notification {
+            run_id: "run-notify".to_string(),
+            node_id: "announce".to_string(),
+            url,
+            delivered: true,
+            attempts: 2,
+            status_code: Some(200),
+            error: None,
+        })
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn notify_node_unreachable_expected_non_fatal_unless_required() {
+    let graph_for = |required: bool| {
+        parse_dot(&format!(
+            r#"
+            digraph G {{
+                start [shape=Mdiamond]
+                announce [type="notify", notify_url="http://127.0.0.1:9/hook", notify_max_attempts=1, notify_required={required}]
+                exit [shape=Msquare]
+                start -> announce -> exit
+            }}
+            "#
+        ))
+        .expect("graph should parse")
+    };
+
+    let result = PipelineRunner
+        .run(&graph_for(false), RunConfig::default())
+        .await
+        .expect("run should succeed");
+    assert_eq!(result.status, forge_attractor::PipelineStatus::Success);
+    assert_eq!(
+        result.node_outcomes["announce"]
+            .context_updates
+            .get("notify.delivered"),
+        Some(&serde_json::Value::Bool(false))
+    );
+
+    let result = PipelineRunner
+        .run(&graph_for(true), RunConfig::default())
+        .await
+        .expect("run should complete");
+    assert_eq!(result.status, forge_attractor::PipelineStatus::Fail);
+}
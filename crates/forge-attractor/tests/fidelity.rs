@@ -87,6 +87,7 @@ async fn fidelity_degrade_on_resume_expected_first_hop_override_then_clear() {
             checkpoint_id: "cp-7".to_string(),
             sequence_no: 7,
             timestamp: "1.000Z".to_string(),
+            sanitized_context_keys: Vec::new(),
         },
         current_node: "review".to_string(),
         next_node: Some("synth".to_string()),
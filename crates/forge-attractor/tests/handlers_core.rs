@@ -205,3 +205,109 @@ async fn codergen_backend_outcome_passthrough_expected_fail_status() {
         .expect("execute");
     assert_eq!(outcome.status, NodeStatus::Fail);
 }
+
+#[tokio::test(flavor = "current_thread")]
+async fn codergen_output_key_expected_downstream_condition_routes_on_captured_value() {
+    struct VerdictBackend {
+        seen: std::sync::Mutex<Vec<String>>,
+    }
+    #[async_trait]
+    impl CodergenBackend for VerdictBackend {
+        async fn run(
+            &self,
+            node: &forge_attractor::Node,
+            _prompt: &str,
+            _context: &RuntimeContext,
+            _graph: &forge_attractor::Graph,
+        ) -> Result<CodergenBackendResult, forge_attractor::AttractorError> {
+            self.seen.lock().expect("lock").push(node.id.clone());
+            let reply = if node.id == "review" { "approve" } else { "ok" };
+            Ok(CodergenBackendResult::Text(reply.to_string()))
+        }
+    }
+
+    let graph = parse_dot(
+        r#"
+        digraph G {
+            start [shape=Mdiamond]
+            review [shape=box, output_key="review.verdict"]
+            gate [shape=diamond]
+            ship [shape=box]
+            rework [shape=box]
+            exit [shape=Msquare]
+            start -> review -> gate
+            gate -> ship [condition="context.review.verdict=approve"]
+            gate -> rework [condition="context.review.verdict!=approve"]
+            ship -> exit
+            rework -> exit
+        }
+        "#,
+    )
+    .expect("graph should parse");
+    let backend = Arc::new(VerdictBackend {
+        seen: std::sync::Mutex::new(Vec::new()),
+    });
+    let registry =
+        forge_attractor::handlers::core_registry_with_codergen_backend(Some(backend.clone()));
+
+    let result = PipelineRunner
+        .run(
+            &graph,
+            RunConfig {
+                executor: Arc::new(
+                    forge_attractor::handlers::registry::RegistryNodeExecutor::new(registry),
+                ),
+                ..RunConfig::default()
+            },
+        )
+        .await
+        .expect("run should succeed");
+
+    assert_eq!(result.status, forge_attractor::PipelineStatus::Success);
+    assert_eq!(
+        result.context.get("review.verdict"),
+        Some(&serde_json::Value::String("approve".to_string()))
+    );
+    assert_eq!(
+        backend.seen.lock().expect("lock").as_slice(),
+        ["review".to_string(), "ship".to_string()]
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn command_node_nonzero_exit_expected_routes_to_error_branch() {
+    let graph = parse_dot(
+        r#"
+        digraph G {
+            start [shape=Mdiamond]
+            lint [type="command", command="echo lint failed >&2; exit 3"]
+            publish [type="command", command="true"]
+            report [type="command", command="true"]
+            exit [shape=Msquare]
+            start -> lint
+            lint -> publish [condition="outcome=success"]
+            lint -> report [condition="outcome=fail"]
+            publish -> exit
+            report -> exit
+        }
+        "#,
+    )
+    .expect("graph should parse");
+
+    let result = PipelineRunner
+        .run(&graph, RunConfig::default())
+        .await
+        .expect("run should succeed");
+
+    assert_eq!(result.status, forge_attractor::PipelineStatus::Success);
+    assert_eq!(result.node_outcomes["lint"].status, NodeStatus::Fail);
+    assert!(result.completed_nodes.iter().any(|n| n == "report"));
+    assert!(!result.completed_nodes.iter().any(|n| n == "publish"));
+    assert_eq!(
+        result.node_outcomes["lint"]
+            .context_updates
+            .get("command.stderr")
+            .and_then(|value| value.as_str()),
+        Some("lint failed\n")
+    );
+}
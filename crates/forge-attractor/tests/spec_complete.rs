@@ -1453,6 +1453,7 @@ mod section_5_state {
                 checkpoint_id: "cp-1".to_string(),
                 sequence_no: 5,
                 timestamp: "123.000Z".to_string(),
+                sanitized_context_keys: Vec::new(),
             },
             current_node: "plan".to_string(),
             next_node: Some("review".to_string()),
@@ -1515,6 +1516,7 @@ mod section_5_state {
                 checkpoint_id: "c".to_string(),
                 sequence_no: 1,
                 timestamp: "t".to_string(),
+                sanitized_context_keys: Vec::new(),
             },
             current_node: "exit".to_string(),
             next_node: None,
@@ -1546,6 +1548,7 @@ mod section_5_state {
                 checkpoint_id: "c".to_string(),
                 sequence_no: 1,
                 timestamp: "t".to_string(),
+                sanitized_context_keys: Vec::new(),
             },
             current_node: "plan".to_string(),
             next_node: None,
@@ -144,6 +144,7 @@ async fn checkpoint_roundtrip_and_resume_parity_memory_and_fs_expected_determini
                 checkpoint_id: "cp-manual".to_string(),
                 sequence_no: 2,
                 timestamp: "1.000Z".to_string(),
+                sanitized_context_keys: Vec::new(),
             },
             current_node: "plan".to_string(),
             next_node: Some("review".to_string()),
@@ -1,3 +1,5 @@
+mod watch;
+
 use clap::{ArgAction, Parser, Subcommand, ValueEnum};
 use forge_agent::{
     AnthropicProviderProfile, CxdbPersistenceMode as AgentCxdbPersistenceMode,
@@ -10,13 +12,13 @@ use forge_attractor::handlers::wait_human::{
     AutoApproveInterviewer, ConsoleInterviewer, HumanAnswer, QueueInterviewer, WaitHumanHandler,
 };
 use forge_attractor::{
-    CheckpointState, CxdbPersistenceMode as AttractorCxdbPersistenceMode, PipelineRunResult,
-    PipelineRunner, PipelineStatus, RunConfig, RuntimeEvent, RuntimeEventKind, RuntimeEventSink,
-    prepare_pipeline, runtime_event_channel,
+    CheckpointState, CxdbPersistenceMode as AttractorCxdbPersistenceMode, PipelineAbortHandle,
+    PipelineRunResult, PipelineRunner, PipelineStatus, RunConfig, RuntimeEvent, RuntimeEventKind,
+    RuntimeEventSink, checkpoint_path_for_run, prepare_pipeline, runtime_event_channel,
 };
 use forge_cxdb_runtime::{
-    CxdbBinaryClient, CxdbHttpClient, CxdbReqwestHttpClient, CxdbSdkBinaryClient,
-    DEFAULT_CXDB_BINARY_ADDR, DEFAULT_CXDB_HTTP_BASE_URL,
+    CxdbBinaryClient, CxdbFileAttachJournal, CxdbHttpClient, CxdbReqwestHttpClient,
+    CxdbRuntimeStore, CxdbSdkBinaryClient, DEFAULT_CXDB_BINARY_ADDR, DEFAULT_CXDB_HTTP_BASE_URL,
 };
 use forge_llm::Client;
 use forge_llm::agent_provider::AgentProvider;
@@ -41,6 +43,9 @@ enum Commands {
     Run(RunArgs),
     Resume(ResumeArgs),
     InspectCheckpoint(InspectCheckpointArgs),
+    DiffCheckpoints(DiffCheckpointsArgs),
+    /// Revalidate a DOT file on every save without executing nodes.
+    Watch(WatchArgs),
 }
 
 #[derive(clap::Args, Debug)]
@@ -57,6 +62,10 @@ struct RunArgs {
     no_stream_events: bool,
     #[arg(long, action = ArgAction::SetTrue)]
     event_json: bool,
+    #[arg(long = "event-kinds", value_enum, value_delimiter = ',')]
+    event_kinds: Vec<EventCategory>,
+    #[arg(long = "max-parallel", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    max_parallel: Option<usize>,
     #[arg(long, value_enum, default_value_t = InterviewerMode::Auto)]
     interviewer: InterviewerMode,
     #[arg(long, value_enum, default_value_t = BackendMode::Agent)]
@@ -81,6 +90,10 @@ struct ResumeArgs {
     no_stream_events: bool,
     #[arg(long, action = ArgAction::SetTrue)]
     event_json: bool,
+    #[arg(long = "event-kinds", value_enum, value_delimiter = ',')]
+    event_kinds: Vec<EventCategory>,
+    #[arg(long = "max-parallel", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    max_parallel: Option<usize>,
     #[arg(long, value_enum, default_value_t = InterviewerMode::Auto)]
     interviewer: InterviewerMode,
     #[arg(long, value_enum, default_value_t = BackendMode::Agent)]
@@ -97,6 +110,35 @@ struct InspectCheckpointArgs {
     json: bool,
 }
 
+#[derive(clap::Args, Debug)]
+struct DiffCheckpointsArgs {
+    #[arg(long)]
+    a: PathBuf,
+    #[arg(long)]
+    b: PathBuf,
+    #[arg(long, action = ArgAction::SetTrue)]
+    json: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct WatchArgs {
+    #[arg(long)]
+    dot_file: PathBuf,
+    #[arg(long = "poll-interval-ms", default_value_t = 250)]
+    poll_interval_ms: u64,
+    #[arg(long = "debounce-ms", default_value_t = 300)]
+    debounce_ms: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum EventCategory {
+    Pipeline,
+    Stage,
+    Parallel,
+    Interview,
+    Checkpoint,
+}
+
 #[derive(Clone, Copy, Debug, ValueEnum)]
 enum InterviewerMode {
     Auto,
@@ -118,6 +160,7 @@ struct CxdbHostConfig {
     persistence: AttractorCxdbPersistenceMode,
     binary_addr: String,
     http_base_url: String,
+    attach_journal_path: PathBuf,
 }
 
 #[tokio::main(flavor = "current_thread")]
@@ -128,6 +171,8 @@ async fn main() -> ExitCode {
         Commands::Run(args) => run_command(args).await,
         Commands::Resume(args) => resume_command(args).await,
         Commands::InspectCheckpoint(args) => inspect_checkpoint_command(args),
+        Commands::DiffCheckpoints(args) => diff_checkpoints_command(args),
+        Commands::Watch(args) => watch_command(args),
     };
 
     match result {
@@ -139,6 +184,15 @@ async fn main() -> ExitCode {
     }
 }
 
+fn watch_command(args: WatchArgs) -> Result<ExitCode, String> {
+    watch::watch_dot_file(
+        &args.dot_file,
+        std::time::Duration::from_millis(args.poll_interval_ms),
+        std::time::Duration::from_millis(args.debounce_ms),
+    )?;
+    Ok(ExitCode::SUCCESS)
+}
+
 fn load_env_files() {
     let _ = dotenvy::from_filename(".env.local");
     let _ = dotenvy::from_filename(".env");
@@ -173,32 +227,57 @@ fn cxdb_host_config_from_env() -> Result<CxdbHostConfig, String> {
             .unwrap_or_else(|| DEFAULT_CXDB_BINARY_ADDR.to_string());
     let http_base_url = first_non_empty_env(&["FORGE_CXDB_HTTP_BASE_URL", "CXDB_HTTP_BASE_URL"])
         .unwrap_or_else(|| DEFAULT_CXDB_HTTP_BASE_URL.to_string());
+    let attach_journal_path = first_non_empty_env(&["FORGE_CXDB_ATTACH_JOURNAL"])
+        .map(PathBuf::from)
+        .unwrap_or_else(default_attach_journal_path);
 
     Ok(CxdbHostConfig {
         persistence,
         binary_addr,
         http_base_url,
+        attach_journal_path,
     })
 }
 
+/// Per-user state location for the attach journal, so concurrent users on
+/// one host never share (or fail to open) each other's journal.
+fn default_attach_journal_path() -> PathBuf {
+    let state_dir = first_non_empty_env(&["XDG_STATE_HOME"])
+        .map(PathBuf::from)
+        .or_else(|| {
+            first_non_empty_env(&["HOME"]).map(|home| Path::new(&home).join(".local/state"))
+        })
+        .unwrap_or_else(|| PathBuf::from(".forge"));
+    state_dir.join("forge").join("cxdb-pending-attaches.json")
+}
+
+fn cxdb_unreachable_message(
+    cxdb: &CxdbHostConfig,
+    what: &str,
+    addr: &str,
+    error: impl std::fmt::Display,
+) -> String {
+    format!(
+        "CXDB {what} failed at '{addr}': {error}\n\n\
+         CXDB is required for pipeline run tracking and playback.\n\
+         To start CXDB:\n\
+           1. Install: see https://github.com/strongdm/cxdb\n\
+           2. Start:   cxdb start\n\
+           3. Verify:  cxdb status\n\n\
+         Default addresses:\n\
+           Binary protocol: {} (set FORGE_CXDB_BINARY_ADDR to override)\n\
+           HTTP API:        {} (set FORGE_CXDB_HTTP_BASE_URL to override)\n\n\
+         To run without persistence (not recommended): FORGE_CXDB_PERSISTENCE=off",
+        cxdb.binary_addr, cxdb.http_base_url
+    )
+}
+
 fn build_cxdb_clients(
     cxdb: &CxdbHostConfig,
 ) -> Result<(Arc<dyn CxdbBinaryClient>, Arc<dyn CxdbHttpClient>), String> {
     let binary: Arc<dyn CxdbBinaryClient> = Arc::new(
         CxdbSdkBinaryClient::connect(&cxdb.binary_addr).map_err(|error| {
-            format!(
-                "CXDB connection failed at '{}': {error}\n\n\
-                 CXDB is required for pipeline run tracking and playback.\n\
-                 To start CXDB:\n\
-                   1. Install: see https://github.com/strongdm/cxdb\n\
-                   2. Start:   cxdb start\n\
-                   3. Verify:  cxdb status\n\n\
-                 Default addresses:\n\
-                   Binary protocol: {} (set FORGE_CXDB_BINARY_ADDR to override)\n\
-                   HTTP API:        {} (set FORGE_CXDB_HTTP_BASE_URL to override)\n\n\
-                 To run without persistence (not recommended): FORGE_CXDB_PERSISTENCE=off",
-                cxdb.binary_addr, cxdb.binary_addr, cxdb.http_base_url
-            )
+            cxdb_unreachable_message(cxdb, "connection", &cxdb.binary_addr, error)
         })?,
     );
     let http: Arc<dyn CxdbHttpClient> =
@@ -206,7 +285,23 @@ fn build_cxdb_clients(
     Ok((binary, http))
 }
 
-fn build_runtime_persistence(
+/// Fails fast when either CXDB endpoint is unreachable, before any run
+/// starts; otherwise a bad HTTP base URL only surfaces mid-run.
+async fn preflight_cxdb(
+    cxdb: &CxdbHostConfig,
+    binary: &dyn CxdbBinaryClient,
+    http: &dyn CxdbHttpClient,
+) -> Result<(), String> {
+    http.health().await.map_err(|error| {
+        cxdb_unreachable_message(cxdb, "HTTP health check", &cxdb.http_base_url, error)
+    })?;
+    binary
+        .ping()
+        .await
+        .map_err(|error| cxdb_unreachable_message(cxdb, "ping", &cxdb.binary_addr, error))
+}
+
+async fn build_runtime_persistence(
     cxdb: &CxdbHostConfig,
 ) -> Result<
     (
@@ -220,11 +315,24 @@ fn build_runtime_persistence(
     }
 
     let (binary, http) = build_cxdb_clients(cxdb)?;
+    preflight_cxdb(cxdb, binary.as_ref(), http.as_ref()).await?;
     let storage = forge_attractor::cxdb_storage_writer(binary.clone(), http.clone());
     let artifacts = forge_attractor::cxdb_artifact_writer(binary, http);
     Ok((Some(storage), Some(artifacts)))
 }
 
+/// Reader the agent backend uses on resume to reuse output the interrupted
+/// run already produced.
+fn build_resume_reader(
+    cxdb: &CxdbHostConfig,
+) -> Result<Option<forge_attractor::SharedAttractorStorageReader>, String> {
+    if cxdb.persistence == AttractorCxdbPersistenceMode::Off {
+        return Ok(None);
+    }
+    let (binary, http) = build_cxdb_clients(cxdb)?;
+    Ok(Some(Arc::new(CxdbRuntimeStore::new(binary, http))))
+}
+
 async fn run_command(args: RunArgs) -> Result<ExitCode, String> {
     let source = load_dot_source(args.dot_file.as_deref(), args.dot_source.as_deref())?;
     let (graph, diagnostics) = prepare_pipeline(&source, &[], &[]).map_err(|error| error.to_string())?;
@@ -232,9 +340,10 @@ async fn run_command(args: RunArgs) -> Result<ExitCode, String> {
         eprintln!("warning: {}", diag.message);
     }
     let cxdb = cxdb_host_config_from_env()?;
-    let (storage, artifacts) = build_runtime_persistence(&cxdb)?;
+    let (storage, artifacts) = build_runtime_persistence(&cxdb).await?;
 
-    let (event_sink, event_task) = event_stream(!args.no_stream_events, args.event_json);
+    let (event_sink, event_task) =
+        event_stream(!args.no_stream_events, args.event_json, args.event_kinds);
 
     let executor = build_executor(
         args.interviewer,
@@ -242,7 +351,10 @@ async fn run_command(args: RunArgs) -> Result<ExitCode, String> {
         args.human_answers,
         &cxdb,
         storage.clone(),
+        None,
     )?;
+    let abort = PipelineAbortHandle::new();
+    let interrupt_task = spawn_interrupt_handler(abort.clone());
     let run_result = PipelineRunner
         .run(
             &graph,
@@ -254,17 +366,21 @@ async fn run_command(args: RunArgs) -> Result<ExitCode, String> {
                 storage,
                 artifacts,
                 cxdb_persistence: cxdb.persistence,
+                abort: Some(abort.clone()),
+                max_parallelism: args.max_parallel,
                 ..RunConfig::default()
             },
         )
         .await
         .map_err(|error| error.to_string())?;
+    interrupt_task.abort();
 
     if let Some(task) = event_task {
         task.await.map_err(|error| error.to_string())?;
     }
 
     print_run_summary(&run_result);
+    print_abort_checkpoint(&run_result, None);
     Ok(exit_code_for_status(run_result.status))
 }
 
@@ -275,9 +391,10 @@ async fn resume_command(args: ResumeArgs) -> Result<ExitCode, String> {
         eprintln!("warning: {}", diag.message);
     }
     let cxdb = cxdb_host_config_from_env()?;
-    let (storage, artifacts) = build_runtime_persistence(&cxdb)?;
+    let (storage, artifacts) = build_runtime_persistence(&cxdb).await?;
 
-    let (event_sink, event_task) = event_stream(!args.no_stream_events, args.event_json);
+    let (event_sink, event_task) =
+        event_stream(!args.no_stream_events, args.event_json, args.event_kinds);
 
     let executor = build_executor(
         args.interviewer,
@@ -285,30 +402,38 @@ async fn resume_command(args: ResumeArgs) -> Result<ExitCode, String> {
         args.human_answers,
         &cxdb,
         storage.clone(),
+        build_resume_reader(&cxdb)?,
     )?;
+    let resume_checkpoint = Some(args.checkpoint);
+    let abort = PipelineAbortHandle::new();
+    let interrupt_task = spawn_interrupt_handler(abort.clone());
     let run_result = PipelineRunner
         .run(
             &graph,
             RunConfig {
                 run_id: args.run_id,
                 logs_root: args.logs_root,
-                resume_from_checkpoint: Some(args.checkpoint),
+                resume_from_checkpoint: resume_checkpoint.clone(),
                 events: event_sink,
                 executor,
                 storage,
                 artifacts,
                 cxdb_persistence: cxdb.persistence,
+                abort: Some(abort.clone()),
+                max_parallelism: args.max_parallel,
                 ..RunConfig::default()
             },
         )
         .await
         .map_err(|error| error.to_string())?;
+    interrupt_task.abort();
 
     if let Some(task) = event_task {
         task.await.map_err(|error| error.to_string())?;
     }
 
     print_run_summary(&run_result);
+    print_abort_checkpoint(&run_result, resume_checkpoint.as_deref());
     Ok(exit_code_for_status(run_result.status))
 }
 
@@ -346,6 +471,97 @@ fn inspect_checkpoint_command(args: InspectCheckpointArgs) -> Result<ExitCode, S
     Ok(ExitCode::SUCCESS)
 }
 
+fn diff_checkpoints_command(args: DiffCheckpointsArgs) -> Result<ExitCode, String> {
+    let a = CheckpointState::load_from_path(&args.a).map_err(|e| e.to_string())?;
+    let b = CheckpointState::load_from_path(&args.b).map_err(|e| e.to_string())?;
+
+    let completed_removed: Vec<&String> = a
+        .completed_nodes
+        .iter()
+        .filter(|node| !b.completed_nodes.contains(node))
+        .collect();
+    let completed_added: Vec<&String> = b
+        .completed_nodes
+        .iter()
+        .filter(|node| !a.completed_nodes.contains(node))
+        .collect();
+    let context_removed: Vec<&String> = a
+        .context_values
+        .keys()
+        .filter(|key| !b.context_values.contains_key(*key))
+        .collect();
+    let context_added: Vec<&String> = b
+        .context_values
+        .keys()
+        .filter(|key| !a.context_values.contains_key(*key))
+        .collect();
+    let context_changed: Vec<&String> = a
+        .context_values
+        .iter()
+        .filter(|(key, value)| {
+            b.context_values
+                .get(*key)
+                .is_some_and(|other| other != *value)
+        })
+        .map(|(key, _)| key)
+        .collect();
+
+    if args.json {
+        let changed_values: serde_json::Map<String, serde_json::Value> = context_changed
+            .iter()
+            .map(|key| {
+                (
+                    (*key).clone(),
+                    serde_json::json!({ "a": a.context_values[*key], "b": b.context_values[*key] }),
+                )
+            })
+            .collect();
+        let diff = serde_json::json!({
+            "a": args.a.display().to_string(),
+            "b": args.b.display().to_string(),
+            "current_node": { "a": a.current_node, "b": b.current_node },
+            "completed_nodes": { "added": completed_added, "removed": completed_removed },
+            "context_values": {
+                "added": context_added,
+                "removed": context_removed,
+                "changed": changed_values,
+            },
+            "terminal_status": { "a": a.terminal_status, "b": b.terminal_status },
+        });
+        let json = serde_json::to_string_pretty(&diff).map_err(|e| e.to_string())?;
+        println!("{json}");
+    } else {
+        println!("a: {}", args.a.display());
+        println!("b: {}", args.b.display());
+        if a.current_node != b.current_node {
+            println!("current_node: {} -> {}", a.current_node, b.current_node);
+        }
+        for node in &completed_added {
+            println!("completed_nodes: + {node}");
+        }
+        for node in &completed_removed {
+            println!("completed_nodes: - {node}");
+        }
+        for key in &context_added {
+            println!("context_values: + {key}");
+        }
+        for key in &context_removed {
+            println!("context_values: - {key}");
+        }
+        for key in &context_changed {
+            println!("context_values: ~ {key}");
+        }
+        if a.terminal_status != b.terminal_status {
+            println!(
+                "terminal_status: {} -> {}",
+                a.terminal_status.as_deref().unwrap_or("<in_progress>"),
+                b.terminal_status.as_deref().unwrap_or("<in_progress>")
+            );
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
 fn load_dot_source(dot_file: Option<&Path>, dot_source: Option<&str>) -> Result<String, String> {
     match (dot_file, dot_source) {
         (Some(_), Some(_)) => Err("provide only one of --dot-file or --dot-source".to_string()),
@@ -359,6 +575,7 @@ fn load_dot_source(dot_file: Option<&Path>, dot_source: Option<&str>) -> Result<
 fn event_stream(
     stream_events: bool,
     event_json: bool,
+    event_kinds: Vec<EventCategory>,
 ) -> (RuntimeEventSink, Option<tokio::task::JoinHandle<()>>) {
     if !stream_events {
         return (RuntimeEventSink::default(), None);
@@ -377,7 +594,12 @@ fn event_stream(
             }
         }
     });
-    (RuntimeEventSink::with_sender(tx), Some(task))
+    let sink = RuntimeEventSink::with_sender(tx);
+    if event_kinds.is_empty() {
+        return (sink, Some(task));
+    }
+    let sink = sink.with_filter(move |event| event_kinds.contains(&event_category(&event.kind)));
+    (sink, Some(task))
 }
 
 fn build_executor(
@@ -386,6 +608,7 @@ fn build_executor(
     human_answers: Vec<String>,
     cxdb: &CxdbHostConfig,
     stage_link_writer: Option<forge_attractor::SharedAttractorStorageWriter>,
+    resume_reader: Option<forge_attractor::SharedAttractorStorageReader>,
 ) -> Result<Arc<dyn forge_attractor::NodeExecutor>, String> {
     let interviewer: Arc<dyn forge_attractor::Interviewer> = match mode {
         InterviewerMode::Auto => {
@@ -404,7 +627,11 @@ fn build_executor(
 
     let codergen_backend = match backend_mode {
         BackendMode::Mock => None,
-        BackendMode::Agent => Some(build_agent_codergen_backend(cxdb, stage_link_writer)?),
+        BackendMode::Agent => Some(build_agent_codergen_backend(
+            cxdb,
+            stage_link_writer,
+            resume_reader,
+        )?),
         BackendMode::ClaudeCode | BackendMode::CodexCli | BackendMode::GeminiCli => {
             Some(build_cli_agent_codergen_backend(backend_mode)?)
         }
@@ -424,6 +651,16 @@ fn print_event_text(event: &RuntimeEvent) {
     );
 }
 
+fn event_category(kind: &RuntimeEventKind) -> EventCategory {
+    match kind {
+        RuntimeEventKind::Pipeline(_) => EventCategory::Pipeline,
+        RuntimeEventKind::Stage(_) => EventCategory::Stage,
+        RuntimeEventKind::Parallel(_) => EventCategory::Parallel,
+        RuntimeEventKind::Interview(_) => EventCategory::Interview,
+        RuntimeEventKind::Checkpoint(_) => EventCategory::Checkpoint,
+    }
+}
+
 fn event_kind_label(kind: &RuntimeEventKind) -> &'static str {
     match kind {
         RuntimeEventKind::Pipeline(_) => "pipeline",
@@ -441,9 +678,20 @@ fn print_run_summary(result: &PipelineRunResult) {
         match result.status {
             PipelineStatus::Success => "success",
             PipelineStatus::Fail => "fail",
+            PipelineStatus::Aborted => "aborted",
         }
     );
     println!("completed_nodes: {}", result.completed_nodes.join(", "));
+    let mut slowest: Vec<_> = result.node_timings.iter().collect();
+    slowest.sort_by_key(|timing| std::cmp::Reverse(timing.duration_ms));
+    if !slowest.is_empty() {
+        let listed: Vec<String> = slowest
+            .iter()
+            .take(3)
+            .map(|timing| format!("{} ({}ms)", timing.node_id, timing.duration_ms))
+            .collect();
+        println!("slowest_nodes: {}", listed.join(", "));
+    }
     if let Some(reason) = result.failure_reason.as_deref() {
         println!("failure_reason: {reason}");
     }
@@ -453,6 +701,41 @@ fn exit_code_for_status(status: PipelineStatus) -> ExitCode {
     match status {
         PipelineStatus::Success => ExitCode::SUCCESS,
         PipelineStatus::Fail => ExitCode::from(2),
+        PipelineStatus::Aborted => ExitCode::from(3),
+    }
+}
+
+/// First Ctrl-C asks the runner to stop after the current stage's checkpoint;
+/// a second one exits immediately.
+fn spawn_interrupt_handler(abort: PipelineAbortHandle) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        abort.request_abort();
+        eprintln!(
+            "interrupt received; stopping after the current stage (Ctrl-C again to exit now)"
+        );
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(130);
+        }
+    })
+}
+
+fn print_abort_checkpoint(result: &PipelineRunResult, resume_checkpoint: Option<&Path>) {
+    if result.status != PipelineStatus::Aborted {
+        return;
+    }
+    let logs_root = result
+        .context
+        .get("runtime.logs_root")
+        .and_then(serde_json::Value::as_str)
+        .map(PathBuf::from);
+    match checkpoint_path_for_run(logs_root.as_deref(), resume_checkpoint) {
+        Some(path) => println!("checkpoint: {}", path.display()),
+        None => {
+            eprintln!("warning: run aborted without a checkpoint; pass --logs-root to resume it")
+        }
     }
 }
 
@@ -463,6 +746,7 @@ fn is_interactive_terminal() -> bool {
 fn build_agent_codergen_backend(
     cxdb: &CxdbHostConfig,
     stage_link_writer: Option<forge_attractor::SharedAttractorStorageWriter>,
+    resume_reader: Option<forge_attractor::SharedAttractorStorageReader>,
 ) -> Result<Arc<dyn forge_attractor::handlers::codergen::CodergenBackend>, String> {
     let provider_profile = select_provider_profile_from_env()?;
     let llm_client =
@@ -471,7 +755,6 @@ fn build_agent_codergen_backend(
         })?);
     let cwd = std::env::current_dir()
         .map_err(|error| format!("failed to resolve current directory for agent env: {error}"))?;
-    let execution_env = Arc::new(LocalExecutionEnvironment::new(cwd));
     let mut session_config = SessionConfig::default();
     session_config.cxdb_persistence = if cxdb.persistence == AttractorCxdbPersistenceMode::Required
     {
@@ -479,16 +762,20 @@ fn build_agent_codergen_backend(
     } else {
         AgentCxdbPersistenceMode::Off
     };
+    let execution_env =
+        Arc::new(LocalExecutionEnvironment::new(cwd).with_session_limits(&session_config));
 
     let session = if cxdb.persistence == AttractorCxdbPersistenceMode::Required {
         let (binary_client, http_client) = build_cxdb_clients(cxdb)?;
-        Session::new_with_cxdb_persistence(
+        let store = CxdbRuntimeStore::new(binary_client, http_client).with_attach_journal(
+            Arc::new(CxdbFileAttachJournal::new(cxdb.attach_journal_path.clone())),
+        );
+        Session::new_with_cxdb_store(
             provider_profile,
             execution_env,
             llm_client,
             session_config,
-            binary_client,
-            http_client,
+            Arc::new(store),
         )
     } else {
         Session::new(provider_profile, execution_env, llm_client, session_config)
@@ -502,6 +789,11 @@ fn build_agent_codergen_backend(
     } else {
         backend
     };
+    let backend = if let Some(reader) = resume_reader {
+        backend.with_resume_reader(reader)
+    } else {
+        backend
+    };
     Ok(Arc::new(backend))
 }
 
@@ -0,0 +1,141 @@
+use forge_attractor::{Diagnostic, Severity, prepare_pipeline};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Outcome of feeding a DOT source to [`DotReparser::reparse`].
+#[derive(Debug, PartialEq)]
+pub(crate) enum ReparseResult {
+    /// Same source as the previous call; nothing was reparsed.
+    Unchanged,
+    /// Parsed and validated; the diagnostics may be empty.
+    Validated(Vec<Diagnostic>),
+    /// The source did not parse or a transform failed.
+    Failed(String),
+}
+
+/// Reparses and validates a DOT source whenever it differs from the last one
+/// seen. Nodes are never executed.
+#[derive(Debug, Default)]
+pub(crate) struct DotReparser {
+    last_source: Option<String>,
+}
+
+impl DotReparser {
+    pub(crate) fn is_current(&self, source: &str) -> bool {
+        self.last_source.as_deref() == Some(source)
+    }
+
+    pub(crate) fn reparse(&mut self, source: &str) -> ReparseResult {
+        if self.is_current(source) {
+            return ReparseResult::Unchanged;
+        }
+        self.last_source = Some(source.to_string());
+        match prepare_pipeline(source, &[], &[]) {
+            Ok((_, diagnostics)) => ReparseResult::Validated(diagnostics),
+            Err(error) => ReparseResult::Failed(error.to_string()),
+        }
+    }
+}
+
+/// Polls `dot_file` and revalidates it once a change has been stable for
+/// `debounce`, so editors that write in several steps trigger one report.
+/// Runs until the process is interrupted.
+pub(crate) fn watch_dot_file(
+    dot_file: &Path,
+    poll_interval: Duration,
+    debounce: Duration,
+) -> Result<(), String> {
+    let mut reparser = DotReparser::default();
+    let source = std::fs::read_to_string(dot_file)
+        .map_err(|error| format!("failed to read {}: {error}", dot_file.display()))?;
+    print_reparse_result(dot_file, &reparser.reparse(&source));
+
+    let mut pending: Option<(String, Instant)> = None;
+    loop {
+        std::thread::sleep(poll_interval);
+        // A save in progress may briefly remove or truncate the file.
+        let Ok(source) = std::fs::read_to_string(dot_file) else {
+            continue;
+        };
+        if reparser.is_current(&source) {
+            pending = None;
+            continue;
+        }
+        match pending.as_ref() {
+            Some((pending_source, since)) if *pending_source == source => {
+                if since.elapsed() >= debounce {
+                    print_reparse_result(dot_file, &reparser.reparse(&source));
+                    pending = None;
+                }
+            }
+            _ => pending = Some((source, Instant::now())),
+        }
+    }
+}
+
+fn print_reparse_result(dot_file: &Path, result: &ReparseResult) {
+    match result {
+        ReparseResult::Unchanged => {}
+        ReparseResult::Validated(diagnostics) if diagnostics.is_empty() => {
+            println!("{}: ok", dot_file.display());
+        }
+        ReparseResult::Validated(diagnostics) => {
+            println!(
+                "{}: {} diagnostic(s)",
+                dot_file.display(),
+                diagnostics.len()
+            );
+            for diag in diagnostics {
+                let severity = match diag.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                    Severity::Info => "info",
+                };
+                println!("  {severity} [{}]: {}", diag.rule, diag.message);
+            }
+        }
+        ReparseResult::Failed(error) => {
+            println!("{}: error: {error}", dot_file.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = r#"
+        digraph G {
+            start [shape=Mdiamond]
+            plan [prompt="plan it"]
+            exit [shape=Msquare]
+            start -> plan -> exit
+        }
+    "#;
+
+    #[test]
+    fn reparse_changed_source_expected_updated_diagnostics() {
+        let mut reparser = DotReparser::default();
+        assert_eq!(reparser.reparse(VALID), ReparseResult::Validated(vec![]));
+        assert_eq!(reparser.reparse(VALID), ReparseResult::Unchanged);
+
+        let broken = VALID.replace("plan -> exit", "plan -> missing");
+        let ReparseResult::Validated(diagnostics) = reparser.reparse(&broken) else {
+            panic!("changed source should be revalidated");
+        };
+        assert!(
+            diagnostics
+                .iter()
+                .any(|diag| diag.severity == Severity::Error && diag.message.contains("missing"))
+        );
+    }
+
+    #[test]
+    fn reparse_unparseable_source_expected_failed() {
+        let mut reparser = DotReparser::default();
+        assert!(matches!(
+            reparser.reparse("digraph G {"),
+            ReparseResult::Failed(_)
+        ));
+    }
+}
@@ -3,6 +3,8 @@ use forge_attractor::{
 };
 use serde_json::Value;
 use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::net::TcpListener;
 use std::path::Path;
 use std::process::Command;
 use tempfile::TempDir;
@@ -38,6 +40,12 @@ fn write_hitl_dot_file(path: &Path) {
 }
 
 fn write_resume_checkpoint(path: &Path) {
+    resume_checkpoint()
+        .save_to_path(path)
+        .expect("checkpoint should save");
+}
+
+fn resume_checkpoint() -> CheckpointState {
     let graph = parse_dot(
         r#"
         digraph G {
@@ -51,13 +59,14 @@ fn write_resume_checkpoint(path: &Path) {
     .expect("graph should parse");
     let start = graph.nodes.get("start").expect("start node should exist");
 
-    let checkpoint = CheckpointState {
+    CheckpointState {
         metadata: CheckpointMetadata {
             schema_version: 1,
             run_id: "G-run".to_string(),
             checkpoint_id: "cp-1".to_string(),
             sequence_no: 1,
             timestamp: "1.000Z".to_string(),
+            sanitized_context_keys: Vec::new(),
         },
         current_node: "start".to_string(),
         next_node: Some("plan".to_string()),
@@ -76,11 +85,7 @@ fn write_resume_checkpoint(path: &Path) {
         graph_dot_source_ref: None,
         graph_snapshot_hash: None,
         graph_snapshot_ref: None,
-    };
-
-    checkpoint
-        .save_to_path(path)
-        .expect("checkpoint should save");
+    }
 }
 
 fn run_cli(args: &[&str], cwd: &Path) -> std::process::Output {
@@ -207,6 +212,173 @@ fn inspect_checkpoint_json_expected_metadata_fields() {
     );
 }
 
+#[test]
+fn run_command_event_kinds_pipeline_expected_only_pipeline_events() {
+    let temp = TempDir::new().expect("tempdir should create");
+    let dot_file = temp.path().join("pipeline.dot");
+    write_dot_file(&dot_file);
+
+    let output = run_cli(
+        &[
+            "run",
+            "--dot-file",
+            dot_file.to_str().expect("dot file path should be utf8"),
+            "--backend",
+            "mock",
+            "--event-json",
+            "--event-kinds",
+            "pipeline",
+            "--interviewer",
+            "auto",
+        ],
+        temp.path(),
+    );
+
+    assert!(
+        output.status.success(),
+        "stdout:\n{}\nstderr:\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    let categories: Vec<String> = stdout
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter_map(|event| {
+            event
+                .get("kind")
+                .and_then(|kind| kind.get("category"))
+                .and_then(Value::as_str)
+                .map(ToOwned::to_owned)
+        })
+        .collect();
+    assert!(!categories.is_empty());
+    assert!(categories.iter().all(|category| category == "pipeline"));
+}
+
+#[test]
+fn run_command_unknown_event_kind_expected_error() {
+    let temp = TempDir::new().expect("tempdir should create");
+    let dot_file = temp.path().join("pipeline.dot");
+    write_dot_file(&dot_file);
+
+    let output = run_cli(
+        &[
+            "run",
+            "--dot-file",
+            dot_file.to_str().expect("dot file path should be utf8"),
+            "--backend",
+            "mock",
+            "--event-kinds",
+            "pipeline,bogus",
+        ],
+        temp.path(),
+    );
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).expect("stderr should be utf8");
+    assert!(stderr.contains("bogus"));
+    assert!(stderr.contains("checkpoint"));
+}
+
+#[test]
+fn run_command_max_parallel_zero_expected_error() {
+    let temp = TempDir::new().expect("tempdir should create");
+    let dot_file = temp.path().join("pipeline.dot");
+    write_dot_file(&dot_file);
+
+    let output = run_cli(
+        &[
+            "run",
+            "--dot-file",
+            dot_file.to_str().expect("dot file path should be utf8"),
+            "--backend",
+            "mock",
+            "--max-parallel",
+            "0",
+        ],
+        temp.path(),
+    );
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).expect("stderr should be utf8");
+    assert!(stderr.contains("--max-parallel"));
+}
+
+#[test]
+fn diff_checkpoints_json_expected_completed_node_and_context_changes() {
+    let temp = TempDir::new().expect("tempdir should create");
+    let a_path = temp.path().join("a.json");
+    let b_path = temp.path().join("b.json");
+    let mut a = resume_checkpoint();
+    a.context_values
+        .insert("stale".to_string(), Value::String("old".to_string()));
+    a.context_values
+        .insert("outcome".to_string(), Value::String("pending".to_string()));
+    a.save_to_path(&a_path).expect("checkpoint a should save");
+    let mut b = resume_checkpoint();
+    b.current_node = "plan".to_string();
+    b.completed_nodes.push("plan".to_string());
+    b.context_values
+        .insert("fresh".to_string(), Value::String("new".to_string()));
+    b.context_values
+        .insert("outcome".to_string(), Value::String("success".to_string()));
+    b.save_to_path(&b_path).expect("checkpoint b should save");
+
+    let output = run_cli(
+        &[
+            "diff-checkpoints",
+            "--a",
+            a_path.to_str().expect("checkpoint path should be utf8"),
+            "--b",
+            b_path.to_str().expect("checkpoint path should be utf8"),
+            "--json",
+        ],
+        temp.path(),
+    );
+
+    assert!(
+        output.status.success(),
+        "stdout:\n{}\nstderr:\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    let value: Value = serde_json::from_str(&stdout).expect("json output should parse");
+    assert_eq!(
+        value["completed_nodes"]["added"],
+        serde_json::json!(["plan"])
+    );
+    assert_eq!(value["completed_nodes"]["removed"], serde_json::json!([]));
+    assert_eq!(value["current_node"]["b"], "plan");
+    assert_eq!(
+        value["context_values"]["added"],
+        serde_json::json!(["fresh"])
+    );
+    assert_eq!(
+        value["context_values"]["removed"],
+        serde_json::json!(["stale"])
+    );
+    assert_eq!(
+        value["context_values"]["changed"]["outcome"],
+        serde_json::json!({ "a": "pending", "b": "success" })
+    );
+
+    let text = run_cli(
+        &[
+            "diff-checkpoints",
+            "--a",
+            a_path.to_str().expect("checkpoint path should be utf8"),
+            "--b",
+            b_path.to_str().expect("checkpoint path should be utf8"),
+        ],
+        temp.path(),
+    );
+    let stdout = String::from_utf8(text.stdout).expect("stdout should be utf8");
+    assert!(stdout.contains("completed_nodes: + plan"));
+    assert!(stdout.contains("context_values: - stale"));
+}
+
 #[test]
 fn run_command_queue_interviewer_expected_human_answer_branch_selected() {
     let temp = TempDir::new().expect("tempdir should create");
@@ -240,3 +412,84 @@ fn run_command_queue_interviewer_expected_human_answer_branch_selected() {
     assert!(stdout.contains("status: success"));
     assert!(stdout.contains("completed_nodes: start, gate, no"));
 }
+
+/// Accepts one binary-protocol connection and answers only the HELLO
+/// handshake, so the CLI can connect before its preflight runs.
+fn spawn_hello_only_cxdb() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("listener should bind");
+    let addr = listener
+        .local_addr()
+        .expect("listener should have addr")
+        .to_string();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("cli should connect");
+        let mut header = [0u8; 16];
+        stream
+            .read_exact(&mut header)
+            .expect("hello header should arrive");
+        let len = u32::from_le_bytes(header[0..4].try_into().expect("length bytes")) as usize;
+        let mut payload = vec![0u8; len];
+        stream
+            .read_exact(&mut payload)
+            .expect("hello payload should arrive");
+
+        let mut response = Vec::new();
+        response.extend_from_slice(&1u64.to_le_bytes());
+        response.extend_from_slice(&1u16.to_le_bytes());
+        let mut frame = Vec::new();
+        frame.extend_from_slice(&(response.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&1u16.to_le_bytes());
+        frame.extend_from_slice(&0u16.to_le_bytes());
+        frame.extend_from_slice(&header[8..16]);
+        frame.extend_from_slice(&response);
+        stream
+            .write_all(&frame)
+            .expect("hello response should send");
+
+        let mut rest = Vec::new();
+        let _ = stream.read_to_end(&mut rest);
+    });
+    addr
+}
+
+#[test]
+fn run_command_required_persistence_with_http_down_expected_preflight_error() {
+    let temp = TempDir::new().expect("tempdir should create");
+    let dot_file = temp.path().join("pipeline.dot");
+    write_dot_file(&dot_file);
+    let binary_addr = spawn_hello_only_cxdb();
+    let http_port = TcpListener::bind("127.0.0.1:0")
+        .expect("listener should bind")
+        .local_addr()
+        .expect("listener should have addr")
+        .port();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_forge-cli"))
+        .args([
+            "run",
+            "--dot-file",
+            dot_file.to_str().expect("dot file path should be utf8"),
+            "--backend",
+            "mock",
+            "--no-stream-events",
+        ])
+        .current_dir(temp.path())
+        .env("FORGE_CXDB_PERSISTENCE", "required")
+        .env("FORGE_CXDB_BINARY_ADDR", &binary_addr)
+        .env(
+            "FORGE_CXDB_HTTP_BASE_URL",
+            format!("http://127.0.0.1:{http_port}"),
+        )
+        .output()
+        .expect("cli process should start");
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("stdout should be utf8");
+    let stderr = String::from_utf8(output.stderr).expect("stderr should be utf8");
+    assert!(
+        !stdout.contains("status:"),
+        "run should not start: {stdout}"
+    );
+    assert!(stderr.contains("CXDB HTTP health check failed"), "{stderr}");
+    assert!(stderr.contains("FORGE_CXDB_HTTP_BASE_URL"), "{stderr}");
+}
@@ -3,6 +3,7 @@ use base64::Engine;
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 pub type ContextId = String;
 pub type TurnId = String;
@@ -89,6 +90,19 @@ pub trait CxdbRecordStore: Send + Sync {
 pub trait CxdbRegistryStore: CxdbRecordStore {
     async fn publish_registry_bundle(&self, bundle: RegistryBundle) -> CxdbRuntimeResult<()>;
     async fn get_registry_bundle(&self, bundle_id: &str) -> CxdbRuntimeResult<Option<Vec<u8>>>;
+
+    /// Fails unless `bundle_id` is published at `registry_version >=
+    /// expected_version`, so writers never emit types the registry cannot
+    /// describe. Returns the published version.
+    async fn ensure_bundle_compatible(
+        &self,
+        bundle_id: &str,
+        expected_version: u32,
+    ) -> CxdbRuntimeResult<u32> {
+        let bundle = self.get_registry_bundle(bundle_id).await?;
+        check_bundle_compatible(bundle_id, bundle.as_deref(), expected_version)
+            .map_err(CxdbClientError::into_runtime_error)
+    }
 }
 
 #[async_trait]
@@ -96,6 +110,31 @@ pub trait CxdbArtifactClient: Send + Sync {
     async fn put_blob(&self, raw_bytes: &[u8]) -> CxdbRuntimeResult<BlobHash>;
     async fn get_blob(&self, content_hash: &BlobHash) -> CxdbRuntimeResult<Option<Vec<u8>>>;
     async fn attach_fs(&self, turn_id: &TurnId, fs_root_hash: &BlobHash) -> CxdbRuntimeResult<()>;
+
+    /// Reads `length` bytes starting at `offset`; `None` when the blob is
+    /// absent. Errors if the range runs past the end of the blob. The
+    /// default fetches the whole blob and slices it.
+    async fn get_blob_range(
+        &self,
+        content_hash: &BlobHash,
+        offset: u64,
+        length: u64,
+    ) -> CxdbRuntimeResult<Option<Vec<u8>>> {
+        self.get_blob(content_hash)
+            .await?
+            .map(|blob| slice_blob_range(blob, offset, length))
+            .transpose()
+            .map_err(CxdbClientError::into_runtime_error)
+    }
+
+    /// Reports whether the blob is already stored, without downloading it.
+    /// Stores that cannot check cheaply return `Unsupported`.
+    async fn blob_exists(&self, content_hash: &BlobHash) -> CxdbRuntimeResult<bool> {
+        let _ = content_hash;
+        Err(CxdbRuntimeError::Unsupported(
+            "blob_exists is not supported by this artifact client".to_string(),
+        ))
+    }
 }
 
 pub const DEFAULT_CXDB_BINARY_ADDR: &str = "127.0.0.1:9009";
@@ -109,17 +148,33 @@ pub enum CxdbClientError {
     Conflict(String),
     #[error("invalid input: {0}")]
     InvalidInput(String),
+    /// The operation needs an endpoint that is currently unavailable, e.g.
+    /// cursor paging while the HTTP API is down. Binary operations still work.
+    #[error("unsupported operation: {0}")]
+    Unsupported(String),
     #[error("backend failure: {0}")]
     Backend(String),
+    /// Transport failure or 5xx-class server error; the same request may
+    /// succeed if retried.
+    #[error("backend unavailable: {0}")]
+    Unavailable(String),
 }
 
 impl CxdbClientError {
+    /// Whether retrying the same request may succeed.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Unavailable(_))
+    }
+
     fn into_runtime_error(self) -> CxdbRuntimeError {
         match self {
             Self::NotFound { resource, id } => CxdbRuntimeError::NotFound { resource, id },
             Self::Conflict(message) => CxdbRuntimeError::Conflict(message),
             Self::InvalidInput(message) => CxdbRuntimeError::InvalidInput(message),
-            Self::Backend(message) => CxdbRuntimeError::Backend(message),
+            Self::Unsupported(message) => CxdbRuntimeError::Unsupported(message),
+            Self::Backend(message) | Self::Unavailable(message) => {
+                CxdbRuntimeError::Backend(message)
+            }
         }
     }
 }
@@ -149,6 +204,7 @@ pub struct BinaryAppendTurnResponse {
     pub new_turn_id: u64,
     pub new_depth: u32,
     pub content_hash: [u8; 32],
+    pub deduplicated: bool,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -196,6 +252,36 @@ pub trait CxdbBinaryClient: Send + Sync {
     async fn get_blob(&self, content_hash: &BlobHash) -> Result<Option<Vec<u8>>, CxdbClientError>;
     async fn attach_fs(&self, turn_id: u64, fs_root_hash: &BlobHash)
     -> Result<(), CxdbClientError>;
+
+    /// Reads `length` bytes starting at `offset`. The binary protocol has no
+    /// ranged `GET_BLOB`, so the default fetches the whole blob and slices it.
+    async fn get_blob_range(
+        &self,
+        content_hash: &BlobHash,
+        offset: u64,
+        length: u64,
+    ) -> Result<Option<Vec<u8>>, CxdbClientError> {
+        self.get_blob(content_hash)
+            .await?
+            .map(|blob| slice_blob_range(blob, offset, length))
+            .transpose()
+    }
+
+    /// Reports whether the blob is already stored, without downloading it.
+    /// The binary protocol has no existence probe, so the default returns
+    /// `Unsupported`; `PUT_BLOB` still deduplicates server-side.
+    async fn blob_exists(&self, content_hash: &BlobHash) -> Result<bool, CxdbClientError> {
+        let _ = content_hash;
+        Err(CxdbClientError::Unsupported(
+            "blob_exists is not supported by this binary client".to_string(),
+        ))
+    }
+
+    /// Round-trips a cheap request to confirm the server is reachable.
+    /// Clients without a transport (mocks) report healthy.
+    async fn ping(&self) -> Result<(), CxdbClientError> {
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -246,6 +332,23 @@ where
     ) -> Result<(), CxdbClientError> {
         (**self).attach_fs(turn_id, fs_root_hash).await
     }
+
+    async fn get_blob_range(
+        &self,
+        content_hash: &BlobHash,
+        offset: u64,
+        length: u64,
+    ) -> Result<Option<Vec<u8>>, CxdbClientError> {
+        (**self).get_blob_range(content_hash, offset, length).await
+    }
+
+    async fn blob_exists(&self, content_hash: &BlobHash) -> Result<bool, CxdbClientError> {
+        (**self).blob_exists(content_hash).await
+    }
+
+    async fn ping(&self) -> Result<(), CxdbClientError> {
+        (**self).ping().await
+    }
 }
 
 #[async_trait]
@@ -267,6 +370,36 @@ pub trait CxdbHttpClient: Send + Sync {
         &self,
         bundle_id: &str,
     ) -> Result<Option<Vec<u8>>, CxdbClientError>;
+
+    /// Reports whether the blob is already stored via `HEAD /v1/blobs/:hash`.
+    /// Clients without that endpoint return `Unsupported`.
+    async fn blob_exists(&self, content_hash: &BlobHash) -> Result<bool, CxdbClientError> {
+        let _ = content_hash;
+        Err(CxdbClientError::Unsupported(
+            "blob_exists is not supported by this http client".to_string(),
+        ))
+    }
+
+    /// Reads `length` bytes starting at `offset` with a ranged
+    /// `GET /v1/blobs/:hash`; `None` when the blob is absent. Clients without
+    /// that endpoint return `Unsupported`.
+    async fn get_blob_range(
+        &self,
+        content_hash: &BlobHash,
+        offset: u64,
+        length: u64,
+    ) -> Result<Option<Vec<u8>>, CxdbClientError> {
+        let _ = (content_hash, offset, length);
+        Err(CxdbClientError::Unsupported(
+            "get_blob_range is not supported by this http client".to_string(),
+        ))
+    }
+
+    /// Confirms the HTTP API is reachable. Clients without a transport
+    /// (mocks) report healthy.
+    async fn health(&self) -> Result<(), CxdbClientError> {
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -299,6 +432,23 @@ where
     ) -> Result<Option<Vec<u8>>, CxdbClientError> {
         (**self).get_registry_bundle(bundle_id).await
     }
+
+    async fn blob_exists(&self, content_hash: &BlobHash) -> Result<bool, CxdbClientError> {
+        (**self).blob_exists(content_hash).await
+    }
+
+    async fn get_blob_range(
+        &self,
+        content_hash: &BlobHash,
+        offset: u64,
+        length: u64,
+    ) -> Result<Option<Vec<u8>>, CxdbClientError> {
+        (**self).get_blob_range(content_hash, offset, length).await
+    }
+
+    async fn health(&self) -> Result<(), CxdbClientError> {
+        (**self).health().await
+    }
 }
 
 #[derive(Clone)]
@@ -382,6 +532,7 @@ impl CxdbBinaryClient for CxdbSdkBinaryClient {
             new_turn_id: appended.turn_id,
             new_depth: appended.depth,
             content_hash: appended.payload_hash,
+            deduplicated: appended.deduplicated,
         })
     }
 
@@ -486,6 +637,16 @@ impl CxdbBinaryClient for CxdbSdkBinaryClient {
             .map_err(map_cxdb_error)?;
         Ok(())
     }
+
+    async fn ping(&self) -> Result<(), CxdbClientError> {
+        // Context 0 never exists; any server answer (including an error
+        // frame) proves the connection is live.
+        let request_context = cxdb::RequestContext::background();
+        match self.client.get_head(&request_context, 0) {
+            Ok(_) | Err(cxdb::Error::ContextNotFound) | Err(cxdb::Error::Server(_)) => Ok(()),
+            Err(error) => Err(map_cxdb_error(error)),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -537,12 +698,12 @@ impl CxdbHttpClient for CxdbReqwestHttpClient {
             .get(self.endpoint(&path))
             .send()
             .await
-            .map_err(|err| CxdbClientError::Backend(format!("http get failed: {err}")))?;
+            .map_err(|err| CxdbClientError::Unavailable(format!("http get failed: {err}")))?;
         let status = response.status();
         let text = response
             .text()
             .await
-            .map_err(|err| CxdbClientError::Backend(format!("http read body failed: {err}")))?;
+            .map_err(|err| CxdbClientError::Unavailable(format!("http read body failed: {err}")))?;
         if !status.is_success() {
             return Err(map_http_status(status, text));
         }
@@ -571,7 +732,7 @@ impl CxdbHttpClient for CxdbReqwestHttpClient {
             .body(bundle_json.to_vec())
             .send()
             .await
-            .map_err(|err| CxdbClientError::Backend(format!("http put failed: {err}")))?;
+            .map_err(|err| CxdbClientError::Unavailable(format!("http put failed: {err}")))?;
 
         if response.status().is_success() {
             return Ok(());
@@ -593,7 +754,7 @@ impl CxdbHttpClient for CxdbReqwestHttpClient {
             .get(self.endpoint(&format!("/v1/registry/bundles/{bundle_id}")))
             .send()
             .await
-            .map_err(|err| CxdbClientError::Backend(format!("http get failed: {err}")))?;
+            .map_err(|err| CxdbClientError::Unavailable(format!("http get failed: {err}")))?;
         if response.status() == reqwest::StatusCode::NOT_FOUND {
             return Ok(None);
         }
@@ -608,15 +769,182 @@ impl CxdbHttpClient for CxdbReqwestHttpClient {
         let bytes = response
             .bytes()
             .await
-            .map_err(|err| CxdbClientError::Backend(format!("http read body failed: {err}")))?;
+            .map_err(|err| CxdbClientError::Unavailable(format!("http read body failed: {err}")))?;
         Ok(Some(bytes.to_vec()))
     }
+
+    async fn blob_exists(&self, content_hash: &BlobHash) -> Result<bool, CxdbClientError> {
+        let response = self
+            .client
+            .head(self.endpoint(&format!("/v1/blobs/{content_hash}")))
+            .send()
+            .await
+            .map_err(|err| CxdbClientError::Unavailable(format!("http head failed: {err}")))?;
+        match response.status() {
+            reqwest::StatusCode::OK => Ok(true),
+            reqwest::StatusCode::NOT_FOUND => Ok(false),
+            // HEAD responses carry no body to report.
+            status => Err(map_http_status(status, String::new())),
+        }
+    }
+
+    async fn get_blob_range(
+        &self,
+        content_hash: &BlobHash,
+        offset: u64,
+        length: u64,
+    ) -> Result<Option<Vec<u8>>, CxdbClientError> {
+        let mut request = self
+            .client
+            .get(self.endpoint(&format!("/v1/blobs/{content_hash}")));
+        // An empty range has no `Range` header form; fetch and slice instead.
+        if length > 0 {
+            let last = offset.checked_add(length - 1).ok_or_else(|| {
+                CxdbClientError::InvalidInput(format!("blob range {offset}+{length} overflows"))
+            })?;
+            request = request.header(reqwest::header::RANGE, format!("bytes={offset}-{last}"));
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|err| CxdbClientError::Unavailable(format!("http get failed: {err}")))?;
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+            return Err(CxdbClientError::InvalidInput(format!(
+                "blob range {offset}+{length} exceeds blob length"
+            )));
+        }
+        if !status.is_success() {
+            let text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<unreadable body>".to_string());
+            return Err(map_http_status(status, text));
+        }
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|err| CxdbClientError::Unavailable(format!("http read body failed: {err}")))?
+            .to_vec();
+        if status == reqwest::StatusCode::PARTIAL_CONTENT {
+            // Servers clamp a range that runs past the end instead of
+            // rejecting it; a short body means the blob is too small.
+            if bytes.len() as u64 != length {
+                return Err(CxdbClientError::InvalidInput(format!(
+                    "blob range {offset}+{length} exceeds blob length"
+                )));
+            }
+            return Ok(Some(bytes));
+        }
+        // The server ignored the range and sent the whole blob.
+        slice_blob_range(bytes, offset, length).map(Some)
+    }
+
+    async fn health(&self) -> Result<(), CxdbClientError> {
+        let response = self
+            .client
+            .get(self.endpoint("/healthz"))
+            .send()
+            .await
+            .map_err(|err| CxdbClientError::Unavailable(format!("http get failed: {err}")))?;
+        if response.status().is_success() {
+            return Ok(());
+        }
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "<unreadable body>".to_string());
+        Err(map_http_status(status, text))
+    }
+}
+
+/// Cuts `offset..offset + length` out of a fetched blob, rejecting ranges
+/// that overflow or run past its end.
+pub(crate) fn slice_blob_range(
+    mut blob: Vec<u8>,
+    offset: u64,
+    length: u64,
+) -> Result<Vec<u8>, CxdbClientError> {
+    let end = offset
+        .checked_add(length)
+        .filter(|end| *end <= blob.len() as u64)
+        .ok_or_else(|| {
+            CxdbClientError::InvalidInput(format!(
+                "blob range {offset}+{length} exceeds blob length {}",
+                blob.len()
+            ))
+        })?;
+    blob.truncate(end as usize);
+    Ok(blob.split_off(offset as usize))
+}
+
+/// Compares a fetched bundle's `registry_version` against what the caller
+/// writes; a missing or older bundle asks for a republish.
+pub(crate) fn check_bundle_compatible(
+    bundle_id: &str,
+    bundle_json: Option<&[u8]>,
+    expected_version: u32,
+) -> Result<u32, CxdbClientError> {
+    let bundle_json = bundle_json.ok_or_else(|| CxdbClientError::NotFound {
+        resource: "registry bundle",
+        id: bundle_id.to_string(),
+    })?;
+    let bundle: Value = serde_json::from_slice(bundle_json).map_err(|error| {
+        CxdbClientError::InvalidInput(format!(
+            "registry bundle '{bundle_id}' is not valid JSON: {error}"
+        ))
+    })?;
+    let published = bundle
+        .get("registry_version")
+        .and_then(Value::as_u64)
+        .and_then(|version| u32::try_from(version).ok())
+        .ok_or_else(|| {
+            CxdbClientError::InvalidInput(format!(
+                "registry bundle '{bundle_id}' has no numeric registry_version"
+            ))
+        })?;
+    if published < expected_version {
+        return Err(CxdbClientError::Conflict(format!(
+            "registry bundle '{bundle_id}' is published at registry_version {published} \
+             but this client writes registry_version {expected_version}; republish the bundle"
+        )));
+    }
+    Ok(published)
+}
+
+/// Records HTTP paging failures so binary-backed stores can keep serving
+/// appends and report cursor paging as unsupported until HTTP recovers.
+/// Only unreachable or unsupported endpoints count as HTTP being unavailable;
+/// other failures, such as malformed responses, are returned unchanged.
+pub(crate) fn observe_http_paging<T>(
+    degraded: &AtomicBool,
+    result: Result<T, CxdbClientError>,
+) -> Result<T, CxdbClientError> {
+    match result {
+        Err(CxdbClientError::Unavailable(message) | CxdbClientError::Unsupported(message)) => {
+            degraded.store(true, Ordering::Relaxed);
+            Err(CxdbClientError::Unsupported(format!(
+                "cursor paging requires the CXDB HTTP API, which is unavailable ({message}); \
+                 fall back to GET_LAST over the binary protocol"
+            )))
+        }
+        Ok(value) => {
+            degraded.store(false, Ordering::Relaxed);
+            Ok(value)
+        }
+        Err(error) => Err(error),
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct CxdbStoreAdapter<B, H> {
     binary_client: B,
     http_client: H,
+    http_degraded: Arc<AtomicBool>,
 }
 
 impl<B, H> CxdbStoreAdapter<B, H> {
@@ -624,8 +952,15 @@ impl<B, H> CxdbStoreAdapter<B, H> {
         Self {
             binary_client,
             http_client,
+            http_degraded: Arc::new(AtomicBool::new(false)),
         }
     }
+
+    /// True when the last HTTP paging request failed; binary operations are
+    /// unaffected.
+    pub fn http_degraded(&self) -> bool {
+        self.http_degraded.load(Ordering::Relaxed)
+    }
 }
 
 impl CxdbStoreAdapter<CxdbSdkBinaryClient, CxdbReqwestHttpClient> {
@@ -918,11 +1253,13 @@ where
             Some(turn_id) => Some(Self::parse_turn_id(turn_id)?),
             None => None,
         };
-        let turns = self
-            .http_client
-            .list_turns(context_id_u64, before_turn_id_u64, limit)
-            .await
-            .map_err(CxdbClientError::into_runtime_error)?;
+        let turns = observe_http_paging(
+            &self.http_degraded,
+            self.http_client
+                .list_turns(context_id_u64, before_turn_id_u64, limit)
+                .await,
+        )
+        .map_err(CxdbClientError::into_runtime_error)?;
         Ok(turns
             .into_iter()
             .map(Self::as_stored_turn_from_http)
@@ -978,6 +1315,37 @@ where
             .await
             .map_err(CxdbClientError::into_runtime_error)
     }
+
+    async fn get_blob_range(
+        &self,
+        content_hash: &BlobHash,
+        offset: u64,
+        length: u64,
+    ) -> CxdbRuntimeResult<Option<Vec<u8>>> {
+        match self
+            .http_client
+            .get_blob_range(content_hash, offset, length)
+            .await
+        {
+            Err(CxdbClientError::Unsupported(_)) => {
+                self.binary_client
+                    .get_blob_range(content_hash, offset, length)
+                    .await
+            }
+            other => other,
+        }
+        .map_err(CxdbClientError::into_runtime_error)
+    }
+
+    async fn blob_exists(&self, content_hash: &BlobHash) -> CxdbRuntimeResult<bool> {
+        match self.binary_client.blob_exists(content_hash).await {
+            Err(CxdbClientError::Unsupported(_)) => {
+                self.http_client.blob_exists(content_hash).await
+            }
+            other => other,
+        }
+        .map_err(CxdbClientError::into_runtime_error)
+    }
 }
 
 fn map_cxdb_error(error: cxdb::Error) -> CxdbClientError {
@@ -1000,11 +1368,18 @@ fn map_cxdb_error(error: cxdb::Error) -> CxdbClientError {
             },
             409 => CxdbClientError::Conflict(server_error.detail),
             422 => CxdbClientError::InvalidInput(server_error.detail),
+            500.. => CxdbClientError::Unavailable(format!(
+                "cxdb server error {}: {}",
+                server_error.code, server_error.detail
+            )),
             _ => CxdbClientError::Backend(format!(
                 "cxdb server error {}: {}",
                 server_error.code, server_error.detail
             )),
         },
+        error @ (cxdb::Error::Io(_) | cxdb::Error::Timeout | cxdb::Error::QueueFull) => {
+            CxdbClientError::Unavailable(error.to_string())
+        }
         other => CxdbClientError::Backend(other.to_string()),
     }
 }
@@ -1019,6 +1394,12 @@ fn map_http_status(status: reqwest::StatusCode, body: String) -> CxdbClientError
         reqwest::StatusCode::UNPROCESSABLE_ENTITY | reqwest::StatusCode::BAD_REQUEST => {
             CxdbClientError::InvalidInput(body)
         }
+        reqwest::StatusCode::METHOD_NOT_ALLOWED | reqwest::StatusCode::NOT_IMPLEMENTED => {
+            CxdbClientError::Unsupported(format!("http endpoint returned status {status}: {body}"))
+        }
+        _ if status.is_server_error() => CxdbClientError::Unavailable(format!(
+            "http request failed with status {status}: {body}"
+        )),
         _ => CxdbClientError::Backend(format!("http request failed with status {status}: {body}")),
     }
 }
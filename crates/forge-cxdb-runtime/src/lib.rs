@@ -12,8 +12,11 @@ Operation mapping (spec cross-check):
 | `CxdbRecordStore::list_turns` | HTTP `GET /v1/contexts/:id/turns` | `spec/cxdb/http-api.md` "Get Turns from Context" |
 | `CxdbRegistryStore::publish_registry_bundle` | HTTP `PUT /v1/registry/bundles/:bundle_id` | `spec/cxdb/http-api.md` "Publish Registry Bundle" |
 | `CxdbRegistryStore::get_registry_bundle` | HTTP `GET /v1/registry/bundles/:bundle_id` | `spec/cxdb/http-api.md` "Get Registry Bundle" |
+| `CxdbRegistryStore::ensure_bundle_compatible` | HTTP `GET /v1/registry/bundles/:bundle_id` | `spec/cxdb/http-api.md` "Get Registry Bundle" |
 | `CxdbArtifactClient::put_blob` | binary `PUT_BLOB` | `spec/cxdb/protocol.md` "9. PUT_BLOB" |
 | `CxdbArtifactClient::get_blob` | binary `GET_BLOB` | `spec/cxdb/protocol.md` "7. GET_BLOB" |
+| `CxdbArtifactClient::get_blob_range` | HTTP `GET /v1/blobs/:content_hash` with `Range` (sliced client-side on `200`); binary `GET_BLOB` sliced client-side when HTTP is unsupported | `spec/cxdb/http-api.md` "Get Blob by Hash" |
+| `CxdbArtifactClient::blob_exists` | HTTP `HEAD /v1/blobs/:content_hash` (404 → `false`); no binary probe | `spec/cxdb/http-api.md` "Get Blob by Hash" |
 | `CxdbArtifactClient::attach_fs` | binary `ATTACH_FS` | `spec/cxdb/protocol.md` "8. ATTACH_FS" |
 
 Implementation notes:
@@ -21,6 +24,7 @@ Implementation notes:
 - `append_turn` computes BLAKE3 content hash over uncompressed payload bytes.
 - If `AppendTurnRequest.idempotency_key` is empty, the adapter generates a deterministic fallback key.
 - `AppendTurnRequest.fs_root_hash` maps to CXDB append-with-fs when provided (atomic attach path).
+- Standalone `attach_fs` and `capture_upload_attach` are two-phase when an attach journal is configured (the latter journals before uploading); `reconcile_pending_attaches` finishes or drops leftovers.
 - Turn listing always uses HTTP typed projection so read/query surfaces stay projection-native.
 "#]
 
@@ -34,10 +38,12 @@ pub use adapter::{
     CxdbStoreAdapter, DEFAULT_CXDB_BINARY_ADDR, DEFAULT_CXDB_HTTP_BASE_URL, HttpStoredTurn,
 };
 pub use runtime::{
-    AppendTurnRequest as CxdbAppendTurnRequest, BlobHash as CxdbBlobHash,
-    ContextId as CxdbContextId, CxdbRuntimeStore, FsSnapshotCapture as CxdbFsSnapshotCapture,
-    FsSnapshotPolicy as CxdbFsSnapshotPolicy, FsSnapshotStats as CxdbFsSnapshotStats,
-    StoreContext as CxdbStoreContext, StoredTurn as CxdbStoredTurn,
-    StoredTurnRef as CxdbStoredTurnRef, TurnId as CxdbTurnId,
+    AppendTurnRequest as CxdbAppendTurnRequest, AttachJournal as CxdbAttachJournal,
+    AttachReconcileReport as CxdbAttachReconcileReport, BlobHash as CxdbBlobHash,
+    ContextId as CxdbContextId, CxdbRuntimeStore, FileAttachJournal as CxdbFileAttachJournal,
+    FsSnapshotCapture as CxdbFsSnapshotCapture, FsSnapshotPolicy as CxdbFsSnapshotPolicy,
+    FsSnapshotStats as CxdbFsSnapshotStats, MemoryAttachJournal as CxdbMemoryAttachJournal,
+    PendingFsAttach as CxdbPendingFsAttach, StoreContext as CxdbStoreContext,
+    StoredTurn as CxdbStoredTurn, StoredTurnRef as CxdbStoredTurnRef, TurnId as CxdbTurnId,
 };
 pub use testing::MockCxdb;
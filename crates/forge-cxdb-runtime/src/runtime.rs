@@ -1,9 +1,15 @@
+use crate::adapter::{check_bundle_compatible, observe_http_paging};
 use crate::{
-    BinaryAppendTurnRequest, CxdbBinaryClient, CxdbClientError, CxdbHttpClient, HttpStoredTurn,
+    BinaryAppendTurnRequest, BinaryContextHead, BinaryStoredTurn, CxdbBinaryClient,
+    CxdbClientError, CxdbHttpClient, HttpStoredTurn,
 };
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 
 pub type ContextId = String;
 pub type TurnId = String;
@@ -54,6 +60,10 @@ pub struct FsSnapshotPolicy {
     pub follow_symlinks: bool,
     pub max_file_size: i64,
     pub max_files: usize,
+    /// Also exclude what the workspace root's `.gitignore` lists, using git's
+    /// matching rules (negations, anchoring, directory-only entries).
+    #[serde(default)]
+    pub respect_gitignore: bool,
 }
 
 impl Default for FsSnapshotPolicy {
@@ -64,6 +74,7 @@ impl Default for FsSnapshotPolicy {
             follow_symlinks: false,
             max_file_size: 100 * 1024 * 1024,
             max_files: 100_000,
+            respect_gitignore: false,
         }
     }
 }
@@ -84,10 +95,200 @@ pub struct FsSnapshotCapture {
     pub stats: FsSnapshotStats,
 }
 
+/// An `ATTACH_FS` that was journaled but not yet confirmed by the server.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingFsAttach {
+    pub turn_id: TurnId,
+    pub fs_root_hash: BlobHash,
+}
+
+/// Outcome of [`CxdbRuntimeStore::reconcile_pending_attaches`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AttachReconcileReport {
+    /// Attaches that were re-issued and succeeded.
+    pub completed: Vec<PendingFsAttach>,
+    /// Attaches whose turn or blob no longer exists (or never did).
+    pub dropped: Vec<PendingFsAttach>,
+}
+
+/// Durable record of in-flight attaches, so an upload whose attach was cut
+/// short by a crash or abort can be finished by the next session.
+pub trait AttachJournal: Send + Sync + std::fmt::Debug {
+    fn record(&self, attach: &PendingFsAttach) -> Result<(), CxdbClientError>;
+    fn clear(&self, attach: &PendingFsAttach) -> Result<(), CxdbClientError>;
+    fn pending(&self) -> Result<Vec<PendingFsAttach>, CxdbClientError>;
+}
+
+/// Process-local journal; survives dropped futures but not process exits.
+#[derive(Debug, Default)]
+pub struct MemoryAttachJournal {
+    entries: Mutex<Vec<PendingFsAttach>>,
+}
+
+impl MemoryAttachJournal {
+    fn entries(&self) -> MutexGuard<'_, Vec<PendingFsAttach>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl AttachJournal for MemoryAttachJournal {
+    fn record(&self, attach: &PendingFsAttach) -> Result<(), CxdbClientError> {
+        let mut entries = self.entries();
+        if !entries.contains(attach) {
+            entries.push(attach.clone());
+        }
+        Ok(())
+    }
+
+    fn clear(&self, attach: &PendingFsAttach) -> Result<(), CxdbClientError> {
+        self.entries().retain(|entry| entry != attach);
+        Ok(())
+    }
+
+    fn pending(&self) -> Result<Vec<PendingFsAttach>, CxdbClientError> {
+        Ok(self.entries().clone())
+    }
+}
+
+/// Journal kept as a JSON array on disk, rewritten atomically on each change.
+///
+/// Changes hold an exclusive lock on a `<path>.lock` sidecar, so processes
+/// sharing one journal file do not lose each other's entries.
+#[derive(Debug)]
+pub struct FileAttachJournal {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileAttachJournal {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn read_entries(&self) -> Result<Vec<PendingFsAttach>, CxdbClientError> {
+        let bytes = match std::fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => {
+                return Err(CxdbClientError::Backend(format!(
+                    "failed to read attach journal {}: {error}",
+                    self.path.display()
+                )));
+            }
+        };
+        serde_json::from_slice(&bytes).map_err(|error| {
+            CxdbClientError::InvalidInput(format!(
+                "attach journal {} is not valid JSON: {error}",
+                self.path.display()
+            ))
+        })
+    }
+
+    fn write_entries(&self, entries: &[PendingFsAttach]) -> Result<(), CxdbClientError> {
+        let bytes = serde_json::to_vec(entries).map_err(|error| {
+            CxdbClientError::Backend(format!("failed to encode attach journal: {error}"))
+        })?;
+        let staging = self.sibling_path(&format!("{}.tmp", std::process::id()));
+        std::fs::write(&staging, bytes)
+            .and_then(|_| std::fs::rename(&staging, &self.path))
+            .map_err(|error| {
+                let _ = std::fs::remove_file(&staging);
+                CxdbClientError::Backend(format!(
+                    "failed to write attach journal {}: {error}",
+                    self.path.display()
+                ))
+            })
+    }
+
+    fn sibling_path(&self, suffix: &str) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".");
+        name.push(suffix);
+        self.path.with_file_name(name)
+    }
+
+    /// Opens the sidecar lock file and takes an exclusive or shared lock on
+    /// it; the lock is released when the returned file is dropped.
+    fn lock_file(&self, exclusive: bool) -> Result<std::fs::File, CxdbClientError> {
+        let lock_path = self.sibling_path("lock");
+        let lock_error = |error: std::io::Error| {
+            CxdbClientError::Backend(format!(
+                "failed to lock attach journal {}: {error}",
+                lock_path.display()
+            ))
+        };
+        if let Some(parent) = self
+            .path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent).map_err(lock_error)?;
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(lock_error)?;
+        if exclusive {
+            file.lock().map_err(lock_error)?;
+        } else {
+            file.lock_shared().map_err(lock_error)?;
+        }
+        Ok(file)
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut Vec<PendingFsAttach>)) -> Result<(), CxdbClientError> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _file_lock = self.lock_file(true)?;
+        let mut entries = self.read_entries()?;
+        apply(&mut entries);
+        self.write_entries(&entries)
+    }
+}
+
+impl AttachJournal for FileAttachJournal {
+    fn record(&self, attach: &PendingFsAttach) -> Result<(), CxdbClientError> {
+        self.update(|entries| {
+            if !entries.contains(attach) {
+                entries.push(attach.clone());
+            }
+        })
+    }
+
+    fn clear(&self, attach: &PendingFsAttach) -> Result<(), CxdbClientError> {
+        self.update(|entries| entries.retain(|entry| entry != attach))
+    }
+
+    fn pending(&self) -> Result<Vec<PendingFsAttach>, CxdbClientError> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _file_lock = self.lock_file(false)?;
+        self.read_entries()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct CxdbRuntimeStore<B, H> {
     binary_client: B,
     http_client: H,
+    head_cache: Option<Arc<HeadCache>>,
+    http_degraded: Arc<AtomicBool>,
+    attach_journal: Option<Arc<dyn AttachJournal>>,
 }
 
 impl<B, H> CxdbRuntimeStore<B, H> {
@@ -95,9 +296,43 @@ impl<B, H> CxdbRuntimeStore<B, H> {
         Self {
             binary_client,
             http_client,
+            head_cache: None,
+            http_degraded: Arc::new(AtomicBool::new(false)),
+            attach_journal: None,
         }
     }
 
+    /// Remember each context's head from creates, forks and appends so that
+    /// `get_head` can answer without a round-trip. Clones share the cache.
+    ///
+    /// Only enable this when this store (and its clones) is the sole writer
+    /// of the contexts it touches. Appends from another process or store do
+    /// not reach the cache, so `get_head` keeps returning the old head until
+    /// [`get_head_with_refresh`](Self::get_head_with_refresh) is called.
+    pub fn with_head_cache(mut self, enabled: bool) -> Self {
+        self.head_cache = enabled.then(|| Arc::new(HeadCache::default()));
+        self
+    }
+
+    /// Journal every `attach_fs` before issuing it and clear it once the
+    /// server confirms, so [`reconcile_pending_attaches`](Self::reconcile_pending_attaches)
+    /// can finish attaches an aborted process left behind.
+    pub fn with_attach_journal(mut self, journal: Arc<dyn AttachJournal>) -> Self {
+        self.attach_journal = Some(journal);
+        self
+    }
+
+    pub fn head_cache_enabled(&self) -> bool {
+        self.head_cache.is_some()
+    }
+
+    /// True when the last `list_turns` call could not reach the HTTP API.
+    /// Appends and other binary operations keep working; page with
+    /// [`get_last`](Self::get_last) instead.
+    pub fn http_degraded(&self) -> bool {
+        self.http_degraded.load(Ordering::Relaxed)
+    }
+
     pub fn binary_client(&self) -> &B {
         &self.binary_client
     }
@@ -131,6 +366,7 @@ where
         };
 
         let created = self.binary_client.ctx_create(base_turn_id).await?;
+        self.remember_head(&created, 0);
         Ok(StoreContext {
             context_id: context_id_string(created.context_id),
             head_turn_id: turn_id_string(created.head_turn_id),
@@ -144,6 +380,7 @@ where
     ) -> Result<StoreContext, CxdbClientError> {
         let from_turn_id = parse_turn_id(&from_turn_id)?;
         let forked = self.binary_client.ctx_fork(from_turn_id).await?;
+        self.remember_head(&forked, 0);
 
         Ok(StoreContext {
             context_id: context_id_string(forked.context_id),
@@ -164,7 +401,7 @@ where
         };
 
         let resolved_parent_turn_id = if requested_parent_turn_id == 0 {
-            self.binary_client.get_head(context_id).await?.head_turn_id
+            self.fetch_head(context_id, false).await?.head_turn_id
         } else {
             requested_parent_turn_id
         };
@@ -195,6 +432,10 @@ where
             None => None,
         };
 
+        let generation = self
+            .head_cache
+            .as_ref()
+            .map(|cache| cache.begin_append(context_id));
         let appended = self
             .binary_client
             .append_turn(BinaryAppendTurnRequest {
@@ -207,7 +448,26 @@ where
                 content_hash,
                 fs_root_hash: request_fs_root_hash,
             })
-            .await?;
+            .await;
+        if let (Some(cache), Some(generation)) = (self.head_cache.as_ref(), generation) {
+            // A deduplicated append returns an existing turn, which need not be the head.
+            let head = appended
+                .as_ref()
+                .ok()
+                .filter(|appended| !appended.deduplicated)
+                .map(|appended| BinaryContextHead {
+                    context_id,
+                    head_turn_id: appended.new_turn_id,
+                    head_depth: appended.new_depth,
+                });
+            cache.finish_append(context_id, generation, head);
+            if appended.is_err() {
+                // The server may have committed before the error surfaced, so
+                // a head read while the append was in flight may be stale.
+                cache.invalidate(context_id);
+            }
+        }
+        let appended = appended?;
 
         let committed_parent_turn_id = if requested_parent_turn_id == 0 {
             self.binary_client
@@ -239,8 +499,18 @@ where
     }
 
     pub async fn get_head(&self, context_id: &ContextId) -> Result<StoredTurnRef, CxdbClientError> {
+        self.get_head_with_refresh(context_id, false).await
+    }
+
+    /// Like `get_head`, but `force_refresh` bypasses the head cache and
+    /// re-reads the head from the server.
+    pub async fn get_head_with_refresh(
+        &self,
+        context_id: &ContextId,
+        force_refresh: bool,
+    ) -> Result<StoredTurnRef, CxdbClientError> {
         let context_id_u64 = parse_context_id(context_id)?;
-        let head = self.binary_client.get_head(context_id_u64).await?;
+        let head = self.fetch_head(context_id_u64, force_refresh).await?;
 
         Ok(StoredTurnRef {
             context_id: context_id_string(head.context_id),
@@ -249,6 +519,29 @@ where
         })
     }
 
+    async fn fetch_head(
+        &self,
+        context_id: u64,
+        force_refresh: bool,
+    ) -> Result<BinaryContextHead, CxdbClientError> {
+        let Some(cache) = self.head_cache.as_ref() else {
+            return self.binary_client.get_head(context_id).await;
+        };
+        if let Some(head) = cache.get(context_id).filter(|_| !force_refresh) {
+            return Ok(head);
+        }
+        let generation = cache.generation(context_id);
+        let head = self.binary_client.get_head(context_id).await?;
+        cache.fill(&head, generation);
+        Ok(head)
+    }
+
+    fn remember_head(&self, head: &BinaryContextHead, generation: u64) {
+        if let Some(cache) = self.head_cache.as_ref() {
+            cache.fill(head, generation);
+        }
+    }
+
     pub async fn list_turns(
         &self,
         context_id: &ContextId,
@@ -265,11 +558,31 @@ where
             Some(turn_id) => Some(parse_turn_id(turn_id)?),
             None => None,
         };
+        let turns = observe_http_paging(
+            &self.http_degraded,
+            self.http_client
+                .list_turns(context_id_u64, before_turn_id_u64, limit)
+                .await,
+        )?;
+        Ok(turns.into_iter().map(stored_turn_from_http).collect())
+    }
+
+    /// Newest `limit` turns of a context over the binary protocol; the
+    /// fallback when cursor paging reports `Unsupported`.
+    pub async fn get_last(
+        &self,
+        context_id: &ContextId,
+        limit: usize,
+    ) -> Result<Vec<StoredTurn>, CxdbClientError> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        let context_id_u64 = parse_context_id(context_id)?;
         let turns = self
-            .http_client
-            .list_turns(context_id_u64, before_turn_id_u64, limit)
+            .binary_client
+            .get_last(context_id_u64, limit, true)
             .await?;
-        Ok(turns.into_iter().map(stored_turn_from_http).collect())
+        Ok(turns.into_iter().map(stored_turn_from_binary).collect())
     }
 
     pub async fn list_typed_records<T: DeserializeOwned>(
@@ -301,19 +614,44 @@ where
         workspace_root: &Path,
         policy: &FsSnapshotPolicy,
     ) -> Result<FsSnapshotCapture, CxdbClientError> {
-        let mut opts = Vec::new();
-        if !policy.exclude_patterns.is_empty() {
-            opts.push(cxdb::fstree::with_exclude(policy.exclude_patterns.clone()));
+        let snapshot = capture_workspace(workspace_root, policy)?;
+        self.upload_snapshot(&snapshot, policy).await
+    }
+
+    /// Captures the workspace and attaches it to `turn_id`. With an attach
+    /// journal configured, the attach is journaled as soon as the root hash
+    /// is known, before any blob is uploaded, so an abort mid-upload is
+    /// still reconciled (dropped if the root never landed).
+    pub async fn capture_upload_attach(
+        &self,
+        turn_id: &TurnId,
+        workspace_root: &Path,
+        policy: &FsSnapshotPolicy,
+    ) -> Result<FsSnapshotCapture, CxdbClientError> {
+        let turn_id_u64 = parse_turn_id(turn_id)?;
+        let snapshot = capture_workspace(workspace_root, policy)?;
+        let pending = PendingFsAttach {
+            turn_id: turn_id.clone(),
+            fs_root_hash: hash_hex(snapshot.root_hash),
+        };
+        if let Some(journal) = self.attach_journal.as_ref() {
+            journal.record(&pending)?;
         }
-        if policy.follow_symlinks {
-            opts.push(cxdb::fstree::with_follow_symlinks());
+        let capture = self.upload_snapshot(&snapshot, policy).await?;
+        self.binary_client
+            .attach_fs(turn_id_u64, &capture.fs_root_hash)
+            .await?;
+        if let Some(journal) = self.attach_journal.as_ref() {
+            journal.clear(&pending)?;
         }
-        opts.push(cxdb::fstree::with_max_file_size(policy.max_file_size));
-        opts.push(cxdb::fstree::with_max_files(policy.max_files));
-
-        let snapshot = cxdb::fstree::capture(workspace_root, opts)
-            .map_err(|error| CxdbClientError::Backend(format!("fstree capture failed: {error}")))?;
+        Ok(capture)
+    }
 
+    async fn upload_snapshot(
+        &self,
+        snapshot: &cxdb::fstree::Snapshot,
+        policy: &FsSnapshotPolicy,
+    ) -> Result<FsSnapshotCapture, CxdbClientError> {
         for tree in snapshot.trees.values() {
             self.binary_client.put_blob(tree).await?;
         }
@@ -366,15 +704,97 @@ where
         self.binary_client.get_blob(content_hash).await
     }
 
+    /// Reads `length` bytes of a blob starting at `offset`. Uses a ranged
+    /// HTTP read when the HTTP client supports one, so only the requested
+    /// bytes are transferred; otherwise the binary client fetches the whole
+    /// blob and slices it.
+    pub async fn get_blob_range(
+        &self,
+        content_hash: &BlobHash,
+        offset: u64,
+        length: u64,
+    ) -> Result<Option<Vec<u8>>, CxdbClientError> {
+        match self
+            .http_client
+            .get_blob_range(content_hash, offset, length)
+            .await
+        {
+            Err(CxdbClientError::Unsupported(_)) => {
+                self.binary_client
+                    .get_blob_range(content_hash, offset, length)
+                    .await
+            }
+            other => other,
+        }
+    }
+
+    /// Reports whether the blob is already stored. Falls back to the HTTP
+    /// API when the binary client has no existence check; `Unsupported`
+    /// only when neither client can answer.
+    pub async fn blob_exists(&self, content_hash: &BlobHash) -> Result<bool, CxdbClientError> {
+        match self.binary_client.blob_exists(content_hash).await {
+            Err(CxdbClientError::Unsupported(_)) => {
+                self.http_client.blob_exists(content_hash).await
+            }
+            other => other,
+        }
+    }
+
     pub async fn attach_fs(
         &self,
         turn_id: &TurnId,
         fs_root_hash: &BlobHash,
     ) -> Result<(), CxdbClientError> {
         let turn_id_u64 = parse_turn_id(turn_id)?;
+        let Some(journal) = self.attach_journal.as_ref() else {
+            return self
+                .binary_client
+                .attach_fs(turn_id_u64, fs_root_hash)
+                .await;
+        };
+        let pending = PendingFsAttach {
+            turn_id: turn_id.clone(),
+            fs_root_hash: fs_root_hash.clone(),
+        };
+        journal.record(&pending)?;
         self.binary_client
             .attach_fs(turn_id_u64, fs_root_hash)
-            .await
+            .await?;
+        journal.clear(&pending)
+    }
+
+    /// Re-issues every journaled attach. Attaches whose turn or blob is gone
+    /// are dropped from the journal; CXDB has no blob delete, so the upload
+    /// itself stays. Transient failures leave the entry for the next call.
+    pub async fn reconcile_pending_attaches(
+        &self,
+    ) -> Result<AttachReconcileReport, CxdbClientError> {
+        let mut report = AttachReconcileReport::default();
+        let Some(journal) = self.attach_journal.as_ref() else {
+            return Ok(report);
+        };
+        for pending in journal.pending()? {
+            let outcome = match parse_turn_id(&pending.turn_id) {
+                Ok(turn_id) => {
+                    self.binary_client
+                        .attach_fs(turn_id, &pending.fs_root_hash)
+                        .await
+                }
+                Err(error) => Err(error),
+            };
+            match outcome {
+                Ok(()) => {
+                    journal.clear(&pending)?;
+                    report.completed.push(pending);
+                }
+                Err(CxdbClientError::NotFound { .. } | CxdbClientError::InvalidInput(_)) => {
+                    journal.clear(&pending)?;
+                    report.dropped.push(pending);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(report)
     }
 
     pub async fn publish_registry_bundle(
@@ -393,6 +813,90 @@ where
     ) -> Result<Option<Vec<u8>>, CxdbClientError> {
         self.http_client.get_registry_bundle(bundle_id).await
     }
+
+    /// Fails unless `bundle_id` is published at `registry_version >=
+    /// expected_version`. Returns the published version.
+    pub async fn ensure_bundle_compatible(
+        &self,
+        bundle_id: &str,
+        expected_version: u32,
+    ) -> Result<u32, CxdbClientError> {
+        let bundle = self.get_registry_bundle(bundle_id).await?;
+        check_bundle_compatible(bundle_id, bundle.as_deref(), expected_version)
+    }
+}
+
+/// Per-context heads observed by a `CxdbRuntimeStore`.
+///
+/// Every append bumps the context's generation and clears its head while it
+/// is in flight. A head is only stored when no other append started in the
+/// meantime, so overlapping appends or reads never leave a stale head behind.
+/// A failed append invalidates the context outright, since the server may
+/// have committed it anyway.
+#[derive(Debug, Default)]
+struct HeadCache {
+    entries: Mutex<HashMap<u64, HeadCacheEntry>>,
+}
+
+#[derive(Debug, Default)]
+struct HeadCacheEntry {
+    head: Option<BinaryContextHead>,
+    generation: u64,
+    in_flight: usize,
+}
+
+impl HeadCache {
+    fn entries(&self) -> MutexGuard<'_, HashMap<u64, HeadCacheEntry>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn get(&self, context_id: u64) -> Option<BinaryContextHead> {
+        self.entries()
+            .get(&context_id)
+            .filter(|entry| entry.in_flight == 0)
+            .and_then(|entry| entry.head.clone())
+    }
+
+    fn generation(&self, context_id: u64) -> u64 {
+        self.entries()
+            .get(&context_id)
+            .map_or(0, |entry| entry.generation)
+    }
+
+    fn fill(&self, head: &BinaryContextHead, generation: u64) {
+        let mut entries = self.entries();
+        let entry = entries.entry(head.context_id).or_default();
+        if entry.in_flight == 0 && entry.generation == generation {
+            entry.head = Some(head.clone());
+        }
+    }
+
+    fn begin_append(&self, context_id: u64) -> u64 {
+        let mut entries = self.entries();
+        let entry = entries.entry(context_id).or_default();
+        entry.generation += 1;
+        entry.in_flight += 1;
+        entry.head = None;
+        entry.generation
+    }
+
+    fn finish_append(&self, context_id: u64, generation: u64, head: Option<BinaryContextHead>) {
+        let mut entries = self.entries();
+        let entry = entries.entry(context_id).or_default();
+        entry.in_flight = entry.in_flight.saturating_sub(1);
+        entry.head = head.filter(|_| entry.in_flight == 0 && entry.generation == generation);
+    }
+
+    /// Drops the context's head and bumps its generation, so reads that
+    /// started earlier cannot fill it back in.
+    fn invalidate(&self, context_id: u64) {
+        let mut entries = self.entries();
+        let entry = entries.entry(context_id).or_default();
+        entry.generation += 1;
+        entry.head = None;
+    }
 }
 
 fn parse_context_id(context_id: &ContextId) -> Result<u64, CxdbClientError> {
@@ -403,6 +907,49 @@ fn parse_context_id(context_id: &ContextId) -> Result<u64, CxdbClientError> {
     })
 }
 
+fn capture_workspace(
+    workspace_root: &Path,
+    policy: &FsSnapshotPolicy,
+) -> Result<cxdb::fstree::Snapshot, CxdbClientError> {
+    let mut opts = Vec::new();
+    if !policy.exclude_patterns.is_empty() {
+        opts.push(cxdb::fstree::with_exclude(policy.exclude_patterns.clone()));
+    }
+    if policy.respect_gitignore
+        && let Some(gitignore) = workspace_gitignore(workspace_root)
+    {
+        opts.push(cxdb::fstree::with_exclude_func(move |rel_path, is_dir| {
+            gitignore
+                .matched_path_or_any_parents(rel_path, is_dir)
+                .is_ignore()
+        }));
+    }
+    if policy.follow_symlinks {
+        opts.push(cxdb::fstree::with_follow_symlinks());
+    }
+    opts.push(cxdb::fstree::with_max_file_size(policy.max_file_size));
+    opts.push(cxdb::fstree::with_max_files(policy.max_files));
+
+    cxdb::fstree::capture(workspace_root, opts)
+        .map_err(|error| CxdbClientError::Backend(format!("fstree capture failed: {error}")))
+}
+
+/// Loads the workspace root's `.gitignore` with git's own matching rules
+/// (anchoring, directory-only entries, negations). Lines that fail to parse
+/// are skipped; `None` when there is no usable file.
+fn workspace_gitignore(workspace_root: &Path) -> Option<Gitignore> {
+    let path = workspace_root.join(".gitignore");
+    if !path.is_file() {
+        return None;
+    }
+    let mut builder = GitignoreBuilder::new(workspace_root);
+    let _ = builder.add(path);
+    builder
+        .build()
+        .ok()
+        .filter(|gitignore| !gitignore.is_empty())
+}
+
 fn parse_turn_id(turn_id: &TurnId) -> Result<u64, CxdbClientError> {
     turn_id.parse::<u64>().map_err(|_| {
         CxdbClientError::InvalidInput(format!(
@@ -433,6 +980,20 @@ fn stored_turn_from_http(turn: HttpStoredTurn) -> StoredTurn {
     }
 }
 
+fn stored_turn_from_binary(turn: BinaryStoredTurn) -> StoredTurn {
+    StoredTurn {
+        context_id: context_id_string(turn.context_id),
+        turn_id: turn_id_string(turn.turn_id),
+        parent_turn_id: turn_id_string(turn.parent_turn_id),
+        depth: turn.depth,
+        type_id: turn.type_id,
+        type_version: turn.type_version,
+        payload: turn.payload,
+        idempotency_key: turn.idempotency_key,
+        content_hash: Some(hash_hex(turn.content_hash)),
+    }
+}
+
 fn hash_hex(hash: [u8; 32]) -> BlobHash {
     let mut hex = String::with_capacity(64);
     for byte in hash {
@@ -476,9 +1037,11 @@ fn encode_part(part: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::MockCxdb;
+    use crate::{BinaryAppendTurnResponse, BinaryStoredTurn, MockCxdb};
+    use async_trait::async_trait;
     use serde::{Deserialize, Serialize};
     use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
     struct TestRecord {
@@ -486,6 +1049,121 @@ mod tests {
         count: u32,
     }
 
+    #[derive(Default)]
+    struct HeadCountingCxdb {
+        inner: MockCxdb,
+        head_calls: AtomicUsize,
+    }
+
+    impl HeadCountingCxdb {
+        fn head_calls(&self) -> usize {
+            self.head_calls.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl CxdbBinaryClient for HeadCountingCxdb {
+        async fn ctx_create(
+            &self,
+            base_turn_id: u64,
+        ) -> Result<BinaryContextHead, CxdbClientError> {
+            self.inner.ctx_create(base_turn_id).await
+        }
+
+        async fn ctx_fork(&self, from_turn_id: u64) -> Result<BinaryContextHead, CxdbClientError> {
+            self.inner.ctx_fork(from_turn_id).await
+        }
+
+        async fn append_turn(
+            &self,
+            request: BinaryAppendTurnRequest,
+        ) -> Result<BinaryAppendTurnResponse, CxdbClientError> {
+            self.inner.append_turn(request).await
+        }
+
+        async fn get_head(&self, context_id: u64) -> Result<BinaryContextHead, CxdbClientError> {
+            self.head_calls.fetch_add(1, Ordering::SeqCst);
+            self.inner.get_head(context_id).await
+        }
+
+        async fn get_last(
+            &self,
+            context_id: u64,
+            limit: usize,
+            include_payload: bool,
+        ) -> Result<Vec<BinaryStoredTurn>, CxdbClientError> {
+            self.inner
+                .get_last(context_id, limit, include_payload)
+                .await
+        }
+
+        async fn put_blob(&self, raw_bytes: &[u8]) -> Result<BlobHash, CxdbClientError> {
+            self.inner.put_blob(raw_bytes).await
+        }
+
+        async fn get_blob(
+            &self,
+            content_hash: &BlobHash,
+        ) -> Result<Option<Vec<u8>>, CxdbClientError> {
+            self.inner.get_blob(content_hash).await
+        }
+
+        async fn attach_fs(
+            &self,
+            turn_id: u64,
+            fs_root_hash: &BlobHash,
+        ) -> Result<(), CxdbClientError> {
+            self.inner.attach_fs(turn_id, fs_root_hash).await
+        }
+    }
+
+    struct UnreachableHttp;
+
+    #[async_trait]
+    impl CxdbHttpClient for UnreachableHttp {
+        async fn list_turns(
+            &self,
+            _context_id: u64,
+            _before_turn_id: Option<u64>,
+            _limit: usize,
+        ) -> Result<Vec<HttpStoredTurn>, CxdbClientError> {
+            Err(CxdbClientError::Unavailable(
+                "http get failed: connection refused".to_string(),
+            ))
+        }
+
+        async fn publish_registry_bundle(
+            &self,
+            _bundle_id: &str,
+            _bundle_json: &[u8],
+        ) -> Result<(), CxdbClientError> {
+            Err(CxdbClientError::Backend(
+                "http put failed: connection refused".to_string(),
+            ))
+        }
+
+        async fn get_registry_bundle(
+            &self,
+            _bundle_id: &str,
+        ) -> Result<Option<Vec<u8>>, CxdbClientError> {
+            Err(CxdbClientError::Backend(
+                "http get failed: connection refused".to_string(),
+            ))
+        }
+    }
+
+    fn test_append(context_id: &ContextId, idempotency_key: &str) -> AppendTurnRequest {
+        AppendTurnRequest {
+            context_id: context_id.clone(),
+            parent_turn_id: None,
+            type_id: "forge.test.record".to_string(),
+            type_version: 1,
+            payload: idempotency_key.as_bytes().to_vec(),
+            idempotency_key: idempotency_key.to_string(),
+            fs_root_hash: None,
+        }
+    }
+
     #[test]
     fn decode_typed_payload_msgpack_expected_record() {
         let payload = rmp_serde::to_vec_named(&TestRecord {
@@ -569,4 +1247,638 @@ mod tests {
             }
         );
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_head_after_append_served_from_cache_when_enabled() {
+        let binary = Arc::new(HeadCountingCxdb::default());
+        let store = CxdbRuntimeStore::new(binary.clone(), Arc::new(MockCxdb::default()))
+            .with_head_cache(true);
+        let created = store
+            .create_context(None)
+            .await
+            .expect("context creation should succeed");
+        let appended = store
+            .append_turn(test_append(&created.context_id, "turn-1"))
+            .await
+            .expect("append should succeed");
+        let calls_after_append = binary.head_calls();
+
+        let head = store
+            .get_head(&created.context_id)
+            .await
+            .expect("head should resolve");
+        assert_eq!(head.turn_id, appended.turn_id);
+        assert_eq!(head.depth, appended.depth);
+        assert_eq!(binary.head_calls(), calls_after_append);
+
+        let refreshed = store
+            .get_head_with_refresh(&created.context_id, true)
+            .await
+            .expect("forced head should resolve");
+        assert_eq!(refreshed, head);
+        assert_eq!(binary.head_calls(), calls_after_append + 1);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_head_without_cache_always_queries_backend() {
+        let binary = Arc::new(HeadCountingCxdb::default());
+        let store = CxdbRuntimeStore::new(binary.clone(), Arc::new(MockCxdb::default()));
+        let created = store
+            .create_context(None)
+            .await
+            .expect("context creation should succeed");
+        store
+            .append_turn(test_append(&created.context_id, "turn-1"))
+            .await
+            .expect("append should succeed");
+        let calls_after_append = binary.head_calls();
+
+        store
+            .get_head(&created.context_id)
+            .await
+            .expect("head should resolve");
+        assert_eq!(binary.head_calls(), calls_after_append + 1);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn deduplicated_append_invalidates_cached_head() {
+        let binary = Arc::new(HeadCountingCxdb::default());
+        let store = CxdbRuntimeStore::new(binary.clone(), Arc::new(MockCxdb::default()))
+            .with_head_cache(true);
+        let created = store
+            .create_context(None)
+            .await
+            .expect("context creation should succeed");
+        let first = store
+            .append_turn(test_append(&created.context_id, "turn-1"))
+            .await
+            .expect("first append should succeed");
+        let second = store
+            .append_turn(test_append(&created.context_id, "turn-2"))
+            .await
+            .expect("second append should succeed");
+        let replayed = store
+            .append_turn(test_append(&created.context_id, "turn-1"))
+            .await
+            .expect("replayed append should succeed");
+        assert_eq!(replayed.turn_id, first.turn_id);
+        let calls_before_head = binary.head_calls();
+
+        let head = store
+            .get_head(&created.context_id)
+            .await
+            .expect("head should resolve");
+        assert_eq!(head.turn_id, second.turn_id);
+        assert_eq!(binary.head_calls(), calls_before_head + 1);
+    }
+
+    #[test]
+    fn head_cache_failed_append_expected_in_flight_read_discarded() {
+        let cache = HeadCache::default();
+        let stale = BinaryContextHead {
+            context_id: 7,
+            head_turn_id: 1,
+            head_depth: 1,
+        };
+        cache.fill(&stale, 0);
+
+        let generation = cache.begin_append(7);
+        let read_generation = cache.generation(7);
+        cache.finish_append(7, generation, None);
+        cache.invalidate(7);
+        cache.fill(&stale, read_generation);
+
+        assert_eq!(cache.get(7), None);
+    }
+
+    #[test]
+    fn workspace_gitignore_expected_git_matching_rules() {
+        let temp = tempfile::tempdir().expect("tempdir should be created");
+        std::fs::write(
+            temp.path().join(".gitignore"),
+            "# build output\n/target/\n*.log\n\n!keep.log\nnode_modules\nbuild/\n",
+        )
+        .expect(".gitignore should be written");
+        let gitignore = workspace_gitignore(temp.path()).expect("gitignore should load");
+        let ignored = |path: &str, is_dir: bool| {
+            gitignore
+                .matched_path_or_any_parents(path, is_dir)
+                .is_ignore()
+        };
+
+        assert!(ignored("target", true));
+        assert!(ignored("target/debug/app", false));
+        assert!(!ignored("src/target", true));
+        assert!(ignored("src/app.log", false));
+        assert!(!ignored("keep.log", false));
+        assert!(ignored("web/node_modules/pkg/index.js", false));
+        assert!(ignored("build", true));
+        assert!(!ignored("build", false));
+        assert!(workspace_gitignore(&temp.path().join("missing")).is_none());
+    }
+
+    #[test]
+    fn capture_workspace_respect_gitignore_expected_negations_kept() {
+        let temp = tempfile::tempdir().expect("tempdir should be created");
+        std::fs::write(temp.path().join(".gitignore"), "*.log\n!keep.log\n")
+            .expect(".gitignore should be written");
+        for name in ["main.rs", "debug.log", "keep.log"] {
+            std::fs::write(temp.path().join(name), name).expect("file should be written");
+        }
+
+        let snapshot = capture_workspace(
+            temp.path(),
+            &FsSnapshotPolicy {
+                respect_gitignore: true,
+                ..FsSnapshotPolicy::default()
+            },
+        )
+        .expect("capture should succeed");
+
+        let mut files = snapshot.list_files().expect("files should list");
+        files.sort();
+        assert_eq!(files, vec![".gitignore", "keep.log", "main.rs"]);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn reconcile_after_interrupted_attach_expected_completed_and_stale_dropped() {
+        let temp = tempfile::tempdir().expect("tempdir should be created");
+        let journal_path = temp.path().join("pending-attaches.json");
+        let backend = Arc::new(MockCxdb::default());
+        let store = CxdbRuntimeStore::new(backend.clone(), backend.clone())
+            .with_attach_journal(Arc::new(FileAttachJournal::new(journal_path.clone())));
+        let created = store
+            .create_context(None)
+            .await
+            .expect("context creation should succeed");
+        let turn = store
+            .append_turn(test_append(&created.context_id, "attach-1"))
+            .await
+            .expect("append should succeed");
+        let fs_root_hash = store
+            .put_blob(b"workspace tree")
+            .await
+            .expect("blob upload should succeed");
+
+        store
+            .attach_fs(&turn.turn_id, &fs_root_hash)
+            .await
+            .expect("journaled attach should succeed");
+        let journal = FileAttachJournal::new(journal_path.clone());
+        assert!(journal.pending().expect("journal should read").is_empty());
+
+        // A previous process journaled these and died before ATTACH_FS returned.
+        let interrupted = PendingFsAttach {
+            turn_id: turn.turn_id.clone(),
+            fs_root_hash: fs_root_hash.clone(),
+        };
+        let stale = PendingFsAttach {
+            turn_id: "999".to_string(),
+            fs_root_hash,
+        };
+        journal
+            .record(&interrupted)
+            .expect("journal record should succeed");
+        journal
+            .record(&stale)
+            .expect("journal record should succeed");
+
+        let restarted = CxdbRuntimeStore::new(backend.clone(), backend)
+            .with_attach_journal(Arc::new(FileAttachJournal::new(journal_path)));
+        let report = restarted
+            .reconcile_pending_attaches()
+            .await
+            .expect("reconcile should succeed");
+        assert_eq!(report.completed, vec![interrupted]);
+        assert_eq!(report.dropped, vec![stale]);
+        assert!(journal.pending().expect("journal should read").is_empty());
+    }
+
+    #[test]
+    fn file_attach_journal_shared_file_expected_no_lost_entries() {
+        let temp = tempfile::tempdir().expect("tempdir should be created");
+        let journal_path = temp.path().join("state").join("pending-attaches.json");
+        // Separate journal instances stand in for separate processes: they do
+        // not share the in-process mutex, only the file.
+        let writers: Vec<_> = (0..4)
+            .map(|writer| {
+                let journal = FileAttachJournal::new(journal_path.clone());
+                std::thread::spawn(move || {
+                    for entry in 0..10 {
+                        journal
+                            .record(&PendingFsAttach {
+                                turn_id: format!("{writer}-{entry}"),
+                                fs_root_hash: "00".repeat(32),
+                            })
+                            .expect("journal record should succeed");
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().expect("writer thread should finish");
+        }
+
+        let pending = FileAttachJournal::new(journal_path)
+            .pending()
+            .expect("journal should read");
+        assert_eq!(pending.len(), 40);
+    }
+
+    #[derive(Debug)]
+    struct RejectingJournal;
+
+    impl AttachJournal for RejectingJournal {
+        fn record(&self, _attach: &PendingFsAttach) -> Result<(), CxdbClientError> {
+            Err(CxdbClientError::Backend("journal disk full".to_string()))
+        }
+
+        fn clear(&self, _attach: &PendingFsAttach) -> Result<(), CxdbClientError> {
+            Ok(())
+        }
+
+        fn pending(&self) -> Result<Vec<PendingFsAttach>, CxdbClientError> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn capture_upload_attach_journals_before_uploading() {
+        let temp = tempfile::tempdir().expect("tempdir should be created");
+        let workspace = temp.path().join("workspace");
+        std::fs::create_dir(&workspace).expect("workspace should be created");
+        std::fs::write(workspace.join("main.rs"), "fn main() {}\n")
+            .expect("workspace file should be written");
+        let policy = FsSnapshotPolicy::default();
+        let backend = Arc::new(MockCxdb::default());
+        let created = CxdbRuntimeStore::new(backend.clone(), backend.clone())
+            .create_context(None)
+            .await
+            .expect("context creation should succeed");
+        let turn = CxdbRuntimeStore::new(backend.clone(), backend.clone())
+            .append_turn(test_append(&created.context_id, "attach-1"))
+            .await
+            .expect("append should succeed");
+
+        let rejecting = CxdbRuntimeStore::new(backend.clone(), backend.clone())
+            .with_attach_journal(Arc::new(RejectingJournal));
+        rejecting
+            .capture_upload_attach(&turn.turn_id, &workspace, &policy)
+            .await
+            .expect_err("unjournaled attach should not proceed");
+        let root_hash = hash_hex(
+            capture_workspace(&workspace, &policy)
+                .expect("capture should succeed")
+                .root_hash,
+        );
+        assert!(
+            !rejecting
+                .blob_exists(&root_hash)
+                .await
+                .expect("existence check should succeed"),
+            "nothing should be uploaded before the attach is journaled"
+        );
+
+        let journal_path = temp.path().join("pending-attaches.json");
+        let store = CxdbRuntimeStore::new(backend.clone(), backend)
+            .with_attach_journal(Arc::new(FileAttachJournal::new(journal_path.clone())));
+        let capture = store
+            .capture_upload_attach(&turn.turn_id, &workspace, &policy)
+            .await
+            .expect("journaled capture should succeed");
+        assert_eq!(capture.fs_root_hash, root_hash);
+        assert!(
+            FileAttachJournal::new(journal_path.clone())
+                .pending()
+                .expect("journal should read")
+                .is_empty()
+        );
+
+        let orphan = store
+            .capture_upload_attach(&"999".to_string(), &workspace, &policy)
+            .await;
+        assert!(matches!(orphan, Err(CxdbClientError::NotFound { .. })));
+        let report = store
+            .reconcile_pending_attaches()
+            .await
+            .expect("reconcile should succeed");
+        assert_eq!(
+            report.dropped,
+            vec![PendingFsAttach {
+                turn_id: "999".to_string(),
+                fs_root_hash: root_hash,
+            }]
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn mock_append_with_repeated_idempotency_key_returns_existing_turn() {
+        let backend = Arc::new(MockCxdb::default());
+        let store = CxdbRuntimeStore::new(backend.clone(), backend);
+        let created = store
+            .create_context(None)
+            .await
+            .expect("context creation should succeed");
+
+        let first = store
+            .append_turn(test_append(&created.context_id, "retry-1"))
+            .await
+            .expect("first append should succeed");
+        let retried = store
+            .append_turn(test_append(&created.context_id, "retry-1"))
+            .await
+            .expect("retried append should succeed");
+        assert_eq!(retried.turn_id, first.turn_id);
+        let turns = store
+            .get_last(&created.context_id, 8)
+            .await
+            .expect("turns should list");
+        assert_eq!(turns.len(), 1);
+
+        // Keys are scoped per context, as in CXDB.
+        let other = store
+            .create_context(None)
+            .await
+            .expect("context creation should succeed");
+        let elsewhere = store
+            .append_turn(test_append(&other.context_id, "retry-1"))
+            .await
+            .expect("append in another context should succeed");
+        assert_ne!(elsewhere.turn_id, first.turn_id);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_blob_range_middle_expected_matching_bytes() {
+        let backend = Arc::new(MockCxdb::default());
+        let store = CxdbRuntimeStore::new(backend.clone(), backend);
+        let hash = store
+            .put_blob(b"0123456789")
+            .await
+            .expect("blob upload should succeed");
+
+        let middle = store
+            .get_blob_range(&hash, 3, 4)
+            .await
+            .expect("range read should succeed");
+        assert_eq!(middle.as_deref(), Some(&b"3456"[..]));
+        let tail = store
+            .get_blob_range(&hash, 10, 0)
+            .await
+            .expect("empty range at the end should succeed");
+        assert_eq!(tail, Some(Vec::new()));
+
+        let error = store
+            .get_blob_range(&hash, 8, 3)
+            .await
+            .expect_err("range past the end should fail");
+        assert!(matches!(error, CxdbClientError::InvalidInput(_)));
+        let missing = store
+            .get_blob_range(&"0".repeat(64), 0, 1)
+            .await
+            .expect("missing blob should not error");
+        assert_eq!(missing, None);
+    }
+
+    /// Serves one HTTP response and hands back the raw request it answered.
+    fn spawn_blob_server(
+        status_line: &'static str,
+        body: &'static [u8],
+    ) -> (String, std::sync::mpsc::Receiver<String>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind listener");
+        let address = listener.local_addr().expect("listener addr");
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().expect("accept");
+            let mut buffer = vec![0_u8; 8192];
+            let read = socket.read(&mut buffer).expect("read request");
+            tx.send(String::from_utf8_lossy(&buffer[..read]).to_string())
+                .expect("send request capture");
+            let head = format!(
+                "HTTP/1.1 {status_line}\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            socket.write_all(head.as_bytes()).expect("write head");
+            socket.write_all(body).expect("write body");
+            socket.flush().expect("flush");
+        });
+        (format!("http://{address}"), rx)
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_blob_range_http_partial_content_expected_only_range_transferred() {
+        let (base_url, requests) = spawn_blob_server("206 Partial Content", b"3456");
+        let store = CxdbRuntimeStore::new(
+            Arc::new(MockCxdb::default()),
+            crate::CxdbReqwestHttpClient::new(base_url),
+        );
+        let hash = "a".repeat(64);
+
+        let middle = store
+            .get_blob_range(&hash, 3, 4)
+            .await
+            .expect("range read should succeed");
+        assert_eq!(middle.as_deref(), Some(&b"3456"[..]));
+        let request = requests.recv().expect("request should be captured");
+        assert!(request.starts_with(&format!("GET /v1/blobs/{hash} ")));
+        assert!(request.to_ascii_lowercase().contains("range: bytes=3-6"));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn get_blob_range_http_range_ignored_expected_sliced_full_body() {
+        let (base_url, _requests) = spawn_blob_server("200 OK", b"0123456789");
+        let store = CxdbRuntimeStore::new(
+            Arc::new(MockCxdb::default()),
+            crate::CxdbReqwestHttpClient::new(base_url),
+        );
+
+        let middle = store
+            .get_blob_range(&"a".repeat(64), 3, 4)
+            .await
+            .expect("range read should succeed");
+        assert_eq!(middle.as_deref(), Some(&b"3456"[..]));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn blob_exists_after_put_expected_true_and_unknown_hash_false() {
+        let backend = Arc::new(MockCxdb::default());
+        let store = CxdbRuntimeStore::new(backend.clone(), backend);
+        let hash = store
+            .put_blob(b"artifact body")
+            .await
+            .expect("blob upload should succeed");
+
+        assert!(
+            store
+                .blob_exists(&hash)
+                .await
+                .expect("existence check should succeed")
+        );
+        assert!(
+            !store
+                .blob_exists(&"f".repeat(64))
+                .await
+                .expect("existence check should succeed")
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn blob_exists_without_binary_probe_expected_http_fallback() {
+        let backend = MockCxdb::default();
+        let binary = HeadCountingCxdb {
+            inner: backend.clone(),
+            ..HeadCountingCxdb::default()
+        };
+        let store = CxdbRuntimeStore::new(binary, backend);
+        let hash = store
+            .put_blob(b"artifact body")
+            .await
+            .expect("blob upload should succeed");
+
+        assert!(
+            store
+                .blob_exists(&hash)
+                .await
+                .expect("http fallback should answer")
+        );
+        assert!(
+            !store
+                .blob_exists(&"f".repeat(64))
+                .await
+                .expect("http fallback should answer")
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn blob_exists_http_expected_head_request() {
+        for (status_line, expected) in [("200 OK", true), ("404 Not Found", false)] {
+            let (base_url, requests) = spawn_blob_server(status_line, b"");
+            let store = CxdbRuntimeStore::new(
+                HeadCountingCxdb::default(),
+                crate::CxdbReqwestHttpClient::new(base_url),
+            );
+            let hash = "a".repeat(64);
+
+            let exists = store
+                .blob_exists(&hash)
+                .await
+                .expect("http probe should answer");
+            assert_eq!(exists, expected, "status {status_line}");
+            let request = requests.recv().expect("request should be captured");
+            assert!(request.starts_with(&format!("HEAD /v1/blobs/{hash} ")));
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn http_down_expected_unsupported_paging_and_working_appends() {
+        let store = CxdbRuntimeStore::new(MockCxdb::default(), UnreachableHttp);
+        let created = store
+            .create_context(None)
+            .await
+            .expect("context creation should succeed");
+        let first = store
+            .append_turn(test_append(&created.context_id, "degraded-1"))
+            .await
+            .expect("binary append should succeed");
+        let second = store
+            .append_turn(test_append(&created.context_id, "degraded-2"))
+            .await
+            .expect("binary append should succeed");
+        assert!(!store.http_degraded());
+
+        let error = store
+            .list_turns(&created.context_id, Some(&second.turn_id), 8)
+            .await
+            .expect_err("cursor paging should fail without HTTP");
+        assert!(matches!(error, CxdbClientError::Unsupported(_)));
+        assert!(store.http_degraded());
+
+        let recent = store
+            .get_last(&created.context_id, 8)
+            .await
+            .expect("GET_LAST fallback should succeed");
+        let turn_ids: Vec<&str> = recent.iter().map(|turn| turn.turn_id.as_str()).collect();
+        assert!(turn_ids.contains(&first.turn_id.as_str()));
+        assert!(turn_ids.contains(&second.turn_id.as_str()));
+
+        store
+            .append_turn(test_append(&created.context_id, "degraded-3"))
+            .await
+            .expect("appends should keep working while degraded");
+    }
+
+    #[test]
+    fn observe_http_paging_backend_error_expected_surfaced_without_fallback() {
+        let degraded = AtomicBool::new(false);
+
+        let error = observe_http_paging::<()>(
+            &degraded,
+            Err(CxdbClientError::Backend(
+                "http json decode failed: expected value".to_string(),
+            )),
+        )
+        .expect_err("decode failures should be surfaced");
+
+        assert!(
+            matches!(error, CxdbClientError::Backend(message) if message.contains("json decode"))
+        );
+        assert!(!degraded.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn observe_http_paging_unsupported_endpoint_expected_fallback() {
+        let degraded = AtomicBool::new(false);
+
+        let error = observe_http_paging::<()>(
+            &degraded,
+            Err(CxdbClientError::Unsupported(
+                "http endpoint returned status 501 Not Implemented: ".to_string(),
+            )),
+        )
+        .expect_err("unsupported endpoints should report paging as unsupported");
+
+        assert!(
+            matches!(error, CxdbClientError::Unsupported(message) if message.contains("GET_LAST"))
+        );
+        assert!(degraded.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn ensure_bundle_compatible_with_older_bundle_expected_conflict() {
+        let backend = Arc::new(MockCxdb::default());
+        let store = CxdbRuntimeStore::new(backend.clone(), backend);
+        let bundle = serde_json::json!({
+            "registry_version": 1,
+            "bundle_id": "forge.test.bundle",
+            "types": {}
+        });
+        store
+            .publish_registry_bundle(
+                "forge.test.bundle",
+                &serde_json::to_vec(&bundle).expect("bundle should encode"),
+            )
+            .await
+            .expect("publish should succeed");
+
+        assert_eq!(
+            store
+                .ensure_bundle_compatible("forge.test.bundle", 1)
+                .await
+                .expect("same version should be compatible"),
+            1
+        );
+        let error = store
+            .ensure_bundle_compatible("forge.test.bundle", 2)
+            .await
+            .expect_err("newer client should require a republish");
+        assert!(matches!(error, CxdbClientError::Conflict(_)));
+        assert!(error.to_string().contains("republish"));
+
+        let missing = store
+            .ensure_bundle_compatible("forge.test.missing", 1)
+            .await
+            .expect_err("unpublished bundle should fail");
+        assert!(matches!(missing, CxdbClientError::NotFound { .. }));
+    }
 }
@@ -19,6 +19,7 @@ struct MockCxdbState {
     turns: BTreeMap<u64, BinaryStoredTurn>,
     idempotency: BTreeMap<String, u64>,
     blobs: BTreeMap<String, Vec<u8>>,
+    registry_bundles: BTreeMap<String, Vec<u8>>,
 }
 
 #[derive(Clone, Debug)]
@@ -146,6 +147,7 @@ impl CxdbBinaryClient for MockCxdb {
                     new_turn_id: existing_turn.turn_id,
                     new_depth: existing_turn.depth,
                     content_hash: existing_turn.content_hash,
+                    deduplicated: true,
                 });
             }
         }
@@ -217,6 +219,7 @@ impl CxdbBinaryClient for MockCxdb {
             new_turn_id: turn.turn_id,
             new_depth: turn.depth,
             content_hash,
+            deduplicated: false,
         })
     }
 
@@ -299,6 +302,14 @@ impl CxdbBinaryClient for MockCxdb {
         Ok(state.blobs.get(content_hash).cloned())
     }
 
+    async fn blob_exists(&self, content_hash: &String) -> Result<bool, CxdbClientError> {
+        let state = self
+            .inner
+            .lock()
+            .map_err(|_| CxdbClientError::Backend("mock backend mutex poisoned".to_string()))?;
+        Ok(state.blobs.contains_key(content_hash))
+    }
+
     async fn attach_fs(&self, turn_id: u64, fs_root_hash: &String) -> Result<(), CxdbClientError> {
         let state = self
             .inner
@@ -371,16 +382,35 @@ impl CxdbHttpClient for MockCxdb {
 
     async fn publish_registry_bundle(
         &self,
-        _bundle_id: &str,
-        _bundle_json: &[u8],
+        bundle_id: &str,
+        bundle_json: &[u8],
     ) -> Result<(), CxdbClientError> {
+        let mut state = self
+            .inner
+            .lock()
+            .map_err(|_| CxdbClientError::Backend("mock backend mutex poisoned".to_string()))?;
+        state
+            .registry_bundles
+            .insert(bundle_id.to_string(), bundle_json.to_vec());
         Ok(())
     }
 
     async fn get_registry_bundle(
         &self,
-        _bundle_id: &str,
+        bundle_id: &str,
     ) -> Result<Option<Vec<u8>>, CxdbClientError> {
-        Ok(None)
+        let state = self
+            .inner
+            .lock()
+            .map_err(|_| CxdbClientError::Backend("mock backend mutex poisoned".to_string()))?;
+        Ok(state.registry_bundles.get(bundle_id).cloned())
+    }
+
+    async fn blob_exists(&self, content_hash: &String) -> Result<bool, CxdbClientError> {
+        let state = self
+            .inner
+            .lock()
+            .map_err(|_| CxdbClientError::Backend("mock backend mutex poisoned".to_string()))?;
+        Ok(state.blobs.contains_key(content_hash))
     }
 }
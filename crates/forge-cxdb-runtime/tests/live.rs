@@ -279,6 +279,7 @@ impl CxdbBinaryClient for LiveHttpClient {
             new_depth: Self::parse_u32_field(&payload, "depth")?,
             content_hash: Self::parse_hash_hex(&payload, "content_hash")
                 .unwrap_or(request.content_hash),
+            deduplicated: false,
         })
     }
 
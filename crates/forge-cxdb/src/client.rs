@@ -7,10 +7,14 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use byteorder::{LittleEndian, WriteBytesExt};
-use rustls::pki_types::ServerName;
-use rustls::{ClientConfig, ClientConnection};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{ClientConfig, ClientConnection, DigitallySignedStruct, SignatureScheme};
 
 use crate::error::{Error, Result};
+use crate::metrics::{RequestMetrics, TelemetrySnapshot};
 use crate::protocol::{
     read_frame, write_frame, Frame, DEFAULT_DIAL_TIMEOUT, DEFAULT_REQUEST_TIMEOUT, MSG_ERROR,
     MSG_HELLO,
@@ -23,15 +27,38 @@ pub struct ClientOptions {
     pub dial_timeout: Duration,
     pub request_timeout: Duration,
     pub client_tag: String,
+    /// PEM bundles trusted by `dial_tls` instead of the platform store.
+    pub root_ca_pems: Vec<Vec<u8>>,
+    pub client_identity: std::option::Option<ClientIdentity>,
+    pub insecure_skip_verify: bool,
     pub(crate) tls_config: std::option::Option<Arc<ClientConfig>>,
 }
 
+/// PEM-encoded certificate chain and private key presented for mutual TLS.
+#[derive(Clone)]
+pub struct ClientIdentity {
+    pub cert_pem: Vec<u8>,
+    pub key_pem: Vec<u8>,
+}
+
+impl std::fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientIdentity")
+            .field("cert_pem", &format!("[{} bytes]", self.cert_pem.len()))
+            .field("key_pem", &"[redacted]")
+            .finish()
+    }
+}
+
 impl Default for ClientOptions {
     fn default() -> Self {
         Self {
             dial_timeout: DEFAULT_DIAL_TIMEOUT,
             request_timeout: DEFAULT_REQUEST_TIMEOUT,
             client_tag: String::new(),
+            root_ca_pems: Vec::new(),
+            client_identity: None,
+            insecure_skip_verify: false,
             tls_config: None,
         }
     }
@@ -50,6 +77,35 @@ pub fn with_client_tag(tag: impl Into<String>) -> ClientOption {
     Arc::new(move |opts| opts.client_tag = tag.clone())
 }
 
+/// Trusts the CA certificates in `pem` for `dial_tls`. Once any root CA is
+/// configured the platform trust store is no longer consulted; call this
+/// repeatedly to trust several bundles.
+pub fn with_root_ca(pem: impl Into<Vec<u8>>) -> ClientOption {
+    let pem = pem.into();
+    Arc::new(move |opts| opts.root_ca_pems.push(pem.clone()))
+}
+
+/// Presents the PEM certificate chain and private key to servers that
+/// require client authentication.
+pub fn with_client_identity(
+    cert_pem: impl Into<Vec<u8>>,
+    key_pem: impl Into<Vec<u8>>,
+) -> ClientOption {
+    let identity = ClientIdentity {
+        cert_pem: cert_pem.into(),
+        key_pem: key_pem.into(),
+    };
+    Arc::new(move |opts| opts.client_identity = Some(identity.clone()))
+}
+
+/// DANGER: disables server certificate and hostname verification for
+/// `dial_tls`, so any peer — including a man-in-the-middle — is accepted.
+/// Only for tests and local development against throwaway certificates;
+/// never enable this for a CXDB that carries real data.
+pub fn with_insecure_skip_verify(skip: bool) -> ClientOption {
+    Arc::new(move |opts| opts.insecure_skip_verify = skip)
+}
+
 #[cfg(test)]
 pub(crate) fn with_tls_config(config: Arc<ClientConfig>) -> ClientOption {
     Arc::new(move |opts| opts.tls_config = Some(config.clone()))
@@ -124,6 +180,7 @@ pub struct Client {
     timeout: Duration,
     session_id: AtomicU64,
     client_tag: String,
+    metrics: RequestMetrics,
 }
 
 impl Client {
@@ -143,6 +200,11 @@ impl Client {
         &self.client_tag
     }
 
+    /// Request counts and latencies per operation since the client was dialed.
+    pub fn telemetry_snapshot(&self) -> TelemetrySnapshot {
+        self.metrics.snapshot()
+    }
+
     pub(crate) fn send_request(
         &self,
         ctx: &RequestContext,
@@ -173,16 +235,11 @@ impl Client {
         conn.set_deadline(Some(effective_deadline))?;
 
         let req_id = self.req_id.fetch_add(1, Ordering::SeqCst) + 1;
-        write_frame(&mut *conn, msg_type, flags, req_id, payload)?;
-        let frame = read_frame(&mut *conn)?;
-
-        conn.set_deadline(None)?;
-
-        if frame.header.msg_type == MSG_ERROR {
-            return Err(parse_server_error(&frame.payload));
-        }
-
-        Ok(frame)
+        let started = Instant::now();
+        let result = round_trip(&mut conn, msg_type, flags, req_id, payload);
+        self.metrics
+            .record(msg_type, started.elapsed(), result.is_ok());
+        result
     }
 
     fn compute_deadline(&self, ctx: &RequestContext) -> Result<Instant> {
@@ -243,6 +300,7 @@ pub fn dial(addr: &str, opts: impl IntoIterator<Item = ClientOption>) -> Result<
         timeout: options.request_timeout,
         session_id: AtomicU64::new(0),
         client_tag: options.client_tag.clone(),
+        metrics: RequestMetrics::default(),
     };
 
     if let Err(err) = client.send_hello(&options.client_tag) {
@@ -264,7 +322,7 @@ pub fn dial_tls(addr: &str, opts: impl IntoIterator<Item = ClientOption>) -> Res
     let stream = connect_tcp(addr, options.dial_timeout)?;
     let config = match options.tls_config.take() {
         Some(cfg) => cfg,
-        None => Arc::new(default_tls_config()?),
+        None => Arc::new(build_tls_config(&options)?),
     };
 
     let server_name = server_name_from_addr(addr)?;
@@ -280,6 +338,7 @@ pub fn dial_tls(addr: &str, opts: impl IntoIterator<Item = ClientOption>) -> Res
         timeout: options.request_timeout,
         session_id: AtomicU64::new(0),
         client_tag: options.client_tag.clone(),
+        metrics: RequestMetrics::default(),
     };
 
     if let Err(err) = client.send_hello(&options.client_tag) {
@@ -312,18 +371,107 @@ fn connect_tcp(addr: &str, timeout: Duration) -> Result<TcpStream> {
         .unwrap_or(Error::Io(std::io::Error::other("no addresses resolved"))))
 }
 
-fn default_tls_config() -> Result<ClientConfig> {
+fn build_tls_config(options: &ClientOptions) -> Result<ClientConfig> {
+    let builder = ClientConfig::builder();
+    let builder = if options.insecure_skip_verify {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoServerVerification(provider)))
+    } else {
+        builder.with_root_certificates(root_cert_store(&options.root_ca_pems)?)
+    };
+
+    match &options.client_identity {
+        Some(identity) => {
+            let certs = parse_pem_certs(&identity.cert_pem, "client certificate")?;
+            let key = PrivateKeyDer::from_pem_slice(&identity.key_pem)
+                .map_err(|err| Error::Tls(format!("invalid client key PEM: {err}")))?;
+            builder
+                .with_client_auth_cert(certs, key)
+                .map_err(|err| Error::Tls(err.to_string()))
+        }
+        None => Ok(builder.with_no_client_auth()),
+    }
+}
+
+fn root_cert_store(root_ca_pems: &[Vec<u8>]) -> Result<rustls::RootCertStore> {
     let mut root_store = rustls::RootCertStore::empty();
-    let certs = rustls_native_certs::load_native_certs();
-    for cert in certs.certs {
+    let certs = if root_ca_pems.is_empty() {
+        rustls_native_certs::load_native_certs().certs
+    } else {
+        let mut certs = Vec::new();
+        for pem in root_ca_pems {
+            certs.extend(parse_pem_certs(pem, "root CA")?);
+        }
+        certs
+    };
+    for cert in certs {
         root_store
             .add(cert)
             .map_err(|err| Error::Tls(err.to_string()))?;
     }
-    let config = ClientConfig::builder()
-        .with_root_certificates(root_store)
-        .with_no_client_auth();
-    Ok(config)
+    Ok(root_store)
+}
+
+fn parse_pem_certs(pem: &[u8], what: &str) -> Result<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_slice_iter(pem)
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|err| Error::Tls(format!("invalid {what} PEM: {err}")))?;
+    if certs.is_empty() {
+        return Err(Error::Tls(format!("no certificates found in {what} PEM")));
+    }
+    Ok(certs)
+}
+
+/// Accepts any server certificate; see [`with_insecure_skip_verify`].
+/// Handshake signatures are still checked so the session keys are sound.
+#[derive(Debug)]
+struct NoServerVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for NoServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
 }
 
 fn server_name_from_addr(addr: &str) -> Result<ServerName<'static>> {
@@ -341,6 +489,47 @@ fn server_name_from_addr(addr: &str) -> Result<ServerName<'static>> {
         .map_err(|_| Error::Tls(format!("invalid server name: {host}")))
 }
 
+fn round_trip(
+    conn: &mut Connection,
+    msg_type: u16,
+    flags: u16,
+    req_id: u64,
+    payload: &[u8],
+) -> Result<Frame> {
+    let frame =
+        match write_frame(conn, msg_type, flags, req_id, payload).and_then(|()| read_frame(conn)) {
+            Ok(frame) => frame,
+            Err(err) if is_deadline_error(&err) => {
+                // The response may still arrive later; shut the socket down so
+                // the next request sees a connection error instead of reading
+                // this request's stale frame.
+                let _ = conn.close();
+                return Err(Error::Timeout);
+            }
+            Err(err) => return Err(err),
+        };
+
+    conn.set_deadline(None)?;
+
+    if frame.header.msg_type == MSG_ERROR {
+        return Err(parse_server_error(&frame.payload));
+    }
+
+    Ok(frame)
+}
+
+/// Socket reads and writes that hit the timeout set from the request
+/// deadline surface as `WouldBlock` (Unix) or `TimedOut` (Windows).
+fn is_deadline_error(err: &Error) -> bool {
+    matches!(
+        err,
+        Error::Io(io_err) if matches!(
+            io_err.kind(),
+            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+        )
+    )
+}
+
 fn parse_server_error(payload: &[u8]) -> Error {
     if payload.len() < 8 {
         return Error::server(0, "unknown error");
@@ -420,7 +609,7 @@ mod tests {
     use super::*;
     use crate::protocol::{read_frame, write_frame, FrameHeader, MSG_HELLO};
     use crate::test_util::{decode_hex, load_fixture};
-    use rcgen::{CertificateParams, DistinguishedName, DnType, KeyPair};
+    use rcgen::{BasicConstraints, CertificateParams, DistinguishedName, DnType, IsCa, KeyPair};
     use rustls::ServerConfig;
     use std::net::TcpListener;
     use std::thread;
@@ -638,4 +827,200 @@ mod tests {
             )),
         )
     }
+
+    #[test]
+    fn request_context_timeout_cancels_slow_get_last() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let frame = read_frame(&mut stream).unwrap();
+            assert_eq!(frame.header.msg_type, MSG_HELLO);
+            let mut resp = Vec::new();
+            resp.write_u64::<LittleEndian>(1).unwrap();
+            resp.write_u16::<LittleEndian>(1).unwrap();
+            write_frame(&mut stream, MSG_HELLO, 0, frame.header.req_id, &resp).unwrap();
+
+            let req = read_frame(&mut stream).unwrap();
+            assert_eq!(req.header.msg_type, crate::protocol::MSG_GET_LAST);
+            // Never answer; hold the socket open until the client gives up.
+            let _ = done_rx.recv_timeout(Duration::from_secs(10));
+        });
+
+        let client = dial(
+            &addr.to_string(),
+            vec![with_request_timeout(Duration::from_secs(10))],
+        )
+        .unwrap();
+        let ctx = RequestContext::with_timeout(Duration::from_millis(100));
+        let started = Instant::now();
+        let err = client
+            .get_last(&ctx, 1, crate::turn::GetLastOptions::default())
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::Timeout),
+            "expected timeout, got {err:?}"
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let err = client
+            .get_last(
+                &RequestContext::background(),
+                1,
+                crate::turn::GetLastOptions::default(),
+            )
+            .unwrap_err();
+        assert!(
+            crate::reconnect::is_connection_error(&err),
+            "expected connection error after timeout, got {err:?}"
+        );
+
+        done_tx.send(()).unwrap();
+        handle.join().unwrap();
+    }
+
+    struct TestPki {
+        ca_pem: String,
+        server_cert: CertificateDer<'static>,
+        server_key: PrivateKeyDer<'static>,
+        client_cert_pem: String,
+        client_key_pem: String,
+    }
+
+    fn generate_pki() -> TestPki {
+        let mut ca_params = CertificateParams::new(Vec::new()).unwrap();
+        ca_params
+            .distinguished_name
+            .push(DnType::CommonName, "cxdb-test-ca");
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca_key = KeyPair::generate().unwrap();
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let server_key = KeyPair::generate().unwrap();
+        let server = CertificateParams::new(vec!["localhost".to_string()])
+            .unwrap()
+            .signed_by(&server_key, &ca, &ca_key)
+            .unwrap();
+
+        let client_key = KeyPair::generate().unwrap();
+        let mut client_params = CertificateParams::new(Vec::new()).unwrap();
+        client_params
+            .distinguished_name
+            .push(DnType::CommonName, "cxdb-test-client");
+        let client = client_params.signed_by(&client_key, &ca, &ca_key).unwrap();
+
+        TestPki {
+            ca_pem: ca.pem(),
+            server_cert: server.der().clone(),
+            server_key: PrivateKeyDer::from(rustls::pki_types::PrivatePkcs8KeyDer::from(
+                server_key.serialize_der(),
+            )),
+            client_cert_pem: client.pem(),
+            client_key_pem: client_key.serialize_pem(),
+        }
+    }
+
+    fn server_config(pki: &TestPki, require_client_cert: bool) -> Arc<ServerConfig> {
+        let builder = ServerConfig::builder();
+        let builder = if require_client_cert {
+            let mut roots = rustls::RootCertStore::empty();
+            for cert in parse_pem_certs(pki.ca_pem.as_bytes(), "root CA").unwrap() {
+                roots.add(cert).unwrap();
+            }
+            let verifier = rustls::server::WebPkiClientVerifier::builder(Arc::new(roots))
+                .build()
+                .unwrap();
+            builder.with_client_cert_verifier(verifier)
+        } else {
+            builder.with_no_client_auth()
+        };
+        Arc::new(
+            builder
+                .with_single_cert(vec![pki.server_cert.clone()], pki.server_key.clone_key())
+                .unwrap(),
+        )
+    }
+
+    /// Serves one HELLO over TLS; the handle yields whether it got that far.
+    fn spawn_tls_hello_server(config: Arc<ServerConfig>) -> (String, thread::JoinHandle<bool>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = format!("localhost:{}", listener.local_addr().unwrap().port());
+        let handle = thread::spawn(move || {
+            let (tcp, _) = listener.accept().unwrap();
+            let conn = rustls::ServerConnection::new(config).unwrap();
+            let mut stream = rustls::StreamOwned::new(conn, tcp);
+            let Ok(frame) = read_frame(&mut stream) else {
+                return false;
+            };
+            let mut resp = Vec::new();
+            resp.write_u64::<LittleEndian>(7).unwrap();
+            resp.write_u16::<LittleEndian>(1).unwrap();
+            write_frame(&mut stream, MSG_HELLO, 0, frame.header.req_id, &resp).is_ok()
+        });
+        (addr, handle)
+    }
+
+    #[test]
+    fn dial_tls_trusts_custom_root_ca() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let pki = generate_pki();
+        let (addr, server) = spawn_tls_hello_server(server_config(&pki, false));
+
+        let client = dial_tls(&addr, vec![with_root_ca(pki.ca_pem.clone())]).unwrap();
+        assert_eq!(client.session_id(), 7);
+        assert!(server.join().unwrap());
+    }
+
+    #[test]
+    fn dial_tls_rejects_private_ca_without_root() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let pki = generate_pki();
+        let (addr, server) = spawn_tls_hello_server(server_config(&pki, false));
+
+        assert!(dial_tls(&addr, Vec::new()).is_err());
+        assert!(!server.join().unwrap());
+    }
+
+    #[test]
+    fn dial_tls_insecure_skip_verify_accepts_untrusted_server() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let pki = generate_pki();
+        let (addr, server) = spawn_tls_hello_server(server_config(&pki, false));
+
+        let client = dial_tls(&addr, vec![with_insecure_skip_verify(true)]).unwrap();
+        assert_eq!(client.session_id(), 7);
+        assert!(server.join().unwrap());
+    }
+
+    #[test]
+    fn dial_tls_presents_client_identity() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let pki = generate_pki();
+        let (addr, server) = spawn_tls_hello_server(server_config(&pki, true));
+        let client = dial_tls(
+            &addr,
+            vec![
+                with_root_ca(pki.ca_pem.clone()),
+                with_client_identity(pki.client_cert_pem.clone(), pki.client_key_pem.clone()),
+            ],
+        )
+        .unwrap();
+        assert_eq!(client.session_id(), 7);
+        assert!(server.join().unwrap());
+
+        let (addr, server) = spawn_tls_hello_server(server_config(&pki, true));
+        assert!(dial_tls(&addr, vec![with_root_ca(pki.ca_pem.clone())]).is_err());
+        assert!(!server.join().unwrap());
+    }
+
+    #[test]
+    fn invalid_root_ca_pem_is_a_tls_error() {
+        let options = ClientOptions {
+            root_ca_pems: vec![b"not a certificate".to_vec()],
+            ..ClientOptions::default()
+        };
+        assert!(matches!(build_tls_config(&options), Err(Error::Tls(_))));
+    }
 }
@@ -9,7 +9,7 @@ use crate::error::{Error, Result};
 use crate::protocol::{
     ENCODING_MSGPACK, MSG_APPEND_TURN, MSG_ATTACH_FS, MSG_GET_BLOB, MSG_PUT_BLOB,
 };
-use crate::turn::{AppendRequest, AppendResult};
+use crate::turn::{parse_append_result, AppendRequest, AppendResult};
 
 #[derive(Debug, Clone)]
 pub struct AttachFsRequest {
@@ -153,24 +153,7 @@ impl Client {
         }
 
         let frame = self.send_request_with_flags(ctx, MSG_APPEND_TURN, flags, &payload)?;
-        if frame.payload.len() < 52 {
-            return Err(Error::invalid_response(format!(
-                "append response too short ({} bytes)",
-                frame.payload.len()
-            )));
-        }
-        let mut cursor = std::io::Cursor::new(frame.payload);
-        let context_id = cursor.read_u64::<LittleEndian>()?;
-        let turn_id = cursor.read_u64::<LittleEndian>()?;
-        let depth = cursor.read_u32::<LittleEndian>()?;
-        let mut hash = [0u8; 32];
-        cursor.read_exact(&mut hash)?;
-        Ok(AppendResult {
-            context_id,
-            turn_id,
-            depth,
-            payload_hash: hash,
-        })
+        parse_append_result(&frame.payload)
     }
 }
 
@@ -13,8 +13,8 @@ use crate::encoding::encode_msgpack;
 
 use super::options::{Options, SnapshotOption};
 use super::types::{
-    EntryKindDirectory, EntryKindFile, EntryKindSymlink, FileRef, Snapshot, SnapshotStats,
-    TreeEntry,
+    CaptureProgress, EntryKindDirectory, EntryKindFile, EntryKindSymlink, FileRef, Snapshot,
+    SnapshotStats, TreeEntry,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -22,6 +22,8 @@ pub enum FstreeErrorKind {
     TooManyFiles,
     FileTooLarge,
     CyclicLink,
+    HashMismatch,
+    UnsafePath,
     Io,
     Msgpack,
     Client,
@@ -77,13 +79,14 @@ pub fn capture(
         ));
     }
 
-    let mut options = Options::default();
-    for opt in opts {
-        opt(&mut options);
-    }
+    let opts: Vec<SnapshotOption> = opts.into_iter().collect();
+    let options = Options::from_opts(&opts);
 
     let mut builder = Builder::new(options);
     let root_hash = builder.build_tree(&abs_root, Path::new(""))?;
+    if builder.file_count != builder.last_reported_files {
+        builder.report_progress();
+    }
 
     Ok(Snapshot {
         root_hash,
@@ -116,6 +119,7 @@ struct Builder {
     dir_count: usize,
     symlink_count: usize,
     total_bytes: u64,
+    last_reported_files: usize,
 }
 
 impl Builder {
@@ -130,6 +134,17 @@ impl Builder {
             dir_count: 0,
             symlink_count: 0,
             total_bytes: 0,
+            last_reported_files: 0,
+        }
+    }
+
+    fn report_progress(&mut self) {
+        if let Some(on_progress) = &self.options.on_progress {
+            on_progress(CaptureProgress {
+                files_seen: self.file_count,
+                bytes_seen: self.total_bytes,
+            });
+            self.last_reported_files = self.file_count;
         }
     }
 
@@ -267,6 +282,13 @@ impl Builder {
         );
         self.file_count += 1;
         self.total_bytes += size;
+        if self.options.on_progress.is_some()
+            && self
+                .file_count
+                .is_multiple_of(self.options.progress_interval)
+        {
+            self.report_progress();
+        }
 
         Ok(TreeEntry {
             name: name.to_string(),
@@ -3,6 +3,7 @@
 
 mod capture;
 mod options;
+mod restore;
 mod snapshot;
 mod tracker;
 mod types;
@@ -14,14 +15,15 @@ pub use capture::{
 };
 pub use options::{
     with_exclude, with_exclude_func, with_follow_symlinks, with_max_file_size, with_max_files,
-    Options, SnapshotOption,
+    with_progress, with_progress_interval, with_upload_concurrency, Options, SnapshotOption,
 };
+pub use restore::{restore, RestoreResult};
 pub use tracker::Tracker;
 pub use types::{
-    EntryKind, EntryKindDirectory, EntryKindFile, EntryKindSymlink, FileRef, Snapshot,
-    SnapshotDiff, SnapshotStats, TreeEntry, TreeObject,
+    CaptureProgress, EntryKind, EntryKindDirectory, EntryKindFile, EntryKindSymlink, FileRef,
+    Snapshot, SnapshotDiff, SnapshotStats, TreeEntry, TreeObject, UploadProgress,
 };
-pub use upload::{capture_and_upload, upload_and_attach, UploadResult};
+pub use upload::{capture_and_upload, upload_and_attach, BlobUploader, UploadResult};
 
 /// Go-parity alias for snapshot option type.
 pub type Option = SnapshotOption;
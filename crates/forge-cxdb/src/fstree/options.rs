@@ -8,6 +8,8 @@ use std::sync::Arc;
 
 use glob::Pattern;
 
+use super::types::CaptureProgress;
+
 pub type SnapshotOption = Arc<dyn Fn(&mut Options) + Send + Sync>;
 
 #[derive(Clone)]
@@ -17,6 +19,13 @@ pub struct Options {
     pub follow_symlinks: bool,
     pub max_file_size: i64,
     pub max_files: usize,
+    pub on_progress: std::option::Option<Arc<dyn Fn(CaptureProgress) + Send + Sync>>,
+    /// Number of files between progress callbacks.
+    pub progress_interval: usize,
+    /// Number of blobs uploaded in parallel by `upload_and_attach` and
+    /// `capture_and_upload`. Requests only overlap when the client has that
+    /// many connections, e.g. a `PooledClient`.
+    pub upload_concurrency: usize,
 }
 
 impl Default for Options {
@@ -27,6 +36,9 @@ impl Default for Options {
             follow_symlinks: false,
             max_file_size: 100 * 1024 * 1024,
             max_files: 100_000,
+            on_progress: None,
+            progress_interval: 256,
+            upload_concurrency: 1,
         }
     }
 }
@@ -58,7 +70,33 @@ pub fn with_max_files(count: usize) -> SnapshotOption {
     Arc::new(move |opts| opts.max_files = count)
 }
 
+/// Invokes `func` every `progress_interval` files during capture, and once
+/// more with the final totals.
+pub fn with_progress<F>(func: F) -> SnapshotOption
+where
+    F: Fn(CaptureProgress) + Send + Sync + 'static,
+{
+    let func = Arc::new(func);
+    Arc::new(move |opts| opts.on_progress = Some(func.clone()))
+}
+
+pub fn with_progress_interval(files: usize) -> SnapshotOption {
+    Arc::new(move |opts| opts.progress_interval = files.max(1))
+}
+
+pub fn with_upload_concurrency(workers: usize) -> SnapshotOption {
+    Arc::new(move |opts| opts.upload_concurrency = workers.max(1))
+}
+
 impl Options {
+    pub(crate) fn from_opts<'a>(opts: impl IntoIterator<Item = &'a SnapshotOption>) -> Self {
+        let mut options = Options::default();
+        for opt in opts {
+            opt(&mut options);
+        }
+        options
+    }
+
     pub fn should_exclude(&self, rel_path: &str, is_dir: bool) -> bool {
         if let Some(func) = &self.exclude_fn {
             if func(rel_path, is_dir) {
@@ -0,0 +1,207 @@
+// Copyright 2025 StrongDM Inc
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::client::RequestContext;
+use crate::fs::GetBlobRequest;
+use crate::Client;
+
+use super::capture::{deserialize_tree, FstreeError, FstreeErrorKind, Result};
+use super::options::{Options, SnapshotOption};
+use super::types::{EntryKindDirectory, EntryKindFile, EntryKindSymlink, TreeEntry};
+
+#[derive(Debug, Clone, Default)]
+pub struct RestoreResult {
+    pub root_hash: [u8; 32],
+    pub files_restored: usize,
+    pub dirs_restored: usize,
+    pub symlinks_restored: usize,
+    pub bytes_written: u64,
+}
+
+/// Materializes the stored tree `root_hash` under `dest_dir`, the inverse of
+/// `capture` + `upload`. Exclude patterns and size/count limits from `opts`
+/// apply to the restored entries. Every file is verified against its hash,
+/// and nothing is written outside `dest_dir` or through an existing symlink.
+pub fn restore(
+    ctx: &RequestContext,
+    client: &Client,
+    root_hash: [u8; 32],
+    dest_dir: impl AsRef<Path>,
+    opts: impl IntoIterator<Item = SnapshotOption>,
+) -> Result<RestoreResult> {
+    let opts: Vec<SnapshotOption> = opts.into_iter().collect();
+    let options = Options::from_opts(&opts);
+
+    fs::create_dir_all(dest_dir.as_ref()).map_err(io_error)?;
+    let abs_dest = fs::canonicalize(dest_dir.as_ref()).map_err(io_error)?;
+
+    let mut restorer = Restorer {
+        ctx,
+        client,
+        options,
+        result: RestoreResult {
+            root_hash,
+            ..RestoreResult::default()
+        },
+    };
+    restorer.restore_tree(root_hash, &abs_dest, Path::new(""))?;
+    Ok(restorer.result)
+}
+
+struct Restorer<'a> {
+    ctx: &'a RequestContext,
+    client: &'a Client,
+    options: Options,
+    result: RestoreResult,
+}
+
+impl Restorer<'_> {
+    fn restore_tree(&mut self, hash: [u8; 32], abs_dir: &Path, rel_dir: &Path) -> Result<()> {
+        let entries = deserialize_tree(&self.fetch_blob(hash)?)?;
+        for entry in &entries {
+            validate_entry_name(&entry.name)?;
+            let rel_path = rel_dir.join(&entry.name);
+            let is_dir = entry.kind == EntryKindDirectory;
+            if self
+                .options
+                .should_exclude(&rel_path.to_string_lossy(), is_dir)
+            {
+                continue;
+            }
+            self.restore_entry(entry, &abs_dir.join(&entry.name), &rel_path)?;
+        }
+        Ok(())
+    }
+
+    fn restore_entry(&mut self, entry: &TreeEntry, abs_path: &Path, rel_path: &Path) -> Result<()> {
+        refuse_existing_symlink(abs_path)?;
+        if entry.kind == EntryKindDirectory {
+            fs::create_dir_all(abs_path).map_err(io_error)?;
+            self.restore_tree(entry.hash, abs_path, rel_path)?;
+            // Applied after the children so read-only directories can be filled.
+            set_mode(abs_path, entry.mode)?;
+            self.result.dirs_restored += 1;
+        } else if entry.kind == EntryKindFile {
+            self.restore_file(entry, abs_path, rel_path)?;
+        } else if entry.kind == EntryKindSymlink {
+            let target = String::from_utf8(self.fetch_blob(entry.hash)?).map_err(|_| {
+                FstreeError::new(
+                    FstreeErrorKind::Other,
+                    format!("symlink target is not UTF-8: {}", rel_path.display()),
+                )
+            })?;
+            create_symlink(&target, abs_path)?;
+            self.result.symlinks_restored += 1;
+        } else {
+            return Err(FstreeError::new(
+                FstreeErrorKind::Other,
+                format!(
+                    "unknown entry kind {} for {}",
+                    entry.kind,
+                    rel_path.display()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    fn restore_file(&mut self, entry: &TreeEntry, abs_path: &Path, rel_path: &Path) -> Result<()> {
+        if self.result.files_restored >= self.options.max_files {
+            return Err(FstreeError::new(
+                FstreeErrorKind::TooManyFiles,
+                "too many files",
+            ));
+        }
+        if entry.size as i64 > self.options.max_file_size {
+            return Err(FstreeError::new(
+                FstreeErrorKind::FileTooLarge,
+                format!(
+                    "file too large: {} ({} bytes)",
+                    rel_path.display(),
+                    entry.size
+                ),
+            ));
+        }
+        let data = self.fetch_blob(entry.hash)?;
+        if *blake3::hash(&data).as_bytes() != entry.hash {
+            return Err(FstreeError::new(
+                FstreeErrorKind::HashMismatch,
+                format!("content hash mismatch for {}", rel_path.display()),
+            ));
+        }
+        fs::write(abs_path, &data).map_err(io_error)?;
+        set_mode(abs_path, entry.mode)?;
+        self.result.files_restored += 1;
+        self.result.bytes_written += data.len() as u64;
+        Ok(())
+    }
+
+    fn fetch_blob(&self, hash: [u8; 32]) -> Result<Vec<u8>> {
+        self.client
+            .get_blob(self.ctx, &GetBlobRequest { hash })
+            .map(|blob| blob.data)
+            .map_err(|err| FstreeError::new(FstreeErrorKind::Client, err.to_string()))
+    }
+}
+
+/// Entry names must be a single normal path component so joins stay inside
+/// the destination.
+fn validate_entry_name(name: &str) -> Result<()> {
+    let unsafe_name = name.is_empty()
+        || name == "."
+        || name == ".."
+        || name.contains(['/', '\\', '\0'])
+        || Path::new(name).is_absolute();
+    if unsafe_name {
+        return Err(FstreeError::new(
+            FstreeErrorKind::UnsafePath,
+            format!("refusing to restore unsafe entry name {name:?}"),
+        ));
+    }
+    Ok(())
+}
+
+fn refuse_existing_symlink(path: &Path) -> Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_symlink() => Err(FstreeError::new(
+            FstreeErrorKind::UnsafePath,
+            format!("refusing to write through symlink {}", path.display()),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn io_error(err: std::io::Error) -> FstreeError {
+    FstreeError::new(FstreeErrorKind::Io, err.to_string())
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    if mode == 0 {
+        return Ok(());
+    }
+    fs::set_permissions(path, fs::Permissions::from_mode(mode)).map_err(io_error)
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &str, link: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(PathBuf::from(target), link).map_err(io_error)
+}
+
+#[cfg(not(unix))]
+fn create_symlink(target: &str, link: &Path) -> Result<()> {
+    let _ = (target, link);
+    Err(FstreeError::new(
+        FstreeErrorKind::Other,
+        "restoring symlinks is only supported on unix",
+    ))
+}
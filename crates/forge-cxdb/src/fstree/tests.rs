@@ -38,11 +38,7 @@ fn seed_workspace(root: &std::path::Path) {
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
-        fs::set_permissions(
-            root.join("src"),
-            fs::Permissions::from_mode(0o755),
-        )
-        .unwrap();
+        fs::set_permissions(root.join("src"), fs::Permissions::from_mode(0o755)).unwrap();
     }
     write_file(root.join("README.md"), b"# Test", 0o644);
     write_file(root.join("src").join("main.go"), b"package main", 0o644);
@@ -315,3 +311,302 @@ fn tracker_snapshot_if_changed() {
     assert!(!changed2);
     assert!(snap2.is_none());
 }
+
+#[test]
+fn capture_reports_progress_with_increasing_counts() {
+    let dir = TempDir::new().unwrap();
+    seed_workspace(dir.path());
+    let reports = std::sync::Arc::new(std::sync::Mutex::new(Vec::<CaptureProgress>::new()));
+    let sink = reports.clone();
+
+    let snap = capture(
+        dir.path(),
+        vec![
+            with_progress(move |progress| sink.lock().unwrap().push(progress)),
+            with_progress_interval(1),
+        ],
+    )
+    .unwrap();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), snap.stats.file_count);
+    assert!(reports
+        .windows(2)
+        .all(|pair| pair[1].files_seen > pair[0].files_seen
+            && pair[1].bytes_seen >= pair[0].bytes_seen));
+    let last = reports.last().unwrap();
+    assert_eq!(last.files_seen, snap.stats.file_count);
+    assert_eq!(last.bytes_seen, snap.stats.total_bytes);
+}
+
+type BlobStore = HashMap<[u8; 32], Vec<u8>>;
+
+/// Serves HELLO, PUT_BLOB and GET_BLOB on one connection, deduplicating blobs
+/// by hash. Returns the address and a handle yielding the stored blobs.
+fn spawn_blob_server() -> (String, std::thread::JoinHandle<BlobStore>) {
+    use crate::protocol::{read_frame, write_frame, MSG_GET_BLOB, MSG_HELLO, MSG_PUT_BLOB};
+    use byteorder::{LittleEndian, WriteBytesExt};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let handle = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        stream.set_nodelay(true).unwrap();
+        let mut stored = HashMap::new();
+        while let Ok(frame) = read_frame(&mut stream) {
+            let response = match frame.header.msg_type {
+                MSG_HELLO => {
+                    let mut resp = Vec::new();
+                    resp.write_u64::<LittleEndian>(1).unwrap();
+                    resp.write_u16::<LittleEndian>(1).unwrap();
+                    resp
+                }
+                MSG_PUT_BLOB => {
+                    let mut hash = [0u8; 32];
+                    hash.copy_from_slice(&frame.payload[..32]);
+                    let was_new = !stored.contains_key(&hash);
+                    stored.insert(hash, frame.payload[36..].to_vec());
+                    let mut resp = hash.to_vec();
+                    resp.push(u8::from(was_new));
+                    resp
+                }
+                MSG_GET_BLOB => {
+                    let data = stored.get(&frame.payload[..32]).expect("blob stored");
+                    let mut resp = Vec::new();
+                    resp.write_u32::<LittleEndian>(data.len() as u32).unwrap();
+                    resp.extend_from_slice(data);
+                    resp
+                }
+                other => panic!("unexpected message type {other}"),
+            };
+            write_frame(
+                &mut stream,
+                frame.header.msg_type,
+                0,
+                frame.header.req_id,
+                &response,
+            )
+            .unwrap();
+        }
+        stored
+    });
+    (addr, handle)
+}
+
+#[test]
+fn concurrent_upload_matches_sequential_upload() {
+    let dir = TempDir::new().unwrap();
+    seed_workspace(dir.path());
+    for i in 0..40 {
+        let sub = dir.path().join(format!("pkg{}", i % 4));
+        fs::create_dir_all(&sub).unwrap();
+        write_file(
+            sub.join(format!("f{i}.txt")),
+            format!("file {i}").as_bytes(),
+            0o644,
+        );
+    }
+    // Identical content in two files exercises hash dedup.
+    write_file(dir.path().join("dup.txt"), b"file 0", 0o644);
+
+    let upload = |concurrency: usize| {
+        let (addr, server) = spawn_blob_server();
+        let client = crate::dial(&addr, Vec::new()).unwrap();
+        let ctx = crate::RequestContext::background();
+        let (snapshot, result) = capture_and_upload(
+            &ctx,
+            &client,
+            dir.path(),
+            vec![with_upload_concurrency(concurrency)],
+        )
+        .unwrap();
+        drop(client);
+        (snapshot, result, server.join().unwrap())
+    };
+
+    let (sequential_snap, sequential, sequential_blobs) = upload(1);
+    let (parallel_snap, parallel, parallel_blobs) = upload(8);
+
+    assert_eq!(sequential_snap.root_hash, parallel_snap.root_hash);
+    assert_eq!(sequential.root_hash, parallel.root_hash);
+    assert_eq!(parallel.root_hash, parallel_snap.root_hash);
+    assert_eq!(sequential.trees_uploaded, parallel.trees_uploaded);
+    assert_eq!(sequential.files_uploaded, parallel.files_uploaded);
+    assert_eq!(sequential.files_skipped, parallel.files_skipped);
+    assert_eq!(sequential.bytes_uploaded, parallel.bytes_uploaded);
+    assert_eq!(sequential_blobs, parallel_blobs);
+    assert_eq!(
+        parallel_blobs.len(),
+        parallel_snap.trees.len() + parallel_snap.files.len()
+    );
+}
+
+/// Serves HELLO and PUT_BLOB on `connections` connections, holding each
+/// upload briefly. Returns the address and a handle yielding the largest
+/// number of uploads observed in flight at once.
+fn spawn_slow_blob_server(connections: usize) -> (String, std::thread::JoinHandle<usize>) {
+    use crate::protocol::{read_frame, write_frame, MSG_HELLO, MSG_PUT_BLOB};
+    use byteorder::{LittleEndian, WriteBytesExt};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let handle = std::thread::spawn(move || {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let mut handlers = Vec::new();
+        for session in 1..=connections as u64 {
+            let (mut stream, _) = listener.accept().unwrap();
+            let (in_flight, max_in_flight) = (in_flight.clone(), max_in_flight.clone());
+            handlers.push(std::thread::spawn(move || {
+                while let Ok(frame) = read_frame(&mut stream) {
+                    let response = match frame.header.msg_type {
+                        MSG_HELLO => {
+                            let mut resp = Vec::new();
+                            resp.write_u64::<LittleEndian>(session).unwrap();
+                            resp.write_u16::<LittleEndian>(1).unwrap();
+                            resp
+                        }
+                        MSG_PUT_BLOB => {
+                            let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                            max_in_flight.fetch_max(now, Ordering::SeqCst);
+                            std::thread::sleep(std::time::Duration::from_millis(20));
+                            in_flight.fetch_sub(1, Ordering::SeqCst);
+                            let mut resp = frame.payload[..32].to_vec();
+                            resp.push(1);
+                            resp
+                        }
+                        other => panic!("unexpected message type {other}"),
+                    };
+                    write_frame(
+                        &mut stream,
+                        frame.header.msg_type,
+                        0,
+                        frame.header.req_id,
+                        &response,
+                    )
+                    .unwrap();
+                }
+            }));
+        }
+        for handler in handlers {
+            handler.join().unwrap();
+        }
+        max_in_flight.load(Ordering::SeqCst)
+    });
+    (addr, handle)
+}
+
+#[test]
+fn concurrent_upload_through_pool_overlaps_requests() {
+    let dir = TempDir::new().unwrap();
+    for i in 0..16 {
+        write_file(
+            dir.path().join(format!("f{i}.txt")),
+            format!("file {i}").as_bytes(),
+            0o644,
+        );
+    }
+    let ctx = crate::RequestContext::background();
+
+    let (addr, server) = spawn_slow_blob_server(4);
+    let pool = crate::dial_pool(&addr, 4, Vec::new()).unwrap();
+    let (snapshot, result) =
+        capture_and_upload(&ctx, &pool, dir.path(), vec![with_upload_concurrency(4)]).unwrap();
+    pool.close().unwrap();
+    drop(pool);
+    assert!(server.join().unwrap() > 1);
+    assert_eq!(result.files_uploaded, snapshot.files.len());
+
+    // One client is one socket: the same workers cannot overlap.
+    let (addr, server) = spawn_slow_blob_server(1);
+    let client = crate::dial(&addr, Vec::new()).unwrap();
+    capture_and_upload(&ctx, &client, dir.path(), vec![with_upload_concurrency(4)]).unwrap();
+    drop(client);
+    assert_eq!(server.join().unwrap(), 1);
+}
+
+#[test]
+fn restore_round_trips_uploaded_tree() {
+    let source = TempDir::new().unwrap();
+    seed_workspace(source.path());
+    #[cfg(unix)]
+    std::os::unix::fs::symlink("README.md", source.path().join("link")).unwrap();
+
+    let (addr, server) = spawn_blob_server();
+    let client = crate::dial(&addr, Vec::new()).unwrap();
+    let ctx = crate::RequestContext::background();
+    let (snapshot, _) =
+        capture_and_upload(&ctx, &client, source.path(), Vec::<SnapshotOption>::new()).unwrap();
+
+    let dest = TempDir::new().unwrap();
+    let restored = restore(
+        &ctx,
+        &client,
+        snapshot.root_hash,
+        dest.path().join("workspace"),
+        Vec::<SnapshotOption>::new(),
+    )
+    .unwrap();
+    drop(client);
+    server.join().unwrap();
+
+    assert_eq!(restored.files_restored, snapshot.stats.file_count);
+    assert_eq!(restored.bytes_written, snapshot.stats.total_bytes);
+    let restored_root = dest.path().join("workspace");
+    for rel in ["README.md", "script.sh", "src/main.go", "src/lib.go"] {
+        assert_eq!(
+            fs::read(source.path().join(rel)).unwrap(),
+            fs::read(restored_root.join(rel)).unwrap(),
+            "content of {rel}"
+        );
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(restored_root.join("script.sh"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o755);
+        assert_eq!(
+            fs::read_link(restored_root.join("link")).unwrap(),
+            std::path::PathBuf::from("README.md")
+        );
+    }
+    let recaptured = capture(&restored_root, Vec::<SnapshotOption>::new()).unwrap();
+    assert_eq!(recaptured.root_hash, snapshot.root_hash);
+}
+
+#[test]
+fn restore_rejects_entry_names_escaping_destination() {
+    let (addr, server) = spawn_blob_server();
+    let client = crate::dial(&addr, Vec::new()).unwrap();
+    let ctx = crate::RequestContext::background();
+    let content = b"escaped".to_vec();
+    let tree = crate::encode_msgpack(&vec![TreeEntry {
+        name: "..".to_string(),
+        kind: EntryKindFile,
+        mode: 0o644,
+        size: content.len() as u64,
+        hash: *blake3::hash(&content).as_bytes(),
+    }])
+    .unwrap();
+    client.put_blob_if_absent(&ctx, content).unwrap();
+    let (root_hash, _) = client.put_blob_if_absent(&ctx, tree).unwrap();
+
+    let dest = TempDir::new().unwrap();
+    let err = restore(
+        &ctx,
+        &client,
+        root_hash,
+        dest.path(),
+        Vec::<SnapshotOption>::new(),
+    )
+    .unwrap_err();
+    drop(client);
+    server.join().unwrap();
+
+    assert_eq!(err.kind, FstreeErrorKind::UnsafePath);
+}
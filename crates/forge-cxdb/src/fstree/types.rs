@@ -61,6 +61,22 @@ pub struct SnapshotStats {
     pub duration: Duration,
 }
 
+/// Running totals reported to a capture progress callback.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CaptureProgress {
+    pub files_seen: usize,
+    pub bytes_seen: u64,
+}
+
+/// Reported to an upload progress callback after each blob.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UploadProgress {
+    pub blobs_done: usize,
+    pub blobs_total: usize,
+    pub bytes_uploaded: i64,
+    pub was_new: bool,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct SnapshotDiff {
     pub added: Vec<String>,
@@ -1,12 +1,20 @@
 // Copyright 2025 StrongDM Inc
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+
 use crate::client::RequestContext;
-use crate::fs::PutBlobRequest;
+use crate::fs::{AttachFsRequest, AttachFsResult, PutBlobRequest, PutBlobResult};
+use crate::pool::PooledClient;
+use crate::reconnect::ReconnectingClient;
 use crate::Client;
 
 use super::capture::{FstreeError, FstreeErrorKind, Result as FstreeResult};
-use super::types::Snapshot;
+use super::options::{Options, SnapshotOption};
+use super::types::{Snapshot, UploadProgress};
 
 #[derive(Debug, Clone, Default)]
 pub struct UploadResult {
@@ -18,75 +26,263 @@ pub struct UploadResult {
     pub bytes_uploaded: i64,
 }
 
+/// Connection the fstree upload helpers send blobs through.
+///
+/// A [`Client`] or [`ReconnectingClient`] serializes requests over one
+/// socket, so uploads only run in parallel through a [`PooledClient`].
+pub trait BlobUploader: Sync {
+    fn put_blob(
+        &self,
+        ctx: &RequestContext,
+        req: &PutBlobRequest,
+    ) -> crate::error::Result<PutBlobResult>;
+
+    fn attach_fs(
+        &self,
+        ctx: &RequestContext,
+        req: &AttachFsRequest,
+    ) -> crate::error::Result<AttachFsResult>;
+}
+
+impl BlobUploader for Client {
+    fn put_blob(
+        &self,
+        ctx: &RequestContext,
+        req: &PutBlobRequest,
+    ) -> crate::error::Result<PutBlobResult> {
+        Client::put_blob(self, ctx, req)
+    }
+
+    fn attach_fs(
+        &self,
+        ctx: &RequestContext,
+        req: &AttachFsRequest,
+    ) -> crate::error::Result<AttachFsResult> {
+        Client::attach_fs(self, ctx, req)
+    }
+}
+
+impl BlobUploader for ReconnectingClient {
+    fn put_blob(
+        &self,
+        ctx: &RequestContext,
+        req: &PutBlobRequest,
+    ) -> crate::error::Result<PutBlobResult> {
+        ReconnectingClient::put_blob(self, ctx, req)
+    }
+
+    fn attach_fs(
+        &self,
+        ctx: &RequestContext,
+        req: &AttachFsRequest,
+    ) -> crate::error::Result<AttachFsResult> {
+        ReconnectingClient::attach_fs(self, ctx, req)
+    }
+}
+
+impl BlobUploader for PooledClient {
+    fn put_blob(
+        &self,
+        ctx: &RequestContext,
+        req: &PutBlobRequest,
+    ) -> crate::error::Result<PutBlobResult> {
+        PooledClient::put_blob(self, ctx, req)
+    }
+
+    fn attach_fs(
+        &self,
+        ctx: &RequestContext,
+        req: &AttachFsRequest,
+    ) -> crate::error::Result<AttachFsResult> {
+        PooledClient::attach_fs(self, ctx, req)
+    }
+}
+
+enum Blob<'a> {
+    Tree(&'a [u8]),
+    File(&'a Path),
+    Symlink(&'a str),
+}
+
+impl Blob<'_> {
+    fn load(&self) -> FstreeResult<Vec<u8>> {
+        match self {
+            Blob::Tree(data) => Ok(data.to_vec()),
+            Blob::File(path) => std::fs::read(path)
+                .map_err(|err| FstreeError::new(FstreeErrorKind::Io, err.to_string())),
+            Blob::Symlink(target) => Ok(target.as_bytes().to_vec()),
+        }
+    }
+}
+
 impl Snapshot {
     pub fn upload(&self, ctx: &RequestContext, client: &Client) -> FstreeResult<UploadResult> {
+        self.upload_with_progress(ctx, client, |_| {})
+    }
+
+    /// Like [`Snapshot::upload`], invoking `on_blob` after each blob is sent.
+    pub fn upload_with_progress<F>(
+        &self,
+        ctx: &RequestContext,
+        client: &Client,
+        on_blob: F,
+    ) -> FstreeResult<UploadResult>
+    where
+        F: FnMut(UploadProgress),
+    {
+        self.upload_concurrent(ctx, client, 1, on_blob)
+    }
+
+    /// Uploads blobs on up to `concurrency` worker threads. Counts in the
+    /// result do not depend on the order in which blobs complete. Workers
+    /// share `client`, so pass a [`PooledClient`] with at least `concurrency`
+    /// connections for the requests to overlap.
+    pub fn upload_concurrent<C, F>(
+        &self,
+        ctx: &RequestContext,
+        client: &C,
+        concurrency: usize,
+        mut on_blob: F,
+    ) -> FstreeResult<UploadResult>
+    where
+        C: BlobUploader + ?Sized,
+        F: FnMut(UploadProgress),
+    {
         let mut result = UploadResult {
             root_hash: self.root_hash,
             ..UploadResult::default()
         };
 
-        for data in self.trees.values() {
-            let was_new = upload_blob(ctx, client, data.to_vec())
-                .map_err(|err| FstreeError::new(FstreeErrorKind::Client, err.to_string()))?;
-            if was_new {
-                result.trees_uploaded += 1;
-                result.bytes_uploaded += data.len() as i64;
+        // A hash shared across trees, files and symlinks is sent once; the
+        // repeats count as skipped, as a deduplicating server would report.
+        let mut seen = HashSet::new();
+        let mut blobs = Vec::new();
+        let mut duplicate_trees = 0;
+        let mut duplicate_files = 0;
+        for (hash, data) in &self.trees {
+            if seen.insert(*hash) {
+                blobs.push(Blob::Tree(data));
             } else {
-                result.trees_skipped += 1;
+                duplicate_trees += 1;
             }
         }
-
-        for file_ref in self.files.values() {
-            let content = std::fs::read(&file_ref.path)
-                .map_err(|err| FstreeError::new(FstreeErrorKind::Io, err.to_string()))?;
-            let was_new = upload_blob(ctx, client, content.clone())
-                .map_err(|err| FstreeError::new(FstreeErrorKind::Client, err.to_string()))?;
-            if was_new {
-                result.files_uploaded += 1;
-                result.bytes_uploaded += content.len() as i64;
+        for (hash, file_ref) in &self.files {
+            if seen.insert(*hash) {
+                blobs.push(Blob::File(&file_ref.path));
+            } else {
+                duplicate_files += 1;
+            }
+        }
+        for (hash, target) in &self.symlinks {
+            if seen.insert(*hash) {
+                blobs.push(Blob::Symlink(target));
             } else {
-                result.files_skipped += 1;
+                duplicate_files += 1;
             }
         }
+        result.trees_skipped += duplicate_trees;
+        result.files_skipped += duplicate_files;
 
-        for target in self.symlinks.values() {
-            let bytes = target.as_bytes().to_vec();
-            let was_new = upload_blob(ctx, client, bytes.clone())
-                .map_err(|err| FstreeError::new(FstreeErrorKind::Client, err.to_string()))?;
+        let blobs_total = blobs.len();
+        let mut blobs_done = 0;
+        let mut record = |result: &mut UploadResult, blob: &Blob<'_>, len: usize, was_new: bool| {
+            let is_tree = matches!(blob, Blob::Tree(_));
+            match (is_tree, was_new) {
+                (true, true) => result.trees_uploaded += 1,
+                (true, false) => result.trees_skipped += 1,
+                (false, true) => result.files_uploaded += 1,
+                (false, false) => result.files_skipped += 1,
+            }
             if was_new {
-                result.files_uploaded += 1;
-                result.bytes_uploaded += bytes.len() as i64;
-            } else {
-                result.files_skipped += 1;
+                result.bytes_uploaded += len as i64;
+            }
+            blobs_done += 1;
+            on_blob(UploadProgress {
+                blobs_done,
+                blobs_total,
+                bytes_uploaded: result.bytes_uploaded,
+                was_new,
+            });
+        };
+
+        if concurrency <= 1 || blobs.len() <= 1 {
+            for blob in &blobs {
+                let (len, was_new) = send_blob(ctx, client, blob)?;
+                record(&mut result, blob, len, was_new);
             }
+            return Ok(result);
         }
 
-        Ok(result)
+        let next = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+        let (sender, receiver) = mpsc::channel();
+        std::thread::scope(|scope| {
+            for _ in 0..concurrency.min(blobs.len()) {
+                let sender = sender.clone();
+                let (blobs, next, failed) = (&blobs, &next, &failed);
+                scope.spawn(move || {
+                    while !failed.load(Ordering::SeqCst) {
+                        let index = next.fetch_add(1, Ordering::SeqCst);
+                        let Some(blob) = blobs.get(index) else {
+                            break;
+                        };
+                        let outcome = send_blob(ctx, client, blob);
+                        if outcome.is_err() {
+                            failed.store(true, Ordering::SeqCst);
+                        }
+                        if sender.send((index, outcome)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+
+            for (index, outcome) in receiver {
+                let (len, was_new) = outcome?;
+                record(&mut result, &blobs[index], len, was_new);
+            }
+            Ok(result)
+        })
     }
 }
 
-fn upload_blob(
+fn send_blob<C: BlobUploader + ?Sized>(
+    ctx: &RequestContext,
+    client: &C,
+    blob: &Blob<'_>,
+) -> FstreeResult<(usize, bool)> {
+    let data = blob.load()?;
+    let len = data.len();
+    let was_new = upload_blob(ctx, client, data)
+        .map_err(|err| FstreeError::new(FstreeErrorKind::Client, err.to_string()))?;
+    Ok((len, was_new))
+}
+
+fn upload_blob<C: BlobUploader + ?Sized>(
     ctx: &RequestContext,
-    client: &Client,
+    client: &C,
     data: Vec<u8>,
 ) -> Result<bool, crate::error::Error> {
     let result = client.put_blob(ctx, &PutBlobRequest { data })?;
     Ok(result.was_new)
 }
 
-pub fn upload_and_attach(
+pub fn upload_and_attach<C: BlobUploader + ?Sized>(
     ctx: &RequestContext,
-    client: &Client,
+    client: &C,
     root: impl AsRef<std::path::Path>,
     turn_id: u64,
-    opts: impl IntoIterator<Item = super::options::SnapshotOption>,
+    opts: impl IntoIterator<Item = SnapshotOption>,
 ) -> FstreeResult<UploadResult> {
+    let opts: Vec<SnapshotOption> = opts.into_iter().collect();
+    let concurrency = Options::from_opts(&opts).upload_concurrency;
     let snapshot = super::capture::capture(root, opts)?;
-    let result = snapshot.upload(ctx, client)?;
+    let result = snapshot.upload_concurrent(ctx, client, concurrency, |_| {})?;
     client
         .attach_fs(
             ctx,
-            &crate::fs::AttachFsRequest {
+            &AttachFsRequest {
                 turn_id,
                 fs_root_hash: snapshot.root_hash,
             },
@@ -95,13 +291,15 @@ pub fn upload_and_attach(
     Ok(result)
 }
 
-pub fn capture_and_upload(
+pub fn capture_and_upload<C: BlobUploader + ?Sized>(
     ctx: &RequestContext,
-    client: &Client,
+    client: &C,
     root: impl AsRef<std::path::Path>,
-    opts: impl IntoIterator<Item = super::options::SnapshotOption>,
+    opts: impl IntoIterator<Item = SnapshotOption>,
 ) -> FstreeResult<(super::types::Snapshot, UploadResult)> {
+    let opts: Vec<SnapshotOption> = opts.into_iter().collect();
+    let concurrency = Options::from_opts(&opts).upload_concurrency;
     let snapshot = super::capture::capture(root, opts)?;
-    let result = snapshot.upload(ctx, client)?;
+    let result = snapshot.upload_concurrent(ctx, client, concurrency, |_| {})?;
     Ok((snapshot, result))
 }
@@ -11,6 +11,8 @@ pub mod context;
 pub mod encoding;
 pub mod error;
 pub mod fs;
+pub mod metrics;
+pub mod pool;
 pub mod protocol;
 pub mod reconnect;
 pub mod telemetry;
@@ -22,8 +24,9 @@ pub mod types;
 #[cfg(test)]
 mod test_util;
 pub use crate::client::{
-    dial, dial_tls, with_client_tag, with_dial_timeout, with_request_timeout, Client, ClientOption,
-    RequestContext,
+    dial, dial_tls, with_client_identity, with_client_tag, with_dial_timeout,
+    with_insecure_skip_verify, with_request_timeout, with_root_ca, Client, ClientIdentity,
+    ClientOption, RequestContext,
 };
 pub use crate::context::ContextHead;
 pub use crate::encoding::{decode_msgpack, decode_msgpack_into, encode_msgpack};
@@ -31,6 +34,8 @@ pub use crate::error::{is_server_error, Error, Result, ServerError};
 pub use crate::fs::{
     AttachFsRequest, AttachFsResult, GetBlobRequest, GetBlobResult, PutBlobRequest, PutBlobResult,
 };
+pub use crate::metrics::{Operation, OperationStats, TelemetrySnapshot};
+pub use crate::pool::{dial_pool, dial_tls_pool, PooledClient};
 pub use crate::reconnect::{
     dial_reconnecting, dial_tls_reconnecting, DialFunc, ReconnectOption, ReconnectingClient,
 };
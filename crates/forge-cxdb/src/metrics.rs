@@ -0,0 +1,246 @@
+// Copyright 2025 StrongDM Inc
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-operation request counters and latency histograms for [`Client`].
+//!
+//! Recording is lock-free: every operation owns a set of atomic counters and a
+//! log2-bucketed latency histogram, so the protocol layer pays a handful of
+//! relaxed atomic adds per request. Percentiles are resolved from the buckets
+//! when a snapshot is taken, which makes them upper bounds accurate to within
+//! a factor of two (clamped to the slowest observed request).
+//!
+//! [`Client`]: crate::client::Client
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::protocol::{
+    MSG_APPEND_TURN, MSG_ATTACH_FS, MSG_CTX_CREATE, MSG_CTX_FORK, MSG_GET_BLOB, MSG_GET_HEAD,
+    MSG_GET_LAST, MSG_PUT_BLOB,
+};
+
+/// One bucket per bit length of the latency in nanoseconds (0..=64).
+const LATENCY_BUCKETS: usize = 65;
+
+/// Request types tracked by the client metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Operation {
+    CtxCreate,
+    CtxFork,
+    GetHead,
+    AppendTurn,
+    GetLast,
+    GetBlob,
+    AttachFs,
+    PutBlob,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 8] = [
+        Operation::CtxCreate,
+        Operation::CtxFork,
+        Operation::GetHead,
+        Operation::AppendTurn,
+        Operation::GetLast,
+        Operation::GetBlob,
+        Operation::AttachFs,
+        Operation::PutBlob,
+    ];
+
+    /// Maps a request message type to its operation; `HELLO` is not tracked.
+    pub fn from_msg_type(msg_type: u16) -> Option<Self> {
+        match msg_type {
+            MSG_CTX_CREATE => Some(Operation::CtxCreate),
+            MSG_CTX_FORK => Some(Operation::CtxFork),
+            MSG_GET_HEAD => Some(Operation::GetHead),
+            MSG_APPEND_TURN => Some(Operation::AppendTurn),
+            MSG_GET_LAST => Some(Operation::GetLast),
+            MSG_GET_BLOB => Some(Operation::GetBlob),
+            MSG_ATTACH_FS => Some(Operation::AttachFs),
+            MSG_PUT_BLOB => Some(Operation::PutBlob),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Operation::CtxCreate => "CTX_CREATE",
+            Operation::CtxFork => "CTX_FORK",
+            Operation::GetHead => "GET_HEAD",
+            Operation::AppendTurn => "APPEND_TURN",
+            Operation::GetLast => "GET_LAST",
+            Operation::GetBlob => "GET_BLOB",
+            Operation::AttachFs => "ATTACH_FS",
+            Operation::PutBlob => "PUT_BLOB",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Point-in-time view of one operation's counters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OperationStats {
+    /// Requests issued, including failed ones.
+    pub count: u64,
+    /// Requests that returned an error (server, I/O, or timeout).
+    pub errors: u64,
+    pub total_latency: Duration,
+    pub max_latency: Duration,
+    pub p50_latency: Duration,
+    pub p99_latency: Duration,
+}
+
+/// Point-in-time view of a client's request metrics, keyed by operation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TelemetrySnapshot {
+    pub operations: BTreeMap<Operation, OperationStats>,
+}
+
+impl TelemetrySnapshot {
+    pub fn get(&self, operation: Operation) -> OperationStats {
+        self.operations.get(&operation).cloned().unwrap_or_default()
+    }
+}
+
+#[derive(Debug)]
+struct OperationMetrics {
+    count: AtomicU64,
+    errors: AtomicU64,
+    total_nanos: AtomicU64,
+    max_nanos: AtomicU64,
+    buckets: [AtomicU64; LATENCY_BUCKETS],
+}
+
+impl Default for OperationMetrics {
+    fn default() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            total_nanos: AtomicU64::new(0),
+            max_nanos: AtomicU64::new(0),
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+}
+
+impl OperationMetrics {
+    fn record(&self, latency: Duration, ok: bool) {
+        let nanos = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        if !ok {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        self.total_nanos.fetch_add(nanos, Ordering::Relaxed);
+        self.max_nanos.fetch_max(nanos, Ordering::Relaxed);
+        self.buckets[bucket_index(nanos)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> OperationStats {
+        let buckets: Vec<u64> = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .collect();
+        let max_nanos = self.max_nanos.load(Ordering::Relaxed);
+        OperationStats {
+            count: self.count.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            total_latency: Duration::from_nanos(self.total_nanos.load(Ordering::Relaxed)),
+            max_latency: Duration::from_nanos(max_nanos),
+            p50_latency: percentile(&buckets, 50, max_nanos),
+            p99_latency: percentile(&buckets, 99, max_nanos),
+        }
+    }
+}
+
+/// Lock-free recorder embedded in each [`Client`](crate::client::Client).
+#[derive(Debug, Default)]
+pub(crate) struct RequestMetrics {
+    operations: [OperationMetrics; Operation::ALL.len()],
+}
+
+impl RequestMetrics {
+    pub(crate) fn record(&self, msg_type: u16, latency: Duration, ok: bool) {
+        if let Some(operation) = Operation::from_msg_type(msg_type) {
+            self.operations[operation.index()].record(latency, ok);
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> TelemetrySnapshot {
+        TelemetrySnapshot {
+            operations: Operation::ALL
+                .iter()
+                .map(|operation| (*operation, self.operations[operation.index()].snapshot()))
+                .collect(),
+        }
+    }
+}
+
+fn bucket_index(nanos: u64) -> usize {
+    (u64::BITS - nanos.leading_zeros()) as usize
+}
+
+/// Largest latency that falls into `bucket`.
+fn bucket_upper_bound(bucket: usize) -> u64 {
+    match bucket {
+        0 => 0,
+        bucket => u64::MAX >> (u64::BITS as usize - bucket),
+    }
+}
+
+fn percentile(buckets: &[u64], percent: u64, max_nanos: u64) -> Duration {
+    let total: u64 = buckets.iter().sum();
+    if total == 0 {
+        return Duration::ZERO;
+    }
+    let rank = (total * percent).div_ceil(100).max(1);
+    let mut seen = 0;
+    for (bucket, count) in buckets.iter().enumerate() {
+        seen += count;
+        if seen >= rank {
+            return Duration::from_nanos(bucket_upper_bound(bucket).min(max_nanos));
+        }
+    }
+    Duration::from_nanos(max_nanos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_bounds_cover_bit_lengths() {
+        assert_eq!(bucket_index(0), 0);
+        assert_eq!(bucket_index(1), 1);
+        assert_eq!(bucket_index(1000), 10);
+        assert_eq!(bucket_index(u64::MAX), 64);
+        assert_eq!(bucket_upper_bound(0), 0);
+        assert_eq!(bucket_upper_bound(10), 1023);
+        assert_eq!(bucket_upper_bound(64), u64::MAX);
+    }
+
+    #[test]
+    fn snapshot_reports_counts_and_percentiles() {
+        let metrics = RequestMetrics::default();
+        for _ in 0..99 {
+            metrics.record(MSG_APPEND_TURN, Duration::from_micros(100), true);
+        }
+        metrics.record(MSG_APPEND_TURN, Duration::from_millis(50), false);
+        metrics.record(crate::protocol::MSG_HELLO, Duration::from_millis(1), true);
+
+        let snapshot = metrics.snapshot();
+        let append = snapshot.get(Operation::AppendTurn);
+        assert_eq!(append.count, 100);
+        assert_eq!(append.errors, 1);
+        assert_eq!(append.max_latency, Duration::from_millis(50));
+        assert!(append.p50_latency >= Duration::from_micros(100));
+        assert!(append.p50_latency < Duration::from_micros(200));
+        assert!(append.p99_latency < Duration::from_micros(200));
+        assert_eq!(snapshot.get(Operation::GetLast), OperationStats::default());
+        assert_eq!(snapshot.operations.len(), Operation::ALL.len());
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright 2025 StrongDM Inc
+// SPDX-License-Identifier: Apache-2.0
+
+//! Pool of reconnecting connections for multi-threaded hosts.
+//!
+//! A single [`Client`](crate::client::Client) serializes every request over one
+//! socket. [`PooledClient`] dials `pool_size` [`ReconnectingClient`]s and hands
+//! each request to the next one in round-robin order, so concurrent callers
+//! proceed in parallel while each connection keeps its own retry behaviour.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::client::{ClientOption, RequestContext};
+use crate::context::ContextHead;
+use crate::error::Result;
+use crate::fs::{
+    AttachFsRequest, AttachFsResult, GetBlobRequest, GetBlobResult, PutBlobRequest, PutBlobResult,
+};
+use crate::reconnect::{dial_reconnecting, dial_tls_reconnecting, ReconnectingClient};
+use crate::turn::{AppendRequest, AppendResult, GetLastOptions, TurnRecord};
+
+pub struct PooledClient {
+    members: Vec<ReconnectingClient>,
+    next: AtomicUsize,
+}
+
+/// Dials `pool_size` connections (at least one) to `addr`.
+pub fn dial_pool(
+    addr: &str,
+    pool_size: usize,
+    opts: impl IntoIterator<Item = ClientOption>,
+) -> Result<PooledClient> {
+    dial_pool_inner(addr, false, pool_size, opts)
+}
+
+/// TLS variant of [`dial_pool`].
+pub fn dial_tls_pool(
+    addr: &str,
+    pool_size: usize,
+    opts: impl IntoIterator<Item = ClientOption>,
+) -> Result<PooledClient> {
+    dial_pool_inner(addr, true, pool_size, opts)
+}
+
+fn dial_pool_inner(
+    addr: &str,
+    use_tls: bool,
+    pool_size: usize,
+    opts: impl IntoIterator<Item = ClientOption>,
+) -> Result<PooledClient> {
+    let options: Vec<ClientOption> = opts.into_iter().collect();
+    let mut members = Vec::with_capacity(pool_size.max(1));
+    for _ in 0..pool_size.max(1) {
+        let dialed = if use_tls {
+            dial_tls_reconnecting(addr, Vec::new(), options.clone())
+        } else {
+            dial_reconnecting(addr, Vec::new(), options.clone())
+        };
+        match dialed {
+            Ok(member) => members.push(member),
+            Err(err) => {
+                for member in &members {
+                    let _ = member.close();
+                }
+                return Err(err);
+            }
+        }
+    }
+
+    Ok(PooledClient {
+        members,
+        next: AtomicUsize::new(0),
+    })
+}
+
+impl PooledClient {
+    pub fn pool_size(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Closes every connection, returning the first error encountered.
+    pub fn close(&self) -> Result<()> {
+        let mut first_err = None;
+        for member in &self.members {
+            if let Err(err) = member.close() {
+                first_err.get_or_insert(err);
+            }
+        }
+        first_err.map_or(Ok(()), Err)
+    }
+
+    pub fn create_context(&self, ctx: &RequestContext, base_turn_id: u64) -> Result<ContextHead> {
+        self.lease().create_context(ctx, base_turn_id)
+    }
+
+    pub fn fork_context(&self, ctx: &RequestContext, base_turn_id: u64) -> Result<ContextHead> {
+        self.lease().fork_context(ctx, base_turn_id)
+    }
+
+    pub fn get_head(&self, ctx: &RequestContext, context_id: u64) -> Result<ContextHead> {
+        self.lease().get_head(ctx, context_id)
+    }
+
+    pub fn append_turn(&self, ctx: &RequestContext, req: &AppendRequest) -> Result<AppendResult> {
+        self.lease().append_turn(ctx, req)
+    }
+
+    pub fn append_turn_with_fs(
+        &self,
+        ctx: &RequestContext,
+        req: &AppendRequest,
+        fs_root_hash: Option<[u8; 32]>,
+    ) -> Result<AppendResult> {
+        self.lease().append_turn_with_fs(ctx, req, fs_root_hash)
+    }
+
+    pub fn get_last(
+        &self,
+        ctx: &RequestContext,
+        context_id: u64,
+        opts: GetLastOptions,
+    ) -> Result<Vec<TurnRecord>> {
+        self.lease().get_last(ctx, context_id, opts)
+    }
+
+    pub fn attach_fs(&self, ctx: &RequestContext, req: &AttachFsRequest) -> Result<AttachFsResult> {
+        self.lease().attach_fs(ctx, req)
+    }
+
+    pub fn put_blob(&self, ctx: &RequestContext, req: &PutBlobRequest) -> Result<PutBlobResult> {
+        self.lease().put_blob(ctx, req)
+    }
+
+    pub fn put_blob_if_absent(
+        &self,
+        ctx: &RequestContext,
+        data: Vec<u8>,
+    ) -> Result<([u8; 32], bool)> {
+        self.lease().put_blob_if_absent(ctx, data)
+    }
+
+    pub fn get_blob(&self, ctx: &RequestContext, req: &GetBlobRequest) -> Result<GetBlobResult> {
+        self.lease().get_blob(ctx, req)
+    }
+
+    fn lease(&self) -> &ReconnectingClient {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.members.len();
+        &self.members[index]
+    }
+}
+
+impl Drop for PooledClient {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{read_frame, write_frame, MSG_APPEND_TURN, MSG_HELLO};
+    use byteorder::{LittleEndian, WriteBytesExt};
+    use std::collections::HashSet;
+    use std::net::TcpListener;
+    use std::sync::atomic::AtomicU64;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn spawn_append_server(connections: usize) -> (String, thread::JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let handle = thread::spawn(move || {
+            let next_turn = Arc::new(AtomicU64::new(1));
+            let mut handlers = Vec::new();
+            for session in 1..=connections as u64 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let next_turn = next_turn.clone();
+                handlers.push(thread::spawn(move || {
+                    let hello = read_frame(&mut stream).unwrap();
+                    assert_eq!(hello.header.msg_type, MSG_HELLO);
+                    let mut resp = Vec::new();
+                    resp.write_u64::<LittleEndian>(session).unwrap();
+                    resp.write_u16::<LittleEndian>(1).unwrap();
+                    write_frame(&mut stream, MSG_HELLO, 0, hello.header.req_id, &resp).unwrap();
+
+                    // Serve appends until the client hangs up.
+                    while let Ok(req) = read_frame(&mut stream) {
+                        assert_eq!(req.header.msg_type, MSG_APPEND_TURN);
+                        let turn_id = next_turn.fetch_add(1, Ordering::SeqCst);
+                        let mut resp = Vec::new();
+                        resp.write_u64::<LittleEndian>(1).unwrap();
+                        resp.write_u64::<LittleEndian>(turn_id).unwrap();
+                        resp.write_u32::<LittleEndian>(1).unwrap();
+                        resp.extend_from_slice(&[0u8; 32]);
+                        write_frame(&mut stream, MSG_APPEND_TURN, 0, req.header.req_id, &resp)
+                            .unwrap();
+                    }
+                }));
+            }
+            for handler in handlers {
+                handler.join().unwrap();
+            }
+        });
+        (addr, handle)
+    }
+
+    #[test]
+    fn concurrent_appends_through_pool_all_succeed() {
+        let (addr, server) = spawn_append_server(4);
+        let pool = Arc::new(dial_pool(&addr, 4, Vec::new()).unwrap());
+        assert_eq!(pool.pool_size(), 4);
+
+        let workers: Vec<_> = (0..8)
+            .map(|worker| {
+                let pool = pool.clone();
+                thread::spawn(move || {
+                    let ctx = RequestContext::background();
+                    (0..5)
+                        .map(|i| {
+                            let req = AppendRequest::new(
+                                1,
+                                "cxdb.ConversationItem",
+                                3,
+                                vec![0x91, worker, i],
+                            );
+                            pool.append_turn(&ctx, &req).unwrap().turn_id
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let turn_ids: HashSet<u64> = workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect();
+        assert_eq!(turn_ids.len(), 40);
+
+        pool.close().unwrap();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn zero_pool_size_dials_one_connection() {
+        let (addr, server) = spawn_append_server(1);
+        let pool = dial_pool(&addr, 0, Vec::new()).unwrap();
+        assert_eq!(pool.pool_size(), 1);
+
+        let req = AppendRequest::new(1, "cxdb.ConversationItem", 3, vec![0x91, 0x01]);
+        pool.append_turn(&RequestContext::background(), &req)
+            .unwrap();
+
+        drop(pool);
+        server.join().unwrap();
+    }
+}
@@ -59,6 +59,10 @@ pub struct AppendResult {
     pub turn_id: u64,
     pub depth: u32,
     pub payload_hash: [u8; 32],
+    /// True when the server matched the idempotency key to an existing turn
+    /// and returned it instead of writing a new one (bit 0 of the response's
+    /// `append_flags`). Always false against servers that predate the flag.
+    pub deduplicated: bool,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -128,7 +132,7 @@ impl Client {
     }
 }
 
-fn parse_append_result(payload: &[u8]) -> Result<AppendResult> {
+pub(crate) fn parse_append_result(payload: &[u8]) -> Result<AppendResult> {
     if payload.len() < 52 {
         return Err(Error::invalid_response(format!(
             "append response too short ({} bytes)",
@@ -141,11 +145,14 @@ fn parse_append_result(payload: &[u8]) -> Result<AppendResult> {
     let depth = cursor.read_u32::<LittleEndian>()?;
     let mut hash = [0u8; 32];
     cursor.read_exact(&mut hash)?;
+    // Servers that predate `append_flags` stop after the hash.
+    let deduplicated = payload.get(52).is_some_and(|flags| flags & 1 != 0);
     Ok(AppendResult {
         context_id,
         turn_id,
         depth,
         payload_hash: hash,
+        deduplicated,
     })
 }
 
@@ -200,7 +207,13 @@ fn parse_turn_records(payload: &[u8]) -> Result<Vec<TurnRecord>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::client::dial;
+    use crate::protocol::{read_frame, write_frame, MSG_HELLO};
     use crate::test_util::{decode_hex, load_fixture};
+    use std::collections::HashMap;
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
 
     fn build_append_payload(req: &AppendRequest) -> Vec<u8> {
         let encoding = if req.encoding == 0 {
@@ -304,4 +317,129 @@ mod tests {
         payload.write_u32::<LittleEndian>(1).unwrap();
         assert_eq!(decode_hex(&fixture.payload_hex), payload);
     }
+
+    fn idempotency_key_of(payload: &[u8]) -> Vec<u8> {
+        let mut cursor = std::io::Cursor::new(payload);
+        cursor.set_position(16);
+        let type_len = cursor.read_u32::<LittleEndian>().unwrap() as u64;
+        // type_id, then version/encoding/compression/uncompressed_len and the hash.
+        cursor.set_position(cursor.position() + type_len + 16 + 32);
+        let payload_len = cursor.read_u32::<LittleEndian>().unwrap() as u64;
+        cursor.set_position(cursor.position() + payload_len);
+        let key_len = cursor.read_u32::<LittleEndian>().unwrap() as usize;
+        let mut key = vec![0u8; key_len];
+        cursor.read_exact(&mut key).unwrap();
+        key
+    }
+
+    #[test]
+    fn append_with_repeated_idempotency_key_reports_deduplicated() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let hello = read_frame(&mut stream).unwrap();
+            assert_eq!(hello.header.msg_type, MSG_HELLO);
+            let mut resp = Vec::new();
+            resp.write_u64::<LittleEndian>(1).unwrap();
+            resp.write_u16::<LittleEndian>(1).unwrap();
+            write_frame(&mut stream, MSG_HELLO, 0, hello.header.req_id, &resp).unwrap();
+
+            let mut seen: HashMap<Vec<u8>, u64> = HashMap::new();
+            for _ in 0..2 {
+                let req = read_frame(&mut stream).unwrap();
+                assert_eq!(req.header.msg_type, MSG_APPEND_TURN);
+                let key = idempotency_key_of(&req.payload);
+                let next_id = seen.len() as u64 + 1;
+                let deduplicated = seen.contains_key(&key);
+                let turn_id = *seen.entry(key).or_insert(next_id);
+
+                let mut resp = Vec::new();
+                resp.write_u64::<LittleEndian>(7).unwrap();
+                resp.write_u64::<LittleEndian>(turn_id).unwrap();
+                resp.write_u32::<LittleEndian>(1).unwrap();
+                resp.extend_from_slice(&[0xAB; 32]);
+                resp.push(u8::from(deduplicated));
+                write_frame(&mut stream, MSG_APPEND_TURN, 0, req.header.req_id, &resp).unwrap();
+            }
+        });
+
+        let client = dial(&addr.to_string(), Vec::new()).unwrap();
+        let ctx = RequestContext::background();
+        let mut req = AppendRequest::new(7, "cxdb.ConversationItem", 3, vec![0x91, 0x01]);
+        req.idempotency_key = b"idem-1".to_vec();
+
+        let first = client.append_turn(&ctx, &req).unwrap();
+        assert!(!first.deduplicated);
+        let second = client.append_turn(&ctx, &req).unwrap();
+        assert!(second.deduplicated);
+        assert_eq!(second.turn_id, first.turn_id);
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn append_result_without_dedup_flag_is_not_deduplicated() {
+        let mut payload = Vec::new();
+        payload.write_u64::<LittleEndian>(1).unwrap();
+        payload.write_u64::<LittleEndian>(2).unwrap();
+        payload.write_u32::<LittleEndian>(3).unwrap();
+        payload.extend_from_slice(&[0u8; 32]);
+
+        let result = parse_append_result(&payload).unwrap();
+        assert_eq!(result.turn_id, 2);
+        assert!(!result.deduplicated);
+    }
+
+    #[test]
+    fn telemetry_snapshot_counts_appends() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let hello = read_frame(&mut stream).unwrap();
+            let mut resp = Vec::new();
+            resp.write_u64::<LittleEndian>(1).unwrap();
+            resp.write_u16::<LittleEndian>(1).unwrap();
+            write_frame(&mut stream, MSG_HELLO, 0, hello.header.req_id, &resp).unwrap();
+
+            for turn_id in 1..=3u64 {
+                let req = read_frame(&mut stream).unwrap();
+                assert_eq!(req.header.msg_type, MSG_APPEND_TURN);
+                let mut resp = Vec::new();
+                resp.write_u64::<LittleEndian>(7).unwrap();
+                resp.write_u64::<LittleEndian>(turn_id).unwrap();
+                resp.write_u32::<LittleEndian>(turn_id as u32).unwrap();
+                resp.extend_from_slice(&[0u8; 32]);
+                write_frame(&mut stream, MSG_APPEND_TURN, 0, req.header.req_id, &resp).unwrap();
+            }
+        });
+
+        let client = dial(&addr.to_string(), Vec::new()).unwrap();
+        assert_eq!(
+            client
+                .telemetry_snapshot()
+                .get(crate::metrics::Operation::AppendTurn)
+                .count,
+            0
+        );
+
+        let ctx = RequestContext::background();
+        let req = AppendRequest::new(7, "cxdb.ConversationItem", 3, vec![0x91, 0x01]);
+        for _ in 0..3 {
+            client.append_turn(&ctx, &req).unwrap();
+        }
+
+        let snapshot = client.telemetry_snapshot();
+        let appends = snapshot.get(crate::metrics::Operation::AppendTurn);
+        assert_eq!(appends.count, 3);
+        assert_eq!(appends.errors, 0);
+        assert!(appends.p50_latency > Duration::ZERO);
+        assert!(appends.p99_latency >= appends.p50_latency);
+        assert_eq!(snapshot.get(crate::metrics::Operation::GetLast).count, 0);
+
+        handle.join().unwrap();
+    }
 }
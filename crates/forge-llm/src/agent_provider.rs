@@ -89,6 +89,21 @@ pub struct AgentRunResult {
     pub cost_usd: Option<f64>,
     /// Wall clock duration in milliseconds.
     pub duration_ms: Option<u64>,
+    /// Why the run ended.
+    pub stop_reason: AgentRunStopReason,
+}
+
+/// Why an agent run ended.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentRunStopReason {
+    /// The agent answered without requesting further tool calls.
+    #[default]
+    Completed,
+    /// The tool round cap was reached while the agent still wanted tools.
+    RoundLimit,
+    /// The turn cap was reached.
+    TurnLimit,
 }
 
 /// Record of a tool call that the provider executed internally.
@@ -12,7 +12,8 @@ use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 
 use crate::agent_provider::{
-    AgentLoopEvent, AgentProvider, AgentRunOptions, AgentRunResult, ToolActivityRecord,
+    AgentLoopEvent, AgentProvider, AgentRunOptions, AgentRunResult, AgentRunStopReason,
+    ToolActivityRecord,
 };
 use crate::errors::{ErrorInfo, ProviderError, ProviderErrorKind, SDKError};
 use crate::types::Usage;
@@ -98,6 +99,7 @@ impl AgentProvider for ClaudeCodeAgentProvider {
         let mut tool_activity = Vec::new();
         let mut total_usage = Usage::default();
         let mut cost_usd = None;
+        let mut stop_reason = AgentRunStopReason::Completed;
         let mut session_model = self.model.clone().unwrap_or_else(|| "claude-code".to_string());
 
         while let Some(line) = lines
@@ -225,6 +227,9 @@ impl AgentProvider for ClaudeCodeAgentProvider {
                     }
                 }
                 Some("result") => {
+                    if event.get("subtype").and_then(|v| v.as_str()) == Some("error_max_turns") {
+                        stop_reason = AgentRunStopReason::TurnLimit;
+                    }
                     if let Some(text) = event.get("result").and_then(|v| v.as_str()) {
                         final_text = text.to_string();
                     }
@@ -268,6 +273,7 @@ impl AgentProvider for ClaudeCodeAgentProvider {
             provider: "claude-code".to_string(),
             cost_usd,
             duration_ms: Some(elapsed.as_millis() as u64),
+            stop_reason,
         })
     }
 }
@@ -11,7 +11,8 @@ use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 
 use crate::agent_provider::{
-    AgentLoopEvent, AgentProvider, AgentRunOptions, AgentRunResult, ToolActivityRecord,
+    AgentLoopEvent, AgentProvider, AgentRunOptions, AgentRunResult, AgentRunStopReason,
+    ToolActivityRecord,
 };
 use crate::errors::{ErrorInfo, ProviderError, ProviderErrorKind, SDKError};
 use crate::types::Usage;
@@ -291,6 +292,7 @@ impl AgentProvider for CodexAgentProvider {
             provider: "codex-cli".to_string(),
             cost_usd: None,
             duration_ms: Some(elapsed.as_millis() as u64),
+            stop_reason: AgentRunStopReason::Completed,
         })
     }
 }
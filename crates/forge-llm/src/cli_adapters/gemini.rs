@@ -14,7 +14,8 @@ use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 
 use crate::agent_provider::{
-    AgentLoopEvent, AgentProvider, AgentRunOptions, AgentRunResult, ToolActivityRecord,
+    AgentLoopEvent, AgentProvider, AgentRunOptions, AgentRunResult, AgentRunStopReason,
+    ToolActivityRecord,
 };
 use crate::errors::{ErrorInfo, ProviderError, ProviderErrorKind, SDKError};
 use crate::types::Usage;
@@ -346,6 +347,7 @@ impl AgentProvider for GeminiAgentProvider {
             provider: "gemini-cli".to_string(),
             cost_usd: None,
             duration_ms: Some(elapsed.as_millis() as u64),
+            stop_reason: AgentRunStopReason::Completed,
         })
     }
 }